@@ -1,26 +1,121 @@
 use serde::{Deserialize, Serialize};
 
+use crate::doc::align::{self, Alignment};
+use crate::doc::anonymize::{self, AnonymizeResult};
 use crate::doc::backup::{self, BackupRecord};
+use crate::doc::binary::{self, BinaryPreview};
+use crate::doc::bookmarks::{self, Annotation, Bookmark, FileMarks};
+use crate::doc::cloud;
+use crate::doc::coerce::{self, NormalizeResult, NormalizeRules};
+use crate::doc::colors::{self, ColorReport, ColorSpace};
+use crate::doc::completions::{self, Completions};
+use crate::doc::conflicts::{self, ConflictFile, MergeResult, ResolutionResult};
+use crate::doc::dedupe::{self, DedupeResult, DedupeStrategy};
 use crate::doc::detect::{detect_and_convert, DetectResult};
-use crate::doc::diff::{compute_diff, DiffEntry};
+use crate::doc::diagnostic_export::{self, DiagnosticFormat, FileDiagnostics};
+use crate::doc::diagnostics::{self, DiagnosticsBundle};
+use crate::doc::diff::{
+    self, compute_diff, compute_diff_with, AdhocDiff, AdhocDiffOptions, ArrayMatch, DiffEntry,
+    DiffOptions,
+};
+use crate::doc::diff_export::{self, DiffExportFormat, DiffResultInput, DiffSides};
+use crate::doc::diff_rules::{self, DiffFilter, DiffProfile, DiffRules};
+use crate::doc::dirdiff::{self, DirDiffOptions, DirDiffSummary};
+use crate::doc::disk_hash::{self, DiskState};
 use crate::doc::document::{
     ApplyResult, ColumnValues, Document, HistoryView, ReplaceResult, SaveResult, SortedRow,
     Summary, EDIT_SIZE_LIMIT,
 };
+use crate::doc::editorconfig::{self, EditorConfig};
+use crate::doc::envsubst::{self, EnvSource, ResolveResult};
 use crate::doc::export::{ExportFormat, ExportPreview};
+use crate::doc::external::{self, ToolOptions, ToolResult};
+use crate::doc::extract::{self, ExtractResult, ReplaceWith};
+use crate::doc::field_stats::{self, DatasetProfile};
+use crate::doc::file_info::{self, FileStat};
+use crate::doc::file_ops::{self, FileOperation, FileOperationKind};
+use crate::doc::fragment::{self, FragmentKind, FragmentResult, FragmentValidation};
+use crate::doc::git::{self, GitFileStatus};
+use crate::doc::git_hooks::{self, HookChecks, HookInstall};
+use crate::doc::goto::{self, PathResolution};
+use crate::doc::graph::{self, GraphExpansion, GraphLayout, GraphOptions, LayoutAlgorithm};
 use crate::doc::grid_filter::GridFilter;
+use crate::doc::har::{self, HarEntry, HarPart, HarSummary};
+use crate::doc::heatmap::{self, Heatmap, HeatmapOptions, LineMarkers};
+use crate::doc::highlight::{self, TokenizedRange};
+use crate::doc::histogram::{self, HistogramOptions, ValueHistogram};
+use crate::doc::hover::{self, HoverInfo};
+use crate::doc::i18n::{self, LocaleComparison, MergeOptions};
+use crate::doc::ids::{self, IdKind, IdOptions};
+use crate::doc::inline::{self, InlineOptions, InlineReport};
+use crate::doc::k8s::{self, K8sResource, K8sValidation};
+use crate::doc::layers::{self, ArrayStrategy, LayerMerge};
+use crate::doc::links::{self, LinkOptions, LinkReport};
+use crate::doc::lint::{self, LintConfig, LintReport};
+use crate::doc::lockfile::{self, LockDiff, LockReport};
+use crate::doc::logs::{self, LogOptions, LogParseResult};
+use crate::doc::macros::{self, Macro, MacroFileResult, MacroInput};
+use crate::doc::memory::MemoryUsage;
+use crate::doc::navigation::{self, PathContext};
+use crate::doc::notebook::{self, Notebook};
+use crate::doc::notify;
 use crate::doc::ops::Op;
+use crate::doc::outline::{self, FoldingRange, Outline};
+use crate::doc::paste::{self, NormalizedPaste};
+use crate::doc::pinned::{self, PinnedFile};
+use crate::doc::pipeline::{self, PipelineResult, PipelineStep};
+use crate::doc::plugins::{self, PluginInfo, PluginOutput};
+use crate::doc::pool::{PoolStats, Priority, WorkerPool};
+use crate::doc::portable::{self, PortableInfo};
+use crate::doc::positions::{Cursor, DuplicateKey, NodeSpan, PositionIndex, TextPosition};
+use crate::doc::profile::{self, ImportedProfile, ProfileSummary};
+use crate::doc::recents::{self, RecentEntry};
+use crate::doc::records::{self, ConvertResult, RecordFormat, RecordSource};
+use crate::doc::references::{self, ReferenceOptions, ReferenceReport};
+use crate::doc::remote::{self, fetch_text, PutOutcome, SaveMethod};
 use crate::doc::repair::{repair as repair_string, RepairResult};
+use crate::doc::save_hooks::SaveHooks;
 use crate::doc::schema::sniff_columns;
+use crate::doc::schema_defaults::{self, DefaultsReport};
+use crate::doc::schema_example::{generate_examples, ExampleOptions, SchemaExample};
+use crate::doc::schema_refs::{self, RefResolution};
 use crate::doc::schema_validate::SchemaValidationResult;
+use crate::doc::script::{self, ScriptBindings, ScriptResult};
 use crate::doc::search::{SearchHit, SearchOptions};
+use crate::doc::search_index::{IndexQueryOptions, IndexSummary, IndexedSearchResult, SearchIndex};
+use crate::doc::search_metadata;
+use crate::doc::series::{self, SeriesData, SeriesOptions};
+use crate::doc::settings::{self, EffectiveSettings, Settings, SettingsStore, UpdateChannel};
+use crate::doc::sftp::{SftpConnection, SftpConnections, SftpEntry, SftpProfile, SftpWriteResult};
+use crate::doc::shape_clusters::{self, ShapeClusters, ShapeOptions};
+use crate::doc::similarity::{self, RankOptions, Similarity, SimilarityRanking};
+use crate::doc::snapshots::{SnapshotDiff, SnapshotInfo, Snapshots};
+use crate::doc::snippets::{self, RenderedSnippet, Snippet, SnippetInfo, SnippetInput};
+use crate::doc::split::{self, ConcatResult, SplitOptions, SplitResult, SplitStrategy};
+use crate::doc::stash::{self, StashEntry, StashedContent};
 use crate::doc::store::DocStore;
+use crate::doc::stream_validate::{self, StreamValidateOptions, StreamValidationReport};
+use crate::doc::syntax::{self, SyntaxReport};
+use crate::doc::tabs::{self, Tab, TabInput, TabList, TabRegistry};
+use crate::doc::tabular::{self, TabularPaste};
+use crate::doc::tail::{self, TailOptions};
+use crate::doc::taskbar::{self, TaskProgress, Taskbar};
+use crate::doc::telemetry;
+use crate::doc::templates::{self, TemplateInfo};
+use crate::doc::text_buffer::{TextBuffer, TextEditResult, TextRange};
 use crate::doc::typegen::TypegenLang;
 use crate::doc::types::{
     ColumnSchema, DocError, DocHandle, DocResult, ErrorKind, NodeView, Path, WireError,
 };
+use crate::doc::updates::{UpdateState, UpdateStatus, Updater};
+use crate::doc::view_state::{self, LoadedViewState, ViewState};
+use crate::doc::watch::{self, ValidationProfile};
+use crate::doc::workspace::{Location, WorkspaceIndex, WorkspaceReference, WorkspaceSummary};
 use parking_lot::RwLock;
+use std::collections::HashMap;
 use std::sync::Arc;
+use tauri::{Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 
 type SharedDoc = Arc<RwLock<Document>>;
 
@@ -38,6 +133,35 @@ where
     }
 }
 
+pub(crate) fn apply_runtime_settings(
+    settings: &Settings,
+    log_dir: &std::path::Path,
+    pool: &WorkerPool,
+    docs: &DocStore,
+) -> std::io::Result<()> {
+    pool.resize(settings.worker_threads as usize);
+    docs.set_memory_limit(settings.memory_limit_mb.saturating_mul(1024 * 1024));
+    diagnostics::set_enabled(log_dir, settings.diagnostics)
+}
+
+async fn run_pooled<T, F>(pool: &WorkerPool, priority: Priority, f: F) -> Result<T, WireError>
+where
+    F: FnOnce() -> DocResult<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    pool.submit(priority, move || {
+        let _ = tx.send(f());
+    });
+    match rx.await {
+        Ok(result) => result.map_err(WireError::from),
+        Err(_) => Err(WireError {
+            kind: ErrorKind::Io,
+            message: "worker task panicked".into(),
+        }),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "kind", rename_all = "lowercase")]
 pub enum OpenSource {
@@ -62,6 +186,23 @@ fn doc_open_inner(store: &DocStore, source: OpenSource) -> DocResult<OpenResult>
     Ok(OpenResult { handle, summary })
 }
 
+fn create_from_template_inner(
+    store: &DocStore,
+    user_dir: Option<&std::path::Path>,
+    id: &str,
+    variables: HashMap<String, String>,
+) -> DocResult<OpenResult> {
+    let template = templates::find(user_dir, id)?;
+    let text = templates::render(&template.body, &variables)?;
+    doc_open_inner(
+        store,
+        OpenSource::Text {
+            text,
+            name: Some(template.info.file_name),
+        },
+    )
+}
+
 fn doc_close_inner(store: &DocStore, handle: DocHandle) -> bool {
     store.remove(handle)
 }
@@ -122,19 +263,643 @@ fn doc_diff_inner(
     }
     let l_arc = store.get(left).ok_or(DocError::NotFound(left))?;
     let r_arc = store.get(right).ok_or(DocError::NotFound(right))?;
-    diff_arcs(l_arc, r_arc, &crate::doc::jobs::CancelFlag::never())
+    diff_arcs(
+        l_arc,
+        r_arc,
+        &DiffOptions::default(),
+        None,
+        &crate::doc::jobs::CancelFlag::never(),
+    )
 }
 
 fn diff_arcs(
     l_arc: SharedDoc,
     r_arc: SharedDoc,
+    opts: &DiffOptions,
+    filter: Option<&DiffFilter>,
     cancel: &crate::doc::jobs::CancelFlag,
 ) -> DocResult<Vec<DiffEntry>> {
     let l_doc = l_arc.read();
     let r_doc = r_arc.read();
     let l_val = l_doc.get_value(&Path::root())?;
     let r_val = r_doc.get_value(&Path::root())?;
-    compute_diff(&l_val, &r_val, cancel)
+    let entries = compute_diff_with(&l_val, &r_val, opts, cancel)?;
+    Ok(match filter {
+        Some(filter) => filter.apply(entries, &l_val, &r_val),
+        None => entries,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHeadDiff {
+    pub path: String,
+    pub in_head: bool,
+    pub entries: Vec<DiffEntry>,
+}
+
+fn git_diff_head_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    path: Option<String>,
+    cancel: &crate::doc::jobs::CancelFlag,
+) -> DocResult<GitHeadDiff> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let doc = arc.read();
+    let path = path
+        .or_else(|| doc.file_path.clone())
+        .or_else(|| doc.source_path.clone())
+        .ok_or_else(|| DocError::Edit("document has no file path".into()))?;
+    let Some(head_text) = git::show_head(std::path::Path::new(&path))? else {
+        return Ok(GitHeadDiff {
+            path,
+            in_head: false,
+            entries: Vec::new(),
+        });
+    };
+    let head = Document::from_text(&head_text, Some(path.clone()))?;
+    let head_val = head.get_value(&Path::root())?;
+    let current = doc.get_value(&Path::root())?;
+    Ok(GitHeadDiff {
+        path,
+        in_head: true,
+        entries: compute_diff(&head_val, &current, cancel)?,
+    })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarBodyResult {
+    pub mime_type: Option<String>,
+    pub size: u64,
+    pub opened: Option<OpenResult>,
+    pub text: Option<String>,
+}
+
+fn har_list_entries_inner(store: &DocStore, handle: DocHandle) -> DocResult<Vec<HarEntry>> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let entries = arc.read().get_value(&har::entries_path())?;
+    har::list_entries(&entries)
+}
+
+fn har_summary_inner(store: &DocStore, handle: DocHandle) -> DocResult<HarSummary> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let doc = arc.read();
+    let entries = doc.get_value(&har::entries_path())?;
+    let pages = doc.get_value(&har::pages_path()).ok();
+    har::summarize(&entries, pages.as_ref())
+}
+
+fn har_extract_body_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    index: u32,
+    part: HarPart,
+) -> DocResult<HarBodyResult> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let entry = arc.read().get_value(&har::entry_path(index))?;
+    let body = har::extract_body(&entry, part)?;
+    let suffix = match part {
+        HarPart::Request => "request",
+        HarPart::Response => "response",
+    };
+    let name = format!("entry-{index}-{suffix}.json");
+    match Document::from_text(&body.text, Some(name)) {
+        Ok(doc) => {
+            let summary = doc.summary();
+            Ok(HarBodyResult {
+                mime_type: body.mime_type,
+                size: body.size,
+                opened: Some(OpenResult {
+                    handle: store.insert(doc),
+                    summary,
+                }),
+                text: None,
+            })
+        }
+        Err(_) => Ok(HarBodyResult {
+            mime_type: body.mime_type,
+            size: body.size,
+            opened: None,
+            text: Some(body.text),
+        }),
+    }
+}
+
+fn notebook_cells_inner(store: &DocStore, handle: DocHandle) -> DocResult<Notebook> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let root = arc.read().get_value(&Path::root())?;
+    notebook::parse(&root, &notebook::image_dir())
+}
+
+fn notebook_set_cell_source_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    index: u32,
+    source: &str,
+) -> DocResult<ApplyResult> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let mut doc = arc.write();
+    let root = doc.get_value(&Path::root())?;
+    let op = notebook::set_source_op(&root, index, source)?;
+    doc.apply(&op)
+}
+
+fn notebook_save_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    path: Option<String>,
+) -> DocResult<SaveResult> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let mut doc = arc.write();
+    let text = notebook::serialize(&doc.get_value(&Path::root())?)?;
+    doc.save_text(path, text)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct K8sStreamDoc {
+    pub resource: Option<K8sResource>,
+    pub opened: OpenResult,
+}
+
+fn k8s_open_stream_inner(store: &DocStore, path: &str) -> DocResult<Vec<K8sStreamDoc>> {
+    let text = std::fs::read_to_string(path)?;
+    let file_name = std::path::Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string());
+    let mut out = Vec::new();
+    for (i, value) in k8s::split_stream(&text)?.into_iter().enumerate() {
+        let resource = k8s::detect(&value);
+        let json = serde_json::to_string(&value).map_err(|e| DocError::Parse(e.to_string()))?;
+        let name = k8s::stream_tab_name(&file_name, i, resource.as_ref());
+        let doc = Document::from_text(&json, Some(name))?;
+        let summary = doc.summary();
+        out.push(K8sStreamDoc {
+            resource,
+            opened: OpenResult {
+                handle: store.insert(doc),
+                summary,
+            },
+        });
+    }
+    Ok(out)
+}
+
+fn k8s_validate_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    version: Option<&str>,
+) -> DocResult<K8sValidation> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let root = arc.read().get_value(&Path::root())?;
+    k8s::validate(&root, version)
+}
+
+fn k8s_cleanup_inner(store: &DocStore, handle: DocHandle) -> DocResult<ApplyResult> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let mut doc = arc.write();
+    let root = doc.get_value(&Path::root())?;
+    doc.apply(&Op::SetValue {
+        path: Path::root(),
+        value: k8s::cleanup(&root),
+    })
+}
+
+fn helm_validate_values_inner(
+    store: &DocStore,
+    handle: DocHandle,
+) -> DocResult<SchemaValidationResult> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let doc = arc.read();
+    let file = doc
+        .file_path
+        .clone()
+        .ok_or_else(|| DocError::Schema("values file is not saved on disk".into()))?;
+    let schema_path = std::path::Path::new(&file)
+        .parent()
+        .map(|dir| dir.join("values.schema.json"))
+        .filter(|p| p.is_file())
+        .ok_or_else(|| DocError::Schema("no values.schema.json next to this values file".into()))?;
+    let schema_path = schema_path.to_string_lossy().into_owned();
+    let schema = std::fs::read_to_string(&schema_path)?;
+    let schema = bundle_for_validation(schema, Some(&schema_path))?;
+    doc.validate_schema(&schema)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleMergeResult {
+    pub applied: ApplyResult,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+fn i18n_compare_inner(
+    store: &DocStore,
+    base: DocHandle,
+    target: DocHandle,
+) -> DocResult<LocaleComparison> {
+    let base_arc = store.get(base).ok_or(DocError::NotFound(base))?;
+    let target_arc = store.get(target).ok_or(DocError::NotFound(target))?;
+    let base_value = base_arc.read().get_value(&Path::root())?;
+    let target_value = target_arc.read().get_value(&Path::root())?;
+    Ok(i18n::compare(&base_value, &target_value))
+}
+
+fn i18n_merge_inner(
+    store: &DocStore,
+    base: DocHandle,
+    target: DocHandle,
+    options: &MergeOptions,
+) -> DocResult<LocaleMergeResult> {
+    let base_arc = store.get(base).ok_or(DocError::NotFound(base))?;
+    let target_arc = store.get(target).ok_or(DocError::NotFound(target))?;
+    let base_value = base_arc.read().get_value(&Path::root())?;
+    let mut doc = target_arc.write();
+    let merge = i18n::merge(&base_value, &doc.get_value(&Path::root())?, options);
+    let applied = doc.apply(&Op::SetValue {
+        path: Path::root(),
+        value: merge.merged,
+    })?;
+    Ok(LocaleMergeResult {
+        applied,
+        added: merge.added,
+        removed: merge.removed,
+    })
+}
+
+pub(crate) fn ensure_search_index(
+    store: &DocStore,
+    handle: DocHandle,
+    cancel: &crate::doc::jobs::CancelFlag,
+) -> DocResult<Arc<SearchIndex>> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let doc = arc.read();
+    if let Some(index) = store.search_index(handle) {
+        if index.version() == doc.version {
+            return Ok(index);
+        }
+    }
+    let (text, source_offsets) = doc.index_text()?;
+    let index = Arc::new(SearchIndex::build(
+        text,
+        doc.version,
+        source_offsets,
+        cancel,
+    )?);
+    store.set_search_index(handle, index.clone());
+    Ok(index)
+}
+
+fn tokenize_range_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    start_line: u32,
+    end_line: u32,
+) -> DocResult<TokenizedRange> {
+    let index = ensure_search_index(store, handle, &crate::doc::jobs::CancelFlag::never())?;
+    let first = start_line.max(1);
+    let last = end_line.min(first.saturating_add(highlight::MAX_RANGE_LINES - 1));
+    Ok(highlight::tokenize_lines(
+        &index.lines(first - 1..last),
+        first,
+        index.line_count(),
+        index.version(),
+    ))
+}
+
+fn doc_heatmap_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    buckets: u32,
+    options: HeatmapOptions,
+) -> DocResult<Heatmap> {
+    let cancel = crate::doc::jobs::CancelFlag::never();
+    let index = ensure_search_index(store, handle, &cancel)?;
+    let mut markers = LineMarkers::default();
+    if let Some(query) = options.query.filter(|q| !q.is_empty()) {
+        let opts = SearchOptions {
+            query,
+            case_sensitive: options.case_sensitive,
+            max_results: heatmap::MAX_SEARCH_MARKERS,
+        };
+        markers.search_hits = index
+            .search(&opts, &cancel)
+            .hits
+            .iter()
+            .map(|hit| index.line_of(hit.offset))
+            .collect();
+    }
+    if options.schema.is_some() || options.compare_to.is_some() {
+        let positions = PositionIndex::build(index.text())?;
+        let lines = heatmap::pointer_lines(&positions);
+        markers.errors = positions
+            .duplicate_keys()
+            .iter()
+            .map(|d| d.duplicate.line)
+            .collect();
+        let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+        if let Some(schema) = &options.schema {
+            let result = arc.read().validate_schema(schema)?;
+            markers.errors.extend(
+                result
+                    .errors
+                    .iter()
+                    .filter_map(|e| heatmap::line_for_pointer(&lines, &e.instance_path)),
+            );
+        }
+        if let Some(other) = options.compare_to {
+            let other = store.get(other).ok_or(DocError::NotFound(other))?;
+            let entries = diff_arcs(arc, other, &DiffOptions::default(), None, &cancel)?;
+            markers.changes = entries
+                .iter()
+                .filter_map(|e| heatmap::line_for_pointer(&lines, &e.path.to_pointer()))
+                .collect();
+        }
+    }
+    Ok(Heatmap {
+        version: index.version(),
+        total_lines: index.line_count(),
+        buckets: heatmap::build(&heatmap::line_depths(index.text()), buckets, &markers),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Selection {
+    Text { text: String },
+    Node { handle: DocHandle, path: Path },
+}
+
+fn selection_inner(
+    store: &DocStore,
+    selection: Selection,
+    indent: u8,
+    base_indent: u32,
+) -> DocResult<FragmentResult> {
+    match selection {
+        Selection::Text { text } if indent == 0 => fragment::minify(&text),
+        Selection::Text { text } => fragment::format(&text, indent, base_indent),
+        Selection::Node { handle, path } => {
+            let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+            let value = arc.read().get_value(&path)?;
+            Ok(FragmentResult {
+                kind: FragmentKind::Value,
+                text: fragment::format_value(&value, indent)?,
+            })
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PathSource {
+    Handle { handle: DocHandle },
+    Text { text: String },
+}
+
+fn resolve_path_inner(
+    store: &DocStore,
+    source: PathSource,
+    input: &str,
+    limit: usize,
+) -> DocResult<PathResolution> {
+    match source {
+        PathSource::Text { text } => {
+            let root: serde_json::Value =
+                serde_json::from_str(&text).map_err(|e| DocError::Parse(e.to_string()))?;
+            Ok(goto::resolve_in_value(&root, input, limit))
+        }
+        PathSource::Handle { handle } => {
+            let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+            let doc = arc.read();
+            Ok(goto::resolve(
+                input,
+                limit,
+                |p| doc.kind_at(p).ok().map(|(kind, _)| kind),
+                || doc.get_value(&Path::root()).ok(),
+            ))
+        }
+    }
+}
+
+fn get_path_context_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    pointer: &str,
+) -> DocResult<PathContext> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let doc = arc.read();
+    let path = navigation::path_from_pointer(&doc, pointer)?;
+    navigation::path_context(&doc, &path)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlinedDocument {
+    pub opened: OpenResult,
+    pub report: InlineReport,
+}
+
+fn inline_refs_inner(
+    store: &DocStore,
+    path: &str,
+    options: &InlineOptions,
+) -> DocResult<InlinedDocument> {
+    let (value, report) = inline::inline_refs(std::path::Path::new(path), options)?;
+    let text = serde_json::to_string_pretty(&value).map_err(|e| DocError::Export(e.to_string()))?;
+    let source = match &options.output_path {
+        Some(out) => {
+            std::fs::write(out, &text)?;
+            OpenSource::File { path: out.clone() }
+        }
+        None => {
+            let stem = std::path::Path::new(path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "document".into());
+            OpenSource::Text {
+                text,
+                name: Some(format!("{stem}.inlined.json")),
+            }
+        }
+    };
+    let opened = doc_open_inner(store, source)?;
+    Ok(InlinedDocument { opened, report })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum TextSource {
+    Content { content: String },
+    Handle { handle: DocHandle },
+}
+
+fn source_text(store: &DocStore, source: TextSource) -> DocResult<String> {
+    match source {
+        TextSource::Content { content } => Ok(content),
+        TextSource::Handle { handle } => {
+            let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+            let doc = arc.read();
+            doc.serialize()
+        }
+    }
+}
+
+fn position_to_path_inner(
+    store: &DocStore,
+    source: TextSource,
+    line: u32,
+    column: u32,
+) -> DocResult<NodeSpan> {
+    let text = source_text(store, source)?;
+    PositionIndex::build(&text)?.path_at(line, column)
+}
+
+fn path_to_position_inner(store: &DocStore, source: TextSource, path: Path) -> DocResult<NodeSpan> {
+    let text = source_text(store, source)?;
+    PositionIndex::build(&text)?.span_of(&path)
+}
+
+fn lint_json_inner(
+    content: &str,
+    config: Option<LintConfig>,
+    path: Option<&str>,
+) -> DocResult<LintReport> {
+    let (config, config_path) = match config {
+        Some(config) => (config, None),
+        None => match path
+            .and_then(|p| std::path::Path::new(p).parent())
+            .and_then(lint::find_config)
+        {
+            Some(found) => (
+                lint::load_config(&found)?,
+                Some(found.to_string_lossy().into_owned()),
+            ),
+            None => (LintConfig::default(), None),
+        },
+    };
+    let (findings, truncated) = lint::lint(content, &config)?;
+    Ok(LintReport {
+        findings,
+        truncated,
+        config_path,
+    })
+}
+
+fn get_completions_inner(
+    content: &str,
+    position: Cursor,
+    schema: Option<&str>,
+) -> DocResult<Completions> {
+    let schema = schema.map(parse_schema_arg).transpose()?;
+    completions::complete(content, position, schema.as_ref())
+}
+
+fn get_hover_info_inner(
+    store: &DocStore,
+    source: TextSource,
+    pointer: &str,
+    schema: Option<&str>,
+) -> DocResult<HoverInfo> {
+    let schema = schema.map(parse_schema_arg).transpose()?;
+    let (path, value) = match source {
+        TextSource::Content { content } => {
+            let root: serde_json::Value =
+                serde_json::from_str(&content).map_err(|e| DocError::Parse(e.to_string()))?;
+            let segments = goto::pointer_segments(pointer);
+            let (path, _) =
+                goto::exact_path(&segments, |p| goto::value_at(&root, p).map(goto::kind_of))
+                    .ok_or_else(|| {
+                        DocError::InvalidPath(Path(
+                            segments
+                                .into_iter()
+                                .map(crate::doc::types::PathSegment::Key)
+                                .collect(),
+                        ))
+                    })?;
+            let value = goto::value_at(&root, &path).cloned().unwrap_or_default();
+            (path, value)
+        }
+        TextSource::Handle { handle } => {
+            let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+            let doc = arc.read();
+            let path = navigation::path_from_pointer(&doc, pointer)?;
+            let value = doc.get_value(&path)?;
+            (path, value)
+        }
+    };
+    Ok(hover::hover(&path, &value, schema.as_ref()))
+}
+
+/// Returns the document's text buffer, re-seeding it from the document when
+/// an op, undo/redo or root replacement moved the document past it. Text
+/// edits that do not parse yet are dropped in that case: the document wins.
+fn synced_text_buffer(
+    store: &DocStore,
+    handle: DocHandle,
+    doc: &Document,
+) -> DocResult<Arc<parking_lot::Mutex<TextBuffer>>> {
+    if let Some(buffer) = store.text_buffer(handle) {
+        {
+            let mut current = buffer.lock();
+            if current.doc_version() != doc.version {
+                current.reseed(doc.serialize()?, doc.version);
+            }
+        }
+        return Ok(buffer);
+    }
+    store.set_text_buffer(handle, TextBuffer::new(doc.serialize()?, doc.version));
+    store.text_buffer(handle).ok_or(DocError::NotFound(handle))
+}
+
+fn text_buffer_open_inner(store: &DocStore, handle: DocHandle) -> DocResult<TextEditResult> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let doc = arc.read();
+    let buffer = synced_text_buffer(store, handle, &doc)?;
+    let status = buffer.lock().status();
+    Ok(status)
+}
+
+/// Applies a text edit and, once the buffer parses again, writes the result
+/// back to the document as one undoable root replacement.
+fn apply_text_edit_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    range: TextRange,
+    new_text: &str,
+) -> DocResult<TextEditResult> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let mut doc = arc.write();
+    let buffer = synced_text_buffer(store, handle, &doc)?;
+    let mut buffer = buffer.lock();
+    let result = buffer.apply(range, new_text)?;
+    if result.structure_valid {
+        let value: serde_json::Value = serde_json::from_str(&buffer.text())
+            .map_err(|e| DocError::Parse(format!("invalid JSON: {e}")))?;
+        if doc.get_value(&Path::root())? != value {
+            doc.apply(&Op::SetValue {
+                path: Path::root(),
+                value,
+            })?;
+        }
+        buffer.set_doc_version(doc.version);
+    }
+    Ok(result)
+}
+
+fn with_text_buffer<T>(
+    store: &DocStore,
+    handle: DocHandle,
+    f: impl FnOnce(&TextBuffer) -> DocResult<T>,
+) -> DocResult<T> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let doc = arc.read();
+    let buffer = synced_text_buffer(store, handle, &doc)?;
+    let buffer = buffer.lock();
+    f(&buffer)
 }
 
 fn doc_get_rows_inner(
@@ -188,6 +953,47 @@ fn doc_apply_op_inner(store: &DocStore, handle: DocHandle, op: Op) -> DocResult<
     doc.apply(&op)
 }
 
+fn run_script_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    source: &str,
+    bindings: ScriptBindings,
+) -> DocResult<ScriptResult> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let selection = bindings.selection.clone().unwrap_or_default();
+    let (input, base_version) = {
+        let doc = arc.read();
+        (doc.get_value(&selection)?, doc.version)
+    };
+    let outcome = script::eval(source, &input, &selection, &bindings)?;
+    let mut version = None;
+    let mut applied = false;
+    if let Some(value) = outcome.document.clone().filter(|_| bindings.apply) {
+        let mut doc = arc.write();
+        if doc.version != base_version {
+            return Err(DocError::Conflict(format!(
+                "the document changed while the script ran (version {base_version} -> {})",
+                doc.version
+            )));
+        }
+        let result = doc.apply(&Op::SetValue {
+            path: selection,
+            value,
+        })?;
+        version = Some(result.version);
+        applied = true;
+    }
+    Ok(ScriptResult {
+        value: outcome.value,
+        document: outcome.document,
+        changed: outcome.changed,
+        output: outcome.output,
+        applied,
+        version,
+        duration_ms: outcome.duration_ms,
+    })
+}
+
 fn doc_undo_inner(store: &DocStore, handle: DocHandle) -> DocResult<Option<ApplyResult>> {
     let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
     let mut doc = arc.write();
@@ -210,10 +1016,45 @@ fn doc_save_inner(
     store: &DocStore,
     handle: DocHandle,
     path: Option<String>,
+    expected_hash: Option<&str>,
+    hooks: Option<HookSource>,
 ) -> DocResult<SaveResult> {
     let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
     let mut doc = arc.write();
-    doc.save(path)
+    if let Some(current) = doc.file_path.clone() {
+        if path.as_deref().unwrap_or(&current) == current {
+            disk_hash::check_unchanged(expected_hash, std::path::Path::new(&current))?;
+        }
+    }
+    let Some(hooks) = hooks else {
+        return doc.save(path);
+    };
+    let target = path
+        .clone()
+        .or_else(|| doc.file_path.clone())
+        .ok_or_else(|| DocError::Edit("no file path — use Save As".into()))?;
+    let hooks = match hooks {
+        HookSource::Explicit(hooks) => hooks,
+        HookSource::Settings(settings) => {
+            SaveHooks::from_effective(&settings::effective(&settings, &target))
+                .with_editorconfig(&editorconfig::resolve(std::path::Path::new(&target))?)
+        }
+    };
+    let text = hooks.apply(doc.serialize()?, std::path::Path::new(&target))?;
+    doc.save_text(path, text)
+}
+
+enum HookSource {
+    Explicit(SaveHooks),
+    Settings(Settings),
+}
+
+/// A broken settings file must not block saving, so fall back to defaults.
+fn settings_or_default(settings: &SettingsStore) -> Settings {
+    settings.get().unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "loading settings failed; saving with defaults");
+        Settings::default()
+    })
 }
 
 fn doc_export_inner(
@@ -276,12 +1117,30 @@ fn doc_validate_schema_inner(
     store: &DocStore,
     handle: DocHandle,
     schema: String,
+    schema_path: Option<String>,
 ) -> DocResult<SchemaValidationResult> {
+    let schema = bundle_for_validation(schema, schema_path.as_deref())?;
     let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
     let doc = arc.read();
     doc.validate_schema(&schema)
 }
 
+fn bundle_for_validation(schema: String, schema_path: Option<&str>) -> DocResult<String> {
+    if schema_path.is_none() && !schema.contains("\"$ref\"") {
+        return Ok(schema);
+    }
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(schema.trim()) else {
+        return Ok(schema);
+    };
+    let bundled = schema_refs::bundle_schema(value, schema_path, &fetch_text)?;
+    serde_json::to_string(&bundled.schema).map_err(|e| DocError::Schema(e.to_string()))
+}
+
+fn parse_schema_arg(schema: &str) -> DocResult<serde_json::Value> {
+    serde_json::from_str(schema.trim())
+        .map_err(|e| DocError::Schema(format!("not valid JSON: {e}")))
+}
+
 fn doc_generate_types_inner(
     store: &DocStore,
     handle: DocHandle,
@@ -296,93 +1155,2417 @@ fn doc_generate_types_inner(
 #[tauri::command]
 pub async fn doc_open(
     state: tauri::State<'_, Arc<DocStore>>,
+    app_state: tauri::State<'_, crate::AppState>,
     source: OpenSource,
 ) -> Result<OpenResult, WireError> {
+    let _timing = telemetry::time("doc_open");
     let store = state.inner().clone();
-    run_blocking(move || doc_open_inner(&store, source)).await
+    let file = match &source {
+        OpenSource::File { path } => Some(path.clone()),
+        OpenSource::Text { .. } => None,
+    };
+    let (opened, disk) = run_blocking(move || {
+        let opened = doc_open_inner(&store, source)?;
+        let disk = file.and_then(|p| DiskState::read(std::path::Path::new(&p)).ok());
+        Ok((opened, disk))
+    })
+    .await?;
+    if let Some(disk) = disk {
+        app_state.disk_hashes.record(opened.handle, disk);
+    }
+    Ok(opened)
 }
 
-#[tauri::command]
-pub async fn doc_close(
-    state: tauri::State<'_, Arc<DocStore>>,
-    handle: DocHandle,
-) -> Result<bool, WireError> {
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlConflict {
+    pub remote_text: String,
+    pub merge: Option<MergeResult>,
+    pub etag: Option<String>,
+    pub revision: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveToUrlResult {
+    pub saved: bool,
+    pub etag: Option<String>,
+    pub conflict: Option<UrlConflict>,
+}
+
+#[tauri::command]
+pub async fn fetch_json_url(
+    state: tauri::State<'_, Arc<DocStore>>,
+    app_state: tauri::State<'_, crate::AppState>,
+    url: String,
+) -> Result<OpenResult, WireError> {
+    let _timing = telemetry::time("fetch_json_url");
+    let origin = remote::fetch_versioned(&url).await?;
+    let store = state.inner().clone();
+    let text = origin.base.clone();
+    let opened = run_blocking(move || {
+        doc_open_inner(
+            &store,
+            OpenSource::Text {
+                text,
+                name: Some(url),
+            },
+        )
+    })
+    .await?;
+    app_state.remote_origins.record(opened.handle, origin);
+    Ok(opened)
+}
+
+fn merge_remote(base: &str, ours: &str, theirs: &str) -> Option<MergeResult> {
+    let base = serde_json::from_str::<serde_json::Value>(base).ok();
+    let ours = serde_json::from_str::<serde_json::Value>(ours).ok()?;
+    let theirs = serde_json::from_str::<serde_json::Value>(theirs).ok()?;
+    conflicts::three_way_merge(base.as_ref(), &ours, &theirs).ok()
+}
+
+#[tauri::command]
+pub async fn save_to_url(
+    state: tauri::State<'_, Arc<DocStore>>,
+    app_state: tauri::State<'_, crate::AppState>,
+    handle: DocHandle,
+    method: Option<SaveMethod>,
+    force: Option<bool>,
+    resolved: Option<String>,
+) -> Result<SaveToUrlResult, WireError> {
+    let _timing = telemetry::time("save_to_url");
+    let origins = &app_state.remote_origins;
+    let origin = origins.get(handle).ok_or_else(|| {
+        WireError::from(DocError::Edit("document was not opened from a URL".into()))
+    })?;
+    let origin = match resolved {
+        Some(revision) => origins.resolved(handle, &revision).ok_or_else(|| {
+            WireError::from(DocError::Conflict(
+                "the remote changed again since the conflict was reported".into(),
+            ))
+        })?,
+        None => origin,
+    };
+    let arc = state.get(handle).ok_or(DocError::NotFound(handle))?;
+    let method = method.unwrap_or_default();
+    let doc_arc = arc.clone();
+    let base = origin.clone();
+    let (body, payload, version) = run_blocking(move || {
+        let doc = doc_arc.read();
+        let body = doc.serialize()?;
+        let payload = remote::request_body(&base, &body, method)?;
+        Ok((body, payload, doc.version))
+    })
+    .await?;
+    let outcome = remote::put_conditional(&origin, payload, method, force.unwrap_or(false)).await?;
+    match outcome {
+        PutOutcome::Saved {
+            etag,
+            last_modified,
+        } => {
+            {
+                let mut doc = arc.write();
+                if doc.version == version {
+                    doc.mark_saved(&body);
+                }
+            }
+            origins.record(
+                handle,
+                remote::RemoteOrigin {
+                    etag: etag.clone(),
+                    last_modified,
+                    base: body,
+                    ..origin
+                },
+            );
+            Ok(SaveToUrlResult {
+                saved: true,
+                etag,
+                conflict: None,
+            })
+        }
+        PutOutcome::PreconditionFailed => {
+            let current = remote::fetch_versioned(&origin.url).await?;
+            let merge = merge_remote(&origin.base, &body, &current.base);
+            let conflict = UrlConflict {
+                remote_text: current.base.clone(),
+                merge,
+                etag: current.etag.clone(),
+                revision: current.revision().map(str::to_string),
+            };
+            origins.record_conflict(handle, current);
+            Ok(SaveToUrlResult {
+                saved: false,
+                etag: None,
+                conflict: Some(conflict),
+            })
+        }
+    }
+}
+
+fn publish_tabs(app: &tauri::AppHandle, tabs: &TabRegistry, window: &str) -> TabList {
+    let list = tabs.list(window);
+    let _ = app.emit(tabs::CHANGED_EVENT, &list);
+    let _ = crate::rebuild_menu(app);
+    list
+}
+
+pub(crate) fn route_select_tab(app: &tauri::AppHandle, tab: Tab) -> Tab {
+    if let Some(window) = app.get_webview_window(&tab.window) {
+        let _ = window.set_focus();
+        let _ = window.emit(tabs::SELECT_EVENT, &tab);
+    }
+    if let Some(tabs) = app.try_state::<Arc<TabRegistry>>() {
+        publish_tabs(app, &tabs, &tab.window);
+    }
+    tab
+}
+
+#[tauri::command]
+pub async fn select_tab(
+    app: tauri::AppHandle,
+    tabs: tauri::State<'_, Arc<TabRegistry>>,
+    id: String,
+) -> Result<Tab, WireError> {
+    let _timing = telemetry::time("select_tab");
+    let tab = tabs.activate(&id)?;
+    Ok(route_select_tab(&app, tab))
+}
+
+#[tauri::command]
+pub async fn open_tab(
+    app: tauri::AppHandle,
+    window: tauri::Window,
+    tabs: tauri::State<'_, Arc<TabRegistry>>,
+    tab: TabInput,
+) -> Result<Tab, WireError> {
+    let _timing = telemetry::time("open_tab");
+    let opened = tabs.open(window.label(), tab);
+    publish_tabs(&app, &tabs, window.label());
+    Ok(opened)
+}
+
+#[tauri::command]
+pub async fn close_tab(
+    app: tauri::AppHandle,
+    tabs: tauri::State<'_, Arc<TabRegistry>>,
+    id: String,
+) -> Result<TabList, WireError> {
+    let _timing = telemetry::time("close_tab");
+    let (closed, _) = tabs.close(&id)?;
+    Ok(publish_tabs(&app, &tabs, &closed.window))
+}
+
+#[tauri::command]
+pub async fn list_tabs(
+    window: tauri::Window,
+    state: tauri::State<'_, Arc<DocStore>>,
+    tabs: tauri::State<'_, Arc<TabRegistry>>,
+    window_label: Option<String>,
+) -> Result<TabList, WireError> {
+    let _timing = telemetry::time("list_tabs");
+    tabs.refresh_dirty(|handle| state.get(handle).map(|arc| arc.read().summary().dirty));
+    Ok(tabs.list(window_label.as_deref().unwrap_or(window.label())))
+}
+
+#[tauri::command]
+pub async fn reorder_tabs(
+    app: tauri::AppHandle,
+    window: tauri::Window,
+    tabs: tauri::State<'_, Arc<TabRegistry>>,
+    order: Vec<String>,
+) -> Result<TabList, WireError> {
+    let _timing = telemetry::time("reorder_tabs");
+    tabs.reorder(window.label(), &order)?;
+    Ok(publish_tabs(&app, &tabs, window.label()))
+}
+
+#[tauri::command]
+pub async fn set_tab_dirty(
+    app: tauri::AppHandle,
+    tabs: tauri::State<'_, Arc<TabRegistry>>,
+    id: String,
+    dirty: bool,
+) -> Result<Tab, WireError> {
+    let _timing = telemetry::time("set_tab_dirty");
+    let tab = tabs.set_dirty(&id, dirty)?;
+    publish_tabs(&app, &tabs, &tab.window);
+    Ok(tab)
+}
+
+#[tauri::command]
+pub async fn activate_tab(
+    app: tauri::AppHandle,
+    tabs: tauri::State<'_, Arc<TabRegistry>>,
+    id: String,
+) -> Result<Tab, WireError> {
+    let _timing = telemetry::time("activate_tab");
+    let tab = tabs.activate(&id)?;
+    publish_tabs(&app, &tabs, &tab.window);
+    Ok(tab)
+}
+
+#[tauri::command]
+pub async fn update_tab(
+    app: tauri::AppHandle,
+    tabs: tauri::State<'_, Arc<TabRegistry>>,
+    id: String,
+    tab: TabInput,
+) -> Result<Tab, WireError> {
+    let _timing = telemetry::time("update_tab");
+    let updated = tabs.update(&id, tab)?;
+    publish_tabs(&app, &tabs, &updated.window);
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn list_templates(app: tauri::AppHandle) -> Result<Vec<TemplateInfo>, WireError> {
+    let _timing = telemetry::time("list_templates");
+    let dir = templates::user_dir(&app)?;
+    run_blocking(move || Ok(templates::list(&dir))).await
+}
+
+#[tauri::command]
+pub async fn create_from_template(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<DocStore>>,
+    id: String,
+    variables: Option<HashMap<String, String>>,
+) -> Result<OpenResult, WireError> {
+    let _timing = telemetry::time("create_from_template");
+    let user_dir = templates::user_dir(&app)?;
+    let store = state.inner().clone();
+    run_blocking(move || {
+        create_from_template_inner(&store, Some(&user_dir), &id, variables.unwrap_or_default())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_snippets(app: tauri::AppHandle) -> Result<Vec<SnippetInfo>, WireError> {
+    let _timing = telemetry::time("list_snippets");
+    let path = snippets::snippets_path(&app)?;
+    run_blocking(move || Ok(snippets::list(&path)?)).await
+}
+
+#[tauri::command]
+pub async fn save_snippet(
+    app: tauri::AppHandle,
+    snippet: SnippetInput,
+) -> Result<Snippet, WireError> {
+    let _timing = telemetry::time("save_snippet");
+    let path = snippets::snippets_path(&app)?;
+    let saved = run_blocking(move || snippets::save(&path, snippet)).await?;
+    let _ = crate::rebuild_menu(&app);
+    Ok(saved)
+}
+
+#[tauri::command]
+pub async fn delete_snippet(app: tauri::AppHandle, id: String) -> Result<bool, WireError> {
+    let _timing = telemetry::time("delete_snippet");
+    let path = snippets::snippets_path(&app)?;
+    let removed = run_blocking(move || Ok(snippets::delete(&path, &id)?)).await?;
+    if removed {
+        let _ = crate::rebuild_menu(&app);
+    }
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn render_snippet(
+    app: tauri::AppHandle,
+    id: String,
+    vars: Option<HashMap<String, String>>,
+) -> Result<RenderedSnippet, WireError> {
+    let _timing = telemetry::time("render_snippet");
+    let path = snippets::snippets_path(&app)?;
+    run_blocking(move || snippets::render(&path, &id, &vars.unwrap_or_default())).await
+}
+
+#[tauri::command]
+pub async fn stash_content(
+    app: tauri::AppHandle,
+    content: String,
+    label: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<StashEntry, WireError> {
+    let _timing = telemetry::time("stash_content");
+    let dir = stash::stash_dir(&app)?;
+    run_blocking(move || stash::stash(&dir, &content, label, tags.unwrap_or_default())).await
+}
+
+#[tauri::command]
+pub async fn list_stash(app: tauri::AppHandle) -> Result<Vec<StashEntry>, WireError> {
+    let _timing = telemetry::time("list_stash");
+    let dir = stash::stash_dir(&app)?;
+    run_blocking(move || Ok(stash::list(&dir)?)).await
+}
+
+#[tauri::command]
+pub async fn restore_stash(app: tauri::AppHandle, id: String) -> Result<StashedContent, WireError> {
+    let _timing = telemetry::time("restore_stash");
+    let dir = stash::stash_dir(&app)?;
+    run_blocking(move || stash::restore(&dir, &id)).await
+}
+
+#[tauri::command]
+pub async fn delete_stash(app: tauri::AppHandle, id: String) -> Result<bool, WireError> {
+    let _timing = telemetry::time("delete_stash");
+    let dir = stash::stash_dir(&app)?;
+    run_blocking(move || stash::delete(&dir, &id)).await
+}
+
+#[tauri::command]
+pub async fn list_macros(app: tauri::AppHandle) -> Result<Vec<Macro>, WireError> {
+    let _timing = telemetry::time("list_macros");
+    let path = macros::macros_path(&app)?;
+    run_blocking(move || Ok(macros::list(&path)?)).await
+}
+
+#[tauri::command]
+pub async fn save_macro(app: tauri::AppHandle, input: MacroInput) -> Result<Macro, WireError> {
+    let _timing = telemetry::time("save_macro");
+    let path = macros::macros_path(&app)?;
+    run_blocking(move || macros::save(&path, input)).await
+}
+
+#[tauri::command]
+pub async fn delete_macro(app: tauri::AppHandle, id: String) -> Result<bool, WireError> {
+    let _timing = telemetry::time("delete_macro");
+    let path = macros::macros_path(&app)?;
+    run_blocking(move || Ok(macros::delete(&path, &id)?)).await
+}
+
+#[tauri::command]
+pub async fn run_macro(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    id: String,
+    content: String,
+) -> Result<String, WireError> {
+    let _timing = telemetry::time("run_macro");
+    let path = macros::macros_path(&app)?;
+    run_pooled(&pool, Priority::Interactive, move || {
+        let found = macros::find(&path, &id)?;
+        macros::run(&found.steps, &content)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn run_macro_batch(
+    app: tauri::AppHandle,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    id: String,
+    paths: Vec<String>,
+) -> Result<Vec<MacroFileResult>, WireError> {
+    let _timing = telemetry::time("run_macro_batch");
+    let macros_path = macros::macros_path(&app)?;
+    let task_app = app.clone();
+    let (found_name, results) = run_pooled(&pool, Priority::Background, move || {
+        let found = macros::find(&macros_path, &id)?;
+        let progress = TaskProgress::start(&task_app, Some(paths.len() as u64));
+        let results = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let result = macros::run_on_file(&found.steps, path);
+                progress.advance(i as u64 + 1);
+                result
+            })
+            .collect::<Vec<_>>();
+        Ok((found.name, results))
+    })
+    .await?;
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    notify::notify_user(
+        &app,
+        &format!("Macro “{found_name}” finished"),
+        &match failed {
+            0 => format!("Processed {} files", results.len()),
+            n => format!("Processed {} files, {n} failed", results.len()),
+        },
+        None,
+    );
+    Ok(results)
+}
+
+#[tauri::command]
+pub async fn generate_ids(
+    kind: IdKind,
+    count: u32,
+    options: Option<IdOptions>,
+) -> Result<Vec<String>, WireError> {
+    let _timing = telemetry::time("generate_ids");
+    ids::generate_ids(kind, count, &options.unwrap_or_default()).map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn list_plugins(app: tauri::AppHandle) -> Result<Vec<PluginInfo>, WireError> {
+    let _timing = telemetry::time("list_plugins");
+    let dir = plugins::plugins_dir(&app)?;
+    Ok(plugins::list(&dir))
+}
+
+#[tauri::command]
+pub async fn run_plugin(
+    app: tauri::AppHandle,
+    id: String,
+    content: String,
+    args: Option<serde_json::Value>,
+) -> Result<PluginOutput, WireError> {
+    let _timing = telemetry::time("run_plugin");
+    let dir = plugins::plugins_dir(&app)?;
+    let args = args.unwrap_or(serde_json::Value::Null);
+    run_blocking(move || plugins::run(&dir, &id, &content, &args)).await
+}
+
+#[tauri::command]
+pub async fn run_external_tool(
+    app: tauri::AppHandle,
+    command: String,
+    args: Option<Vec<String>>,
+    stdin_content: Option<String>,
+    options: Option<ToolOptions>,
+) -> Result<ToolResult, WireError> {
+    let _timing = telemetry::time("run_external_tool");
+    let args = args.unwrap_or_default();
+    let options = options.unwrap_or_default();
+    let consent = external::consent_path(&app)?;
+    run_blocking(move || {
+        let line = external::approval_key(&command, &args, &options);
+        if !external::is_approved(&consent, &line)? {
+            let allowed = app
+                .dialog()
+                .message(format!(
+                    "Pandia wants to run an external program with the current document as input:\n\n{line}\n\nOnly allow programs you trust."
+                ))
+                .title("Run external tool?")
+                .kind(MessageDialogKind::Warning)
+                .buttons(MessageDialogButtons::OkCancelCustom(
+                    "Run".into(),
+                    "Cancel".into(),
+                ))
+                .blocking_show();
+            if !allowed {
+                return Err(DocError::Cancelled);
+            }
+            if options.remember {
+                external::approve(&consent, &line)?;
+            }
+        }
+        external::run_tool(&command, &args, stdin_content.as_deref(), &options)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_external_tools(app: tauri::AppHandle) -> Result<Vec<String>, WireError> {
+    let _timing = telemetry::time("list_external_tools");
+    let path = external::consent_path(&app)?;
+    run_blocking(move || Ok(external::approved_tools(&path)?)).await
+}
+
+#[tauri::command]
+pub async fn revoke_external_tool(
+    app: tauri::AppHandle,
+    command: String,
+) -> Result<bool, WireError> {
+    let _timing = telemetry::time("revoke_external_tool");
+    let path = external::consent_path(&app)?;
+    run_blocking(move || Ok(external::revoke(&path, &command)?)).await
+}
+
+#[tauri::command]
+pub async fn run_script(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    source: String,
+    bindings: Option<ScriptBindings>,
+) -> Result<ScriptResult, WireError> {
+    let _timing = telemetry::time("run_script");
+    let store = state.inner().clone();
+    run_blocking(move || run_script_inner(&store, handle, &source, bindings.unwrap_or_default()))
+        .await
+}
+
+#[tauri::command]
+pub async fn git_status(path: String) -> Result<GitFileStatus, WireError> {
+    let _timing = telemetry::time("git_status");
+    run_blocking(move || git::status(std::path::Path::new(&path))).await
+}
+
+#[tauri::command]
+pub async fn git_show_head(path: String) -> Result<Option<String>, WireError> {
+    let _timing = telemetry::time("git_show_head");
+    run_blocking(move || git::show_head(std::path::Path::new(&path))).await
+}
+
+#[tauri::command]
+pub async fn install_git_hook(
+    repo_path: String,
+    checks: Option<HookChecks>,
+) -> Result<HookInstall, WireError> {
+    let _timing = telemetry::time("install_git_hook");
+    run_blocking(move || {
+        git_hooks::install(
+            std::path::Path::new(&repo_path),
+            &checks.unwrap_or_default(),
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn uninstall_git_hook(repo_path: String) -> Result<bool, WireError> {
+    let _timing = telemetry::time("uninstall_git_hook");
+    run_blocking(move || git_hooks::uninstall(std::path::Path::new(&repo_path))).await
+}
+
+#[tauri::command]
+pub async fn git_diff_head(
+    state: tauri::State<'_, Arc<DocStore>>,
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    handle: DocHandle,
+    path: Option<String>,
+    job_id: Option<String>,
+) -> Result<GitHeadDiff, WireError> {
+    let _timing = telemetry::time("git_diff_head");
+    let store = state.inner().clone();
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let result = run_blocking(move || git_diff_head_inner(&store, handle, path, &cancel)).await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn list_snapshots(
+    snapshots: tauri::State<'_, Arc<Snapshots>>,
+    handle: DocHandle,
+) -> Result<Vec<SnapshotInfo>, WireError> {
+    let _timing = telemetry::time("list_snapshots");
+    Ok(snapshots.list(handle))
+}
+
+#[tauri::command]
+pub async fn diff_with_snapshot(
+    state: tauri::State<'_, Arc<DocStore>>,
+    snapshots: tauri::State<'_, Arc<Snapshots>>,
+    handle: DocHandle,
+    id: u64,
+) -> Result<SnapshotDiff, WireError> {
+    let _timing = telemetry::time("diff_with_snapshot");
+    let store = state.inner().clone();
+    let snapshots = snapshots.inner().clone();
+    run_blocking(move || {
+        let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+        let doc = arc.read();
+        snapshots.diff(handle, id, &doc)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn anonymize_preserving_shape(
+    content: String,
+    seed: Option<u64>,
+) -> Result<AnonymizeResult, WireError> {
+    let _timing = telemetry::time("anonymize_preserving_shape");
+    run_blocking(move || anonymize::anonymize(&content, seed)).await
+}
+
+#[tauri::command]
+pub async fn parse_conflict_file(path: String) -> Result<ConflictFile, WireError> {
+    let _timing = telemetry::time("parse_conflict_file");
+    run_blocking(move || conflicts::parse_conflict_file(std::path::Path::new(&path))).await
+}
+
+#[tauri::command]
+pub async fn write_resolution(
+    path: String,
+    content: String,
+) -> Result<ResolutionResult, WireError> {
+    let _timing = telemetry::time("write_resolution");
+    run_blocking(move || conflicts::write_resolution(std::path::Path::new(&path), &content)).await
+}
+
+#[tauri::command]
+pub async fn lockfile_analyze(path: String) -> Result<LockReport, WireError> {
+    let _timing = telemetry::time("lockfile_analyze");
+    run_blocking(move || lockfile::analyze_file(std::path::Path::new(&path))).await
+}
+
+#[tauri::command]
+pub async fn lockfile_diff(left: String, right: String) -> Result<LockDiff, WireError> {
+    let _timing = telemetry::time("lockfile_diff");
+    run_blocking(move || {
+        lockfile::diff_files(std::path::Path::new(&left), std::path::Path::new(&right))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn parse_log_file(
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    path: String,
+    options: Option<LogOptions>,
+    job_id: Option<String>,
+) -> Result<LogParseResult, WireError> {
+    let _timing = telemetry::time("parse_log_file");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let options = options.unwrap_or_default();
+    let result =
+        run_blocking(move || logs::parse_log_file(std::path::Path::new(&path), &options, &cancel))
+            .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn har_list_entries(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+) -> Result<Vec<HarEntry>, WireError> {
+    let _timing = telemetry::time("har_list_entries");
+    let store = state.inner().clone();
+    run_blocking(move || har_list_entries_inner(&store, handle)).await
+}
+
+#[tauri::command]
+pub async fn har_summary(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+) -> Result<HarSummary, WireError> {
+    let _timing = telemetry::time("har_summary");
+    let store = state.inner().clone();
+    run_blocking(move || har_summary_inner(&store, handle)).await
+}
+
+#[tauri::command]
+pub async fn har_extract_body(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    index: u32,
+    part: HarPart,
+) -> Result<HarBodyResult, WireError> {
+    let _timing = telemetry::time("har_extract_body");
+    let store = state.inner().clone();
+    run_blocking(move || har_extract_body_inner(&store, handle, index, part)).await
+}
+
+#[tauri::command]
+pub async fn notebook_cells(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+) -> Result<Notebook, WireError> {
+    let _timing = telemetry::time("notebook_cells");
+    let store = state.inner().clone();
+    run_blocking(move || notebook_cells_inner(&store, handle)).await
+}
+
+#[tauri::command]
+pub async fn notebook_set_cell_source(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    index: u32,
+    source: String,
+) -> Result<ApplyResult, WireError> {
+    let _timing = telemetry::time("notebook_set_cell_source");
+    let store = state.inner().clone();
+    run_blocking(move || notebook_set_cell_source_inner(&store, handle, index, &source)).await
+}
+
+#[tauri::command]
+pub async fn notebook_save(
+    state: tauri::State<'_, Arc<DocStore>>,
+    app_state: tauri::State<'_, crate::AppState>,
+    handle: DocHandle,
+    path: Option<String>,
+) -> Result<SaveResult, WireError> {
+    let _timing = telemetry::time("notebook_save");
+    let store = state.inner().clone();
+    let saved = run_blocking(move || {
+        let saved = notebook_save_inner(&store, handle, path)?;
+        let disk = DiskState::read(std::path::Path::new(&saved.path))?;
+        Ok((saved, disk))
+    })
+    .await?;
+    app_state.disk_hashes.record(handle, saved.1);
+    Ok(saved.0)
+}
+
+#[tauri::command]
+pub async fn k8s_open_stream(
+    state: tauri::State<'_, Arc<DocStore>>,
+    path: String,
+) -> Result<Vec<K8sStreamDoc>, WireError> {
+    let _timing = telemetry::time("k8s_open_stream");
+    let store = state.inner().clone();
+    run_blocking(move || k8s_open_stream_inner(&store, &path)).await
+}
+
+#[tauri::command]
+pub async fn k8s_validate(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    version: Option<String>,
+) -> Result<K8sValidation, WireError> {
+    let _timing = telemetry::time("k8s_validate");
+    let store = state.inner().clone();
+    run_blocking(move || k8s_validate_inner(&store, handle, version.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn k8s_cleanup(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+) -> Result<ApplyResult, WireError> {
+    let _timing = telemetry::time("k8s_cleanup");
+    let store = state.inner().clone();
+    run_blocking(move || k8s_cleanup_inner(&store, handle)).await
+}
+
+#[tauri::command]
+pub async fn helm_validate_values(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+) -> Result<SchemaValidationResult, WireError> {
+    let _timing = telemetry::time("helm_validate_values");
+    let store = state.inner().clone();
+    run_blocking(move || helm_validate_values_inner(&store, handle)).await
+}
+
+#[tauri::command]
+pub async fn resolve_placeholders(
+    content: String,
+    env_source: EnvSource,
+) -> Result<ResolveResult, WireError> {
+    let _timing = telemetry::time("resolve_placeholders");
+    run_blocking(move || {
+        let vars = envsubst::load_source(&env_source)?;
+        Ok(envsubst::resolve(&content, &vars))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn i18n_compare(
+    state: tauri::State<'_, Arc<DocStore>>,
+    base: DocHandle,
+    target: DocHandle,
+) -> Result<LocaleComparison, WireError> {
+    let _timing = telemetry::time("i18n_compare");
+    let store = state.inner().clone();
+    run_blocking(move || i18n_compare_inner(&store, base, target)).await
+}
+
+#[tauri::command]
+pub async fn i18n_merge(
+    state: tauri::State<'_, Arc<DocStore>>,
+    base: DocHandle,
+    target: DocHandle,
+    options: Option<MergeOptions>,
+) -> Result<LocaleMergeResult, WireError> {
+    let _timing = telemetry::time("i18n_merge");
+    let store = state.inner().clone();
+    let options = options.unwrap_or_default();
+    run_blocking(move || i18n_merge_inner(&store, base, target, &options)).await
+}
+
+#[tauri::command]
+pub async fn get_editorconfig(path: String) -> Result<EditorConfig, WireError> {
+    let _timing = telemetry::time("get_editorconfig");
+    run_blocking(move || editorconfig::resolve(std::path::Path::new(&path))).await
+}
+
+#[tauri::command]
+pub async fn get_effective_settings(
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    path: String,
+) -> Result<EffectiveSettings, WireError> {
+    let _timing = telemetry::time("get_effective_settings");
+    let store = settings.inner().clone();
+    run_blocking(move || Ok(settings::effective(&store.get()?, &path))).await
+}
+
+#[tauri::command]
+pub async fn get_settings(
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+) -> Result<Settings, WireError> {
+    let _timing = telemetry::time("get_settings");
+    let store = settings.inner().clone();
+    run_blocking(move || store.get()).await
+}
+
+#[tauri::command]
+pub async fn update_settings(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    docs: tauri::State<'_, Arc<DocStore>>,
+    patch: serde_json::Value,
+) -> Result<Settings, WireError> {
+    let _timing = telemetry::time("update_settings");
+    let store = settings.inner().clone();
+    let updated = run_blocking(move || store.update(&patch)).await?;
+    let log_dir = diagnostics::log_dir(&portable::data_dir(&app)?);
+    apply_runtime_settings(&updated, &log_dir, &pool, &docs)?;
+    let _ = app.emit(settings::CHANGED_EVENT, &updated);
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn reset_settings(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    docs: tauri::State<'_, Arc<DocStore>>,
+) -> Result<Settings, WireError> {
+    let _timing = telemetry::time("reset_settings");
+    let store = settings.inner().clone();
+    let updated = run_blocking(move || store.reset()).await?;
+    let log_dir = diagnostics::log_dir(&portable::data_dir(&app)?);
+    apply_runtime_settings(&updated, &log_dir, &pool, &docs)?;
+    let _ = app.emit(settings::CHANGED_EVENT, &updated);
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn get_portable_info(app: tauri::AppHandle) -> Result<PortableInfo, WireError> {
+    let _timing = telemetry::time("get_portable_info");
+    portable::info(&app).map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn export_settings(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    path: String,
+    preferences: Option<serde_json::Value>,
+) -> Result<ProfileSummary, WireError> {
+    let _timing = telemetry::time("export_settings");
+    let store = settings.inner().clone();
+    let data_dir = portable::data_dir(&app)?;
+    let templates_dir = templates::user_dir(&app)?;
+    let snippets_path = snippets::snippets_path(&app)?;
+    run_blocking(move || {
+        let bundle = profile::collect(
+            &data_dir,
+            &store,
+            &templates_dir,
+            &snippets_path,
+            preferences,
+        )?;
+        profile::write(std::path::Path::new(&path), &bundle)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn import_settings(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    docs: tauri::State<'_, Arc<DocStore>>,
+    path: String,
+) -> Result<ImportedProfile, WireError> {
+    let _timing = telemetry::time("import_settings");
+    let store = settings.inner().clone();
+    let data_dir = portable::data_dir(&app)?;
+    let log_dir = diagnostics::log_dir(&data_dir);
+    let templates_dir = templates::user_dir(&app)?;
+    let snippets_path = snippets::snippets_path(&app)?;
+    let imported = run_blocking(move || {
+        let path = std::path::Path::new(&path);
+        let bundle = profile::read(path)?;
+        profile::apply(
+            path,
+            bundle,
+            &data_dir,
+            &store,
+            &templates_dir,
+            &snippets_path,
+        )
+    })
+    .await?;
+    apply_runtime_settings(&imported.settings, &log_dir, &pool, &docs)?;
+    let _ = app.emit(settings::CHANGED_EVENT, &imported.settings);
+    Ok(imported)
+}
+
+#[tauri::command]
+pub async fn get_log_path(app: tauri::AppHandle) -> Result<String, WireError> {
+    let _timing = telemetry::time("get_log_path");
+    let dir = diagnostics::log_dir(&portable::data_dir(&app)?);
+    Ok(dir.to_string_lossy().into_owned())
+}
+
+#[tauri::command]
+pub async fn collect_diagnostics(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<DiagnosticsBundle, WireError> {
+    let _timing = telemetry::time("collect_diagnostics");
+    let data_dir = portable::data_dir(&app)?;
+    let out = match path {
+        Some(p) => std::path::PathBuf::from(p),
+        None => data_dir.join(format!(
+            "pandia-diagnostics-{}.zip",
+            crate::doc::timefmt::now_millis()
+        )),
+    };
+    let system = diagnostics::system_info(
+        app.package_info().version.to_string(),
+        portable::portable_root().is_some(),
+    );
+    run_blocking(move || diagnostics::collect(&diagnostics::log_dir(&data_dir), &out, &system))
+        .await
+}
+
+#[tauri::command]
+pub async fn set_update_channel(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    updater: tauri::State<'_, Arc<Updater>>,
+    channel: UpdateChannel,
+) -> Result<UpdateStatus, WireError> {
+    let _timing = telemetry::time("set_update_channel");
+    let store = settings.inner().clone();
+    let patch = serde_json::json!({ "updateChannel": channel });
+    let updated = run_blocking(move || store.update(&patch)).await?;
+    updater.forget_pending(&app);
+    let _ = app.emit(settings::CHANGED_EVENT, &updated);
+    updater.status(&app, &settings).map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn get_update_status(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    updater: tauri::State<'_, Arc<Updater>>,
+) -> Result<UpdateStatus, WireError> {
+    let _timing = telemetry::time("get_update_status");
+    updater.status(&app, &settings).map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn check_for_updates_now(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    updater: tauri::State<'_, Arc<Updater>>,
+) -> Result<UpdateState, WireError> {
+    let _timing = telemetry::time("check_for_updates_now");
+    let channel = settings.get()?.update_channel;
+    updater.check(&app, channel).await.map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn download_update(
+    app: tauri::AppHandle,
+    updater: tauri::State<'_, Arc<Updater>>,
+) -> Result<UpdateState, WireError> {
+    let _timing = telemetry::time("download_update");
+    updater.download(&app).await.map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn tokenize_range(
+    state: tauri::State<'_, Arc<DocStore>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    handle: DocHandle,
+    start_line: u32,
+    end_line: u32,
+) -> Result<TokenizedRange, WireError> {
+    let _timing = telemetry::time("tokenize_range");
+    let store = state.inner().clone();
+    run_pooled(&pool, Priority::Interactive, move || {
+        tokenize_range_inner(&store, handle, start_line, end_line)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_document_heatmap(
+    state: tauri::State<'_, Arc<DocStore>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    handle: DocHandle,
+    buckets: u32,
+    options: Option<HeatmapOptions>,
+) -> Result<Heatmap, WireError> {
+    let _timing = telemetry::time("get_document_heatmap");
+    let store = state.inner().clone();
+    run_pooled(&pool, Priority::Normal, move || {
+        doc_heatmap_inner(&store, handle, buckets, options.unwrap_or_default())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn build_search_index(
+    state: tauri::State<'_, Arc<DocStore>>,
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    handle: DocHandle,
+    job_id: Option<String>,
+) -> Result<IndexSummary, WireError> {
+    let _timing = telemetry::time("build_search_index");
+    let store = state.inner().clone();
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let result = run_pooled(&pool, Priority::Background, move || {
+        ensure_search_index(&store, handle, &cancel).map(|index| index.summary().clone())
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn search_document(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    query: String,
+    options: Option<IndexQueryOptions>,
+) -> Result<IndexedSearchResult, WireError> {
+    let _timing = telemetry::time("search_document");
+    let store = state.inner().clone();
+    let opts = options.unwrap_or_default().with_query(query);
+    run_blocking(move || {
+        let cancel = crate::doc::jobs::CancelFlag::never();
+        let index = ensure_search_index(&store, handle, &cancel)?;
+        Ok(index.search(&opts, &cancel))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn resolve_path(
+    state: tauri::State<'_, Arc<DocStore>>,
+    source: PathSource,
+    input: String,
+    limit: Option<u32>,
+) -> Result<PathResolution, WireError> {
+    let _timing = telemetry::time("resolve_path");
+    let store = state.inner().clone();
+    run_blocking(move || resolve_path_inner(&store, source, &input, limit.unwrap_or(0) as usize))
+        .await
+}
+
+#[tauri::command]
+pub async fn get_path_context(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    pointer: String,
+) -> Result<PathContext, WireError> {
+    let _timing = telemetry::time("get_path_context");
+    let store = state.inner().clone();
+    run_blocking(move || get_path_context_inner(&store, handle, &pointer)).await
+}
+
+#[tauri::command]
+pub async fn add_bookmark(
+    app: tauri::AppHandle,
+    path: String,
+    pointer: String,
+    label: Option<String>,
+) -> Result<Bookmark, WireError> {
+    let _timing = telemetry::time("add_bookmark");
+    let db = bookmarks::bookmarks_path(&app)?;
+    run_blocking(move || bookmarks::add_bookmark(&db, &path, &pointer, label)).await
+}
+
+#[tauri::command]
+pub async fn remove_bookmark(
+    app: tauri::AppHandle,
+    path: String,
+    id: String,
+) -> Result<bool, WireError> {
+    let _timing = telemetry::time("remove_bookmark");
+    let db = bookmarks::bookmarks_path(&app)?;
+    run_blocking(move || bookmarks::remove_bookmark(&db, &path, &id)).await
+}
+
+#[tauri::command]
+pub async fn list_bookmarks(app: tauri::AppHandle, path: String) -> Result<FileMarks, WireError> {
+    let _timing = telemetry::time("list_bookmarks");
+    let db = bookmarks::bookmarks_path(&app)?;
+    run_blocking(move || Ok(bookmarks::list(&db, &path)?)).await
+}
+
+#[tauri::command]
+pub async fn save_view_state(
+    app: tauri::AppHandle,
+    path: String,
+    state: ViewState,
+) -> Result<(), WireError> {
+    let _timing = telemetry::time("save_view_state");
+    let db = view_state::view_state_path(&app)?;
+    run_blocking(move || view_state::save(&db, &path, state)).await
+}
+
+#[tauri::command]
+pub async fn load_view_state(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<Option<LoadedViewState>, WireError> {
+    let _timing = telemetry::time("load_view_state");
+    let db = view_state::view_state_path(&app)?;
+    run_blocking(move || view_state::load(&db, &path)).await
+}
+
+#[tauri::command]
+pub async fn annotate(
+    app: tauri::AppHandle,
+    path: String,
+    pointer: String,
+    note: String,
+) -> Result<Option<Annotation>, WireError> {
+    let _timing = telemetry::time("annotate");
+    let db = bookmarks::bookmarks_path(&app)?;
+    run_blocking(move || bookmarks::annotate(&db, &path, &pointer, &note)).await
+}
+
+#[tauri::command]
+pub async fn export_annotations(
+    app: tauri::AppHandle,
+    path: String,
+    out: Option<String>,
+) -> Result<String, WireError> {
+    let _timing = telemetry::time("export_annotations");
+    let db = bookmarks::bookmarks_path(&app)?;
+    run_blocking(move || {
+        let written =
+            bookmarks::export_sidecar(&db, &path, out.as_deref().map(std::path::Path::new))?;
+        Ok(written.to_string_lossy().into_owned())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn import_annotations(
+    app: tauri::AppHandle,
+    path: String,
+    from: Option<String>,
+) -> Result<FileMarks, WireError> {
+    let _timing = telemetry::time("import_annotations");
+    let db = bookmarks::bookmarks_path(&app)?;
+    run_blocking(move || {
+        bookmarks::import_sidecar(&db, &path, from.as_deref().map(std::path::Path::new))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn pin_file(app: tauri::AppHandle, path: String) -> Result<Vec<PinnedFile>, WireError> {
+    let _timing = telemetry::time("pin_file");
+    let db = pinned::pinned_path(&app)?;
+    let pinned = run_blocking(move || pinned::pin(&db, &path)).await?;
+    let _ = crate::rebuild_menu(&app);
+    Ok(pinned)
+}
+
+#[tauri::command]
+pub async fn unpin_file(app: tauri::AppHandle, path: String) -> Result<Vec<PinnedFile>, WireError> {
+    let _timing = telemetry::time("unpin_file");
+    let db = pinned::pinned_path(&app)?;
+    let pinned = run_blocking(move || Ok(pinned::unpin(&db, &path)?)).await?;
+    let _ = crate::rebuild_menu(&app);
+    Ok(pinned)
+}
+
+#[tauri::command]
+pub async fn list_pinned(app: tauri::AppHandle) -> Result<Vec<PinnedFile>, WireError> {
+    let _timing = telemetry::time("list_pinned");
+    let db = pinned::pinned_path(&app)?;
+    run_blocking(move || Ok(pinned::load(&db)?)).await
+}
+
+async fn update_recents<F>(app: &tauri::AppHandle, f: F) -> Result<Vec<RecentEntry>, WireError>
+where
+    F: FnOnce(Vec<RecentEntry>) -> Vec<RecentEntry> + Send + 'static,
+{
+    let task_app = app.clone();
+    let list = run_blocking(move || Ok(recents::update(&task_app, f)?)).await?;
+    let _ = crate::set_menu_recents(app, &list);
+    Ok(list)
+}
+
+#[tauri::command]
+pub async fn add_recent_file(
+    app: tauri::AppHandle,
+    path: String,
+    name: Option<String>,
+    size: Option<u64>,
+) -> Result<Vec<RecentEntry>, WireError> {
+    let _timing = telemetry::time("add_recent_file");
+    update_recents(&app, move |list| {
+        recents::add(list, &path, name, size, recents::now())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remove_recent_file(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<Vec<RecentEntry>, WireError> {
+    let _timing = telemetry::time("remove_recent_file");
+    update_recents(&app, move |list| {
+        list.into_iter().filter(|e| e.path != path).collect()
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn clear_recent_files(app: tauri::AppHandle) -> Result<Vec<RecentEntry>, WireError> {
+    let _timing = telemetry::time("clear_recent_files");
+    update_recents(&app, |list| list.into_iter().filter(|e| e.pinned).collect()).await
+}
+
+#[tauri::command]
+pub async fn list_recent_files(app: tauri::AppHandle) -> Result<Vec<RecentEntry>, WireError> {
+    let _timing = telemetry::time("list_recent_files");
+    update_recents(&app, |list| {
+        recents::prune_missing(list, |p| !recents::deleted(p))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn pin_recent_file(
+    app: tauri::AppHandle,
+    path: String,
+    pinned: bool,
+) -> Result<Vec<RecentEntry>, WireError> {
+    let _timing = telemetry::time("pin_recent_file");
+    update_recents(&app, move |list| recents::set_pinned(list, &path, pinned)).await
+}
+
+#[tauri::command]
+pub async fn doc_close(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<DocStore>>,
+    app_state: tauri::State<'_, crate::AppState>,
+    taskbar: tauri::State<'_, Arc<Taskbar>>,
+    handle: DocHandle,
+) -> Result<bool, WireError> {
+    let _timing = telemetry::time("doc_close");
+    app_state.disk_hashes.forget(handle);
+    app_state.remote_origins.forget(handle);
+    taskbar.set_invalid(handle, false);
+    taskbar::refresh(&app);
     Ok(doc_close_inner(&state, handle))
 }
 
 #[tauri::command]
-pub async fn doc_get_slice(
+pub async fn doc_get_slice(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    path: Path,
+    start: u32,
+    end: u32,
+) -> Result<Vec<NodeView>, WireError> {
+    let _timing = telemetry::time("doc_get_slice");
+    doc_get_slice_inner(&state, handle, &path, start, end).map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn doc_get_value(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    path: Path,
+) -> Result<serde_json::Value, WireError> {
+    let _timing = telemetry::time("doc_get_value");
+    let store = state.inner().clone();
+    run_blocking(move || doc_get_value_inner(&store, handle, &path)).await
+}
+
+#[tauri::command]
+pub async fn doc_value_json(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    path: Path,
+) -> Result<String, WireError> {
+    let _timing = telemetry::time("doc_value_json");
+    let store = state.inner().clone();
+    run_blocking(move || doc_value_json_inner(&store, handle, &path)).await
+}
+
+#[tauri::command]
+pub async fn doc_summary(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+) -> Result<Summary, WireError> {
+    let _timing = telemetry::time("doc_summary");
+    doc_summary_inner(&state, handle).map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn doc_child_count(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    path: Path,
+) -> Result<Option<u32>, WireError> {
+    let _timing = telemetry::time("doc_child_count");
+    doc_child_count_inner(&state, handle, &path).map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn doc_diff(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<DocStore>>,
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    left: DocHandle,
+    right: DocHandle,
+    rules: Option<DiffRules>,
+    array_match: Option<ArrayMatch>,
+    job_id: Option<String>,
+) -> Result<Vec<DiffEntry>, WireError> {
+    let _timing = telemetry::time("doc_diff");
+    if left == right {
+        return Ok(Vec::new());
+    }
+    let filter = rules
+        .filter(|r| !r.is_empty())
+        .map(|r| r.compile())
+        .transpose()?;
+    let diff_opts = DiffOptions {
+        array_match: array_match.unwrap_or_default(),
+    };
+    let l_arc = state.get(left).ok_or(DocError::NotFound(left))?;
+    let r_arc = state.get(right).ok_or(DocError::NotFound(right))?;
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let result = run_pooled(&pool, Priority::Normal, move || {
+        let _task = TaskProgress::start(&app, None);
+        diff_arcs(l_arc, r_arc, &diff_opts, filter.as_ref(), &cancel)
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn diff_directories(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    left_dir: String,
+    right_dir: String,
+    options: Option<DirDiffOptions>,
+    job_id: Option<String>,
+) -> Result<DirDiffSummary, WireError> {
+    let _timing = telemetry::time("diff_directories");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let options = options.unwrap_or_default();
+    let result = run_pooled(&pool, Priority::Background, move || {
+        let _task = TaskProgress::start(&app, None);
+        dirdiff::diff_directories(
+            std::path::Path::new(&left_dir),
+            std::path::Path::new(&right_dir),
+            &options,
+            &cancel,
+        )
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn diff_adhoc(
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    left_content: String,
+    right_content: String,
+    options: Option<AdhocDiffOptions>,
+    job_id: Option<String>,
+) -> Result<AdhocDiff, WireError> {
+    let _timing = telemetry::time("diff_adhoc");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let options = options.unwrap_or_default();
+    let result = run_pooled(&pool, Priority::Normal, move || {
+        diff::diff_adhoc(&left_content, &right_content, &options, &cancel)
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn align_documents(left: String, right: String) -> Result<Alignment, WireError> {
+    let _timing = telemetry::time("align_documents");
+    run_blocking(move || align::align(&left, &right)).await
+}
+
+#[tauri::command]
+pub async fn list_diff_profiles(app: tauri::AppHandle) -> Result<Vec<DiffProfile>, WireError> {
+    let _timing = telemetry::time("list_diff_profiles");
+    let db = diff_rules::profiles_path(&app)?;
+    run_blocking(move || Ok(diff_rules::load(&db)?)).await
+}
+
+#[tauri::command]
+pub async fn save_diff_profile(
+    app: tauri::AppHandle,
+    name: String,
+    rules: DiffRules,
+) -> Result<Vec<DiffProfile>, WireError> {
+    let _timing = telemetry::time("save_diff_profile");
+    let db = diff_rules::profiles_path(&app)?;
+    run_blocking(move || diff_rules::save_profile(&db, &name, rules)).await
+}
+
+#[tauri::command]
+pub async fn delete_diff_profile(
+    app: tauri::AppHandle,
+    name: String,
+) -> Result<Vec<DiffProfile>, WireError> {
+    let _timing = telemetry::time("delete_diff_profile");
+    let db = diff_rules::profiles_path(&app)?;
+    run_blocking(move || Ok(diff_rules::delete_profile(&db, &name)?)).await
+}
+
+#[tauri::command]
+pub async fn export_diff(
+    state: tauri::State<'_, Arc<DocStore>>,
+    diff_result: DiffResultInput,
+    format: DiffExportFormat,
+    path: String,
+    left: Option<DocHandle>,
+    right: Option<DocHandle>,
+) -> Result<String, WireError> {
+    let _timing = telemetry::time("export_diff");
+    let store = state.inner().clone();
+    run_blocking(move || {
+        let root_of = |handle: Option<DocHandle>| -> DocResult<Option<serde_json::Value>> {
+            let Some(handle) = handle else {
+                return Ok(None);
+            };
+            let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+            let doc = arc.read();
+            doc.get_value(&Path::root()).map(Some)
+        };
+        let left_val = root_of(left)?;
+        let right_val = root_of(right)?;
+        let sides = DiffSides {
+            left: left_val.as_ref(),
+            right: right_val.as_ref(),
+        };
+        let text = diff_export::render(&diff_result, format, &sides)?;
+        diff_export::write(std::path::Path::new(&path), &text)?;
+        Ok(path)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn export_diagnostics(
+    results: Vec<FileDiagnostics>,
+    format: DiagnosticFormat,
+    path: String,
+) -> Result<String, WireError> {
+    let _timing = telemetry::time("export_diagnostics");
+    run_blocking(move || {
+        let text = diagnostic_export::render(&results, format)?;
+        std::fs::write(&path, text)?;
+        Ok(path)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn import_diff_patch(path: String) -> Result<Vec<DiffEntry>, WireError> {
+    let _timing = telemetry::time("import_diff_patch");
+    run_blocking(move || diff_export::read_patch(std::path::Path::new(&path))).await
+}
+
+#[tauri::command]
+pub async fn extract_subtree(
+    path: String,
+    pointer: String,
+    output_path: String,
+    replace_with: Option<ReplaceWith>,
+) -> Result<ExtractResult, WireError> {
+    let _timing = telemetry::time("extract_subtree");
+    run_blocking(move || {
+        extract::extract_subtree(
+            std::path::Path::new(&path),
+            &pointer,
+            std::path::Path::new(&output_path),
+            replace_with,
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn inline_refs(
+    state: tauri::State<'_, Arc<DocStore>>,
+    path: String,
+    options: Option<InlineOptions>,
+) -> Result<InlinedDocument, WireError> {
+    let _timing = telemetry::time("inline_refs");
+    let store = state.inner().clone();
+    let options = options.unwrap_or_default();
+    run_blocking(move || inline_refs_inner(&store, &path, &options)).await
+}
+
+#[tauri::command]
+pub async fn split_file(
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    path: String,
+    strategy: SplitStrategy,
+    options: Option<SplitOptions>,
+    job_id: Option<String>,
+) -> Result<SplitResult, WireError> {
+    let _timing = telemetry::time("split_file");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let options = options.unwrap_or_default();
+    let result = run_blocking(move || {
+        split::split_file(std::path::Path::new(&path), strategy, &options, &cancel)
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn concat_files(
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    paths: Vec<String>,
+    output: String,
+    provenance: Option<bool>,
+    job_id: Option<String>,
+) -> Result<ConcatResult, WireError> {
+    let _timing = telemetry::time("concat_files");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let result = run_blocking(move || {
+        split::concat_files(
+            &paths,
+            std::path::Path::new(&output),
+            provenance.unwrap_or(false),
+            &cancel,
+        )
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn array_to_ndjson(
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    source: RecordSource,
+    output_path: Option<String>,
+    job_id: Option<String>,
+) -> Result<ConvertResult, WireError> {
+    let _timing = telemetry::time("array_to_ndjson");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let result = run_pooled(&pool, Priority::Background, move || {
+        let output = output_path.as_deref().map(std::path::Path::new);
+        records::convert_source(
+            &source,
+            RecordFormat::Array,
+            RecordFormat::Ndjson,
+            output,
+            &cancel,
+        )
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn ndjson_to_array(
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    source: RecordSource,
+    output_path: Option<String>,
+    job_id: Option<String>,
+) -> Result<ConvertResult, WireError> {
+    let _timing = telemetry::time("ndjson_to_array");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let result = run_pooled(&pool, Priority::Background, move || {
+        let output = output_path.as_deref().map(std::path::Path::new);
+        records::convert_source(
+            &source,
+            RecordFormat::Ndjson,
+            RecordFormat::Array,
+            output,
+            &cancel,
+        )
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PipelineProgressEvent<'a> {
+    job_id: Option<&'a str>,
+    #[serde(flatten)]
+    progress: &'a pipeline::PipelineProgress,
+}
+
+#[tauri::command]
+pub async fn run_pipeline(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    input_path: String,
+    steps: Vec<PipelineStep>,
+    output_path: String,
+    provenance: Option<bool>,
+    job_id: Option<String>,
+) -> Result<PipelineResult, WireError> {
+    let _timing = telemetry::time("run_pipeline");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let event_id = owned_id.clone();
+    let task_app = app.clone();
+    let result = run_blocking(move || {
+        let app = task_app;
+        let total = std::fs::metadata(&input_path).ok().map(|m| m.len());
+        let task = TaskProgress::start(&app, total);
+        pipeline::run_pipeline(
+            std::path::Path::new(&input_path),
+            &steps,
+            std::path::Path::new(&output_path),
+            provenance.unwrap_or(false),
+            &cancel,
+            |progress| {
+                task.advance(progress.bytes_read);
+                let _ = app.emit(
+                    pipeline::PROGRESS_EVENT,
+                    PipelineProgressEvent {
+                        job_id: event_id.as_deref(),
+                        progress,
+                    },
+                );
+            },
+        )
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    if let Ok(done) = &result {
+        notify::notify_user(
+            &app,
+            "Pipeline finished",
+            &format!(
+                "Wrote {} records to {}",
+                done.records_out,
+                notify::display_name(&done.output_path)
+            ),
+            Some(&format!("open::{}", done.output_path)),
+        );
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn dedupe_ndjson(
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    path: String,
+    key: String,
+    strategy: DedupeStrategy,
+    output: String,
+    job_id: Option<String>,
+) -> Result<DedupeResult, WireError> {
+    let _timing = telemetry::time("dedupe_ndjson");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let result = run_blocking(move || {
+        dedupe::dedupe(
+            std::path::Path::new(&path),
+            &key,
+            strategy,
+            std::path::Path::new(&output),
+            &cancel,
+        )
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TailStarted {
+    tail_id: String,
+    offset: u64,
+}
+
+#[tauri::command]
+pub async fn tail_file(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    path: String,
+    options: Option<TailOptions>,
+    tail_id: String,
+) -> Result<TailStarted, WireError> {
+    let _timing = telemetry::time("tail_file");
+    let opts = options.unwrap_or_default();
+    let tailer = tail::Tailer::new(std::path::Path::new(&path), opts.from_start)?;
+    let offset = tailer.offset();
+    let cancel = jobs.register(tail_id.clone())?;
+    let registry = jobs.inner().clone();
+    let id = tail_id.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = tail::follow(&id, tailer, &opts, &cancel, |batch| {
+            let _ = app.emit(tail::RECORD_EVENT, &batch);
+        });
+        if let Err(e) = result {
+            tracing::warn!(error = %e, tail_id = %id, "tailing file stopped");
+        }
+        registry.unregister(&id);
+    });
+    Ok(TailStarted { tail_id, offset })
+}
+
+#[tauri::command]
+pub async fn watch_directory(
+    app: tauri::AppHandle,
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    path: String,
+    profile: Option<ValidationProfile>,
+    watch_id: String,
+) -> Result<(), WireError> {
+    let _timing = telemetry::time("watch_directory");
+    let profile = profile.unwrap_or_default();
+    let root = std::path::PathBuf::from(path);
+    let checker = {
+        let (root, profile) = (root.clone(), profile.clone());
+        run_blocking(move || {
+            let schema = match (&profile.schema, &profile.schema_path) {
+                (Some(schema), _) => Some(schema.clone()),
+                (None, Some(path)) => Some(std::fs::read_to_string(path)?),
+                (None, None) => None,
+            };
+            let schema = schema
+                .map(|s| bundle_for_validation(s, profile.schema_path.as_deref()))
+                .transpose()?
+                .map(|s| parse_schema_arg(&s))
+                .transpose()?;
+            watch::Checker::new(&root, schema.as_ref(), &profile)
+        })
+        .await?
+    };
+    let cancel = jobs.register(watch_id.clone())?;
+    let registry = jobs.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = watch::watch(&watch_id, &root, &checker, &profile, &cancel, |batch| {
+            let _ = app.emit(watch::RESULT_EVENT, &batch);
+        });
+        if let Err(e) = result {
+            tracing::warn!(error = %e, watch_id = %watch_id, "directory watch stopped");
+        }
+        registry.unregister(&watch_id);
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn value_histogram(
+    content: String,
+    pointer_glob: String,
+    options: Option<HistogramOptions>,
+) -> Result<ValueHistogram, WireError> {
+    let _timing = telemetry::time("value_histogram");
+    run_blocking(move || {
+        histogram::histogram(&content, &pointer_glob, &options.unwrap_or_default())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn extract_series(
+    content: String,
+    x_path: String,
+    y_paths: Vec<String>,
+    options: Option<SeriesOptions>,
+) -> Result<SeriesData, WireError> {
+    let _timing = telemetry::time("extract_series");
+    run_blocking(move || series::extract(&content, &x_path, &y_paths, &options.unwrap_or_default()))
+        .await
+}
+
+#[tauri::command]
+pub async fn layout_graph(
     state: tauri::State<'_, Arc<DocStore>>,
     handle: DocHandle,
-    path: Path,
-    start: u32,
-    end: u32,
-) -> Result<Vec<NodeView>, WireError> {
-    doc_get_slice_inner(&state, handle, &path, start, end).map_err(WireError::from)
+    algorithm: Option<LayoutAlgorithm>,
+    options: Option<GraphOptions>,
+) -> Result<GraphLayout, WireError> {
+    let _timing = telemetry::time("layout_graph");
+    let store = state.inner().clone();
+    run_blocking(move || {
+        let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+        let doc = arc.read();
+        graph::layout(
+            &doc,
+            algorithm.unwrap_or_default(),
+            &options.unwrap_or_default(),
+        )
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn doc_get_value(
+pub async fn expand_graph_node(
     state: tauri::State<'_, Arc<DocStore>>,
     handle: DocHandle,
+    pointer: String,
+    algorithm: Option<LayoutAlgorithm>,
+    options: Option<GraphOptions>,
+) -> Result<GraphExpansion, WireError> {
+    let _timing = telemetry::time("expand_graph_node");
+    let store = state.inner().clone();
+    run_blocking(move || {
+        let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+        let doc = arc.read();
+        let opts = options.unwrap_or_default();
+        graph::expand(&doc, &pointer, algorithm.unwrap_or_default(), &opts)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn similarity(a: String, b: String) -> Result<Similarity, WireError> {
+    let _timing = telemetry::time("similarity");
+    run_blocking(move || similarity::similarity(&a, &b)).await
+}
+
+#[tauri::command]
+pub async fn rank_similar_files(
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    reference_path: String,
+    dir: String,
+    options: Option<RankOptions>,
+    job_id: Option<String>,
+) -> Result<SimilarityRanking, WireError> {
+    let _timing = telemetry::time("rank_similar_files");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let options = options.unwrap_or_default();
+    let result = run_pooled(&pool, Priority::Background, move || {
+        similarity::rank_folder(
+            std::path::Path::new(&reference_path),
+            std::path::Path::new(&dir),
+            &options,
+            &cancel,
+        )
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn cluster_by_shape(
+    content: String,
+    path: String,
+    options: Option<ShapeOptions>,
+) -> Result<ShapeClusters, WireError> {
+    let _timing = telemetry::time("cluster_by_shape");
+    run_blocking(move || shape_clusters::cluster(&content, &path, &options.unwrap_or_default()))
+        .await
+}
+
+#[tauri::command]
+pub async fn normalize_types(
+    content: String,
+    rules: Option<NormalizeRules>,
+) -> Result<NormalizeResult, WireError> {
+    let _timing = telemetry::time("normalize_types");
+    run_blocking(move || coerce::normalize(&content, &rules.unwrap_or_default())).await
+}
+
+#[tauri::command]
+pub async fn diff_against_defaults(
+    content: String,
+    schema: String,
+    schema_path: Option<String>,
+) -> Result<DefaultsReport, WireError> {
+    let _timing = telemetry::time("diff_against_defaults");
+    run_blocking(move || {
+        let schema = bundle_for_validation(schema, schema_path.as_deref())?;
+        let schema = parse_schema_arg(&schema)?;
+        schema_defaults::diff_against_defaults(&content, &schema)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn validate_ndjson_against_schema(
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    path: String,
+    schema: String,
+    options: Option<StreamValidateOptions>,
+    job_id: Option<String>,
+) -> Result<StreamValidationReport, WireError> {
+    let _timing = telemetry::time("validate_ndjson_against_schema");
+    let opts = options.unwrap_or_default();
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let result = run_blocking(move || {
+        let schema = bundle_for_validation(schema, opts.schema_path.as_deref())?;
+        let schema = parse_schema_arg(&schema)?;
+        let reader = records::open(std::path::Path::new(&path))?;
+        stream_validate::validate_records(reader, &schema, &opts, &cancel)
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn profile_ndjson(
+    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
+    path: String,
+    fields: Option<Vec<String>>,
+    job_id: Option<String>,
+) -> Result<DatasetProfile, WireError> {
+    let _timing = telemetry::time("profile_ndjson");
+    let (cancel, owned_id) = match job_id {
+        Some(id) => {
+            let flag = jobs.register(id.clone())?;
+            (flag, Some(id))
+        }
+        None => (crate::doc::jobs::CancelFlag::never(), None),
+    };
+    let result = run_blocking(move || {
+        let reader = records::open(std::path::Path::new(&path))?;
+        field_stats::profile_records(reader, fields.as_deref(), &cancel)
+    })
+    .await;
+    if let Some(id) = owned_id {
+        jobs.unregister(&id);
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn position_to_path(
+    state: tauri::State<'_, Arc<DocStore>>,
+    source: TextSource,
+    line: u32,
+    column: u32,
+) -> Result<NodeSpan, WireError> {
+    let _timing = telemetry::time("position_to_path");
+    let store = state.inner().clone();
+    run_blocking(move || position_to_path_inner(&store, source, line, column)).await
+}
+
+#[tauri::command]
+pub async fn path_to_position(
+    state: tauri::State<'_, Arc<DocStore>>,
+    source: TextSource,
     path: Path,
-) -> Result<serde_json::Value, WireError> {
+) -> Result<NodeSpan, WireError> {
+    let _timing = telemetry::time("path_to_position");
     let store = state.inner().clone();
-    run_blocking(move || doc_get_value_inner(&store, handle, &path)).await
+    run_blocking(move || path_to_position_inner(&store, source, path)).await
 }
 
 #[tauri::command]
-pub async fn doc_value_json(
+pub async fn validate_json(content: String) -> Result<SyntaxReport, WireError> {
+    let _timing = telemetry::time("validate_json");
+    run_blocking(move || Ok(syntax::check(&content))).await
+}
+
+#[tauri::command]
+pub async fn format_selection(
+    state: tauri::State<'_, Arc<DocStore>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    selection: Selection,
+    indent: Option<u8>,
+    base_indent: Option<u32>,
+) -> Result<FragmentResult, WireError> {
+    let _timing = telemetry::time("format_selection");
+    let store = state.inner().clone();
+    let indent = match indent {
+        Some(indent) => indent,
+        None => settings.get()?.indent,
+    };
+    run_blocking(move || {
+        selection_inner(&store, selection, indent.max(1), base_indent.unwrap_or(0))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn minify_selection(
+    state: tauri::State<'_, Arc<DocStore>>,
+    selection: Selection,
+) -> Result<FragmentResult, WireError> {
+    let _timing = telemetry::time("minify_selection");
+    let store = state.inner().clone();
+    run_blocking(move || selection_inner(&store, selection, 0, 0)).await
+}
+
+#[tauri::command]
+pub async fn validate_fragment(content: String) -> Result<FragmentValidation, WireError> {
+    let _timing = telemetry::time("validate_fragment");
+    run_blocking(move || Ok(fragment::validate(&content))).await
+}
+
+#[tauri::command]
+pub async fn normalize_pasted(text: String) -> Result<NormalizedPaste, WireError> {
+    let _timing = telemetry::time("normalize_pasted");
+    run_blocking(move || paste::normalize(&text)).await
+}
+
+#[tauri::command]
+pub async fn parse_tabular_clipboard(
+    text: String,
+    header: Option<bool>,
+) -> Result<TabularPaste, WireError> {
+    let _timing = telemetry::time("parse_tabular_clipboard");
+    run_blocking(move || tabular::parse(&text, header.unwrap_or(true))).await
+}
+
+#[tauri::command]
+pub async fn detect_colors(content: String) -> Result<ColorReport, WireError> {
+    let _timing = telemetry::time("detect_colors");
+    run_blocking(move || colors::detect(&content)).await
+}
+
+#[tauri::command]
+pub fn convert_color(value: String, target_space: ColorSpace) -> Result<String, WireError> {
+    let _timing = telemetry::time("convert_color");
+    colors::convert(&value, target_space).map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn analyze_references(
+    content: String,
+    options: Option<ReferenceOptions>,
+) -> Result<ReferenceReport, WireError> {
+    let _timing = telemetry::time("analyze_references");
+    run_blocking(move || references::analyze(&content, &options.unwrap_or_default())).await
+}
+
+#[tauri::command]
+pub async fn index_workspace(
+    workspace: tauri::State<'_, Arc<WorkspaceIndex>>,
+    root: String,
+) -> Result<WorkspaceSummary, WireError> {
+    let _timing = telemetry::time("index_workspace");
+    let workspace = workspace.inner().clone();
+    run_blocking(move || workspace.index(std::path::Path::new(&root))).await
+}
+
+#[tauri::command]
+pub async fn find_references(
+    workspace: tauri::State<'_, Arc<WorkspaceIndex>>,
+    value_or_pointer: String,
+    file: Option<String>,
+) -> Result<Vec<WorkspaceReference>, WireError> {
+    let _timing = telemetry::time("find_references");
+    let workspace = workspace.inner().clone();
+    run_blocking(move || workspace.find_references(&value_or_pointer, file.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn go_to_definition(
+    workspace: tauri::State<'_, Arc<WorkspaceIndex>>,
+    file: String,
+    pointer: String,
+) -> Result<Vec<Location>, WireError> {
+    let _timing = telemetry::time("go_to_definition");
+    let workspace = workspace.inner().clone();
+    run_blocking(move || workspace.go_to_definition(&file, &pointer)).await
+}
+
+#[tauri::command]
+pub async fn check_links(
+    content: String,
+    options: Option<LinkOptions>,
+) -> Result<LinkReport, WireError> {
+    let _timing = telemetry::time("check_links");
+    links::check(&content, &options.unwrap_or_default())
+        .await
+        .map_err(WireError::from)
+}
+
+#[tauri::command]
+pub async fn merge_layers(
+    paths: Vec<String>,
+    strategy: Option<ArrayStrategy>,
+) -> Result<LayerMerge, WireError> {
+    let _timing = telemetry::time("merge_layers");
+    run_blocking(move || layers::merge_files(&paths, &strategy.unwrap_or_default())).await
+}
+
+#[tauri::command]
+pub async fn lint_json(
+    content: String,
+    config: Option<LintConfig>,
+    path: Option<String>,
+) -> Result<LintReport, WireError> {
+    let _timing = telemetry::time("lint_json");
+    run_blocking(move || lint_json_inner(&content, config, path.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn find_duplicate_keys(content: String) -> Result<Vec<DuplicateKey>, WireError> {
+    let _timing = telemetry::time("find_duplicate_keys");
+    run_blocking(move || Ok(PositionIndex::build(&content)?.duplicate_keys())).await
+}
+
+#[tauri::command]
+pub async fn get_completions(
+    content: String,
+    position: Cursor,
+    schema: Option<String>,
+) -> Result<Completions, WireError> {
+    let _timing = telemetry::time("get_completions");
+    run_blocking(move || get_completions_inner(&content, position, schema.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn get_hover_info(
+    state: tauri::State<'_, Arc<DocStore>>,
+    source: TextSource,
+    pointer: String,
+    schema: Option<String>,
+) -> Result<HoverInfo, WireError> {
+    let _timing = telemetry::time("get_hover_info");
+    let store = state.inner().clone();
+    run_blocking(move || get_hover_info_inner(&store, source, &pointer, schema.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn get_folding_ranges(content: String) -> Result<Vec<FoldingRange>, WireError> {
+    let _timing = telemetry::time("get_folding_ranges");
+    run_blocking(move || Ok(outline::folding_ranges(&PositionIndex::build(&content)?))).await
+}
+
+#[tauri::command]
+pub async fn get_outline(content: String, max_depth: usize) -> Result<Outline, WireError> {
+    let _timing = telemetry::time("get_outline");
+    run_blocking(move || {
+        Ok(outline::outline(
+            &PositionIndex::build(&content)?,
+            max_depth,
+        ))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn lsp_message(
+    server: tauri::State<'_, Arc<parking_lot::Mutex<crate::lsp::Server>>>,
+    message: serde_json::Value,
+) -> Result<Vec<serde_json::Value>, WireError> {
+    let _timing = telemetry::time("lsp_message");
+    let server = server.inner().clone();
+    run_blocking(move || Ok(server.lock().handle(&message))).await
+}
+
+#[tauri::command]
+pub async fn text_buffer_open(
     state: tauri::State<'_, Arc<DocStore>>,
     handle: DocHandle,
-    path: Path,
+) -> Result<TextEditResult, WireError> {
+    let _timing = telemetry::time("text_buffer_open");
+    let store = state.inner().clone();
+    run_blocking(move || text_buffer_open_inner(&store, handle)).await
+}
+
+#[tauri::command]
+pub async fn apply_text_edit(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    range: TextRange,
+    new_text: String,
+) -> Result<TextEditResult, WireError> {
+    let _timing = telemetry::time("apply_text_edit");
+    let store = state.inner().clone();
+    run_blocking(move || apply_text_edit_inner(&store, handle, range, &new_text)).await
+}
+
+#[tauri::command]
+pub async fn text_buffer_outline(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    max_depth: usize,
+) -> Result<Outline, WireError> {
+    let _timing = telemetry::time("text_buffer_outline");
+    let store = state.inner().clone();
+    run_blocking(move || with_text_buffer(&store, handle, |buffer| buffer.outline(max_depth))).await
+}
+
+#[tauri::command]
+pub async fn text_buffer_read(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    range: TextRange,
 ) -> Result<String, WireError> {
+    let _timing = telemetry::time("text_buffer_read");
     let store = state.inner().clone();
-    run_blocking(move || doc_value_json_inner(&store, handle, &path)).await
+    run_blocking(move || with_text_buffer(&store, handle, |buffer| buffer.read(range))).await
 }
 
 #[tauri::command]
-pub async fn doc_summary(
+pub async fn text_buffer_lines(
     state: tauri::State<'_, Arc<DocStore>>,
     handle: DocHandle,
-) -> Result<Summary, WireError> {
-    doc_summary_inner(&state, handle).map_err(WireError::from)
+    first: u32,
+    count: u32,
+) -> Result<Vec<String>, WireError> {
+    let _timing = telemetry::time("text_buffer_lines");
+    let store = state.inner().clone();
+    run_blocking(move || {
+        with_text_buffer(&store, handle, |buffer| Ok(buffer.read_lines(first, count)))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn text_buffer_position(
+    state: tauri::State<'_, Arc<DocStore>>,
+    handle: DocHandle,
+    offset: u32,
+) -> Result<TextPosition, WireError> {
+    let _timing = telemetry::time("text_buffer_position");
+    let store = state.inner().clone();
+    run_blocking(move || {
+        with_text_buffer(
+            &store,
+            handle,
+            |buffer| Ok(buffer.position(offset as usize)),
+        )
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn doc_child_count(
-    state: tauri::State<'_, Arc<DocStore>>,
-    handle: DocHandle,
-    path: Path,
-) -> Result<Option<u32>, WireError> {
-    doc_child_count_inner(&state, handle, &path).map_err(WireError::from)
+pub async fn get_worker_pool_stats(
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+) -> Result<PoolStats, WireError> {
+    let _timing = telemetry::time("get_worker_pool_stats");
+    Ok(pool.stats())
 }
 
 #[tauri::command]
-pub async fn doc_diff(
+pub async fn get_memory_usage(
     state: tauri::State<'_, Arc<DocStore>>,
-    jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
-    left: DocHandle,
-    right: DocHandle,
-    job_id: Option<String>,
-) -> Result<Vec<DiffEntry>, WireError> {
-    if left == right {
-        return Ok(Vec::new());
-    }
-    let l_arc = state.get(left).ok_or(DocError::NotFound(left))?;
-    let r_arc = state.get(right).ok_or(DocError::NotFound(right))?;
-    let (cancel, owned_id) = match job_id {
-        Some(id) => {
-            let flag = jobs.register(id.clone());
-            (flag, Some(id))
-        }
-        None => (crate::doc::jobs::CancelFlag::never(), None),
-    };
-    let result = run_blocking(move || diff_arcs(l_arc, r_arc, &cancel)).await;
-    if let Some(id) = owned_id {
-        jobs.unregister(&id);
-    }
-    result
+) -> Result<MemoryUsage, WireError> {
+    let _timing = telemetry::time("get_memory_usage");
+    let store = state.inner().clone();
+    run_blocking(move || Ok(store.memory_usage())).await
 }
 
 #[derive(Serialize)]
@@ -414,6 +3597,7 @@ pub async fn doc_get_rows(
     start: u32,
     end: u32,
 ) -> Result<Vec<String>, WireError> {
+    let _timing = telemetry::time("doc_get_rows");
     let store = state.inner().clone();
     run_blocking(move || {
         let values = doc_get_rows_inner(&store, handle, &path, start, end)?;
@@ -436,6 +3620,7 @@ pub async fn doc_get_rows_sorted(
     key: String,
     descending: bool,
 ) -> Result<Vec<RowJson>, WireError> {
+    let _timing = telemetry::time("doc_get_rows_sorted");
     let arc = state.get(handle).ok_or(DocError::NotFound(handle))?;
     run_blocking(move || {
         let doc = arc.read();
@@ -461,10 +3646,11 @@ pub async fn doc_get_rows_filtered(
     descending: bool,
     job_id: Option<String>,
 ) -> Result<FilteredRowsJson, WireError> {
+    let _timing = telemetry::time("doc_get_rows_filtered");
     let arc = state.get(handle).ok_or(DocError::NotFound(handle))?;
     let (cancel, owned_id) = match job_id {
         Some(id) => {
-            let flag = jobs.register(id.clone());
+            let flag = jobs.register(id.clone())?;
             (flag, Some(id))
         }
         None => (crate::doc::jobs::CancelFlag::never(), None),
@@ -500,6 +3686,7 @@ pub async fn doc_get_rows_at(
     path: Path,
     indices: Vec<u32>,
 ) -> Result<String, WireError> {
+    let _timing = telemetry::time("doc_get_rows_at");
     let store = state.inner().clone();
     run_blocking(move || {
         let values = doc_get_rows_at_inner(&store, handle, &path, indices)?;
@@ -516,6 +3703,7 @@ pub async fn doc_column_values(
     key: String,
     limit: u32,
 ) -> Result<ColumnValues, WireError> {
+    let _timing = telemetry::time("doc_column_values");
     doc_column_values_inner(&state, handle, &path, &key, limit).map_err(WireError::from)
 }
 
@@ -525,6 +3713,7 @@ pub async fn doc_column_schema(
     handle: DocHandle,
     path: Path,
 ) -> Result<ColumnSchema, WireError> {
+    let _timing = telemetry::time("doc_column_schema");
     doc_column_schema_inner(&state, handle, &path).map_err(WireError::from)
 }
 
@@ -534,6 +3723,7 @@ pub async fn doc_apply_op(
     handle: DocHandle,
     op: Op,
 ) -> Result<ApplyResult, WireError> {
+    let _timing = telemetry::time("doc_apply_op");
     doc_apply_op_inner(&state, handle, op).map_err(WireError::from)
 }
 
@@ -543,6 +3733,7 @@ pub async fn doc_set_root_text(
     handle: DocHandle,
     text: String,
 ) -> Result<ApplyResult, WireError> {
+    let _timing = telemetry::time("doc_set_root_text");
     if text.len() as u64 > EDIT_SIZE_LIMIT {
         return Err(DocError::TooLarge {
             actual: text.len() as u64,
@@ -571,6 +3762,7 @@ pub async fn doc_undo(
     state: tauri::State<'_, Arc<DocStore>>,
     handle: DocHandle,
 ) -> Result<Option<ApplyResult>, WireError> {
+    let _timing = telemetry::time("doc_undo");
     doc_undo_inner(&state, handle).map_err(WireError::from)
 }
 
@@ -579,6 +3771,7 @@ pub async fn doc_redo(
     state: tauri::State<'_, Arc<DocStore>>,
     handle: DocHandle,
 ) -> Result<Option<ApplyResult>, WireError> {
+    let _timing = telemetry::time("doc_redo");
     doc_redo_inner(&state, handle).map_err(WireError::from)
 }
 
@@ -590,10 +3783,11 @@ pub async fn doc_search(
     opts: SearchOptions,
     job_id: Option<String>,
 ) -> Result<Vec<SearchHit>, WireError> {
+    let _timing = telemetry::time("doc_search");
     let arc = state.get(handle).ok_or(DocError::NotFound(handle))?;
     let (cancel, owned_id) = match job_id {
         Some(id) => {
-            let flag = jobs.register(id.clone());
+            let flag = jobs.register(id.clone())?;
             (flag, Some(id))
         }
         None => (crate::doc::jobs::CancelFlag::never(), None),
@@ -614,17 +3808,26 @@ pub async fn cancel_job(
     jobs: tauri::State<'_, std::sync::Arc<crate::doc::jobs::JobRegistry>>,
     job_id: String,
 ) -> Result<bool, WireError> {
+    let _timing = telemetry::time("cancel_job");
     Ok(jobs.cancel(&job_id))
 }
 
 #[tauri::command]
 pub async fn doc_repair_text(text: String) -> Result<RepairResult, WireError> {
+    let _timing = telemetry::time("doc_repair_text");
     run_blocking(move || Ok(repair_string(&text))).await
 }
 
 #[tauri::command]
-pub async fn doc_detect_and_convert(text: String) -> Result<DetectResult, WireError> {
-    run_blocking(move || Ok(detect_and_convert(&text))).await
+pub async fn doc_detect_and_convert(
+    pool: tauri::State<'_, Arc<WorkerPool>>,
+    text: String,
+) -> Result<DetectResult, WireError> {
+    let _timing = telemetry::time("doc_detect_and_convert");
+    run_pooled(&pool, Priority::Interactive, move || {
+        Ok(detect_and_convert(&text))
+    })
+    .await
 }
 
 #[tauri::command]
@@ -632,26 +3835,311 @@ pub async fn doc_history(
     state: tauri::State<'_, Arc<DocStore>>,
     handle: DocHandle,
 ) -> Result<HistoryView, WireError> {
+    let _timing = telemetry::time("doc_history");
     doc_history_inner(&state, handle).map_err(WireError::from)
 }
 
 #[tauri::command]
 pub async fn doc_save(
     state: tauri::State<'_, Arc<DocStore>>,
+    app_state: tauri::State<'_, crate::AppState>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
     handle: DocHandle,
     path: Option<String>,
+    force: Option<bool>,
+    normalize: Option<bool>,
+    hooks: Option<SaveHooks>,
 ) -> Result<SaveResult, WireError> {
+    let _timing = telemetry::time("doc_save");
+    let store = state.inner().clone();
+    let settings = settings.inner().clone();
+    let expected = if force.unwrap_or(false) {
+        None
+    } else {
+        app_state.disk_hashes.get(handle)
+    };
+    let saved = run_blocking(move || {
+        let hooks = match (hooks, normalize.unwrap_or(true)) {
+            (Some(hooks), _) => Some(HookSource::Explicit(hooks)),
+            (None, true) => Some(HookSource::Settings(settings_or_default(&settings))),
+            (None, false) => None,
+        };
+        let saved = doc_save_inner(&store, handle, path, expected.as_deref(), hooks)?;
+        let target = std::path::Path::new(&saved.path);
+        if settings_or_default(&settings).write_search_metadata {
+            if let Err(e) = search_metadata::write_for_file(target) {
+                tracing::warn!(error = %e, path = %saved.path, "writing search metadata failed");
+            }
+        }
+        let disk = DiskState::read(target)?;
+        Ok((saved, disk))
+    })
+    .await?;
+    app_state.disk_hashes.record(handle, saved.1);
+    Ok(saved.0)
+}
+
+#[tauri::command]
+pub async fn sftp_connect(
+    sftp: tauri::State<'_, Arc<SftpConnections>>,
+    profile: SftpProfile,
+) -> Result<SftpConnection, WireError> {
+    let _timing = telemetry::time("sftp_connect");
+    let sftp = sftp.inner().clone();
+    run_blocking(move || sftp.connect(&profile)).await
+}
+
+#[tauri::command]
+pub async fn sftp_disconnect(
+    sftp: tauri::State<'_, Arc<SftpConnections>>,
+    connection: String,
+) -> Result<bool, WireError> {
+    let _timing = telemetry::time("sftp_disconnect");
+    Ok(sftp.disconnect(&connection))
+}
+
+#[tauri::command]
+pub async fn sftp_list(
+    sftp: tauri::State<'_, Arc<SftpConnections>>,
+    connection: String,
+    path: String,
+) -> Result<Vec<SftpEntry>, WireError> {
+    let _timing = telemetry::time("sftp_list");
+    let sftp = sftp.inner().clone();
+    run_blocking(move || sftp.list(&connection, &path)).await
+}
+
+#[tauri::command]
+pub async fn sftp_read(
+    sftp: tauri::State<'_, Arc<SftpConnections>>,
+    connection: String,
+    path: String,
+) -> Result<String, WireError> {
+    let _timing = telemetry::time("sftp_read");
+    let sftp = sftp.inner().clone();
+    run_blocking(move || sftp.read(&connection, &path)).await
+}
+
+#[tauri::command]
+pub async fn sftp_write(
+    sftp: tauri::State<'_, Arc<SftpConnections>>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
+    connection: String,
+    path: String,
+    content: String,
+    normalize: Option<bool>,
+) -> Result<SftpWriteResult, WireError> {
+    let _timing = telemetry::time("sftp_write");
+    let sftp = sftp.inner().clone();
+    let settings = settings.inner().clone();
+    run_blocking(move || {
+        let hooks = if normalize.unwrap_or(true) {
+            let effective = settings::effective(&settings.get()?, &path);
+            Some(SaveHooks::from_effective(&effective))
+        } else {
+            None
+        };
+        sftp.write(&connection, &path, content, hooks)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn materialize_file(path: String) -> Result<bool, WireError> {
+    let _timing = telemetry::time("materialize_file");
+    run_blocking(move || cloud::materialize(std::path::Path::new(&path))).await
+}
+
+#[tauri::command]
+pub async fn stat_file(path: String) -> Result<FileStat, WireError> {
+    let _timing = telemetry::time("stat_file");
+    run_blocking(move || file_info::stat(std::path::Path::new(&path))).await
+}
+
+#[tauri::command]
+pub async fn touch_file(path: String) -> Result<FileStat, WireError> {
+    let _timing = telemetry::time("touch_file");
+    run_blocking(move || file_info::touch(std::path::Path::new(&path))).await
+}
+
+#[tauri::command]
+pub async fn set_file_permissions(
+    path: String,
+    mode: Option<u32>,
+    readonly: Option<bool>,
+) -> Result<FileStat, WireError> {
+    let _timing = telemetry::time("set_file_permissions");
+    run_blocking(move || file_info::set_permissions(std::path::Path::new(&path), mode, readonly))
+        .await
+}
+
+async fn publish_file_operation(
+    app: &tauri::AppHandle,
+    op: FileOperation,
+) -> Result<FileOperation, WireError> {
+    match (&op.kind, &op.to) {
+        (FileOperationKind::Trashed, _) => {
+            let from = op.from.clone();
+            update_recents(app, move |list| {
+                list.into_iter().filter(|e| e.path != from).collect()
+            })
+            .await?;
+        }
+        (FileOperationKind::Renamed, Some(to)) => {
+            let (from, to) = (op.from.clone(), to.clone());
+            update_recents(app, move |list| recents::rename(list, &from, &to)).await?;
+        }
+        _ => {}
+    }
+    let _ = app.emit(file_ops::CHANGED_EVENT, &op);
+    Ok(op)
+}
+
+#[tauri::command]
+pub async fn delete_file_to_trash(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<FileOperation, WireError> {
+    let _timing = telemetry::time("delete_file_to_trash");
+    let op = run_blocking(move || file_ops::trash(std::path::Path::new(&path))).await?;
+    publish_file_operation(&app, op).await
+}
+
+#[tauri::command]
+pub async fn rename_file(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<DocStore>>,
+    old: String,
+    new: String,
+) -> Result<FileOperation, WireError> {
+    let _timing = telemetry::time("rename_file");
     let store = state.inner().clone();
-    run_blocking(move || doc_save_inner(&store, handle, path)).await
+    let app_handle = app.clone();
+    let op = run_blocking(move || {
+        let op = file_ops::rename(std::path::Path::new(&old), std::path::Path::new(&new))?;
+        let hashes = &app_handle.state::<crate::AppState>().disk_hashes;
+        for moved in store.retarget_file(&old, &new) {
+            hashes.refresh(moved, std::path::Path::new(&new));
+        }
+        Ok(op)
+    })
+    .await?;
+    publish_file_operation(&app, op).await
+}
+
+#[tauri::command]
+pub async fn duplicate_file(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<FileOperation, WireError> {
+    let _timing = telemetry::time("duplicate_file");
+    let op = run_blocking(move || file_ops::duplicate(std::path::Path::new(&path))).await?;
+    publish_file_operation(&app, op).await
+}
+
+#[tauri::command]
+pub async fn preview_binary(
+    path: String,
+    offset: Option<u64>,
+    length: Option<u64>,
+) -> Result<BinaryPreview, WireError> {
+    let _timing = telemetry::time("preview_binary");
+    run_blocking(move || {
+        binary::preview(
+            std::path::Path::new(&path),
+            offset.unwrap_or(0),
+            length.unwrap_or(4096),
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_content_hash(path: String) -> Result<String, WireError> {
+    let _timing = telemetry::time("get_content_hash");
+    run_blocking(move || Ok(disk_hash::hash_file(std::path::Path::new(&path))?)).await
 }
 
 #[tauri::command]
 pub async fn doc_set_file_path(
     state: tauri::State<'_, Arc<DocStore>>,
+    app_state: tauri::State<'_, crate::AppState>,
     handle: DocHandle,
     path: String,
 ) -> Result<Summary, WireError> {
-    doc_set_file_path_inner(&state, handle, path).map_err(WireError::from)
+    let _timing = telemetry::time("doc_set_file_path");
+    let store = state.inner().clone();
+    let (summary, disk) = run_blocking(move || {
+        let disk = DiskState::read(std::path::Path::new(&path)).ok();
+        Ok((doc_set_file_path_inner(&store, handle, path)?, disk))
+    })
+    .await?;
+    match disk {
+        Some(disk) => app_state.disk_hashes.record(handle, disk),
+        None => app_state.disk_hashes.forget(handle),
+    }
+    Ok(summary)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskMergeResult {
+    pub clean: bool,
+    pub conflicts: Vec<Path>,
+    pub applied: Option<ApplyResult>,
+}
+
+fn doc_merge_disk_inner(
+    store: &DocStore,
+    handle: DocHandle,
+    base: Option<&str>,
+) -> DocResult<(DiskMergeResult, DiskState)> {
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let mut doc = arc.write();
+    let path = doc
+        .file_path
+        .clone()
+        .ok_or_else(|| DocError::Edit("document is not backed by a file".into()))?;
+    let bytes = std::fs::read(&path)?;
+    let theirs: serde_json::Value = serde_json::from_slice(&bytes)
+        .map_err(|e| DocError::Parse(format!("{path} on disk is not valid JSON: {e}")))?;
+    let base = base.and_then(|b| serde_json::from_str::<serde_json::Value>(b).ok());
+    let ours = doc.get_value(&Path::root())?;
+    let merge = conflicts::three_way_merge(base.as_ref(), &ours, &theirs)?;
+    let applied = if merge.clean && merge.merged != ours {
+        Some(doc.apply(&Op::SetValue {
+            path: Path::root(),
+            value: merge.merged,
+        })?)
+    } else {
+        None
+    };
+    let result = DiskMergeResult {
+        clean: merge.clean,
+        conflicts: merge.conflicts.into_iter().map(|c| c.path).collect(),
+        applied,
+    };
+    Ok((result, DiskState::from_bytes(&bytes)))
+}
+
+/// Merges what another program wrote to the document's file into the open
+/// document, using the content seen at open/save as the common base. The
+/// disk state is only adopted when the merge is clean.
+#[tauri::command]
+pub async fn doc_merge_disk(
+    state: tauri::State<'_, Arc<DocStore>>,
+    app_state: tauri::State<'_, crate::AppState>,
+    handle: DocHandle,
+) -> Result<DiskMergeResult, WireError> {
+    let _timing = telemetry::time("doc_merge_disk");
+    let store = state.inner().clone();
+    let base = app_state.disk_hashes.base(handle);
+    let (result, disk) =
+        run_blocking(move || doc_merge_disk_inner(&store, handle, base.as_deref())).await?;
+    if result.clean {
+        app_state.disk_hashes.record(handle, disk);
+    }
+    Ok(result)
 }
 
 #[tauri::command]
@@ -661,6 +4149,7 @@ pub async fn doc_backup(
     handle: DocHandle,
     display_name: Option<String>,
 ) -> Result<bool, WireError> {
+    let _timing = telemetry::time("doc_backup");
     let (dirty, source_path, content) = {
         let arc = state.get(handle).ok_or(DocError::NotFound(handle))?;
         let doc = arc.read();
@@ -689,44 +4178,69 @@ pub async fn doc_backup(
 
 #[tauri::command]
 pub async fn doc_backup_clear(app: tauri::AppHandle, doc_id: String) -> Result<(), WireError> {
+    let _timing = telemetry::time("doc_backup_clear");
     backup::clear(&app, &doc_id).map_err(WireError::from)
 }
 
 #[tauri::command]
 pub async fn doc_backup_scan(app: tauri::AppHandle) -> Result<Vec<BackupRecord>, WireError> {
+    let _timing = telemetry::time("doc_backup_scan");
     backup::scan_once(&app).map_err(WireError::from)
 }
 
 #[tauri::command]
 pub async fn doc_export(
     state: tauri::State<'_, Arc<DocStore>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
     handle: DocHandle,
     format: ExportFormat,
 ) -> Result<String, WireError> {
+    let _timing = telemetry::time("doc_export");
     let store = state.inner().clone();
-    run_blocking(move || doc_export_inner(&store, handle, format)).await
+    run_pooled(&pool, Priority::Interactive, move || {
+        doc_export_inner(&store, handle, format)
+    })
+    .await
 }
 
 #[tauri::command]
 pub async fn doc_export_preview(
     state: tauri::State<'_, Arc<DocStore>>,
+    pool: tauri::State<'_, Arc<WorkerPool>>,
     handle: DocHandle,
     format: ExportFormat,
     max_chars: u32,
 ) -> Result<ExportPreview, WireError> {
+    let _timing = telemetry::time("doc_export_preview");
     let store = state.inner().clone();
-    run_blocking(move || doc_export_preview_inner(&store, handle, format, max_chars)).await
+    run_pooled(&pool, Priority::Interactive, move || {
+        doc_export_preview_inner(&store, handle, format, max_chars)
+    })
+    .await
 }
 
 #[tauri::command]
 pub async fn doc_export_to_file(
+    app: tauri::AppHandle,
     state: tauri::State<'_, Arc<DocStore>>,
     handle: DocHandle,
     format: ExportFormat,
     path: String,
 ) -> Result<(), WireError> {
+    let _timing = telemetry::time("doc_export_to_file");
     let store = state.inner().clone();
-    run_blocking(move || doc_export_to_file_inner(&store, handle, format, &path)).await
+    let started = std::time::Instant::now();
+    let target = path.clone();
+    run_blocking(move || doc_export_to_file_inner(&store, handle, format, &path)).await?;
+    if started.elapsed() >= notify::LONG_TASK {
+        notify::notify_user(
+            &app,
+            "Export finished",
+            &format!("Saved {}", notify::display_name(&target)),
+            Some(&format!("reveal::{target}")),
+        );
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -737,6 +4251,7 @@ pub async fn doc_replace(
     replacement: String,
     case_sensitive: bool,
 ) -> Result<ReplaceResult, WireError> {
+    let _timing = telemetry::time("doc_replace");
     let store = state.inner().clone();
     run_blocking(move || doc_replace_inner(&store, handle, &query, &replacement, case_sensitive))
         .await
@@ -744,12 +4259,63 @@ pub async fn doc_replace(
 
 #[tauri::command]
 pub async fn doc_validate_schema(
+    app: tauri::AppHandle,
     state: tauri::State<'_, Arc<DocStore>>,
+    taskbar: tauri::State<'_, Arc<Taskbar>>,
     handle: DocHandle,
     schema: String,
+    schema_path: Option<String>,
 ) -> Result<SchemaValidationResult, WireError> {
+    let _timing = telemetry::time("doc_validate_schema");
     let store = state.inner().clone();
-    run_blocking(move || doc_validate_schema_inner(&store, handle, schema)).await
+    let result =
+        run_blocking(move || doc_validate_schema_inner(&store, handle, schema, schema_path))
+            .await?;
+    taskbar.set_invalid(handle, !result.valid);
+    taskbar::refresh(&app);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn resolve_schema_refs(
+    schema: String,
+    base_path: Option<String>,
+) -> Result<RefResolution, WireError> {
+    let _timing = telemetry::time("resolve_schema_refs");
+    run_blocking(move || {
+        let value = parse_schema_arg(&schema)?;
+        schema_refs::resolve_schema_refs(value, base_path.as_deref(), &fetch_text)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn schema_example(
+    schema: String,
+    options: Option<ExampleOptions>,
+) -> Result<SchemaExample, WireError> {
+    let _timing = telemetry::time("schema_example");
+    run_blocking(move || {
+        let opts = options.unwrap_or_default();
+        let value = parse_schema_arg(&schema)?;
+        let resolved =
+            schema_refs::resolve_schema_refs(value, opts.base_path.as_deref(), &fetch_text)?;
+        Ok(generate_examples(&resolved.schema, &opts))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn bundle_schema(
+    schema: String,
+    base_path: Option<String>,
+) -> Result<RefResolution, WireError> {
+    let _timing = telemetry::time("bundle_schema");
+    run_blocking(move || {
+        let value = parse_schema_arg(&schema)?;
+        schema_refs::bundle_schema(value, base_path.as_deref(), &fetch_text)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -759,6 +4325,7 @@ pub async fn doc_generate_types(
     lang: TypegenLang,
     type_name: String,
 ) -> Result<String, WireError> {
+    let _timing = telemetry::time("doc_generate_types");
     let store = state.inner().clone();
     run_blocking(move || doc_generate_types_inner(&store, handle, lang, type_name)).await
 }
@@ -766,6 +4333,7 @@ pub async fn doc_generate_types(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::doc::test_support::TempDir;
     use crate::doc::types::{NodeKind, PathSegment};
 
     #[test]
@@ -805,6 +4373,71 @@ mod tests {
         assert_eq!(store.len(), 0);
     }
 
+    #[test]
+    fn create_from_template_opens_rendered_doc() {
+        let store = DocStore::new();
+        let mut vars = HashMap::new();
+        vars.insert("title".to_string(), "Pets".to_string());
+        let opened = create_from_template_inner(&store, None, "builtin:openapi", vars).unwrap();
+        assert_eq!(opened.summary.root_kind, NodeKind::Object);
+        assert_eq!(opened.summary.source_path.as_deref(), Some("openapi.json"));
+
+        let path = Path(vec![
+            PathSegment::Key("info".into()),
+            PathSegment::Key("title".into()),
+        ]);
+        let title = doc_get_value_inner(&store, opened.handle, &path).unwrap();
+        assert_eq!(title, serde_json::json!("Pets"));
+
+        let err =
+            create_from_template_inner(&store, None, "builtin:nope", HashMap::new()).unwrap_err();
+        assert!(matches!(err, DocError::Edit(_)));
+    }
+
+    #[test]
+    fn run_script_applies_result_to_selection() {
+        let store = DocStore::new();
+        let opened = doc_open_inner(
+            &store,
+            OpenSource::Text {
+                text: r#"{"items": [1, 2, 3], "keep": true}"#.into(),
+                name: None,
+            },
+        )
+        .unwrap();
+        let selection = Path(vec![PathSegment::Key("items".into())]);
+
+        let preview = run_script_inner(
+            &store,
+            opened.handle,
+            "doc.map(|x| x * 2)",
+            ScriptBindings {
+                selection: Some(selection.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(!preview.applied);
+        assert_eq!(preview.value, Some(serde_json::json!([2, 4, 6])));
+
+        let applied = run_script_inner(
+            &store,
+            opened.handle,
+            "doc.map(|x| x * 2)",
+            ScriptBindings {
+                selection: Some(selection.clone()),
+                apply: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(applied.applied);
+        assert_eq!(
+            doc_get_value_inner(&store, opened.handle, &selection).unwrap(),
+            serde_json::json!([2, 4, 6])
+        );
+    }
+
     #[test]
     fn close_known_handle_succeeds() {
         let store = DocStore::new();
@@ -983,6 +4616,164 @@ mod tests {
         assert!(matches!(err, DocError::NotFound(_)));
     }
 
+    #[test]
+    fn positions_round_trip_through_an_open_document() {
+        let store = DocStore::new();
+        let opened = doc_open_inner(
+            &store,
+            OpenSource::Text {
+                text: r#"{"a": {"b": [1, 2]}}"#.into(),
+                name: None,
+            },
+        )
+        .unwrap();
+        let source = || TextSource::Handle {
+            handle: opened.handle,
+        };
+        let span = path_to_position_inner(
+            &store,
+            source(),
+            Path(vec![
+                crate::doc::types::PathSegment::Key("a".into()),
+                crate::doc::types::PathSegment::Key("b".into()),
+                crate::doc::types::PathSegment::Index(1),
+            ]),
+        )
+        .unwrap();
+        let back =
+            position_to_path_inner(&store, source(), span.start.line, span.start.column).unwrap();
+        assert_eq!(back.pointer, "/a/b/1");
+    }
+
+    #[test]
+    fn validate_schema_bundles_refs_relative_to_schema_path() {
+        let dir = TempDir::new("validate-refs");
+        std::fs::write(
+            dir.join("defs.json"),
+            r#"{"$defs": {"n": {"type": "integer"}}}"#,
+        )
+        .unwrap();
+        let schema_path = dir.join("root.schema.json");
+        let schema = r#"{"properties": {"n": {"$ref": "defs.json#/$defs/n"}}}"#.to_string();
+
+        let store = DocStore::new();
+        let opened = doc_open_inner(
+            &store,
+            OpenSource::Text {
+                text: r#"{"n": "nope"}"#.into(),
+                name: None,
+            },
+        )
+        .unwrap();
+        let r = doc_validate_schema_inner(
+            &store,
+            opened.handle,
+            schema,
+            Some(schema_path.to_string_lossy().into_owned()),
+        )
+        .unwrap();
+        assert!(!r.valid);
+        assert_eq!(r.errors[0].instance_path, "/n");
+    }
+
+    #[test]
+    fn text_buffer_follows_the_document_both_ways() {
+        let store = DocStore::new();
+        let opened = doc_open_inner(
+            &store,
+            OpenSource::Text {
+                text: "[1]".into(),
+                name: None,
+            },
+        )
+        .unwrap();
+        let handle = opened.handle;
+        assert_eq!(text_buffer_open_inner(&store, handle).unwrap().version, 0);
+
+        doc_apply_op_inner(
+            &store,
+            handle,
+            Op::SetValue {
+                path: Path::root(),
+                value: serde_json::json!([2]),
+            },
+        )
+        .unwrap();
+        let lines = with_text_buffer(&store, handle, |b| Ok(b.read_lines(1, 10))).unwrap();
+        assert_eq!(lines.join("\n").replace(char::is_whitespace, ""), "[2]");
+
+        let text = with_text_buffer(&store, handle, |b| Ok(b.text())).unwrap();
+        let end = text.lines().count() as u32;
+        let whole = TextRange {
+            start: Cursor { line: 1, column: 1 },
+            end: Cursor {
+                line: end,
+                column: 100,
+            },
+        };
+        let result = apply_text_edit_inner(&store, handle, whole, "{\"a\": 3}").unwrap();
+        assert!(result.structure_valid);
+        let root = store.get(handle).unwrap().read().get_value(&Path::root());
+        assert_eq!(root.unwrap(), serde_json::json!({"a": 3}));
+
+        let first_line = TextRange {
+            start: Cursor { line: 1, column: 1 },
+            end: Cursor {
+                line: 1,
+                column: 100,
+            },
+        };
+        let broken = apply_text_edit_inner(&store, handle, first_line, "{").unwrap();
+        assert!(!broken.structure_valid);
+        let root = store.get(handle).unwrap().read().get_value(&Path::root());
+        assert_eq!(root.unwrap(), serde_json::json!({"a": 3}));
+    }
+
+    #[test]
+    fn merge_disk_folds_external_edits_into_the_document() {
+        let dir = TempDir::new("merge-disk");
+        let path = dir.join("doc.json");
+        let base = r#"{"a": 1, "b": 1}"#;
+        std::fs::write(&path, base).unwrap();
+        let store = DocStore::new();
+        let opened = doc_open_inner(
+            &store,
+            OpenSource::File {
+                path: path.to_string_lossy().into_owned(),
+            },
+        )
+        .unwrap();
+        let a = Path(vec![PathSegment::Key("a".into())]);
+        doc_apply_op_inner(
+            &store,
+            opened.handle,
+            Op::SetValue {
+                path: a,
+                value: serde_json::json!(2),
+            },
+        )
+        .unwrap();
+        std::fs::write(&path, r#"{"a": 1, "b": 3}"#).unwrap();
+
+        let (merged, disk) = doc_merge_disk_inner(&store, opened.handle, Some(base)).unwrap();
+        assert!(merged.clean);
+        assert!(merged.applied.is_some());
+        assert_eq!(disk.hash, disk_hash::hash_file(&path).unwrap());
+        let root = store
+            .get(opened.handle)
+            .unwrap()
+            .read()
+            .get_value(&Path::root());
+        assert_eq!(root.unwrap(), serde_json::json!({"a": 2, "b": 3}));
+
+        std::fs::write(&path, r#"{"a": 5, "b": 3}"#).unwrap();
+        let (conflicted, _) =
+            doc_merge_disk_inner(&store, opened.handle, Some(r#"{"a": 1, "b": 3}"#)).unwrap();
+        assert!(!conflicted.clean);
+        assert!(conflicted.applied.is_none());
+        assert_eq!(conflicted.conflicts.len(), 1);
+    }
+
     #[test]
     fn open_source_deserializes_tagged() {
         let file_form: OpenSource =
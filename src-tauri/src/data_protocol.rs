@@ -0,0 +1,409 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+
+use sonic_rs::FastStr;
+use tauri::http::{header, HeaderValue, Request, Response, StatusCode, Uri};
+use tauri::{AppHandle, Manager, UriSchemeResponder};
+
+use crate::commands::ensure_search_index;
+use crate::doc::blobs::Blob;
+use crate::doc::export::ExportFormat;
+use crate::doc::jobs::CancelFlag;
+use crate::doc::pool::{Priority, WorkerPool};
+use crate::doc::search_index::IndexQueryOptions;
+use crate::doc::store::DocStore;
+use crate::doc::types::{DocError, DocHandle, DocResult, ErrorKind, WireError};
+
+pub const SCHEME: &str = "pandia-data";
+
+/// Origins the app's own webview loads from: custom-scheme platforms, the
+/// Windows/Android http(s) mapping, and the dev server.
+const APP_ORIGINS: &[&str] = &[
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+    #[cfg(debug_assertions)]
+    "http://localhost:1420",
+];
+
+/// A response body. Bytes built for the response are handed to tauri as-is;
+/// a slice of a cached blob is copied once, when tauri takes ownership,
+/// because the cache keeps its own reference.
+pub enum Body {
+    Owned(Vec<u8>),
+    Shared { data: FastStr, range: Range<usize> },
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Body::Owned(Vec::new())
+    }
+}
+
+impl Deref for Body {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Body::Owned(bytes) => bytes,
+            Body::Shared { data, range } => &data.as_bytes()[range.clone()],
+        }
+    }
+}
+
+impl From<Body> for Cow<'static, [u8]> {
+    fn from(body: Body) -> Self {
+        match body {
+            Body::Owned(bytes) => Cow::Owned(bytes),
+            Body::Shared { data, range } => Cow::Owned(data.as_bytes()[range].to_vec()),
+        }
+    }
+}
+
+enum Resource {
+    Document(DocHandle),
+    Export(DocHandle, ExportFormat),
+    Search(DocHandle, String, IndexQueryOptions),
+}
+
+impl Resource {
+    fn handle(&self) -> DocHandle {
+        match self {
+            Resource::Document(h) | Resource::Export(h, _) | Resource::Search(h, _, _) => *h,
+        }
+    }
+}
+
+fn hex(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                (Some(hi), Some(lo)) => {
+                    out.push((hi << 4) | lo);
+                    i += 2;
+                }
+                _ => out.push(b'%'),
+            },
+            b'+' => out.push(b' '),
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn query_params(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn parse(uri: &Uri) -> Result<Resource, String> {
+    let mut parts = uri.path().trim_matches('/').split('/');
+    let kind = parts.next().unwrap_or_default();
+    let handle = parts
+        .next()
+        .and_then(|h| uuid::Uuid::parse_str(h).ok())
+        .map(DocHandle)
+        .ok_or("missing or invalid document handle")?;
+    let query = query_params(uri.query().unwrap_or_default());
+    match kind {
+        "doc" => Ok(Resource::Document(handle)),
+        "export" => {
+            let name = query.get("format").map_or("json", String::as_str);
+            let format = serde_json::from_value(serde_json::Value::String(name.into()))
+                .map_err(|_| format!("unknown export format {name:?}"))?;
+            Ok(Resource::Export(handle, format))
+        }
+        "search" => Ok(Resource::Search(
+            handle,
+            query.get("q").cloned().unwrap_or_default(),
+            IndexQueryOptions {
+                case_sensitive: query.get("caseSensitive").is_some_and(|v| v == "true"),
+                max_results: query
+                    .get("maxResults")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0),
+            },
+        )),
+        other => Err(format!("unknown resource {other:?}")),
+    }
+}
+
+pub fn parse_range(range: &str, len: usize) -> Option<(usize, usize)> {
+    let spec = range.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') || len == 0 {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let n: usize = suffix.parse().ok()?;
+            (len.saturating_sub(n), len - 1)
+        }
+        (start, "") => (start.parse().ok()?, len - 1),
+        (start, end) => (start.parse().ok()?, end.parse::<usize>().ok()?.min(len - 1)),
+    };
+    (start <= end && start < len).then_some((start, end))
+}
+
+fn content_type(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Json | ExportFormat::JsonMin => "application/json",
+        ExportFormat::Yaml => "application/yaml",
+        ExportFormat::Csv => "text/csv",
+        ExportFormat::Xml => "application/xml",
+    }
+}
+
+fn load(store: &DocStore, resource: &Resource, key: String) -> DocResult<Blob> {
+    let handle = resource.handle();
+    if let Resource::Search(_, query, options) = resource {
+        let cancel = CancelFlag::never();
+        let index = ensure_search_index(store, handle, &cancel)?;
+        if let Some(blob) = store.blobs().get(&key, index.version()) {
+            return Ok(blob);
+        }
+        let result = index.search(&options.clone().with_query(query.clone()), &cancel);
+        let data = serde_json::to_string(&result).map_err(|e| DocError::Export(e.to_string()))?;
+        return Ok(store.remember_blob(Blob {
+            handle,
+            key,
+            version: index.version(),
+            content_type: "application/json",
+            data: data.into(),
+            shared: false,
+        }));
+    }
+    let arc = store.get(handle).ok_or(DocError::NotFound(handle))?;
+    let blob = {
+        let doc = arc.read();
+        if let Some(blob) = store.blobs().get(&key, doc.version) {
+            return Ok(blob);
+        }
+        let (data, shared, content_type) = match resource {
+            Resource::Export(_, format) => {
+                (doc.export(*format)?.into(), false, content_type(*format))
+            }
+            _ => {
+                let (data, shared) = doc.index_text()?;
+                (data, shared, "application/json")
+            }
+        };
+        Blob {
+            handle,
+            key,
+            version: doc.version,
+            content_type,
+            data,
+            shared,
+        }
+    };
+    Ok(store.remember_blob(blob))
+}
+
+fn allowed_origin(request: &Request<Vec<u8>>) -> Option<&HeaderValue> {
+    request
+        .headers()
+        .get(header::ORIGIN)
+        .filter(|origin| APP_ORIGINS.iter().any(|allowed| origin == allowed))
+}
+
+fn error_response(
+    request: &Request<Vec<u8>>,
+    status: StatusCode,
+    error: WireError,
+) -> Response<Body> {
+    let mut builder = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(origin) = allowed_origin(request) {
+        builder = builder.header(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    }
+    builder
+        .body(Body::Owned(serde_json::to_vec(&error).unwrap_or_default()))
+        .unwrap_or_default()
+}
+
+pub fn respond(store: &DocStore, request: &Request<Vec<u8>>) -> Response<Body> {
+    let resource = match parse(request.uri()) {
+        Ok(resource) => resource,
+        Err(message) => {
+            return error_response(
+                request,
+                StatusCode::BAD_REQUEST,
+                WireError {
+                    kind: ErrorKind::InvalidPath,
+                    message,
+                },
+            )
+        }
+    };
+    let key = format!(
+        "{}?{}",
+        request.uri().path(),
+        request.uri().query().unwrap_or_default()
+    );
+    let blob = match load(store, &resource, key) {
+        Ok(blob) => blob,
+        Err(e) => {
+            let status = match e {
+                DocError::NotFound(_) => StatusCode::NOT_FOUND,
+                DocError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            return error_response(request, status, WireError::from(e));
+        }
+    };
+    let len = blob.data.len();
+    let mut builder = Response::builder()
+        .header(header::CONTENT_TYPE, blob.content_type)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header("X-Pandia-Version", blob.version);
+    if let Some(origin) = allowed_origin(request) {
+        builder = builder
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin)
+            .header(header::VARY, "Origin")
+            .header(
+                header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                "Content-Range, X-Pandia-Version",
+            );
+    }
+    let body = |range: Range<usize>| Body::Shared {
+        data: blob.data.clone(),
+        range,
+    };
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok());
+    let response = match range.map(|r| parse_range(r, len)) {
+        None => builder.status(StatusCode::OK).body(body(0..len)),
+        Some(Some((start, end))) => builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}"))
+            .body(body(start..end + 1)),
+        Some(None) => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{len}"))
+            .body(Body::default()),
+    };
+    response.unwrap_or_default()
+}
+
+pub fn serve(app: &AppHandle, request: Request<Vec<u8>>, responder: UriSchemeResponder) {
+    let store = app.state::<Arc<DocStore>>().inner().clone();
+    let pool = app.state::<Arc<WorkerPool>>();
+    pool.submit(Priority::Interactive, move || {
+        responder.respond(respond(&store, &request));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::document::Document;
+
+    fn get(store: &DocStore, uri: &str, range: Option<&str>) -> Response<Body> {
+        let mut request = Request::builder().uri(uri);
+        if let Some(range) = range {
+            request = request.header(header::RANGE, range);
+        }
+        respond(store, &request.body(Vec::new()).unwrap())
+    }
+
+    #[test]
+    fn parses_byte_ranges() {
+        assert_eq!(parse_range("bytes=0-9", 100), Some((0, 9)));
+        assert_eq!(parse_range("bytes=90-", 100), Some((90, 99)));
+        assert_eq!(parse_range("bytes=-10", 100), Some((90, 99)));
+        assert_eq!(parse_range("bytes=50-500", 100), Some((50, 99)));
+        assert_eq!(parse_range("bytes=100-", 100), None);
+        assert_eq!(parse_range("bytes=0-1,4-5", 100), None);
+        assert_eq!(parse_range("items=0-1", 100), None);
+    }
+
+    #[test]
+    fn streams_document_and_export_ranges() {
+        let store = DocStore::new();
+        let handle =
+            store.insert(Document::from_text(r#"{"name": "a b", "n": [1, 2]}"#, None).unwrap());
+        let base = format!(
+            "pandia-data://localhost/export/{}?format=json-min",
+            handle.0
+        );
+
+        let full = get(&store, &base, None);
+        assert_eq!(full.status(), StatusCode::OK);
+        assert_eq!(&**full.body(), br#"{"name":"a b","n":[1,2]}"#);
+        assert!(full
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+
+        let part = get(&store, &base, Some("bytes=1-6"));
+        assert_eq!(part.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(&**part.body(), br#""name""#);
+        assert_eq!(part.headers()[header::CONTENT_RANGE], "bytes 1-6/24");
+        assert!(store.blobs().bytes(handle) > 0);
+        assert!(store.memory_usage().documents[0].blob_bytes > 0);
+        store.remove(handle);
+        assert_eq!(store.blobs().bytes(handle), 0);
+
+        let missing = get(
+            &store,
+            &format!("pandia-data://localhost/doc/{}", DocHandle::new().0),
+            None,
+        );
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            get(&store, "pandia-data://localhost/nope/x", None).status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn serves_search_results_with_decoded_queries() {
+        let store = DocStore::new();
+        let handle = store.insert(
+            Document::from_text(r#"[{"city": "New York"}, {"city": "Oslo"}]"#, None).unwrap(),
+        );
+        let response = get(
+            &store,
+            &format!("pandia-data://localhost/search/{}?q=new%20york", handle.0),
+            None,
+        );
+        assert_eq!(response.status(), StatusCode::OK);
+        let result: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(result["hits"].as_array().unwrap().len(), 1);
+        assert!(response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+
+        let request = Request::builder()
+            .uri(format!("pandia-data://localhost/doc/{}", handle.0))
+            .header(header::ORIGIN, "tauri://localhost")
+            .body(Vec::new())
+            .unwrap();
+        let response = respond(&store, &request);
+        assert_eq!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "tauri://localhost"
+        );
+    }
+}
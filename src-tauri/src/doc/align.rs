@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::positions::PositionIndex;
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlignedRow {
+    pub left: Option<u32>,
+    pub right: Option<u32>,
+    pub anchored: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Anchor {
+    pub pointer: String,
+    pub left_line: u32,
+    pub right_line: u32,
+    pub closing: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alignment {
+    pub rows: Vec<AlignedRow>,
+    pub anchors: Vec<Anchor>,
+    pub left_lines: u32,
+    pub right_lines: u32,
+}
+
+fn spans(text: &str, side: &str) -> DocResult<HashMap<String, (u32, u32)>> {
+    let index = PositionIndex::build(text).map_err(|e| DocError::Parse(format!("{side}: {e}")))?;
+    Ok(index
+        .nodes()
+        .iter()
+        .map(|node| {
+            let start = index.position(node.key_start.unwrap_or(node.start)).line;
+            let end = index.position(node.end.saturating_sub(1)).line;
+            (node.path.to_pointer(), (start, end))
+        })
+        .collect())
+}
+
+fn line_count(text: &str) -> u32 {
+    text.lines().count().max(1) as u32
+}
+
+fn increasing_subsequence(candidates: &[Anchor]) -> Vec<usize> {
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev = vec![usize::MAX; candidates.len()];
+    for (i, anchor) in candidates.iter().enumerate() {
+        let at = tails.partition_point(|&t| candidates[t].right_line < anchor.right_line);
+        if at > 0 {
+            prev[i] = tails[at - 1];
+        }
+        if at == tails.len() {
+            tails.push(i);
+        } else {
+            tails[at] = i;
+        }
+    }
+    let mut out = Vec::with_capacity(tails.len());
+    let mut cursor = tails.last().copied().unwrap_or(usize::MAX);
+    while cursor != usize::MAX {
+        out.push(cursor);
+        cursor = prev[cursor];
+    }
+    out.reverse();
+    out
+}
+
+fn fill_gap(rows: &mut Vec<AlignedRow>, left: (u32, u32), right: (u32, u32)) {
+    let (mut l, mut r) = (left.0, right.0);
+    while l < left.1 || r < right.1 {
+        rows.push(AlignedRow {
+            left: (l < left.1).then_some(l),
+            right: (r < right.1).then_some(r),
+            anchored: false,
+        });
+        l += 1;
+        r += 1;
+    }
+}
+
+pub fn align(left: &str, right: &str) -> DocResult<Alignment> {
+    let left_spans = spans(left, "left")?;
+    let right_spans = spans(right, "right")?;
+    let mut candidates: Vec<Anchor> = Vec::new();
+    for (pointer, &(ls, le)) in &left_spans {
+        let Some(&(rs, re)) = right_spans.get(pointer) else {
+            continue;
+        };
+        candidates.push(Anchor {
+            pointer: pointer.clone(),
+            left_line: ls,
+            right_line: rs,
+            closing: false,
+        });
+        if le != ls && re != rs {
+            candidates.push(Anchor {
+                pointer: pointer.clone(),
+                left_line: le,
+                right_line: re,
+                closing: true,
+            });
+        }
+    }
+    candidates.sort_by(|a, b| {
+        (a.left_line, a.right_line, a.closing, &a.pointer).cmp(&(
+            b.left_line,
+            b.right_line,
+            b.closing,
+            &b.pointer,
+        ))
+    });
+    candidates.dedup_by_key(|a| a.left_line);
+    let anchors: Vec<Anchor> = increasing_subsequence(&candidates)
+        .into_iter()
+        .map(|i| candidates[i].clone())
+        .collect();
+
+    let (left_lines, right_lines) = (line_count(left), line_count(right));
+    let mut rows = Vec::new();
+    let (mut l, mut r) = (1, 1);
+    for anchor in &anchors {
+        fill_gap(&mut rows, (l, anchor.left_line), (r, anchor.right_line));
+        rows.push(AlignedRow {
+            left: Some(anchor.left_line),
+            right: Some(anchor.right_line),
+            anchored: true,
+        });
+        l = anchor.left_line + 1;
+        r = anchor.right_line + 1;
+    }
+    fill_gap(&mut rows, (l, left_lines + 1), (r, right_lines + 1));
+    Ok(Alignment {
+        rows,
+        anchors,
+        left_lines,
+        right_lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_inserted_lines_between_matching_paths() {
+        let left = "{\n  \"a\": 1,\n  \"c\": 3\n}";
+        let right = "{\n  \"a\": 1,\n  \"b\": {\n    \"x\": 2\n  },\n  \"c\": 3\n}";
+        let out = align(left, right).unwrap();
+        let pairs: Vec<(Option<u32>, Option<u32>)> =
+            out.rows.iter().map(|row| (row.left, row.right)).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (Some(1), Some(1)),
+                (Some(2), Some(2)),
+                (None, Some(3)),
+                (None, Some(4)),
+                (None, Some(5)),
+                (Some(3), Some(6)),
+                (Some(4), Some(7)),
+            ]
+        );
+        assert!(out
+            .anchors
+            .iter()
+            .any(|a| a.pointer == "/c" && a.right_line == 6));
+        assert!(out
+            .anchors
+            .iter()
+            .any(|a| a.pointer.is_empty() && a.closing));
+    }
+
+    #[test]
+    fn ignores_anchors_that_cross() {
+        let left = "{\n  \"a\": 1,\n  \"b\": 2\n}";
+        let right = "{\n  \"b\": 2,\n  \"a\": 1\n}";
+        let out = align(left, right).unwrap();
+        let lefts: Vec<u32> = out.anchors.iter().map(|a| a.left_line).collect();
+        let rights: Vec<u32> = out.anchors.iter().map(|a| a.right_line).collect();
+        assert!(lefts.windows(2).all(|w| w[0] < w[1]));
+        assert!(rights.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(out.rows.iter().filter(|r| r.left.is_some()).count(), 4);
+        assert_eq!(out.rows.iter().filter(|r| r.right.is_some()).count(), 4);
+        assert!(align("{", "{}").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+use super::fragment::format_value;
+use super::types::{DocError, DocResult};
+
+const VOWELS: &[u8] = b"aeiou";
+const CONSONANTS: &[u8] = b"bcdfghjklmnprstvwz";
+const HEX: &[u8] = b"0123456789abcdef";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnonymizeResult {
+    pub content: String,
+    pub seed: u64,
+    pub replaced: u32,
+}
+
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n.max(1)
+    }
+
+    fn pick(&mut self, set: &[u8]) -> char {
+        set[self.below(set.len() as u64) as usize] as char
+    }
+
+    fn digit(&mut self, nonzero: bool) -> char {
+        if nonzero {
+            (b'1' + self.below(9) as u8) as char
+        } else {
+            (b'0' + self.below(10) as u8) as char
+        }
+    }
+}
+
+struct Faker {
+    rng: SplitMix,
+    strings: HashMap<String, String>,
+    numbers: HashMap<String, Number>,
+    replaced: u32,
+}
+
+fn is_hex_token(s: &str) -> bool {
+    s.len() >= 8
+        && s.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+        && s.chars().any(|c| c.is_ascii_digit())
+}
+
+fn date_prefix(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() >= 10
+        && b[..4].iter().all(u8::is_ascii_digit)
+        && b[4] == b'-'
+        && b[5..7].iter().all(u8::is_ascii_digit)
+        && b[7] == b'-'
+        && b[8..10].iter().all(u8::is_ascii_digit)
+}
+
+impl Faker {
+    fn new(seed: u64) -> Self {
+        Self {
+            rng: SplitMix(seed),
+            strings: HashMap::new(),
+            numbers: HashMap::new(),
+            replaced: 0,
+        }
+    }
+
+    fn value(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(map) => map.values_mut().for_each(|v| self.value(v)),
+            Value::Array(items) => items.iter_mut().for_each(|v| self.value(v)),
+            Value::String(s) => {
+                *s = self.string(s);
+                self.replaced += 1;
+            }
+            Value::Number(n) => {
+                *n = self.number(n);
+                self.replaced += 1;
+            }
+            Value::Bool(b) => {
+                *b = self.rng.below(2) == 1;
+                self.replaced += 1;
+            }
+            Value::Null => {}
+        }
+    }
+
+    fn string(&mut self, original: &str) -> String {
+        if let Some(known) = self.strings.get(original) {
+            return known.clone();
+        }
+        let fake = if date_prefix(original) {
+            self.timestamp(original)
+        } else if is_hex_token(original) {
+            self.hex(original)
+        } else if let Some((scheme, rest)) = original.split_once("://") {
+            format!("{scheme}://{}", self.text(rest))
+        } else {
+            self.text(original)
+        };
+        self.strings.insert(original.to_string(), fake.clone());
+        fake
+    }
+
+    fn hex(&mut self, original: &str) -> String {
+        original
+            .chars()
+            .map(|c| match c {
+                '-' => '-',
+                c if c.is_ascii_uppercase() => self.rng.pick(HEX).to_ascii_uppercase(),
+                _ => self.rng.pick(HEX),
+            })
+            .collect()
+    }
+
+    fn timestamp(&mut self, original: &str) -> String {
+        let date = format!(
+            "{:04}-{:02}-{:02}",
+            1990 + self.rng.below(40),
+            1 + self.rng.below(12),
+            1 + self.rng.below(28)
+        );
+        let rest = &original[10..];
+        let b = rest.as_bytes();
+        let is_time = b.len() >= 9
+            && matches!(b[0], b'T' | b' ')
+            && b[3] == b':'
+            && b[6] == b':'
+            && [1, 2, 4, 5, 7, 8].iter().all(|&i| b[i].is_ascii_digit());
+        if !is_time {
+            return date + rest;
+        }
+        let time = format!(
+            "{}{:02}:{:02}:{:02}",
+            b[0] as char,
+            self.rng.below(24),
+            self.rng.below(60),
+            self.rng.below(60)
+        );
+        let tail: String = rest[9..]
+            .chars()
+            .map(|c| {
+                if c.is_ascii_digit() {
+                    self.rng.digit(false)
+                } else {
+                    c
+                }
+            })
+            .collect();
+        date + &time + &tail
+    }
+
+    fn text(&mut self, original: &str) -> String {
+        let mut out = String::with_capacity(original.len());
+        let mut vowel_next = self.rng.below(2) == 0;
+        let mut first_digit = true;
+        for c in original.chars() {
+            let fake = if c.is_ascii_alphabetic() {
+                let letter = self.rng.pick(if vowel_next { VOWELS } else { CONSONANTS });
+                vowel_next = !vowel_next;
+                if c.is_ascii_uppercase() {
+                    letter.to_ascii_uppercase()
+                } else {
+                    letter
+                }
+            } else if c.is_ascii_digit() {
+                let digit = self.rng.digit(first_digit && c != '0');
+                first_digit = false;
+                digit
+            } else if c.is_alphanumeric() {
+                self.rng.pick(CONSONANTS)
+            } else {
+                vowel_next = self.rng.below(2) == 0;
+                first_digit = true;
+                c
+            };
+            out.push(fake);
+        }
+        out
+    }
+
+    fn number(&mut self, original: &Number) -> Number {
+        let text = original.to_string();
+        if let Some(known) = self.numbers.get(&text) {
+            return known.clone();
+        }
+        let whole = text.trim_start_matches('-').split(['.', 'e', 'E']).next();
+        let multi_digit = whole.is_some_and(|w| w.len() > 1);
+        let mut out = String::with_capacity(text.len());
+        let (mut first, mut in_exponent) = (true, false);
+        for c in text.chars() {
+            in_exponent |= matches!(c, 'e' | 'E');
+            if c.is_ascii_digit() && !in_exponent {
+                out.push(self.rng.digit(first && multi_digit));
+                first = false;
+            } else {
+                out.push(c);
+            }
+        }
+        let fake: Number = serde_json::from_str(&out).unwrap_or_else(|_| original.clone());
+        self.numbers.insert(text, fake.clone());
+        fake
+    }
+}
+
+pub fn anonymize(content: &str, seed: Option<u64>) -> DocResult<AnonymizeResult> {
+    let mut value: Value =
+        serde_json::from_str(content).map_err(|e| DocError::Parse(e.to_string()))?;
+    let seed = match seed {
+        Some(seed) => seed,
+        None => getrandom::u64().map_err(|e| DocError::Io(std::io::Error::other(e.to_string())))?,
+    };
+    let mut faker = Faker::new(seed);
+    faker.value(&mut value);
+    Ok(AnonymizeResult {
+        content: format_value(&value, 2)?,
+        seed,
+        replaced: faker.replaced,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn shape(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), shape(v))).collect(),
+            Value::Array(items) => items.iter().map(shape).collect(),
+            Value::String(s) => json!(format!("string:{}", s.chars().count())),
+            Value::Number(n) => json!(format!("number:{}", n.to_string().len())),
+            Value::Bool(_) => json!("bool"),
+            Value::Null => Value::Null,
+        }
+    }
+
+    #[test]
+    fn keeps_keys_structure_and_formats() {
+        let input = json!({
+            "id": "3f2b8c1e-9a4d-4e7b-8c21-0d5e6f7a8b9c",
+            "email": "Jane.Doe@corp.example",
+            "createdAt": "2024-03-15T10:42:07.123Z",
+            "site": "https://corp.example/users/42",
+            "age": 37,
+            "score": -0.75,
+            "tags": ["vip", "vip"],
+            "active": true,
+            "manager": null
+        });
+        let text = input.to_string();
+        let out = anonymize(&text, Some(7)).unwrap();
+        let fake: Value = serde_json::from_str(&out.content).unwrap();
+        assert_eq!(shape(&fake), shape(&input));
+        assert_ne!(fake["email"], input["email"]);
+        assert_eq!(out.replaced, 9);
+
+        let email = fake["email"].as_str().unwrap();
+        assert!(email.contains('@') && email.chars().next().unwrap().is_ascii_uppercase());
+        assert!(fake["site"].as_str().unwrap().starts_with("https://"));
+        assert!(fake["id"]
+            .as_str()
+            .unwrap()
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || c == '-'));
+        let created = fake["createdAt"].as_str().unwrap();
+        let month: u32 = created[5..7].parse().unwrap();
+        assert!((1..=12).contains(&month) && created.ends_with('Z'));
+        assert_eq!(fake["tags"][0], fake["tags"][1]);
+        assert!(fake["manager"].is_null());
+    }
+
+    #[test]
+    fn same_seed_is_reproducible() {
+        let text = r#"{"name": "Ada", "n": 1024}"#;
+        let a = anonymize(text, Some(42)).unwrap();
+        let b = anonymize(text, Some(42)).unwrap();
+        let c = anonymize(text, Some(43)).unwrap();
+        assert_eq!(a.content, b.content);
+        assert_ne!(a.content, c.content);
+        assert!(anonymize("{", Some(1)).is_err());
+    }
+}
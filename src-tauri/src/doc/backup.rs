@@ -2,7 +2,7 @@ use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 static RECOVERY_OFFERED: AtomicBool = AtomicBool::new(false);
 
@@ -17,10 +17,7 @@ pub struct BackupRecord {
 }
 
 fn backup_dir(app: &AppHandle) -> std::io::Result<PathBuf> {
-    let base = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    let base = super::portable::data_dir(app)?;
     let dir = base.join("backups");
     std::fs::create_dir_all(&dir)?;
     Ok(dir)
@@ -0,0 +1,199 @@
+use std::fmt::Write as _;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path as FsPath;
+
+use serde::Serialize;
+
+use super::types::DocResult;
+
+const ROW_BYTES: usize = 16;
+const MAX_PREVIEW_BYTES: u64 = 64 * 1024;
+const HEADER_BYTES: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HexRow {
+    pub offset: u64,
+    pub hex: String,
+    pub ascii: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatHint {
+    pub format: &'static str,
+    pub description: &'static str,
+    pub certain: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryPreview {
+    pub size: u64,
+    pub offset: u64,
+    pub length: u64,
+    pub rows: Vec<HexRow>,
+    pub hints: Vec<FormatHint>,
+    pub utf8: bool,
+}
+
+const SIGNATURES: &[(&[u8], &str, &str)] = &[
+    (b"\x1f\x8b", "gzip", "gzip-compressed data"),
+    (b"SQLite format 3\0", "sqlite", "SQLite 3 database"),
+    (b"\x89PNG\r\n\x1a\n", "png", "PNG image"),
+    (b"\xff\xd8\xff", "jpeg", "JPEG image"),
+    (b"GIF87a", "gif", "GIF image"),
+    (b"GIF89a", "gif", "GIF image"),
+    (b"PK\x03\x04", "zip", "ZIP archive (also xlsx, docx, jar)"),
+    (b"%PDF-", "pdf", "PDF document"),
+    (b"\x28\xb5\x2f\xfd", "zstd", "Zstandard-compressed data"),
+    (b"BZh", "bzip2", "bzip2-compressed data"),
+    (b"\xfd7zXZ\0", "xz", "xz-compressed data"),
+    (
+        b"\xef\xbb\xbf",
+        "utf8-bom",
+        "UTF-8 text with a byte order mark",
+    ),
+    (b"\xff\xfe", "utf16le", "UTF-16 little-endian text"),
+    (b"\xfe\xff", "utf16be", "UTF-16 big-endian text"),
+];
+
+pub fn detect_formats(header: &[u8]) -> Vec<FormatHint> {
+    let mut hints: Vec<FormatHint> = SIGNATURES
+        .iter()
+        .filter(|(magic, _, _)| header.starts_with(magic))
+        .map(|&(_, format, description)| FormatHint {
+            format,
+            description,
+            certain: true,
+        })
+        .collect();
+    if header.len() >= 12 && header.starts_with(b"RIFF") && &header[8..12] == b"WEBP" {
+        hints.push(FormatHint {
+            format: "webp",
+            description: "WebP image",
+            certain: true,
+        });
+    }
+    if hints.is_empty() {
+        if let Some(&first) = header.first() {
+            if matches!(first, 0x80..=0x8f | 0x90..=0x9f | 0xdc..=0xdf) {
+                hints.push(FormatHint {
+                    format: "msgpack",
+                    description: "possibly MessagePack (starts with a map or array marker)",
+                    certain: false,
+                });
+            }
+        }
+    }
+    hints
+}
+
+pub fn hex_rows(bytes: &[u8], base: u64) -> Vec<HexRow> {
+    bytes
+        .chunks(ROW_BYTES)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut hex = String::with_capacity(ROW_BYTES * 3);
+            for (j, b) in chunk.iter().enumerate() {
+                if j > 0 {
+                    hex.push(' ');
+                }
+                let _ = write!(hex, "{b:02x}");
+            }
+            let ascii = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            HexRow {
+                offset: base + (i * ROW_BYTES) as u64,
+                hex,
+                ascii,
+            }
+        })
+        .collect()
+}
+
+fn is_utf8_prefix(bytes: &[u8]) -> bool {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+pub fn preview(path: &FsPath, offset: u64, length: u64) -> DocResult<BinaryPreview> {
+    let mut file = std::fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut header = Vec::with_capacity(HEADER_BYTES);
+    file.by_ref()
+        .take(HEADER_BYTES as u64)
+        .read_to_end(&mut header)?;
+
+    let offset = offset.min(size);
+    let length = length.min(MAX_PREVIEW_BYTES).min(size - offset);
+    let mut window = Vec::with_capacity(length as usize);
+    file.seek(SeekFrom::Start(offset))?;
+    file.take(length).read_to_end(&mut window)?;
+
+    Ok(BinaryPreview {
+        size,
+        offset,
+        length: window.len() as u64,
+        rows: hex_rows(&window, offset),
+        hints: detect_formats(&header),
+        utf8: is_utf8_prefix(&header),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn dumps_hex_and_ascii_rows() {
+        let rows = hex_rows(b"{\"a\":1}\x00\x01 tail of row two", 32);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].offset, 32);
+        assert_eq!(
+            rows[0].hex,
+            "7b 22 61 22 3a 31 7d 00 01 20 74 61 69 6c 20 6f"
+        );
+        assert_eq!(rows[0].ascii, "{\"a\":1}.. tail o");
+        assert_eq!((rows[1].offset, rows[1].ascii.as_str()), (48, "f row two"));
+    }
+
+    #[test]
+    fn detects_common_formats() {
+        let formats = |bytes: &[u8]| -> Vec<&'static str> {
+            detect_formats(bytes).iter().map(|h| h.format).collect()
+        };
+        assert_eq!(formats(b"\x1f\x8b\x08\x00"), vec!["gzip"]);
+        assert_eq!(formats(b"SQLite format 3\0\x10\x00"), vec!["sqlite"]);
+        assert_eq!(formats(b"RIFF\x10\x00\x00\x00WEBPVP8 "), vec!["webp"]);
+        assert_eq!(formats(b"\x82\xa1a\x01\xa1b\x02"), vec!["msgpack"]);
+        assert!(!detect_formats(b"\x82\xa1a").first().unwrap().certain);
+        assert!(formats(b"{\"a\": 1}").is_empty());
+    }
+
+    #[test]
+    fn previews_a_window_of_the_file() {
+        let dir = TempDir::new("binary");
+        let path = dir.join("blob.gz");
+        let mut bytes = vec![0x1f, 0x8b, 0x08, 0x00];
+        bytes.extend((0..100u8).map(|b| b.wrapping_mul(7)));
+        std::fs::write(&path, &bytes).unwrap();
+
+        let preview = preview(&path, 96, 1000).unwrap();
+        assert_eq!((preview.size, preview.offset, preview.length), (104, 96, 8));
+        assert_eq!(preview.rows.len(), 1);
+        assert_eq!(preview.hints[0].format, "gzip");
+        assert!(!preview.utf8);
+    }
+}
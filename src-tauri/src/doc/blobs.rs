@@ -0,0 +1,111 @@
+use parking_lot::Mutex;
+use sonic_rs::FastStr;
+
+use super::types::DocHandle;
+
+const MAX_BLOBS: usize = 4;
+
+/// A rendered payload served over the data protocol, keyed by request and
+/// valid while the document stays at `version`.
+#[derive(Clone)]
+pub struct Blob {
+    pub handle: DocHandle,
+    pub key: String,
+    pub version: u64,
+    pub content_type: &'static str,
+    pub data: FastStr,
+    /// The bytes belong to the document itself (a lazy document's source),
+    /// so dropping the blob frees nothing.
+    pub shared: bool,
+}
+
+impl Blob {
+    fn heap_bytes(&self) -> u64 {
+        if self.shared {
+            0
+        } else {
+            self.data.len() as u64
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct BlobCache {
+    blobs: Mutex<Vec<Blob>>,
+}
+
+impl BlobCache {
+    pub fn get(&self, key: &str, version: u64) -> Option<Blob> {
+        self.blobs
+            .lock()
+            .iter()
+            .find(|b| b.key == key && b.version == version)
+            .cloned()
+    }
+
+    pub fn remember(&self, blob: Blob) -> Blob {
+        let mut blobs = self.blobs.lock();
+        blobs.retain(|b| b.key != blob.key);
+        if blobs.len() == MAX_BLOBS {
+            blobs.remove(0);
+        }
+        blobs.push(blob.clone());
+        blob
+    }
+
+    pub fn bytes(&self, handle: DocHandle) -> u64 {
+        self.blobs
+            .lock()
+            .iter()
+            .filter(|b| b.handle == handle)
+            .map(Blob::heap_bytes)
+            .sum()
+    }
+
+    pub fn evict(&self, handle: DocHandle) -> u64 {
+        let mut freed = 0;
+        self.blobs.lock().retain(|b| {
+            let keep = b.handle != handle;
+            if !keep {
+                freed += b.heap_bytes();
+            }
+            keep
+        });
+        freed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blob(handle: DocHandle, key: &str, data: &str, shared: bool) -> Blob {
+        Blob {
+            handle,
+            key: key.into(),
+            version: 1,
+            content_type: "application/json",
+            data: FastStr::new(data),
+            shared,
+        }
+    }
+
+    #[test]
+    fn counts_owned_bytes_and_keeps_the_newest_blobs() {
+        let cache = BlobCache::default();
+        let (a, b) = (DocHandle::new(), DocHandle::new());
+        cache.remember(blob(a, "a1", "1234", false));
+        cache.remember(blob(a, "a2", "12345678", true));
+        cache.remember(blob(b, "b1", "12", false));
+        assert_eq!(cache.bytes(a), 4);
+        assert!(cache.get("a2", 1).is_some());
+        assert!(cache.get("a2", 2).is_none());
+
+        for i in 0..MAX_BLOBS {
+            cache.remember(blob(b, &format!("b{i}"), "x", false));
+        }
+        assert!(cache.get("a1", 1).is_none());
+        assert_eq!(cache.evict(b), MAX_BLOBS as u64);
+        assert_eq!(cache.bytes(b), 0);
+    }
+}
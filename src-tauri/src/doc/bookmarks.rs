@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::timefmt::now_millis;
+use super::types::{DocError, DocResult};
+
+const SIDECAR_KIND: &str = "pandia-annotations";
+const SIDECAR_SUFFIX: &str = ".notes.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bookmark {
+    pub id: String,
+    pub pointer: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    pub pointer: String,
+    pub note: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMarks {
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+impl FileMarks {
+    fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty() && self.annotations.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Sidecar {
+    #[serde(rename = "$kind")]
+    kind: String,
+    file: String,
+    #[serde(flatten)]
+    marks: FileMarks,
+}
+
+type Store = BTreeMap<String, FileMarks>;
+
+pub fn bookmarks_path(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let base = super::portable::data_dir(app)?;
+    Ok(base.join("bookmarks.json"))
+}
+
+pub fn file_key(file: &str) -> String {
+    std::fs::canonicalize(file)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| file.to_string())
+}
+
+pub fn sidecar_path(file: &str) -> PathBuf {
+    PathBuf::from(format!("{file}{SIDECAR_SUFFIX}"))
+}
+
+fn load(path: &FsPath) -> std::io::Result<Store> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Store::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn store(path: &FsPath, all: &Store) -> std::io::Result<()> {
+    let json =
+        serde_json::to_string_pretty(all).map_err(|e| std::io::Error::other(e.to_string()))?;
+    std::fs::write(path, json)
+}
+
+fn update<T>(path: &FsPath, file: &str, f: impl FnOnce(&mut FileMarks) -> T) -> DocResult<T> {
+    let mut all = load(path)?;
+    let key = file_key(file);
+    let marks = all.entry(key.clone()).or_default();
+    let out = f(marks);
+    if marks.is_empty() {
+        all.remove(&key);
+    }
+    store(path, &all)?;
+    Ok(out)
+}
+
+fn check_pointer(pointer: &str) -> DocResult<()> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return Err(DocError::Edit(format!(
+            "bookmarks are addressed by JSON Pointer, got {pointer:?}"
+        )));
+    }
+    Ok(())
+}
+
+pub fn list(path: &FsPath, file: &str) -> std::io::Result<FileMarks> {
+    Ok(load(path)?.remove(&file_key(file)).unwrap_or_default())
+}
+
+pub fn add_bookmark(
+    path: &FsPath,
+    file: &str,
+    pointer: &str,
+    label: Option<String>,
+) -> DocResult<Bookmark> {
+    check_pointer(pointer)?;
+    update(path, file, |marks| {
+        if let Some(existing) = marks.bookmarks.iter_mut().find(|b| b.pointer == pointer) {
+            if label.is_some() {
+                existing.label = label;
+            }
+            return existing.clone();
+        }
+        let bookmark = Bookmark {
+            id: uuid::Uuid::new_v4().to_string(),
+            pointer: pointer.to_string(),
+            label,
+            created_at: now_millis().to_string(),
+        };
+        marks.bookmarks.push(bookmark.clone());
+        bookmark
+    })
+}
+
+pub fn remove_bookmark(path: &FsPath, file: &str, id: &str) -> DocResult<bool> {
+    update(path, file, |marks| {
+        let before = marks.bookmarks.len();
+        marks.bookmarks.retain(|b| b.id != id);
+        marks.bookmarks.len() != before
+    })
+}
+
+pub fn annotate(
+    path: &FsPath,
+    file: &str,
+    pointer: &str,
+    note: &str,
+) -> DocResult<Option<Annotation>> {
+    check_pointer(pointer)?;
+    let note = note.trim();
+    update(path, file, |marks| {
+        if note.is_empty() {
+            marks.annotations.retain(|a| a.pointer != pointer);
+            return None;
+        }
+        let updated_at = now_millis().to_string();
+        let annotation = match marks.annotations.iter_mut().find(|a| a.pointer == pointer) {
+            Some(existing) => {
+                existing.note = note.to_string();
+                existing.updated_at = updated_at;
+                existing.clone()
+            }
+            None => {
+                let a = Annotation {
+                    pointer: pointer.to_string(),
+                    note: note.to_string(),
+                    updated_at,
+                };
+                marks.annotations.push(a.clone());
+                a
+            }
+        };
+        Some(annotation)
+    })
+}
+
+pub fn export_sidecar(path: &FsPath, file: &str, out: Option<&FsPath>) -> DocResult<PathBuf> {
+    let marks = list(path, file)?;
+    let out = out
+        .map(FsPath::to_path_buf)
+        .unwrap_or_else(|| sidecar_path(file));
+    let name = FsPath::new(file)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| file.to_string());
+    let sidecar = Sidecar {
+        kind: SIDECAR_KIND.into(),
+        file: name,
+        marks,
+    };
+    let json =
+        serde_json::to_string_pretty(&sidecar).map_err(|e| DocError::Export(e.to_string()))?;
+    std::fs::write(&out, json)?;
+    Ok(out)
+}
+
+pub fn import_sidecar(path: &FsPath, file: &str, from: Option<&FsPath>) -> DocResult<FileMarks> {
+    let from = from
+        .map(FsPath::to_path_buf)
+        .unwrap_or_else(|| sidecar_path(file));
+    let text = std::fs::read_to_string(&from)?;
+    let sidecar: Sidecar =
+        serde_json::from_str(&text).map_err(|e| DocError::Parse(e.to_string()))?;
+    if sidecar.kind != SIDECAR_KIND {
+        return Err(DocError::Parse(format!(
+            "not an annotations file: $kind is {:?}",
+            sidecar.kind
+        )));
+    }
+    update(path, file, |marks| {
+        for incoming in sidecar.marks.bookmarks {
+            if !marks
+                .bookmarks
+                .iter()
+                .any(|b| b.pointer == incoming.pointer)
+            {
+                marks.bookmarks.push(incoming);
+            }
+        }
+        for incoming in sidecar.marks.annotations {
+            match marks
+                .annotations
+                .iter_mut()
+                .find(|a| a.pointer == incoming.pointer)
+            {
+                Some(existing) => *existing = incoming,
+                None => marks.annotations.push(incoming),
+            }
+        }
+        marks.clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn bookmarks_are_kept_per_file_and_deduplicated() {
+        let dir = TempDir::new("bookmarks-per-file");
+        let db = dir.join("bookmarks.json");
+        let a = dir.join("a.json").to_string_lossy().into_owned();
+        let b = dir.join("b.json").to_string_lossy().into_owned();
+        let first = add_bookmark(&db, &a, "/users/0", None).unwrap();
+        let again = add_bookmark(&db, &a, "/users/0", Some("first user".into())).unwrap();
+        assert_eq!(first.id, again.id);
+        add_bookmark(&db, &b, "/meta", None).unwrap();
+        let marks = list(&db, &a).unwrap();
+        assert_eq!(marks.bookmarks.len(), 1);
+        assert_eq!(marks.bookmarks[0].label.as_deref(), Some("first user"));
+        assert!(remove_bookmark(&db, &a, &first.id).unwrap());
+        assert!(list(&db, &a).unwrap().bookmarks.is_empty());
+        assert_eq!(list(&db, &b).unwrap().bookmarks.len(), 1);
+        assert!(add_bookmark(&db, &a, "users.0", None).is_err());
+    }
+
+    #[test]
+    fn annotations_update_and_clear() {
+        let dir = TempDir::new("bookmarks-annotate");
+        let db = dir.join("bookmarks.json");
+        let file = dir.join("doc.json").to_string_lossy().into_owned();
+        annotate(&db, &file, "/a", "check this").unwrap();
+        let updated = annotate(&db, &file, "/a", "  fixed  ").unwrap().unwrap();
+        assert_eq!(updated.note, "fixed");
+        assert_eq!(list(&db, &file).unwrap().annotations.len(), 1);
+        assert_eq!(annotate(&db, &file, "/a", "").unwrap(), None);
+        assert_eq!(list(&db, &file).unwrap(), FileMarks::default());
+    }
+
+    #[test]
+    fn sidecar_roundtrips_into_another_store() {
+        let dir = TempDir::new("bookmarks-sidecar");
+        let file = dir.join("shared.json").to_string_lossy().into_owned();
+        let ours = dir.join("ours.json");
+        add_bookmark(&ours, &file, "/items/3", Some("bug".into())).unwrap();
+        annotate(&ours, &file, "/items/3/id", "duplicate id").unwrap();
+        let out = export_sidecar(&ours, &file, None).unwrap();
+        assert_eq!(out, sidecar_path(&file));
+
+        let theirs = dir.join("theirs.json");
+        let imported = import_sidecar(&theirs, &file, None).unwrap();
+        assert_eq!(imported, list(&ours, &file).unwrap());
+    }
+}
@@ -0,0 +1,156 @@
+use std::fs::Metadata;
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::Serialize;
+
+use super::types::{DocError, DocResult};
+
+const PROVIDERS: &[(&str, &str)] = &[
+    ("Mobile Documents", "icloud"),
+    ("iCloud Drive", "icloud"),
+    ("iCloudDrive", "icloud"),
+    ("OneDrive", "onedrive"),
+    ("Dropbox", "dropbox"),
+    ("Google Drive", "google-drive"),
+    ("GoogleDrive", "google-drive"),
+    ("Box", "box"),
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudStatus {
+    pub provider: Option<&'static str>,
+    pub placeholder: bool,
+}
+
+pub fn provider_for(path: &FsPath) -> Option<&'static str> {
+    path.components().find_map(|component| {
+        let name = component.as_os_str().to_string_lossy();
+        PROVIDERS.iter().find_map(|&(prefix, provider)| {
+            let matches = name == prefix
+                || name.starts_with(&format!("{prefix}-"))
+                || name.starts_with(&format!("{prefix} ("));
+            matches.then_some(provider)
+        })
+    })
+}
+
+pub fn icloud_stub(path: &FsPath) -> Option<PathBuf> {
+    let name = path.file_name()?.to_string_lossy();
+    Some(path.with_file_name(format!(".{name}.icloud")))
+}
+
+#[cfg(target_os = "macos")]
+fn dataless(meta: &Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    const SF_DATALESS: u32 = 0x4000_0000;
+    meta.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(windows)]
+fn dataless(meta: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const OFFLINE: u32 = 0x1000;
+    const RECALL_ON_OPEN: u32 = 0x4_0000;
+    const RECALL_ON_DATA_ACCESS: u32 = 0x40_0000;
+    meta.file_attributes() & (OFFLINE | RECALL_ON_OPEN | RECALL_ON_DATA_ACCESS) != 0
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn dataless(_meta: &Metadata) -> bool {
+    false
+}
+
+pub fn status(path: &FsPath, meta: Option<&Metadata>) -> CloudStatus {
+    let placeholder = match meta {
+        Some(meta) => dataless(meta),
+        None => icloud_stub(path).is_some_and(|stub| stub.exists()),
+    };
+    CloudStatus {
+        provider: provider_for(path),
+        placeholder,
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn materialize(path: &FsPath) -> DocResult<bool> {
+    let status = std::process::Command::new("brctl")
+        .arg("download")
+        .arg(path)
+        .status()?;
+    Ok(status.success())
+}
+
+#[cfg(windows)]
+pub fn materialize(path: &FsPath) -> DocResult<bool> {
+    let path = path.to_path_buf();
+    std::thread::spawn(move || {
+        if let Ok(mut file) = std::fs::File::open(&path) {
+            let _ = std::io::copy(&mut file, &mut std::io::sink());
+        }
+    });
+    Ok(true)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn materialize(_path: &FsPath) -> DocResult<bool> {
+    Ok(false)
+}
+
+pub fn ensure_local(path: &FsPath) -> DocResult<()> {
+    let meta = std::fs::metadata(path).ok();
+    let cloud = status(path, meta.as_ref());
+    if !cloud.placeholder {
+        return Ok(());
+    }
+    let requested = materialize(path).unwrap_or(false);
+    let provider = cloud.provider.unwrap_or("cloud storage");
+    Err(DocError::NotDownloaded(if requested {
+        format!(
+            "{} is still downloading from {provider}; try again when it finishes",
+            path.display()
+        )
+    } else {
+        format!(
+            "{} is not downloaded from {provider}; make it available offline and try again",
+            path.display()
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn recognizes_sync_folders() {
+        let at = |p: &str| provider_for(FsPath::new(p));
+        assert_eq!(
+            at("/Users/a/Library/Mobile Documents/com~apple~CloudDocs/x.json"),
+            Some("icloud")
+        );
+        assert_eq!(
+            at("/Users/a/Library/CloudStorage/OneDrive-Contoso/x.json"),
+            Some("onedrive")
+        );
+        assert_eq!(at("/home/a/Dropbox (Team)/x.json"), Some("dropbox"));
+        assert_eq!(at("/home/a/Boxes/x.json"), None);
+    }
+
+    #[test]
+    fn reports_icloud_stubs_as_not_downloaded() {
+        let dir = TempDir::new("cloud");
+        let file = dir.join("data.json");
+        let stub = icloud_stub(&file).unwrap();
+        assert_eq!(stub.file_name().unwrap(), ".data.json.icloud");
+        std::fs::write(&stub, b"bplist00").unwrap();
+        assert!(status(&file, None).placeholder);
+        assert!(matches!(
+            ensure_local(&file),
+            Err(DocError::NotDownloaded(_))
+        ));
+        std::fs::write(&file, "{}").unwrap();
+        assert!(ensure_local(&file).is_ok());
+    }
+}
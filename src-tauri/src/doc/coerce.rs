@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+use super::fragment::format_value;
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NormalizeRules {
+    pub trim: bool,
+    pub empty_to_null: bool,
+    pub booleans: bool,
+    pub numbers: bool,
+    pub dry_run: bool,
+}
+
+impl Default for NormalizeRules {
+    fn default() -> Self {
+        Self {
+            trim: true,
+            empty_to_null: true,
+            booleans: true,
+            numbers: true,
+            dry_run: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Coercion {
+    Trim,
+    EmptyToNull,
+    Boolean,
+    Number,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeChange {
+    pub pointer: String,
+    pub applied: Vec<Coercion>,
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizeResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub changes: Vec<TypeChange>,
+    pub trimmed: u32,
+    pub nulled: u32,
+    pub booleans: u32,
+    pub numbers: u32,
+}
+
+fn parse_number(text: &str) -> Option<Number> {
+    if text.trim() != text {
+        return None;
+    }
+    serde_json::from_str(text).ok()
+}
+
+fn coerce(text: &str, rules: &NormalizeRules) -> Option<(Value, Vec<Coercion>)> {
+    let mut applied = Vec::new();
+    let mut text = text;
+    if rules.trim && text.trim() != text {
+        text = text.trim();
+        applied.push(Coercion::Trim);
+    }
+    let coerced = if rules.empty_to_null && text.is_empty() {
+        applied.push(Coercion::EmptyToNull);
+        Value::Null
+    } else if rules.booleans
+        && (text.eq_ignore_ascii_case("true") || text.eq_ignore_ascii_case("false"))
+    {
+        applied.push(Coercion::Boolean);
+        Value::Bool(text.eq_ignore_ascii_case("true"))
+    } else if let Some(n) = rules.numbers.then_some(text).and_then(parse_number) {
+        applied.push(Coercion::Number);
+        Value::Number(n)
+    } else {
+        Value::String(text.to_string())
+    };
+    (!applied.is_empty()).then_some((coerced, applied))
+}
+
+fn walk(value: &mut Value, path: &mut Path, rules: &NormalizeRules, out: &mut Vec<TypeChange>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                path.push(PathSegment::Key(key.clone()));
+                walk(child, path, rules, out);
+                path.0.pop();
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter_mut().enumerate() {
+                path.push(PathSegment::Index(i as u32));
+                walk(child, path, rules, out);
+                path.0.pop();
+            }
+        }
+        Value::String(s) => {
+            if let Some((after, applied)) = coerce(s, rules) {
+                let before = std::mem::replace(value, after.clone());
+                out.push(TypeChange {
+                    pointer: path.to_pointer(),
+                    applied,
+                    before,
+                    after,
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn normalize(content: &str, rules: &NormalizeRules) -> DocResult<NormalizeResult> {
+    let mut value: Value =
+        serde_json::from_str(content).map_err(|e| DocError::Parse(e.to_string()))?;
+    let mut changes = Vec::new();
+    walk(&mut value, &mut Path::root(), rules, &mut changes);
+    let count =
+        |rule: Coercion| changes.iter().filter(|c| c.applied.contains(&rule)).count() as u32;
+    Ok(NormalizeResult {
+        content: if rules.dry_run {
+            None
+        } else {
+            Some(format_value(&value, 2)?)
+        },
+        trimmed: count(Coercion::Trim),
+        nulled: count(Coercion::EmptyToNull),
+        booleans: count(Coercion::Boolean),
+        numbers: count(Coercion::Number),
+        changes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SHEET: &str = r#"[
+        {"id": "42", "zip": "02134", "active": "TRUE",
+         "note": "  hi ", "blank": " ", "ratio": "1.5"},
+        {"id": 7, "code": "+1", "flag": "yes", "tags": [" a", "false"]}
+    ]"#;
+
+    #[test]
+    fn coerces_spreadsheet_strings_and_reports_paths() {
+        let out = normalize(SHEET, &NormalizeRules::default()).unwrap();
+        let value: Value = serde_json::from_str(out.content.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            value[0],
+            json!({
+                "id": 42, "zip": "02134", "active": true,
+                "note": "hi", "blank": null, "ratio": 1.5
+            })
+        );
+        assert_eq!(
+            value[1],
+            json!({"id": 7, "code": "+1", "flag": "yes", "tags": ["a", false]})
+        );
+        let pointers: Vec<&str> = out.changes.iter().map(|c| c.pointer.as_str()).collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "/0/id",
+                "/0/active",
+                "/0/note",
+                "/0/blank",
+                "/0/ratio",
+                "/1/tags/0",
+                "/1/tags/1"
+            ]
+        );
+        assert_eq!(
+            out.changes[3].applied,
+            vec![Coercion::Trim, Coercion::EmptyToNull]
+        );
+        assert_eq!(out.changes[0].before, json!("42"));
+        assert_eq!(
+            (out.trimmed, out.nulled, out.booleans, out.numbers),
+            (3, 1, 2, 2)
+        );
+    }
+
+    #[test]
+    fn dry_run_and_disabled_rules() {
+        let rules = NormalizeRules {
+            numbers: false,
+            empty_to_null: false,
+            dry_run: true,
+            ..NormalizeRules::default()
+        };
+        let out = normalize(SHEET, &rules).unwrap();
+        assert!(out.content.is_none());
+        assert_eq!(out.numbers, 0);
+        let blank = out
+            .changes
+            .iter()
+            .find(|c| c.pointer == "/0/blank")
+            .unwrap();
+        assert_eq!(blank.after, json!(""));
+        assert!(normalize("[", &rules).is_err());
+    }
+}
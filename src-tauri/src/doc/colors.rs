@@ -0,0 +1,332 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+pub const MAX_COLORS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSpace {
+    Hex,
+    Rgb,
+    Hsl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorMatch {
+    pub path: Path,
+    pub pointer: String,
+    pub value: String,
+    pub space: ColorSpace,
+    pub hex: String,
+    pub alpha: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorReport {
+    pub colors: Vec<ColorMatch>,
+    pub truncated: bool,
+}
+
+pub fn detect(content: &str) -> DocResult<ColorReport> {
+    let value: Value = serde_json::from_str(content).map_err(|e| DocError::Parse(e.to_string()))?;
+    let mut report = ColorReport::default();
+    let mut path = Path::root();
+    walk(&value, &mut path, &mut report);
+    Ok(report)
+}
+
+fn walk(value: &Value, path: &mut Path, report: &mut ColorReport) {
+    if report.truncated {
+        return;
+    }
+    match value {
+        Value::String(s) => {
+            if let Some((space, color)) = parse(s) {
+                if report.colors.len() == MAX_COLORS {
+                    report.truncated = true;
+                    return;
+                }
+                report.colors.push(ColorMatch {
+                    pointer: path.to_pointer(),
+                    path: path.clone(),
+                    value: s.clone(),
+                    space,
+                    hex: format_color(color, ColorSpace::Hex),
+                    alpha: color.a,
+                });
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                path.push(PathSegment::Index(i as u32));
+                walk(item, path, report);
+                path.0.pop();
+            }
+        }
+        Value::Object(map) => {
+            for (k, v) in map {
+                path.push(PathSegment::Key(k.clone()));
+                walk(v, path, report);
+                path.0.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn convert(value: &str, target: ColorSpace) -> DocResult<String> {
+    let (_, color) =
+        parse(value).ok_or_else(|| DocError::Parse(format!("not a color: {}", value.trim())))?;
+    Ok(format_color(color, target))
+}
+
+pub fn parse(s: &str) -> Option<(ColorSpace, Rgba)> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix('#') {
+        return parse_hex(hex).map(|c| (ColorSpace::Hex, c));
+    }
+    let open = s.find('(')?;
+    let name = s[..open].trim().to_ascii_lowercase();
+    let args = s[open + 1..].strip_suffix(')')?;
+    let (space, color) = match name.as_str() {
+        "rgb" | "rgba" => (ColorSpace::Rgb, parse_rgb(args)?),
+        "hsl" | "hsla" => (ColorSpace::Hsl, parse_hsl(args)?),
+        _ => return None,
+    };
+    Some((space, color))
+}
+
+fn parse_hex(hex: &str) -> Option<Rgba> {
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits: Vec<u8> = match hex.len() {
+        3 | 4 => hex
+            .chars()
+            .map(|c| u8::from_str_radix(&format!("{c}{c}"), 16).ok())
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    Some(Rgba {
+        r: digits[0],
+        g: digits[1],
+        b: digits[2],
+        a: digits.get(3).map_or(1.0, |a| *a as f64 / 255.0),
+    })
+}
+
+fn split_args(args: &str) -> Option<(Vec<&str>, Option<&str>)> {
+    let (channels, alpha) = match args.split_once('/') {
+        Some((c, a)) => (c, Some(a.trim())),
+        None => (args, None),
+    };
+    let mut parts: Vec<&str> = if channels.contains(',') {
+        channels.split(',').map(str::trim).collect()
+    } else {
+        channels.split_whitespace().collect()
+    };
+    let alpha = match (alpha, parts.len()) {
+        (Some(a), 3) => Some(a),
+        (None, 4) => parts.pop(),
+        (None, 3) => None,
+        _ => return None,
+    };
+    Some((parts, alpha))
+}
+
+fn parse_alpha(s: Option<&str>) -> Option<f64> {
+    let Some(s) = s else { return Some(1.0) };
+    let a = match s.strip_suffix('%') {
+        Some(p) => p.trim().parse::<f64>().ok()? / 100.0,
+        None => s.parse::<f64>().ok()?,
+    };
+    a.is_finite().then(|| a.clamp(0.0, 1.0))
+}
+
+fn parse_rgb(args: &str) -> Option<Rgba> {
+    let (parts, alpha) = split_args(args)?;
+    let mut channels = [0u8; 3];
+    for (slot, part) in channels.iter_mut().zip(&parts) {
+        let v = match part.strip_suffix('%') {
+            Some(p) => p.trim().parse::<f64>().ok()? * 2.55,
+            None => part.parse::<f64>().ok()?,
+        };
+        if !v.is_finite() {
+            return None;
+        }
+        *slot = v.round().clamp(0.0, 255.0) as u8;
+    }
+    Some(Rgba {
+        r: channels[0],
+        g: channels[1],
+        b: channels[2],
+        a: parse_alpha(alpha)?,
+    })
+}
+
+fn parse_hsl(args: &str) -> Option<Rgba> {
+    let (parts, alpha) = split_args(args)?;
+    let hue = parts[0].strip_suffix("deg").unwrap_or(parts[0]);
+    let h = hue.trim().parse::<f64>().ok()?;
+    let s = parts[1].strip_suffix('%')?.trim().parse::<f64>().ok()?;
+    let l = parts[2].strip_suffix('%')?.trim().parse::<f64>().ok()?;
+    if ![h, s, l].iter().all(|v| v.is_finite()) {
+        return None;
+    }
+    let (r, g, b) = hsl_to_rgb(
+        h.rem_euclid(360.0),
+        s.clamp(0.0, 100.0) / 100.0,
+        l.clamp(0.0, 100.0) / 100.0,
+    );
+    Some(Rgba {
+        r,
+        g,
+        b,
+        a: parse_alpha(alpha)?,
+    })
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+    let (r, g, b) = match (h / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let to_byte = |v: f64| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (to_byte(r), to_byte(g), to_byte(b))
+}
+
+fn rgb_to_hsl(color: Rgba) -> (f64, f64, f64) {
+    let r = color.r as f64 / 255.0;
+    let g = color.g as f64 / 255.0;
+    let b = color.b as f64 / 255.0;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+    if d == 0.0 {
+        return (0.0, 0.0, l * 100.0);
+    }
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    (h, s * 100.0, l * 100.0)
+}
+
+fn trim_number(v: f64, places: i32) -> String {
+    let scale = 10f64.powi(places);
+    let rounded = (v * scale).round() / scale;
+    let s = format!("{rounded:.*}", places as usize);
+    let s = if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &s
+    };
+    if s == "-0" {
+        "0".into()
+    } else {
+        s.into()
+    }
+}
+
+pub fn format_color(color: Rgba, space: ColorSpace) -> String {
+    let opaque = color.a >= 1.0;
+    match space {
+        ColorSpace::Hex if opaque => format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b),
+        ColorSpace::Hex => format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.r,
+            color.g,
+            color.b,
+            (color.a * 255.0).round() as u8
+        ),
+        ColorSpace::Rgb if opaque => format!("rgb({}, {}, {})", color.r, color.g, color.b),
+        ColorSpace::Rgb => format!(
+            "rgba({}, {}, {}, {})",
+            color.r,
+            color.g,
+            color.b,
+            trim_number(color.a, 3)
+        ),
+        ColorSpace::Hsl => {
+            let (h, s, l) = rgb_to_hsl(color);
+            let (h, s, l) = (trim_number(h, 0), trim_number(s, 1), trim_number(l, 1));
+            if opaque {
+                format!("hsl({h}, {s}%, {l}%)")
+            } else {
+                format!("hsla({h}, {s}%, {l}%, {})", trim_number(color.a, 3))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_colors_with_paths() {
+        let text = r##"{"theme":{"primary":"#1E90FF","bg":"rgb(0 0 0 / 50%)","muted":["hsl(120, 100%, 25%)","#12"]},"name":"red"}"##;
+        let report = detect(text).unwrap();
+        let found: Vec<(&str, ColorSpace, &str)> = report
+            .colors
+            .iter()
+            .map(|c| (c.pointer.as_str(), c.space, c.hex.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("/theme/primary", ColorSpace::Hex, "#1e90ff"),
+                ("/theme/bg", ColorSpace::Rgb, "#00000080"),
+                ("/theme/muted/0", ColorSpace::Hsl, "#008000"),
+            ]
+        );
+        assert_eq!(report.colors[1].alpha, 0.5);
+        assert!(detect("{").is_err());
+    }
+
+    #[test]
+    fn converts_between_spaces() {
+        assert_eq!(convert("#f00", ColorSpace::Rgb).unwrap(), "rgb(255, 0, 0)");
+        assert_eq!(
+            convert("#ff000080", ColorSpace::Rgb).unwrap(),
+            "rgba(255, 0, 0, 0.502)"
+        );
+        assert_eq!(
+            convert("rgb(30, 144, 255)", ColorSpace::Hsl).unwrap(),
+            "hsl(210, 100%, 55.9%)"
+        );
+        assert_eq!(
+            convert("hsla(210deg, 100%, 50%, 0.25)", ColorSpace::Hex).unwrap(),
+            "#0080ff40"
+        );
+        assert!(convert("blue", ColorSpace::Hex).is_err());
+    }
+}
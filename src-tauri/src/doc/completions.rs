@@ -0,0 +1,673 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::positions::{Cursor, LineIndex, TextPosition};
+use super::types::{DocResult, Path, PathSegment};
+
+const MAX_REF_DEPTH: usize = 32;
+const MAX_ITEMS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionKind {
+    Property,
+    Value,
+    Closer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionSource {
+    Schema,
+    Siblings,
+    Syntax,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub insert_text: String,
+    pub source: CompletionSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Completions {
+    pub pointer: String,
+    pub replace_start: TextPosition,
+    pub replace_end: TextPosition,
+    pub items: Vec<CompletionItem>,
+}
+
+pub fn schemas_at<'a>(root: &'a Value, path: &[PathSegment]) -> Vec<&'a Map<String, Value>> {
+    let mut current = Vec::new();
+    expand(root, root, &mut current, 0);
+    for seg in path {
+        let mut next = Vec::new();
+        for s in &current {
+            let child = match seg {
+                PathSegment::Key(k) => s
+                    .get("properties")
+                    .and_then(|p| p.get(k))
+                    .or_else(|| s.get("additionalProperties").filter(|v| v.is_object())),
+                PathSegment::Index(i) => s
+                    .get("prefixItems")
+                    .and_then(|p| p.get(*i as usize))
+                    .or_else(|| match s.get("items") {
+                        Some(Value::Array(items)) => items.get(*i as usize),
+                        Some(v @ Value::Object(_)) => Some(v),
+                        _ => None,
+                    })
+                    .or_else(|| s.get("additionalItems").filter(|v| v.is_object())),
+            };
+            if let Some(child) = child {
+                expand(root, child, &mut next, 0);
+            }
+        }
+        current = next;
+    }
+    current
+}
+
+pub fn expand<'a>(
+    root: &'a Value,
+    schema: &'a Value,
+    out: &mut Vec<&'a Map<String, Value>>,
+    depth: usize,
+) {
+    let Value::Object(s) = schema else {
+        return;
+    };
+    if depth > MAX_REF_DEPTH {
+        return;
+    }
+    if let Some(target) = s
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix('#'))
+        .and_then(|ptr| root.pointer(ptr))
+    {
+        expand(root, target, out, depth + 1);
+    }
+    out.push(s);
+    for key in ["allOf", "anyOf", "oneOf"] {
+        if let Some(Value::Array(parts)) = s.get(key) {
+            for part in parts {
+                expand(root, part, out, depth + 1);
+            }
+        }
+    }
+}
+
+pub fn type_label(schemas: &[&Map<String, Value>]) -> Option<String> {
+    schemas.iter().find_map(|s| match s.get("type") {
+        Some(Value::String(t)) => Some(t.clone()),
+        Some(Value::Array(types)) => Some(
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(" | "),
+        ),
+        _ => None,
+    })
+}
+
+fn description(schemas: &[&Map<String, Value>]) -> Option<String> {
+    schemas
+        .iter()
+        .find_map(|s| s.get("description").and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Key,
+    Colon,
+    Value,
+    Comma,
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    id: usize,
+    expect: Expect,
+    key: Option<String>,
+    index: u32,
+    members: u32,
+}
+
+struct Frame {
+    object: bool,
+    path: Vec<PathSegment>,
+    keys: Vec<String>,
+    closed: bool,
+}
+
+enum Context {
+    Key,
+    Value(Vec<PathSegment>),
+    Other,
+}
+
+struct Snapshot {
+    stack: Vec<State>,
+    context: Context,
+    prefix: String,
+    start: usize,
+    end: usize,
+}
+
+struct Scan<'a> {
+    text: &'a str,
+    pos: usize,
+    cursor: usize,
+    frames: Vec<Frame>,
+    stack: Vec<State>,
+    root_done: bool,
+    snapshot: Option<Snapshot>,
+}
+
+impl Scan<'_> {
+    fn bytes(&self) -> &[u8] {
+        self.text.as_bytes()
+    }
+
+    fn run(&mut self) {
+        loop {
+            while self
+                .bytes()
+                .get(self.pos)
+                .is_some_and(|b| b.is_ascii_whitespace())
+            {
+                self.pos += 1;
+            }
+            let start = self.pos;
+            if self.snapshot.is_none() && start >= self.cursor {
+                self.snap(self.cursor, self.cursor);
+            }
+            let Some(&b) = self.bytes().get(start) else {
+                break;
+            };
+            match b {
+                b'{' | b'[' => {
+                    self.open(b == b'{');
+                    self.pos += 1;
+                }
+                b'}' | b']' => {
+                    if let Some(top) = self.stack.pop() {
+                        self.frames[top.id].closed = true;
+                        self.value_done();
+                    }
+                    self.pos += 1;
+                }
+                b':' => {
+                    if let Some(top) = self.stack.last_mut() {
+                        if top.expect == Expect::Colon {
+                            top.expect = Expect::Value;
+                        }
+                    }
+                    self.pos += 1;
+                }
+                b',' => {
+                    if let Some(top) = self.stack.last_mut() {
+                        if self.frames[top.id].object {
+                            top.expect = Expect::Key;
+                            top.key = None;
+                        } else {
+                            top.expect = Expect::Value;
+                            top.index += 1;
+                        }
+                    }
+                    self.pos += 1;
+                }
+                b'"' => {
+                    let (end, terminated) = self.string_end(start);
+                    let editing = start < self.cursor
+                        && (self.cursor < end || (!terminated && self.cursor <= end));
+                    if editing && self.snapshot.is_none() {
+                        self.snap(start, end);
+                    }
+                    let is_key = self
+                        .stack
+                        .last()
+                        .is_some_and(|top| self.frames[top.id].object && top.expect == Expect::Key);
+                    if is_key {
+                        let key: String =
+                            serde_json::from_str(&self.text[start..end]).unwrap_or_default();
+                        if let Some(top) = self.stack.last_mut() {
+                            if !editing {
+                                self.frames[top.id].keys.push(key.clone());
+                            }
+                            top.key = Some(key);
+                            top.expect = Expect::Colon;
+                        }
+                    } else {
+                        self.value_done();
+                    }
+                    self.pos = end;
+                }
+                _ => {
+                    let end = start
+                        + self.bytes()[start..]
+                            .iter()
+                            .position(|b| b" \t\r\n,:[]{}\"".contains(b))
+                            .unwrap_or(self.text.len() - start);
+                    if start < self.cursor && self.cursor <= end && self.snapshot.is_none() {
+                        self.snap(start, end);
+                    }
+                    self.value_done();
+                    self.pos = end;
+                }
+            }
+        }
+    }
+
+    fn string_end(&self, start: usize) -> (usize, bool) {
+        let mut escaped = false;
+        for (i, &b) in self.bytes()[start + 1..].iter().enumerate() {
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                return (start + i + 2, true);
+            } else if b == b'\n' {
+                return (start + i + 1, false);
+            }
+        }
+        (self.text.len(), false)
+    }
+
+    fn open(&mut self, object: bool) {
+        let path = match self.stack.last() {
+            None => Vec::new(),
+            Some(parent) => {
+                let mut path = self.frames[parent.id].path.clone();
+                path.push(if self.frames[parent.id].object {
+                    PathSegment::Key(parent.key.clone().unwrap_or_default())
+                } else {
+                    PathSegment::Index(parent.index)
+                });
+                path
+            }
+        };
+        self.stack.push(State {
+            id: self.frames.len(),
+            expect: if object { Expect::Key } else { Expect::Value },
+            key: None,
+            index: 0,
+            members: 0,
+        });
+        self.frames.push(Frame {
+            object,
+            path,
+            keys: Vec::new(),
+            closed: false,
+        });
+    }
+
+    fn value_done(&mut self) {
+        match self.stack.last_mut() {
+            Some(top) => {
+                top.expect = Expect::Comma;
+                top.members += 1;
+            }
+            None => self.root_done = true,
+        }
+    }
+
+    fn snap(&mut self, start: usize, end: usize) {
+        let context = match self.stack.last() {
+            None if !self.root_done => Context::Value(Vec::new()),
+            None => Context::Other,
+            Some(top) => {
+                let frame = &self.frames[top.id];
+                let mut path = frame.path.clone();
+                match (frame.object, top.expect) {
+                    (true, Expect::Key) => Context::Key,
+                    (true, Expect::Value) => {
+                        path.push(PathSegment::Key(top.key.clone().unwrap_or_default()));
+                        Context::Value(path)
+                    }
+                    (false, Expect::Value) => {
+                        path.push(PathSegment::Index(top.index));
+                        Context::Value(path)
+                    }
+                    _ => Context::Other,
+                }
+            }
+        };
+        self.snapshot = Some(Snapshot {
+            stack: self.stack.clone(),
+            context,
+            prefix: self.text[start..self.cursor].to_string(),
+            start,
+            end,
+        });
+    }
+}
+
+fn pattern(path: &[PathSegment]) -> Vec<Option<&str>> {
+    path.iter()
+        .map(|seg| match seg {
+            PathSegment::Key(k) => Some(k.as_str()),
+            PathSegment::Index(_) => None,
+        })
+        .collect()
+}
+
+fn key_items(scan: &Scan, id: usize, schema: Option<&Value>, items: &mut Vec<CompletionItem>) {
+    let frame = &scan.frames[id];
+    let mut taken: Vec<&str> = frame.keys.iter().map(String::as_str).collect();
+    if let Some(root) = schema {
+        let mut found = Vec::new();
+        for s in schemas_at(root, &frame.path) {
+            let required: Vec<&str> = s
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|r| r.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let Some(props) = s.get("properties").and_then(Value::as_object) else {
+                continue;
+            };
+            for (name, prop) in props {
+                if taken.contains(&name.as_str()) {
+                    continue;
+                }
+                taken.push(name);
+                let mut prop_schemas = Vec::new();
+                expand(root, prop, &mut prop_schemas, 0);
+                let ty = type_label(&prop_schemas);
+                let placeholder = match ty.as_deref() {
+                    Some("object") => "{}",
+                    Some("array") => "[]",
+                    Some("string") => "\"\"",
+                    _ => "",
+                };
+                let is_required = required.contains(&name.as_str());
+                found.push((
+                    !is_required,
+                    CompletionItem {
+                        label: name.clone(),
+                        kind: CompletionKind::Property,
+                        insert_text: format!("{}: {placeholder}", Value::from(name.as_str())),
+                        source: CompletionSource::Schema,
+                        detail: match (ty, is_required) {
+                            (Some(ty), true) => Some(format!("{ty} (required)")),
+                            (None, true) => Some("required".into()),
+                            (ty, false) => ty,
+                        },
+                        documentation: description(&prop_schemas),
+                    },
+                ));
+            }
+        }
+        found.sort_by_key(|(optional, _)| *optional);
+        items.extend(found.into_iter().map(|(_, item)| item));
+    }
+
+    if !matches!(frame.path.last(), Some(PathSegment::Index(_))) {
+        return;
+    }
+    let wanted = pattern(&frame.path);
+    let mut siblings = 0usize;
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (other_id, other) in scan.frames.iter().enumerate() {
+        if other_id == id || !other.object || pattern(&other.path) != wanted {
+            continue;
+        }
+        siblings += 1;
+        let mut seen: Vec<&str> = Vec::new();
+        for key in &other.keys {
+            if seen.contains(&key.as_str()) {
+                continue;
+            }
+            seen.push(key);
+            let next = counts.len();
+            counts.entry(key).or_insert((0, next)).0 += 1;
+        }
+    }
+    let mut ranked: Vec<(&str, (usize, usize))> = counts
+        .into_iter()
+        .filter(|(key, _)| !taken.contains(key))
+        .collect();
+    ranked.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then(a.1 .1.cmp(&b.1 .1)));
+    items.extend(ranked.into_iter().map(|(key, (count, _))| CompletionItem {
+        label: key.to_string(),
+        kind: CompletionKind::Property,
+        insert_text: format!("{}: ", Value::from(key)),
+        source: CompletionSource::Siblings,
+        detail: Some(format!("in {count} of {siblings} sibling objects")),
+        documentation: None,
+    }));
+}
+
+fn value_items(root: &Value, path: &[PathSegment], items: &mut Vec<CompletionItem>) {
+    let schemas = schemas_at(root, path);
+    let documentation = description(&schemas);
+    let mut push = |value: String, detail: Option<&str>| {
+        if items.iter().any(|i| i.insert_text == value) {
+            return;
+        }
+        items.push(CompletionItem {
+            label: value.clone(),
+            kind: CompletionKind::Value,
+            insert_text: value,
+            source: CompletionSource::Schema,
+            detail: detail.map(str::to_string),
+            documentation: documentation.clone(),
+        });
+    };
+    for s in &schemas {
+        if let Some(Value::Array(options)) = s.get("enum") {
+            for option in options {
+                push(option.to_string(), Some("enum"));
+            }
+        }
+        if let Some(value) = s.get("const") {
+            push(value.to_string(), Some("const"));
+        }
+        if let Some(value) = s.get("default") {
+            push(value.to_string(), Some("default"));
+        }
+    }
+    for s in &schemas {
+        let types: Vec<&str> = match s.get("type") {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        for ty in types {
+            match ty {
+                "boolean" => {
+                    push("true".into(), None);
+                    push("false".into(), None);
+                }
+                "null" => push("null".into(), None),
+                "object" => push("{}".into(), None),
+                "array" => push("[]".into(), None),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn closer_items(scan: &Scan, stack: &[State], items: &mut Vec<CompletionItem>) {
+    let Some(top) = stack.last() else {
+        return;
+    };
+    let frame = &scan.frames[top.id];
+    let empty = top.members == 0
+        && if frame.object {
+            top.expect == Expect::Key
+        } else {
+            top.expect == Expect::Value && top.index == 0
+        };
+    if top.expect != Expect::Comma && !empty {
+        return;
+    }
+    let closers: String = stack
+        .iter()
+        .rev()
+        .map(|s| &scan.frames[s.id])
+        .take_while(|f| !f.closed)
+        .map(|f| if f.object { '}' } else { ']' })
+        .collect();
+    let mut chars = closers.chars();
+    let Some(first) = chars.next() else {
+        return;
+    };
+    items.push(CompletionItem {
+        label: first.to_string(),
+        kind: CompletionKind::Closer,
+        insert_text: first.to_string(),
+        source: CompletionSource::Syntax,
+        detail: Some(format!(
+            "close {}",
+            if frame.object { "object" } else { "array" }
+        )),
+        documentation: None,
+    });
+    if closers.len() > 1 {
+        items.push(CompletionItem {
+            label: closers.clone(),
+            kind: CompletionKind::Closer,
+            insert_text: closers,
+            source: CompletionSource::Syntax,
+            detail: Some("close all open containers".into()),
+            documentation: None,
+        });
+    }
+}
+
+pub fn complete(text: &str, cursor: Cursor, schema: Option<&Value>) -> DocResult<Completions> {
+    let lines = LineIndex::new(text);
+    let offset = lines.offset(cursor.line, cursor.column)?;
+    let mut scan = Scan {
+        text,
+        pos: 0,
+        cursor: offset,
+        frames: Vec::new(),
+        stack: Vec::new(),
+        root_done: false,
+        snapshot: None,
+    };
+    scan.run();
+    let Some(snapshot) = scan.snapshot.take() else {
+        return Ok(Completions {
+            pointer: String::new(),
+            replace_start: lines.position(offset),
+            replace_end: lines.position(offset),
+            items: Vec::new(),
+        });
+    };
+
+    let mut items = Vec::new();
+    let path = match &snapshot.context {
+        Context::Key => {
+            let id = snapshot.stack.last().map_or(0, |s| s.id);
+            key_items(&scan, id, schema, &mut items);
+            let prefix = snapshot.prefix.trim_start_matches('"').to_lowercase();
+            items.retain(|i| i.label.to_lowercase().starts_with(&prefix));
+            scan.frames[id].path.clone()
+        }
+        Context::Value(path) => {
+            if let Some(root) = schema {
+                value_items(root, path, &mut items);
+            }
+            let prefix = snapshot.prefix.to_lowercase();
+            items.retain(|i| i.insert_text.to_lowercase().starts_with(&prefix));
+            path.clone()
+        }
+        Context::Other => snapshot
+            .stack
+            .last()
+            .map(|s| scan.frames[s.id].path.clone())
+            .unwrap_or_default(),
+    };
+    if snapshot.prefix.is_empty() {
+        closer_items(&scan, &snapshot.stack, &mut items);
+    }
+    items.truncate(MAX_ITEMS);
+    Ok(Completions {
+        pointer: Path(path).to_pointer(),
+        replace_start: lines.position(snapshot.start),
+        replace_end: lines.position(snapshot.end),
+        items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at_end(text: &str) -> Cursor {
+        let last = text.lines().last().unwrap_or_default();
+        Cursor {
+            line: text.lines().count().max(1) as u32,
+            column: last.chars().count() as u32 + 1,
+        }
+    }
+
+    fn labels(c: &Completions) -> Vec<&str> {
+        c.items.iter().map(|i| i.label.as_str()).collect()
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "name": {"type": "string", "description": "Display name"},
+                "status": {"$ref": "#/$defs/status"},
+                "id": {"type": "integer"}
+            },
+            "$defs": {"status": {"enum": ["active", "inactive"], "description": "Lifecycle"}}
+        })
+    }
+
+    #[test]
+    fn suggests_missing_schema_keys_required_first() {
+        let text = "{\"name\": \"x\", ";
+        let c = complete(text, at_end(text), Some(&schema())).unwrap();
+        assert_eq!(labels(&c), vec!["id", "status"]);
+        assert_eq!(c.items[0].insert_text, "\"id\": ");
+        assert_eq!(c.items[0].detail.as_deref(), Some("integer (required)"));
+        assert_eq!(c.items[1].documentation.as_deref(), Some("Lifecycle"));
+    }
+
+    #[test]
+    fn filters_enum_values_by_typed_prefix() {
+        let text = "{\"status\": \"ac";
+        let c = complete(text, at_end(text), Some(&schema())).unwrap();
+        assert_eq!(c.pointer, "/status");
+        assert_eq!(labels(&c), vec!["\"active\""]);
+        assert_eq!((c.replace_start.column, c.replace_end.column), (12, 15));
+    }
+
+    #[test]
+    fn falls_back_to_sibling_keys_and_closers() {
+        let text = "[{\"id\": 1, \"name\": \"a\"},\n {\"id\": 2, \"tags\": []}, {\"id\": 3, ";
+        let c = complete(text, at_end(text), None).unwrap();
+        assert_eq!(c.pointer, "/2");
+        assert_eq!(labels(&c), vec!["name", "tags"]);
+        assert_eq!(c.items[0].source, CompletionSource::Siblings);
+
+        let text = "{\"a\": [1, 2";
+        let c = complete(text, at_end(text), None).unwrap();
+        assert!(c.items.is_empty());
+        let text = "{\"a\": [1, 2 ";
+        let c = complete(text, at_end(text), None).unwrap();
+        assert_eq!(labels(&c), vec!["]", "]}"]);
+    }
+}
@@ -0,0 +1,437 @@
+use std::path::Path as FsPath;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::document::write_atomic;
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SPLIT_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictHunk {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub ours_label: String,
+    pub theirs_label: String,
+    pub ours: String,
+    pub base: Option<String>,
+    pub theirs: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeConflict {
+    pub path: Path,
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeResult {
+    pub merged: Value,
+    pub merged_text: String,
+    pub conflicts: Vec<MergeConflict>,
+    pub clean: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictFile {
+    pub path: String,
+    pub hunks: Vec<ConflictHunk>,
+    pub ours: String,
+    pub base: Option<String>,
+    pub theirs: String,
+    pub merge: Option<MergeResult>,
+    pub parse_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionResult {
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Common,
+    Ours,
+    Base,
+    Theirs,
+}
+
+fn marker_label(line: &str, marker: &str) -> Option<String> {
+    let rest = line.strip_prefix(marker)?;
+    let rest = rest.trim_end_matches(['\r', '\n']);
+    if rest.is_empty() {
+        return Some(String::new());
+    }
+    rest.strip_prefix(' ').map(|l| l.trim().to_string())
+}
+
+pub struct SplitVersions {
+    pub hunks: Vec<ConflictHunk>,
+    pub ours: String,
+    pub base: Option<String>,
+    pub theirs: String,
+}
+
+pub fn split_versions(text: &str) -> DocResult<SplitVersions> {
+    let mut hunks = Vec::new();
+    let mut ours = String::new();
+    let mut base = String::new();
+    let mut theirs = String::new();
+    let mut all_have_base = true;
+    let mut section = Section::Common;
+    let mut current: Option<ConflictHunk> = None;
+
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        let line_no = i as u32 + 1;
+        match section {
+            Section::Common => {
+                if let Some(label) = marker_label(line, OURS_MARKER) {
+                    current = Some(ConflictHunk {
+                        start_line: line_no,
+                        end_line: line_no,
+                        ours_label: label,
+                        theirs_label: String::new(),
+                        ours: String::new(),
+                        base: None,
+                        theirs: String::new(),
+                    });
+                    section = Section::Ours;
+                } else {
+                    ours.push_str(line);
+                    base.push_str(line);
+                    theirs.push_str(line);
+                }
+            }
+            Section::Ours | Section::Base => {
+                let hunk = current.as_mut().expect("inside a hunk");
+                if section == Section::Ours && marker_label(line, BASE_MARKER).is_some() {
+                    hunk.base = Some(String::new());
+                    section = Section::Base;
+                } else if marker_label(line, SPLIT_MARKER).is_some_and(|l| l.is_empty()) {
+                    section = Section::Theirs;
+                } else if section == Section::Ours {
+                    hunk.ours.push_str(line);
+                    ours.push_str(line);
+                } else {
+                    hunk.base.get_or_insert_with(String::new).push_str(line);
+                    base.push_str(line);
+                }
+            }
+            Section::Theirs => {
+                let hunk = current.as_mut().expect("inside a hunk");
+                if let Some(label) = marker_label(line, THEIRS_MARKER) {
+                    hunk.theirs_label = label;
+                    hunk.end_line = line_no;
+                    let done = current.take().expect("inside a hunk");
+                    all_have_base &= done.base.is_some();
+                    hunks.push(done);
+                    section = Section::Common;
+                } else {
+                    hunk.theirs.push_str(line);
+                    theirs.push_str(line);
+                }
+            }
+        }
+    }
+    if section != Section::Common {
+        return Err(DocError::Parse(
+            "unterminated conflict block (missing >>>>>>> marker)".into(),
+        ));
+    }
+    let base = (!hunks.is_empty() && all_have_base).then_some(base);
+    Ok(SplitVersions {
+        hunks,
+        ours,
+        base,
+        theirs,
+    })
+}
+
+fn child(path: &Path, seg: PathSegment) -> Path {
+    let mut p = path.clone();
+    p.push(seg);
+    p
+}
+
+fn merge_value(
+    base: Option<&Value>,
+    ours: &Value,
+    theirs: &Value,
+    path: &Path,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Value {
+    if ours == theirs {
+        return ours.clone();
+    }
+    if base == Some(ours) {
+        return theirs.clone();
+    }
+    if base == Some(theirs) {
+        return ours.clone();
+    }
+    match (ours, theirs) {
+        (Value::Object(o), Value::Object(t)) => {
+            let b = base.and_then(Value::as_object);
+            let mut out = serde_json::Map::new();
+            for key in o.keys().chain(t.keys().filter(|k| !o.contains_key(*k))) {
+                let bv = b.and_then(|m| m.get(key));
+                let p = child(path, PathSegment::Key(key.clone()));
+                match (o.get(key), t.get(key)) {
+                    (Some(ov), Some(tv)) => {
+                        out.insert(key.clone(), merge_value(bv, ov, tv, &p, conflicts));
+                    }
+                    (Some(side), None) | (None, Some(side)) => match bv {
+                        None => {
+                            out.insert(key.clone(), side.clone());
+                        }
+                        Some(bv) if bv == side => {}
+                        Some(bv) => {
+                            let ours_side = o.contains_key(key);
+                            conflicts.push(MergeConflict {
+                                path: p,
+                                base: Some(bv.clone()),
+                                ours: ours_side.then(|| side.clone()),
+                                theirs: (!ours_side).then(|| side.clone()),
+                            });
+                            out.insert(key.clone(), side.clone());
+                        }
+                    },
+                    (None, None) => {}
+                }
+            }
+            Value::Object(out)
+        }
+        (Value::Array(o), Value::Array(t))
+            if o.len() == t.len()
+                && base
+                    .and_then(Value::as_array)
+                    .is_none_or(|b| b.len() == o.len()) =>
+        {
+            let b = base.and_then(Value::as_array);
+            Value::Array(
+                o.iter()
+                    .zip(t)
+                    .enumerate()
+                    .map(|(i, (ov, tv))| {
+                        let bv = b.and_then(|b| b.get(i));
+                        merge_value(
+                            bv,
+                            ov,
+                            tv,
+                            &child(path, PathSegment::Index(i as u32)),
+                            conflicts,
+                        )
+                    })
+                    .collect(),
+            )
+        }
+        _ => {
+            conflicts.push(MergeConflict {
+                path: path.clone(),
+                base: base.cloned(),
+                ours: Some(ours.clone()),
+                theirs: Some(theirs.clone()),
+            });
+            ours.clone()
+        }
+    }
+}
+
+pub fn three_way_merge(
+    base: Option<&Value>,
+    ours: &Value,
+    theirs: &Value,
+) -> DocResult<MergeResult> {
+    let mut conflicts = Vec::new();
+    let merged = merge_value(base, ours, theirs, &Path::root(), &mut conflicts);
+    let merged_text =
+        serde_json::to_string_pretty(&merged).map_err(|e| DocError::Export(e.to_string()))?;
+    Ok(MergeResult {
+        merged,
+        merged_text,
+        clean: conflicts.is_empty(),
+        conflicts,
+    })
+}
+
+fn parse_side(label: &str, text: &str) -> Result<Value, String> {
+    serde_json::from_str(text).map_err(|e| format!("{label} version is not valid JSON: {e}"))
+}
+
+pub fn analyze(path: &str, text: &str) -> DocResult<ConflictFile> {
+    let split = split_versions(text)?;
+    let mut merge = None;
+    let mut parse_error = None;
+    if !split.hunks.is_empty() {
+        let parsed = parse_side("ours", &split.ours).and_then(|o| {
+            let t = parse_side("theirs", &split.theirs)?;
+            let b = split
+                .base
+                .as_deref()
+                .map(|b| parse_side("base", b))
+                .transpose()?;
+            Ok((o, b, t))
+        });
+        match parsed {
+            Ok((o, b, t)) => merge = Some(three_way_merge(b.as_ref(), &o, &t)?),
+            Err(e) => parse_error = Some(e),
+        }
+    }
+    Ok(ConflictFile {
+        path: path.to_string(),
+        hunks: split.hunks,
+        ours: split.ours,
+        base: split.base,
+        theirs: split.theirs,
+        merge,
+        parse_error,
+    })
+}
+
+pub fn parse_conflict_file(path: &FsPath) -> DocResult<ConflictFile> {
+    let text = std::fs::read_to_string(path)?;
+    analyze(&path.to_string_lossy(), &text)
+}
+
+pub fn write_resolution(path: &FsPath, content: &str) -> DocResult<ResolutionResult> {
+    if !split_versions(content)?.hunks.is_empty() {
+        return Err(DocError::Edit(
+            "resolution still contains conflict markers".into(),
+        ));
+    }
+    serde_json::from_str::<Value>(content)
+        .map_err(|e| DocError::Parse(format!("resolution is not valid JSON: {e}")))?;
+    write_atomic(path, content.as_bytes())?;
+    Ok(ResolutionResult {
+        path: path.to_string_lossy().into_owned(),
+        bytes: content.len() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+
+    const TWO_WAY: &str = r#"{
+  "name": "app",
+<<<<<<< HEAD
+  "version": "1.1.0",
+=======
+  "version": "1.2.0",
+>>>>>>> feature
+  "private": true
+}
+"#;
+
+    const DIFF3: &str = r#"{
+<<<<<<< ours
+  "a": 2,
+  "b": 1,
+  "c": 1
+||||||| base
+  "a": 1,
+  "b": 1,
+  "c": 1
+=======
+  "a": 1,
+  "b": 3,
+  "c": 1,
+  "d": 4
+>>>>>>> theirs
+}
+"#;
+
+    #[test]
+    fn splits_two_way_markers() {
+        let split = split_versions(TWO_WAY).unwrap();
+        assert_eq!(split.hunks.len(), 1);
+        let h = &split.hunks[0];
+        assert_eq!((h.start_line, h.end_line), (3, 7));
+        assert_eq!(h.ours_label, "HEAD");
+        assert_eq!(h.theirs_label, "feature");
+        assert!(split.base.is_none());
+        assert_eq!(parse_side("ours", &split.ours).unwrap()["version"], "1.1.0");
+        assert_eq!(
+            parse_side("theirs", &split.theirs).unwrap()["version"],
+            "1.2.0"
+        );
+    }
+
+    #[test]
+    fn two_way_scalar_difference_is_a_conflict() {
+        let report = analyze("package.json", TWO_WAY).unwrap();
+        let merge = report.merge.unwrap();
+        assert!(!merge.clean);
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].path.to_string(), "$.version");
+        assert_eq!(merge.conflicts[0].theirs, Some(json!("1.2.0")));
+    }
+
+    #[test]
+    fn diff3_markers_merge_cleanly_with_base() {
+        let report = analyze("x.json", DIFF3).unwrap();
+        assert!(report.base.is_some());
+        let merge = report.merge.unwrap();
+        assert!(merge.clean, "{:?}", merge.conflicts);
+        assert_eq!(merge.merged, json!({"a": 2, "b": 3, "c": 1, "d": 4}));
+    }
+
+    #[test]
+    fn three_way_detects_modify_delete() {
+        let base = json!({"a": 1, "b": 1});
+        let ours = json!({"a": 1});
+        let theirs = json!({"a": 1, "b": 2});
+        let merge = three_way_merge(Some(&base), &ours, &theirs).unwrap();
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].ours, None);
+
+        let theirs_same = json!({"a": 5, "b": 1});
+        let merge = three_way_merge(Some(&base), &ours, &theirs_same).unwrap();
+        assert!(merge.clean);
+        assert_eq!(merge.merged, json!({"a": 5}));
+    }
+
+    #[test]
+    fn unterminated_block_is_a_parse_error() {
+        let err = split_versions("<<<<<<< a\n1\n=======\n2\n").unwrap_err();
+        assert!(matches!(err, DocError::Parse(_)));
+    }
+
+    #[test]
+    fn files_without_markers_have_no_hunks() {
+        let report = analyze("x.json", "{\"a\": 1}\n").unwrap();
+        assert!(report.hunks.is_empty());
+        assert!(report.merge.is_none());
+    }
+
+    #[test]
+    fn write_resolution_validates_content() {
+        let dir = TempDir::new("resolve");
+        let path = dir.join("resolved.json");
+        assert!(matches!(
+            write_resolution(&path, TWO_WAY),
+            Err(DocError::Edit(_))
+        ));
+        assert!(matches!(
+            write_resolution(&path, "{oops"),
+            Err(DocError::Parse(_))
+        ));
+        let r = write_resolution(&path, r#"{"ok": true}"#).unwrap();
+        assert_eq!(r.bytes, 12);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), r#"{"ok": true}"#);
+    }
+}
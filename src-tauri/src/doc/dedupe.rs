@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path as FsPath;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::jobs::CancelFlag;
+use super::records::{self, RecordFormat};
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DedupeStrategy {
+    KeepFirst,
+    KeepLast,
+    MergeObjects,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupeResult {
+    pub output_path: String,
+    pub records_in: u64,
+    pub records_out: u64,
+    pub duplicates: u64,
+    pub missing_key: u64,
+}
+
+type KeyHash = [u8; 32];
+
+fn record_key(record: &Value, key: &str) -> Option<KeyHash> {
+    let value = if key.starts_with('/') {
+        record.pointer(key)
+    } else {
+        record.get(key)
+    }?;
+    if value.is_null() {
+        return None;
+    }
+    Some(*blake3::hash(value.to_string().as_bytes()).as_bytes())
+}
+
+fn merge(into: &mut Value, next: Value) {
+    match (into, next) {
+        (Value::Object(base), Value::Object(more)) => base.extend(more),
+        (slot, next) => *slot = next,
+    }
+}
+
+struct Group {
+    remaining: u64,
+    last_index: u64,
+    merged: Option<Value>,
+}
+
+fn scan(path: &FsPath, key: &str, cancel: &CancelFlag) -> DocResult<HashMap<KeyHash, Group>> {
+    let mut reader = records::open(path)?;
+    let mut groups: HashMap<KeyHash, Group> = HashMap::new();
+    let mut index = 0u64;
+    while let Some(record) = reader.next_value()? {
+        if cancel.is_cancelled() {
+            return Err(DocError::Cancelled);
+        }
+        if let Some(hash) = record_key(&record, key) {
+            let group = groups.entry(hash).or_insert(Group {
+                remaining: 0,
+                last_index: 0,
+                merged: None,
+            });
+            group.remaining += 1;
+            group.last_index = index;
+        }
+        index += 1;
+    }
+    Ok(groups)
+}
+
+pub fn dedupe(
+    path: &FsPath,
+    key: &str,
+    strategy: DedupeStrategy,
+    output: &FsPath,
+    cancel: &CancelFlag,
+) -> DocResult<DedupeResult> {
+    if key.is_empty() {
+        return Err(DocError::Edit("dedupe needs a key field".into()));
+    }
+    let mut groups = match strategy {
+        DedupeStrategy::KeepFirst => HashMap::new(),
+        DedupeStrategy::KeepLast | DedupeStrategy::MergeObjects => scan(path, key, cancel)?,
+    };
+    let mut reader = records::open(path)?;
+    let format = RecordFormat::from_extension(output).unwrap_or(reader.format());
+    let mut writer = records::create(output, format)?;
+    let mut seen: HashSet<KeyHash> = HashSet::new();
+    let (mut records_in, mut missing_key) = (0u64, 0u64);
+    while let Some(record) = reader.next_value()? {
+        if cancel.is_cancelled() {
+            return Err(DocError::Cancelled);
+        }
+        let index = records_in;
+        records_in += 1;
+        let Some(hash) = record_key(&record, key) else {
+            missing_key += 1;
+            writer.write(&record)?;
+            continue;
+        };
+        match strategy {
+            DedupeStrategy::KeepFirst => {
+                if seen.insert(hash) {
+                    writer.write(&record)?;
+                }
+            }
+            DedupeStrategy::KeepLast => {
+                if groups.get(&hash).is_some_and(|g| g.last_index == index) {
+                    writer.write(&record)?;
+                }
+            }
+            DedupeStrategy::MergeObjects => {
+                let Some(group) = groups.get_mut(&hash) else {
+                    continue;
+                };
+                group.remaining -= 1;
+                match &mut group.merged {
+                    Some(merged) => merge(merged, record),
+                    None => group.merged = Some(record),
+                }
+                if group.remaining == 0 {
+                    let merged = groups.remove(&hash).and_then(|g| g.merged);
+                    if let Some(merged) = merged {
+                        writer.write(&merged)?;
+                    }
+                }
+            }
+        }
+    }
+    let records_out = writer.count();
+    writer.finish()?;
+    Ok(DedupeResult {
+        output_path: output.to_string_lossy().into_owned(),
+        records_in,
+        records_out,
+        duplicates: records_in - records_out,
+        missing_key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+
+    fn run(strategy: DedupeStrategy, key: &str) -> (DedupeResult, Vec<Value>) {
+        let dir = TempDir::new("dedupe");
+        let name = uuid::Uuid::new_v4();
+        let input = dir.join(format!("{name}.ndjson"));
+        let output = dir.join(format!("{name}-out.ndjson"));
+        let lines = [
+            json!({"id": 1, "v": "a"}),
+            json!({"id": 2, "v": "b"}),
+            json!({"v": "no key"}),
+            json!({"id": 1, "w": true}),
+            json!({"id": 1, "v": "c"}),
+        ];
+        let text: String = lines.iter().map(|l| format!("{l}\n")).collect();
+        std::fs::write(&input, text).unwrap();
+        let result = dedupe(&input, key, strategy, &output, &CancelFlag::never()).unwrap();
+        let mut reader = records::open(&output).unwrap();
+        let mut out = Vec::new();
+        while let Some(v) = reader.next_value().unwrap() {
+            out.push(v);
+        }
+        (result, out)
+    }
+
+    #[test]
+    fn keeps_first_or_last_occurrence() {
+        let (result, out) = run(DedupeStrategy::KeepFirst, "id");
+        assert_eq!(
+            out,
+            vec![
+                json!({"id": 1, "v": "a"}),
+                json!({"id": 2, "v": "b"}),
+                json!({"v": "no key"})
+            ]
+        );
+        assert_eq!(
+            (result.records_in, result.duplicates, result.missing_key),
+            (5, 2, 1)
+        );
+
+        let (_, out) = run(DedupeStrategy::KeepLast, "/id");
+        assert_eq!(
+            out,
+            vec![
+                json!({"id": 2, "v": "b"}),
+                json!({"v": "no key"}),
+                json!({"id": 1, "v": "c"})
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_duplicate_objects() {
+        let (result, out) = run(DedupeStrategy::MergeObjects, "id");
+        assert_eq!(result.records_out, 3);
+        assert_eq!(out[2], json!({"id": 1, "v": "c", "w": true}));
+        let keys: Vec<&String> = out[2].as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["id", "v", "w"]);
+    }
+}
@@ -320,7 +320,7 @@ fn convert_csv(text: &str) -> DetectResult {
     }
 }
 
-fn infer_cell(s: &str) -> serde_json::Value {
+pub fn infer_cell(s: &str) -> serde_json::Value {
     let t = s.trim();
     if t.is_empty() {
         return serde_json::Value::Null;
@@ -0,0 +1,257 @@
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::export::escape_xml;
+use super::lint::Severity;
+use super::types::{DocError, DocResult};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const TOOL_NAME: &str = "Pandia";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticFormat {
+    Sarif,
+    Junit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    #[serde(default)]
+    pub rule: Option<String>,
+    #[serde(default = "default_severity")]
+    pub severity: Severity,
+    pub message: String,
+    #[serde(default)]
+    pub pointer: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    #[serde(default)]
+    pub column: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiagnostics {
+    pub file: String,
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+fn default_severity() -> Severity {
+    Severity::Error
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Off => "note",
+    }
+}
+
+fn has_errors(file: &FileDiagnostics) -> bool {
+    file.diagnostics
+        .iter()
+        .any(|d| d.severity == Severity::Error)
+}
+
+fn artifact_uri(file: &str) -> String {
+    file.replace('\\', "/")
+}
+
+pub fn render_sarif(results: &[FileDiagnostics]) -> Value {
+    let rules: BTreeSet<&str> = results
+        .iter()
+        .flat_map(|r| &r.diagnostics)
+        .filter_map(|d| d.rule.as_deref())
+        .collect();
+    let mut out = Vec::new();
+    for file in results {
+        for d in file
+            .diagnostics
+            .iter()
+            .filter(|d| d.severity != Severity::Off)
+        {
+            let mut physical = Map::new();
+            physical.insert(
+                "artifactLocation".into(),
+                json!({ "uri": artifact_uri(&file.file) }),
+            );
+            if let Some(line) = d.line.filter(|l| *l > 0) {
+                let mut region = json!({ "startLine": line });
+                if let Some(column) = d.column.filter(|c| *c > 0) {
+                    region["startColumn"] = json!(column);
+                }
+                physical.insert("region".into(), region);
+            }
+            let mut location = json!({ "physicalLocation": physical });
+            if let Some(pointer) = &d.pointer {
+                location["logicalLocations"] = json!([{ "fullyQualifiedName": pointer }]);
+            }
+            let mut result = json!({
+                "level": level(d.severity),
+                "message": { "text": d.message },
+                "locations": [location],
+            });
+            if let Some(rule) = &d.rule {
+                result["ruleId"] = json!(rule);
+            }
+            out.push(result);
+        }
+    }
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                }
+            },
+            "results": out,
+        }]
+    })
+}
+
+pub fn render_junit(results: &[FileDiagnostics]) -> String {
+    let failures = results.iter().filter(|r| has_errors(r)).count();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuites name=\"pandia\" tests=\"{}\" failures=\"{failures}\">",
+        results.len()
+    );
+    let _ = writeln!(
+        out,
+        "  <testsuite name=\"pandia\" tests=\"{}\" failures=\"{failures}\" errors=\"0\">",
+        results.len()
+    );
+    for file in results {
+        let name = escape_xml(&artifact_uri(&file.file));
+        let _ = write!(out, "    <testcase classname=\"pandia\" name=\"{name}\"");
+        let reported: Vec<String> = file
+            .diagnostics
+            .iter()
+            .filter(|d| d.severity != Severity::Off)
+            .map(|d| {
+                let at = match (d.line, d.column) {
+                    (Some(l), Some(c)) => format!("{l}:{c}"),
+                    (Some(l), None) => l.to_string(),
+                    _ => d.pointer.clone().unwrap_or_default(),
+                };
+                let rule = d
+                    .rule
+                    .as_deref()
+                    .map(|r| format!(" [{r}]"))
+                    .unwrap_or_default();
+                format!("{} {at}{rule}: {}", level(d.severity), d.message)
+            })
+            .collect();
+        if reported.is_empty() {
+            out.push_str("/>\n");
+            continue;
+        }
+        out.push_str(">\n");
+        let body = escape_xml(&reported.join("\n"));
+        if has_errors(file) {
+            let errors = file
+                .diagnostics
+                .iter()
+                .filter(|d| d.severity == Severity::Error)
+                .count();
+            let _ = writeln!(
+                out,
+                "      <failure message=\"{errors} error(s)\" type=\"validation\">{body}</failure>"
+            );
+        } else {
+            let _ = writeln!(out, "      <system-out>{body}</system-out>");
+        }
+        out.push_str("    </testcase>\n");
+    }
+    out.push_str("  </testsuite>\n</testsuites>\n");
+    out
+}
+
+pub fn render(results: &[FileDiagnostics], format: DiagnosticFormat) -> DocResult<String> {
+    match format {
+        DiagnosticFormat::Sarif => serde_json::to_string_pretty(&render_sarif(results))
+            .map_err(|e| DocError::Export(e.to_string())),
+        DiagnosticFormat::Junit => Ok(render_junit(results)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<FileDiagnostics> {
+        vec![
+            FileDiagnostics {
+                file: "config\\app.json".into(),
+                diagnostics: vec![
+                    Diagnostic {
+                        rule: Some("schema".into()),
+                        severity: Severity::Error,
+                        message: "\"80\" is not of type \"integer\"".into(),
+                        pointer: Some("/port".into()),
+                        line: Some(3),
+                        column: Some(11),
+                    },
+                    Diagnostic {
+                        rule: Some("no-empty-keys".into()),
+                        severity: Severity::Warning,
+                        message: "empty key".into(),
+                        pointer: None,
+                        line: None,
+                        column: None,
+                    },
+                ],
+            },
+            FileDiagnostics {
+                file: "ok.json".into(),
+                diagnostics: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn sarif_lists_results_with_regions() {
+        let sarif = render_sarif(&sample());
+        let run = &sarif["runs"][0];
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+        let first = &run["results"][0];
+        assert_eq!(first["ruleId"], "schema");
+        assert_eq!(first["level"], "error");
+        let location = &first["locations"][0];
+        assert_eq!(
+            location["physicalLocation"]["artifactLocation"]["uri"],
+            "config/app.json"
+        );
+        assert_eq!(location["physicalLocation"]["region"]["startColumn"], 11);
+        assert_eq!(
+            location["logicalLocations"][0]["fullyQualifiedName"],
+            "/port"
+        );
+        assert!(run["results"][1]["locations"][0]["physicalLocation"]
+            .get("region")
+            .is_none());
+    }
+
+    #[test]
+    fn junit_marks_files_with_errors_as_failures() {
+        let xml = render_junit(&sample());
+        assert!(xml.contains("tests=\"2\" failures=\"1\""));
+        assert!(xml.contains(
+            "<failure message=\"1 error(s)\" type=\"validation\">error 3:11 [schema]: &quot;80&quot;"
+        ));
+        assert!(xml.contains("<testcase classname=\"pandia\" name=\"ok.json\"/>"));
+    }
+}
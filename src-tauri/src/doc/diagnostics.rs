@@ -0,0 +1,228 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{filter, fmt, Layer};
+
+use super::timefmt;
+use super::types::{DocError, DocResult};
+
+const LOG_PREFIX: &str = "pandia";
+const LOG_SUFFIX: &str = "log";
+const CRASH_PREFIX: &str = "crash-";
+const MAX_LOG_FILES: usize = 7;
+const MAX_BUNDLED_BYTES: u64 = 5 * 1024 * 1024;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static INSTALLED: OnceCell<PathBuf> = OnceCell::new();
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemInfo {
+    pub app_version: String,
+    pub os: String,
+    pub family: String,
+    pub arch: String,
+    pub cpus: u32,
+    pub portable: bool,
+    pub collected_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsBundle {
+    pub path: String,
+    pub files: Vec<String>,
+    pub bytes: u64,
+}
+
+pub fn log_dir(data_dir: &FsPath) -> PathBuf {
+    data_dir.join("logs")
+}
+
+pub fn system_info(app_version: String, portable: bool) -> SystemInfo {
+    SystemInfo {
+        app_version,
+        os: std::env::consts::OS.into(),
+        family: std::env::consts::FAMILY.into(),
+        arch: std::env::consts::ARCH.into(),
+        cpus: std::thread::available_parallelism().map_or(1, |n| n.get() as u32),
+        portable,
+        collected_at: timefmt::rfc3339_utc(timefmt::now_millis()),
+    }
+}
+
+fn write_crash_report(
+    dir: &FsPath,
+    info: &std::panic::PanicHookInfo<'_>,
+) -> std::io::Result<PathBuf> {
+    let now = timefmt::now_millis();
+    let path = dir.join(format!("{CRASH_PREFIX}{now}.txt"));
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".into());
+    let location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+        .unwrap_or_else(|| "unknown".into());
+    let thread = std::thread::current();
+    let mut file = std::fs::File::create(&path)?;
+    writeln!(file, "time: {}", timefmt::rfc3339_utc(now))?;
+    writeln!(file, "version: {}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(file, "thread: {}", thread.name().unwrap_or("unnamed"))?;
+    writeln!(file, "location: {location}")?;
+    writeln!(file, "message: {message}")?;
+    writeln!(file)?;
+    writeln!(file, "{}", std::backtrace::Backtrace::force_capture())?;
+    Ok(path)
+}
+
+fn install_panic_hook(dir: PathBuf) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if ENABLED.load(Ordering::Relaxed) {
+            if let Ok(path) = write_crash_report(&dir, info) {
+                tracing::error!(report = %path.display(), "panic");
+            }
+        }
+        previous(info);
+    }));
+}
+
+pub fn init(dir: &FsPath) -> std::io::Result<()> {
+    ENABLED.store(true, Ordering::Relaxed);
+    if INSTALLED.get().is_some() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(dir)?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix(LOG_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    let layer = fmt::layer()
+        .json()
+        .with_writer(appender)
+        .with_filter(filter::LevelFilter::INFO)
+        .with_filter(filter::filter_fn(|_| ENABLED.load(Ordering::Relaxed)));
+    if tracing_subscriber::registry()
+        .with(layer)
+        .try_init()
+        .is_err()
+    {
+        return Ok(());
+    }
+    if INSTALLED.set(dir.to_path_buf()).is_ok() {
+        install_panic_hook(dir.to_path_buf());
+        tracing::info!(version = env!("CARGO_PKG_VERSION"), "logging started");
+    }
+    Ok(())
+}
+
+pub fn set_enabled(dir: &FsPath, enabled: bool) -> std::io::Result<()> {
+    if enabled {
+        init(dir)
+    } else {
+        tracing::info!("logging stopped");
+        ENABLED.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+fn bundled_files(dir: &FsPath) -> std::io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                let name = p.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                p.is_file() && (name.starts_with(LOG_PREFIX) || name.starts_with(CRASH_PREFIX))
+            })
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    files.sort();
+    Ok(files)
+}
+
+fn tail(path: &FsPath) -> std::io::Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    if len > MAX_BUNDLED_BYTES {
+        file.seek(SeekFrom::Start(len - MAX_BUNDLED_BYTES))?;
+    }
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+pub fn collect(dir: &FsPath, out: &FsPath, system: &SystemInfo) -> DocResult<DiagnosticsBundle> {
+    let zip_err = |e: zip::result::ZipError| DocError::Export(e.to_string());
+    let file = std::fs::File::create(out)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut names = vec!["system.json".to_string()];
+    let system_json =
+        serde_json::to_vec_pretty(system).map_err(|e| DocError::Export(e.to_string()))?;
+    zip.start_file("system.json", options).map_err(zip_err)?;
+    zip.write_all(&system_json)?;
+    for path in bundled_files(dir)? {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let name = format!("logs/{name}");
+        zip.start_file(name.as_str(), options).map_err(zip_err)?;
+        zip.write_all(&tail(&path)?)?;
+        names.push(name);
+    }
+    zip.finish().map_err(zip_err)?;
+    Ok(DiagnosticsBundle {
+        path: out.to_string_lossy().into_owned(),
+        files: names,
+        bytes: std::fs::metadata(out)?.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn bundles_logs_crash_reports_and_system_info() {
+        let dir = TempDir::new("diag");
+        std::fs::write(dir.join("pandia.2026-10-16.log"), "{\"level\":\"INFO\"}\n").unwrap();
+        std::fs::write(dir.join("crash-1.txt"), "message: boom\n").unwrap();
+        std::fs::write(dir.join("unrelated.json"), "{}").unwrap();
+        let out = dir.join("bundle.zip");
+        let bundle = collect(&dir, &out, &system_info("1.0.0".into(), false)).unwrap();
+        assert_eq!(
+            bundle.files,
+            vec![
+                "system.json",
+                "logs/crash-1.txt",
+                "logs/pandia.2026-10-16.log"
+            ]
+        );
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&out).unwrap()).unwrap();
+        let mut text = String::new();
+        archive
+            .by_name("logs/crash-1.txt")
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "message: boom\n");
+    }
+}
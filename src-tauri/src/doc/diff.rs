@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::detect::{detect_and_convert, DetectKind};
+use super::diff_rules::DiffRules;
 use super::jobs::CancelFlag;
 use super::types::{DocError, DocResult, Path, PathSegment};
 
@@ -30,11 +32,52 @@ pub struct DiffEntry {
     pub from_index: Option<u32>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "mode")]
+pub enum ArrayMatch {
+    #[default]
+    Index,
+    Key {
+        keys: Vec<String>,
+    },
+    Similarity {
+        #[serde(default = "default_similarity")]
+        threshold: f64,
+    },
+}
+
+fn default_similarity() -> f64 {
+    0.5
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiffOptions {
+    pub array_match: ArrayMatch,
+}
+
+struct Ctx<'a> {
+    opts: &'a DiffOptions,
+    cancel: &'a CancelFlag,
+}
+
+const SIMILARITY_MAX_PAIRS: usize = 1_000_000;
+
 pub fn compute_diff(left: &Value, right: &Value, cancel: &CancelFlag) -> DocResult<Vec<DiffEntry>> {
+    compute_diff_with(left, right, &DiffOptions::default(), cancel)
+}
+
+pub fn compute_diff_with(
+    left: &Value,
+    right: &Value,
+    opts: &DiffOptions,
+    cancel: &CancelFlag,
+) -> DocResult<Vec<DiffEntry>> {
     let mut out = Vec::new();
     let mut stack: Vec<PathSegment> = Vec::new();
     let mut checked: u32 = 0;
-    walk(left, right, &mut stack, &mut out, cancel, &mut checked)?;
+    let ctx = Ctx { opts, cancel };
+    walk(left, right, &mut stack, &mut out, &ctx, &mut checked)?;
     Ok(out)
 }
 
@@ -43,11 +86,11 @@ fn walk(
     right: &Value,
     stack: &mut Vec<PathSegment>,
     out: &mut Vec<DiffEntry>,
-    cancel: &CancelFlag,
+    ctx: &Ctx,
     checked: &mut u32,
 ) -> DocResult<()> {
     *checked = checked.wrapping_add(1);
-    if *checked & CANCEL_CHECK_MASK == 0 && cancel.is_cancelled() {
+    if *checked & CANCEL_CHECK_MASK == 0 && ctx.cancel.is_cancelled() {
         return Err(DocError::Cancelled);
     }
     if left == right {
@@ -59,7 +102,7 @@ fn walk(
             for (k, lv) in l.iter() {
                 stack.push(PathSegment::Key(k.clone()));
                 let res = match r.get(k) {
-                    Some(rv) => walk(lv, rv, stack, out, cancel, checked),
+                    Some(rv) => walk(lv, rv, stack, out, ctx, checked),
                     None => {
                         out.push(DiffEntry {
                             path: Path(stack.clone()),
@@ -88,7 +131,17 @@ fn walk(
                 }
             }
         }
-        (Value::Array(l), Value::Array(r)) => diff_arrays(l, r, stack, out, cancel, checked)?,
+        (Value::Array(l), Value::Array(r)) => {
+            let matches = match &ctx.opts.array_match {
+                ArrayMatch::Index => None,
+                ArrayMatch::Key { keys } => match_by_key(l, r, keys),
+                ArrayMatch::Similarity { threshold } => match_by_similarity(l, r, *threshold),
+            };
+            match matches {
+                Some(matches) => diff_matched(l, r, &matches, stack, out, ctx, checked)?,
+                None => diff_arrays(l, r, stack, out, ctx, checked)?,
+            }
+        }
         _ => out.push(DiffEntry {
             path: Path(stack.clone()),
             kind: DiffKind::Changed,
@@ -110,9 +163,10 @@ fn diff_arrays(
     r: &[Value],
     stack: &mut Vec<PathSegment>,
     out: &mut Vec<DiffEntry>,
-    cancel: &CancelFlag,
+    ctx: &Ctx,
     checked: &mut u32,
 ) -> DocResult<()> {
+    let cancel = ctx.cancel;
     let mut pre = 0;
     while pre < l.len() && pre < r.len() && l[pre] == r[pre] {
         pre += 1;
@@ -196,7 +250,7 @@ fn diff_arrays(
                     _ => unreachable!("ChangedAt pairs a Remove with an Add"),
                 };
                 stack.push(PathSegment::Index(idx as u32));
-                let res = walk(lv, rv, stack, out, cancel, checked);
+                let res = walk(lv, rv, stack, out, ctx, checked);
                 stack.pop();
                 res?;
             }
@@ -246,6 +300,178 @@ fn diff_arrays(
     Ok(())
 }
 
+fn match_by_key(l: &[Value], r: &[Value], keys: &[String]) -> Option<Vec<(usize, usize)>> {
+    let identities = |items: &[Value], key: &str| -> Option<Vec<String>> {
+        let ids: Vec<String> = items
+            .iter()
+            .map(|v| match v.as_object()?.get(key)? {
+                id @ (Value::String(_) | Value::Number(_) | Value::Bool(_)) => Some(id.to_string()),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+        let unique: std::collections::HashSet<&String> = ids.iter().collect();
+        (unique.len() == ids.len()).then_some(ids)
+    };
+    keys.iter().find_map(|key| {
+        let left_ids = identities(l, key)?;
+        let right_ids = identities(r, key)?;
+        let by_id: HashMap<&String, usize> =
+            left_ids.iter().enumerate().map(|(i, id)| (id, i)).collect();
+        Some(
+            right_ids
+                .iter()
+                .enumerate()
+                .filter_map(|(j, id)| by_id.get(id).map(|&i| (i, j)))
+                .collect(),
+        )
+    })
+}
+
+fn similarity(a: &Value, b: &Value) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    match (a, b) {
+        (Value::Object(x), Value::Object(y)) => {
+            let shared = x.iter().filter(|(k, v)| y.get(*k) == Some(*v)).count();
+            let total = x.len() + y.keys().filter(|k| !x.contains_key(*k)).count();
+            shared as f64 / total as f64
+        }
+        (Value::Array(x), Value::Array(y)) => {
+            let shared = x.iter().zip(y).filter(|(p, q)| p == q).count();
+            shared as f64 / x.len().max(y.len()) as f64
+        }
+        _ => 0.0,
+    }
+}
+
+fn match_by_similarity(l: &[Value], r: &[Value], threshold: f64) -> Option<Vec<(usize, usize)>> {
+    if l.len().saturating_mul(r.len()) > SIMILARITY_MAX_PAIRS {
+        return None;
+    }
+    let mut left_used = vec![false; l.len()];
+    let mut right_from: Vec<Option<usize>> = vec![None; r.len()];
+    for min_score in [1.0, threshold.min(1.0)] {
+        for (j, rv) in r.iter().enumerate() {
+            if right_from[j].is_some() {
+                continue;
+            }
+            let mut best: Option<(usize, f64)> = None;
+            for (i, lv) in l.iter().enumerate() {
+                if left_used[i] {
+                    continue;
+                }
+                let score = similarity(lv, rv);
+                let better = match best {
+                    None => true,
+                    Some((bi, bs)) => score > bs || (score == bs && i.abs_diff(j) < bi.abs_diff(j)),
+                };
+                if score >= min_score && better {
+                    best = Some((i, score));
+                }
+            }
+            if let Some((i, _)) = best {
+                left_used[i] = true;
+                right_from[j] = Some(i);
+            }
+        }
+    }
+    Some(
+        right_from
+            .iter()
+            .enumerate()
+            .filter_map(|(j, i)| i.map(|i| (i, j)))
+            .collect(),
+    )
+}
+
+fn stable_matches(right_from: &[Option<usize>]) -> Vec<bool> {
+    let seq: Vec<(usize, usize)> = right_from
+        .iter()
+        .enumerate()
+        .filter_map(|(j, i)| i.map(|i| (j, i)))
+        .collect();
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; seq.len()];
+    for (k, &(_, i)) in seq.iter().enumerate() {
+        let pos = tails.partition_point(|&t| seq[t].1 < i);
+        prev[k] = pos.checked_sub(1).map(|p| tails[p]);
+        if pos == tails.len() {
+            tails.push(k);
+        } else {
+            tails[pos] = k;
+        }
+    }
+    let mut stable = vec![false; right_from.len()];
+    let mut cur = tails.last().copied();
+    while let Some(k) = cur {
+        stable[seq[k].0] = true;
+        cur = prev[k];
+    }
+    stable
+}
+
+fn diff_matched(
+    l: &[Value],
+    r: &[Value],
+    matches: &[(usize, usize)],
+    stack: &mut Vec<PathSegment>,
+    out: &mut Vec<DiffEntry>,
+    ctx: &Ctx,
+    checked: &mut u32,
+) -> DocResult<()> {
+    let mut left_used = vec![false; l.len()];
+    let mut right_from: Vec<Option<usize>> = vec![None; r.len()];
+    for &(i, j) in matches {
+        left_used[i] = true;
+        right_from[j] = Some(i);
+    }
+    let stable = stable_matches(&right_from);
+    for (i, lv) in l.iter().enumerate() {
+        if !left_used[i] {
+            stack.push(PathSegment::Index(i as u32));
+            out.push(DiffEntry {
+                path: Path(stack.clone()),
+                kind: DiffKind::Removed,
+                left_preview: Some(preview(lv)),
+                right_preview: None,
+                from_index: None,
+            });
+            stack.pop();
+        }
+    }
+    for (j, rv) in r.iter().enumerate() {
+        stack.push(PathSegment::Index(j as u32));
+        let res = match right_from[j] {
+            None => {
+                out.push(DiffEntry {
+                    path: Path(stack.clone()),
+                    kind: DiffKind::Added,
+                    left_preview: None,
+                    right_preview: Some(preview(rv)),
+                    from_index: None,
+                });
+                Ok(())
+            }
+            Some(i) => {
+                if !stable[j] {
+                    out.push(DiffEntry {
+                        path: Path(stack.clone()),
+                        kind: DiffKind::Moved,
+                        left_preview: Some(preview(&l[i])),
+                        right_preview: Some(preview(rv)),
+                        from_index: Some(i as u32),
+                    });
+                }
+                walk(&l[i], rv, stack, out, ctx, checked)
+            }
+        };
+        stack.pop();
+        res?;
+    }
+    Ok(())
+}
+
 #[allow(clippy::needless_range_loop)]
 fn align_middle<'a>(a: &'a [Value], b: &'a [Value], base: usize) -> Vec<ArrOp<'a>> {
     let (n, m) = (a.len(), b.len());
@@ -313,7 +539,7 @@ fn align_middle<'a>(a: &'a [Value], b: &'a [Value], base: usize) -> Vec<ArrOp<'a
     ops
 }
 
-fn preview(v: &Value) -> String {
+pub(crate) fn preview(v: &Value) -> String {
     match v {
         Value::Null => "null".into(),
         Value::Bool(b) => b.to_string(),
@@ -334,6 +560,72 @@ fn preview(v: &Value) -> String {
     }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AdhocDiffOptions {
+    pub detect_format: bool,
+    pub rules: DiffRules,
+    pub array_match: ArrayMatch,
+}
+
+impl Default for AdhocDiffOptions {
+    fn default() -> Self {
+        Self {
+            detect_format: true,
+            rules: DiffRules::default(),
+            array_match: ArrayMatch::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdhocDiff {
+    pub left_format: DetectKind,
+    pub right_format: DetectKind,
+    pub entries: Vec<DiffEntry>,
+}
+
+fn parse_adhoc(
+    side: &str,
+    content: &str,
+    opts: &AdhocDiffOptions,
+) -> DocResult<(Value, DetectKind)> {
+    match serde_json::from_str(content) {
+        Ok(v) => Ok((v, DetectKind::Json)),
+        Err(e) if !opts.detect_format => Err(DocError::Parse(format!("{side}: {e}"))),
+        Err(_) => {
+            let detected = detect_and_convert(content);
+            if let Some(err) = detected.error {
+                return Err(DocError::Parse(format!("{side}: {err}")));
+            }
+            let v = serde_json::from_str(&detected.json)
+                .map_err(|e| DocError::Parse(format!("{side}: {e}")))?;
+            Ok((v, detected.kind))
+        }
+    }
+}
+
+pub fn diff_adhoc(
+    left: &str,
+    right: &str,
+    opts: &AdhocDiffOptions,
+    cancel: &CancelFlag,
+) -> DocResult<AdhocDiff> {
+    let filter = opts.rules.compile()?;
+    let (l, left_format) = parse_adhoc("left", left, opts)?;
+    let (r, right_format) = parse_adhoc("right", right, opts)?;
+    let diff_opts = DiffOptions {
+        array_match: opts.array_match.clone(),
+    };
+    let entries = compute_diff_with(&l, &r, &diff_opts, cancel)?;
+    Ok(AdhocDiff {
+        left_format,
+        right_format,
+        entries: filter.apply(entries, &l, &r),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -558,4 +850,90 @@ mod tests {
         assert_eq!(entries[0].left_preview.as_deref(), Some("[3 items]"));
         assert_eq!(entries[0].right_preview.as_deref(), Some("\"scalar\""));
     }
+
+    #[test]
+    fn adhoc_diffs_raw_contents() {
+        let out = diff_adhoc(
+            r#"{"a": 1, "b": 2}"#,
+            r#"{"a": 1, "b": 3}"#,
+            &AdhocDiffOptions::default(),
+            &CancelFlag::never(),
+        )
+        .unwrap();
+        assert_eq!(out.left_format, DetectKind::Json);
+        assert_eq!(out.entries.len(), 1);
+        assert_eq!(out.entries[0].kind, DiffKind::Changed);
+    }
+
+    #[test]
+    fn adhoc_detects_other_formats_only_when_asked() {
+        let yaml = "a: 1\nb: 2\n";
+        let out = diff_adhoc(
+            r#"{"a": 1, "b": 2}"#,
+            yaml,
+            &AdhocDiffOptions::default(),
+            &CancelFlag::never(),
+        )
+        .unwrap();
+        assert_eq!(out.right_format, DetectKind::Yaml);
+        assert!(out.entries.is_empty());
+        let strict = AdhocDiffOptions {
+            detect_format: false,
+            ..Default::default()
+        };
+        assert!(diff_adhoc("{}", yaml, &strict, &CancelFlag::never()).is_err());
+    }
+
+    #[test]
+    fn key_matching_reports_moves_instead_of_changes() {
+        let l = json!([{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"id": 3, "v": "c"}]);
+        let r = json!([{"id": 3, "v": "c"}, {"id": 1, "v": "a"}, {"id": 2, "v": "B"}]);
+        let opts = DiffOptions {
+            array_match: ArrayMatch::Key {
+                keys: vec!["name".into(), "id".into()],
+            },
+        };
+        let d = compute_diff_with(&l, &r, &opts, &CancelFlag::never()).unwrap();
+        assert_eq!(d.len(), 2);
+        assert_eq!(d[0].kind, DiffKind::Moved);
+        assert_eq!(d[0].path, segs(&["0"]));
+        assert_eq!(d[0].from_index, Some(2));
+        assert_eq!(d[1].kind, DiffKind::Changed);
+        assert_eq!(d[1].path, segs(&["2", "v"]));
+    }
+
+    #[test]
+    fn key_matching_falls_back_without_unique_ids() {
+        let l = json!([{"id": 1}, {"id": 1}]);
+        let r = json!([{"id": 1}, {"id": 2}]);
+        let opts = DiffOptions {
+            array_match: ArrayMatch::Key {
+                keys: vec!["id".into()],
+            },
+        };
+        assert_eq!(
+            compute_diff_with(&l, &r, &opts, &CancelFlag::never()).unwrap(),
+            diff(l, r)
+        );
+    }
+
+    #[test]
+    fn similarity_matching_pairs_closest_elements() {
+        let l = json!([{"name": "a", "age": 1}, {"name": "b", "age": 2}, {"name": "c", "age": 9}]);
+        let r = json!([{"name": "b", "age": 3}, {"name": "a", "age": 1}, {"x": true}]);
+        let opts = DiffOptions {
+            array_match: ArrayMatch::Similarity { threshold: 0.5 },
+        };
+        let d = compute_diff_with(&l, &r, &opts, &CancelFlag::never()).unwrap();
+        let kinds: Vec<(DiffKind, Path)> = d.into_iter().map(|e| (e.kind, e.path)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (DiffKind::Removed, segs(&["2"])),
+                (DiffKind::Moved, segs(&["0"])),
+                (DiffKind::Changed, segs(&["0", "age"])),
+                (DiffKind::Added, segs(&["2"])),
+            ]
+        );
+    }
 }
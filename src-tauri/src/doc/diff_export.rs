@@ -0,0 +1,458 @@
+use std::fmt::Write as _;
+use std::path::Path as FsPath;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::diff::{preview, DiffEntry, DiffKind};
+use super::goto::{pointer_segments, value_at};
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiffExportFormat {
+    Unified,
+    Html,
+    JsonPatch,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffResultInput {
+    pub entries: Vec<DiffEntry>,
+    #[serde(default)]
+    pub left_label: Option<String>,
+    #[serde(default)]
+    pub right_label: Option<String>,
+}
+
+pub struct DiffSides<'a> {
+    pub left: Option<&'a Value>,
+    pub right: Option<&'a Value>,
+}
+
+impl DiffSides<'_> {
+    fn left_path(entry: &DiffEntry) -> Path {
+        match (entry.kind, entry.from_index) {
+            (DiffKind::Moved, Some(from)) => {
+                let mut segs = entry.path.0.clone();
+                segs.pop();
+                segs.push(PathSegment::Index(from));
+                Path(segs)
+            }
+            _ => entry.path.clone(),
+        }
+    }
+
+    fn left_text(&self, entry: &DiffEntry) -> Option<String> {
+        match self.left.and_then(|v| value_at(v, &Self::left_path(entry))) {
+            Some(v) => Some(v.to_string()),
+            None => entry.left_preview.clone(),
+        }
+    }
+
+    fn right_text(&self, entry: &DiffEntry) -> Option<String> {
+        match self.right.and_then(|v| value_at(v, &entry.path)) {
+            Some(v) => Some(v.to_string()),
+            None => entry.right_preview.clone(),
+        }
+    }
+
+    fn right_value(&self, path: &Path, preview: Option<&str>) -> DocResult<Value> {
+        let exact = self.right.and_then(|v| value_at(v, path)).cloned();
+        exact
+            .or_else(|| {
+                preview
+                    .filter(|p| !p.ends_with("\u{2026}\""))
+                    .and_then(|p| serde_json::from_str(p).ok())
+            })
+            .ok_or_else(|| {
+                DocError::Export(format!(
+                    "value at {} is only available as a preview; export with the right document",
+                    path.to_pointer()
+                ))
+            })
+    }
+}
+
+fn left_pointer(entry: &DiffEntry) -> String {
+    DiffSides::left_path(entry).to_pointer()
+}
+
+fn kind_label(kind: DiffKind) -> &'static str {
+    match kind {
+        DiffKind::Added => "added",
+        DiffKind::Removed => "removed",
+        DiffKind::Changed => "changed",
+        DiffKind::Moved => "moved",
+    }
+}
+
+pub fn to_unified(input: &DiffResultInput, sides: &DiffSides) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "--- {}", input.left_label.as_deref().unwrap_or("left"));
+    let _ = writeln!(
+        out,
+        "+++ {}",
+        input.right_label.as_deref().unwrap_or("right")
+    );
+    for entry in &input.entries {
+        let pointer = entry.path.to_pointer();
+        match entry.kind {
+            DiffKind::Moved => {
+                let _ = writeln!(out, "@@ {pointer} (moved from {}) @@", left_pointer(entry));
+                if let Some(v) = sides.right_text(entry) {
+                    let _ = writeln!(out, " {v}");
+                }
+            }
+            _ => {
+                let _ = writeln!(out, "@@ {pointer} @@");
+                if entry.kind != DiffKind::Added {
+                    if let Some(v) = sides.left_text(entry) {
+                        let _ = writeln!(out, "-{v}");
+                    }
+                }
+                if entry.kind != DiffKind::Removed {
+                    if let Some(v) = sides.right_text(entry) {
+                        let _ = writeln!(out, "+{v}");
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;margin:1.5rem;color:#1f2328}\
+table{border-collapse:collapse;width:100%;table-layout:fixed}\
+th,td{border:1px solid #d0d7de;padding:4px 8px;vertical-align:top;text-align:left}\
+td{font-family:ui-monospace,monospace;font-size:12px;white-space:pre-wrap;word-break:break-all}\
+th{background:#f6f8fa}th.path{width:28%}th.kind{width:8%}\
+tr.added td.right{background:#dafbe1}tr.removed td.left{background:#ffebe9}\
+tr.changed td.left{background:#ffebe9}tr.changed td.right{background:#dafbe1}\
+tr.moved td{background:#ddf4ff}";
+
+pub fn to_html(input: &DiffResultInput, sides: &DiffSides) -> String {
+    let left_label = escape_html(input.left_label.as_deref().unwrap_or("Left"));
+    let right_label = escape_html(input.right_label.as_deref().unwrap_or("Right"));
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{left_label} \u{2194} {right_label}</title>\
+         <style>{HTML_STYLE}</style></head><body>\n<h1>{left_label} \u{2194} {right_label}</h1>\n\
+         <p>{} difference(s)</p>\n<table>\n<tr><th class=\"path\">Path</th><th class=\"kind\">Change</th>\
+         <th>{left_label}</th><th>{right_label}</th></tr>\n",
+        input.entries.len()
+    );
+    for entry in &input.entries {
+        let kind = kind_label(entry.kind);
+        let path = match entry.kind {
+            DiffKind::Moved => format!(
+                "{} \u{2190} {}",
+                entry.path.to_pointer(),
+                left_pointer(entry)
+            ),
+            _ => entry.path.to_pointer(),
+        };
+        let left = match entry.kind {
+            DiffKind::Added => String::new(),
+            _ => sides.left_text(entry).unwrap_or_default(),
+        };
+        let right = match entry.kind {
+            DiffKind::Removed => String::new(),
+            _ => sides.right_text(entry).unwrap_or_default(),
+        };
+        let _ = writeln!(
+            out,
+            "<tr class=\"{kind}\"><td>{}</td><td>{kind}</td><td class=\"left\">{}</td><td class=\"right\">{}</td></tr>",
+            escape_html(&path),
+            escape_html(&left),
+            escape_html(&right)
+        );
+    }
+    out.push_str("</table>\n</body></html>\n");
+    out
+}
+
+fn is_under(ancestor: &Path, path: &Path) -> bool {
+    path.0.len() > ancestor.0.len() && path.0.starts_with(&ancestor.0)
+}
+
+pub fn to_json_patch(entries: &[DiffEntry], sides: &DiffSides) -> DocResult<Value> {
+    let restructured: Vec<Path> = entries
+        .iter()
+        .filter(|e| e.kind != DiffKind::Changed)
+        .filter(|e| matches!(e.path.0.last(), Some(PathSegment::Index(_))))
+        .map(|e| Path(e.path.0[..e.path.0.len() - 1].to_vec()))
+        .collect();
+    let mut replaced: Vec<Path> = Vec::new();
+    let mut ops = Vec::new();
+    for entry in entries {
+        let covering = restructured
+            .iter()
+            .filter(|p| is_under(p, &entry.path))
+            .min_by_key(|p| p.0.len());
+        if let Some(array) = covering {
+            if !replaced.contains(array) {
+                let value = sides.right_value(array, None)?;
+                ops.push(json!({ "op": "replace", "path": array.to_pointer(), "value": value }));
+                replaced.push(array.clone());
+            }
+            continue;
+        }
+        let pointer = entry.path.to_pointer();
+        ops.push(match entry.kind {
+            DiffKind::Removed => json!({ "op": "remove", "path": pointer }),
+            DiffKind::Added => json!({
+                "op": "add",
+                "path": pointer,
+                "value": sides.right_value(&entry.path, entry.right_preview.as_deref())?,
+            }),
+            DiffKind::Changed | DiffKind::Moved => json!({
+                "op": "replace",
+                "path": pointer,
+                "value": sides.right_value(&entry.path, entry.right_preview.as_deref())?,
+            }),
+        });
+    }
+    Ok(Value::Array(ops))
+}
+
+pub fn render(
+    input: &DiffResultInput,
+    format: DiffExportFormat,
+    sides: &DiffSides,
+) -> DocResult<String> {
+    match format {
+        DiffExportFormat::Unified => Ok(to_unified(input, sides)),
+        DiffExportFormat::Html => Ok(to_html(input, sides)),
+        DiffExportFormat::JsonPatch => {
+            let patch = to_json_patch(&input.entries, sides)?;
+            serde_json::to_string_pretty(&patch).map_err(|e| DocError::Export(e.to_string()))
+        }
+    }
+}
+
+fn path_of_pointer(pointer: &str) -> Path {
+    Path(
+        pointer_segments(pointer)
+            .into_iter()
+            .map(|s| match s.parse::<u32>() {
+                Ok(i) if !s.starts_with('0') || s == "0" => PathSegment::Index(i),
+                _ => PathSegment::Key(s),
+            })
+            .collect(),
+    )
+}
+
+pub fn entries_from_patch(patch: &Value) -> DocResult<Vec<DiffEntry>> {
+    let ops = patch
+        .as_array()
+        .ok_or_else(|| DocError::Parse("JSON Patch must be an array of operations".into()))?;
+    let mut out = Vec::with_capacity(ops.len());
+    for (i, op) in ops.iter().enumerate() {
+        let field = |name: &str| op.get(name).and_then(Value::as_str);
+        let path =
+            field("path").ok_or_else(|| DocError::Parse(format!("operation {i} has no path")))?;
+        let value_preview = op.get("value").map(preview);
+        let (kind, from_index) = match field("op") {
+            Some("add") => (DiffKind::Added, None),
+            Some("remove") => (DiffKind::Removed, None),
+            Some("replace") => (DiffKind::Changed, None),
+            Some("move") => {
+                let from = field("from")
+                    .ok_or_else(|| DocError::Parse(format!("operation {i} has no from")))?;
+                let from_index = match path_of_pointer(from).0.last() {
+                    Some(PathSegment::Index(idx)) => Some(*idx),
+                    _ => None,
+                };
+                (DiffKind::Moved, from_index)
+            }
+            Some("copy") | Some("test") => continue,
+            other => {
+                return Err(DocError::Parse(format!(
+                    "operation {i} has unsupported op {other:?}"
+                )))
+            }
+        };
+        out.push(DiffEntry {
+            path: path_of_pointer(path),
+            kind,
+            left_preview: None,
+            right_preview: if kind == DiffKind::Removed {
+                None
+            } else {
+                value_preview
+            },
+            from_index,
+        });
+    }
+    Ok(out)
+}
+
+pub fn write(path: &FsPath, text: &str) -> DocResult<()> {
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+pub fn read_patch(path: &FsPath) -> DocResult<Vec<DiffEntry>> {
+    let text = std::fs::read_to_string(path)?;
+    let patch: Value = serde_json::from_str(&text).map_err(|e| DocError::Parse(e.to_string()))?;
+    entries_from_patch(&patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::diff::compute_diff;
+    use crate::doc::jobs::CancelFlag;
+
+    fn apply(doc: &mut Value, patch: &Value) {
+        for op in patch.as_array().unwrap() {
+            let path = path_of_pointer(op["path"].as_str().unwrap());
+            let (last, parent) = path.0.split_last().unwrap();
+            let mut target = &mut *doc;
+            for seg in parent {
+                target = match seg {
+                    PathSegment::Key(k) => &mut target[k.as_str()],
+                    PathSegment::Index(i) => &mut target[*i as usize],
+                };
+            }
+            match (op["op"].as_str().unwrap(), last, target) {
+                ("remove", PathSegment::Index(i), Value::Array(a)) => {
+                    a.remove(*i as usize);
+                }
+                ("remove", PathSegment::Key(k), Value::Object(m)) => {
+                    m.shift_remove(k);
+                }
+                ("add", PathSegment::Index(i), Value::Array(a)) => {
+                    a.insert(*i as usize, op["value"].clone())
+                }
+                ("add" | "replace", PathSegment::Key(k), Value::Object(m)) => {
+                    m.insert(k.clone(), op["value"].clone());
+                }
+                ("replace", PathSegment::Index(i), Value::Array(a)) => {
+                    a[*i as usize] = op["value"].clone()
+                }
+                other => panic!("unexpected op {other:?}"),
+            }
+        }
+    }
+
+    fn input(entries: Vec<DiffEntry>) -> DiffResultInput {
+        DiffResultInput {
+            entries,
+            left_label: Some("old.json".into()),
+            right_label: Some("new.json".into()),
+        }
+    }
+
+    #[test]
+    fn json_patch_rebuilds_right_document() {
+        let left = json!({"a": 1, "gone": true, "list": [1, 2, 3, 4], "tags": ["x", "y"]});
+        let right = json!({"a": 2, "list": [2, 4, 5], "tags": ["y", "x"], "new": {"k": [1]}});
+        let entries = compute_diff(&left, &right, &CancelFlag::never()).unwrap();
+        let sides = DiffSides {
+            left: Some(&left),
+            right: Some(&right),
+        };
+        let patch = to_json_patch(&entries, &sides).unwrap();
+        let mut rebuilt = left.clone();
+        apply(&mut rebuilt, &patch);
+        assert_eq!(rebuilt, right);
+    }
+
+    #[test]
+    fn json_patch_keeps_in_place_array_edits_granular() {
+        let left = json!({"rows": [{"v": 1}, {"v": 2}]});
+        let right = json!({"rows": [{"v": 1}, {"v": 3}]});
+        let entries = compute_diff(&left, &right, &CancelFlag::never()).unwrap();
+        let patch = to_json_patch(
+            &entries,
+            &DiffSides {
+                left: None,
+                right: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            patch,
+            json!([{"op": "replace", "path": "/rows/1/v", "value": 3}])
+        );
+    }
+
+    #[test]
+    fn json_patch_needs_full_values_for_containers() {
+        let left = json!({});
+        let right = json!({"n": 3, "obj": {"k": 1}});
+        let entries = compute_diff(&left, &right, &CancelFlag::never()).unwrap();
+        let sides = DiffSides {
+            left: None,
+            right: None,
+        };
+        assert!(to_json_patch(&entries[..1], &sides).is_ok());
+        assert!(to_json_patch(&entries, &sides).is_err());
+    }
+
+    #[test]
+    fn unified_and_html_render_each_entry() {
+        let left = json!({"name": "<a>", "old": 1});
+        let right = json!({"name": "<b>", "new": 2});
+        let entries = compute_diff(&left, &right, &CancelFlag::never()).unwrap();
+        let sides = DiffSides {
+            left: Some(&left),
+            right: Some(&right),
+        };
+        let input = input(entries);
+        let unified = to_unified(&input, &sides);
+        assert!(unified.starts_with("--- old.json\n+++ new.json\n"));
+        assert!(unified.contains("@@ /name @@\n-\"<a>\"\n+\"<b>\"\n"));
+        assert!(unified.contains("@@ /old @@\n-1\n"));
+        assert!(unified.contains("@@ /new @@\n+2\n"));
+        let html = to_html(&input, &sides);
+        assert!(html.contains("&quot;&lt;a&gt;&quot;"));
+        assert!(!html.contains("\"<a>\""));
+        assert_eq!(html.matches("<tr class=").count(), 3);
+    }
+
+    #[test]
+    fn imports_json_patch_as_entries() {
+        let patch = json!([
+            {"op": "replace", "path": "/a", "value": 2},
+            {"op": "remove", "path": "/list/0"},
+            {"op": "move", "from": "/list/3", "path": "/list/1"},
+            {"op": "test", "path": "/a", "value": 2},
+            {"op": "add", "path": "/m~1n", "value": {"k": 1}}
+        ]);
+        let entries = entries_from_patch(&patch).unwrap();
+        let kinds: Vec<DiffKind> = entries.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiffKind::Changed,
+                DiffKind::Removed,
+                DiffKind::Moved,
+                DiffKind::Added
+            ]
+        );
+        assert_eq!(entries[2].from_index, Some(3));
+        assert_eq!(entries[3].path.to_pointer(), "/m~1n");
+        assert_eq!(entries[3].right_preview.as_deref(), Some("{1 keys}"));
+        assert!(entries_from_patch(&json!([{"op": "nope", "path": ""}])).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use super::diff::{DiffEntry, DiffKind};
+use super::goto::{kind_of, value_at};
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiffRules {
+    pub ignore_paths: Vec<String>,
+    pub ignore_values: Vec<String>,
+    pub type_only: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffProfile {
+    pub name: String,
+    #[serde(default)]
+    pub rules: DiffRules,
+}
+
+struct PathGlob(Vec<String>);
+
+impl PathGlob {
+    fn parse(pattern: &str) -> Option<Self> {
+        let trimmed = pattern.trim().trim_start_matches('/');
+        if trimmed.is_empty() {
+            return None;
+        }
+        let sep = if trimmed.contains('/') { '/' } else { '.' };
+        Some(Self(trimmed.split(sep).map(str::to_string).collect()))
+    }
+
+    fn covers(&self, path: &[String]) -> bool {
+        (0..=path.len()).any(|len| glob_segments(&self.0, &path[..len]))
+    }
+}
+
+fn glob_segments(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((head, rest)) if head == "**" => {
+            (0..=path.len()).any(|skip| glob_segments(rest, &path[skip..]))
+        }
+        Some((head, rest)) => match path.split_first() {
+            Some((seg, tail)) => wildcard(head, seg) && glob_segments(rest, tail),
+            None => false,
+        },
+    }
+}
+
+fn wildcard(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or("");
+    for part in parts {
+        match remaining.find(part) {
+            Some(at) => remaining = &remaining[at + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
+
+pub struct DiffFilter {
+    ignore_paths: Vec<PathGlob>,
+    ignore_values: Vec<Regex>,
+    type_only: Vec<PathGlob>,
+}
+
+impl DiffRules {
+    pub fn is_empty(&self) -> bool {
+        self.ignore_paths.is_empty() && self.ignore_values.is_empty() && self.type_only.is_empty()
+    }
+
+    pub fn compile(&self) -> DocResult<DiffFilter> {
+        let ignore_values = self
+            .ignore_values
+            .iter()
+            .map(|re| {
+                Regex::new(re).map_err(|e| DocError::Parse(format!("invalid value matcher: {e}")))
+            })
+            .collect::<DocResult<Vec<_>>>()?;
+        Ok(DiffFilter {
+            ignore_paths: self
+                .ignore_paths
+                .iter()
+                .filter_map(|p| PathGlob::parse(p))
+                .collect(),
+            ignore_values,
+            type_only: self
+                .type_only
+                .iter()
+                .filter_map(|p| PathGlob::parse(p))
+                .collect(),
+        })
+    }
+}
+
+fn segment_strings(path: &Path) -> Vec<String> {
+    path.0
+        .iter()
+        .map(|seg| match seg {
+            PathSegment::Key(k) => k.clone(),
+            PathSegment::Index(i) => i.to_string(),
+        })
+        .collect()
+}
+
+fn value_text(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl DiffFilter {
+    fn keeps(&self, entry: &DiffEntry, left: &Value, right: &Value) -> bool {
+        let segments = segment_strings(&entry.path);
+        if self.ignore_paths.iter().any(|g| g.covers(&segments)) {
+            return false;
+        }
+        let type_only = self.type_only.iter().any(|g| g.covers(&segments));
+        match entry.kind {
+            DiffKind::Moved => !type_only,
+            DiffKind::Added | DiffKind::Removed => true,
+            DiffKind::Changed => {
+                let (Some(l), Some(r)) =
+                    (value_at(left, &entry.path), value_at(right, &entry.path))
+                else {
+                    return true;
+                };
+                if type_only && kind_of(l) == kind_of(r) {
+                    return false;
+                }
+                let (lt, rt) = (value_text(l), value_text(r));
+                !self
+                    .ignore_values
+                    .iter()
+                    .any(|re| re.is_match(&lt) && re.is_match(&rt))
+            }
+        }
+    }
+
+    pub fn apply(&self, entries: Vec<DiffEntry>, left: &Value, right: &Value) -> Vec<DiffEntry> {
+        entries
+            .into_iter()
+            .filter(|e| self.keeps(e, left, right))
+            .collect()
+    }
+}
+
+pub fn profiles_path(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let base = super::portable::data_dir(app)?;
+    Ok(base.join("diff_profiles.json"))
+}
+
+pub fn load(path: &FsPath) -> std::io::Result<Vec<DiffProfile>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn store(path: &FsPath, profiles: &[DiffProfile]) -> std::io::Result<()> {
+    let json =
+        serde_json::to_string_pretty(profiles).map_err(|e| std::io::Error::other(e.to_string()))?;
+    std::fs::write(path, json)
+}
+
+pub fn save_profile(path: &FsPath, name: &str, rules: DiffRules) -> DocResult<Vec<DiffProfile>> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DocError::Edit("diff profile name is empty".into()));
+    }
+    rules.compile()?;
+    let mut all = load(path)?;
+    match all.iter_mut().find(|p| p.name == name) {
+        Some(existing) => existing.rules = rules,
+        None => all.push(DiffProfile {
+            name: name.to_string(),
+            rules,
+        }),
+    }
+    store(path, &all)?;
+    Ok(all)
+}
+
+pub fn delete_profile(path: &FsPath, name: &str) -> std::io::Result<Vec<DiffProfile>> {
+    let mut all = load(path)?;
+    let before = all.len();
+    all.retain(|p| p.name != name);
+    if all.len() != before {
+        store(path, &all)?;
+    }
+    Ok(all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::diff::compute_diff;
+    use crate::doc::jobs::CancelFlag;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+
+    fn filtered(rules: DiffRules, l: Value, r: Value) -> Vec<String> {
+        let entries = compute_diff(&l, &r, &CancelFlag::never()).unwrap();
+        rules
+            .compile()
+            .unwrap()
+            .apply(entries, &l, &r)
+            .iter()
+            .map(|e| segment_strings(&e.path).join("/"))
+            .collect()
+    }
+
+    #[test]
+    fn globs_match_segments() {
+        let glob = PathGlob::parse("**/updatedAt").unwrap();
+        assert!(glob.covers(&["updatedAt".into()]));
+        assert!(glob.covers(&["a".into(), "0".into(), "updatedAt".into()]));
+        assert!(!glob.covers(&["updatedAtX".into()]));
+        let dotted = PathGlob::parse("*.etag").unwrap();
+        assert!(dotted.covers(&["items".into(), "etag".into()]));
+        assert!(!dotted.covers(&["etag".into()]));
+        assert!(wildcard("updated*", "updatedAt"));
+        assert!(wildcard("*_at", "created_at"));
+        assert!(!wildcard("a*b*c", "abx"));
+    }
+
+    #[test]
+    fn ignores_paths_and_their_subtrees() {
+        let rules = DiffRules {
+            ignore_paths: vec!["**/updatedAt".into(), "meta".into()],
+            ..Default::default()
+        };
+        let l = json!({"a": 1, "updatedAt": "x", "meta": {"v": 1}, "list": [{"updatedAt": 1}]});
+        let r =
+            json!({"a": 2, "updatedAt": "y", "meta": {"v": 2, "w": 3}, "list": [{"updatedAt": 2}]});
+        assert_eq!(filtered(rules, l, r), vec!["a"]);
+    }
+
+    #[test]
+    fn value_matchers_need_both_sides() {
+        let rules = DiffRules {
+            ignore_values: vec![r"^\d{4}-\d{2}-\d{2}T".into()],
+            ..Default::default()
+        };
+        let l = json!({"ts": "2026-01-01T00:00:00Z", "name": "2026-01-01T"});
+        let r = json!({"ts": "2026-02-01T00:00:00Z", "name": "bob"});
+        assert_eq!(filtered(rules, l, r), vec!["name"]);
+    }
+
+    #[test]
+    fn type_only_subtrees_compare_kinds() {
+        let rules = DiffRules {
+            type_only: vec!["stats".into()],
+            ..Default::default()
+        };
+        let l = json!({"stats": {"hits": 1, "ratio": 0.5}, "id": 1});
+        let r = json!({"stats": {"hits": 9, "ratio": "n/a"}, "id": 2});
+        assert_eq!(filtered(rules, l, r), vec!["stats/ratio", "id"]);
+    }
+
+    #[test]
+    fn profiles_persist_by_name() {
+        let dir = TempDir::new("diff-profiles");
+        let db = dir.join("profiles.json");
+        let rules = DiffRules {
+            ignore_paths: vec!["**/etag".into()],
+            ..Default::default()
+        };
+        save_profile(&db, "api", rules.clone()).unwrap();
+        save_profile(&db, "api", DiffRules::default()).unwrap();
+        assert_eq!(load(&db).unwrap().len(), 1);
+        assert!(load(&db).unwrap()[0].rules.is_empty());
+        let bad = DiffRules {
+            ignore_values: vec!["(".into()],
+            ..Default::default()
+        };
+        assert!(save_profile(&db, "bad", bad).is_err());
+        assert!(save_profile(&db, "  ", rules).is_err());
+        assert!(delete_profile(&db, "api").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::BTreeSet;
+use std::path::Path as FsPath;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::diff::{compute_diff_with, ArrayMatch, DiffEntry, DiffKind, DiffOptions};
+use super::diff_rules::{DiffFilter, DiffRules};
+use super::jobs::CancelFlag;
+use super::types::{DocError, DocResult};
+
+const DEFAULT_EXTENSIONS: &[&str] = &["json", "jsonc", "geojson"];
+const DEFAULT_MAX_FILES: usize = 10_000;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DirDiffOptions {
+    pub extensions: Vec<String>,
+    pub recursive: bool,
+    pub include_hidden: bool,
+    pub include_entries: bool,
+    pub max_files: usize,
+    pub rules: DiffRules,
+    pub array_match: ArrayMatch,
+}
+
+impl Default for DirDiffOptions {
+    fn default() -> Self {
+        Self {
+            extensions: DEFAULT_EXTENSIONS.iter().map(|s| s.to_string()).collect(),
+            recursive: true,
+            include_hidden: false,
+            include_entries: false,
+            max_files: DEFAULT_MAX_FILES,
+            rules: DiffRules::default(),
+            array_match: ArrayMatch::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileStatus {
+    Identical,
+    Changed,
+    OnlyLeft,
+    OnlyRight,
+    Error,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeCounts {
+    pub added: u32,
+    pub removed: u32,
+    pub changed: u32,
+    pub moved: u32,
+}
+
+impl ChangeCounts {
+    fn of(entries: &[DiffEntry]) -> Self {
+        let mut counts = Self::default();
+        for e in entries {
+            match e.kind {
+                DiffKind::Added => counts.added += 1,
+                DiffKind::Removed => counts.removed += 1,
+                DiffKind::Changed => counts.changed += 1,
+                DiffKind::Moved => counts.moved += 1,
+            }
+        }
+        counts
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileComparison {
+    pub relative_path: String,
+    pub status: FileStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<ChangeCounts>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entries: Option<Vec<DiffEntry>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirDiffSummary {
+    pub identical: u32,
+    pub changed: u32,
+    pub only_left: u32,
+    pub only_right: u32,
+    pub errors: u32,
+    pub truncated: bool,
+    pub files: Vec<FileComparison>,
+}
+
+fn collect(
+    root: &FsPath,
+    dir: &FsPath,
+    opts: &DirDiffOptions,
+    out: &mut BTreeSet<String>,
+) -> std::io::Result<()> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        if out.len() >= opts.max_files {
+            return Ok(());
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !opts.include_hidden && name.starts_with('.') {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            if opts.recursive {
+                collect(root, &path, opts, out)?;
+            }
+            continue;
+        }
+        let matches_ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| {
+                opts.extensions
+                    .iter()
+                    .any(|want| want.trim_start_matches('.').eq_ignore_ascii_case(ext))
+            });
+        if !matches_ext {
+            continue;
+        }
+        if let Ok(rel) = path.strip_prefix(root) {
+            let rel = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            out.insert(rel);
+        }
+    }
+    Ok(())
+}
+
+pub fn list_files(root: &FsPath, opts: &DirDiffOptions) -> DocResult<BTreeSet<String>> {
+    if !root.is_dir() {
+        return Err(DocError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("not a directory: {}", root.display()),
+        )));
+    }
+    let mut out = BTreeSet::new();
+    collect(root, root, opts, &mut out)?;
+    Ok(out)
+}
+
+fn read_json(path: &FsPath) -> Result<Value, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn compare_file(
+    left: &FsPath,
+    right: &FsPath,
+    relative_path: String,
+    include_entries: bool,
+    diff_opts: &DiffOptions,
+    filter: &DiffFilter,
+    cancel: &CancelFlag,
+) -> DocResult<FileComparison> {
+    let failed = |relative_path: String, error: String| FileComparison {
+        relative_path,
+        status: FileStatus::Error,
+        changes: None,
+        error: Some(error),
+        entries: None,
+    };
+    let (l, r) = match (read_json(left), read_json(right)) {
+        (Ok(l), Ok(r)) => (l, r),
+        (Err(e), _) | (_, Err(e)) => return Ok(failed(relative_path, e)),
+    };
+    let entries = filter.apply(compute_diff_with(&l, &r, diff_opts, cancel)?, &l, &r);
+    let status = if entries.is_empty() {
+        FileStatus::Identical
+    } else {
+        FileStatus::Changed
+    };
+    Ok(FileComparison {
+        relative_path,
+        status,
+        changes: (status == FileStatus::Changed).then(|| ChangeCounts::of(&entries)),
+        error: None,
+        entries: (include_entries && status == FileStatus::Changed).then_some(entries),
+    })
+}
+
+fn one_sided(relative_path: String, status: FileStatus) -> FileComparison {
+    FileComparison {
+        relative_path,
+        status,
+        changes: None,
+        error: None,
+        entries: None,
+    }
+}
+
+pub fn diff_directories(
+    left: &FsPath,
+    right: &FsPath,
+    opts: &DirDiffOptions,
+    cancel: &CancelFlag,
+) -> DocResult<DirDiffSummary> {
+    let filter = opts.rules.compile()?;
+    let filter = &filter;
+    let diff_opts = &DiffOptions {
+        array_match: opts.array_match.clone(),
+    };
+    let left_files = list_files(left, opts)?;
+    let right_files = list_files(right, opts)?;
+    let truncated = left_files.len() >= opts.max_files || right_files.len() >= opts.max_files;
+
+    let mut files: Vec<FileComparison> = Vec::new();
+    let mut paired: Vec<String> = Vec::new();
+    for rel in left_files.union(&right_files) {
+        match (left_files.contains(rel), right_files.contains(rel)) {
+            (true, true) => paired.push(rel.clone()),
+            (true, false) => files.push(one_sided(rel.clone(), FileStatus::OnlyLeft)),
+            _ => files.push(one_sided(rel.clone(), FileStatus::OnlyRight)),
+        }
+    }
+
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(paired.len().max(1));
+    let chunk = paired.len().div_ceil(workers).max(1);
+    let compared: Vec<DocResult<Vec<FileComparison>>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = paired
+            .chunks(chunk)
+            .map(|rels| {
+                scope.spawn(move || {
+                    rels.iter()
+                        .map(|rel| {
+                            if cancel.is_cancelled() {
+                                return Err(DocError::Cancelled);
+                            }
+                            compare_file(
+                                &left.join(rel),
+                                &right.join(rel),
+                                rel.clone(),
+                                opts.include_entries,
+                                diff_opts,
+                                filter,
+                                cancel,
+                            )
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join().unwrap_or_else(|_| {
+                    Err(DocError::Edit("directory diff worker panicked".into()))
+                })
+            })
+            .collect()
+    });
+    for batch in compared {
+        files.extend(batch?);
+    }
+    files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+    let mut summary = DirDiffSummary {
+        truncated,
+        ..Default::default()
+    };
+    for f in &files {
+        match f.status {
+            FileStatus::Identical => summary.identical += 1,
+            FileStatus::Changed => summary.changed += 1,
+            FileStatus::OnlyLeft => summary.only_left += 1,
+            FileStatus::OnlyRight => summary.only_right += 1,
+            FileStatus::Error => summary.errors += 1,
+        }
+    }
+    summary.files = files;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    fn write(dir: &FsPath, rel: &str, text: &str) {
+        let path = dir.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn pairs_files_by_relative_path() {
+        let root = TempDir::new("dirdiff-pairs");
+        let (left, right) = (root.join("left"), root.join("right"));
+        write(&left, "app.json", r#"{"port": 80, "debug": false}"#);
+        write(
+            &right,
+            "app.json",
+            r#"{"port": 8080, "debug": false, "tls": true}"#,
+        );
+        write(&left, "nested/db.json", r#"{"host": "db"}"#);
+        write(&right, "nested/db.json", r#"{ "host": "db" }"#);
+        write(&left, "old.json", "{}");
+        write(&right, "new.json", "{}");
+        write(&right, "broken.json", "{");
+        write(&left, "broken.json", "{}");
+        write(&left, "notes.txt", "ignored");
+        write(&left, ".hidden/x.json", "{}");
+
+        let summary = diff_directories(
+            &left,
+            &right,
+            &DirDiffOptions::default(),
+            &CancelFlag::never(),
+        )
+        .unwrap();
+        let statuses: Vec<(&str, FileStatus)> = summary
+            .files
+            .iter()
+            .map(|f| (f.relative_path.as_str(), f.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("app.json", FileStatus::Changed),
+                ("broken.json", FileStatus::Error),
+                ("nested/db.json", FileStatus::Identical),
+                ("new.json", FileStatus::OnlyRight),
+                ("old.json", FileStatus::OnlyLeft),
+            ]
+        );
+        assert_eq!(
+            summary.files[0].changes,
+            Some(ChangeCounts {
+                added: 1,
+                changed: 1,
+                ..Default::default()
+            })
+        );
+        assert!(summary.files[0].entries.is_none());
+        assert_eq!(
+            (
+                summary.identical,
+                summary.changed,
+                summary.only_left,
+                summary.only_right,
+                summary.errors
+            ),
+            (1, 1, 1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn non_recursive_mode_and_entries() {
+        let root = TempDir::new("dirdiff-flat");
+        let (left, right) = (root.join("left"), root.join("right"));
+        write(&left, "a.json", "[1, 2]");
+        write(&right, "a.json", "[1, 3]");
+        write(&left, "sub/b.json", "{}");
+        let opts = DirDiffOptions {
+            recursive: false,
+            include_entries: true,
+            ..Default::default()
+        };
+        let summary = diff_directories(&left, &right, &opts, &CancelFlag::never()).unwrap();
+        assert_eq!(summary.files.len(), 1);
+        assert_eq!(summary.files[0].entries.as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejects_missing_directory() {
+        let root = TempDir::new("dirdiff-missing");
+        assert!(diff_directories(
+            &root.join("nope"),
+            &root,
+            &DirDiffOptions::default(),
+            &CancelFlag::never()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use std::path::Path as FsPath;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+
+use super::types::{DocError, DocHandle, DocResult};
+
+pub fn hash_file(path: &FsPath) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Largest file whose text is kept as the base for a three-way merge.
+const MERGE_BASE_LIMIT: usize = 8 * 1024 * 1024;
+
+/// What was on disk the last time a document was read from or written to
+/// its file.
+#[derive(Debug, Clone)]
+pub struct DiskState {
+    pub hash: String,
+    pub base: Option<Arc<str>>,
+}
+
+impl DiskState {
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let base = (bytes.len() <= MERGE_BASE_LIMIT)
+            .then(|| std::str::from_utf8(bytes).ok().map(Arc::from))
+            .flatten();
+        Self {
+            hash: blake3::hash(bytes).to_hex().to_string(),
+            base,
+        }
+    }
+
+    pub fn read(path: &FsPath) -> std::io::Result<Self> {
+        Ok(Self::from_bytes(&std::fs::read(path)?))
+    }
+}
+
+#[derive(Default)]
+pub struct DiskHashes {
+    known: DashMap<DocHandle, DiskState>,
+}
+
+impl DiskHashes {
+    pub fn record(&self, handle: DocHandle, state: DiskState) {
+        self.known.insert(handle, state);
+    }
+
+    /// Re-reads `path` after a write or retarget; a missing file drops the
+    /// entry so the next save is not checked against stale content.
+    pub fn refresh(&self, handle: DocHandle, path: &FsPath) {
+        match DiskState::read(path) {
+            Ok(state) => self.record(handle, state),
+            Err(_) => self.forget(handle),
+        }
+    }
+
+    pub fn forget(&self, handle: DocHandle) {
+        self.known.remove(&handle);
+    }
+
+    pub fn get(&self, handle: DocHandle) -> Option<String> {
+        self.known.get(&handle).map(|s| s.hash.clone())
+    }
+
+    pub fn base(&self, handle: DocHandle) -> Option<Arc<str>> {
+        self.known.get(&handle).and_then(|s| s.base.clone())
+    }
+}
+
+pub fn check_unchanged(expected: Option<&str>, path: &FsPath) -> DocResult<()> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    match hash_file(path) {
+        Ok(actual) if actual != expected => Err(DocError::Conflict(format!(
+            "{} was changed on disk since it was opened",
+            path.display()
+        ))),
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn detects_external_edits() {
+        let dir = TempDir::new("disk-hash");
+        let path = dir.join("doc.json");
+        std::fs::write(&path, r#"{"a": 1}"#).unwrap();
+        let hashes = DiskHashes::default();
+        let handle = DocHandle::new();
+        hashes.record(handle, DiskState::read(&path).unwrap());
+        assert_eq!(hashes.get(handle), Some(hash_file(&path).unwrap()));
+        assert_eq!(hashes.base(handle).as_deref(), Some(r#"{"a": 1}"#));
+        let known = hashes.get(handle);
+        assert!(check_unchanged(known.as_deref(), &path).is_ok());
+
+        std::fs::write(&path, r#"{"a": 2}"#).unwrap();
+        assert!(matches!(
+            check_unchanged(known.as_deref(), &path),
+            Err(DocError::Conflict(_))
+        ));
+        assert!(check_unchanged(None, &path).is_ok());
+
+        hashes.refresh(handle, &path);
+        assert_eq!(hashes.base(handle).as_deref(), Some(r#"{"a": 2}"#));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(check_unchanged(known.as_deref(), &path).is_ok());
+        hashes.refresh(handle, &path);
+        assert_eq!(hashes.get(handle), None);
+    }
+}
@@ -7,15 +7,17 @@ use regex::RegexBuilder;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::cloud;
 use super::eager::{
     cell, cmp_cell, eager_cell_text_lower, kind_and_child_count_eager, replace_in_value,
     resolve_eager, slice_eager,
 };
 use super::export::{self, export as export_value, ExportFormat};
+use super::file_info;
 use super::grid_filter::{row_passes, GridFilter};
 use super::history::History;
 use super::lazy::LazyDoc;
-use super::ops::{Op, OpDescription, OpOutcome};
+use super::ops::{self, Op, OpDescription, OpOutcome};
 use super::schema_validate::{
     validate as schema_validate_value, SchemaCompileError, SchemaValidationResult,
 };
@@ -231,9 +233,16 @@ impl Document {
 
     pub fn from_file<P: AsRef<FsPath>>(path: P) -> DocResult<Self> {
         let p = path.as_ref();
+        cloud::ensure_local(p)?;
         let size = std::fs::metadata(p)?.len();
         Self::ensure_within_max(size)?;
-        let text = std::fs::read_to_string(p)?;
+        let text = String::from_utf8(std::fs::read(p)?).map_err(|e| {
+            DocError::Parse(format!(
+                "{} is not UTF-8 text (invalid byte at offset {}); use the binary preview to inspect it",
+                p.display(),
+                e.utf8_error().valid_up_to()
+            ))
+        })?;
         let path_str = p.to_string_lossy().into_owned();
         let mut doc = Self::from_text(&text, Some(path_str.clone()))?;
         doc.file_path = Some(path_str);
@@ -337,29 +346,44 @@ impl Document {
         }
     }
 
+    pub fn index_text(&self) -> DocResult<(sonic_rs::FastStr, bool)> {
+        match &self.inner {
+            DocumentImpl::Eager(_) => Ok((self.serialize()?.into(), false)),
+            DocumentImpl::Lazy(d) => Ok((d.shared_source(), true)),
+        }
+    }
+
     pub fn set_file_path(&mut self, path: String) {
         self.file_path = Some(path.clone());
         self.source_path = Some(path);
     }
 
     pub fn save(&mut self, path: Option<String>) -> DocResult<SaveResult> {
+        let text = self.serialize()?;
+        self.save_text(path, text)
+    }
+
+    pub fn save_text(&mut self, path: Option<String>, text: String) -> DocResult<SaveResult> {
         let target = path
             .or_else(|| self.file_path.clone())
             .ok_or_else(|| DocError::Edit("no file path — use Save As".into()))?;
-        let text = self.serialize()?;
-        let new_hash = blake3::hash(text.as_bytes());
-        std::fs::write(&target, text)?;
+        write_atomic(FsPath::new(&target), text.as_bytes())?;
         self.file_path = Some(target.clone());
         self.source_path = Some(target.clone());
-        self.saved_version = self.version;
-        self.saved_hash = new_hash;
-        *self.hash_cache.lock() = Some((self.version, new_hash));
+        self.mark_saved(&text);
         Ok(SaveResult {
             path: target,
             version: self.version,
         })
     }
 
+    pub fn mark_saved(&mut self, text: &str) {
+        let new_hash = blake3::hash(text.as_bytes());
+        self.saved_version = self.version;
+        self.saved_hash = new_hash;
+        *self.hash_cache.lock() = Some((self.version, new_hash));
+    }
+
     pub fn get_slice(&self, path: &Path, range: Range<u32>) -> DocResult<Vec<NodeView>> {
         match &self.inner {
             DocumentImpl::Eager(v) => slice_eager(v, path, range),
@@ -810,11 +834,63 @@ impl Document {
         }
     }
 
-    #[allow(dead_code)]
     pub fn history_lens(&self) -> (usize, usize) {
         (self.history.undo_len(), self.history.redo_len())
     }
 
+    pub fn value_heap_bytes(&self) -> usize {
+        match &self.inner {
+            DocumentImpl::Eager(v) => ops::value_heap_bytes(v),
+            DocumentImpl::Lazy(d) => d.heap_bytes(),
+        }
+    }
+
+    pub fn history_heap_bytes(&self) -> usize {
+        self.history.heap_bytes()
+    }
+
+    pub fn cache_heap_bytes(&self) -> usize {
+        let sort = self
+            .sort_cache
+            .lock()
+            .as_ref()
+            .map_or(0, |c| c.perm.len() * 4);
+        let filter = self
+            .filter_cache
+            .lock()
+            .as_ref()
+            .map_or(0, |c| c.perm.len() * 4);
+        let quick: usize = self
+            .quick_text_cache
+            .lock()
+            .values()
+            .flat_map(|c| c.text.iter())
+            .map(|t| 24 + t.as_ref().map_or(0, String::len))
+            .sum();
+        sort + filter + quick
+    }
+
+    pub fn clear_caches(&self) -> usize {
+        let freed = self.cache_heap_bytes();
+        *self.sort_cache.lock() = None;
+        *self.filter_cache.lock() = None;
+        self.quick_text_cache.lock().clear();
+        freed
+    }
+
+    pub fn can_spill(&self) -> bool {
+        !self.is_dirty() && self.history_lens() == (0, 0)
+    }
+
+    pub fn snapshot_text(&self) -> DocResult<String> {
+        match &self.inner {
+            DocumentImpl::Eager(v) => {
+                serde_json::to_string(v).map_err(|e| DocError::Export(e.to_string()))
+            }
+            DocumentImpl::Lazy(d) => Ok(d.source().to_string()),
+        }
+    }
+
     pub fn history_view(&self) -> HistoryView {
         HistoryView {
             undo: self.history.undo_ops().map(|op| op.describe()).collect(),
@@ -927,14 +1003,51 @@ impl Document {
     }
 }
 
+pub fn write_atomic(target: &FsPath, bytes: &[u8]) -> std::io::Result<()> {
+    let is_link = std::fs::symlink_metadata(target).is_ok_and(|m| m.file_type().is_symlink());
+    if is_link {
+        return match std::fs::canonicalize(target) {
+            Ok(resolved) => write_atomic(&resolved, bytes),
+            Err(_) => std::fs::write(target, bytes),
+        };
+    }
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(".pandia-tmp");
+    let tmp = target.with_file_name(name);
+    let written = std::fs::write(&tmp, bytes)
+        .and_then(|_| file_info::copy_permissions(target, &tmp))
+        .and_then(|_| std::fs::rename(&tmp, target));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    written
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::doc::test_support::TempDir;
 
     fn doc(text: &str) -> Document {
         Document::from_text(text, None).expect("valid JSON in test")
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_keeps_symlinks_in_place() {
+        let dir = TempDir::new("atomic");
+        let real = dir.join("real.json");
+        let link = dir.join("link.json");
+        std::fs::write(&real, "[1]").unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        write_atomic(&link, b"[2]").unwrap();
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(std::fs::read_to_string(&real).unwrap(), "[2]");
+    }
+
     #[test]
     fn parse_invalid_returns_parse_error() {
         let err = Document::from_text("{not json", None).unwrap_err();
@@ -969,8 +1082,8 @@ mod tests {
 
         assert!(matches!(d.save(None), Err(DocError::Edit(_))));
 
-        let mut path = std::env::temp_dir();
-        path.push(format!("pandia-save-test-{}.json", std::process::id()));
+        let dir = TempDir::new("save-test");
+        let path = dir.join("doc.json");
         let path_str = path.to_string_lossy().into_owned();
         let res = d.save(Some(path_str.clone())).unwrap();
         assert_eq!(res.path, path_str);
@@ -989,8 +1102,6 @@ mod tests {
         assert!(d.summary().dirty);
         d.save(None).unwrap(); // writes to remembered path
         assert!(!d.summary().dirty);
-
-        let _ = std::fs::remove_file(&path);
     }
 
     #[test]
@@ -1813,13 +1924,13 @@ mod tests {
     #[test]
     fn export_to_file_writes_streamed_json() {
         let d = doc(r#"{"a":1,"b":[2,3]}"#);
-        let p = std::env::temp_dir().join("pandia_export_to_file_test.json");
+        let dir = TempDir::new("export-to-file");
+        let p = dir.join("export.json");
         let ps = p.to_str().unwrap();
         d.export_to_file(ExportFormat::JsonMin, ps).unwrap();
         assert_eq!(std::fs::read_to_string(&p).unwrap(), r#"{"a":1,"b":[2,3]}"#);
         d.export_to_file(ExportFormat::Json, ps).unwrap();
         assert!(std::fs::read_to_string(&p).unwrap().contains('\n')); // pretty
-        std::fs::remove_file(&p).ok();
     }
 
     #[test]
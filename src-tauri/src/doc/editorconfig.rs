@@ -0,0 +1,220 @@
+use std::path::Path as FsPath;
+
+use serde::Serialize;
+
+use super::settings::LineEnding;
+use super::types::DocResult;
+
+const FILE_NAME: &str = ".editorconfig";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IndentStyle {
+    Space,
+    Tab,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditorConfig {
+    pub indent_style: Option<IndentStyle>,
+    pub indent_size: Option<u8>,
+    pub end_of_line: Option<LineEnding>,
+    pub insert_final_newline: Option<bool>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub charset: Option<String>,
+    pub sources: Vec<String>,
+}
+
+impl EditorConfig {
+    fn set(&mut self, key: &str, value: &str) {
+        let value = value.to_ascii_lowercase();
+        let flag = match value.as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        };
+        match key {
+            "indent_style" => {
+                self.indent_style = match value.as_str() {
+                    "space" => Some(IndentStyle::Space),
+                    "tab" => Some(IndentStyle::Tab),
+                    _ => None,
+                }
+            }
+            "indent_size" => self.indent_size = value.parse().ok().filter(|n| *n <= 8),
+            "end_of_line" => {
+                self.end_of_line = match value.as_str() {
+                    "lf" => Some(LineEnding::Lf),
+                    "crlf" => Some(LineEnding::Crlf),
+                    _ => None,
+                }
+            }
+            "insert_final_newline" => self.insert_final_newline = flag,
+            "trim_trailing_whitespace" => self.trim_trailing_whitespace = flag,
+            "charset" => self.charset = (value != "unset").then_some(value),
+            _ => {}
+        }
+    }
+}
+
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(open) = pattern.find('{') else {
+        return vec![pattern.to_string()];
+    };
+    let Some(len) = pattern[open..].find('}') else {
+        return vec![pattern.to_string()];
+    };
+    let (head, inner, tail) = (
+        &pattern[..open],
+        &pattern[open + 1..open + len],
+        &pattern[open + len + 1..],
+    );
+    inner
+        .split(',')
+        .flat_map(|alt| expand_braces(&format!("{head}{alt}{tail}")))
+        .collect()
+}
+
+fn glob(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) if rest.first() == Some(&b'*') => {
+            let rest = &rest[1..];
+            (0..=text.len()).any(|i| glob(rest, &text[i..]))
+        }
+        Some((b'*', rest)) => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob(rest, &text[i..])),
+        Some((b'?', rest)) => {
+            matches!(text.first(), Some(c) if *c != b'/') && glob(rest, &text[1..])
+        }
+        Some((b'[', rest)) => match (rest.iter().position(|&c| c == b']'), text.first()) {
+            (Some(end), Some(c)) => {
+                let (negate, set) = match rest[..end].split_first() {
+                    Some((b'!', set)) => (true, set),
+                    _ => (false, &rest[..end]),
+                };
+                set.contains(c) != negate && glob(&rest[end + 1..], &text[1..])
+            }
+            _ => false,
+        },
+        Some((&p, rest)) => text.first() == Some(&p) && glob(rest, &text[1..]),
+    }
+}
+
+pub fn section_matches(section: &str, relative: &str) -> bool {
+    let pattern = if section.contains('/') {
+        section.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{section}")
+    };
+    expand_braces(&pattern).iter().any(|p| {
+        glob(p.as_bytes(), relative.as_bytes())
+            || (p.starts_with("**/") && glob(&p.as_bytes()[3..], relative.as_bytes()))
+    })
+}
+
+fn apply_file(config: &mut EditorConfig, text: &str, relative: &str) {
+    let mut active = false;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            active = section_matches(section, relative);
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| active) {
+            config.set(&key.trim().to_ascii_lowercase(), value.trim());
+        }
+    }
+}
+
+fn declares_root(text: &str) -> bool {
+    text.lines()
+        .take_while(|l| !l.trim_start().starts_with('['))
+        .filter_map(|l| l.split_once('='))
+        .any(|(k, v)| {
+            k.trim().eq_ignore_ascii_case("root") && v.trim().eq_ignore_ascii_case("true")
+        })
+}
+
+pub fn resolve(path: &FsPath) -> DocResult<EditorConfig> {
+    let mut files = Vec::new();
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        let candidate = current.join(FILE_NAME);
+        match std::fs::read_to_string(&candidate) {
+            Ok(text) => {
+                let is_root = declares_root(&text);
+                files.push((current.to_path_buf(), candidate, text));
+                if is_root {
+                    break;
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        dir = current.parent();
+    }
+    let mut config = EditorConfig::default();
+    for (base, file, text) in files.iter().rev() {
+        let relative = path
+            .strip_prefix(base)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        apply_file(&mut config, text, &relative);
+        config.sources.push(file.to_string_lossy().into_owned());
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn matches_editorconfig_globs() {
+        assert!(section_matches("*", "a/b/c.json"));
+        assert!(section_matches("*.json", "pkg/package.json"));
+        assert!(section_matches("*.{json,jsonc}", "tsconfig.jsonc"));
+        assert!(!section_matches("*.{json,jsonc}", "notes.md"));
+        assert!(section_matches("locales/*.json", "locales/en.json"));
+        assert!(!section_matches("locales/*.json", "src/locales/en.json"));
+        assert!(section_matches("src/**.json", "src/a/b/c.json"));
+        assert!(section_matches("[Mm]akefile", "Makefile"));
+        assert!(section_matches("data?.json", "data1.json"));
+    }
+
+    #[test]
+    fn cascades_from_the_root_down() {
+        let base = TempDir::new("editorconfig");
+        let nested = base.join("repo/pkg");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(base.join(FILE_NAME), "[*]\nindent_size = 8\n").unwrap();
+        std::fs::write(
+            base.join("repo").join(FILE_NAME),
+            "root = true\n\n[*]\nindent_style = space\nindent_size = 2\nend_of_line = lf\n\n[*.json]\ninsert_final_newline = true\ncharset = utf-8\n",
+        )
+        .unwrap();
+        std::fs::write(
+            nested.join(FILE_NAME),
+            "[package.json]\nindent_style = tab\nend_of_line = crlf\n",
+        )
+        .unwrap();
+
+        let config = resolve(&nested.join("package.json")).unwrap();
+        assert_eq!(config.indent_style, Some(IndentStyle::Tab));
+        assert_eq!(config.indent_size, Some(2));
+        assert_eq!(config.end_of_line, Some(LineEnding::Crlf));
+        assert_eq!(config.insert_final_newline, Some(true));
+        assert_eq!(config.charset.as_deref(), Some("utf-8"));
+        assert_eq!(config.sources.len(), 2);
+
+        let other = resolve(&nested.join("notes.md")).unwrap();
+        assert_eq!(other.indent_style, Some(IndentStyle::Space));
+        assert_eq!(other.insert_final_newline, None);
+    }
+}
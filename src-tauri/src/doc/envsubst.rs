@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::types::DocResult;
+
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?:(:?[-?])([^}]*))?\}|\{\{\s*([A-Za-z_][A-Za-z0-9_.]*)\s*(?:\|([^}]*))?\}\}",
+    )
+    .expect("valid regex")
+});
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum EnvSource {
+    Process,
+    Dotenv { path: String },
+    Map { values: HashMap<String, String> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlaceholderSyntax {
+    Shell,
+    Mustache,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResolvedFrom {
+    Env,
+    Default,
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedPlaceholder {
+    pub name: String,
+    pub raw: String,
+    pub syntax: PlaceholderSyntax,
+    pub line: u32,
+    pub column: u32,
+    pub value: Option<String>,
+    pub from: ResolvedFrom,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveResult {
+    pub original: String,
+    pub resolved: String,
+    pub placeholders: Vec<ResolvedPlaceholder>,
+    pub unresolved: Vec<String>,
+}
+
+fn unquote(raw: &str) -> String {
+    let raw = raw.trim();
+    if raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"') {
+        let inner = &raw[1..raw.len() - 1];
+        let mut out = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        }
+        return out;
+    }
+    if raw.len() >= 2 && raw.starts_with('\'') && raw.ends_with('\'') {
+        return raw[1..raw.len() - 1].to_string();
+    }
+    match raw.find(" #") {
+        Some(i) => raw[..i].trim_end().to_string(),
+        None => raw.to_string(),
+    }
+}
+
+pub fn parse_dotenv(text: &str) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            continue;
+        }
+        vars.insert(key.to_string(), unquote(value));
+    }
+    vars
+}
+
+pub fn load_source(source: &EnvSource) -> DocResult<HashMap<String, String>> {
+    match source {
+        EnvSource::Process => Ok(std::env::vars().collect()),
+        EnvSource::Dotenv { path } => {
+            let text = std::fs::read_to_string(path)?;
+            Ok(parse_dotenv(&text))
+        }
+        EnvSource::Map { values } => Ok(values.clone()),
+    }
+}
+
+fn position(content: &str, offset: usize) -> (u32, u32) {
+    let before = &content[..offset];
+    let line = before.matches('\n').count() as u32 + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() as u32 + 1;
+    (line, column)
+}
+
+pub fn resolve(content: &str, vars: &HashMap<String, String>) -> ResolveResult {
+    let mut placeholders = Vec::new();
+    let mut resolved = String::with_capacity(content.len());
+    let mut last = 0;
+    for caps in PLACEHOLDER.captures_iter(content) {
+        let whole = caps.get(0).expect("match has group 0");
+        let (name, syntax, default) = match caps.get(1) {
+            Some(name) => {
+                let op = caps.get(2).map_or("", |m| m.as_str());
+                let arg = caps.get(3).map(|m| m.as_str().to_string());
+                let set = vars.get(name.as_str());
+                let default = match op {
+                    ":-" => arg.filter(|_| set.is_none_or(|v| v.is_empty())),
+                    "-" => arg.filter(|_| set.is_none()),
+                    _ => None,
+                };
+                (name.as_str(), PlaceholderSyntax::Shell, default)
+            }
+            None => (
+                caps.get(4).expect("mustache name").as_str(),
+                PlaceholderSyntax::Mustache,
+                caps.get(5).map(|m| m.as_str().to_string()),
+            ),
+        };
+        let (value, from) = match (default, vars.get(name)) {
+            (Some(d), _) if syntax == PlaceholderSyntax::Shell => (Some(d), ResolvedFrom::Default),
+            (_, Some(v)) => (Some(v.clone()), ResolvedFrom::Env),
+            (Some(d), None) => (Some(d), ResolvedFrom::Default),
+            (None, None) => (None, ResolvedFrom::Missing),
+        };
+        resolved.push_str(&content[last..whole.start()]);
+        resolved.push_str(value.as_deref().unwrap_or(whole.as_str()));
+        last = whole.end();
+        let (line, column) = position(content, whole.start());
+        placeholders.push(ResolvedPlaceholder {
+            name: name.to_string(),
+            raw: whole.as_str().to_string(),
+            syntax,
+            line,
+            column,
+            value,
+            from,
+        });
+    }
+    resolved.push_str(&content[last..]);
+    let mut unresolved: Vec<String> = placeholders
+        .iter()
+        .filter(|p| p.from == ResolvedFrom::Missing)
+        .map(|p| p.name.clone())
+        .collect();
+    unresolved.sort();
+    unresolved.dedup();
+    ResolveResult {
+        original: content.to_string(),
+        resolved,
+        placeholders,
+        unresolved,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_dotenv_quoting_and_comments() {
+        let env = parse_dotenv(
+            "# comment\nexport HOST=db.local\nPORT=5432 # inline\nNAME=\"a \\\"b\\\"\\nc\"\nRAW='x # y'\nbad line\n",
+        );
+        assert_eq!(env["HOST"], "db.local");
+        assert_eq!(env["PORT"], "5432");
+        assert_eq!(env["NAME"], "a \"b\"\nc");
+        assert_eq!(env["RAW"], "x # y");
+        assert_eq!(env.len(), 4);
+    }
+
+    #[test]
+    fn resolves_shell_and_mustache_placeholders() {
+        let env = vars(&[("HOST", "db"), ("EMPTY", ""), ("user", "ana")]);
+        let text = "{\n  \"url\": \"${HOST}:${PORT:-5432}\",\n  \"x\": \"${EMPTY-keep}${EMPTY:-fill}\",\n  \"who\": \"{{ user }}\", \"missing\": \"${NOPE}\"\n}";
+        let r = resolve(text, &env);
+        assert_eq!(
+            r.resolved,
+            "{\n  \"url\": \"db:5432\",\n  \"x\": \"fill\",\n  \"who\": \"ana\", \"missing\": \"${NOPE}\"\n}"
+        );
+        assert_eq!(r.unresolved, vec!["NOPE".to_string()]);
+        let port = r.placeholders.iter().find(|p| p.name == "PORT").unwrap();
+        assert_eq!(port.from, ResolvedFrom::Default);
+        assert_eq!((port.line, port.column), (2, 19));
+        let user = r.placeholders.iter().find(|p| p.name == "user").unwrap();
+        assert_eq!(user.syntax, PlaceholderSyntax::Mustache);
+        assert_eq!(user.from, ResolvedFrom::Env);
+    }
+}
@@ -4,6 +4,8 @@ use std::fmt::Write as _;
 use std::io::{self, Write};
 use thiserror::Error;
 
+use super::simd;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum ExportFormat {
@@ -67,15 +69,22 @@ pub fn write_json_source<W: Write>(
     pretty: bool,
     writer: W,
 ) -> Result<(), ExportError> {
+    // The minifier only tracks string boundaries, so reject malformed input
+    // up front instead of writing a half-copied file.
+    serde_json::from_str::<serde::de::IgnoredAny>(source)
+        .map_err(|e| ExportError::Serialize(e.to_string()))?;
+    transcode_source(source, pretty, writer)
+}
+
+fn transcode_source<W: Write>(source: &str, pretty: bool, writer: W) -> Result<(), ExportError> {
+    if !pretty {
+        return simd::minify_to(source, writer).map_err(|e| ExportError::Serialize(e.to_string()));
+    }
     let mut de = serde_json::Deserializer::from_str(source);
-    let r = if pretty {
-        let mut ser = serde_json::Serializer::pretty(writer);
-        serde_transcode::transcode(&mut de, &mut ser).map(|_| ())
-    } else {
-        let mut ser = serde_json::Serializer::new(writer);
-        serde_transcode::transcode(&mut de, &mut ser).map(|_| ())
-    };
-    r.map_err(|e| ExportError::Serialize(e.to_string()))
+    let mut ser = serde_json::Serializer::pretty(writer);
+    serde_transcode::transcode(&mut de, &mut ser)
+        .map(|_| ())
+        .map_err(|e| ExportError::Serialize(e.to_string()))
 }
 
 struct CappedWriter {
@@ -102,15 +111,24 @@ impl CappedWriter {
 
 impl Write for CappedWriter {
     fn write(&mut self, data: &[u8]) -> io::Result<usize> {
-        if self.buf.len() + data.len() > self.cap {
-            self.overflowed = true;
+        let room = self.cap.saturating_sub(self.buf.len());
+        if data.len() <= room {
+            self.buf.extend_from_slice(data);
+            return Ok(data.len());
+        }
+        self.overflowed = true;
+        let mut cut = room;
+        while cut > 0 && data[cut] & 0xC0 == 0x80 {
+            cut -= 1;
+        }
+        if cut == 0 {
             return Err(io::Error::new(
                 io::ErrorKind::WriteZero,
                 "preview cap reached",
             ));
         }
-        self.buf.extend_from_slice(data);
-        Ok(data.len())
+        self.buf.extend_from_slice(&data[..cut]);
+        Ok(cut)
     }
     fn flush(&mut self) -> io::Result<()> {
         Ok(())
@@ -128,8 +146,9 @@ pub fn preview_json_source(
     pretty: bool,
     max_bytes: usize,
 ) -> Result<(String, bool), ExportError> {
+    // Previews only read as far as the cap, so skip the full validation pass.
     let mut w = CappedWriter::new(max_bytes);
-    match write_json_source(source, pretty, &mut w) {
+    match transcode_source(source, pretty, &mut w) {
         Ok(()) => Ok(w.finish()),
         Err(e) if w.overflowed => {
             let _ = e;
@@ -245,7 +264,7 @@ fn sanitize_tag(key: &str) -> String {
     s
 }
 
-fn escape_xml(s: &str) -> String {
+pub fn escape_xml(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     for c in s.chars() {
         match c {
@@ -359,6 +378,15 @@ mod tests {
         assert_eq!(String::from_utf8(out).unwrap(), src);
     }
 
+    #[test]
+    fn write_json_source_rejects_malformed_input_before_writing() {
+        for pretty in [false, true] {
+            let mut out = Vec::new();
+            assert!(write_json_source(r#"{"a": [1, 2}"#, pretty, &mut out).is_err());
+            assert!(out.is_empty());
+        }
+    }
+
     #[test]
     fn preview_truncates_at_cap() {
         let big = json!({ "items": vec![json!({"v": "xxxxxxxxxx"}); 500] });
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path as FsPath, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::types::{DocError, DocResult};
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+const MAX_TIMEOUT_MS: u64 = 600_000;
+const DEFAULT_MAX_OUTPUT: usize = 64 * 1024 * 1024;
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+const READER_GRACE: Duration = Duration::from_millis(500);
+
+type Captured = Arc<Mutex<(Vec<u8>, bool)>>;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolOptions {
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
+    #[serde(default)]
+    pub remember: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolResult {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub timed_out: bool,
+    pub truncated: bool,
+    pub duration_ms: u64,
+    pub stdout_is_json: bool,
+}
+
+pub fn consent_path(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let base = super::portable::data_dir(app)?;
+    Ok(base.join("external-tools.json"))
+}
+
+pub fn approved_tools(path: &FsPath) -> std::io::Result<Vec<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn store_tools(path: &FsPath, tools: &[String]) -> std::io::Result<()> {
+    let json =
+        serde_json::to_string_pretty(tools).map_err(|e| std::io::Error::other(e.to_string()))?;
+    std::fs::write(path, json)
+}
+
+pub fn is_approved(path: &FsPath, command: &str) -> std::io::Result<bool> {
+    Ok(approved_tools(path)?.iter().any(|c| c == command))
+}
+
+pub fn approve(path: &FsPath, command: &str) -> std::io::Result<()> {
+    let mut tools = approved_tools(path)?;
+    if !tools.iter().any(|c| c == command) {
+        tools.push(command.to_string());
+        store_tools(path, &tools)?;
+    }
+    Ok(())
+}
+
+pub fn revoke(path: &FsPath, command: &str) -> std::io::Result<bool> {
+    let mut tools = approved_tools(path)?;
+    let before = tools.len();
+    tools.retain(|c| c != command);
+    if tools.len() == before {
+        return Ok(false);
+    }
+    store_tools(path, &tools)?;
+    Ok(true)
+}
+
+pub fn command_line(command: &str, args: &[String]) -> String {
+    std::iter::once(command)
+        .chain(args.iter().map(String::as_str))
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn quote(part: &str) -> String {
+    if part.is_empty() || part.contains(char::is_whitespace) || part.contains('"') {
+        format!("{part:?}")
+    } else {
+        part.to_string()
+    }
+}
+
+pub fn approval_key(command: &str, args: &[String], opts: &ToolOptions) -> String {
+    let mut env: Vec<String> = opts
+        .env
+        .iter()
+        .map(|(k, v)| quote(&format!("{k}={v}")))
+        .collect();
+    env.sort();
+    env.push(command_line(command, args));
+    let line = env.join(" ");
+    match &opts.cwd {
+        Some(cwd) => format!("{line} (in {})", quote(cwd)),
+        None => line,
+    }
+}
+
+fn spawn_reader(reader: impl Read + Send + 'static, cap: usize) -> (Captured, mpsc::Receiver<()>) {
+    let captured: Captured = Arc::default();
+    let (done, finished) = mpsc::channel();
+    let sink = captured.clone();
+    std::thread::spawn(move || {
+        read_capped(reader, cap, &sink);
+        let _ = done.send(());
+    });
+    (captured, finished)
+}
+
+fn read_capped(mut reader: impl Read, cap: usize, sink: &Captured) {
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let mut out = sink.lock();
+                let room = cap.saturating_sub(out.0.len());
+                if n > room {
+                    out.1 = true;
+                }
+                out.0.extend_from_slice(&buf[..n.min(room)]);
+            }
+        }
+    }
+}
+
+fn collect(
+    captured: &Captured,
+    finished: &mpsc::Receiver<()>,
+    deadline: Instant,
+) -> (Vec<u8>, bool) {
+    let complete = finished
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .is_ok();
+    let mut out = captured.lock();
+    (std::mem::take(&mut out.0), out.1 || !complete)
+}
+
+#[cfg(unix)]
+fn isolate(cmd: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    cmd.process_group(0);
+}
+
+#[cfg(not(unix))]
+fn isolate(_cmd: &mut Command) {}
+
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+        // `kill(0, ..)` would hit our own process group and a wrapped id
+        // could name some unrelated group, so only signal a real child pid.
+        if pid > 0 {
+            // SAFETY: `kill` takes no pointers; `isolate` made the child the
+            // leader of its own group, so `-pid` only reaches that group.
+            unsafe {
+                libc::kill(-pid, libc::SIGKILL);
+            }
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+pub fn run_tool(
+    command: &str,
+    args: &[String],
+    stdin_content: Option<&str>,
+    opts: &ToolOptions,
+) -> DocResult<ToolResult> {
+    if command.trim().is_empty() {
+        return Err(DocError::Edit("command is empty".into()));
+    }
+    let timeout = Duration::from_millis(
+        opts.timeout_ms
+            .unwrap_or(DEFAULT_TIMEOUT_MS)
+            .clamp(1, MAX_TIMEOUT_MS),
+    );
+    let cap = opts.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT);
+
+    let mut cmd = Command::new(command);
+    cmd.args(args)
+        .envs(&opts.env)
+        .stdin(if stdin_content.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(cwd) = &opts.cwd {
+        cmd.current_dir(cwd);
+    }
+    isolate(&mut cmd);
+
+    let started = Instant::now();
+    let mut child = cmd.spawn()?;
+
+    if let (Some(mut stdin), Some(content)) = (child.stdin.take(), stdin_content) {
+        let content = content.to_string();
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(content.as_bytes());
+        });
+    }
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let (stdout, stdout_done) = spawn_reader(stdout, cap);
+    let (stderr, stderr_done) = spawn_reader(stderr, cap);
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if started.elapsed() >= timeout {
+            timed_out = true;
+            kill_tree(&mut child);
+            break None;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+
+    let deadline = Instant::now() + READER_GRACE;
+    let (stdout, out_truncated) = collect(&stdout, &stdout_done, deadline);
+    let (stderr, err_truncated) = collect(&stderr, &stderr_done, deadline);
+    let stdout = String::from_utf8_lossy(&stdout).into_owned();
+    let stdout_is_json =
+        !stdout.trim().is_empty() && serde_json::from_str::<serde_json::Value>(&stdout).is_ok();
+
+    Ok(ToolResult {
+        stdout,
+        stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        exit_code: status.and_then(|s| s.code()),
+        success: status.is_some_and(|s| s.success()),
+        timed_out,
+        truncated: out_truncated || err_truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+        stdout_is_json,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn consent_is_recorded_and_revoked() {
+        let dir = TempDir::new("external-consent");
+        let path = dir.join("consent.json");
+        assert!(!is_approved(&path, "jq").unwrap());
+        approve(&path, "jq").unwrap();
+        approve(&path, "jq").unwrap();
+        assert_eq!(approved_tools(&path).unwrap(), vec!["jq".to_string()]);
+        assert!(is_approved(&path, "jq").unwrap());
+        assert!(revoke(&path, "jq").unwrap());
+        assert!(!revoke(&path, "jq").unwrap());
+    }
+
+    #[test]
+    fn command_line_quotes_awkward_args() {
+        assert_eq!(
+            command_line("jq", &args(&[".a b", "-c"])),
+            r#"jq ".a b" -c"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipes_stdin_through_and_detects_json() {
+        let r = run_tool("cat", &[], Some(r#"{"a": 1}"#), &ToolOptions::default()).unwrap();
+        assert!(r.success);
+        assert_eq!(r.exit_code, Some(0));
+        assert_eq!(r.stdout, r#"{"a": 1}"#);
+        assert!(r.stdout_is_json);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn captures_stderr_and_exit_code() {
+        let r = run_tool(
+            "sh",
+            &args(&["-c", "echo oops >&2; exit 3"]),
+            None,
+            &ToolOptions::default(),
+        )
+        .unwrap();
+        assert!(!r.success);
+        assert_eq!(r.exit_code, Some(3));
+        assert_eq!(r.stderr.trim(), "oops");
+        assert!(!r.stdout_is_json);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_processes_that_exceed_timeout() {
+        let opts = ToolOptions {
+            timeout_ms: Some(100),
+            ..Default::default()
+        };
+        let r = run_tool("sleep", &args(&["5"]), None, &opts).unwrap();
+        assert!(r.timed_out);
+        assert!(!r.success);
+        assert!(r.duration_ms < 5000);
+    }
+
+    #[test]
+    fn approval_covers_the_whole_command_line() {
+        let mut opts = ToolOptions::default();
+        let plain = approval_key("sh", &args(&["-c", "jq ."]), &opts);
+        assert_eq!(plain, r#"sh -c "jq .""#);
+        opts.env.insert("B".into(), "2".into());
+        opts.env.insert("A".into(), "x y".into());
+        opts.cwd = Some("/tmp".into());
+        assert_eq!(
+            approval_key("sh", &args(&["-c", "jq ."]), &opts),
+            r#""A=x y" B=2 sh -c "jq ." (in /tmp)"#
+        );
+        let other = approval_key("sh", &args(&["-c", "rm -rf ~"]), &ToolOptions::default());
+        assert_ne!(plain, other);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeout_does_not_wait_for_grandchildren_holding_pipes() {
+        let opts = ToolOptions {
+            timeout_ms: Some(100),
+            ..Default::default()
+        };
+        let r = run_tool("sh", &args(&["-c", "sleep 5 & sleep 5"]), None, &opts).unwrap();
+        assert!(r.timed_out);
+        assert!(r.duration_ms < 3000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn output_is_capped() {
+        let opts = ToolOptions {
+            max_output_bytes: Some(4),
+            ..Default::default()
+        };
+        let r = run_tool("echo", &args(&["hello world"]), None, &opts).unwrap();
+        assert_eq!(r.stdout, "hell");
+        assert!(r.truncated);
+    }
+
+    #[test]
+    fn missing_program_is_an_io_error() {
+        let err = run_tool(
+            "pandia-definitely-missing-tool",
+            &[],
+            None,
+            &ToolOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, DocError::Io(_)));
+        assert!(run_tool("  ", &[], None, &ToolOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use std::path::{Component, Path as FsPath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReplaceWith {
+    Ref,
+    Path,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractResult {
+    pub output_path: String,
+    pub reference: String,
+    pub source_updated: bool,
+}
+
+pub fn relative_ref(from_dir: &FsPath, target: &FsPath) -> String {
+    let from: Vec<Component> = from_dir.components().collect();
+    let to: Vec<Component> = target.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return target.to_string_lossy().replace('\\', "/");
+    }
+    let mut parts: Vec<String> = from[common..].iter().map(|_| "..".to_string()).collect();
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    let joined = parts.join("/");
+    if joined.starts_with("..") {
+        joined
+    } else {
+        format!("./{joined}")
+    }
+}
+
+fn read_json(path: &FsPath) -> DocResult<Value> {
+    let text = std::fs::read_to_string(path)?;
+    serde_json::from_str(&text).map_err(|e| DocError::Parse(format!("{}: {e}", path.display())))
+}
+
+fn write_json(path: &FsPath, value: &Value) -> DocResult<()> {
+    let mut text =
+        serde_json::to_string_pretty(value).map_err(|e| DocError::Export(e.to_string()))?;
+    text.push('\n');
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+fn absolute(path: &FsPath) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+pub fn extract_subtree(
+    source: &FsPath,
+    pointer: &str,
+    output: &FsPath,
+    replace_with: Option<ReplaceWith>,
+) -> DocResult<ExtractResult> {
+    if !pointer.starts_with('/') {
+        return Err(DocError::Edit(format!(
+            "extract needs a JSON Pointer below the root, got {pointer:?}"
+        )));
+    }
+    if absolute(source) == absolute(output) {
+        return Err(DocError::Edit(
+            "the extracted file cannot replace its source".into(),
+        ));
+    }
+    let mut root = read_json(source)?;
+    let slot = root
+        .pointer_mut(pointer)
+        .ok_or_else(|| DocError::Edit(format!("nothing at {pointer}")))?;
+    write_json(output, slot)?;
+
+    let source_dir = absolute(source)
+        .parent()
+        .map(FsPath::to_path_buf)
+        .unwrap_or_default();
+    let reference = relative_ref(&source_dir, &absolute(output));
+    let source_updated = match replace_with {
+        Some(ReplaceWith::Ref) => {
+            *slot = json!({ "$ref": reference });
+            true
+        }
+        Some(ReplaceWith::Path) => {
+            *slot = Value::String(reference.clone());
+            true
+        }
+        None => false,
+    };
+    if source_updated {
+        write_json(source, &root)?;
+    }
+    Ok(ExtractResult {
+        output_path: output.to_string_lossy().into_owned(),
+        reference,
+        source_updated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn extracts_and_replaces_with_ref() {
+        let dir = TempDir::new("extract-ref");
+        let source = dir.join("config.json");
+        std::fs::write(
+            &source,
+            r#"{"db": {"host": "x", "port": 5432}, "name": "app"}"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.join("parts")).unwrap();
+        let output = dir.join("parts").join("db.json");
+        let result = extract_subtree(&source, "/db", &output, Some(ReplaceWith::Ref)).unwrap();
+        assert_eq!(result.reference, "./parts/db.json");
+        assert!(result.source_updated);
+        assert_eq!(
+            read_json(&output).unwrap(),
+            json!({"host": "x", "port": 5432})
+        );
+        assert_eq!(
+            read_json(&source).unwrap(),
+            json!({"db": {"$ref": "./parts/db.json"}, "name": "app"})
+        );
+    }
+
+    #[test]
+    fn extract_without_replacement_leaves_source() {
+        let dir = TempDir::new("extract-copy");
+        let source = dir.join("a.json");
+        std::fs::write(&source, r#"{"list": [1, {"k": true}]}"#).unwrap();
+        let result = extract_subtree(&source, "/list/1", &dir.join("k.json"), None).unwrap();
+        assert!(!result.source_updated);
+        assert_eq!(read_json(&dir.join("k.json")).unwrap(), json!({"k": true}));
+        assert!(std::fs::read_to_string(&source).unwrap().contains("[1, {"));
+        assert!(extract_subtree(&source, "", &dir.join("r.json"), None).is_err());
+        assert!(extract_subtree(&source, "/nope", &dir.join("r.json"), None).is_err());
+        assert!(extract_subtree(&source, "/list", &source, None).is_err());
+    }
+
+    #[test]
+    fn relative_refs_walk_up() {
+        assert_eq!(
+            relative_ref(FsPath::new("/a/b"), FsPath::new("/a/c/d.json")),
+            "../c/d.json"
+        );
+        assert_eq!(
+            relative_ref(FsPath::new("/a"), FsPath::new("/a/d.json")),
+            "./d.json"
+        );
+    }
+}
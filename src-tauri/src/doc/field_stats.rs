@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::jobs::CancelFlag;
+use super::records::RecordReader;
+use super::types::{DocError, DocResult};
+
+const HLL_BITS: u32 = 12;
+const HLL_REGISTERS: usize = 1 << HLL_BITS;
+const MAX_FIELDS: usize = 1_000;
+
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; HLL_REGISTERS],
+        }
+    }
+}
+
+impl HyperLogLog {
+    pub fn insert(&mut self, bytes: &[u8]) {
+        let hash = blake3::hash(bytes);
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&hash.as_bytes()[..8]);
+        let h = u64::from_le_bytes(word);
+        let index = (h >> (64 - HLL_BITS)) as usize;
+        let rest = (h << HLL_BITS) | (1 << (HLL_BITS - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    pub fn estimate(&self) -> u64 {
+        let m = HLL_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeCounts {
+    pub null: u64,
+    pub boolean: u64,
+    pub integer: u64,
+    pub float: u64,
+    pub string: u64,
+    pub array: u64,
+    pub object: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumericStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldProfile {
+    pub field: String,
+    pub present: u64,
+    pub presence: f64,
+    pub types: TypeCounts,
+    pub distinct_estimate: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numeric: Option<NumericStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetProfile {
+    pub records: u64,
+    pub fields: Vec<FieldProfile>,
+    pub fields_truncated: bool,
+    pub bytes_read: u64,
+    pub duration_ms: u64,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    present: u64,
+    types: TypeCounts,
+    distinct: HyperLogLog,
+    numbers: u64,
+    min: f64,
+    max: f64,
+    sum: f64,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: &Value) {
+        self.present += 1;
+        match value {
+            Value::Null => self.types.null += 1,
+            Value::Bool(_) => self.types.boolean += 1,
+            Value::Number(n) => {
+                if n.is_i64() || n.is_u64() {
+                    self.types.integer += 1;
+                } else {
+                    self.types.float += 1;
+                }
+                if let Some(f) = n.as_f64().filter(|f| f.is_finite()) {
+                    if self.numbers == 0 {
+                        (self.min, self.max) = (f, f);
+                    } else {
+                        self.min = self.min.min(f);
+                        self.max = self.max.max(f);
+                    }
+                    self.sum += f;
+                    self.numbers += 1;
+                }
+            }
+            Value::String(_) => self.types.string += 1,
+            Value::Array(_) => self.types.array += 1,
+            Value::Object(_) => self.types.object += 1,
+        }
+        self.distinct.insert(value.to_string().as_bytes());
+    }
+
+    fn finish(self, field: String, records: u64) -> FieldProfile {
+        FieldProfile {
+            field,
+            present: self.present,
+            presence: if records == 0 {
+                0.0
+            } else {
+                self.present as f64 / records as f64
+            },
+            types: self.types,
+            distinct_estimate: self.distinct.estimate().min(self.present),
+            numeric: (self.numbers > 0).then(|| NumericStats {
+                min: self.min,
+                max: self.max,
+                mean: self.sum / self.numbers as f64,
+            }),
+        }
+    }
+}
+
+pub fn profile_values<'a>(
+    field: String,
+    values: impl IntoIterator<Item = &'a Value>,
+) -> FieldProfile {
+    let mut acc = Accumulator::default();
+    for value in values {
+        acc.add(value);
+    }
+    let present = acc.present;
+    acc.finish(field, present)
+}
+
+pub fn profile_records<R: BufRead>(
+    mut reader: RecordReader<R>,
+    fields: Option<&[String]>,
+    cancel: &CancelFlag,
+) -> DocResult<DatasetProfile> {
+    let started = Instant::now();
+    let mut order: Vec<String> = fields.map(<[String]>::to_vec).unwrap_or_default();
+    let mut stats: HashMap<String, Accumulator> = HashMap::new();
+    let mut records = 0u64;
+    let mut fields_truncated = false;
+    while let Some(record) = reader.next_value()? {
+        if cancel.is_cancelled() {
+            return Err(DocError::Cancelled);
+        }
+        records += 1;
+        match fields {
+            Some(fields) => {
+                for field in fields {
+                    let value = if field.starts_with('/') {
+                        record.pointer(field)
+                    } else {
+                        record.get(field)
+                    };
+                    if let Some(value) = value {
+                        stats.entry(field.clone()).or_default().add(value);
+                    }
+                }
+            }
+            None => {
+                let Value::Object(map) = &record else {
+                    continue;
+                };
+                for (key, value) in map {
+                    if !stats.contains_key(key) {
+                        if stats.len() >= MAX_FIELDS {
+                            fields_truncated = true;
+                            continue;
+                        }
+                        order.push(key.clone());
+                    }
+                    stats.entry(key.clone()).or_default().add(value);
+                }
+            }
+        }
+    }
+    let fields = order
+        .into_iter()
+        .map(|field| {
+            let acc = stats.remove(&field).unwrap_or_default();
+            acc.finish(field, records)
+        })
+        .collect();
+    Ok(DatasetProfile {
+        records,
+        fields,
+        fields_truncated,
+        bytes_read: reader.bytes_read(),
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(text: &str, fields: Option<&[String]>) -> DatasetProfile {
+        let reader = RecordReader::new(text.as_bytes(), None).unwrap();
+        profile_records(reader, fields, &CancelFlag::never()).unwrap()
+    }
+
+    #[test]
+    fn counts_presence_types_and_numbers() {
+        let text =
+            "{\"a\": 1, \"b\": \"x\"}\n{\"a\": 2.5}\n{\"a\": null, \"b\": \"x\", \"c\": [1]}\n";
+        let p = profile(text, None);
+        assert_eq!(p.records, 3);
+        let names: Vec<&str> = p.fields.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        let a = &p.fields[0];
+        assert_eq!((a.types.integer, a.types.float, a.types.null), (1, 1, 1));
+        let n = a.numeric.as_ref().unwrap();
+        assert_eq!((n.min, n.max, n.mean), (1.0, 2.5, 1.75));
+        let b = &p.fields[1];
+        assert_eq!((b.present, b.distinct_estimate), (2, 1));
+        assert!((b.presence - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn explicit_fields_accept_pointers() {
+        let text = "[{\"u\": {\"id\": 1}}, {\"u\": {\"id\": 2}}, {\"v\": 0}]";
+        let fields = vec!["/u/id".to_string(), "missing".to_string()];
+        let p = profile(text, Some(&fields));
+        assert_eq!(p.fields.len(), 2);
+        assert_eq!(p.fields[0].present, 2);
+        assert_eq!(p.fields[0].distinct_estimate, 2);
+        assert_eq!(p.fields[1].present, 0);
+    }
+
+    #[test]
+    fn hyperloglog_estimates_within_tolerance() {
+        let mut hll = HyperLogLog::default();
+        for i in 0..50_000u32 {
+            hll.insert(&i.to_le_bytes());
+            hll.insert(&i.to_le_bytes());
+        }
+        let estimate = hll.estimate() as f64;
+        assert!((estimate - 50_000.0).abs() / 50_000.0 < 0.05, "{estimate}");
+    }
+}
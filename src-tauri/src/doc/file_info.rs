@@ -0,0 +1,222 @@
+use std::fs::Metadata;
+use std::path::Path as FsPath;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use super::cloud::{self, CloudStatus};
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStat {
+    pub path: String,
+    pub size: u64,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub symlink_target: Option<String>,
+    pub created_ms: Option<u64>,
+    pub modified_ms: Option<u64>,
+    pub accessed_ms: Option<u64>,
+    pub readonly: bool,
+    pub hidden: bool,
+    pub mode: Option<u32>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub windows_attributes: Option<u32>,
+    pub tags: Vec<String>,
+    pub cloud: CloudStatus,
+}
+
+fn millis(time: std::io::Result<SystemTime>) -> Option<u64> {
+    time.ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as u64)
+}
+
+#[cfg(unix)]
+fn owner(meta: &Metadata) -> (Option<u32>, Option<u32>, Option<u32>) {
+    use std::os::unix::fs::MetadataExt;
+    (
+        Some(meta.mode() & 0o7777),
+        Some(meta.uid()),
+        Some(meta.gid()),
+    )
+}
+
+#[cfg(not(unix))]
+fn owner(_meta: &Metadata) -> (Option<u32>, Option<u32>, Option<u32>) {
+    (None, None, None)
+}
+
+#[cfg(windows)]
+fn windows_attributes(meta: &Metadata) -> Option<u32> {
+    use std::os::windows::fs::MetadataExt;
+    Some(meta.file_attributes())
+}
+
+#[cfg(not(windows))]
+fn windows_attributes(_meta: &Metadata) -> Option<u32> {
+    None
+}
+
+const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+
+#[cfg(target_os = "macos")]
+fn finder_tags(path: &FsPath) -> Vec<String> {
+    let output = std::process::Command::new("mdls")
+        .args(["-raw", "-name", "kMDItemUserTags"])
+        .arg(path)
+        .output();
+    match output {
+        Ok(out) if out.status.success() => parse_mdls_list(&String::from_utf8_lossy(&out.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn finder_tags(_path: &FsPath) -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg_attr(not(any(target_os = "macos", test)), allow(dead_code))]
+fn parse_mdls_list(text: &str) -> Vec<String> {
+    let text = text.trim();
+    let Some(inner) = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) else {
+        return Vec::new();
+    };
+    inner
+        .split(',')
+        .map(|tag| tag.trim().trim_matches('"').to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+pub fn stat(path: &FsPath) -> DocResult<FileStat> {
+    let link = std::fs::symlink_metadata(path)?;
+    let is_symlink = link.file_type().is_symlink();
+    let symlink_target = if is_symlink {
+        std::fs::read_link(path)
+            .ok()
+            .map(|t| t.to_string_lossy().into_owned())
+    } else {
+        None
+    };
+    let meta = if is_symlink {
+        std::fs::metadata(path).unwrap_or(link)
+    } else {
+        link
+    };
+    let (mode, uid, gid) = owner(&meta);
+    let attributes = windows_attributes(&meta);
+    let dotfile = path
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+    Ok(FileStat {
+        path: path.to_string_lossy().into_owned(),
+        size: meta.len(),
+        is_dir: meta.is_dir(),
+        is_symlink,
+        symlink_target,
+        created_ms: millis(meta.created()),
+        modified_ms: millis(meta.modified()),
+        accessed_ms: millis(meta.accessed()),
+        readonly: meta.permissions().readonly(),
+        hidden: dotfile || attributes.is_some_and(|a| a & FILE_ATTRIBUTE_HIDDEN != 0),
+        mode,
+        uid,
+        gid,
+        windows_attributes: attributes,
+        tags: finder_tags(path),
+        cloud: cloud::status(path, Some(&meta)),
+    })
+}
+
+pub fn touch(path: &FsPath) -> DocResult<FileStat> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.set_modified(SystemTime::now())?;
+    stat(path)
+}
+
+pub fn set_permissions(
+    path: &FsPath,
+    mode: Option<u32>,
+    readonly: Option<bool>,
+) -> DocResult<FileStat> {
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    if let Some(mode) = mode {
+        apply_mode(&mut permissions, mode)?;
+    }
+    if let Some(readonly) = readonly {
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(readonly);
+    }
+    std::fs::set_permissions(path, permissions)?;
+    stat(path)
+}
+
+#[cfg(unix)]
+fn apply_mode(permissions: &mut std::fs::Permissions, mode: u32) -> DocResult<()> {
+    use std::os::unix::fs::PermissionsExt;
+    if mode > 0o7777 {
+        return Err(DocError::Edit(format!("invalid file mode {mode:o}")));
+    }
+    permissions.set_mode(mode);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn apply_mode(_permissions: &mut std::fs::Permissions, _mode: u32) -> DocResult<()> {
+    Err(DocError::Edit(
+        "file modes are not supported on this platform".into(),
+    ))
+}
+
+pub fn copy_permissions(from: &FsPath, to: &FsPath) -> std::io::Result<()> {
+    match std::fs::metadata(from) {
+        Ok(meta) => std::fs::set_permissions(to, meta.permissions()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn parses_finder_tag_lists() {
+        assert_eq!(
+            parse_mdls_list("(\n    Red,\n    \"work stuff\"\n)\n"),
+            vec!["Red", "work stuff"]
+        );
+        assert!(parse_mdls_list("(null)").is_empty());
+    }
+
+    #[test]
+    fn stats_touches_and_changes_permissions() {
+        let dir = TempDir::new("file-info");
+        let path = dir.join(".data.json");
+        let _ = std::fs::remove_file(&path);
+
+        let created = touch(&path).unwrap();
+        assert_eq!(created.size, 0);
+        assert!(created.hidden && !created.is_dir && !created.readonly);
+        assert!(created.modified_ms.is_some());
+
+        let locked = set_permissions(&path, None, Some(true)).unwrap();
+        assert!(locked.readonly);
+        #[cfg(unix)]
+        {
+            let opened = set_permissions(&path, Some(0o640), None).unwrap();
+            assert_eq!(opened.mode, Some(0o640));
+            assert!(set_permissions(&path, Some(0o70000), None).is_err());
+        }
+        set_permissions(&path, None, Some(false)).unwrap();
+    }
+}
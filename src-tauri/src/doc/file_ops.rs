@@ -0,0 +1,220 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::Serialize;
+
+use super::file_info;
+use super::types::{DocError, DocResult};
+
+pub const CHANGED_EVENT: &str = "file-operation";
+const MAX_COPIES: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileOperationKind {
+    Trashed,
+    Renamed,
+    Duplicated,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileOperation {
+    pub kind: FileOperationKind,
+    pub from: String,
+    pub to: Option<String>,
+}
+
+pub fn trash(path: &FsPath) -> DocResult<FileOperation> {
+    std::fs::symlink_metadata(path)?;
+    trash::delete(path).map_err(|e| DocError::Io(std::io::Error::other(e.to_string())))?;
+    Ok(FileOperation {
+        kind: FileOperationKind::Trashed,
+        from: path.to_string_lossy().into_owned(),
+        to: None,
+    })
+}
+
+pub fn rename(from: &FsPath, to: &FsPath) -> DocResult<FileOperation> {
+    std::fs::symlink_metadata(from)?;
+    if to.file_name().is_none() {
+        return Err(DocError::Edit(format!(
+            "invalid target name: {}",
+            to.display()
+        )));
+    }
+    let same_file = same_file::is_same_file(from, to).unwrap_or(false);
+    let moved = if same_file {
+        std::fs::rename(from, to)
+    } else {
+        rename_no_replace(from, to)
+    };
+    match moved {
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            return Err(DocError::Edit(format!("{} already exists", to.display())));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices && from.is_file() => {
+            copy_new(from, to)?;
+            std::fs::remove_file(from)?;
+        }
+        other => other?,
+    }
+    Ok(FileOperation {
+        kind: FileOperationKind::Renamed,
+        from: from.to_string_lossy().into_owned(),
+        to: Some(to.to_string_lossy().into_owned()),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn rename_exclusive(from: &FsPath, to: &FsPath) -> Option<std::io::Result<()>> {
+    use std::os::unix::ffi::OsStrExt;
+    let from = std::ffi::CString::new(from.as_os_str().as_bytes()).ok()?;
+    let to = std::ffi::CString::new(to.as_os_str().as_bytes()).ok()?;
+    let status = unsafe {
+        libc::renameat2(
+            libc::AT_FDCWD,
+            from.as_ptr(),
+            libc::AT_FDCWD,
+            to.as_ptr(),
+            libc::RENAME_NOREPLACE,
+        )
+    };
+    if status == 0 {
+        return Some(Ok(()));
+    }
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::EINVAL) | Some(libc::ENOSYS) => None,
+        _ => Some(Err(err)),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn rename_exclusive(from: &FsPath, to: &FsPath) -> Option<std::io::Result<()>> {
+    use std::os::unix::ffi::OsStrExt;
+    let from = std::ffi::CString::new(from.as_os_str().as_bytes()).ok()?;
+    let to = std::ffi::CString::new(to.as_os_str().as_bytes()).ok()?;
+    let status = unsafe { libc::renamex_np(from.as_ptr(), to.as_ptr(), libc::RENAME_EXCL) };
+    if status == 0 {
+        return Some(Ok(()));
+    }
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::ENOTSUP) => None,
+        _ => Some(Err(err)),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn rename_exclusive(_from: &FsPath, _to: &FsPath) -> Option<std::io::Result<()>> {
+    None
+}
+
+fn rename_no_replace(from: &FsPath, to: &FsPath) -> std::io::Result<()> {
+    if let Some(result) = rename_exclusive(from, to) {
+        return result;
+    }
+    if !std::fs::symlink_metadata(from)?.is_dir() {
+        match std::fs::hard_link(from, to) {
+            Ok(()) => return std::fs::remove_file(from),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Err(e),
+            Err(_) => {}
+        }
+    }
+    if std::fs::symlink_metadata(to).is_ok() {
+        return Err(std::io::ErrorKind::AlreadyExists.into());
+    }
+    std::fs::rename(from, to)
+}
+
+pub fn duplicate_name(path: &FsPath, exists: impl Fn(&FsPath) -> bool) -> Option<PathBuf> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (1..=MAX_COPIES)
+        .map(|n| match n {
+            1 => path.with_file_name(format!("{stem} copy{ext}")),
+            n => path.with_file_name(format!("{stem} copy {n}{ext}")),
+        })
+        .find(|candidate| !exists(candidate))
+}
+
+fn copy_new(from: &FsPath, to: &FsPath) -> std::io::Result<()> {
+    let mut source = std::fs::File::open(from)?;
+    let mut target = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(to)?;
+    let copied = std::io::copy(&mut source, &mut target)
+        .and_then(|_| target.sync_all())
+        .and_then(|_| file_info::copy_permissions(from, to));
+    if copied.is_err() {
+        let _ = std::fs::remove_file(to);
+    }
+    copied
+}
+
+pub fn duplicate(path: &FsPath) -> DocResult<FileOperation> {
+    if !path.is_file() {
+        return Err(DocError::Edit(format!("{} is not a file", path.display())));
+    }
+    let target = duplicate_name(path, FsPath::exists)
+        .ok_or_else(|| DocError::Edit(format!("too many copies of {}", path.display())))?;
+    copy_new(path, &target)?;
+    Ok(FileOperation {
+        kind: FileOperationKind::Duplicated,
+        from: path.to_string_lossy().into_owned(),
+        to: Some(target.to_string_lossy().into_owned()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn picks_the_next_free_copy_name() {
+        let taken = |p: &FsPath| p.ends_with("data copy.json") || p.ends_with("data copy 2.json");
+        assert_eq!(
+            duplicate_name(FsPath::new("/x/data.json"), taken).unwrap(),
+            PathBuf::from("/x/data copy 3.json")
+        );
+        assert_eq!(
+            duplicate_name(FsPath::new("/x/Makefile"), |_| false).unwrap(),
+            PathBuf::from("/x/Makefile copy")
+        );
+    }
+
+    #[test]
+    fn renames_and_duplicates_without_clobbering() {
+        let dir = TempDir::new("file-ops-ops");
+        let a = dir.join("a.json");
+        let b = dir.join("b.json");
+        std::fs::write(&a, "[1]").unwrap();
+        std::fs::write(&b, "[2]").unwrap();
+
+        assert!(matches!(rename(&a, &b), Err(DocError::Edit(_))));
+        let c = dir.join("c.json");
+        let op = rename(&a, &c).unwrap();
+        assert_eq!(op.kind, FileOperationKind::Renamed);
+        assert!(!a.exists());
+        assert_eq!(std::fs::read_to_string(&c).unwrap(), "[1]");
+
+        let upper = dir.join("C.json");
+        if !upper.exists() {
+            std::fs::write(&upper, "[3]").unwrap();
+            assert!(matches!(rename(&c, &upper), Err(DocError::Edit(_))));
+            assert_eq!(std::fs::read_to_string(&upper).unwrap(), "[3]");
+            std::fs::remove_file(&upper).unwrap();
+        }
+
+        let copy = duplicate(&c).unwrap();
+        let copied = PathBuf::from(copy.to.unwrap());
+        assert_eq!(copied, dir.join("c copy.json"));
+        assert_eq!(std::fs::read_to_string(&copied).unwrap(), "[1]");
+        assert!(duplicate(&dir).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use super::positions::LineIndex;
+use super::simd::minify_to;
+use super::syntax::{self, SyntaxReport};
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FragmentKind {
+    Value,
+    Members,
+    Elements,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FragmentResult {
+    pub kind: FragmentKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FragmentValidation {
+    pub kind: Option<FragmentKind>,
+    pub report: SyntaxReport,
+}
+
+struct Fragment<'a> {
+    kind: FragmentKind,
+    leading: &'a str,
+    body: &'a str,
+    trailing: &'a str,
+}
+
+impl FragmentKind {
+    fn wrap(self, body: &str) -> String {
+        match self {
+            FragmentKind::Value => body.to_string(),
+            FragmentKind::Members => format!("{{{body}}}"),
+            FragmentKind::Elements => format!("[{body}]"),
+        }
+    }
+}
+
+fn split(text: &str) -> (&str, &str, &str) {
+    let body = text.trim();
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let mut end = body.len();
+    if body.ends_with(',') {
+        end -= 1;
+    }
+    let trimmed = body[..end].trim_end();
+    let trailing_start = leading.len() + trimmed.len();
+    (leading, trimmed, &text[trailing_start..])
+}
+
+fn classify(text: &str) -> Option<(Fragment<'_>, Value)> {
+    let (leading, body, trailing) = split(text);
+    [
+        FragmentKind::Value,
+        FragmentKind::Members,
+        FragmentKind::Elements,
+    ]
+    .into_iter()
+    .find_map(|kind| {
+        let value = serde_json::from_str::<Value>(&kind.wrap(body)).ok()?;
+        Some((
+            Fragment {
+                kind,
+                leading,
+                body,
+                trailing,
+            },
+            value,
+        ))
+    })
+}
+
+fn unwrap(kind: FragmentKind, wrapped: &str) -> String {
+    if kind == FragmentKind::Value {
+        return wrapped.to_string();
+    }
+    let inner = &wrapped[1..wrapped.len() - 1];
+    let lines: Vec<&str> = inner.lines().filter(|l| !l.trim().is_empty()).collect();
+    let strip = lines
+        .iter()
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|l| &l[strip..])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn reindent(text: &str, base_indent: u32) -> String {
+    let pad = " ".repeat(base_indent as usize);
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 || line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{pad}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn parse_error(text: &str) -> DocError {
+    match syntax::check(text).diagnostics.first() {
+        Some(d) => DocError::Parse(format!(
+            "selection is not JSON: {} (line {}, column {})",
+            d.message, d.line, d.column
+        )),
+        None => DocError::Parse("selection is not JSON".into()),
+    }
+}
+
+pub fn format_value(value: &Value, indent: u8) -> DocResult<String> {
+    if indent == 0 {
+        return serde_json::to_string(value).map_err(|e| DocError::Export(e.to_string()));
+    }
+    let pad = " ".repeat(indent.min(8) as usize);
+    let mut out = Vec::new();
+    let mut ser = serde_json::Serializer::with_formatter(
+        &mut out,
+        serde_json::ser::PrettyFormatter::with_indent(pad.as_bytes()),
+    );
+    serde::Serialize::serialize(value, &mut ser).map_err(|e| DocError::Export(e.to_string()))?;
+    String::from_utf8(out).map_err(|e| DocError::Export(e.to_string()))
+}
+
+pub fn format(text: &str, indent: u8, base_indent: u32) -> DocResult<FragmentResult> {
+    let (fragment, value) = classify(text).ok_or_else(|| parse_error(text))?;
+    let body = unwrap(fragment.kind, &format_value(&value, indent)?);
+    Ok(FragmentResult {
+        kind: fragment.kind,
+        text: format!(
+            "{}{}{}",
+            fragment.leading,
+            reindent(&body, base_indent),
+            fragment.trailing
+        ),
+    })
+}
+
+pub fn minify(text: &str) -> DocResult<FragmentResult> {
+    let (fragment, _) = classify(text).ok_or_else(|| parse_error(text))?;
+    let mut out = Vec::with_capacity(fragment.body.len());
+    minify_to(fragment.body, &mut out)?;
+    let body = String::from_utf8(out).map_err(|e| DocError::Export(e.to_string()))?;
+    Ok(FragmentResult {
+        kind: fragment.kind,
+        text: format!("{}{}{}", fragment.leading, body, fragment.trailing.trim()),
+    })
+}
+
+pub fn validate(text: &str) -> FragmentValidation {
+    if let Some((fragment, _)) = classify(text) {
+        let shift = fragment.leading.len() as u32;
+        let mut report = syntax::check(&fragment.kind.wrap(fragment.body));
+        let wrapper = u32::from(fragment.kind != FragmentKind::Value);
+        let lines = LineIndex::new(text);
+        for d in &mut report.diagnostics {
+            d.offset = (d.offset + shift).saturating_sub(wrapper);
+            d.end_offset = (d.end_offset + shift).saturating_sub(wrapper);
+            let at = lines.position(d.offset as usize);
+            d.line = at.line;
+            d.column = at.column;
+        }
+        return FragmentValidation {
+            kind: Some(fragment.kind),
+            report,
+        };
+    }
+    FragmentValidation {
+        kind: None,
+        report: syntax::check(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_members_and_elements_in_place() {
+        let members = format(r#"  "a": {"b":1}, "c": [true],"#, 2, 4).unwrap();
+        assert_eq!(members.kind, FragmentKind::Members);
+        assert_eq!(
+            members.text,
+            "  \"a\": {\n      \"b\": 1\n    },\n    \"c\": [\n      true\n    ],"
+        );
+        let elements = format("1, {\"x\":null}", 0, 0).unwrap();
+        assert_eq!(elements.kind, FragmentKind::Elements);
+        assert_eq!(elements.text, "1,{\"x\":null}");
+        assert!(format("\"a\": ", 2, 0).is_err());
+    }
+
+    #[test]
+    fn minifies_without_rewriting_numbers() {
+        let out = minify("\n  \"n\": 1.50,\n  \"s\": \"a b\"\n").unwrap();
+        assert_eq!(out.kind, FragmentKind::Members);
+        assert_eq!(out.text, "\n  \"n\":1.50,\"s\":\"a b\"");
+        assert_eq!(minify("[1, 2]").unwrap().text, "[1,2]");
+    }
+
+    #[test]
+    fn validates_fragments_and_reports_raw_errors() {
+        assert!(validate("\"k\": [1, 2]").report.valid);
+        assert_eq!(validate("\"k\": [1, 2]").kind, Some(FragmentKind::Members));
+        let dup = validate("\"a\": 1,\n\"a\": 2");
+        let d = &dup.report.diagnostics[0];
+        assert_eq!((d.line, d.column, d.offset), (2, 1, 8));
+        let broken = validate("{\"a\": tru}");
+        assert_eq!(broken.kind, None);
+        assert!(!broken.report.valid);
+    }
+}
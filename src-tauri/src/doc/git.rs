@@ -0,0 +1,231 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use git2::{Oid, Repository, Sort, Status};
+use serde::Serialize;
+
+use super::timefmt;
+use super::types::{DocError, DocResult};
+
+const MAX_HISTORY_WALK: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileState {
+    Unmodified,
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    Ignored,
+    Conflicted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitInfo {
+    pub id: String,
+    pub summary: String,
+    pub author: String,
+    pub time: String,
+    pub age_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitFileStatus {
+    pub repo_root: String,
+    pub relative_path: String,
+    pub branch: Option<String>,
+    pub state: FileState,
+    pub staged: bool,
+    pub last_commit: Option<CommitInfo>,
+}
+
+pub fn git_err(e: git2::Error) -> DocError {
+    DocError::Io(std::io::Error::other(format!("git: {}", e.message())))
+}
+
+fn open(path: &FsPath) -> DocResult<(Repository, PathBuf)> {
+    let abs = std::fs::canonicalize(path)?;
+    let repo = Repository::discover(&abs).map_err(git_err)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| DocError::Edit("repository has no working directory".into()))?;
+    let workdir = std::fs::canonicalize(workdir)?;
+    let rel = abs
+        .strip_prefix(&workdir)
+        .map_err(|_| DocError::Edit(format!("{} is outside the repository", path.display())))?
+        .to_path_buf();
+    Ok((repo, rel))
+}
+
+fn state_of(status: Status) -> (FileState, bool) {
+    let staged = status.intersects(
+        Status::INDEX_NEW
+            | Status::INDEX_MODIFIED
+            | Status::INDEX_DELETED
+            | Status::INDEX_RENAMED
+            | Status::INDEX_TYPECHANGE,
+    );
+    let state = if status.is_conflicted() {
+        FileState::Conflicted
+    } else if status.is_ignored() {
+        FileState::Ignored
+    } else if status.is_wt_new() {
+        FileState::Untracked
+    } else if status.is_index_new() {
+        FileState::Added
+    } else if status.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+        FileState::Deleted
+    } else if status.intersects(Status::INDEX_RENAMED | Status::WT_RENAMED) {
+        FileState::Renamed
+    } else if status.intersects(
+        Status::INDEX_MODIFIED
+            | Status::WT_MODIFIED
+            | Status::INDEX_TYPECHANGE
+            | Status::WT_TYPECHANGE,
+    ) {
+        FileState::Modified
+    } else {
+        FileState::Unmodified
+    };
+    (state, staged)
+}
+
+fn blob_at(commit: &git2::Commit<'_>, rel: &FsPath) -> Option<Oid> {
+    let tree = commit.tree().ok()?;
+    tree.get_path(rel).ok().map(|entry| entry.id())
+}
+
+fn last_commit_for(repo: &Repository, rel: &FsPath) -> DocResult<Option<CommitInfo>> {
+    let Ok(head) = repo.head() else {
+        return Ok(None);
+    };
+    let Some(head_oid) = head.target() else {
+        return Ok(None);
+    };
+    let mut walk = repo.revwalk().map_err(git_err)?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+        .map_err(git_err)?;
+    walk.push(head_oid).map_err(git_err)?;
+    for oid in walk.take(MAX_HISTORY_WALK) {
+        let commit = repo.find_commit(oid.map_err(git_err)?).map_err(git_err)?;
+        let Some(blob) = blob_at(&commit, rel) else {
+            continue;
+        };
+        let unchanged_in_parent = commit
+            .parents()
+            .any(|parent| blob_at(&parent, rel) == Some(blob));
+        if !unchanged_in_parent {
+            let seconds = commit.time().seconds().max(0) as u64;
+            let now = timefmt::now_millis() / 1000;
+            return Ok(Some(CommitInfo {
+                id: commit.id().to_string(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+                author: commit.author().name().unwrap_or_default().to_string(),
+                time: timefmt::rfc3339_utc(seconds * 1000),
+                age_seconds: now.saturating_sub(seconds),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+pub fn status(path: &FsPath) -> DocResult<GitFileStatus> {
+    let (repo, rel) = open(path)?;
+    let flags = repo.status_file(&rel).map_err(git_err)?;
+    let (state, staged) = state_of(flags);
+    let branch = repo
+        .head()
+        .ok()
+        .and_then(|h| h.shorthand().map(str::to_string));
+    let last_commit = last_commit_for(&repo, &rel)?;
+    Ok(GitFileStatus {
+        repo_root: repo
+            .workdir()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        relative_path: rel.to_string_lossy().replace('\\', "/"),
+        branch,
+        state,
+        staged,
+        last_commit,
+    })
+}
+
+pub fn show_head(path: &FsPath) -> DocResult<Option<String>> {
+    let (repo, rel) = open(path)?;
+    let Ok(head) = repo.head() else {
+        return Ok(None);
+    };
+    let tree = head.peel_to_tree().map_err(git_err)?;
+    let Ok(entry) = tree.get_path(&rel) else {
+        return Ok(None);
+    };
+    let blob = repo.find_blob(entry.id()).map_err(git_err)?;
+    String::from_utf8(blob.content().to_vec())
+        .map(Some)
+        .map_err(|_| DocError::Parse("committed file is not valid UTF-8".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use git2::{IndexAddOption, Signature};
+
+    fn temp_repo(name: &str) -> (TempDir, Repository) {
+        let dir = TempDir::new(&format!("git-{name}"));
+        let repo = Repository::init(&dir).unwrap();
+        (dir, repo)
+    }
+
+    fn commit_all(repo: &Repository, message: &str) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"].iter(), IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Tester", "tester@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit<'_>> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap();
+    }
+
+    #[test]
+    fn untracked_then_committed_then_modified() {
+        let (dir, repo) = temp_repo("status");
+        let file = dir.join("config.json");
+        std::fs::write(&file, r#"{"a": 1}"#).unwrap();
+        assert_eq!(status(&file).unwrap().state, FileState::Untracked);
+        assert_eq!(show_head(&file).unwrap(), None);
+
+        commit_all(&repo, "add config");
+        std::fs::write(dir.join("other.json"), "{}").unwrap();
+        commit_all(&repo, "add other");
+
+        let s = status(&file).unwrap();
+        assert_eq!(s.state, FileState::Unmodified);
+        assert_eq!(s.relative_path, "config.json");
+        assert_eq!(s.last_commit.unwrap().summary, "add config");
+
+        std::fs::write(&file, r#"{"a": 2}"#).unwrap();
+        let s = status(&file).unwrap();
+        assert_eq!(s.state, FileState::Modified);
+        assert!(!s.staged);
+        assert_eq!(show_head(&file).unwrap().as_deref(), Some(r#"{"a": 1}"#));
+    }
+
+    #[test]
+    fn paths_outside_a_repository_error() {
+        let dir = TempDir::new("nogit");
+        let file = dir.join("x.json");
+        std::fs::write(&file, "{}").unwrap();
+        if Repository::discover(&dir).is_err() {
+            assert!(status(&file).is_err());
+        }
+    }
+}
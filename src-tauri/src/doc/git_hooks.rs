@@ -0,0 +1,421 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+
+use super::fragment::format_value;
+use super::git::git_err;
+use super::schema_refs::parse_schema_text;
+use super::types::{DocError, DocResult};
+use super::watch::{Checker, FileCheck, ValidationProfile};
+
+const HOOK_NAME: &str = "pre-commit";
+const BACKUP_SUFFIX: &str = ".pandia-backup";
+const MARKER: &str = "# pandia-pre-commit";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HookChecks {
+    pub validate: bool,
+    pub lint: bool,
+    pub format: bool,
+    pub indent: u8,
+    pub schema_path: Option<String>,
+    pub extensions: Vec<String>,
+}
+
+impl Default for HookChecks {
+    fn default() -> Self {
+        Self {
+            validate: true,
+            lint: true,
+            format: false,
+            indent: 2,
+            schema_path: None,
+            extensions: vec!["json".into()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookInstall {
+    pub hook_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_path: Option<String>,
+}
+
+fn hooks_dir(repo: &Repository) -> PathBuf {
+    let configured = repo
+        .config()
+        .and_then(|c| c.get_path("core.hooksPath"))
+        .ok();
+    match (configured, repo.workdir()) {
+        (Some(dir), _) if dir.is_absolute() => dir,
+        (Some(dir), Some(workdir)) => workdir.join(dir),
+        _ => repo.path().join("hooks"),
+    }
+}
+
+fn hook_path(repo_path: &FsPath) -> DocResult<PathBuf> {
+    let repo = Repository::discover(repo_path).map_err(git_err)?;
+    Ok(hooks_dir(&repo).join(HOOK_NAME))
+}
+
+fn backup_of(hook: &FsPath) -> PathBuf {
+    let mut name = hook.file_name().unwrap_or_default().to_os_string();
+    name.push(BACKUP_SUFFIX);
+    hook.with_file_name(name)
+}
+
+fn is_ours(hook: &FsPath) -> bool {
+    std::fs::read_to_string(hook).is_ok_and(|text| text.contains(MARKER))
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn script(exe: &str, checks: &HookChecks) -> String {
+    let patterns: Vec<String> = checks
+        .extensions
+        .iter()
+        .map(|ext| shell_quote(&format!("*.{}", ext.trim_start_matches('.'))))
+        .collect();
+    let mut flags = String::from(" --staged");
+    if !checks.validate {
+        flags.push_str(" --no-validate");
+    }
+    if !checks.lint {
+        flags.push_str(" --no-lint");
+    }
+    if checks.format {
+        flags.push_str(&format!(" --format --indent {}", checks.indent));
+    }
+    if let Some(schema) = checks.schema_path.as_deref().filter(|_| checks.validate) {
+        flags.push_str(&format!(" --schema {}", shell_quote(schema)));
+    }
+    format!(
+        "#!/bin/sh\n{MARKER}\n# Installed by Pandia; remove it from the app or delete this file.\n\
+         PANDIA={}\n\
+         PREVIOUS=\"$(dirname \"$0\")/{HOOK_NAME}{BACKUP_SUFFIX}\"\n\
+         if [ -x \"$PREVIOUS\" ]; then \"$PREVIOUS\" \"$@\" || exit $?; fi\n\
+         staged() {{ git diff --cached --name-only --diff-filter=ACM -z -- {}; }}\n\
+         [ -z \"$(staged | tr -d '\\0')\" ] && exit 0\n\
+         staged | xargs -0 \"$PANDIA\" --check{flags} || exit 1\n",
+        shell_quote(exe),
+        patterns.join(" ")
+    )
+}
+
+pub fn install(repo_path: &FsPath, checks: &HookChecks) -> DocResult<HookInstall> {
+    let hook = hook_path(repo_path)?;
+    let exe = std::env::current_exe()?;
+    if let Some(dir) = hook.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut backup_path = None;
+    if hook.exists() && !is_ours(&hook) {
+        let backup = backup_of(&hook);
+        if backup.exists() {
+            return Err(DocError::Edit(format!(
+                "{} already exists; remove it before installing",
+                backup.display()
+            )));
+        }
+        std::fs::rename(&hook, &backup)?;
+        backup_path = Some(backup.to_string_lossy().into_owned());
+    }
+    std::fs::write(&hook, script(&exe.to_string_lossy(), checks))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(HookInstall {
+        hook_path: hook.to_string_lossy().into_owned(),
+        backup_path,
+    })
+}
+
+pub fn uninstall(repo_path: &FsPath) -> DocResult<bool> {
+    let hook = hook_path(repo_path)?;
+    if !is_ours(&hook) {
+        return Ok(false);
+    }
+    std::fs::remove_file(&hook)?;
+    let backup = backup_of(&hook);
+    if backup.exists() {
+        std::fs::rename(&backup, &hook)?;
+    }
+    Ok(true)
+}
+
+fn format_file(path: &FsPath, indent: u8) -> DocResult<bool> {
+    let text = std::fs::read_to_string(path)?;
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) else {
+        return Ok(false);
+    };
+    let formatted = format_value(&value, indent)? + "\n";
+    if formatted == text {
+        return Ok(false);
+    }
+    std::fs::write(path, formatted)?;
+    Ok(true)
+}
+
+fn staged_text(repo: &Repository, file: &str) -> DocResult<String> {
+    let index = repo.index().map_err(git_err)?;
+    let entry = index
+        .get_path(FsPath::new(file), 0)
+        .ok_or_else(|| DocError::Edit(format!("{file} is not staged")))?;
+    let blob = repo.find_blob(entry.id).map_err(git_err)?;
+    String::from_utf8(blob.content().to_vec())
+        .map_err(|_| DocError::Parse(format!("{file} is not valid UTF-8")))
+}
+
+fn format_staged(repo: &Repository, file: &str, indent: u8) -> DocResult<bool> {
+    let text = staged_text(repo, file)?;
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) else {
+        return Ok(false);
+    };
+    let formatted = format_value(&value, indent)? + "\n";
+    if formatted == text {
+        return Ok(false);
+    }
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| DocError::Edit("repository has no working tree".into()))?;
+    let path = workdir.join(file);
+    if std::fs::read(&path).ok().as_deref() != Some(text.as_bytes()) {
+        return Err(DocError::Edit(format!(
+            "{file} has unstaged changes; stage or stash them before formatting"
+        )));
+    }
+    let mut index = repo.index().map_err(git_err)?;
+    let mut entry = index
+        .get_path(FsPath::new(file), 0)
+        .ok_or_else(|| DocError::Edit(format!("{file} is not staged")))?;
+    entry.id = repo.blob(formatted.as_bytes()).map_err(git_err)?;
+    entry.file_size = formatted.len() as u32;
+    index.add(&entry).map_err(git_err)?;
+    index.write().map_err(git_err)?;
+    std::fs::write(&path, formatted)?;
+    Ok(true)
+}
+
+fn check_staged(
+    repo: &Repository,
+    checker: &Checker,
+    file: &str,
+    format: Option<u8>,
+) -> DocResult<FileCheck> {
+    if let Some(indent) = format {
+        if format_staged(repo, file, indent)? {
+            eprintln!("{file}: formatted");
+        }
+    }
+    Ok(checker.check(file, &staged_text(repo, file)?))
+}
+
+fn report(check: &FileCheck) {
+    if let Some(err) = &check.parse_error {
+        eprintln!("{}: parse error: {err}", check.path);
+    }
+    for err in &check.schema_errors {
+        eprintln!("{}: {}: {}", check.path, err.instance_path, err.message);
+    }
+    for f in &check.lint_findings {
+        eprintln!(
+            "{}:{}:{}: {:?} [{}] {}",
+            check.path, f.line, f.column, f.severity, f.rule, f.message
+        );
+    }
+}
+
+pub fn run_cli(args: &[String]) -> Result<bool, String> {
+    let mut profile = ValidationProfile::default();
+    let (mut validate, mut format, mut indent, mut staged) = (true, false, 2u8, false);
+    let mut files = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--check" => {}
+            "--staged" => staged = true,
+            "--no-validate" => validate = false,
+            "--no-lint" => profile.lint = false,
+            "--format" => format = true,
+            "--indent" => {
+                indent = iter
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("--indent expects a number")?
+            }
+            "--schema" => profile.schema_path = iter.next().cloned(),
+            other => files.push(other.to_string()),
+        }
+    }
+    let schema = match profile.schema_path.as_deref().filter(|_| validate) {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+            Some(parse_schema_text(&text).map_err(|e| format!("{path}: {e}"))?)
+        }
+        None => None,
+    };
+    let root = std::env::current_dir().map_err(|e| e.to_string())?;
+    let checker = Checker::new(&root, schema.as_ref(), &profile).map_err(|e| e.to_string())?;
+    let repo = if staged {
+        Some(Repository::discover(&root).map_err(|e| e.message().to_string())?)
+    } else {
+        None
+    };
+    let mut passed = true;
+    for file in &files {
+        if let Some(repo) = &repo {
+            let check = check_staged(repo, &checker, file, format.then_some(indent))
+                .map_err(|e| format!("{file}: {e}"))?;
+            report(&check);
+            passed &= check.passed;
+            continue;
+        }
+        let path = FsPath::new(file);
+        if format && format_file(path, indent).map_err(|e| format!("{file}: {e}"))? {
+            eprintln!("{file}: formatted");
+        }
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{file}: {e}"))?;
+        let check = checker.check(file, &text);
+        report(&check);
+        passed &= check.passed;
+    }
+    Ok(passed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    fn temp_repo(name: &str) -> TempDir {
+        let dir = TempDir::new(&format!("git-hooks-{name}"));
+        Repository::init(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn install_backs_up_and_uninstall_restores_existing_hook() {
+        let dir = temp_repo("install");
+        let hook = dir.join(".git/hooks/pre-commit");
+        std::fs::create_dir_all(hook.parent().unwrap()).unwrap();
+        std::fs::write(&hook, "#!/bin/sh\nexit 0\n").unwrap();
+
+        let checks = HookChecks {
+            format: true,
+            schema_path: Some("schemas/app's.json".into()),
+            ..HookChecks::default()
+        };
+        let installed = install(&dir, &checks).unwrap();
+        assert!(installed.backup_path.is_some());
+        let text = std::fs::read_to_string(&hook).unwrap();
+        assert!(text.contains(MARKER));
+        assert!(
+            text.contains("--check --staged --format --indent 2 --schema 'schemas/app'\\''s.json'")
+        );
+        assert!(!text.contains("git add"));
+
+        assert!(install(&dir, &checks).unwrap().backup_path.is_none());
+        assert!(uninstall(&dir).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&hook).unwrap(),
+            "#!/bin/sh\nexit 0\n"
+        );
+        assert!(!uninstall(&dir).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn generated_hook_runs_the_backed_up_hook_first() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_repo("chain");
+        let hook = dir.join(".git/hooks/pre-commit");
+        std::fs::create_dir_all(hook.parent().unwrap()).unwrap();
+        let ran = dir.join("previous-ran");
+        std::fs::write(
+            &hook,
+            format!(
+                "#!/bin/sh\ntouch {}\nexit 3\n",
+                shell_quote(&ran.to_string_lossy())
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        install(&dir, &HookChecks::default()).unwrap();
+        let status = std::process::Command::new(&hook)
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        assert!(ran.exists());
+        assert_eq!(status.code(), Some(3));
+    }
+
+    #[test]
+    fn cli_formats_and_reports_failures() {
+        let dir = temp_repo("cli");
+        let schema = dir.join("schema.json");
+        let good = dir.join("good.json");
+        let bad = dir.join("bad.json");
+        std::fs::write(&schema, r#"{"properties": {"port": {"type": "integer"}}}"#).unwrap();
+        std::fs::write(&good, r#"{"port":80}"#).unwrap();
+        std::fs::write(&bad, r#"{"port": "80"}"#).unwrap();
+        let arg = |p: &PathBuf| p.to_string_lossy().into_owned();
+        let args = ["--check", "--no-lint", "--format", "--schema"].map(String::from);
+        let mut good_args = args.to_vec();
+        good_args.extend([arg(&schema), arg(&good)]);
+        assert!(run_cli(&good_args).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&good).unwrap(),
+            "{\n  \"port\": 80\n}\n"
+        );
+        let mut bad_args = args.to_vec();
+        bad_args.extend([arg(&schema), arg(&bad)]);
+        assert!(!run_cli(&bad_args).unwrap());
+    }
+
+    #[test]
+    fn staged_mode_checks_and_formats_the_index_copy() {
+        let dir = temp_repo("staged");
+        let repo = Repository::open(&dir).unwrap();
+        let file = dir.join("data.json");
+        std::fs::write(&file, r#"{"a":1}"#).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(FsPath::new("data.json")).unwrap();
+        index.write().unwrap();
+        let profile = ValidationProfile {
+            lint: false,
+            ..ValidationProfile::default()
+        };
+        let checker = Checker::new(&dir, None, &profile).unwrap();
+
+        std::fs::write(&file, "{").unwrap();
+        assert!(
+            check_staged(&repo, &checker, "data.json", None)
+                .unwrap()
+                .passed
+        );
+        assert!(matches!(
+            check_staged(&repo, &checker, "data.json", Some(2)),
+            Err(DocError::Edit(_))
+        ));
+
+        std::fs::write(&file, r#"{"a":1}"#).unwrap();
+        assert!(
+            check_staged(&repo, &checker, "data.json", Some(2))
+                .unwrap()
+                .passed
+        );
+        let formatted = "{\n  \"a\": 1\n}\n";
+        assert_eq!(staged_text(&repo, "data.json").unwrap(), formatted);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), formatted);
+    }
+}
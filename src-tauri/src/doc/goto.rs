@@ -0,0 +1,360 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use super::types::{NodeKind, Path, PathSegment};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_VISITED: usize = 500_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryMode {
+    Pointer,
+    Dot,
+    Fuzzy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathCandidate {
+    pub path: Path,
+    pub pointer: String,
+    pub display: String,
+    pub kind: NodeKind,
+    pub score: i32,
+    pub exact: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathResolution {
+    pub mode: QueryMode,
+    pub candidates: Vec<PathCandidate>,
+    pub truncated: bool,
+}
+
+pub fn mode_of(input: &str) -> QueryMode {
+    let input = input.trim();
+    if input.is_empty() || input.starts_with('/') || input.starts_with("#/") {
+        QueryMode::Pointer
+    } else if input.starts_with('$')
+        || (!input.contains(char::is_whitespace) && (input.contains('.') || input.contains('[')))
+    {
+        QueryMode::Dot
+    } else {
+        QueryMode::Fuzzy
+    }
+}
+
+pub fn pointer_segments(input: &str) -> Vec<String> {
+    let input = input.trim().trim_start_matches('#');
+    if input.is_empty() {
+        return Vec::new();
+    }
+    input
+        .split('/')
+        .skip(1)
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+pub fn dot_segments(input: &str) -> Option<Vec<String>> {
+    let input = input.trim();
+    let mut rest = input.strip_prefix('$').unwrap_or(input);
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix('[') {
+            let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'');
+            if let Some(q) = quote {
+                let body = &after[1..];
+                let end = body.find(q)?;
+                let raw = &body[..end];
+                let key = if q == '"' {
+                    serde_json::from_str(&format!("\"{raw}\"")).ok()?
+                } else {
+                    raw.to_string()
+                };
+                segments.push(key);
+                rest = body[end + 1..].strip_prefix(']')?;
+            } else {
+                let end = after.find(']')?;
+                segments.push(after[..end].trim().to_string());
+                rest = &after[end + 1..];
+            }
+            continue;
+        }
+        let end = rest.find(['.', '[']).unwrap_or(rest.len());
+        segments.push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+    Some(segments)
+}
+
+pub fn exact_path<F>(segments: &[String], kind_at: F) -> Option<(Path, NodeKind)>
+where
+    F: Fn(&Path) -> Option<NodeKind>,
+{
+    let mut path = Path::root();
+    let mut kind = kind_at(&path)?;
+    for seg in segments {
+        match kind {
+            NodeKind::Array => path.push(PathSegment::Index(seg.parse().ok()?)),
+            NodeKind::Object => path.push(PathSegment::Key(seg.clone())),
+            _ => return None,
+        }
+        kind = kind_at(&path)?;
+    }
+    Some((path, kind))
+}
+
+pub fn value_at<'a>(root: &'a Value, path: &Path) -> Option<&'a Value> {
+    path.0.iter().try_fold(root, |v, seg| match (v, seg) {
+        (Value::Object(map), PathSegment::Key(k)) => map.get(k),
+        (Value::Array(arr), PathSegment::Index(i)) => arr.get(*i as usize),
+        _ => None,
+    })
+}
+
+pub fn kind_of(v: &Value) -> NodeKind {
+    match v {
+        Value::Object(_) => NodeKind::Object,
+        Value::Array(_) => NodeKind::Array,
+        Value::String(_) => NodeKind::String,
+        Value::Number(_) => NodeKind::Number,
+        Value::Bool(_) => NodeKind::Bool,
+        Value::Null => NodeKind::Null,
+    }
+}
+
+fn candidate(path: Path, kind: NodeKind, score: i32, exact: bool) -> PathCandidate {
+    PathCandidate {
+        pointer: path.to_pointer(),
+        display: path.to_string(),
+        path,
+        kind,
+        score,
+        exact,
+    }
+}
+
+fn token_score(token: &str, segment: &str) -> i32 {
+    if segment == token {
+        return 100;
+    }
+    if segment.starts_with(token) {
+        return 70;
+    }
+    if segment.contains(token) {
+        return 40;
+    }
+    let mut chars = segment.chars();
+    if token.chars().all(|t| chars.any(|c| c == t)) {
+        return 15;
+    }
+    0
+}
+
+pub fn fuzzy_score(tokens: &[String], path: &Path) -> Option<i32> {
+    let segments: Vec<String> = path
+        .0
+        .iter()
+        .map(|seg| match seg {
+            PathSegment::Key(k) => k.to_lowercase(),
+            PathSegment::Index(i) => i.to_string(),
+        })
+        .collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last_matched = None;
+    for token in tokens {
+        let (idx, s) = (next..segments.len())
+            .map(|i| (i, token_score(token, &segments[i])))
+            .find(|(_, s)| *s > 0)?;
+        score += s;
+        next = idx + 1;
+        last_matched = Some(idx);
+    }
+    if last_matched == Some(segments.len().wrapping_sub(1)) {
+        score += 50;
+    }
+    Some(score - 2 * segments.len() as i32)
+}
+
+pub fn fuzzy_search(root: &Value, query: &str, limit: usize) -> (Vec<PathCandidate>, bool) {
+    let tokens: Vec<String> = query
+        .split(|c: char| c.is_whitespace() || c == '.' || c == '/')
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if tokens.is_empty() {
+        return (Vec::new(), false);
+    }
+    let mut found = Vec::new();
+    let mut visited = 0usize;
+    let mut stack: Vec<(Path, &Value)> = vec![(Path::root(), root)];
+    while let Some((path, value)) = stack.pop() {
+        visited += 1;
+        if visited > MAX_VISITED {
+            break;
+        }
+        if let Some(score) = fuzzy_score(&tokens, &path) {
+            found.push(candidate(path.clone(), kind_of(value), score, false));
+        }
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map.iter().rev() {
+                    let mut child = path.clone();
+                    child.push(PathSegment::Key(k.clone()));
+                    stack.push((child, v));
+                }
+            }
+            Value::Array(arr) => {
+                for (i, v) in arr.iter().enumerate().rev() {
+                    let mut child = path.clone();
+                    child.push(PathSegment::Index(i as u32));
+                    stack.push((child, v));
+                }
+            }
+            _ => {}
+        }
+    }
+    found.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.path.0.len().cmp(&b.path.0.len()))
+    });
+    let truncated = visited > MAX_VISITED || found.len() > limit;
+    found.truncate(limit);
+    (found, truncated)
+}
+
+pub fn exact_candidate<F>(input: &str, kind_at: F) -> Option<PathCandidate>
+where
+    F: Fn(&Path) -> Option<NodeKind>,
+{
+    let segments = match mode_of(input) {
+        QueryMode::Pointer => pointer_segments(input),
+        QueryMode::Dot => dot_segments(input)?,
+        QueryMode::Fuzzy => vec![input.trim().to_string()],
+    };
+    let (path, kind) = exact_path(&segments, kind_at)?;
+    Some(candidate(path, kind, i32::MAX, true))
+}
+
+pub fn resolve<F>(
+    input: &str,
+    limit: usize,
+    kind_at: F,
+    root: impl FnOnce() -> Option<Value>,
+) -> PathResolution
+where
+    F: Fn(&Path) -> Option<NodeKind>,
+{
+    let limit = if limit == 0 { DEFAULT_LIMIT } else { limit };
+    let mode = mode_of(input);
+    let exact = exact_candidate(input, kind_at);
+    if mode == QueryMode::Pointer {
+        return PathResolution {
+            mode,
+            candidates: exact.into_iter().collect(),
+            truncated: false,
+        };
+    }
+    let (mut candidates, truncated) = root()
+        .map(|value| fuzzy_search(&value, input, limit))
+        .unwrap_or_default();
+    if let Some(exact) = exact {
+        candidates.retain(|c| c.path != exact.path);
+        candidates.insert(0, exact);
+        candidates.truncate(limit);
+    }
+    PathResolution {
+        mode,
+        candidates,
+        truncated,
+    }
+}
+
+pub fn resolve_in_value(root: &Value, input: &str, limit: usize) -> PathResolution {
+    resolve(
+        input,
+        limit,
+        |p| value_at(root, p).map(kind_of),
+        || Some(root.clone()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> Value {
+        json!({
+            "user": {
+                "name": "Ada",
+                "address": { "city": "London", "zip": "N1" },
+                "tags": ["a", "b"]
+            },
+            "users": [{ "address": { "city": "Paris" } }],
+            "a/b": 1
+        })
+    }
+
+    #[test]
+    fn detects_query_modes() {
+        assert_eq!(mode_of("/user/name"), QueryMode::Pointer);
+        assert_eq!(mode_of(""), QueryMode::Pointer);
+        assert_eq!(mode_of("$.user.name"), QueryMode::Dot);
+        assert_eq!(mode_of("user.tags[1]"), QueryMode::Dot);
+        assert_eq!(mode_of("usr adr city"), QueryMode::Fuzzy);
+    }
+
+    #[test]
+    fn resolves_json_pointer_exactly() {
+        let res = resolve_in_value(&sample(), "/user/tags/1", 0);
+        assert_eq!(res.candidates.len(), 1);
+        assert_eq!(res.candidates[0].display, "$.user.tags[1]");
+        assert!(res.candidates[0].exact);
+        let escaped = resolve_in_value(&sample(), "/a~1b", 0);
+        assert_eq!(escaped.candidates[0].kind, NodeKind::Number);
+        assert!(resolve_in_value(&sample(), "/nope", 0)
+            .candidates
+            .is_empty());
+    }
+
+    #[test]
+    fn resolves_dot_notation_with_brackets() {
+        assert_eq!(
+            dot_segments(r#"$.users[0]["address"].city"#).unwrap(),
+            vec!["users", "0", "address", "city"]
+        );
+        let res = resolve_in_value(&sample(), "$.users[0].address.city", 0);
+        assert_eq!(res.candidates[0].pointer, "/users/0/address/city");
+        assert!(res.candidates[0].exact);
+    }
+
+    #[test]
+    fn ranks_fuzzy_key_queries() {
+        let res = resolve_in_value(&sample(), "usr adr city", 0);
+        assert_eq!(res.mode, QueryMode::Fuzzy);
+        let displays: Vec<&str> = res.candidates.iter().map(|c| c.display.as_str()).collect();
+        assert_eq!(
+            displays,
+            vec!["$.user.address.city", "$.users[0].address.city"]
+        );
+        assert!(res.candidates.iter().all(|c| !c.exact));
+    }
+
+    #[test]
+    fn exact_key_outranks_partial_matches() {
+        let res = resolve_in_value(&sample(), "city", 0);
+        assert_eq!(res.candidates.len(), 2);
+        assert!(res.candidates[0].score >= res.candidates[1].score);
+        assert_eq!(res.candidates[0].display, "$.user.address.city");
+    }
+}
@@ -0,0 +1,391 @@
+use std::collections::{HashSet, VecDeque};
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use super::document::Document;
+use super::navigation::path_from_pointer;
+use super::types::{DocResult, NodeKind, Path, PathSegment};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutAlgorithm {
+    #[default]
+    Tidy,
+    Radial,
+    Layered,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GraphOptions {
+    pub max_depth: u32,
+    pub max_children: u32,
+    pub max_nodes: usize,
+    pub node_width: f64,
+    pub node_height: f64,
+    pub h_gap: f64,
+    pub v_gap: f64,
+    pub expanded: Vec<String>,
+}
+
+impl Default for GraphOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 2,
+            max_children: 100,
+            max_nodes: 5_000,
+            node_width: 160.0,
+            node_height: 32.0,
+            h_gap: 24.0,
+            v_gap: 48.0,
+            expanded: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNode {
+    pub id: String,
+    pub parent: Option<String>,
+    pub label: String,
+    pub kind: NodeKind,
+    pub preview: String,
+    pub child_count: Option<u32>,
+    pub hidden_children: u32,
+    pub expanded: bool,
+    pub depth: u32,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphLayout {
+    pub algorithm: LayoutAlgorithm,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    pub width: f64,
+    pub height: f64,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodePosition {
+    pub id: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphExpansion {
+    pub added: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    pub moved: Vec<NodePosition>,
+    pub expanded: Vec<String>,
+    pub width: f64,
+    pub height: f64,
+    pub truncated: bool,
+}
+
+struct Tree {
+    nodes: Vec<GraphNode>,
+    children: Vec<Vec<usize>>,
+    truncated: bool,
+}
+
+fn label_of(seg: Option<&PathSegment>) -> String {
+    match seg {
+        None => "$".into(),
+        Some(PathSegment::Key(k)) => k.clone(),
+        Some(PathSegment::Index(i)) => format!("[{i}]"),
+    }
+}
+
+fn build(doc: &Document, opts: &GraphOptions) -> DocResult<Tree> {
+    let expanded: HashSet<&str> = opts.expanded.iter().map(String::as_str).collect();
+    let summary = doc.summary();
+    let mut tree = Tree {
+        nodes: vec![GraphNode {
+            id: String::new(),
+            parent: None,
+            label: label_of(None),
+            kind: summary.root_kind,
+            preview: String::new(),
+            child_count: summary.root_child_count,
+            hidden_children: summary.root_child_count.unwrap_or(0),
+            expanded: false,
+            depth: 0,
+            x: 0.0,
+            y: 0.0,
+        }],
+        children: vec![Vec::new()],
+        truncated: false,
+    };
+    let max_nodes = opts.max_nodes.max(1);
+    let mut queue = VecDeque::from([(0usize, Path::root())]);
+    while let Some((at, path)) = queue.pop_front() {
+        let node = &tree.nodes[at];
+        let wanted = node.depth < opts.max_depth || expanded.contains(node.id.as_str());
+        if !wanted || node.child_count.unwrap_or(0) == 0 {
+            continue;
+        }
+        let room = (max_nodes - tree.nodes.len()).min(opts.max_children as usize) as u32;
+        if room == 0 {
+            tree.truncated = true;
+            continue;
+        }
+        let (parent_id, depth) = (node.id.clone(), node.depth + 1);
+        let slice = doc.get_slice(&path, 0..room)?;
+        tree.nodes[at].expanded = true;
+        let shown = slice.len() as u32;
+        tree.nodes[at].hidden_children = tree.nodes[at].hidden_children.saturating_sub(shown);
+        for view in slice {
+            let mut child_path = path.clone();
+            child_path.push(view.key.clone());
+            let index = tree.nodes.len();
+            tree.nodes.push(GraphNode {
+                id: child_path.to_pointer(),
+                parent: Some(parent_id.clone()),
+                label: label_of(Some(&view.key)),
+                kind: view.kind,
+                preview: view.preview,
+                child_count: view.child_count,
+                hidden_children: view.child_count.unwrap_or(0),
+                expanded: false,
+                depth,
+                x: 0.0,
+                y: 0.0,
+            });
+            tree.children.push(Vec::new());
+            tree.children[at].push(index);
+            queue.push_back((index, child_path));
+        }
+    }
+    Ok(tree)
+}
+
+fn tidy(tree: &mut Tree, at: usize, next_leaf: &mut f64, opts: &GraphOptions) {
+    let y = tree.nodes[at].depth as f64 * (opts.node_height + opts.v_gap);
+    let children = tree.children[at].clone();
+    let x = if children.is_empty() {
+        let x = *next_leaf;
+        *next_leaf += opts.node_width + opts.h_gap;
+        x
+    } else {
+        for &child in &children {
+            tidy(tree, child, next_leaf, opts);
+        }
+        let first = tree.nodes[children[0]].x;
+        let last = tree.nodes[children[children.len() - 1]].x;
+        (first + last) / 2.0
+    };
+    tree.nodes[at].x = x;
+    tree.nodes[at].y = y;
+}
+
+fn leaf_counts(tree: &Tree) -> Vec<f64> {
+    let mut leaves = vec![0.0; tree.nodes.len()];
+    for (at, kids) in tree.children.iter().enumerate().rev() {
+        leaves[at] = match kids.as_slice() {
+            [] => 1.0,
+            kids => kids.iter().map(|&k| leaves[k]).sum(),
+        };
+    }
+    leaves
+}
+
+fn radial(tree: &mut Tree, opts: &GraphOptions) {
+    let leaves = leaf_counts(tree);
+    let ring = opts.node_width + opts.h_gap;
+    let mut spans = vec![(0.0, TAU); tree.nodes.len()];
+    for at in 0..tree.nodes.len() {
+        let (start, span) = spans[at];
+        let radius = tree.nodes[at].depth as f64 * ring;
+        let angle = start + span / 2.0;
+        tree.nodes[at].x = radius * angle.cos();
+        tree.nodes[at].y = radius * angle.sin();
+        let mut offset = start;
+        for &child in &tree.children[at] {
+            let share = span * leaves[child] / leaves[at];
+            spans[child] = (offset, share);
+            offset += share;
+        }
+    }
+}
+
+fn layered(tree: &mut Tree, opts: &GraphOptions) {
+    let mut layers: Vec<Vec<usize>> = Vec::new();
+    for (at, node) in tree.nodes.iter().enumerate() {
+        let depth = node.depth as usize;
+        if layers.len() <= depth {
+            layers.resize(depth + 1, Vec::new());
+        }
+        layers[depth].push(at);
+    }
+    let row = opts.node_height + opts.v_gap / 2.0;
+    let widest = layers.iter().map(Vec::len).max().unwrap_or(1) as f64;
+    for (depth, layer) in layers.iter().enumerate() {
+        let offset = (widest - layer.len() as f64) * row / 2.0;
+        for (i, &at) in layer.iter().enumerate() {
+            tree.nodes[at].x = depth as f64 * (opts.node_width + opts.h_gap);
+            tree.nodes[at].y = offset + i as f64 * row;
+        }
+    }
+}
+
+fn normalize(nodes: &mut [GraphNode], opts: &GraphOptions) -> (f64, f64) {
+    let min_x = nodes.iter().map(|n| n.x).fold(f64::INFINITY, f64::min);
+    let min_y = nodes.iter().map(|n| n.y).fold(f64::INFINITY, f64::min);
+    let (half_w, half_h) = (opts.node_width / 2.0, opts.node_height / 2.0);
+    let (mut width, mut height) = (0.0f64, 0.0f64);
+    for node in nodes.iter_mut() {
+        node.x = node.x - min_x + half_w;
+        node.y = node.y - min_y + half_h;
+        width = width.max(node.x + half_w);
+        height = height.max(node.y + half_h);
+    }
+    (width, height)
+}
+
+pub fn layout(
+    doc: &Document,
+    algorithm: LayoutAlgorithm,
+    opts: &GraphOptions,
+) -> DocResult<GraphLayout> {
+    let mut tree = build(doc, opts)?;
+    match algorithm {
+        LayoutAlgorithm::Tidy => tidy(&mut tree, 0, &mut 0.0, opts),
+        LayoutAlgorithm::Radial => radial(&mut tree, opts),
+        LayoutAlgorithm::Layered => layered(&mut tree, opts),
+    }
+    let (width, height) = normalize(&mut tree.nodes, opts);
+    let edges = tree
+        .nodes
+        .iter()
+        .filter_map(|n| {
+            n.parent.as_ref().map(|p| GraphEdge {
+                source: p.clone(),
+                target: n.id.clone(),
+            })
+        })
+        .collect();
+    Ok(GraphLayout {
+        algorithm,
+        nodes: tree.nodes,
+        edges,
+        width,
+        height,
+        truncated: tree.truncated,
+    })
+}
+
+pub fn expand(
+    doc: &Document,
+    pointer: &str,
+    algorithm: LayoutAlgorithm,
+    opts: &GraphOptions,
+) -> DocResult<GraphExpansion> {
+    let pointer = path_from_pointer(doc, pointer)?.to_pointer();
+    let before: HashSet<String> = build(doc, opts)?.nodes.into_iter().map(|n| n.id).collect();
+    let mut opts = opts.clone();
+    if !opts.expanded.contains(&pointer) {
+        opts.expanded.push(pointer);
+    }
+    let after = layout(doc, algorithm, &opts)?;
+    let edges = after
+        .edges
+        .iter()
+        .filter(|e| !before.contains(&e.target))
+        .cloned()
+        .collect();
+    let (added, kept): (Vec<GraphNode>, Vec<GraphNode>) = after
+        .nodes
+        .into_iter()
+        .partition(|n| !before.contains(&n.id));
+    Ok(GraphExpansion {
+        added,
+        edges,
+        moved: kept
+            .into_iter()
+            .map(|n| NodePosition {
+                id: n.id,
+                x: n.x,
+                y: n.y,
+            })
+            .collect(),
+        expanded: opts.expanded,
+        width: after.width,
+        height: after.height,
+        truncated: after.truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc() -> Document {
+        Document::from_text(
+            r#"{"meta": {"a": 1, "b": {"deep": true}}, "items": [{"id": 1}, {"id": 2}, 3]}"#,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn position<'a>(layout: &'a GraphLayout, id: &str) -> &'a GraphNode {
+        layout.nodes.iter().find(|n| n.id == id).unwrap()
+    }
+
+    #[test]
+    fn tidy_layout_centres_parents_over_children() {
+        let out = layout(&doc(), LayoutAlgorithm::Tidy, &GraphOptions::default()).unwrap();
+        assert_eq!(out.nodes.len(), 8);
+        assert_eq!(out.edges.len(), 7);
+        let (meta, a, b) = (
+            position(&out, "/meta"),
+            position(&out, "/meta/a"),
+            position(&out, "/meta/b"),
+        );
+        assert_eq!(meta.x, (a.x + b.x) / 2.0);
+        assert!(a.y > meta.y && a.x < b.x);
+        assert!(!b.expanded && b.hidden_children == 1);
+        assert!(out
+            .nodes
+            .iter()
+            .all(|n| n.x >= 80.0 && n.x <= out.width - 80.0));
+
+        let opts = GraphOptions {
+            max_depth: 1,
+            max_children: 1,
+            ..GraphOptions::default()
+        };
+        let out = layout(&doc(), LayoutAlgorithm::Layered, &opts).unwrap();
+        assert_eq!(out.nodes.len(), 2);
+        assert_eq!(out.nodes[0].hidden_children, 1);
+        assert!(out.nodes[1].x > out.nodes[0].x);
+    }
+
+    #[test]
+    fn expanding_a_node_returns_only_new_nodes() {
+        let opts = GraphOptions::default();
+        let out = expand(&doc(), "/meta/b", LayoutAlgorithm::Radial, &opts).unwrap();
+        let added: Vec<&str> = out.added.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(added, vec!["/meta/b/deep"]);
+        assert_eq!(out.edges.len(), 1);
+        assert_eq!(out.edges[0].source, "/meta/b");
+        assert_eq!(out.moved.len(), 8);
+        assert_eq!(out.expanded, vec!["/meta/b"]);
+        assert!(expand(&doc(), "/missing", LayoutAlgorithm::Radial, &opts).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+const TOP_N: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HarPart {
+    Request,
+    Response,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarTimings {
+    pub blocked: f64,
+    pub dns: f64,
+    pub connect: f64,
+    pub ssl: f64,
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    pub index: u32,
+    pub started: Option<String>,
+    pub method: String,
+    pub url: String,
+    pub host: String,
+    pub status: i64,
+    pub status_text: String,
+    pub mime_type: Option<String>,
+    pub time_ms: f64,
+    pub timings: HarTimings,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+    pub transfer_bytes: i64,
+    pub server_ip: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRanked {
+    pub index: u32,
+    pub url: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarSummary {
+    pub entry_count: u32,
+    pub page_count: u32,
+    pub total_time_ms: f64,
+    pub total_transfer_bytes: i64,
+    pub total_response_bytes: i64,
+    pub failed: u32,
+    pub by_status: BTreeMap<String, u32>,
+    pub by_mime: BTreeMap<String, u32>,
+    pub by_host: BTreeMap<String, u32>,
+    pub slowest: Vec<HarRanked>,
+    pub largest: Vec<HarRanked>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HarBody {
+    pub mime_type: Option<String>,
+    pub text: String,
+    pub size: u64,
+}
+
+pub fn entries_path() -> Path {
+    Path(vec![
+        PathSegment::Key("log".into()),
+        PathSegment::Key("entries".into()),
+    ])
+}
+
+pub fn pages_path() -> Path {
+    Path(vec![
+        PathSegment::Key("log".into()),
+        PathSegment::Key("pages".into()),
+    ])
+}
+
+pub fn entry_path(index: u32) -> Path {
+    let mut p = entries_path();
+    p.push(PathSegment::Index(index));
+    p
+}
+
+fn num(v: Option<&Value>) -> f64 {
+    v.and_then(Value::as_f64)
+        .filter(|n| *n >= 0.0)
+        .unwrap_or(0.0)
+}
+
+fn int(v: Option<&Value>) -> i64 {
+    v.and_then(Value::as_i64).filter(|n| *n >= 0).unwrap_or(0)
+}
+
+fn text(v: Option<&Value>) -> Option<String> {
+    v.and_then(Value::as_str).map(str::to_string)
+}
+
+fn host_of(url: &str) -> String {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    authority
+        .rsplit_once('@')
+        .map(|(_, h)| h)
+        .unwrap_or(authority)
+        .to_string()
+}
+
+pub fn summarize_entry(index: u32, entry: &Value) -> HarEntry {
+    let request = entry.get("request");
+    let response = entry.get("response");
+    let url = text(request.and_then(|r| r.get("url"))).unwrap_or_default();
+    let t = entry.get("timings");
+    let timing = |k: &str| num(t.and_then(|t| t.get(k)));
+    let request_bytes = int(request.and_then(|r| r.get("headersSize")))
+        + int(request.and_then(|r| r.get("bodySize")));
+    let content = response.and_then(|r| r.get("content"));
+    let response_bytes = int(content.and_then(|c| c.get("size")));
+    let transfer_bytes = match response
+        .and_then(|r| r.get("_transferSize"))
+        .and_then(Value::as_i64)
+    {
+        Some(n) if n >= 0 => n,
+        _ => {
+            int(response.and_then(|r| r.get("headersSize")))
+                + int(response.and_then(|r| r.get("bodySize")))
+        }
+    };
+    HarEntry {
+        index,
+        started: text(entry.get("startedDateTime")),
+        method: text(request.and_then(|r| r.get("method"))).unwrap_or_default(),
+        host: host_of(&url),
+        url,
+        status: response
+            .and_then(|r| r.get("status"))
+            .and_then(Value::as_i64)
+            .unwrap_or(0),
+        status_text: text(response.and_then(|r| r.get("statusText"))).unwrap_or_default(),
+        mime_type: text(content.and_then(|c| c.get("mimeType"))),
+        time_ms: num(entry.get("time")),
+        timings: HarTimings {
+            blocked: timing("blocked"),
+            dns: timing("dns"),
+            connect: timing("connect"),
+            ssl: timing("ssl"),
+            send: timing("send"),
+            wait: timing("wait"),
+            receive: timing("receive"),
+        },
+        request_bytes,
+        response_bytes,
+        transfer_bytes,
+        server_ip: text(entry.get("serverIPAddress")),
+    }
+}
+
+fn entries_array(entries: &Value) -> DocResult<&Vec<Value>> {
+    entries
+        .as_array()
+        .ok_or_else(|| DocError::Parse("log.entries is not an array".into()))
+}
+
+pub fn list_entries(entries: &Value) -> DocResult<Vec<HarEntry>> {
+    Ok(entries_array(entries)?
+        .iter()
+        .enumerate()
+        .map(|(i, e)| summarize_entry(i as u32, e))
+        .collect())
+}
+
+fn top_n(entries: &[HarEntry], key: impl Fn(&HarEntry) -> f64) -> Vec<HarRanked> {
+    let mut ranked: Vec<HarRanked> = entries
+        .iter()
+        .map(|e| HarRanked {
+            index: e.index,
+            url: e.url.clone(),
+            value: key(e),
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.value.total_cmp(&a.value));
+    ranked.truncate(TOP_N);
+    ranked
+}
+
+pub fn summarize(entries: &Value, pages: Option<&Value>) -> DocResult<HarSummary> {
+    let list = list_entries(entries)?;
+    let mut by_status = BTreeMap::new();
+    let mut by_mime = BTreeMap::new();
+    let mut by_host = BTreeMap::new();
+    for e in &list {
+        let class = match e.status {
+            0 => "failed".to_string(),
+            s => format!("{}xx", s / 100),
+        };
+        *by_status.entry(class).or_insert(0) += 1;
+        let mime = e
+            .mime_type
+            .as_deref()
+            .map(|m| m.split(';').next().unwrap_or(m).trim().to_string())
+            .filter(|m| !m.is_empty())
+            .unwrap_or_else(|| "unknown".into());
+        *by_mime.entry(mime).or_insert(0) += 1;
+        *by_host.entry(e.host.clone()).or_insert(0) += 1;
+    }
+    Ok(HarSummary {
+        entry_count: list.len() as u32,
+        page_count: pages
+            .and_then(Value::as_array)
+            .map_or(0, |p| p.len() as u32),
+        total_time_ms: list.iter().map(|e| e.time_ms).sum(),
+        total_transfer_bytes: list.iter().map(|e| e.transfer_bytes).sum(),
+        total_response_bytes: list.iter().map(|e| e.response_bytes).sum(),
+        failed: list
+            .iter()
+            .filter(|e| e.status == 0 || e.status >= 400)
+            .count() as u32,
+        by_status,
+        by_mime,
+        by_host,
+        slowest: top_n(&list, |e| e.time_ms),
+        largest: top_n(&list, |e| e.transfer_bytes as f64),
+    })
+}
+
+pub fn extract_body(entry: &Value, part: HarPart) -> DocResult<HarBody> {
+    let (mime_type, raw, encoding) = match part {
+        HarPart::Request => {
+            let post = entry
+                .get("request")
+                .and_then(|r| r.get("postData"))
+                .ok_or_else(|| DocError::Edit("request has no body".into()))?;
+            (text(post.get("mimeType")), post.get("text"), None)
+        }
+        HarPart::Response => {
+            let content = entry
+                .get("response")
+                .and_then(|r| r.get("content"))
+                .ok_or_else(|| DocError::Edit("response has no content".into()))?;
+            (
+                text(content.get("mimeType")),
+                content.get("text"),
+                text(content.get("encoding")),
+            )
+        }
+    };
+    let raw = raw
+        .and_then(Value::as_str)
+        .ok_or_else(|| DocError::Edit("body was not captured in this HAR".into()))?;
+    let text = match encoding.as_deref() {
+        Some("base64") => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(raw.trim())
+                .map_err(|e| DocError::Parse(format!("invalid base64 body: {e}")))?;
+            String::from_utf8(bytes)
+                .map_err(|_| DocError::Parse("body is binary, not text".into()))?
+        }
+        _ => raw.to_string(),
+    };
+    Ok(HarBody {
+        mime_type,
+        size: text.len() as u64,
+        text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> Value {
+        json!([
+            {
+                "startedDateTime": "2024-01-01T00:00:00.000Z",
+                "time": 120.5,
+                "request": {
+                    "method": "POST",
+                    "url": "https://api.example.com/v1/items?x=1",
+                    "headersSize": 200,
+                    "bodySize": 15,
+                    "postData": { "mimeType": "application/json", "text": "{\"name\":\"a\"}" }
+                },
+                "response": {
+                    "status": 201,
+                    "statusText": "Created",
+                    "headersSize": 100,
+                    "bodySize": 50,
+                    "_transferSize": 400,
+                    "content": {
+                        "size": 13,
+                        "mimeType": "application/json; charset=utf-8",
+                        "text": "eyJpZCI6IDF9",
+                        "encoding": "base64"
+                    }
+                },
+                "timings": { "blocked": -1, "dns": 3, "connect": 10, "send": 1, "wait": 100, "receive": 6.5 }
+            },
+            {
+                "time": 30,
+                "request": { "method": "GET", "url": "https://cdn.example.com/app.js", "headersSize": -1, "bodySize": 0 },
+                "response": {
+                    "status": 404,
+                    "headersSize": 80,
+                    "bodySize": 20,
+                    "content": { "size": 20, "mimeType": "text/html" }
+                },
+                "timings": { "wait": 25 }
+            }
+        ])
+    }
+
+    #[test]
+    fn lists_entries_with_sizes_and_timings() {
+        let entries = list_entries(&sample()).unwrap();
+        assert_eq!(entries.len(), 2);
+        let e = &entries[0];
+        assert_eq!(e.method, "POST");
+        assert_eq!(e.host, "api.example.com");
+        assert_eq!(e.status, 201);
+        assert_eq!(e.request_bytes, 215);
+        assert_eq!(e.transfer_bytes, 400);
+        assert_eq!(e.timings.blocked, 0.0);
+        assert_eq!(e.timings.wait, 100.0);
+        assert_eq!(entries[1].transfer_bytes, 100);
+        assert_eq!(entries[1].request_bytes, 0);
+    }
+
+    #[test]
+    fn summary_groups_and_ranks() {
+        let s = summarize(&sample(), Some(&json!([{}]))).unwrap();
+        assert_eq!(s.entry_count, 2);
+        assert_eq!(s.page_count, 1);
+        assert_eq!(s.failed, 1);
+        assert_eq!(s.total_transfer_bytes, 500);
+        assert_eq!(s.by_status.get("2xx"), Some(&1));
+        assert_eq!(s.by_status.get("4xx"), Some(&1));
+        assert_eq!(s.by_mime.get("application/json"), Some(&1));
+        assert_eq!(s.slowest[0].index, 0);
+        assert_eq!(s.largest[0].value, 400.0);
+    }
+
+    #[test]
+    fn extracts_and_decodes_bodies() {
+        let list = sample();
+        let response = extract_body(&list[0], HarPart::Response).unwrap();
+        assert_eq!(response.text, r#"{"id": 1}"#);
+        assert_eq!(
+            response.mime_type.as_deref(),
+            Some("application/json; charset=utf-8")
+        );
+        let request = extract_body(&list[0], HarPart::Request).unwrap();
+        assert_eq!(request.text, r#"{"name":"a"}"#);
+
+        assert!(matches!(
+            extract_body(&list[1], HarPart::Response),
+            Err(DocError::Edit(_))
+        ));
+        assert!(extract_body(&list[1], HarPart::Request).is_err());
+    }
+
+    #[test]
+    fn non_array_entries_are_rejected() {
+        assert!(list_entries(&json!({})).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::positions::PositionIndex;
+use super::types::DocHandle;
+
+pub const MAX_BUCKETS: u32 = 4096;
+pub const MAX_SEARCH_MARKERS: u32 = 100_000;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HeatmapOptions {
+    pub query: Option<String>,
+    pub case_sensitive: bool,
+    pub compare_to: Option<DocHandle>,
+    pub schema: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapBucket {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub max_depth: u32,
+    pub errors: u32,
+    pub search_hits: u32,
+    pub changes: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Heatmap {
+    pub version: u64,
+    pub total_lines: u32,
+    pub buckets: Vec<HeatmapBucket>,
+}
+
+#[derive(Default)]
+pub struct LineMarkers {
+    pub errors: Vec<u32>,
+    pub search_hits: Vec<u32>,
+    pub changes: Vec<u32>,
+}
+
+pub fn line_depths(text: &str) -> Vec<u32> {
+    let mut depths = vec![0];
+    let (mut depth, mut in_string, mut escaped) = (0u32, false, false);
+    for &b in text.as_bytes() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                in_string = false;
+            }
+            if b != b'\n' {
+                continue;
+            }
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                let last = depths.last_mut().expect("at least one line");
+                *last = (*last).max(depth);
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b'\n' => depths.push(depth),
+            _ => {}
+        }
+    }
+    depths
+}
+
+pub fn pointer_lines(index: &PositionIndex<'_>) -> HashMap<String, u32> {
+    index
+        .nodes()
+        .iter()
+        .map(|node| {
+            let at = node.key_start.unwrap_or(node.start);
+            (node.path.to_pointer(), index.position(at).line)
+        })
+        .collect()
+}
+
+pub fn line_for_pointer(lines: &HashMap<String, u32>, pointer: &str) -> Option<u32> {
+    let mut current = pointer;
+    loop {
+        if let Some(line) = lines.get(current) {
+            return Some(*line);
+        }
+        current = &current[..current.rfind('/')?];
+    }
+}
+
+pub fn build(depths: &[u32], buckets: u32, markers: &LineMarkers) -> Vec<HeatmapBucket> {
+    let total = depths.len().max(1) as u64;
+    let count = (buckets.clamp(1, MAX_BUCKETS) as u64).min(total);
+    let mut out: Vec<HeatmapBucket> = (0..count)
+        .map(|i| HeatmapBucket {
+            start_line: (i * total / count) as u32 + 1,
+            end_line: ((i + 1) * total / count) as u32,
+            ..HeatmapBucket::default()
+        })
+        .collect();
+    let bucket_of = |line: u32| {
+        let line = (line.max(1) as u64 - 1).min(total - 1);
+        (line * count / total) as usize
+    };
+    for (i, depth) in depths.iter().enumerate() {
+        let bucket = &mut out[bucket_of(i as u32 + 1)];
+        bucket.max_depth = bucket.max_depth.max(*depth);
+    }
+    for line in &markers.errors {
+        out[bucket_of(*line)].errors += 1;
+    }
+    for line in &markers.search_hits {
+        out[bucket_of(*line)].search_hits += 1;
+    }
+    for line in &markers.changes {
+        out[bucket_of(*line)].changes += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_depth_per_line_ignoring_strings() {
+        let text = "{\n  \"a\": [\n    \"{[\\\"\",\n    {\"b\": 1}\n  ]\n}";
+        assert_eq!(line_depths(text), vec![1, 2, 2, 3, 2, 1]);
+    }
+
+    #[test]
+    fn buckets_lines_and_markers() {
+        let depths = vec![1, 2, 3, 2, 1, 0];
+        let markers = LineMarkers {
+            errors: vec![3],
+            search_hits: vec![1, 2, 6],
+            changes: vec![4, 5],
+        };
+        let buckets = build(&depths, 3, &markers);
+        assert_eq!(buckets.len(), 3);
+        assert_eq!((buckets[0].start_line, buckets[0].end_line), (1, 2));
+        assert_eq!((buckets[2].start_line, buckets[2].end_line), (5, 6));
+        assert_eq!(buckets[1].max_depth, 3);
+        assert_eq!(buckets[1].errors, 1);
+        assert_eq!(buckets[0].search_hits, 2);
+        assert_eq!(buckets[2].changes, 1);
+        assert_eq!(build(&depths, 100, &LineMarkers::default()).len(), 6);
+    }
+
+    #[test]
+    fn maps_pointers_to_nearest_known_line() {
+        let index = PositionIndex::build("{\n  \"a\": {\n    \"b\": 1\n  }\n}").unwrap();
+        let lines = pointer_lines(&index);
+        assert_eq!(line_for_pointer(&lines, "/a/b"), Some(3));
+        assert_eq!(line_for_pointer(&lines, "/a/missing/x"), Some(2));
+        assert_eq!(line_for_pointer(&lines, ""), Some(1));
+    }
+}
@@ -0,0 +1,176 @@
+use serde::Serialize;
+
+pub const MAX_RANGE_LINES: u32 = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TokenKind {
+    Key,
+    String,
+    Number,
+    Bool,
+    Null,
+    Punctuation,
+    Comment,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Token {
+    pub start: u32,
+    pub end: u32,
+    pub kind: TokenKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenizedRange {
+    pub version: u64,
+    pub start_line: u32,
+    pub total_lines: u32,
+    pub lines: Vec<Vec<Token>>,
+}
+
+fn string_end(bytes: &[u8], start: usize) -> (usize, bool) {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return (i + 1, true),
+            _ => i += 1,
+        }
+    }
+    (bytes.len(), false)
+}
+
+fn followed_by_colon(bytes: &[u8], mut i: usize) -> bool {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    bytes.get(i) == Some(&b':')
+}
+
+pub fn tokenize_line(line: &str) -> Vec<Token> {
+    let bytes = line.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let column = |offset: usize| line[..offset].chars().count() as u32;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let kind = match b {
+            b'"' => {
+                let (end, closed) = string_end(bytes, i);
+                i = end;
+                if !closed {
+                    TokenKind::Error
+                } else if followed_by_colon(bytes, end) {
+                    TokenKind::Key
+                } else {
+                    TokenKind::String
+                }
+            }
+            b'{' | b'}' | b'[' | b']' | b':' | b',' => {
+                i += 1;
+                TokenKind::Punctuation
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = bytes.len();
+                TokenKind::Comment
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = line[i + 2..].find("*/").map_or(bytes.len(), |p| i + p + 4);
+                TokenKind::Comment
+            }
+            _ => {
+                while i < bytes.len()
+                    && !bytes[i].is_ascii_whitespace()
+                    && !matches!(bytes[i], b'{' | b'}' | b'[' | b']' | b':' | b',' | b'"')
+                {
+                    i += 1;
+                }
+                match &line[start..i] {
+                    "true" | "false" => TokenKind::Bool,
+                    "null" => TokenKind::Null,
+                    word if word.starts_with(|c: char| c == '-' || c.is_ascii_digit())
+                        && word.ends_with(|c: char| c.is_ascii_digit())
+                        && word.parse::<f64>().is_ok() =>
+                    {
+                        TokenKind::Number
+                    }
+                    _ => TokenKind::Error,
+                }
+            }
+        };
+        tokens.push(Token {
+            start: column(start),
+            end: column(i),
+            kind,
+        });
+    }
+    tokens
+}
+
+pub fn tokenize_lines(
+    lines: &[&str],
+    start_line: u32,
+    total_lines: u32,
+    version: u64,
+) -> TokenizedRange {
+    TokenizedRange {
+        version,
+        start_line,
+        total_lines,
+        lines: lines.iter().map(|line| tokenize_line(line)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(line: &str) -> Vec<TokenKind> {
+        tokenize_line(line).into_iter().map(|t| t.kind).collect()
+    }
+
+    #[test]
+    fn classifies_keys_values_and_punctuation() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(r#"  "name" : "x\"y", "n": -1.5e3, "ok": [true, null] // c"#),
+            vec![
+                Key,
+                Punctuation,
+                String,
+                Punctuation,
+                Key,
+                Punctuation,
+                Number,
+                Punctuation,
+                Key,
+                Punctuation,
+                Punctuation,
+                Bool,
+                Punctuation,
+                Null,
+                Punctuation,
+                Comment
+            ]
+        );
+        let tokens = tokenize_line(r#"{"é": 1}"#);
+        assert_eq!((tokens[1].start, tokens[1].end), (1, 4));
+    }
+
+    #[test]
+    fn marks_invalid_words_and_unterminated_strings() {
+        assert_eq!(
+            kinds(r#"undefined, "open"#),
+            vec![TokenKind::Error, TokenKind::Punctuation, TokenKind::Error]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::goto::{dot_segments, pointer_segments};
+use super::types::{DocError, DocResult};
+
+const MAX_LABEL_CHARS: usize = 80;
+const MAX_BINS: u32 = 200;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistogramOptions {
+    pub top_k: usize,
+    pub ignore_case: bool,
+    pub numeric_bins: Option<u32>,
+}
+
+impl Default for HistogramOptions {
+    fn default() -> Self {
+        Self {
+            top_k: 20,
+            ignore_case: false,
+            numeric_bins: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    pub label: String,
+    pub value: Value,
+    pub count: u64,
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumericBin {
+    pub start: f64,
+    pub end: f64,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueHistogram {
+    pub matched: u64,
+    pub distinct: u64,
+    pub buckets: Vec<HistogramBucket>,
+    pub other: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bins: Option<Vec<NumericBin>>,
+}
+
+pub fn parse_glob(expr: &str) -> DocResult<Vec<String>> {
+    let expr = expr.trim();
+    let segments = if expr.starts_with('/') || expr.starts_with("#/") {
+        Some(pointer_segments(expr))
+    } else {
+        dot_segments(expr)
+    };
+    segments.ok_or_else(|| DocError::Edit(format!("invalid path expression: {expr}")))
+}
+
+pub fn select<'a>(value: &'a Value, segments: &[String], out: &mut Vec<&'a Value>) {
+    let Some((head, rest)) = segments.split_first() else {
+        out.push(value);
+        return;
+    };
+    if head == "**" {
+        select(value, rest, out);
+    }
+    let children: Box<dyn Iterator<Item = (String, &Value)>> = match value {
+        Value::Object(map) => Box::new(map.iter().map(|(k, v)| (k.clone(), v))),
+        Value::Array(items) => Box::new(items.iter().enumerate().map(|(i, v)| (i.to_string(), v))),
+        _ => return,
+    };
+    for (key, child) in children {
+        match head.as_str() {
+            "**" => select(child, segments, out),
+            "*" => select(child, rest, out),
+            name if name == key => select(child, rest, out),
+            _ => {}
+        }
+    }
+}
+
+fn label_of(value: &Value, ignore_case: bool) -> String {
+    let text = match value {
+        Value::String(s) if ignore_case => s.to_lowercase(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    match text.char_indices().nth(MAX_LABEL_CHARS) {
+        Some((at, _)) => format!("{}…", &text[..at]),
+        None => text,
+    }
+}
+
+fn bin(values: &[f64], bins: u32) -> Vec<NumericBin> {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let count = bins.clamp(1, MAX_BINS) as usize;
+    let width = if max > min {
+        (max - min) / count as f64
+    } else {
+        1.0
+    };
+    let mut out: Vec<NumericBin> = (0..count)
+        .map(|i| NumericBin {
+            start: min + width * i as f64,
+            end: min + width * (i + 1) as f64,
+            count: 0,
+        })
+        .collect();
+    for v in values {
+        let i = (((v - min) / width) as usize).min(count - 1);
+        out[i].count += 1;
+    }
+    out
+}
+
+pub fn histogram(content: &str, expr: &str, opts: &HistogramOptions) -> DocResult<ValueHistogram> {
+    let root: Value = serde_json::from_str(content).map_err(|e| DocError::Parse(e.to_string()))?;
+    let segments = parse_glob(expr)?;
+    let mut matched = Vec::new();
+    select(&root, &segments, &mut matched);
+
+    let mut counts: HashMap<String, (usize, u64, &Value)> = HashMap::new();
+    for (order, value) in matched.iter().enumerate() {
+        let entry = counts
+            .entry(label_of(value, opts.ignore_case))
+            .or_insert((order, 0, *value));
+        entry.1 += 1;
+    }
+    let total = matched.len() as u64;
+    let mut ranked: Vec<(String, (usize, u64, &Value))> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then(a.1 .0.cmp(&b.1 .0)));
+    let distinct = ranked.len() as u64;
+    let buckets: Vec<HistogramBucket> = ranked
+        .into_iter()
+        .take(opts.top_k.max(1))
+        .map(|(label, (_, count, value))| HistogramBucket {
+            label,
+            value: value.clone(),
+            count,
+            share: count as f64 / total as f64,
+        })
+        .collect();
+    let shown: u64 = buckets.iter().map(|b| b.count).sum();
+
+    let numbers: Vec<f64> = matched.iter().filter_map(|v| v.as_f64()).collect();
+    let bins = opts
+        .numeric_bins
+        .filter(|_| !numbers.is_empty() && numbers.len() == matched.len())
+        .map(|n| bin(&numbers, n));
+    Ok(ValueHistogram {
+        matched: total,
+        distinct,
+        buckets,
+        other: total - shown,
+        bins,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDERS: &str = r#"{"items": [
+        {"status": "paid", "total": 10},
+        {"status": "Paid", "total": 25},
+        {"status": "open", "total": 40},
+        {"status": "void", "total": 12},
+        {"nested": {"status": "open"}}
+    ]}"#;
+
+    #[test]
+    fn counts_values_matched_by_wildcards() {
+        let out = histogram(ORDERS, "items[*].status", &HistogramOptions::default()).unwrap();
+        assert_eq!((out.matched, out.distinct), (4, 4));
+
+        let opts = HistogramOptions {
+            top_k: 1,
+            ignore_case: true,
+            numeric_bins: None,
+        };
+        let out = histogram(ORDERS, "$.items.**.status", &opts).unwrap();
+        assert_eq!(out.matched, 5);
+        assert_eq!(out.buckets[0].label, "paid");
+        assert_eq!(out.buckets[0].count, 2);
+        assert_eq!(out.other, 3);
+        assert!(histogram(ORDERS, "items[0", &opts).is_err());
+    }
+
+    #[test]
+    fn bins_numeric_values() {
+        let opts = HistogramOptions {
+            numeric_bins: Some(3),
+            ..HistogramOptions::default()
+        };
+        let out = histogram(ORDERS, "/items/*/total", &opts).unwrap();
+        let counts: Vec<u64> = out.bins.unwrap().iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![2, 1, 1]);
+        assert!(histogram(ORDERS, "items.*.status", &opts)
+            .unwrap()
+            .bins
+            .is_none());
+    }
+}
@@ -82,12 +82,10 @@ impl History {
         self.evict_redo();
     }
 
-    #[allow(dead_code)] // exposed for Document::history_lens + history tests
     pub fn undo_len(&self) -> usize {
         self.undo.len()
     }
 
-    #[allow(dead_code)] // exposed for Document::history_lens + history tests
     pub fn redo_len(&self) -> usize {
         self.redo.len()
     }
@@ -104,6 +102,10 @@ impl History {
         self.cap
     }
 
+    pub fn heap_bytes(&self) -> usize {
+        self.undo_bytes + self.redo_bytes
+    }
+
     fn evict_undo(&mut self) {
         while self.undo.len() > self.cap
             || (self.undo.len() > 1 && self.undo_bytes > self.budget_bytes)
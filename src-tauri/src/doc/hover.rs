@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::completions::{schemas_at, type_label};
+use super::field_stats::{self, FieldProfile};
+use super::goto;
+use super::types::{NodeKind, Path};
+
+const MAX_STATS_ITEMS: usize = 50_000;
+const CONSTRAINT_KEYS: &[&str] = &[
+    "enum",
+    "const",
+    "format",
+    "pattern",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minLength",
+    "maxLength",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "minProperties",
+    "maxProperties",
+    "required",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaHover {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub schema_type: Option<String>,
+    pub constraints: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoverInfo {
+    pub path: Path,
+    pub pointer: String,
+    pub kind: NodeKind,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub child_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<SchemaHover>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub array_stats: Option<FieldProfile>,
+    pub stats_truncated: bool,
+}
+
+fn schema_hover(root: &Value, path: &Path) -> Option<SchemaHover> {
+    let schemas = schemas_at(root, &path.0);
+    if schemas.is_empty() {
+        return None;
+    }
+    let text = |key: &str| {
+        schemas
+            .iter()
+            .find_map(|s| s.get(key).and_then(Value::as_str))
+            .map(str::to_string)
+    };
+    let mut constraints = BTreeMap::new();
+    for s in &schemas {
+        for key in CONSTRAINT_KEYS {
+            if let Some(value) = s.get(*key) {
+                constraints
+                    .entry(key.to_string())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+    }
+    Some(SchemaHover {
+        title: text("title"),
+        description: text("description"),
+        schema_type: type_label(&schemas),
+        constraints,
+    })
+}
+
+pub fn hover(path: &Path, value: &Value, schema: Option<&Value>) -> HoverInfo {
+    let (child_count, array_stats, stats_truncated) = match value {
+        Value::Object(map) => (Some(map.len() as u32), None, false),
+        Value::Array(items) => (
+            Some(items.len() as u32),
+            Some(field_stats::profile_values(
+                path.to_pointer(),
+                items.iter().take(MAX_STATS_ITEMS),
+            )),
+            items.len() > MAX_STATS_ITEMS,
+        ),
+        _ => (None, None, false),
+    };
+    HoverInfo {
+        path: path.clone(),
+        pointer: path.to_pointer(),
+        kind: goto::kind_of(value),
+        size: serde_json::to_vec(value).map_or(0, |bytes| bytes.len() as u64),
+        child_count,
+        schema: schema.and_then(|root| schema_hover(root, path)),
+        array_stats,
+        stats_truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::types::PathSegment;
+    use serde_json::json;
+
+    #[test]
+    fn describes_value_with_schema_constraints() {
+        let schema = json!({
+            "properties": {
+                "age": {"$ref": "#/$defs/age"}
+            },
+            "$defs": {
+                "age": {"title": "Age", "type": "integer", "minimum": 0, "description": "Years"}
+            }
+        });
+        let path = Path(vec![PathSegment::Key("age".into())]);
+        let info = hover(&path, &json!(42), Some(&schema));
+        assert_eq!(
+            (info.kind, info.size, info.child_count),
+            (NodeKind::Number, 2, None)
+        );
+        let s = info.schema.unwrap();
+        assert_eq!(s.title.as_deref(), Some("Age"));
+        assert_eq!(s.schema_type.as_deref(), Some("integer"));
+        assert_eq!(s.constraints.get("minimum"), Some(&json!(0)));
+    }
+
+    #[test]
+    fn summarizes_arrays() {
+        let info = hover(&Path::root(), &json!([1, 2, "x", null]), None);
+        assert_eq!(info.child_count, Some(4));
+        assert!(info.schema.is_none());
+        let stats = info.array_stats.unwrap();
+        assert_eq!(stats.present, 4);
+        assert_eq!(
+            (stats.types.integer, stats.types.string, stats.types.null),
+            (2, 1, 1)
+        );
+        assert_eq!(stats.numeric.map(|n| n.mean), Some(1.5));
+    }
+}
@@ -0,0 +1,272 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\{\{\s*([\w.]+)\s*\}\}|\{\s*([\w.]+)\s*(?:,[^{}]*(?:\{[^{}]*\}[^{}]*)*)?\}|%(?:\d+\$)?[sdif@]")
+        .expect("valid regex")
+});
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaceholderMismatch {
+    pub key: String,
+    pub source: Vec<String>,
+    pub translation: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleComparison {
+    pub total_keys: u32,
+    pub translated: u32,
+    pub coverage: f64,
+    pub missing: Vec<String>,
+    pub extra: Vec<String>,
+    pub empty: Vec<String>,
+    pub placeholder_mismatches: Vec<PlaceholderMismatch>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MissingFill {
+    #[default]
+    Source,
+    Empty,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MergeOptions {
+    pub fill: MissingFill,
+    pub keep_extra: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LocaleMerge {
+    pub merged: Value,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+fn flatten_into(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                flatten_into(&join_key(prefix, k), v, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                flatten_into(&join_key(prefix, &i.to_string()), v, out);
+            }
+        }
+        leaf => out.push((prefix.to_string(), leaf.clone())),
+    }
+}
+
+pub fn flatten(value: &Value) -> Vec<(String, Value)> {
+    let mut out = Vec::new();
+    flatten_into("", value, &mut out);
+    out
+}
+
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut found: Vec<String> = PLACEHOLDER
+        .captures_iter(text)
+        .map(|c| match (c.get(1), c.get(2)) {
+            (Some(name), _) | (None, Some(name)) => format!("{{{}}}", name.as_str()),
+            (None, None) => c[0].to_string(),
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+fn is_empty_translation(v: &Value) -> bool {
+    match v {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+pub fn compare(base: &Value, target: &Value) -> LocaleComparison {
+    let base_flat = flatten(base);
+    let target_flat: Map<String, Value> = flatten(target).into_iter().collect();
+    let base_keys: std::collections::HashSet<&str> =
+        base_flat.iter().map(|(k, _)| k.as_str()).collect();
+
+    let mut missing = Vec::new();
+    let mut empty = Vec::new();
+    let mut placeholder_mismatches = Vec::new();
+    for (key, source) in &base_flat {
+        let Some(translation) = target_flat.get(key) else {
+            missing.push(key.clone());
+            continue;
+        };
+        if is_empty_translation(translation) {
+            empty.push(key.clone());
+            continue;
+        }
+        if let (Some(s), Some(t)) = (source.as_str(), translation.as_str()) {
+            let (source, translation) = (placeholders(s), placeholders(t));
+            if source != translation {
+                placeholder_mismatches.push(PlaceholderMismatch {
+                    key: key.clone(),
+                    source,
+                    translation,
+                });
+            }
+        }
+    }
+    let extra = target_flat
+        .keys()
+        .filter(|k| !base_keys.contains(k.as_str()))
+        .cloned()
+        .collect();
+    let total_keys = base_flat.len() as u32;
+    let translated = total_keys - missing.len() as u32 - empty.len() as u32;
+    LocaleComparison {
+        total_keys,
+        translated,
+        coverage: if total_keys == 0 {
+            1.0
+        } else {
+            f64::from(translated) / f64::from(total_keys)
+        },
+        missing,
+        extra,
+        empty,
+        placeholder_mismatches,
+    }
+}
+
+fn merge_value(
+    prefix: &str,
+    base: &Value,
+    target: Option<&Value>,
+    opts: &MergeOptions,
+    out: &mut LocaleMerge,
+) -> Value {
+    match base {
+        Value::Object(base_map) => {
+            let target_map = target.and_then(Value::as_object);
+            let mut merged = Map::new();
+            for (k, v) in base_map {
+                let key = join_key(prefix, k);
+                let t = target_map.and_then(|m| m.get(k));
+                merged.insert(k.clone(), merge_value(&key, v, t, opts, out));
+            }
+            for (k, v) in target_map.into_iter().flatten() {
+                if base_map.contains_key(k) {
+                    continue;
+                }
+                if opts.keep_extra {
+                    merged.insert(k.clone(), v.clone());
+                } else {
+                    out.removed.push(join_key(prefix, k));
+                }
+            }
+            Value::Object(merged)
+        }
+        leaf => match target {
+            Some(t) if !t.is_object() && !t.is_array() => t.clone(),
+            _ => {
+                out.added.push(prefix.to_string());
+                match (opts.fill, leaf) {
+                    (MissingFill::Empty, Value::String(_)) => Value::String(String::new()),
+                    _ => leaf.clone(),
+                }
+            }
+        },
+    }
+}
+
+pub fn merge(base: &Value, target: &Value, opts: &MergeOptions) -> LocaleMerge {
+    let mut out = LocaleMerge::default();
+    let merged = merge_value("", base, Some(target), opts, &mut out);
+    out.merged = merged;
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn base() -> Value {
+        json!({
+            "greeting": "Hello, {name}!",
+            "inbox": { "count": "{count, plural, one {# message} other {# messages}}", "empty": "No mail" },
+            "title": "Welcome {{user}}",
+            "legacy": "%1$s of %2$s"
+        })
+    }
+
+    #[test]
+    fn extracts_placeholders_across_syntaxes() {
+        assert_eq!(
+            placeholders("Hi {name}, {{ user }}"),
+            vec!["{name}", "{user}"]
+        );
+        assert_eq!(
+            placeholders("{count, plural, one {# item} other {# items}}"),
+            vec!["{count}"]
+        );
+        assert_eq!(placeholders("%1$s and %d"), vec!["%1$s", "%d"]);
+    }
+
+    #[test]
+    fn compare_reports_missing_extra_empty_and_mismatches() {
+        let target = json!({
+            "greeting": "Hola, {nombre}!",
+            "inbox": { "count": "{count, plural, one {# mensaje} other {# mensajes}}", "empty": "" },
+            "legacy": "%1$s de %2$s",
+            "old": "viejo"
+        });
+        let c = compare(&base(), &target);
+        assert_eq!(c.total_keys, 5);
+        assert_eq!(c.missing, vec!["title"]);
+        assert_eq!(c.empty, vec!["inbox.empty"]);
+        assert_eq!(c.extra, vec!["old"]);
+        assert_eq!(c.placeholder_mismatches.len(), 1);
+        assert_eq!(c.placeholder_mismatches[0].key, "greeting");
+        assert_eq!(c.translated, 3);
+    }
+
+    #[test]
+    fn merge_keeps_translations_and_follows_base_layout() {
+        let target =
+            json!({ "old": "x", "inbox": { "empty": "Sin correo" }, "greeting": "Hola, {name}!" });
+        let m = merge(&base(), &target, &MergeOptions::default());
+        assert_eq!(
+            m.merged,
+            json!({
+                "greeting": "Hola, {name}!",
+                "inbox": { "count": "{count, plural, one {# message} other {# messages}}", "empty": "Sin correo" },
+                "title": "Welcome {{user}}",
+                "legacy": "%1$s of %2$s"
+            })
+        );
+        assert_eq!(m.added, vec!["inbox.count", "title", "legacy"]);
+        assert_eq!(m.removed, vec!["old"]);
+
+        let opts = MergeOptions {
+            fill: MissingFill::Empty,
+            keep_extra: true,
+        };
+        let m = merge(&base(), &target, &opts);
+        assert_eq!(m.merged["title"], "");
+        assert_eq!(m.merged["old"], "x");
+    }
+}
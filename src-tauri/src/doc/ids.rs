@@ -0,0 +1,324 @@
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Deserialize;
+
+use super::timefmt::now_millis;
+use super::types::{DocError, DocResult};
+
+pub const MAX_IDS: u32 = 10_000;
+const NANOID_ALPHABET: &str = "useandom-26T198340PX75pxJACKVERYMINDBUSHWOLF_GQZbfghjklqvwyzrict";
+const NANOID_DEFAULT_SIZE: u32 = 21;
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const SNOWFLAKE_EPOCH_MS: u64 = 1_288_834_974_657;
+const SNOWFLAKE_MAX_WORKER: u16 = 1023;
+const SNOWFLAKE_MAX_SEQUENCE: u64 = 4095;
+const ULID_RANDOM_MAX: u128 = (1u128 << 80) - 1;
+
+static SNOWFLAKE_CLOCK: Lazy<Mutex<(u64, u64)>> = Lazy::new(|| Mutex::new((0, 0)));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdKind {
+    UuidV4,
+    UuidV7,
+    Ulid,
+    Nanoid,
+    Snowflake,
+    Sequence,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdOptions {
+    #[serde(default)]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub uppercase: bool,
+    #[serde(default)]
+    pub size: Option<u32>,
+    #[serde(default)]
+    pub alphabet: Option<String>,
+    #[serde(default)]
+    pub worker_id: Option<u16>,
+    #[serde(default)]
+    pub epoch_ms: Option<u64>,
+    #[serde(default)]
+    pub start: Option<u64>,
+    #[serde(default)]
+    pub pad: Option<u32>,
+}
+
+pub fn generate_ids(kind: IdKind, count: u32, opts: &IdOptions) -> DocResult<Vec<String>> {
+    if count > MAX_IDS {
+        return Err(DocError::TooLarge {
+            actual: count as u64,
+            limit: MAX_IDS as u64,
+        });
+    }
+    let n = count as usize;
+    let raw = match kind {
+        IdKind::UuidV4 => (0..n).map(|_| uuid::Uuid::new_v4().to_string()).collect(),
+        IdKind::UuidV7 => (0..n).map(|_| uuid::Uuid::now_v7().to_string()).collect(),
+        IdKind::Ulid => ulids(n)?,
+        IdKind::Nanoid => nanoids(n, opts)?,
+        IdKind::Snowflake => snowflakes(n, opts)?,
+        IdKind::Sequence => sequence(n, opts)?,
+    };
+    let prefix = opts.prefix.as_deref().unwrap_or("");
+    Ok(raw
+        .into_iter()
+        .map(|id: String| {
+            let id = if opts.uppercase && matches!(kind, IdKind::UuidV4 | IdKind::UuidV7) {
+                id.to_ascii_uppercase()
+            } else {
+                id
+            };
+            format!("{prefix}{id}")
+        })
+        .collect())
+}
+
+pub fn ulid() -> DocResult<String> {
+    Ok(ulids(1)?.remove(0))
+}
+
+pub fn nanoid() -> DocResult<String> {
+    Ok(nanoids(1, &IdOptions::default())?.remove(0))
+}
+
+fn random_bytes(n: usize) -> DocResult<Vec<u8>> {
+    let mut buf = vec![0u8; n];
+    getrandom::fill(&mut buf).map_err(|e| DocError::Io(std::io::Error::other(e.to_string())))?;
+    Ok(buf)
+}
+
+fn ulids(n: usize) -> DocResult<Vec<String>> {
+    let mut out = Vec::with_capacity(n);
+    let mut last: Option<(u64, u128)> = None;
+    for _ in 0..n {
+        let ms = now_millis() & 0xFFFF_FFFF_FFFF;
+        let random = match last {
+            Some((prev_ms, prev_rand)) if prev_ms == ms => {
+                if prev_rand == ULID_RANDOM_MAX {
+                    return Err(DocError::Edit(
+                        "ULID random component overflowed within one millisecond".into(),
+                    ));
+                }
+                prev_rand + 1
+            }
+            _ => {
+                let bytes = random_bytes(10)?;
+                bytes.iter().fold(0u128, |acc, b| (acc << 8) | *b as u128)
+            }
+        };
+        last = Some((ms, random));
+        out.push(encode_ulid(((ms as u128) << 80) | random));
+    }
+    Ok(out)
+}
+
+fn encode_ulid(value: u128) -> String {
+    (0..26)
+        .map(|i| CROCKFORD[((value >> (125 - 5 * i)) & 0x1F) as usize] as char)
+        .collect()
+}
+
+fn nanoids(n: usize, opts: &IdOptions) -> DocResult<Vec<String>> {
+    let alphabet: Vec<char> = opts
+        .alphabet
+        .as_deref()
+        .filter(|a| !a.is_empty())
+        .unwrap_or(NANOID_ALPHABET)
+        .chars()
+        .collect();
+    if alphabet.len() < 2 || alphabet.len() > 256 {
+        return Err(DocError::Edit(
+            "nanoid alphabet must have between 2 and 256 characters".into(),
+        ));
+    }
+    let size = opts.size.unwrap_or(NANOID_DEFAULT_SIZE).clamp(1, 256) as usize;
+    let mask = alphabet.len().next_power_of_two() - 1;
+    let mut out = Vec::with_capacity(n);
+    for _ in 0..n {
+        let mut id = String::with_capacity(size);
+        while id.chars().count() < size {
+            for b in random_bytes(size * 2)? {
+                let idx = b as usize & mask;
+                if idx < alphabet.len() {
+                    id.push(alphabet[idx]);
+                    if id.chars().count() == size {
+                        break;
+                    }
+                }
+            }
+        }
+        out.push(id);
+    }
+    Ok(out)
+}
+
+fn snowflakes(n: usize, opts: &IdOptions) -> DocResult<Vec<String>> {
+    let worker = opts.worker_id.unwrap_or(0);
+    if worker > SNOWFLAKE_MAX_WORKER {
+        return Err(DocError::Edit(format!(
+            "snowflake worker id must be at most {SNOWFLAKE_MAX_WORKER}"
+        )));
+    }
+    let epoch = opts.epoch_ms.unwrap_or(SNOWFLAKE_EPOCH_MS);
+    let mut clock = SNOWFLAKE_CLOCK.lock();
+    let mut out = Vec::with_capacity(n);
+    for _ in 0..n {
+        let (last_ms, seq) = *clock;
+        let now = now_millis().max(last_ms);
+        *clock = if now > last_ms {
+            (now, 0)
+        } else if seq < SNOWFLAKE_MAX_SEQUENCE {
+            (now, seq + 1)
+        } else {
+            (next_millis(last_ms), 0)
+        };
+        let (ms, seq) = *clock;
+        let ms = ms.saturating_sub(epoch);
+        let id = ((ms & 0x1FF_FFFF_FFFF) << 22) | ((worker as u64) << 12) | seq;
+        out.push(id.to_string());
+    }
+    Ok(out)
+}
+
+fn next_millis(after: u64) -> u64 {
+    loop {
+        let now = now_millis();
+        if now > after {
+            return now;
+        }
+        std::thread::sleep(std::time::Duration::from_micros(100));
+    }
+}
+
+fn sequence(n: usize, opts: &IdOptions) -> DocResult<Vec<String>> {
+    let start = opts.start.unwrap_or(1);
+    let pad = opts.pad.unwrap_or(0) as usize;
+    (0..n as u64)
+        .map(|i| {
+            start
+                .checked_add(i)
+                .map(|id| format!("{id:0pad$}"))
+                .ok_or_else(|| DocError::Edit(format!("sequence starting at {start} overflows")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn ids(kind: IdKind, count: u32) -> Vec<String> {
+        generate_ids(kind, count, &IdOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn uuids_are_unique_and_versioned() {
+        let v4 = ids(IdKind::UuidV4, 50);
+        assert_eq!(v4.iter().collect::<HashSet<_>>().len(), 50);
+        assert!(v4.iter().all(|u| u.as_bytes()[14] == b'4'));
+        let v7 = ids(IdKind::UuidV7, 50);
+        assert!(v7.iter().all(|u| u.as_bytes()[14] == b'7'));
+        let mut sorted = v7.clone();
+        sorted.sort();
+        assert_eq!(sorted, v7);
+    }
+
+    #[test]
+    fn ulids_are_crockford_and_monotonic() {
+        let u = ids(IdKind::Ulid, 200);
+        assert!(u.iter().all(|id| id.len() == 26));
+        assert!(u
+            .iter()
+            .all(|id| id.bytes().all(|b| CROCKFORD.contains(&b))));
+        let mut sorted = u.clone();
+        sorted.sort();
+        assert_eq!(sorted, u);
+        assert_eq!(u.iter().collect::<HashSet<_>>().len(), 200);
+    }
+
+    #[test]
+    fn encode_ulid_known_values() {
+        assert_eq!(encode_ulid(0), "00000000000000000000000000");
+        assert_eq!(encode_ulid(u128::MAX), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+    }
+
+    #[test]
+    fn nanoid_respects_size_and_alphabet() {
+        let opts = IdOptions {
+            size: Some(10),
+            alphabet: Some("abc".into()),
+            ..Default::default()
+        };
+        let out = generate_ids(IdKind::Nanoid, 20, &opts).unwrap();
+        assert!(out
+            .iter()
+            .all(|id| id.len() == 10 && id.chars().all(|c| "abc".contains(c))));
+        assert_eq!(ids(IdKind::Nanoid, 1)[0].len(), 21);
+
+        let bad = IdOptions {
+            alphabet: Some("a".into()),
+            ..Default::default()
+        };
+        assert!(generate_ids(IdKind::Nanoid, 1, &bad).is_err());
+    }
+
+    #[test]
+    fn snowflakes_encode_worker_and_increase() {
+        let opts = IdOptions {
+            worker_id: Some(5),
+            ..Default::default()
+        };
+        let out = generate_ids(IdKind::Snowflake, 5000, &opts).unwrap();
+        let nums: Vec<u64> = out.iter().map(|s| s.parse().unwrap()).collect();
+        assert!(nums.windows(2).all(|w| w[0] < w[1]));
+        assert!(nums.iter().all(|n| (n >> 12) & 0x3FF == 5));
+        let next = generate_ids(IdKind::Snowflake, 1, &opts).unwrap();
+        assert!(next[0].parse::<u64>().unwrap() > *nums.last().unwrap());
+
+        let bad = IdOptions {
+            worker_id: Some(2000),
+            ..Default::default()
+        };
+        assert!(generate_ids(IdKind::Snowflake, 1, &bad).is_err());
+    }
+
+    #[test]
+    fn sequence_with_prefix_and_padding() {
+        let opts = IdOptions {
+            prefix: Some("user_".into()),
+            start: Some(9),
+            pad: Some(3),
+            ..Default::default()
+        };
+        let out = generate_ids(IdKind::Sequence, 3, &opts).unwrap();
+        assert_eq!(out, vec!["user_009", "user_010", "user_011"]);
+        let near_max = IdOptions {
+            start: Some(u64::MAX - 1),
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_ids(IdKind::Sequence, 2, &near_max).unwrap().len(),
+            2
+        );
+        assert!(generate_ids(IdKind::Sequence, 3, &near_max).is_err());
+    }
+
+    #[test]
+    fn count_is_capped() {
+        let err = generate_ids(IdKind::UuidV4, MAX_IDS + 1, &IdOptions::default()).unwrap_err();
+        assert!(matches!(err, DocError::TooLarge { .. }));
+        assert!(ids(IdKind::Ulid, 0).is_empty());
+    }
+
+    #[test]
+    fn kind_deserializes_kebab_case() {
+        let k: IdKind = serde_json::from_str(r#""uuid-v7""#).unwrap();
+        assert_eq!(k, IdKind::UuidV7);
+    }
+}
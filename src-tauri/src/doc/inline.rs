@@ -0,0 +1,341 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::types::{DocError, DocResult};
+
+const DEFAULT_MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InlineOptions {
+    pub root_dir: Option<String>,
+    pub inline_paths: bool,
+    pub max_depth: usize,
+    pub output_path: Option<String>,
+}
+
+impl Default for InlineOptions {
+    fn default() -> Self {
+        Self {
+            root_dir: None,
+            inline_paths: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            output_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlinedFile {
+    pub path: String,
+    pub references: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedRef {
+    pub file: String,
+    pub reference: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineReport {
+    pub inlined_files: Vec<InlinedFile>,
+    pub skipped: Vec<SkippedRef>,
+}
+
+type Target = (PathBuf, String);
+
+struct Inliner<'a> {
+    root: PathBuf,
+    opts: &'a InlineOptions,
+    cache: HashMap<PathBuf, Value>,
+    stack: Vec<Target>,
+    counts: BTreeMap<String, u32>,
+    skipped: Vec<SkippedRef>,
+}
+
+impl Inliner<'_> {
+    fn display(&self, path: &FsPath) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    fn skip(&mut self, file: &FsPath, reference: &str, reason: impl Into<String>) {
+        self.skipped.push(SkippedRef {
+            file: self.display(file),
+            reference: reference.to_string(),
+            reason: reason.into(),
+        });
+    }
+
+    fn target(
+        &self,
+        base: &FsPath,
+        reference: &str,
+        local_ok: bool,
+    ) -> Result<Option<Target>, String> {
+        if reference.contains("://") {
+            return Ok(None);
+        }
+        let (file_part, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        if !fragment.is_empty() && !fragment.starts_with('/') {
+            return Err(format!("unsupported fragment #{fragment}"));
+        }
+        let file = if file_part.is_empty() {
+            if !local_ok {
+                return Ok(None);
+            }
+            base.to_path_buf()
+        } else {
+            let dir = base.parent().unwrap_or(FsPath::new(""));
+            std::fs::canonicalize(dir.join(file_part)).map_err(|e| e.to_string())?
+        };
+        if !file.starts_with(&self.root) {
+            return Err("outside the root directory".into());
+        }
+        Ok(Some((file, fragment.to_string())))
+    }
+
+    fn load(&mut self, file: &FsPath) -> Result<&Value, String> {
+        if !self.cache.contains_key(file) {
+            let text = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+            let value: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+            self.cache.insert(file.to_path_buf(), value);
+        }
+        Ok(&self.cache[file])
+    }
+
+    fn fetch(&mut self, base: &FsPath, reference: &str, target: Target) -> Option<Value> {
+        if self.stack.contains(&target) {
+            self.skip(base, reference, "cycle");
+            return None;
+        }
+        if self.stack.len() > self.opts.max_depth {
+            self.skip(base, reference, "nesting too deep");
+            return None;
+        }
+        let (file, fragment) = &target;
+        let found = match self.load(file) {
+            Ok(doc) => doc.pointer(fragment).cloned(),
+            Err(e) => {
+                self.skip(base, reference, e);
+                return None;
+            }
+        };
+        let Some(mut value) = found else {
+            self.skip(base, reference, format!("nothing at #{fragment}"));
+            return None;
+        };
+        let file = file.clone();
+        self.stack.push(target);
+        self.walk(&mut value, &file);
+        self.stack.pop();
+        let key = self.display(&file);
+        *self.counts.entry(key).or_default() += 1;
+        Some(value)
+    }
+
+    fn resolve(&mut self, base: &FsPath, reference: &str, local_ok: bool) -> Option<Value> {
+        match self.target(base, reference, local_ok) {
+            Ok(Some(target)) => self.fetch(base, reference, target),
+            Ok(None) => None,
+            Err(reason) => {
+                self.skip(base, reference, reason);
+                None
+            }
+        }
+    }
+
+    fn looks_like_path(s: &str) -> bool {
+        !s.contains("://")
+            && !s.contains('\n')
+            && FsPath::new(s.split('#').next().unwrap_or(s))
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+    }
+
+    fn walk(&mut self, value: &mut Value, base: &FsPath) {
+        let local_ok = self.stack.len() > 1;
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(reference)) = map.get("$ref") {
+                    let reference = reference.clone();
+                    if let Some(resolved) = self.resolve(base, &reference, local_ok) {
+                        map.remove("$ref");
+                        *value = match resolved {
+                            Value::Object(mut target) if !map.is_empty() => {
+                                target.extend(std::mem::take(map));
+                                Value::Object(target)
+                            }
+                            other => other,
+                        };
+                    }
+                    return;
+                }
+                for child in map.values_mut() {
+                    self.walk(child, base);
+                }
+            }
+            Value::Array(items) => {
+                for child in items {
+                    self.walk(child, base);
+                }
+            }
+            Value::String(s) if self.opts.inline_paths && Self::looks_like_path(s) => {
+                let reference = s.clone();
+                let exists = base.parent().is_some_and(|dir| {
+                    dir.join(reference.split('#').next().unwrap_or(""))
+                        .is_file()
+                });
+                if exists {
+                    if let Some(resolved) = self.resolve(base, &reference, false) {
+                        *value = resolved;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn inline_refs(entry: &FsPath, opts: &InlineOptions) -> DocResult<(Value, InlineReport)> {
+    let entry = std::fs::canonicalize(entry)?;
+    let root = match &opts.root_dir {
+        Some(dir) => std::fs::canonicalize(dir)?,
+        None => entry.parent().map(FsPath::to_path_buf).unwrap_or_default(),
+    };
+    if !entry.starts_with(&root) {
+        return Err(DocError::Edit(format!(
+            "{} is not inside {}",
+            entry.display(),
+            root.display()
+        )));
+    }
+    let text = std::fs::read_to_string(&entry)?;
+    let mut value: Value = serde_json::from_str(&text)
+        .map_err(|e| DocError::Parse(format!("{}: {e}", entry.display())))?;
+    let mut inliner = Inliner {
+        root,
+        opts,
+        cache: HashMap::new(),
+        stack: vec![(entry.clone(), String::new())],
+        counts: BTreeMap::new(),
+        skipped: Vec::new(),
+    };
+    inliner.walk(&mut value, &entry);
+    let report = InlineReport {
+        inlined_files: inliner
+            .counts
+            .into_iter()
+            .map(|(path, references)| InlinedFile { path, references })
+            .collect(),
+        skipped: inliner.skipped,
+    };
+    Ok((value, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+
+    fn write(dir: &FsPath, rel: &str, value: Value) {
+        std::fs::write(dir.join(rel), value.to_string()).unwrap();
+    }
+
+    #[test]
+    fn inlines_files_fragments_and_nested_refs() {
+        let dir = TempDir::new("inline-nested");
+        std::fs::create_dir_all(dir.join("parts")).unwrap();
+        write(
+            &dir,
+            "main.json",
+            json!({
+                "db": {"$ref": "./parts/db.json"},
+                "user": {"$ref": "parts/defs.json#/user", "description": "override"},
+                "local": {"$ref": "#/db"}
+            }),
+        );
+        write(
+            &dir,
+            "parts/db.json",
+            json!({"host": "x", "pool": {"$ref": "#/../pool"}}),
+        );
+        write(
+            &dir,
+            "parts/defs.json",
+            json!({"user": {"name": {"$ref": "#/name"}}, "name": {"type": "string"}}),
+        );
+        let (value, report) =
+            inline_refs(&dir.join("main.json"), &InlineOptions::default()).unwrap();
+        assert_eq!(value["db"]["host"], "x");
+        assert_eq!(
+            value["user"],
+            json!({"name": {"type": "string"}, "description": "override"})
+        );
+        assert_eq!(value["local"], json!({"$ref": "#/db"}));
+        assert_eq!(
+            report.inlined_files,
+            vec![
+                InlinedFile {
+                    path: "parts/db.json".into(),
+                    references: 1
+                },
+                InlinedFile {
+                    path: "parts/defs.json".into(),
+                    references: 2
+                },
+            ]
+        );
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].reason, "nothing at #/../pool");
+    }
+
+    #[test]
+    fn detects_cycles_and_escapes() {
+        let dir = TempDir::new("inline-cycle");
+        std::fs::create_dir_all(dir.join("parts")).unwrap();
+        write(
+            &dir,
+            "a.json",
+            json!({"b": {"$ref": "parts/b.json"}, "up": {"$ref": "../x.json"}}),
+        );
+        write(&dir, "parts/b.json", json!({"a": {"$ref": "../a.json"}}));
+        let (value, report) = inline_refs(&dir.join("a.json"), &InlineOptions::default()).unwrap();
+        assert_eq!(value["b"]["a"], json!({"$ref": "../a.json"}));
+        let reasons: Vec<&str> = report.skipped.iter().map(|s| s.reason.as_str()).collect();
+        assert!(reasons.contains(&"cycle"));
+        assert_eq!(report.skipped.len(), 2);
+    }
+
+    #[test]
+    fn inlines_path_strings_when_enabled() {
+        let dir = TempDir::new("inline-paths");
+        std::fs::create_dir_all(dir.join("parts")).unwrap();
+        write(
+            &dir,
+            "main.json",
+            json!({"db": "./parts/db.json", "note": "readme.json"}),
+        );
+        write(&dir, "parts/db.json", json!({"port": 1}));
+        let opts = InlineOptions {
+            inline_paths: true,
+            ..Default::default()
+        };
+        let (value, report) = inline_refs(&dir.join("main.json"), &opts).unwrap();
+        assert_eq!(value, json!({"db": {"port": 1}, "note": "readme.json"}));
+        assert!(report.skipped.is_empty());
+        let (plain, _) = inline_refs(&dir.join("main.json"), &InlineOptions::default()).unwrap();
+        assert_eq!(plain["db"], "./parts/db.json");
+    }
+}
@@ -4,6 +4,8 @@ use std::sync::Arc;
 
 use parking_lot::Mutex;
 
+use super::types::{DocError, DocResult};
+
 #[derive(Clone, Debug, Default)]
 pub struct CancelFlag {
     cancelled: Arc<AtomicBool>,
@@ -29,10 +31,16 @@ pub struct JobRegistry {
 }
 
 impl JobRegistry {
-    pub fn register(&self, id: String) -> CancelFlag {
+    /// Fails on an id that is still running: replacing its flag would leave
+    /// the first job uncancellable and let its unregister drop the second.
+    pub fn register(&self, id: String) -> DocResult<CancelFlag> {
+        let mut inner = self.inner.lock();
+        if inner.contains_key(&id) {
+            return Err(DocError::Conflict(format!("job {id} is already running")));
+        }
         let flag = CancelFlag::default();
-        self.inner.lock().insert(id, flag.clone());
-        flag
+        inner.insert(id, flag.clone());
+        Ok(flag)
     }
 
     pub fn unregister(&self, id: &str) {
@@ -65,12 +73,26 @@ mod tests {
     #[test]
     fn register_then_cancel_flips_flag() {
         let reg = JobRegistry::default();
-        let flag = reg.register("a".into());
+        let flag = reg.register("a".into()).unwrap();
         assert!(!flag.is_cancelled());
         assert!(reg.cancel("a"));
         assert!(flag.is_cancelled());
     }
 
+    #[test]
+    fn duplicate_ids_are_rejected_until_unregistered() {
+        let reg = JobRegistry::default();
+        let first = reg.register("a".into()).unwrap();
+        assert!(matches!(
+            reg.register("a".into()),
+            Err(DocError::Conflict(_))
+        ));
+        assert!(reg.cancel("a"));
+        assert!(first.is_cancelled());
+        reg.unregister("a");
+        assert!(!reg.register("a".into()).unwrap().is_cancelled());
+    }
+
     #[test]
     fn cancel_unknown_id_returns_false() {
         let reg = JobRegistry::default();
@@ -80,7 +102,7 @@ mod tests {
     #[test]
     fn unregister_removes_flag_from_future_cancels() {
         let reg = JobRegistry::default();
-        let flag = reg.register("a".into());
+        let flag = reg.register("a".into()).unwrap();
         reg.unregister("a");
         assert!(!reg.cancel("a"));
         flag.cancel();
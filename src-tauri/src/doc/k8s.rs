@@ -0,0 +1,709 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use super::schema_validate::{self, SchemaError};
+use super::types::{DocError, DocResult};
+
+pub const DEFAULT_VERSION: &str = "1.30";
+pub const SUPPORTED_VERSIONS: &[&str] = &[
+    "1.16", "1.17", "1.18", "1.19", "1.20", "1.21", "1.22", "1.23", "1.24", "1.25", "1.26", "1.27",
+    "1.28", "1.29", "1.30", "1.31",
+];
+
+const CLEANUP_METADATA: &[&str] = &[
+    "managedFields",
+    "uid",
+    "resourceVersion",
+    "generation",
+    "creationTimestamp",
+    "selfLink",
+];
+const LAST_APPLIED: &str = "kubectl.kubernetes.io/last-applied-configuration";
+
+struct ApiLifecycle {
+    api_version: &'static str,
+    kinds: &'static [&'static str],
+    introduced: Option<u32>,
+    removed: Option<u32>,
+    replacement: Option<&'static str>,
+}
+
+const LIFECYCLE: &[ApiLifecycle] = &[
+    ApiLifecycle {
+        api_version: "extensions/v1beta1",
+        kinds: &["Deployment", "DaemonSet", "ReplicaSet", "NetworkPolicy"],
+        introduced: None,
+        removed: Some(16),
+        replacement: Some("apps/v1"),
+    },
+    ApiLifecycle {
+        api_version: "apps/v1beta1",
+        kinds: &["Deployment", "StatefulSet"],
+        introduced: None,
+        removed: Some(16),
+        replacement: Some("apps/v1"),
+    },
+    ApiLifecycle {
+        api_version: "apps/v1beta2",
+        kinds: &["Deployment", "StatefulSet", "DaemonSet", "ReplicaSet"],
+        introduced: None,
+        removed: Some(16),
+        replacement: Some("apps/v1"),
+    },
+    ApiLifecycle {
+        api_version: "extensions/v1beta1",
+        kinds: &["Ingress"],
+        introduced: None,
+        removed: Some(22),
+        replacement: Some("networking.k8s.io/v1"),
+    },
+    ApiLifecycle {
+        api_version: "networking.k8s.io/v1beta1",
+        kinds: &["Ingress", "IngressClass"],
+        introduced: None,
+        removed: Some(22),
+        replacement: Some("networking.k8s.io/v1"),
+    },
+    ApiLifecycle {
+        api_version: "networking.k8s.io/v1",
+        kinds: &["Ingress", "IngressClass"],
+        introduced: Some(19),
+        removed: None,
+        replacement: None,
+    },
+    ApiLifecycle {
+        api_version: "batch/v1beta1",
+        kinds: &["CronJob"],
+        introduced: None,
+        removed: Some(25),
+        replacement: Some("batch/v1"),
+    },
+    ApiLifecycle {
+        api_version: "batch/v1",
+        kinds: &["CronJob"],
+        introduced: Some(21),
+        removed: None,
+        replacement: None,
+    },
+    ApiLifecycle {
+        api_version: "policy/v1beta1",
+        kinds: &["PodDisruptionBudget"],
+        introduced: None,
+        removed: Some(25),
+        replacement: Some("policy/v1"),
+    },
+    ApiLifecycle {
+        api_version: "policy/v1beta1",
+        kinds: &["PodSecurityPolicy"],
+        introduced: None,
+        removed: Some(25),
+        replacement: None,
+    },
+    ApiLifecycle {
+        api_version: "policy/v1",
+        kinds: &["PodDisruptionBudget"],
+        introduced: Some(21),
+        removed: None,
+        replacement: None,
+    },
+    ApiLifecycle {
+        api_version: "autoscaling/v2beta1",
+        kinds: &["HorizontalPodAutoscaler"],
+        introduced: None,
+        removed: Some(25),
+        replacement: Some("autoscaling/v2"),
+    },
+    ApiLifecycle {
+        api_version: "autoscaling/v2beta2",
+        kinds: &["HorizontalPodAutoscaler"],
+        introduced: None,
+        removed: Some(26),
+        replacement: Some("autoscaling/v2"),
+    },
+    ApiLifecycle {
+        api_version: "autoscaling/v2",
+        kinds: &["HorizontalPodAutoscaler"],
+        introduced: Some(23),
+        removed: None,
+        replacement: None,
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct K8sResource {
+    pub api_version: String,
+    pub group: String,
+    pub version: String,
+    pub kind: String,
+    pub name: Option<String>,
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct K8sResourceReport {
+    pub pointer: String,
+    pub resource: K8sResource,
+    pub schema_bundled: bool,
+    pub api_warning: Option<String>,
+    pub errors: Vec<SchemaError>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct K8sValidation {
+    pub version: String,
+    pub valid: bool,
+    pub resources: Vec<K8sResourceReport>,
+}
+
+pub fn detect(value: &Value) -> Option<K8sResource> {
+    let api_version = value.get("apiVersion")?.as_str()?.to_string();
+    let kind = value.get("kind")?.as_str()?.to_string();
+    if api_version.is_empty() || kind.is_empty() {
+        return None;
+    }
+    let (group, version) = match api_version.split_once('/') {
+        Some((g, v)) => (g.to_string(), v.to_string()),
+        None => (String::new(), api_version.clone()),
+    };
+    let metadata = value.get("metadata");
+    let field = |k: &str| {
+        metadata
+            .and_then(|m| m.get(k))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    Some(K8sResource {
+        api_version,
+        group,
+        version,
+        kind,
+        name: field("name").or_else(|| field("generateName")),
+        namespace: field("namespace"),
+    })
+}
+
+pub fn split_stream(text: &str) -> DocResult<Vec<Value>> {
+    use serde::Deserialize;
+    let mut docs = Vec::new();
+    for (i, de) in serde_yaml_ng::Deserializer::from_str(text).enumerate() {
+        let value = Value::deserialize(de)
+            .map_err(|e| DocError::Parse(format!("YAML document {}: {e}", i + 1)))?;
+        if !value.is_null() {
+            docs.push(value);
+        }
+    }
+    Ok(docs)
+}
+
+fn parse_minor(version: &str) -> DocResult<u32> {
+    let v = version.trim().trim_start_matches('v');
+    if !SUPPORTED_VERSIONS.contains(&v) {
+        return Err(DocError::Schema(format!(
+            "unsupported Kubernetes version {version} (expected one of {})",
+            SUPPORTED_VERSIONS.join(", ")
+        )));
+    }
+    v.split('.')
+        .nth(1)
+        .and_then(|m| m.parse().ok())
+        .ok_or_else(|| DocError::Schema(format!("bad Kubernetes version {version}")))
+}
+
+fn api_warning(resource: &K8sResource, minor: u32) -> Option<String> {
+    let entry = LIFECYCLE.iter().find(|l| {
+        l.api_version == resource.api_version && l.kinds.contains(&resource.kind.as_str())
+    })?;
+    if let Some(removed) = entry.removed.filter(|r| minor >= *r) {
+        let hint = entry
+            .replacement
+            .map(|r| format!("; use {r}"))
+            .unwrap_or_default();
+        return Some(format!(
+            "{} {} was removed in 1.{removed}{hint}",
+            resource.api_version, resource.kind
+        ));
+    }
+    if let Some(introduced) = entry.introduced.filter(|i| minor < *i) {
+        return Some(format!(
+            "{} {} is not available before 1.{introduced}",
+            resource.api_version, resource.kind
+        ));
+    }
+    None
+}
+
+fn string_map() -> Value {
+    json!({ "type": "object", "additionalProperties": { "type": "string" } })
+}
+
+fn object_meta(require_name: bool) -> Value {
+    let mut meta = json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "pattern": "^[a-z0-9]([-a-z0-9.]*[a-z0-9])?$", "maxLength": 253 },
+            "generateName": { "type": "string" },
+            "namespace": { "type": "string", "pattern": "^[a-z0-9]([-a-z0-9]*[a-z0-9])?$", "maxLength": 63 },
+            "labels": string_map(),
+            "annotations": string_map()
+        }
+    });
+    if require_name {
+        meta["anyOf"] = json!([{ "required": ["name"] }, { "required": ["generateName"] }]);
+    }
+    meta
+}
+
+fn int_or_string() -> Value {
+    json!({ "type": ["integer", "string"] })
+}
+
+fn container() -> Value {
+    json!({
+        "type": "object",
+        "required": ["name"],
+        "properties": {
+            "name": { "type": "string" },
+            "image": { "type": "string" },
+            "command": { "type": "array", "items": { "type": "string" } },
+            "args": { "type": "array", "items": { "type": "string" } },
+            "imagePullPolicy": { "enum": ["Always", "IfNotPresent", "Never"] },
+            "ports": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["containerPort"],
+                    "properties": {
+                        "containerPort": { "type": "integer", "minimum": 1, "maximum": 65535 },
+                        "protocol": { "enum": ["TCP", "UDP", "SCTP"] }
+                    }
+                }
+            },
+            "env": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": { "name": { "type": "string" }, "value": { "type": "string" } }
+                }
+            }
+        }
+    })
+}
+
+fn pod_spec() -> Value {
+    json!({
+        "type": "object",
+        "required": ["containers"],
+        "properties": {
+            "containers": { "type": "array", "minItems": 1, "items": container() },
+            "initContainers": { "type": "array", "items": container() },
+            "restartPolicy": { "enum": ["Always", "OnFailure", "Never"] },
+            "serviceAccountName": { "type": "string" },
+            "nodeSelector": string_map()
+        }
+    })
+}
+
+fn pod_template() -> Value {
+    json!({
+        "type": "object",
+        "required": ["spec"],
+        "properties": { "metadata": object_meta(false), "spec": pod_spec() }
+    })
+}
+
+fn label_selector() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "matchLabels": string_map(),
+            "matchExpressions": { "type": "array" }
+        }
+    })
+}
+
+fn workload_spec() -> Value {
+    json!({
+        "type": "object",
+        "required": ["selector", "template"],
+        "properties": {
+            "replicas": { "type": "integer", "minimum": 0 },
+            "selector": label_selector(),
+            "template": pod_template()
+        }
+    })
+}
+
+fn spec_schema(api_version: &str, kind: &str) -> Option<Option<Value>> {
+    let spec = match (api_version, kind) {
+        ("v1", "Pod") => Some(pod_spec()),
+        ("v1", "Service") => Some(json!({
+            "type": "object",
+            "properties": {
+                "type": { "enum": ["ClusterIP", "NodePort", "LoadBalancer", "ExternalName"] },
+                "selector": string_map(),
+                "ports": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["port"],
+                        "properties": {
+                            "port": { "type": "integer", "minimum": 1, "maximum": 65535 },
+                            "targetPort": int_or_string(),
+                            "nodePort": { "type": "integer" },
+                            "protocol": { "enum": ["TCP", "UDP", "SCTP"] }
+                        }
+                    }
+                }
+            }
+        })),
+        ("v1", "PersistentVolumeClaim") => Some(json!({
+            "type": "object",
+            "properties": {
+                "accessModes": {
+                    "type": "array",
+                    "items": { "enum": ["ReadWriteOnce", "ReadOnlyMany", "ReadWriteMany", "ReadWriteOncePod"] }
+                },
+                "storageClassName": { "type": "string" },
+                "resources": { "type": "object" }
+            }
+        })),
+        ("v1", "ConfigMap" | "Secret" | "Namespace" | "ServiceAccount") => None,
+        ("apps/v1", "Deployment" | "DaemonSet" | "ReplicaSet" | "StatefulSet") => {
+            Some(workload_spec())
+        }
+        ("batch/v1", "Job") => Some(json!({
+            "type": "object",
+            "required": ["template"],
+            "properties": {
+                "template": pod_template(),
+                "backoffLimit": { "type": "integer", "minimum": 0 },
+                "completions": { "type": "integer", "minimum": 0 },
+                "parallelism": { "type": "integer", "minimum": 0 }
+            }
+        })),
+        ("batch/v1", "CronJob") => Some(json!({
+            "type": "object",
+            "required": ["schedule", "jobTemplate"],
+            "properties": {
+                "schedule": { "type": "string" },
+                "suspend": { "type": "boolean" },
+                "concurrencyPolicy": { "enum": ["Allow", "Forbid", "Replace"] },
+                "jobTemplate": {
+                    "type": "object",
+                    "required": ["spec"],
+                    "properties": {
+                        "spec": {
+                            "type": "object",
+                            "required": ["template"],
+                            "properties": { "template": pod_template() }
+                        }
+                    }
+                }
+            }
+        })),
+        ("networking.k8s.io/v1", "Ingress") => Some(json!({
+            "type": "object",
+            "properties": {
+                "ingressClassName": { "type": "string" },
+                "rules": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "host": { "type": "string" },
+                            "http": {
+                                "type": "object",
+                                "required": ["paths"],
+                                "properties": {
+                                    "paths": {
+                                        "type": "array",
+                                        "items": {
+                                            "type": "object",
+                                            "required": ["pathType", "backend"],
+                                            "properties": {
+                                                "path": { "type": "string" },
+                                                "pathType": { "enum": ["Exact", "Prefix", "ImplementationSpecific"] },
+                                                "backend": { "type": "object" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })),
+        ("autoscaling/v2", "HorizontalPodAutoscaler") => Some(json!({
+            "type": "object",
+            "required": ["scaleTargetRef", "maxReplicas"],
+            "properties": {
+                "scaleTargetRef": {
+                    "type": "object",
+                    "required": ["kind", "name"],
+                    "properties": { "kind": { "type": "string" }, "name": { "type": "string" } }
+                },
+                "minReplicas": { "type": "integer", "minimum": 1 },
+                "maxReplicas": { "type": "integer", "minimum": 1 },
+                "metrics": { "type": "array" }
+            }
+        })),
+        _ => return None,
+    };
+    Some(spec)
+}
+
+pub fn bundled_schema(api_version: &str, kind: &str) -> Option<Value> {
+    let spec = spec_schema(api_version, kind)?;
+    let mut properties = Map::new();
+    properties.insert("apiVersion".into(), json!({ "const": api_version }));
+    properties.insert("kind".into(), json!({ "const": kind }));
+    properties.insert("metadata".into(), object_meta(true));
+    let mut required = vec![json!("apiVersion"), json!("kind"), json!("metadata")];
+    match kind {
+        "ConfigMap" => {
+            properties.insert("data".into(), string_map());
+            properties.insert("binaryData".into(), string_map());
+            properties.insert("immutable".into(), json!({ "type": "boolean" }));
+        }
+        "Secret" => {
+            properties.insert("data".into(), string_map());
+            properties.insert("stringData".into(), string_map());
+            properties.insert("type".into(), json!({ "type": "string" }));
+            properties.insert("immutable".into(), json!({ "type": "boolean" }));
+        }
+        _ => {}
+    }
+    if let Some(spec) = spec {
+        properties.insert("spec".into(), spec);
+        if kind != "Service" && kind != "PersistentVolumeClaim" {
+            required.push(json!("spec"));
+        }
+    }
+    Some(json!({ "type": "object", "required": required, "properties": properties }))
+}
+
+fn generic_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["apiVersion", "kind", "metadata"],
+        "properties": { "metadata": object_meta(true) }
+    })
+}
+
+fn check_resource(
+    pointer: String,
+    value: &Value,
+    minor: u32,
+) -> DocResult<Option<K8sResourceReport>> {
+    let Some(resource) = detect(value) else {
+        return Ok(None);
+    };
+    let bundled = bundled_schema(&resource.api_version, &resource.kind);
+    let schema = bundled.clone().unwrap_or_else(generic_schema);
+    let result = schema_validate::validate_value(value, &schema)
+        .map_err(|e| DocError::Schema(e.to_string()))?;
+    let errors = result
+        .errors
+        .into_iter()
+        .map(|mut e| {
+            e.instance_path = format!("{pointer}{}", e.instance_path);
+            e
+        })
+        .collect();
+    Ok(Some(K8sResourceReport {
+        api_warning: api_warning(&resource, minor),
+        pointer,
+        resource,
+        schema_bundled: bundled.is_some(),
+        errors,
+    }))
+}
+
+pub fn validate(root: &Value, version: Option<&str>) -> DocResult<K8sValidation> {
+    let version = version.unwrap_or(DEFAULT_VERSION);
+    let minor = parse_minor(version)?;
+    let mut resources = Vec::new();
+    let items = match root {
+        Value::Array(items) => Some(items),
+        v if v
+            .get("kind")
+            .and_then(Value::as_str)
+            .is_some_and(|k| k.ends_with("List")) =>
+        {
+            v.get("items").and_then(Value::as_array)
+        }
+        _ => None,
+    };
+    match items {
+        Some(items) => {
+            let base = if root.is_array() { "" } else { "/items" };
+            for (i, item) in items.iter().enumerate() {
+                if let Some(r) = check_resource(format!("{base}/{i}"), item, minor)? {
+                    resources.push(r);
+                }
+            }
+        }
+        None => {
+            if let Some(r) = check_resource(String::new(), root, minor)? {
+                resources.push(r);
+            }
+        }
+    }
+    if resources.is_empty() {
+        return Err(DocError::Schema(
+            "no Kubernetes resources found (apiVersion and kind are required)".into(),
+        ));
+    }
+    Ok(K8sValidation {
+        version: version.trim_start_matches('v').to_string(),
+        valid: resources
+            .iter()
+            .all(|r| r.errors.is_empty() && r.api_warning.is_none()),
+        resources,
+    })
+}
+
+fn cleanup_resource(value: &mut Value) {
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+    obj.shift_remove("status");
+    if let Some(meta) = obj.get_mut("metadata").and_then(Value::as_object_mut) {
+        for key in CLEANUP_METADATA {
+            meta.shift_remove(*key);
+        }
+        let empty = match meta.get_mut("annotations").and_then(Value::as_object_mut) {
+            Some(annotations) => {
+                annotations.shift_remove(LAST_APPLIED);
+                annotations.is_empty()
+            }
+            None => false,
+        };
+        if empty {
+            meta.shift_remove("annotations");
+        }
+    }
+}
+
+pub fn cleanup(root: &Value) -> Value {
+    let mut out = root.clone();
+    let is_list = out
+        .get("kind")
+        .and_then(Value::as_str)
+        .is_some_and(|k| k.ends_with("List"));
+    match &mut out {
+        Value::Array(items) => items.iter_mut().for_each(cleanup_resource),
+        v if is_list => {
+            if let Some(items) = v.get_mut("items").and_then(Value::as_array_mut) {
+                items.iter_mut().for_each(cleanup_resource);
+            }
+            if let Some(meta) = v.get_mut("metadata").and_then(Value::as_object_mut) {
+                meta.shift_remove("resourceVersion");
+                meta.shift_remove("selfLink");
+            }
+        }
+        v => cleanup_resource(v),
+    }
+    out
+}
+
+pub fn stream_tab_name(file_name: &str, index: usize, resource: Option<&K8sResource>) -> String {
+    match resource {
+        Some(r) => format!(
+            "{file_name} [{}] {}/{}",
+            index + 1,
+            r.kind,
+            r.name.as_deref().unwrap_or("unnamed")
+        ),
+        None => format!("{file_name} [{}]", index + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployment() -> Value {
+        json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": { "name": "web", "namespace": "prod" },
+            "spec": {
+                "selector": { "matchLabels": { "app": "web" } },
+                "template": {
+                    "metadata": { "labels": { "app": "web" } },
+                    "spec": { "containers": [{ "name": "web", "image": "nginx:1.27" }] }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn detects_group_version_kind() {
+        let r = detect(&deployment()).unwrap();
+        assert_eq!(r.group, "apps");
+        assert_eq!(r.version, "v1");
+        assert_eq!(r.kind, "Deployment");
+        assert_eq!(r.name.as_deref(), Some("web"));
+        assert_eq!(r.namespace.as_deref(), Some("prod"));
+        assert!(detect(&json!({ "replicaCount": 2 })).is_none());
+    }
+
+    #[test]
+    fn splits_multi_document_streams() {
+        let text = "---\napiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: a\n---\n---\napiVersion: v1\nkind: Secret\nmetadata:\n  name: b\n";
+        let docs = split_stream(text).unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[1]["kind"], "Secret");
+    }
+
+    #[test]
+    fn validates_against_bundled_schema_and_api_lifecycle() {
+        let ok = validate(&deployment(), None).unwrap();
+        assert!(ok.valid, "{:?}", ok.resources[0].errors);
+        assert!(ok.resources[0].schema_bundled);
+
+        let mut broken = deployment();
+        broken["spec"]["template"]["spec"]["containers"] = json!([]);
+        broken["spec"]["replicas"] = json!(-1);
+        let report = validate(&broken, Some("1.30")).unwrap();
+        assert!(!report.valid);
+        assert_eq!(report.resources[0].errors.len(), 2);
+
+        let mut old = json!({
+            "apiVersion": "batch/v1beta1",
+            "kind": "CronJob",
+            "metadata": { "name": "nightly" }
+        });
+        assert!(validate(&old, Some("1.24")).unwrap().valid);
+        let warned = validate(&old, Some("1.25")).unwrap();
+        assert!(warned.resources[0]
+            .api_warning
+            .as_deref()
+            .unwrap()
+            .contains("batch/v1"));
+        old["apiVersion"] = json!("batch/v1");
+        assert!(validate(&old, Some("1.20")).unwrap().resources[0]
+            .api_warning
+            .is_some());
+        assert!(validate(&old, Some("2.0")).is_err());
+    }
+
+    #[test]
+    fn cleanup_strips_server_fields() {
+        let mut item = deployment();
+        item["status"] = json!({ "replicas": 1 });
+        item["metadata"]["managedFields"] = json!([{ "manager": "kubectl" }]);
+        item["metadata"]["uid"] = json!("abc");
+        item["metadata"]["annotations"] = json!({ LAST_APPLIED: "{}" });
+        let list = json!({ "apiVersion": "v1", "kind": "List", "metadata": { "resourceVersion": "9" }, "items": [item] });
+        let cleaned = cleanup(&list);
+        assert_eq!(cleaned["items"][0], deployment());
+        assert_eq!(cleaned["metadata"], json!({}));
+    }
+}
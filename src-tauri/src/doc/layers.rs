@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::heatmap::{line_for_pointer, pointer_lines};
+use super::positions::PositionIndex;
+use super::provenance::Origin;
+use super::schema_refs::parse_schema_text;
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+pub const MAX_LAYERS: usize = 32;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "mode")]
+pub enum ArrayStrategy {
+    #[default]
+    Replace,
+    Concat,
+    MergeByKey {
+        key: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerMerge {
+    pub merged: Value,
+    pub layers: Vec<String>,
+    pub provenance: Vec<Origin>,
+}
+
+pub struct Layer {
+    pub name: String,
+    pub value: Value,
+    pub lines: HashMap<String, u32>,
+}
+
+impl Layer {
+    pub fn new(name: impl Into<String>, value: Value) -> Self {
+        Self {
+            name: name.into(),
+            value,
+            lines: HashMap::new(),
+        }
+    }
+}
+
+struct Source {
+    layer: usize,
+    pointer: String,
+}
+
+enum Node {
+    Leaf(Value, Source),
+    Object(Vec<(String, Node)>, Source),
+    Array(Vec<Node>, Source),
+}
+
+impl Node {
+    fn from_value(value: Value, layer: usize, path: &mut Path) -> Self {
+        let source = Source {
+            layer,
+            pointer: path.to_pointer(),
+        };
+        match value {
+            Value::Object(map) => {
+                let mut fields = Vec::with_capacity(map.len());
+                for (k, v) in map {
+                    path.push(PathSegment::Key(k.clone()));
+                    fields.push((k, Node::from_value(v, layer, path)));
+                    path.0.pop();
+                }
+                Node::Object(fields, source)
+            }
+            Value::Array(items) => {
+                let mut nodes = Vec::with_capacity(items.len());
+                for (i, item) in items.into_iter().enumerate() {
+                    path.push(PathSegment::Index(i as u32));
+                    nodes.push(Node::from_value(item, layer, path));
+                    path.0.pop();
+                }
+                Node::Array(nodes, source)
+            }
+            leaf => Node::Leaf(leaf, source),
+        }
+    }
+
+    fn key_of(&self, key: &str) -> Option<&Value> {
+        let Node::Object(fields, _) = self else {
+            return None;
+        };
+        fields.iter().find_map(|(k, v)| match v {
+            Node::Leaf(value, _) if k == key && !value.is_null() => Some(value),
+            _ => None,
+        })
+    }
+
+    fn into_value(self, path: &mut Path, layers: &[Layer], out: &mut Vec<Origin>) -> Value {
+        match self {
+            Node::Leaf(value, source) => {
+                push_origin(out, layers, &source, path);
+                value
+            }
+            Node::Object(fields, source) => {
+                if fields.is_empty() {
+                    push_origin(out, layers, &source, path);
+                }
+                let mut map = Map::new();
+                for (k, v) in fields {
+                    path.push(PathSegment::Key(k.clone()));
+                    map.insert(k, v.into_value(path, layers, out));
+                    path.0.pop();
+                }
+                Value::Object(map)
+            }
+            Node::Array(items, source) => {
+                if items.is_empty() {
+                    push_origin(out, layers, &source, path);
+                }
+                let mut values = Vec::with_capacity(items.len());
+                for (i, item) in items.into_iter().enumerate() {
+                    path.push(PathSegment::Index(i as u32));
+                    values.push(item.into_value(path, layers, out));
+                    path.0.pop();
+                }
+                Value::Array(values)
+            }
+        }
+    }
+}
+
+fn push_origin(out: &mut Vec<Origin>, layers: &[Layer], source: &Source, path: &Path) {
+    let layer = &layers[source.layer];
+    out.push(Origin {
+        pointer: path.to_pointer(),
+        file: layer.name.clone(),
+        layer: source.layer as u32,
+        line: line_for_pointer(&layer.lines, &source.pointer).map(u64::from),
+    });
+}
+
+fn merge(base: Node, over: Node, strategy: &ArrayStrategy) -> Node {
+    match (base, over) {
+        (Node::Object(mut fields, layer), Node::Object(over_fields, _)) => {
+            for (k, v) in over_fields {
+                match fields.iter().position(|(existing, _)| *existing == k) {
+                    Some(i) => {
+                        let (key, old) = fields.remove(i);
+                        fields.insert(i, (key, merge(old, v, strategy)));
+                    }
+                    None => fields.push((k, v)),
+                }
+            }
+            Node::Object(fields, layer)
+        }
+        (Node::Array(mut items, layer), Node::Array(over_items, over_layer)) => match strategy {
+            ArrayStrategy::Replace => Node::Array(over_items, over_layer),
+            ArrayStrategy::Concat => {
+                items.extend(over_items);
+                Node::Array(items, layer)
+            }
+            ArrayStrategy::MergeByKey { key } => {
+                for item in over_items {
+                    let matched = item.key_of(key).and_then(|id| {
+                        items
+                            .iter()
+                            .position(|existing| existing.key_of(key) == Some(id))
+                    });
+                    match matched {
+                        Some(i) => {
+                            let old = items.remove(i);
+                            items.insert(i, merge(old, item, strategy));
+                        }
+                        None => items.push(item),
+                    }
+                }
+                Node::Array(items, layer)
+            }
+        },
+        (_, over) => over,
+    }
+}
+
+pub fn merge_values(mut layers: Vec<Layer>, strategy: &ArrayStrategy) -> DocResult<LayerMerge> {
+    if layers.is_empty() {
+        return Err(DocError::Edit("at least one layer is required".into()));
+    }
+    if layers.len() > MAX_LAYERS {
+        return Err(DocError::TooLarge {
+            actual: layers.len() as u64,
+            limit: MAX_LAYERS as u64,
+        });
+    }
+    let mut merged: Option<Node> = None;
+    for (i, layer) in layers.iter_mut().enumerate() {
+        let node = Node::from_value(layer.value.take(), i, &mut Path::root());
+        merged = Some(match merged {
+            Some(base) => merge(base, node, strategy),
+            None => node,
+        });
+    }
+    let mut provenance = Vec::new();
+    let merged = merged
+        .map(|node| node.into_value(&mut Path::root(), &layers, &mut provenance))
+        .unwrap_or(Value::Null);
+    Ok(LayerMerge {
+        merged,
+        layers: layers.into_iter().map(|l| l.name).collect(),
+        provenance,
+    })
+}
+
+pub fn merge_files(paths: &[String], strategy: &ArrayStrategy) -> DocResult<LayerMerge> {
+    let layers = paths
+        .iter()
+        .map(|path| -> DocResult<Layer> {
+            let text = std::fs::read_to_string(path)?;
+            let value =
+                parse_schema_text(&text).map_err(|e| DocError::Parse(format!("{path}: {e}")))?;
+            let lines = PositionIndex::build(&text)
+                .map(|index| pointer_lines(&index))
+                .unwrap_or_default();
+            Ok(Layer {
+                name: path.clone(),
+                value,
+                lines,
+            })
+        })
+        .collect::<DocResult<Vec<_>>>()?;
+    merge_values(layers, strategy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+
+    fn layers() -> Vec<Layer> {
+        vec![
+            Layer::new(
+                "base.json",
+                json!({
+                    "db": {"host": "localhost", "port": 5432},
+                    "features": ["a"],
+                    "servers": [{"name": "a", "port": 1}]
+                }),
+            ),
+            Layer::new(
+                "prod.json",
+                json!({
+                    "db": {"host": "db.internal"},
+                    "features": ["b"],
+                    "servers": [{"name": "b", "port": 3}, {"name": "a", "port": 2}]
+                }),
+            ),
+        ]
+    }
+
+    fn origin_of<'a>(merge: &'a LayerMerge, pointer: &str) -> &'a str {
+        &merge
+            .provenance
+            .iter()
+            .find(|o| o.pointer == pointer)
+            .unwrap()
+            .file
+    }
+
+    #[test]
+    fn merges_with_provenance() {
+        let out = merge_values(layers(), &ArrayStrategy::Replace).unwrap();
+        assert_eq!(
+            out.merged["db"],
+            json!({"host": "db.internal", "port": 5432})
+        );
+        assert_eq!(out.merged["features"], json!(["b"]));
+        assert_eq!(origin_of(&out, "/db/host"), "prod.json");
+        assert_eq!(origin_of(&out, "/db/port"), "base.json");
+        assert_eq!(origin_of(&out, "/features/0"), "prod.json");
+        let port = out.provenance.iter().find(|o| o.pointer == "/db/port");
+        assert_eq!(port.map(|o| o.layer), Some(0));
+    }
+
+    #[test]
+    fn array_strategies() {
+        let concat = merge_values(layers(), &ArrayStrategy::Concat).unwrap();
+        assert_eq!(concat.merged["features"], json!(["a", "b"]));
+        assert_eq!(origin_of(&concat, "/features/0"), "base.json");
+
+        let keyed = ArrayStrategy::MergeByKey { key: "name".into() };
+        let by_key = merge_values(layers(), &keyed).unwrap();
+        assert_eq!(
+            by_key.merged["servers"],
+            json!([{"name": "a", "port": 2}, {"name": "b", "port": 3}])
+        );
+        assert_eq!(origin_of(&by_key, "/servers/1/port"), "prod.json");
+        assert_eq!(origin_of(&by_key, "/servers/0/port"), "prod.json");
+        assert!(merge_values(Vec::new(), &keyed).is_err());
+    }
+
+    #[test]
+    fn file_layers_report_source_lines() {
+        let dir = TempDir::new("layers");
+        let base = dir.join("base.json");
+        let local = dir.join("local.json");
+        std::fs::write(&base, "{\n  \"port\": 80,\n  \"host\": \"a\"\n}\n").unwrap();
+        std::fs::write(&local, "{\n\n  \"host\": \"b\"\n}\n").unwrap();
+        let paths = [base, local].map(|p| p.to_string_lossy().into_owned());
+        let out = merge_files(&paths, &ArrayStrategy::Replace).unwrap();
+        let line = |pointer: &str| {
+            let o = out
+                .provenance
+                .iter()
+                .find(|o| o.pointer == pointer)
+                .unwrap();
+            (o.file.as_str(), o.line)
+        };
+        assert_eq!(line("/port"), (paths[0].as_str(), Some(2)));
+        assert_eq!(line("/host"), (paths[1].as_str(), Some(3)));
+    }
+}
@@ -8,6 +8,7 @@ use super::search::{
     prepare as search_prepare, string_match_snippet, substr_contains, MatchField, SearchHit,
     SearchOptions,
 };
+use super::simd;
 use super::typegen::{
     merge as merge_shape, ObjectProp, PrimitiveKind, TypeShape, ARRAY_SAMPLE_CAP,
 };
@@ -43,6 +44,10 @@ impl LazyDoc {
         })
     }
 
+    pub fn heap_bytes(&self) -> usize {
+        self.source.len() + self.root_index.as_ref().map_or(0, |index| index.len() * 8)
+    }
+
     pub fn root_kind(&self) -> NodeKind {
         self.root_kind
     }
@@ -51,6 +56,10 @@ impl LazyDoc {
         self.source.as_str()
     }
 
+    pub fn shared_source(&self) -> FastStr {
+        self.source.clone()
+    }
+
     pub fn root_child_count(&self) -> Option<u32> {
         if let Some(idx) = &self.root_index {
             return Some(idx.len() as u32);
@@ -357,22 +366,9 @@ fn skip_value(bytes: &[u8], i: usize) -> DocResult<usize> {
     }
 }
 
-fn skip_string(bytes: &[u8], mut i: usize) -> DocResult<usize> {
+fn skip_string(bytes: &[u8], i: usize) -> DocResult<usize> {
     debug_assert_eq!(bytes[i], b'"');
-    i += 1;
-    while i < bytes.len() {
-        match bytes[i] {
-            b'"' => return Ok(i + 1),
-            b'\\' => {
-                if i + 1 >= bytes.len() {
-                    return Err(DocError::Parse("trailing escape".into()));
-                }
-                i += 2;
-            }
-            _ => i += 1,
-        }
-    }
-    Err(DocError::Parse("unterminated string".into()))
+    simd::string_end(bytes, i).ok_or_else(|| DocError::Parse("unterminated string".into()))
 }
 
 fn skip_balanced(bytes: &[u8], i: usize) -> DocResult<usize> {
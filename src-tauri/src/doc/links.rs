@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri_plugin_http::reqwest::{Client, StatusCode};
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
+
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+const MAX_CONCURRENCY: usize = 32;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LinkOptions {
+    pub concurrency: usize,
+    pub timeout_ms: u64,
+    pub requests_per_second: Option<f64>,
+    pub max_links: usize,
+}
+
+impl Default for LinkOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            timeout_ms: 10_000,
+            requests_per_second: None,
+            max_links: 1_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkStatus {
+    pub pointer: String,
+    pub url: String,
+    pub ok: bool,
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirected_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkReport {
+    pub links: Vec<LinkStatus>,
+    pub checked: u32,
+    pub broken: u32,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Outcome {
+    ok: bool,
+    status: Option<u16>,
+    redirected_to: Option<String>,
+    error: Option<String>,
+    elapsed_ms: u64,
+}
+
+pub fn extract(value: &Value) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    let mut path = Path::root();
+    collect(value, &mut path, &mut out);
+    out
+}
+
+fn collect(value: &Value, path: &mut Path, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::String(s) => {
+            let url = s.trim();
+            if is_link(url) {
+                out.push((path.to_pointer(), url.to_string()));
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                path.push(PathSegment::Index(i as u32));
+                collect(item, path, out);
+                path.0.pop();
+            }
+        }
+        Value::Object(map) => {
+            for (k, v) in map {
+                path.push(PathSegment::Key(k.clone()));
+                collect(v, path, out);
+                path.0.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_link(s: &str) -> bool {
+    let rest = s
+        .strip_prefix("https://")
+        .or_else(|| s.strip_prefix("http://"));
+    rest.is_some_and(|r| !r.is_empty() && !r.starts_with('/') && !r.contains(char::is_whitespace))
+}
+
+struct Throttle {
+    interval: Option<Duration>,
+    next: Mutex<Instant>,
+}
+
+impl Throttle {
+    async fn wait(&self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let slot = {
+            let mut next = self.next.lock().await;
+            let slot = (*next).max(Instant::now());
+            *next = slot + interval;
+            slot
+        };
+        tokio::time::sleep_until(slot.into()).await;
+    }
+}
+
+async fn probe(client: &Client, url: &str) -> Outcome {
+    let started = Instant::now();
+    let mut result = client.head(url).send().await;
+    let retry_with_get = match &result {
+        Ok(resp) => matches!(
+            resp.status(),
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED | StatusCode::FORBIDDEN
+        ),
+        Err(e) => !e.is_timeout(),
+    };
+    if retry_with_get {
+        result = client.get(url).send().await;
+    }
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(resp) => {
+            let status = resp.status();
+            let final_url = resp.url().as_str();
+            Outcome {
+                ok: status.is_success(),
+                status: Some(status.as_u16()),
+                redirected_to: (final_url != url).then(|| final_url.to_string()),
+                error: None,
+                elapsed_ms,
+            }
+        }
+        Err(e) => Outcome {
+            ok: false,
+            status: None,
+            redirected_to: None,
+            error: Some(if e.is_timeout() {
+                "timed out".to_string()
+            } else {
+                e.to_string()
+            }),
+            elapsed_ms,
+        },
+    }
+}
+
+pub async fn check(content: &str, opts: &LinkOptions) -> DocResult<LinkReport> {
+    let value: Value = serde_json::from_str(content).map_err(|e| DocError::Parse(e.to_string()))?;
+    let mut found = extract(&value);
+    let mut report = LinkReport::default();
+    if found.len() > opts.max_links {
+        found.truncate(opts.max_links);
+        report.truncated = true;
+    }
+    let mut unique: BTreeMap<String, Option<Outcome>> =
+        found.iter().map(|(_, url)| (url.clone(), None)).collect();
+
+    let client = Client::builder()
+        .timeout(Duration::from_millis(opts.timeout_ms.max(1)))
+        .build()
+        .map_err(|e| DocError::Io(std::io::Error::other(e.to_string())))?;
+    let permits = Arc::new(Semaphore::new(opts.concurrency.clamp(1, MAX_CONCURRENCY)));
+    let throttle = Arc::new(Throttle {
+        interval: opts
+            .requests_per_second
+            .filter(|r| r.is_finite() && *r > 0.0)
+            .map(|r| Duration::from_secs_f64(1.0 / r)),
+        next: Mutex::new(Instant::now()),
+    });
+    let mut tasks = JoinSet::new();
+    for url in unique.keys().cloned() {
+        let (client, permits, throttle) = (client.clone(), permits.clone(), throttle.clone());
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await.ok();
+            throttle.wait().await;
+            let outcome = probe(&client, &url).await;
+            (url, outcome)
+        });
+    }
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((url, outcome)) = joined {
+            unique.insert(url, Some(outcome));
+        }
+    }
+
+    report.checked = unique.len() as u32;
+    for (pointer, url) in found {
+        let outcome = unique.get(&url).cloned().flatten().unwrap_or(Outcome {
+            ok: false,
+            status: None,
+            redirected_to: None,
+            error: Some("check did not complete".into()),
+            elapsed_ms: 0,
+        });
+        if !outcome.ok {
+            report.broken += 1;
+        }
+        report.links.push(LinkStatus {
+            pointer,
+            url,
+            ok: outcome.ok,
+            status: outcome.status,
+            redirected_to: outcome.redirected_to,
+            error: outcome.error,
+            elapsed_ms: outcome.elapsed_ms,
+        });
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn extracts_http_urls_with_pointers() {
+        let doc = json!({
+            "home": "https://example.com/",
+            "pages": [{"loc": " http://example.com/a "}, {"loc": "/relative"}],
+            "notes": "see https://example.com for details",
+            "mail": "mailto:a@example.com",
+            "bare": "https://"
+        });
+        assert_eq!(
+            extract(&doc),
+            vec![
+                ("/home".to_string(), "https://example.com/".to_string()),
+                (
+                    "/pages/0/loc".to_string(),
+                    "http://example.com/a".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::BTreeMap;
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::positions::{Node, PositionIndex, TextPosition};
+use super::types::{DocError, DocResult, PathSegment};
+
+pub const CONFIG_FILE: &str = ".pandia-lint.json";
+
+const DEFAULT_MAX_DEPTH: usize = 20;
+const MAX_FINDINGS: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct LintRules {
+    pub duplicate_keys: Severity,
+    pub key_casing: Severity,
+    pub empty_containers: Severity,
+    pub numeric_strings: Severity,
+    pub mixed_array_types: Severity,
+    pub max_depth: Severity,
+    pub trailing_whitespace: Severity,
+}
+
+impl Default for LintRules {
+    fn default() -> Self {
+        Self {
+            duplicate_keys: Severity::Error,
+            key_casing: Severity::Info,
+            empty_containers: Severity::Info,
+            numeric_strings: Severity::Info,
+            mixed_array_types: Severity::Warning,
+            max_depth: Severity::Warning,
+            trailing_whitespace: Severity::Info,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct LintConfig {
+    pub rules: LintRules,
+    pub max_depth: usize,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            rules: LintRules::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintFinding {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub pointer: String,
+    pub line: u32,
+    pub column: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related: Option<TextPosition>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+    pub findings: Vec<LintFinding>,
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_path: Option<String>,
+}
+
+pub fn find_config(start: &FsPath) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|candidate| candidate.is_file())
+}
+
+pub fn load_config(path: &FsPath) -> DocResult<LintConfig> {
+    let text = std::fs::read_to_string(path)?;
+    serde_json::from_str(&text).map_err(|e| DocError::Parse(format!("{}: {e}", path.display())))
+}
+
+fn kind_of(text: &str, node: &Node) -> &'static str {
+    match text.as_bytes()[node.start] {
+        b'{' => "object",
+        b'[' => "array",
+        b'"' => "string",
+        b't' | b'f' => "boolean",
+        b'n' => "null",
+        _ => "number",
+    }
+}
+
+fn key_of(node: &Node) -> Option<&str> {
+    match node.path.0.last() {
+        Some(PathSegment::Key(k)) => Some(k),
+        _ => None,
+    }
+}
+
+fn casing(key: &str) -> Option<&'static str> {
+    if key.is_empty()
+        || !key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+    {
+        return None;
+    }
+    let has_upper = key.bytes().any(|b| b.is_ascii_uppercase());
+    let has_lower = key.bytes().any(|b| b.is_ascii_lowercase());
+    if key.contains('_') {
+        return match (has_upper, has_lower) {
+            (true, false) => Some("SCREAMING_SNAKE_CASE"),
+            (false, true) => Some("snake_case"),
+            _ => None,
+        };
+    }
+    if key.contains('-') {
+        return (!has_upper).then_some("kebab-case");
+    }
+    let first_upper = key.as_bytes()[0].is_ascii_uppercase();
+    match (first_upper, has_upper, has_lower) {
+        (true, _, true) => Some("PascalCase"),
+        (false, true, true) => Some("camelCase"),
+        _ => None,
+    }
+}
+
+struct Findings<'a, 'b> {
+    index: &'b PositionIndex<'a>,
+    list: Vec<LintFinding>,
+}
+
+impl Findings<'_, '_> {
+    fn push(
+        &mut self,
+        rule: &'static str,
+        severity: Severity,
+        message: String,
+        pointer: String,
+        at: usize,
+        related: Option<usize>,
+    ) {
+        if severity == Severity::Off {
+            return;
+        }
+        let pos = self.index.position(at);
+        self.list.push(LintFinding {
+            rule,
+            severity,
+            message,
+            pointer,
+            line: pos.line,
+            column: pos.column,
+            related: related.map(|r| self.index.position(r)),
+        });
+    }
+}
+
+pub fn lint(text: &str, config: &LintConfig) -> DocResult<(Vec<LintFinding>, bool)> {
+    let index = PositionIndex::build(text)?;
+    let nodes = index.nodes();
+    let rules = &config.rules;
+    let mut out = Findings {
+        index: &index,
+        list: Vec::new(),
+    };
+
+    let children = index.children();
+
+    let mut styles: Vec<(usize, &'static str)> = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        let kind = kind_of(text, node);
+        if node.path.0.len() == config.max_depth + 1 {
+            out.push(
+                "max-depth",
+                rules.max_depth,
+                format!("nesting is deeper than {} levels", config.max_depth),
+                node.path.to_pointer(),
+                node.key_start.unwrap_or(node.start),
+                None,
+            );
+        }
+        if let Some(style) = key_of(node).and_then(casing) {
+            styles.push((i, style));
+        }
+        match kind {
+            "object" | "array" if children[i].is_empty() => {
+                out.push(
+                    "empty-containers",
+                    rules.empty_containers,
+                    format!("empty {kind}"),
+                    node.path.to_pointer(),
+                    node.start,
+                    None,
+                );
+            }
+            "string" => {
+                let decoded: String =
+                    serde_json::from_str(&text[node.start..node.end]).unwrap_or_default();
+                if !decoded.is_empty()
+                    && decoded.trim() == decoded
+                    && serde_json::from_str::<serde_json::Number>(&decoded).is_ok()
+                {
+                    out.push(
+                        "numeric-strings",
+                        rules.numeric_strings,
+                        format!("string {decoded:?} looks like a number"),
+                        node.path.to_pointer(),
+                        node.start,
+                        None,
+                    );
+                }
+            }
+            _ => {}
+        }
+        if kind == "array" {
+            let mut kinds: Vec<&str> = children[i]
+                .iter()
+                .map(|&c| kind_of(text, &nodes[c]))
+                .filter(|k| *k != "null")
+                .collect();
+            kinds.sort_unstable();
+            kinds.dedup();
+            if kinds.len() > 1 {
+                out.push(
+                    "mixed-array-types",
+                    rules.mixed_array_types,
+                    format!("array mixes {}", kinds.join(", ")),
+                    node.path.to_pointer(),
+                    node.start,
+                    None,
+                );
+            }
+        }
+    }
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for (_, style) in &styles {
+        *counts.entry(*style).or_default() += 1;
+    }
+    if counts.len() > 1 {
+        let dominant = counts
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(style, _)| *style)
+            .unwrap_or_default();
+        for (i, style) in styles {
+            if style != dominant {
+                let node = &nodes[i];
+                out.push(
+                    "key-casing",
+                    rules.key_casing,
+                    format!(
+                        "key {:?} is {style} but most keys are {dominant}",
+                        key_of(node).unwrap_or_default()
+                    ),
+                    node.path.to_pointer(),
+                    node.key_start.unwrap_or(node.start),
+                    None,
+                );
+            }
+        }
+    }
+
+    for dup in index.duplicate_keys() {
+        out.push(
+            "duplicate-keys",
+            rules.duplicate_keys,
+            format!(
+                "duplicate key {:?} (first defined on line {})",
+                dup.key, dup.first.line
+            ),
+            dup.pointer,
+            dup.duplicate.offset as usize,
+            Some(dup.first.offset as usize),
+        );
+    }
+
+    let mut line_start = 0usize;
+    for line in text.split('\n') {
+        let content = line.strip_suffix('\r').unwrap_or(line);
+        let trimmed = content.trim_end_matches([' ', '\t']);
+        if trimmed.len() < content.len() {
+            let at = line_start + trimmed.len();
+            let pos = index.position(at);
+            if let Ok(hit) = index.path_at(pos.line, pos.column) {
+                if let Some(node) = nodes.iter().find(|n| n.path == hit.path) {
+                    out.push(
+                        "trailing-whitespace",
+                        rules.trailing_whitespace,
+                        "trailing whitespace".into(),
+                        node.path.to_pointer(),
+                        at,
+                        None,
+                    );
+                }
+            }
+        }
+        line_start += line.len() + 1;
+    }
+
+    let mut findings = out.list;
+    findings.sort_by_key(|f| (f.line, f.column));
+    let truncated = findings.len() > MAX_FINDINGS;
+    findings.truncate(MAX_FINDINGS);
+    Ok((findings, truncated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    fn rules_hit(text: &str, config: &LintConfig) -> Vec<(&'static str, u32)> {
+        lint(text, config)
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|f| (f.rule, f.line))
+            .collect()
+    }
+
+    #[test]
+    fn reports_each_rule_with_positions() {
+        let text = "{\n  \"userId\": \"42\",\n  \"userName\": [], \n  \"user_type\": [1, \"a\", null],\n  \"userId\": {}\n}";
+        let findings = lint(text, &LintConfig::default()).unwrap().0;
+        let hits: Vec<(&str, u32)> = findings.iter().map(|f| (f.rule, f.line)).collect();
+        assert_eq!(
+            hits,
+            vec![
+                ("numeric-strings", 2),
+                ("empty-containers", 3),
+                ("trailing-whitespace", 3),
+                ("key-casing", 4),
+                ("mixed-array-types", 4),
+                ("duplicate-keys", 5),
+                ("empty-containers", 5),
+            ]
+        );
+        let dup = findings
+            .iter()
+            .find(|f| f.rule == "duplicate-keys")
+            .unwrap();
+        assert_eq!(dup.severity, Severity::Error);
+        assert_eq!(dup.related.map(|p| p.line), Some(2));
+        assert_eq!(dup.pointer, "/userId");
+    }
+
+    #[test]
+    fn rules_can_be_turned_off_and_tuned() {
+        let config: LintConfig = serde_json::from_str(
+            r#"{"maxDepth": 2, "rules": {"emptyContainers": "off", "maxDepth": "error"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            rules_hit(r#"{"a": {"b": {"c": {}}}}"#, &config),
+            vec![("max-depth", 1)]
+        );
+        assert!(serde_json::from_str::<LintConfig>(r#"{"rules": {"nope": "off"}}"#).is_err());
+    }
+
+    #[test]
+    fn finds_config_in_parent_directories() {
+        let dir = TempDir::new("lint");
+        let nested = dir.join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.join(CONFIG_FILE), r#"{"maxDepth": 3}"#).unwrap();
+        let found = find_config(&nested).unwrap();
+        assert_eq!(found, dir.join(CONFIG_FILE));
+        assert_eq!(load_config(&found).unwrap().max_depth, 3);
+    }
+}
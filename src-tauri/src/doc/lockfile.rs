@@ -0,0 +1,652 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path as FsPath;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LockKind {
+    Npm,
+    YarnClassic,
+    YarnBerry,
+    Pnpm,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockPackage {
+    pub name: String,
+    pub version: String,
+    pub resolved: Option<String>,
+    pub integrity: Option<String>,
+    pub dev: bool,
+    pub dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatePackage {
+    pub name: String,
+    pub versions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockReport {
+    pub kind: LockKind,
+    pub package_count: usize,
+    pub packages: Vec<LockPackage>,
+    pub duplicates: Vec<DuplicatePackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageVersion {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionChange {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockDiff {
+    pub added: Vec<PackageVersion>,
+    pub removed: Vec<PackageVersion>,
+    pub upgraded: Vec<VersionChange>,
+    pub downgraded: Vec<VersionChange>,
+}
+
+pub fn detect_kind(file_name: &str, text: &str) -> Option<LockKind> {
+    let lower = file_name.to_ascii_lowercase();
+    if lower.ends_with("package-lock.json") || lower.ends_with("npm-shrinkwrap.json") {
+        return Some(LockKind::Npm);
+    }
+    if lower.ends_with("pnpm-lock.yaml") || lower.ends_with("pnpm-lock.yml") {
+        return Some(LockKind::Pnpm);
+    }
+    if lower.ends_with("yarn.lock") {
+        return Some(if text.contains("__metadata:") {
+            LockKind::YarnBerry
+        } else {
+            LockKind::YarnClassic
+        });
+    }
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('{') && text.contains("\"lockfileVersion\"") {
+        return Some(LockKind::Npm);
+    }
+    if trimmed.starts_with("lockfileVersion:") {
+        return Some(LockKind::Pnpm);
+    }
+    if text.contains("# yarn lockfile v1") {
+        return Some(LockKind::YarnClassic);
+    }
+    None
+}
+
+fn string_map(value: Option<&Value>) -> BTreeMap<String, String> {
+    value
+        .and_then(Value::as_object)
+        .map(|m| {
+            m.iter()
+                .map(|(k, v)| {
+                    let range = match v {
+                        Value::String(s) => s.clone(),
+                        Value::Object(o) => o
+                            .get("version")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                        other => other.to_string(),
+                    };
+                    (k.clone(), range)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn str_field(entry: &Value, key: &str) -> Option<String> {
+    match entry.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_npm(text: &str) -> DocResult<Vec<LockPackage>> {
+    let root: Value = serde_json::from_str(text).map_err(|e| DocError::Parse(e.to_string()))?;
+    let mut out = Vec::new();
+    if let Some(packages) = root.get("packages").and_then(Value::as_object) {
+        for (key, entry) in packages {
+            if key.is_empty() {
+                continue;
+            }
+            let Some(version) = str_field(entry, "version") else {
+                continue;
+            };
+            let name = str_field(entry, "name").unwrap_or_else(|| {
+                key.rsplit_once("node_modules/")
+                    .map(|(_, n)| n)
+                    .unwrap_or(key)
+                    .to_string()
+            });
+            let mut dependencies = string_map(entry.get("dependencies"));
+            dependencies.extend(string_map(entry.get("optionalDependencies")));
+            out.push(LockPackage {
+                name,
+                version,
+                resolved: str_field(entry, "resolved"),
+                integrity: str_field(entry, "integrity"),
+                dev: entry.get("dev").and_then(Value::as_bool).unwrap_or(false),
+                dependencies,
+            });
+        }
+        return Ok(out);
+    }
+    fn walk_v1(deps: &serde_json::Map<String, Value>, out: &mut Vec<LockPackage>) {
+        for (name, entry) in deps {
+            if let Some(version) = str_field(entry, "version") {
+                out.push(LockPackage {
+                    name: name.clone(),
+                    version,
+                    resolved: str_field(entry, "resolved"),
+                    integrity: str_field(entry, "integrity"),
+                    dev: entry.get("dev").and_then(Value::as_bool).unwrap_or(false),
+                    dependencies: string_map(entry.get("requires")),
+                });
+            }
+            if let Some(nested) = entry.get("dependencies").and_then(Value::as_object) {
+                walk_v1(nested, out);
+            }
+        }
+    }
+    if let Some(deps) = root.get("dependencies").and_then(Value::as_object) {
+        walk_v1(deps, &mut out);
+    }
+    Ok(out)
+}
+
+fn split_spec(spec: &str) -> Option<(&str, &str)> {
+    let (at, _) = spec.char_indices().skip(1).find(|(_, c)| *c == '@')?;
+    Some((&spec[..at], &spec[at + 1..]))
+}
+
+fn unquote(s: &str) -> &str {
+    let s = s.trim();
+    s.strip_prefix('"')
+        .and_then(|r| r.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+fn parse_yarn_classic(text: &str) -> DocResult<Vec<LockPackage>> {
+    let mut out: Vec<LockPackage> = Vec::new();
+    let mut current: Option<LockPackage> = None;
+    let mut in_deps = false;
+    for (i, raw) in text.lines().enumerate() {
+        if raw.trim().is_empty() || raw.trim_start().starts_with('#') {
+            continue;
+        }
+        let indent = raw.len() - raw.trim_start().len();
+        let line = raw.trim();
+        if indent == 0 {
+            out.extend(current.take());
+            let header = line.strip_suffix(':').ok_or_else(|| {
+                DocError::Parse(format!("yarn.lock line {}: expected entry header", i + 1))
+            })?;
+            let first = header.split(", ").next().map(unquote).unwrap_or_default();
+            let (name, _) = split_spec(first).ok_or_else(|| {
+                DocError::Parse(format!("yarn.lock line {}: invalid spec {first}", i + 1))
+            })?;
+            current = Some(LockPackage {
+                name: name.to_string(),
+                version: String::new(),
+                resolved: None,
+                integrity: None,
+                dev: false,
+                dependencies: BTreeMap::new(),
+            });
+            in_deps = false;
+            continue;
+        }
+        let Some(pkg) = current.as_mut() else {
+            continue;
+        };
+        if indent <= 2 {
+            in_deps = matches!(line, "dependencies:" | "optionalDependencies:");
+            if in_deps {
+                continue;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = unquote(value).to_string();
+            match key {
+                "version" => pkg.version = value,
+                "resolved" => pkg.resolved = Some(value),
+                "integrity" => pkg.integrity = Some(value),
+                _ => {}
+            }
+        } else if in_deps {
+            if let Some((name, range)) = line.split_once(' ') {
+                pkg.dependencies
+                    .insert(unquote(name).to_string(), unquote(range).to_string());
+            }
+        }
+    }
+    out.extend(current);
+    Ok(out)
+}
+
+fn parse_yaml(text: &str) -> DocResult<Value> {
+    serde_yaml_ng::from_str(text).map_err(|e| DocError::Parse(e.to_string()))
+}
+
+fn parse_yarn_berry(text: &str) -> DocResult<Vec<LockPackage>> {
+    let root = parse_yaml(text)?;
+    let mut out = Vec::new();
+    for (key, entry) in root.as_object().into_iter().flatten() {
+        if key == "__metadata" {
+            continue;
+        }
+        let Some(version) = str_field(entry, "version") else {
+            continue;
+        };
+        let first = key.split(", ").next().unwrap_or(key);
+        let Some((name, _)) = split_spec(first) else {
+            continue;
+        };
+        if str_field(entry, "linkType").as_deref() == Some("soft") && version == "0.0.0-use.local" {
+            continue;
+        }
+        out.push(LockPackage {
+            name: name.to_string(),
+            version,
+            resolved: str_field(entry, "resolution"),
+            integrity: str_field(entry, "checksum"),
+            dev: false,
+            dependencies: string_map(entry.get("dependencies")),
+        });
+    }
+    Ok(out)
+}
+
+fn pnpm_key(key: &str) -> Option<(String, String)> {
+    let key = key.strip_prefix('/').unwrap_or(key);
+    let key = key.split('(').next().unwrap_or(key);
+    if let Some((name, version)) = split_spec(key) {
+        return Some((name.to_string(), version.to_string()));
+    }
+    let (name, version) = key.rsplit_once('/')?;
+    let version = version.split('_').next().unwrap_or(version);
+    Some((name.to_string(), version.to_string()))
+}
+
+fn parse_pnpm(text: &str) -> DocResult<Vec<LockPackage>> {
+    let root = parse_yaml(text)?;
+    let snapshots = root.get("snapshots").and_then(Value::as_object);
+    let mut out = Vec::new();
+    for (key, entry) in root
+        .get("packages")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        let Some((name, key_version)) = pnpm_key(key) else {
+            continue;
+        };
+        let deps_source = snapshots.and_then(|s| s.get(key)).unwrap_or(entry);
+        let mut dependencies = string_map(deps_source.get("dependencies"));
+        dependencies.extend(string_map(deps_source.get("optionalDependencies")));
+        out.push(LockPackage {
+            name: str_field(entry, "name").unwrap_or(name),
+            version: str_field(entry, "version").unwrap_or(key_version),
+            resolved: entry
+                .get("resolution")
+                .and_then(|r| str_field(r, "tarball")),
+            integrity: entry
+                .get("resolution")
+                .and_then(|r| str_field(r, "integrity")),
+            dev: entry.get("dev").and_then(Value::as_bool).unwrap_or(false),
+            dependencies,
+        });
+    }
+    Ok(out)
+}
+
+pub fn parse(file_name: &str, text: &str) -> DocResult<(LockKind, Vec<LockPackage>)> {
+    let kind = detect_kind(file_name, text)
+        .ok_or_else(|| DocError::Parse(format!("{file_name} is not a recognized lockfile")))?;
+    let mut packages = match kind {
+        LockKind::Npm => parse_npm(text)?,
+        LockKind::YarnClassic => parse_yarn_classic(text)?,
+        LockKind::YarnBerry => parse_yarn_berry(text)?,
+        LockKind::Pnpm => parse_pnpm(text)?,
+    };
+    packages.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| compare_versions(&a.version, &b.version))
+    });
+    packages.dedup_by(|a, b| a.name == b.name && a.version == b.version);
+    Ok((kind, packages))
+}
+
+fn version_map(packages: &[LockPackage]) -> BTreeMap<&str, BTreeSet<&str>> {
+    let mut map: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for p in packages {
+        map.entry(&p.name).or_default().insert(&p.version);
+    }
+    map
+}
+
+fn sorted_versions<'a>(versions: impl IntoIterator<Item = &'a &'a str>) -> Vec<String> {
+    let mut out: Vec<String> = versions.into_iter().map(|v| v.to_string()).collect();
+    out.sort_by(|a, b| compare_versions(a, b));
+    out
+}
+
+pub fn analyze(file_name: &str, text: &str) -> DocResult<LockReport> {
+    let (kind, packages) = parse(file_name, text)?;
+    let duplicates = version_map(&packages)
+        .into_iter()
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|(name, versions)| DuplicatePackage {
+            name: name.to_string(),
+            versions: sorted_versions(&versions),
+        })
+        .collect();
+    Ok(LockReport {
+        kind,
+        package_count: packages.len(),
+        packages,
+        duplicates,
+    })
+}
+
+pub fn analyze_file(path: &FsPath) -> DocResult<LockReport> {
+    let text = std::fs::read_to_string(path)?;
+    analyze(&path.to_string_lossy(), &text)
+}
+
+fn version_parts(v: &str) -> (Vec<u64>, Option<&str>) {
+    let v = v.trim_start_matches(['v', '=']);
+    let v = v.split('+').next().unwrap_or(v);
+    let (core, pre) = match v.split_once('-') {
+        Some((c, p)) => (c, Some(p)),
+        None => (v, None),
+    };
+    let nums = core
+        .split('.')
+        .map(|p| p.parse::<u64>().unwrap_or(0))
+        .collect();
+    (nums, pre)
+}
+
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (an, ap) = version_parts(a);
+    let (bn, bp) = version_parts(b);
+    let len = an.len().max(bn.len());
+    for i in 0..len {
+        let ord = an
+            .get(i)
+            .copied()
+            .unwrap_or(0)
+            .cmp(&bn.get(i).copied().unwrap_or(0));
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    match (ap, bp) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => x.cmp(y),
+    }
+}
+
+pub fn diff(left: &[LockPackage], right: &[LockPackage]) -> LockDiff {
+    let l = version_map(left);
+    let r = version_map(right);
+    let mut out = LockDiff::default();
+    let names: BTreeSet<&str> = l.keys().chain(r.keys()).copied().collect();
+    let empty = BTreeSet::new();
+    for name in names {
+        let lv = l.get(name).unwrap_or(&empty);
+        let rv = r.get(name).unwrap_or(&empty);
+        if lv == rv {
+            continue;
+        }
+        let gone: Vec<&str> = lv.difference(rv).copied().collect();
+        let new: Vec<&str> = rv.difference(lv).copied().collect();
+        if gone.len() == 1 && new.len() == 1 {
+            let change = VersionChange {
+                name: name.to_string(),
+                from: gone[0].to_string(),
+                to: new[0].to_string(),
+            };
+            if compare_versions(gone[0], new[0]) == Ordering::Greater {
+                out.downgraded.push(change);
+            } else {
+                out.upgraded.push(change);
+            }
+            continue;
+        }
+        out.removed.extend(gone.iter().map(|v| PackageVersion {
+            name: name.to_string(),
+            version: v.to_string(),
+        }));
+        out.added.extend(new.iter().map(|v| PackageVersion {
+            name: name.to_string(),
+            version: v.to_string(),
+        }));
+    }
+    out
+}
+
+pub fn diff_files(left: &FsPath, right: &FsPath) -> DocResult<LockDiff> {
+    let (_, l) = parse(&left.to_string_lossy(), &std::fs::read_to_string(left)?)?;
+    let (_, r) = parse(&right.to_string_lossy(), &std::fs::read_to_string(right)?)?;
+    Ok(diff(&l, &r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NPM_V3: &str = r#"{
+  "name": "app",
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "name": "app", "dependencies": { "a": "^1.0.0" } },
+    "node_modules/a": { "version": "1.2.0", "resolved": "https://r/a.tgz", "dependencies": { "b": "^2.0.0" } },
+    "node_modules/b": { "version": "2.0.0", "dev": true },
+    "node_modules/a/node_modules/b": { "version": "1.0.0" },
+    "node_modules/@scope/c": { "version": "0.1.0" }
+  }
+}"#;
+
+    const NPM_V1: &str = r#"{
+  "lockfileVersion": 1,
+  "dependencies": {
+    "a": { "version": "1.0.0", "requires": { "b": "^1.0.0" },
+      "dependencies": { "b": { "version": "1.0.0" } } },
+    "b": { "version": "2.0.0" }
+  }
+}"#;
+
+    const YARN_V1: &str = r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@scope/c@^0.1.0":
+  version "0.1.0"
+  resolved "https://r/c.tgz"
+  integrity sha512-abc
+
+a@^1.0.0, a@^1.1.0:
+  version "1.3.0"
+  dependencies:
+    b "^2.0.0"
+
+b@^2.0.0:
+  version "2.1.0"
+"#;
+
+    const YARN_BERRY: &str = r#"__metadata:
+  version: 6
+
+"a@npm:^1.0.0":
+  version: 1.3.0
+  resolution: "a@npm:1.3.0"
+  dependencies:
+    b: ^2.0.0
+  checksum: deadbeef
+
+"app@workspace:.":
+  version: 0.0.0-use.local
+  resolution: "app@workspace:."
+  linkType: soft
+"#;
+
+    const PNPM_V6: &str = r#"lockfileVersion: '6.0'
+
+dependencies:
+  a:
+    specifier: ^1.0.0
+    version: 1.3.0
+
+packages:
+
+  /a@1.3.0:
+    resolution: {integrity: sha512-x}
+    dependencies:
+      b: 2.1.0
+    dev: false
+
+  /b@2.1.0(react@18.0.0):
+    resolution: {integrity: sha512-y}
+    dev: true
+
+  /@scope/c@0.1.0:
+    resolution: {integrity: sha512-z}
+    dev: false
+"#;
+
+    fn names_versions(packages: &[LockPackage]) -> Vec<(String, String)> {
+        packages
+            .iter()
+            .map(|p| (p.name.clone(), p.version.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn npm_v3_packages_and_duplicates() {
+        let report = analyze("package-lock.json", NPM_V3).unwrap();
+        assert_eq!(report.kind, LockKind::Npm);
+        assert_eq!(report.package_count, 4);
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(report.duplicates[0].name, "b");
+        assert_eq!(report.duplicates[0].versions, vec!["1.0.0", "2.0.0"]);
+        let a = report.packages.iter().find(|p| p.name == "a").unwrap();
+        assert_eq!(a.dependencies.get("b").map(String::as_str), Some("^2.0.0"));
+        assert!(report.packages.iter().any(|p| p.name == "@scope/c"));
+    }
+
+    #[test]
+    fn npm_v1_walks_nested_dependencies() {
+        let (_, packages) = parse("package-lock.json", NPM_V1).unwrap();
+        assert_eq!(
+            names_versions(&packages),
+            vec![
+                ("a".into(), "1.0.0".into()),
+                ("b".into(), "1.0.0".into()),
+                ("b".into(), "2.0.0".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn yarn_classic_entries() {
+        let (kind, packages) = parse("yarn.lock", YARN_V1).unwrap();
+        assert_eq!(kind, LockKind::YarnClassic);
+        assert_eq!(
+            names_versions(&packages),
+            vec![
+                ("@scope/c".into(), "0.1.0".into()),
+                ("a".into(), "1.3.0".into()),
+                ("b".into(), "2.1.0".into())
+            ]
+        );
+        assert_eq!(packages[0].integrity.as_deref(), Some("sha512-abc"));
+        assert_eq!(packages[1].dependencies.get("b").unwrap(), "^2.0.0");
+    }
+
+    #[test]
+    fn yarn_berry_skips_workspace_entries() {
+        let (kind, packages) = parse("yarn.lock", YARN_BERRY).unwrap();
+        assert_eq!(kind, LockKind::YarnBerry);
+        assert_eq!(
+            names_versions(&packages),
+            vec![("a".into(), "1.3.0".into())]
+        );
+    }
+
+    #[test]
+    fn pnpm_keys_strip_peer_suffixes() {
+        let (kind, packages) = parse("pnpm-lock.yaml", PNPM_V6).unwrap();
+        assert_eq!(kind, LockKind::Pnpm);
+        assert_eq!(
+            names_versions(&packages),
+            vec![
+                ("@scope/c".into(), "0.1.0".into()),
+                ("a".into(), "1.3.0".into()),
+                ("b".into(), "2.1.0".into())
+            ]
+        );
+        assert!(packages[2].dev);
+        assert_eq!(pnpm_key("/left-pad/1.3.0_abc").unwrap().1, "1.3.0");
+    }
+
+    #[test]
+    fn diff_classifies_changes_across_formats() {
+        let (_, npm) = parse("package-lock.json", NPM_V3).unwrap();
+        let (_, yarn) = parse("yarn.lock", YARN_V1).unwrap();
+        let d = diff(&npm, &yarn);
+        assert_eq!(
+            d.upgraded,
+            vec![VersionChange {
+                name: "a".into(),
+                from: "1.2.0".into(),
+                to: "1.3.0".into()
+            }]
+        );
+        assert_eq!(d.removed.len(), 2);
+        assert_eq!(d.added.len(), 1);
+        assert!(d.downgraded.is_empty());
+    }
+
+    #[test]
+    fn version_ordering_handles_prereleases() {
+        assert_eq!(compare_versions("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(compare_versions("2.0.0-beta.1", "2.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("v1.0.0", "1.0.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn unknown_files_are_rejected() {
+        assert!(matches!(
+            parse("notes.txt", "hello"),
+            Err(DocError::Parse(_))
+        ));
+    }
+}
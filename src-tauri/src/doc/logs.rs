@@ -0,0 +1,385 @@
+use std::io::{BufRead, BufReader};
+use std::path::Path as FsPath;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::jobs::CancelFlag;
+use super::timefmt;
+use super::types::{DocError, DocResult};
+
+const DEFAULT_MAX_RECORDS: u32 = 10_000;
+const MAX_RECORDS_CAP: u32 = 200_000;
+const MAX_JSON_ATTEMPTS: usize = 8;
+
+const TIMESTAMP_FIELDS: &[&str] = &["@timestamp", "timestamp", "time", "ts", "date", "t"];
+const LEVEL_FIELDS: &[&str] = &["level", "lvl", "severity", "levelname", "log.level"];
+const MESSAGE_FIELDS: &[&str] = &["msg", "message", "@message", "text", "event", "log"];
+
+static ISO_TIMESTAMP: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:[.,]\d+)?(?:Z|[+-]\d{2}:?\d{2})?")
+        .expect("valid regex")
+});
+static SYSLOG_TIMESTAMP: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b[A-Z][a-z]{2} [ \d]\d \d{2}:\d{2}:\d{2}\b").expect("valid regex"));
+static LEVEL_WORD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(trace|debug|info|notice|warn|warning|error|err|fatal|critical|crit)\b")
+        .expect("valid regex")
+});
+static CRI_PREFIX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\S+ (stdout|stderr) [FP](?:\s|$)").expect("valid regex"));
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogOptions {
+    #[serde(default)]
+    pub start_line: u32,
+    #[serde(default)]
+    pub max_records: u32,
+    #[serde(default)]
+    pub include_plain: bool,
+    #[serde(default)]
+    pub levels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+    pub line: u32,
+    pub byte_offset: u64,
+    pub prefix: String,
+    pub timestamp: Option<String>,
+    pub level: Option<String>,
+    pub message: Option<String>,
+    pub stream: Option<String>,
+    pub data: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogParseResult {
+    pub records: Vec<LogRecord>,
+    pub lines_scanned: u32,
+    pub json_lines: u32,
+    pub next_line: Option<u32>,
+}
+
+fn find_json_object(line: &str) -> Option<(usize, Value)> {
+    let mut from = 0;
+    for _ in 0..MAX_JSON_ATTEMPTS {
+        let pos = from + line[from..].find('{')?;
+        let mut stream = serde_json::Deserializer::from_str(&line[pos..]).into_iter::<Value>();
+        if let Some(Ok(value @ Value::Object(_))) = stream.next() {
+            return Some((pos, value));
+        }
+        from = pos + 1;
+    }
+    None
+}
+
+fn field<'a>(data: &'a Value, names: &[&str]) -> Option<&'a Value> {
+    names.iter().find_map(|name| {
+        data.get(*name).or_else(|| {
+            let (head, tail) = name.split_once('.')?;
+            data.get(head)?.get(tail)
+        })
+    })
+}
+
+fn normalize_level(raw: &str) -> String {
+    match raw.to_ascii_lowercase().as_str() {
+        "warning" => "warn".into(),
+        "err" => "error".into(),
+        "crit" | "critical" => "fatal".into(),
+        other => other.to_string(),
+    }
+}
+
+fn level_from(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(normalize_level(s)),
+        Value::Number(n) => Some(
+            match n.as_u64()? {
+                0..=10 => "trace",
+                11..=20 => "debug",
+                21..=30 => "info",
+                31..=40 => "warn",
+                41..=50 => "error",
+                _ => "fatal",
+            }
+            .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+fn timestamp_from(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => {
+            let f = n.as_f64()?;
+            let millis = if f > 1e11 { f } else { f * 1000.0 };
+            Some(timefmt::rfc3339_utc(millis.max(0.0) as u64))
+        }
+        _ => None,
+    }
+}
+
+fn timestamp_in(text: &str) -> Option<String> {
+    ISO_TIMESTAMP
+        .find(text)
+        .or_else(|| SYSLOG_TIMESTAMP.find(text))
+        .map(|m| m.as_str().to_string())
+}
+
+fn parse_line(
+    line: &str,
+    line_no: u32,
+    byte_offset: u64,
+    include_plain: bool,
+) -> Option<LogRecord> {
+    let mut stream = None;
+    let mut prefix = String::new();
+    let mut data = None;
+
+    if let Some((pos, value)) = find_json_object(line) {
+        prefix = line[..pos].trim_end().to_string();
+        let docker_log = value
+            .get("log")
+            .and_then(Value::as_str)
+            .filter(|_| value.get("stream").is_some());
+        match docker_log {
+            Some(inner) => {
+                stream = value
+                    .get("stream")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                if let Some(t) = value.get("time").and_then(Value::as_str) {
+                    prefix = t.to_string();
+                }
+                data = Some(match find_json_object(inner) {
+                    Some((_, nested)) => nested,
+                    None => Value::String(inner.trim_end().to_string()),
+                });
+            }
+            None => data = Some(value),
+        }
+    }
+    if let Some(caps) = CRI_PREFIX.captures(&prefix) {
+        stream = Some(caps[1].to_string());
+    }
+    let data = match data {
+        Some(d) => d,
+        None if include_plain && !line.trim().is_empty() => Value::Null,
+        None => return None,
+    };
+
+    let (timestamp, level, message) = match &data {
+        Value::Object(_) => (
+            field(&data, TIMESTAMP_FIELDS).and_then(timestamp_from),
+            field(&data, LEVEL_FIELDS).and_then(level_from),
+            field(&data, MESSAGE_FIELDS).and_then(|v| match v {
+                Value::String(s) => Some(s.clone()),
+                Value::Null => None,
+                other => Some(other.to_string()),
+            }),
+        ),
+        Value::String(s) => (None, None, Some(s.clone())),
+        _ => (None, None, Some(line.to_string())),
+    };
+    let context = if data.is_null() {
+        line
+    } else {
+        prefix.as_str()
+    };
+    Some(LogRecord {
+        line: line_no,
+        byte_offset,
+        timestamp: timestamp.or_else(|| timestamp_in(context)),
+        level: level.or_else(|| LEVEL_WORD.captures(context).map(|c| normalize_level(&c[1]))),
+        message,
+        stream,
+        prefix,
+        data,
+    })
+}
+
+pub fn parse_reader(
+    mut reader: impl BufRead,
+    opts: &LogOptions,
+    cancel: &CancelFlag,
+) -> DocResult<LogParseResult> {
+    let cap = match opts.max_records {
+        0 => DEFAULT_MAX_RECORDS,
+        n => n.min(MAX_RECORDS_CAP),
+    } as usize;
+    let levels: Vec<String> = opts.levels.iter().map(|l| normalize_level(l)).collect();
+    let mut records = Vec::new();
+    let mut lines_scanned = 0u32;
+    let mut json_lines = 0u32;
+    let mut next_line = None;
+    let mut offset = 0u64;
+    let mut buf = Vec::new();
+    let mut line_no = 0u32;
+
+    loop {
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf)?;
+        if read == 0 {
+            break;
+        }
+        line_no += 1;
+        let line_offset = offset;
+        offset += read as u64;
+        if line_no <= opts.start_line {
+            continue;
+        }
+        if line_no % 4096 == 0 && cancel.is_cancelled() {
+            return Err(DocError::Cancelled);
+        }
+        if records.len() >= cap {
+            next_line = Some(line_no - 1);
+            break;
+        }
+        lines_scanned += 1;
+        let text = String::from_utf8_lossy(&buf);
+        let text = text.trim_end_matches(['\r', '\n']);
+        let Some(record) = parse_line(text, line_no, line_offset, opts.include_plain) else {
+            continue;
+        };
+        if !record.data.is_null() {
+            json_lines += 1;
+        }
+        if !levels.is_empty() && !record.level.as_ref().is_some_and(|l| levels.contains(l)) {
+            continue;
+        }
+        records.push(record);
+    }
+    Ok(LogParseResult {
+        records,
+        lines_scanned,
+        json_lines,
+        next_line,
+    })
+}
+
+pub fn parse_log_file(
+    path: &FsPath,
+    opts: &LogOptions,
+    cancel: &CancelFlag,
+) -> DocResult<LogParseResult> {
+    let file = std::fs::File::open(path)?;
+    parse_reader(BufReader::new(file), opts, cancel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str, opts: LogOptions) -> LogParseResult {
+        parse_reader(text.as_bytes(), &opts, &CancelFlag::never()).unwrap()
+    }
+
+    #[test]
+    fn extracts_json_after_plain_prefix() {
+        let out = parse(
+            "2024-03-01T10:00:00Z INFO app: {\"msg\": \"started\", \"port\": 8080}\nplain line\n",
+            LogOptions::default(),
+        );
+        assert_eq!(out.lines_scanned, 2);
+        assert_eq!(out.json_lines, 1);
+        let r = &out.records[0];
+        assert_eq!(r.line, 1);
+        assert_eq!(r.prefix, "2024-03-01T10:00:00Z INFO app:");
+        assert_eq!(r.timestamp.as_deref(), Some("2024-03-01T10:00:00Z"));
+        assert_eq!(r.level.as_deref(), Some("info"));
+        assert_eq!(r.message.as_deref(), Some("started"));
+        assert_eq!(r.data["port"], 8080);
+    }
+
+    #[test]
+    fn json_fields_take_precedence_and_pino_levels_map() {
+        let out = parse(
+            r#"{"level":50,"time":1700000000000,"msg":"boom"}"#,
+            LogOptions::default(),
+        );
+        let r = &out.records[0];
+        assert_eq!(r.level.as_deref(), Some("error"));
+        assert_eq!(r.timestamp.as_deref(), Some("2023-11-14T22:13:20.000Z"));
+        assert_eq!(r.message.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn docker_json_file_lines_unwrap_inner_json() {
+        let line = r#"{"log":"{\"level\":\"warn\",\"msg\":\"slow\"}\n","stream":"stderr","time":"2024-01-01T00:00:00.5Z"}"#;
+        let out = parse(line, LogOptions::default());
+        let r = &out.records[0];
+        assert_eq!(r.stream.as_deref(), Some("stderr"));
+        assert_eq!(r.level.as_deref(), Some("warn"));
+        assert_eq!(r.prefix, "2024-01-01T00:00:00.5Z");
+        assert_eq!(r.timestamp.as_deref(), Some("2024-01-01T00:00:00.5Z"));
+    }
+
+    #[test]
+    fn cri_prefix_sets_stream() {
+        let out = parse(
+            r#"2024-01-01T00:00:00.1Z stdout F {"message":"hi"}"#,
+            LogOptions::default(),
+        );
+        assert_eq!(out.records[0].stream.as_deref(), Some("stdout"));
+        assert_eq!(out.records[0].message.as_deref(), Some("hi"));
+    }
+
+    #[test]
+    fn plain_lines_and_level_filter() {
+        let text = "Mar  1 10:00:00 host sshd: ERROR failed\n{\"level\":\"info\"}\n";
+        let out = parse(
+            text,
+            LogOptions {
+                include_plain: true,
+                levels: vec!["ERROR".into()],
+                ..Default::default()
+            },
+        );
+        assert_eq!(out.records.len(), 1);
+        let r = &out.records[0];
+        assert!(r.data.is_null());
+        assert_eq!(r.timestamp.as_deref(), Some("Mar  1 10:00:00"));
+        assert_eq!(r.level.as_deref(), Some("error"));
+    }
+
+    #[test]
+    fn paging_reports_next_line_and_offsets() {
+        let text = "{\"a\":1}\n{\"a\":2}\n{\"a\":3}\n";
+        let out = parse(
+            text,
+            LogOptions {
+                max_records: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(out.records.len(), 2);
+        assert_eq!(out.next_line, Some(2));
+        assert_eq!(out.records[1].byte_offset, 8);
+
+        let rest = parse(
+            text,
+            LogOptions {
+                start_line: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(rest.records.len(), 1);
+        assert_eq!(rest.records[0].line, 3);
+        assert_eq!(rest.next_line, None);
+    }
+
+    #[test]
+    fn braces_that_are_not_json_are_skipped() {
+        let out = parse(r#"handler {id} failed: {"code": 7}"#, LogOptions::default());
+        assert_eq!(out.records[0].data["code"], 7);
+        assert_eq!(out.records[0].prefix, "handler {id} failed:");
+    }
+}
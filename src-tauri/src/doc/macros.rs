@@ -0,0 +1,304 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::AppHandle;
+
+use super::ops::sort_keys_in_place;
+use super::timefmt::now_millis;
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MacroStep {
+    SortKeys {
+        #[serde(default)]
+        descending: bool,
+    },
+    Redact {
+        keys: Vec<String>,
+        #[serde(default = "default_replacement")]
+        replacement: Value,
+    },
+    RenameKeys {
+        fields: BTreeMap<String, String>,
+    },
+    Format {
+        #[serde(default)]
+        indent: u8,
+    },
+}
+
+fn default_replacement() -> Value {
+    Value::String("[REDACTED]".into())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Macro {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub steps: Vec<MacroStep>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroInput {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub steps: Vec<MacroStep>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroFileResult {
+    pub path: String,
+    pub changed: bool,
+    pub error: Option<String>,
+}
+
+pub fn macros_path(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let base = super::portable::data_dir(app)?;
+    Ok(base.join("macros.json"))
+}
+
+pub fn list(path: &FsPath) -> std::io::Result<Vec<Macro>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn store(path: &FsPath, macros: &[Macro]) -> std::io::Result<()> {
+    let json =
+        serde_json::to_string_pretty(macros).map_err(|e| std::io::Error::other(e.to_string()))?;
+    std::fs::write(path, json)
+}
+
+pub fn find(path: &FsPath, id: &str) -> DocResult<Macro> {
+    list(path)?
+        .into_iter()
+        .find(|m| m.id == id)
+        .ok_or_else(|| DocError::Edit(format!("unknown macro: {id}")))
+}
+
+pub fn save(path: &FsPath, input: MacroInput) -> DocResult<Macro> {
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err(DocError::Edit("macro name is required".into()));
+    }
+    if input.steps.is_empty() {
+        return Err(DocError::Edit("macro has no steps".into()));
+    }
+    let mut all = list(path)?;
+    let now = now_millis().to_string();
+    let existing = input
+        .id
+        .as_deref()
+        .and_then(|id| all.iter_mut().find(|m| m.id == id));
+    let saved = match existing {
+        Some(m) => {
+            m.name = name;
+            m.description = input.description;
+            m.steps = input.steps;
+            m.updated_at = now;
+            m.clone()
+        }
+        None => {
+            let m = Macro {
+                id: input.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                name,
+                description: input.description,
+                steps: input.steps,
+                created_at: now.clone(),
+                updated_at: now,
+            };
+            all.push(m.clone());
+            m
+        }
+    };
+    store(path, &all)?;
+    Ok(saved)
+}
+
+pub fn delete(path: &FsPath, id: &str) -> std::io::Result<bool> {
+    let mut all = list(path)?;
+    let before = all.len();
+    all.retain(|m| m.id != id);
+    if all.len() == before {
+        return Ok(false);
+    }
+    store(path, &all)?;
+    Ok(true)
+}
+
+fn redact(value: &mut Value, keys: &BTreeSet<&str>, replacement: &Value) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                if keys.contains(k.as_str()) {
+                    *v = replacement.clone();
+                } else {
+                    redact(v, keys, replacement);
+                }
+            }
+        }
+        Value::Array(arr) => arr.iter_mut().for_each(|v| redact(v, keys, replacement)),
+        _ => {}
+    }
+}
+
+fn rename_keys(value: &mut Value, fields: &BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            let old = std::mem::replace(map, Map::new());
+            for (k, mut v) in old {
+                rename_keys(&mut v, fields);
+                let key = fields.get(&k).cloned().unwrap_or(k);
+                map.insert(key, v);
+            }
+        }
+        Value::Array(arr) => arr.iter_mut().for_each(|v| rename_keys(v, fields)),
+        _ => {}
+    }
+}
+
+fn serialize(value: &Value, indent: u8) -> DocResult<String> {
+    if indent == 0 {
+        return serde_json::to_string(value).map_err(|e| DocError::Export(e.to_string()));
+    }
+    let indent = " ".repeat(indent.min(8) as usize);
+    let mut out = Vec::new();
+    let mut ser = serde_json::Serializer::with_formatter(
+        &mut out,
+        serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes()),
+    );
+    value
+        .serialize(&mut ser)
+        .map_err(|e| DocError::Export(e.to_string()))?;
+    String::from_utf8(out).map_err(|e| DocError::Export(e.to_string()))
+}
+
+pub fn run(steps: &[MacroStep], content: &str) -> DocResult<String> {
+    let mut value: Value =
+        serde_json::from_str(content).map_err(|e| DocError::Parse(e.to_string()))?;
+    let mut indent = 2;
+    for step in steps {
+        match step {
+            MacroStep::SortKeys { descending } => sort_keys_in_place(&mut value, *descending),
+            MacroStep::Redact { keys, replacement } => {
+                let keys = keys.iter().map(String::as_str).collect();
+                redact(&mut value, &keys, replacement);
+            }
+            MacroStep::RenameKeys { fields } => rename_keys(&mut value, fields),
+            MacroStep::Format { indent: n } => indent = *n,
+        }
+    }
+    let mut text = serialize(&value, indent)?;
+    if content.ends_with('\n') {
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+pub fn run_on_file(steps: &[MacroStep], file: &str) -> MacroFileResult {
+    let outcome = std::fs::read_to_string(file)
+        .map_err(DocError::from)
+        .and_then(|text| {
+            let out = run(steps, &text)?;
+            let changed = out != text;
+            if changed {
+                std::fs::write(file, out)?;
+            }
+            Ok(changed)
+        });
+    MacroFileResult {
+        path: file.to_string(),
+        changed: outcome.as_ref().is_ok_and(|c| *c),
+        error: outcome.err().map(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    fn steps() -> Vec<MacroStep> {
+        vec![
+            MacroStep::RenameKeys {
+                fields: BTreeMap::from([("user_name".to_string(), "name".to_string())]),
+            },
+            MacroStep::Redact {
+                keys: vec!["password".into()],
+                replacement: default_replacement(),
+            },
+            MacroStep::SortKeys { descending: false },
+            MacroStep::Format { indent: 0 },
+        ]
+    }
+
+    #[test]
+    fn runs_steps_in_order() {
+        let out = run(
+            &steps(),
+            r#"[{"user_name": "ann", "password": "x", "auth": {"password": 1}}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            r#"[{"auth":{"password":"[REDACTED]"},"name":"ann","password":"[REDACTED]"}]"#
+        );
+        assert!(matches!(run(&steps(), "{"), Err(DocError::Parse(_))));
+    }
+
+    #[test]
+    fn stores_macros_and_applies_them_to_files() {
+        let dir = TempDir::new("macros");
+        let db = dir.join("macros.json");
+        assert!(list(&db).unwrap().is_empty());
+        let saved = save(
+            &db,
+            MacroInput {
+                id: None,
+                name: "cleanup".into(),
+                description: None,
+                steps: steps(),
+            },
+        )
+        .unwrap();
+        assert_eq!(find(&db, &saved.id).unwrap().steps, steps());
+        assert!(save(
+            &db,
+            MacroInput {
+                id: None,
+                name: "empty".into(),
+                description: None,
+                steps: Vec::new(),
+            }
+        )
+        .is_err());
+
+        let file = dir.join("input.json");
+        std::fs::write(&file, r#"{"b": 1, "a": 2}"#).unwrap();
+        let file = file.to_string_lossy().into_owned();
+        let result = run_on_file(&saved.steps, &file);
+        assert!(result.changed && result.error.is_none());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), r#"{"a":2,"b":1}"#);
+        assert!(!run_on_file(&saved.steps, &file).changed);
+
+        assert!(delete(&db, &saved.id).unwrap());
+    }
+}
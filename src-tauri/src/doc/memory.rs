@@ -0,0 +1,194 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use super::document::Document;
+use super::types::{DocHandle, DocResult};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentMemory {
+    pub handle: DocHandle,
+    pub source_path: Option<String>,
+    pub value_bytes: u64,
+    pub history_bytes: u64,
+    pub cache_bytes: u64,
+    pub index_bytes: u64,
+    pub text_buffer_bytes: u64,
+    pub blob_bytes: u64,
+    pub snapshot_bytes: u64,
+    pub spilled_bytes: u64,
+}
+
+impl DocumentMemory {
+    pub fn resident_bytes(&self) -> u64 {
+        self.value_bytes
+            + self.history_bytes
+            + self.cache_bytes
+            + self.index_bytes
+            + self.text_buffer_bytes
+            + self.blob_bytes
+            + self.snapshot_bytes
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsage {
+    pub rss_bytes: Option<u64>,
+    pub tracked_bytes: u64,
+    pub spilled_bytes: u64,
+    pub limit_bytes: u64,
+    pub documents: Vec<DocumentMemory>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PressureRelief {
+    pub dropped_indexes: u32,
+    pub dropped_blobs: u32,
+    pub cleared_caches: u32,
+    pub dropped_snapshots: u32,
+    pub spilled_documents: u32,
+    pub freed_bytes: u64,
+}
+
+impl PressureRelief {
+    pub fn is_empty(&self) -> bool {
+        self.freed_bytes == 0
+    }
+}
+
+pub fn excess_bytes(tracked: u64, limit: u64) -> u64 {
+    if limit == 0 {
+        return 0;
+    }
+    tracked.saturating_sub(limit)
+}
+
+pub fn largest_indexes(documents: &[DocumentMemory], keep: DocHandle) -> Vec<(DocHandle, u64)> {
+    let mut found: Vec<(DocHandle, u64)> = documents
+        .iter()
+        .filter(|d| d.index_bytes > 0 && d.handle != keep)
+        .map(|d| (d.handle, d.index_bytes))
+        .collect();
+    found.sort_by(|a, b| b.1.cmp(&a.1));
+    found
+}
+
+pub fn least_recent(
+    mut handles: Vec<DocHandle>,
+    keep: DocHandle,
+    touched: impl Fn(DocHandle) -> u64,
+) -> Vec<DocHandle> {
+    handles.retain(|&handle| handle != keep);
+    handles.sort_by_key(|&handle| touched(handle));
+    handles
+}
+
+pub fn spill_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("pandia-spill-{}", std::process::id()))
+}
+
+pub struct Spilled {
+    path: PathBuf,
+    bytes: u64,
+    source_path: Option<String>,
+    file_path: Option<String>,
+    source_size: u64,
+    version: u64,
+}
+
+impl Spilled {
+    pub fn write(handle: DocHandle, doc: &Document) -> DocResult<Self> {
+        let text = doc.snapshot_text()?;
+        let dir = spill_dir();
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.json", handle.0));
+        std::fs::write(&path, &text)?;
+        Ok(Self {
+            path,
+            bytes: text.len() as u64,
+            source_path: doc.source_path.clone(),
+            file_path: doc.file_path.clone(),
+            source_size: doc.source_size,
+            version: doc.version,
+        })
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn source_path(&self) -> Option<&str> {
+        self.source_path.as_deref()
+    }
+
+    pub fn retarget(&mut self, from: &str, to: &str) -> bool {
+        if self.file_path.as_deref() != Some(from) {
+            return false;
+        }
+        self.file_path = Some(to.to_string());
+        self.source_path = Some(to.to_string());
+        true
+    }
+
+    pub fn load(&self) -> DocResult<Document> {
+        let text = std::fs::read_to_string(&self.path)?;
+        let mut doc = Document::from_text(&text, self.source_path.clone())?;
+        doc.file_path = self.file_path.clone();
+        doc.source_size = self.source_size;
+        doc.version = self.version;
+        doc.saved_version = self.version;
+        Ok(doc)
+    }
+
+    pub fn discard(&self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(handle: DocHandle, index_bytes: u64) -> DocumentMemory {
+        DocumentMemory {
+            handle,
+            source_path: None,
+            value_bytes: 10,
+            history_bytes: 0,
+            cache_bytes: 0,
+            index_bytes,
+            text_buffer_bytes: 0,
+            blob_bytes: 0,
+            snapshot_bytes: 0,
+            spilled_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn excess_is_measured_against_an_enabled_limit() {
+        assert_eq!(excess_bytes(500, 0), 0);
+        assert_eq!(excess_bytes(500, 500), 0);
+        assert_eq!(excess_bytes(400, 500), 0);
+        assert_eq!(excess_bytes(501, 500), 1);
+    }
+
+    #[test]
+    fn picks_largest_indexes_and_least_recent_documents_first() {
+        let [a, b, c, keep] = [(); 4].map(|_| DocHandle::new());
+        let docs = [usage(a, 5), usage(b, 0), usage(c, 50), usage(keep, 500)];
+        assert_eq!(largest_indexes(&docs, keep), vec![(c, 50), (a, 5)]);
+
+        let ticks = |handle: DocHandle| match handle {
+            h if h == a => 3,
+            h if h == b => 1,
+            _ => 2,
+        };
+        assert_eq!(
+            least_recent(vec![a, b, c, keep], keep, ticks),
+            vec![b, c, a]
+        );
+    }
+}
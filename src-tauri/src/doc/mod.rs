@@ -1,21 +1,119 @@
+pub mod align;
+pub mod anonymize;
 pub mod backup;
+pub mod binary;
+pub mod blobs;
+pub mod bookmarks;
+pub mod cloud;
+pub mod coerce;
+pub mod colors;
+pub mod completions;
+pub mod conflicts;
+pub mod dedupe;
 pub mod detect;
+pub mod diagnostic_export;
+pub mod diagnostics;
 pub mod diff;
+pub mod diff_export;
+pub mod diff_rules;
+pub mod dirdiff;
+pub mod disk_hash;
 pub mod document;
 pub mod eager;
+pub mod editorconfig;
+pub mod envsubst;
 pub mod export;
+pub mod external;
+pub mod extract;
+pub mod field_stats;
+pub mod file_info;
+pub mod file_ops;
+pub mod fragment;
+pub mod git;
+pub mod git_hooks;
+pub mod goto;
+pub mod graph;
 pub mod grid_filter;
+pub mod har;
+pub mod heatmap;
+pub mod highlight;
+pub mod histogram;
 pub mod history;
+pub mod hover;
+pub mod i18n;
+pub mod ids;
+pub mod inline;
 pub mod jobs;
+pub mod k8s;
+pub mod layers;
 pub mod lazy;
+pub mod links;
+pub mod lint;
+pub mod lockfile;
+pub mod logs;
+pub mod macros;
+pub mod memory;
+pub mod navigation;
+pub mod notebook;
+pub mod notify;
 pub mod ops;
+pub mod outline;
+pub mod paste;
+pub mod pinned;
+pub mod pipeline;
+pub mod placeholders;
+pub mod plugins;
+pub mod pool;
+pub mod portable;
+pub mod positions;
+pub mod preview;
+pub mod profile;
+pub mod provenance;
+pub mod recents;
+pub mod records;
+pub mod references;
+pub mod remote;
 pub mod repair;
+pub mod save_hooks;
 pub mod schema;
+pub mod schema_defaults;
+pub mod schema_example;
+pub mod schema_refs;
 pub mod schema_validate;
+pub mod script;
 pub mod search;
+pub mod search_index;
+pub mod search_metadata;
+pub mod series;
+pub mod settings;
+pub mod sftp;
+pub mod shape_clusters;
+pub mod simd;
+pub mod similarity;
+pub mod snapshots;
+pub mod snippets;
+pub mod split;
+pub mod stash;
 pub mod store;
+pub mod stream_validate;
+pub mod syntax;
+pub mod tabs;
+pub mod tabular;
+pub mod tail;
+pub mod taskbar;
+pub mod telemetry;
+pub mod templates;
+pub mod text_buffer;
+pub mod timefmt;
 pub mod typegen;
 pub mod types;
+pub mod updates;
+pub mod view_state;
+pub mod watch;
+pub mod workspace;
 
 #[cfg(test)]
 mod baseline;
+
+#[cfg(test)]
+pub mod test_support;
@@ -0,0 +1,256 @@
+use serde::Serialize;
+
+use super::document::Document;
+use super::goto;
+use super::types::{DocError, DocResult, NodeKind, Path, PathSegment};
+
+const SIBLING_KEY_CAP: u32 = 500;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Siblings {
+    Keys { keys: Vec<String>, truncated: bool },
+    Indices { index: u32, length: u32 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Crumb {
+    pub path: Path,
+    pub pointer: String,
+    pub label: String,
+    pub kind: NodeKind,
+    pub child_count: Option<u32>,
+    pub siblings: Option<Siblings>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathContext {
+    pub path: Path,
+    pub pointer: String,
+    pub ancestors: Vec<Crumb>,
+    pub parent: Option<String>,
+    pub previous: Option<String>,
+    pub next: Option<String>,
+    pub first_child: Option<String>,
+}
+
+pub fn path_from_pointer(doc: &Document, pointer: &str) -> DocResult<Path> {
+    let segments = goto::pointer_segments(pointer);
+    goto::exact_path(&segments, |p| doc.kind_at(p).ok().map(|(kind, _)| kind))
+        .map(|(path, _)| path)
+        .ok_or_else(|| {
+            DocError::InvalidPath(Path(segments.into_iter().map(PathSegment::Key).collect()))
+        })
+}
+
+fn label_of(seg: Option<&PathSegment>) -> String {
+    match seg {
+        None => "$".into(),
+        Some(PathSegment::Key(k)) => k.clone(),
+        Some(PathSegment::Index(i)) => format!("[{i}]"),
+    }
+}
+
+fn child_path(parent: &Path, seg: PathSegment) -> Path {
+    let mut path = parent.clone();
+    path.push(seg);
+    path
+}
+
+fn object_keys(doc: &Document, parent: &Path, limit: u32) -> DocResult<Vec<String>> {
+    Ok(doc
+        .get_slice(parent, 0..limit)?
+        .into_iter()
+        .filter_map(|node| match node.key {
+            PathSegment::Key(k) => Some(k),
+            PathSegment::Index(_) => None,
+        })
+        .collect())
+}
+
+struct Neighbours {
+    siblings: Siblings,
+    previous: Option<PathSegment>,
+    next: Option<PathSegment>,
+}
+
+fn neighbours(doc: &Document, parent: &Path, seg: &PathSegment) -> DocResult<Neighbours> {
+    match seg {
+        PathSegment::Index(i) => {
+            let length = doc.child_count_at(parent)?.unwrap_or(0);
+            Ok(Neighbours {
+                siblings: Siblings::Indices { index: *i, length },
+                previous: i.checked_sub(1).map(PathSegment::Index),
+                next: (i + 1 < length).then_some(PathSegment::Index(i + 1)),
+            })
+        }
+        PathSegment::Key(key) => {
+            let mut keys = object_keys(doc, parent, SIBLING_KEY_CAP + 1)?;
+            let truncated = keys.len() > SIBLING_KEY_CAP as usize;
+            let mut position = keys.iter().position(|k| k == key);
+            if position.is_none() && truncated {
+                let all = object_keys(doc, parent, u32::MAX)?;
+                position = all.iter().position(|k| k == key);
+                let neighbours = position.map(|p| {
+                    (
+                        p.checked_sub(1).map(|q| all[q].clone()),
+                        all.get(p + 1).cloned(),
+                    )
+                });
+                keys.truncate(SIBLING_KEY_CAP as usize);
+                let (previous, next) = neighbours.unwrap_or_default();
+                return Ok(Neighbours {
+                    siblings: Siblings::Keys { keys, truncated },
+                    previous: previous.map(PathSegment::Key),
+                    next: next.map(PathSegment::Key),
+                });
+            }
+            let previous = position
+                .and_then(|p| p.checked_sub(1))
+                .map(|p| PathSegment::Key(keys[p].clone()));
+            let next = position
+                .and_then(|p| keys.get(p + 1))
+                .map(|k| PathSegment::Key(k.clone()));
+            keys.truncate(SIBLING_KEY_CAP as usize);
+            Ok(Neighbours {
+                siblings: Siblings::Keys { keys, truncated },
+                previous,
+                next,
+            })
+        }
+    }
+}
+
+pub fn path_context(doc: &Document, path: &Path) -> DocResult<PathContext> {
+    let mut ancestors = Vec::with_capacity(path.0.len() + 1);
+    let mut current = Path::root();
+    let mut target_neighbours = None;
+    for depth in 0..=path.0.len() {
+        let (kind, child_count) = doc.kind_at(&current)?;
+        let seg = depth.checked_sub(1).map(|d| &path.0[d]);
+        let siblings = match seg {
+            Some(seg) => {
+                let parent = Path(path.0[..depth - 1].to_vec());
+                let n = neighbours(doc, &parent, seg)?;
+                let siblings = n.siblings.clone();
+                if depth == path.0.len() {
+                    target_neighbours = Some((parent, n));
+                }
+                Some(siblings)
+            }
+            None => None,
+        };
+        ancestors.push(Crumb {
+            pointer: current.to_pointer(),
+            label: label_of(seg),
+            kind,
+            child_count,
+            siblings,
+            path: current.clone(),
+        });
+        if let Some(next) = path.0.get(depth) {
+            current.push(next.clone());
+        }
+    }
+
+    let target = ancestors.last().expect("root crumb is always present");
+    let first_child = match target.kind {
+        NodeKind::Array if target.child_count.unwrap_or(0) > 0 => {
+            Some(child_path(path, PathSegment::Index(0)).to_pointer())
+        }
+        NodeKind::Object => object_keys(doc, path, 1)?
+            .into_iter()
+            .next()
+            .map(|k| child_path(path, PathSegment::Key(k)).to_pointer()),
+        _ => None,
+    };
+    let (parent, previous, next) = match target_neighbours {
+        Some((parent, n)) => (
+            Some(parent.to_pointer()),
+            n.previous.map(|s| child_path(&parent, s).to_pointer()),
+            n.next.map(|s| child_path(&parent, s).to_pointer()),
+        ),
+        None => (None, None, None),
+    };
+    Ok(PathContext {
+        pointer: path.to_pointer(),
+        path: path.clone(),
+        ancestors,
+        parent,
+        previous,
+        next,
+        first_child,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc() -> Document {
+        Document::from_text(
+            r#"{"meta": {"a": 1, "b": 2, "c": 3}, "items": [{"id": 1}, {"id": 2}, {"id": 3}]}"#,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn builds_ancestor_chain_with_siblings() {
+        let d = doc();
+        let path = path_from_pointer(&d, "/items/1/id").unwrap();
+        let ctx = path_context(&d, &path).unwrap();
+        let labels: Vec<&str> = ctx.ancestors.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["$", "items", "[1]", "id"]);
+        assert_eq!(
+            ctx.ancestors[1].siblings,
+            Some(Siblings::Keys {
+                keys: vec!["meta".into(), "items".into()],
+                truncated: false
+            })
+        );
+        assert_eq!(
+            ctx.ancestors[2].siblings,
+            Some(Siblings::Indices {
+                index: 1,
+                length: 3
+            })
+        );
+        assert_eq!(ctx.parent.as_deref(), Some("/items/1"));
+        assert_eq!(ctx.previous, None);
+        assert_eq!(ctx.next, None);
+    }
+
+    #[test]
+    fn reports_previous_and_next_siblings() {
+        let d = doc();
+        let ctx = path_context(&d, &path_from_pointer(&d, "/meta/b").unwrap()).unwrap();
+        assert_eq!(ctx.previous.as_deref(), Some("/meta/a"));
+        assert_eq!(ctx.next.as_deref(), Some("/meta/c"));
+
+        let ctx = path_context(&d, &path_from_pointer(&d, "/items/2").unwrap()).unwrap();
+        assert_eq!(ctx.previous.as_deref(), Some("/items/1"));
+        assert_eq!(ctx.next, None);
+        assert_eq!(ctx.first_child.as_deref(), Some("/items/2/id"));
+    }
+
+    #[test]
+    fn root_context_has_no_parent() {
+        let d = doc();
+        let ctx = path_context(&d, &path_from_pointer(&d, "").unwrap()).unwrap();
+        assert_eq!(ctx.ancestors.len(), 1);
+        assert_eq!(ctx.parent, None);
+        assert_eq!(ctx.first_child.as_deref(), Some("/meta"));
+    }
+
+    #[test]
+    fn rejects_unknown_pointer() {
+        let d = doc();
+        assert!(matches!(
+            path_from_pointer(&d, "/missing/0"),
+            Err(DocError::InvalidPath(_))
+        ));
+    }
+}
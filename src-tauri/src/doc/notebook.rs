@@ -0,0 +1,398 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use base64::Engine as _;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::ops::Op;
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+const MAX_OUTPUT_TEXT: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookOutput {
+    pub output_type: String,
+    pub name: Option<String>,
+    pub text: Option<String>,
+    pub truncated: bool,
+    pub images: Vec<NotebookImage>,
+    pub mime_types: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookImage {
+    pub mime_type: String,
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookCell {
+    pub index: u32,
+    pub cell_type: String,
+    pub id: Option<String>,
+    pub source: String,
+    pub execution_count: Option<i64>,
+    pub outputs: Vec<NotebookOutput>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notebook {
+    pub nbformat: i64,
+    pub nbformat_minor: i64,
+    pub language: Option<String>,
+    pub kernel: Option<String>,
+    pub cells: Vec<NotebookCell>,
+}
+
+pub fn image_dir() -> PathBuf {
+    std::env::temp_dir().join("pandia-notebook")
+}
+
+fn join_text(v: Option<&Value>) -> String {
+    match v {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            chars.next();
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn truncate(s: String) -> (String, bool) {
+    if s.chars().count() <= MAX_OUTPUT_TEXT {
+        return (s, false);
+    }
+    (s.chars().take(MAX_OUTPUT_TEXT).collect(), true)
+}
+
+fn image_ext(mime: &str) -> Option<&'static str> {
+    match mime {
+        "image/png" => Some("png"),
+        "image/jpeg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/svg+xml" => Some("svg"),
+        _ => None,
+    }
+}
+
+fn extract_image(mime: &str, data: &Value, dir: &FsPath) -> DocResult<Option<NotebookImage>> {
+    let Some(ext) = image_ext(mime) else {
+        return Ok(None);
+    };
+    let raw = join_text(Some(data));
+    let bytes = if mime == "image/svg+xml" {
+        raw.into_bytes()
+    } else {
+        let compact: String = raw.chars().filter(|c| !c.is_whitespace()).collect();
+        base64::engine::general_purpose::STANDARD
+            .decode(compact)
+            .map_err(|e| DocError::Parse(format!("{mime} output is not valid base64: {e}")))?
+    };
+    let hash = blake3::hash(&bytes).to_hex();
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.{ext}", &hash[..16]));
+    if !path.exists() {
+        std::fs::write(&path, &bytes)?;
+    }
+    Ok(Some(NotebookImage {
+        mime_type: mime.to_string(),
+        path: path.to_string_lossy().into_owned(),
+        size: bytes.len() as u64,
+    }))
+}
+
+fn summarize_output(output: &Value, dir: &FsPath) -> DocResult<NotebookOutput> {
+    let output_type = output
+        .get("output_type")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown")
+        .to_string();
+    let mut images = Vec::new();
+    let mut mime_types = Vec::new();
+    let text = match output_type.as_str() {
+        "stream" => Some(join_text(output.get("text"))),
+        "error" => {
+            let ename = output
+                .get("ename")
+                .and_then(Value::as_str)
+                .unwrap_or("Error");
+            let evalue = output.get("evalue").and_then(Value::as_str).unwrap_or("");
+            let traceback: Vec<&str> = output
+                .get("traceback")
+                .and_then(Value::as_array)
+                .map(|t| t.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            if traceback.is_empty() {
+                Some(format!("{ename}: {evalue}"))
+            } else {
+                Some(strip_ansi(&traceback.join("\n")))
+            }
+        }
+        _ => {
+            let data = output.get("data").and_then(Value::as_object);
+            for (mime, value) in data.into_iter().flatten() {
+                mime_types.push(mime.clone());
+                if let Some(image) = extract_image(mime, value, dir)? {
+                    images.push(image);
+                }
+            }
+            data.and_then(|d| d.get("text/plain"))
+                .map(|t| join_text(Some(t)))
+        }
+    };
+    let (text, truncated) = match text {
+        Some(t) => {
+            let (t, cut) = truncate(t);
+            (Some(t), cut)
+        }
+        None => (None, false),
+    };
+    Ok(NotebookOutput {
+        output_type,
+        name: output
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        text,
+        truncated,
+        images,
+        mime_types,
+    })
+}
+
+fn cells_of(root: &Value) -> DocResult<&Vec<Value>> {
+    root.get("cells")
+        .and_then(Value::as_array)
+        .ok_or_else(|| DocError::Parse("not a notebook: missing cells array".into()))
+}
+
+pub fn parse(root: &Value, dir: &FsPath) -> DocResult<Notebook> {
+    let metadata = root.get("metadata");
+    let kernelspec = metadata.and_then(|m| m.get("kernelspec"));
+    let language = metadata
+        .and_then(|m| m.get("language_info"))
+        .and_then(|l| l.get("name"))
+        .or_else(|| kernelspec.and_then(|k| k.get("language")))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let mut cells = Vec::new();
+    for (i, cell) in cells_of(root)?.iter().enumerate() {
+        let outputs = cell
+            .get("outputs")
+            .and_then(Value::as_array)
+            .map(|o| {
+                o.iter()
+                    .map(|o| summarize_output(o, dir))
+                    .collect::<DocResult<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+        cells.push(NotebookCell {
+            index: i as u32,
+            cell_type: cell
+                .get("cell_type")
+                .and_then(Value::as_str)
+                .unwrap_or("raw")
+                .to_string(),
+            id: cell.get("id").and_then(Value::as_str).map(str::to_string),
+            source: join_text(cell.get("source")),
+            execution_count: cell.get("execution_count").and_then(Value::as_i64),
+            outputs,
+        });
+    }
+    Ok(Notebook {
+        nbformat: root.get("nbformat").and_then(Value::as_i64).unwrap_or(0),
+        nbformat_minor: root
+            .get("nbformat_minor")
+            .and_then(Value::as_i64)
+            .unwrap_or(0),
+        language,
+        kernel: kernelspec
+            .and_then(|k| k.get("display_name").or_else(|| k.get("name")))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        cells,
+    })
+}
+
+fn split_lines(source: &str) -> Vec<Value> {
+    source
+        .split_inclusive('\n')
+        .map(|l| Value::String(l.to_string()))
+        .collect()
+}
+
+pub fn source_path(index: u32) -> Path {
+    Path(vec![
+        PathSegment::Key("cells".into()),
+        PathSegment::Index(index),
+        PathSegment::Key("source".into()),
+    ])
+}
+
+pub fn set_source_op(root: &Value, index: u32, source: &str) -> DocResult<Op> {
+    let cell = cells_of(root)?
+        .get(index as usize)
+        .ok_or_else(|| DocError::Edit(format!("notebook has no cell {index}")))?;
+    let value = match cell.get("source") {
+        Some(Value::String(_)) => Value::String(source.to_string()),
+        _ => Value::Array(split_lines(source)),
+    };
+    Ok(Op::SetValue {
+        path: source_path(index),
+        value,
+    })
+}
+
+fn check_cell(i: usize, cell: &Value) -> DocResult<()> {
+    let fail = |msg: &str| Err(DocError::Schema(format!("cell {i}: {msg}")));
+    let Some(obj) = cell.as_object() else {
+        return fail("not an object");
+    };
+    let cell_type = obj.get("cell_type").and_then(Value::as_str);
+    if !matches!(cell_type, Some("code" | "markdown" | "raw")) {
+        return fail("cell_type must be code, markdown or raw");
+    }
+    match obj.get("source") {
+        Some(Value::String(_)) => {}
+        Some(Value::Array(lines)) if lines.iter().all(Value::is_string) => {}
+        _ => return fail("source must be a string or a list of strings"),
+    }
+    if !obj.get("metadata").is_some_and(Value::is_object) {
+        return fail("metadata must be an object");
+    }
+    if cell_type == Some("code") && !obj.get("outputs").is_some_and(Value::is_array) {
+        return fail("code cells need an outputs list");
+    }
+    Ok(())
+}
+
+pub fn validate(root: &Value) -> DocResult<()> {
+    let obj: &Map<String, Value> = root
+        .as_object()
+        .ok_or_else(|| DocError::Schema("notebook root must be an object".into()))?;
+    if obj.get("nbformat").and_then(Value::as_i64) != Some(4) {
+        return Err(DocError::Schema(
+            "only nbformat 4 notebooks can be saved".into(),
+        ));
+    }
+    if !obj.get("metadata").is_some_and(Value::is_object) {
+        return Err(DocError::Schema(
+            "notebook metadata must be an object".into(),
+        ));
+    }
+    for (i, cell) in cells_of(root)?.iter().enumerate() {
+        check_cell(i, cell)?;
+    }
+    Ok(())
+}
+
+pub fn serialize(root: &Value) -> DocResult<String> {
+    validate(root)?;
+    let mut buf = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    root.serialize(&mut ser)
+        .map_err(|e| DocError::Export(e.to_string()))?;
+    let mut text = String::from_utf8(buf).map_err(|e| DocError::Export(e.to_string()))?;
+    text.push('\n');
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+
+    fn sample() -> Value {
+        json!({
+            "nbformat": 4,
+            "nbformat_minor": 5,
+            "metadata": {
+                "kernelspec": { "name": "python3", "display_name": "Python 3", "language": "python" }
+            },
+            "cells": [
+                { "cell_type": "markdown", "id": "a", "metadata": {}, "source": ["# Title\n", "intro"] },
+                {
+                    "cell_type": "code", "id": "b", "metadata": {}, "execution_count": 3,
+                    "source": "print(1)",
+                    "outputs": [
+                        { "output_type": "stream", "name": "stdout", "text": ["1\n"] },
+                        {
+                            "output_type": "display_data", "metadata": {},
+                            "data": { "text/plain": ["<Figure>"], "image/png": "aGVsbG8=\n" }
+                        },
+                        {
+                            "output_type": "error", "ename": "ValueError", "evalue": "bad",
+                            "traceback": ["\u{1b}[0;31mValueError\u{1b}[0m: bad"]
+                        }
+                    ]
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn parses_cells_and_summarizes_outputs() {
+        let dir = TempDir::new("nb");
+        let nb = parse(&sample(), &dir).unwrap();
+        assert_eq!(nb.language.as_deref(), Some("python"));
+        assert_eq!(nb.kernel.as_deref(), Some("Python 3"));
+        assert_eq!(nb.cells[0].source, "# Title\nintro");
+        let outputs = &nb.cells[1].outputs;
+        assert_eq!(outputs[0].text.as_deref(), Some("1\n"));
+        assert_eq!(outputs[1].text.as_deref(), Some("<Figure>"));
+        let image = &outputs[1].images[0];
+        assert_eq!(std::fs::read(&image.path).unwrap(), b"hello");
+        assert_eq!(outputs[2].text.as_deref(), Some("ValueError: bad"));
+    }
+
+    #[test]
+    fn set_source_keeps_representation() {
+        let root = sample();
+        let Op::SetValue { value, .. } = set_source_op(&root, 0, "a\nb").unwrap() else {
+            panic!("expected SetValue");
+        };
+        assert_eq!(value, json!(["a\n", "b"]));
+        let Op::SetValue { value, .. } = set_source_op(&root, 1, "x = 2").unwrap() else {
+            panic!("expected SetValue");
+        };
+        assert_eq!(value, json!("x = 2"));
+        assert!(set_source_op(&root, 9, "").is_err());
+    }
+
+    #[test]
+    fn serialize_uses_jupyter_layout_and_validates() {
+        let text = serialize(&sample()).unwrap();
+        assert!(text.starts_with("{\n \"nbformat\": 4,"));
+        assert!(text.ends_with("}\n"));
+        let mut broken = sample();
+        broken["cells"][1]
+            .as_object_mut()
+            .unwrap()
+            .remove("outputs");
+        assert!(matches!(serialize(&broken), Err(DocError::Schema(_))));
+    }
+}
@@ -0,0 +1,86 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+pub const ACTION_EVENT: &str = "notification-action";
+pub const LONG_TASK: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+pub struct Notifier {
+    pending: Mutex<Option<String>>,
+}
+
+impl Notifier {
+    fn remember(&self, action: Option<&str>) {
+        if let Some(action) = action {
+            *self.pending.lock() = Some(action.to_string());
+        }
+    }
+
+    fn take(&self) -> Option<String> {
+        self.pending.lock().take()
+    }
+}
+
+fn in_background(app: &AppHandle) -> bool {
+    !app.webview_windows()
+        .values()
+        .any(|w| w.is_focused().unwrap_or(false))
+}
+
+pub fn display_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+pub fn notify_user(app: &AppHandle, title: &str, body: &str, action: Option<&str>) -> bool {
+    if !in_background(app) {
+        return false;
+    }
+    let shown = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+        .is_ok();
+    if shown {
+        if let Some(notifier) = app.try_state::<Arc<Notifier>>() {
+            notifier.remember(action);
+        }
+    }
+    shown
+}
+
+pub fn on_focus(app: &AppHandle) {
+    let Some(action) = app
+        .try_state::<Arc<Notifier>>()
+        .and_then(|notifier| notifier.take())
+    else {
+        return;
+    };
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit(ACTION_EVENT, action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_the_latest_action_until_taken() {
+        let notifier = Notifier::default();
+        notifier.remember(Some("open::/a.json"));
+        notifier.remember(None);
+        notifier.remember(Some("open::/b.json"));
+        assert_eq!(notifier.take().as_deref(), Some("open::/b.json"));
+        assert_eq!(notifier.take(), None);
+    }
+}
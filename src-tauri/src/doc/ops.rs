@@ -106,7 +106,7 @@ impl Op {
     }
 }
 
-fn value_heap_bytes(v: &Value) -> usize {
+pub fn value_heap_bytes(v: &Value) -> usize {
     match v {
         Value::Null | Value::Bool(_) => 8,
         Value::Number(_) => 16,
@@ -548,7 +548,7 @@ fn apply_sort_keys(root: &mut Value, path: &Path, descending: bool) -> DocResult
     })
 }
 
-fn sort_keys_in_place(value: &mut Value, descending: bool) {
+pub fn sort_keys_in_place(value: &mut Value, descending: bool) {
     match value {
         Value::Object(map) => {
             for (_, v) in map.iter_mut() {
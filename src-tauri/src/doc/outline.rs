@@ -0,0 +1,174 @@
+use serde::Serialize;
+
+use super::positions::{Node, PositionIndex, TextPosition};
+use super::types::{NodeKind, PathSegment};
+
+const MAX_SYMBOLS: usize = 5_000;
+const DETAIL_CHARS: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FoldingRange {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineSymbol {
+    pub name: String,
+    pub pointer: String,
+    pub kind: NodeKind,
+    pub detail: String,
+    pub start: TextPosition,
+    pub end: TextPosition,
+    pub children: Vec<OutlineSymbol>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Outline {
+    pub symbols: Vec<OutlineSymbol>,
+    pub truncated: bool,
+}
+
+fn kind_of(text: &str, node: &Node) -> NodeKind {
+    match text.as_bytes()[node.start] {
+        b'{' => NodeKind::Object,
+        b'[' => NodeKind::Array,
+        b'"' => NodeKind::String,
+        b't' | b'f' => NodeKind::Bool,
+        b'n' => NodeKind::Null,
+        _ => NodeKind::Number,
+    }
+}
+
+pub fn folding_ranges(index: &PositionIndex) -> Vec<FoldingRange> {
+    let text = index.text();
+    index
+        .nodes()
+        .iter()
+        .filter_map(|node| {
+            let kind = kind_of(text, node);
+            if !matches!(kind, NodeKind::Object | NodeKind::Array) {
+                return None;
+            }
+            let start_line = index.position(node.start).line;
+            let end_line = index.position(node.end - 1).line;
+            (end_line > start_line).then_some(FoldingRange {
+                start_line,
+                end_line,
+                kind,
+            })
+        })
+        .collect()
+}
+
+struct Builder<'a, 'b> {
+    index: &'b PositionIndex<'a>,
+    children: Vec<Vec<usize>>,
+    max_depth: usize,
+    budget: usize,
+    truncated: bool,
+}
+
+impl Builder<'_, '_> {
+    fn build(&mut self, parent: usize, depth: usize) -> Vec<OutlineSymbol> {
+        let mut out = Vec::new();
+        for i in 0..self.children[parent].len() {
+            if self.budget == 0 {
+                self.truncated = true;
+                break;
+            }
+            self.budget -= 1;
+            let child = self.children[parent][i];
+            let nested = if depth < self.max_depth {
+                self.build(child, depth + 1)
+            } else {
+                Vec::new()
+            };
+            out.push(self.symbol(child, nested));
+        }
+        out
+    }
+
+    fn symbol(&self, i: usize, children: Vec<OutlineSymbol>) -> OutlineSymbol {
+        let text = self.index.text();
+        let node = &self.index.nodes()[i];
+        let kind = kind_of(text, node);
+        let count = self.children[i].len();
+        let detail = match kind {
+            NodeKind::Object => format!("{count} {}", if count == 1 { "key" } else { "keys" }),
+            NodeKind::Array => format!("{count} {}", if count == 1 { "item" } else { "items" }),
+            _ => {
+                let raw = &text[node.start..node.end];
+                match raw.char_indices().nth(DETAIL_CHARS) {
+                    Some((cut, _)) => format!("{}…", &raw[..cut]),
+                    None => raw.to_string(),
+                }
+            }
+        };
+        OutlineSymbol {
+            name: match node.path.0.last() {
+                Some(PathSegment::Key(k)) => k.clone(),
+                Some(PathSegment::Index(i)) => format!("[{i}]"),
+                None => "$".into(),
+            },
+            pointer: node.path.to_pointer(),
+            kind,
+            detail,
+            start: self.index.position(node.key_start.unwrap_or(node.start)),
+            end: self.index.position(node.end),
+            children,
+        }
+    }
+}
+
+pub fn outline(index: &PositionIndex, max_depth: usize) -> Outline {
+    let mut builder = Builder {
+        index,
+        children: index.children(),
+        max_depth: max_depth.max(1),
+        budget: MAX_SYMBOLS,
+        truncated: false,
+    };
+    let symbols = builder.build(0, 1);
+    Outline {
+        symbols,
+        truncated: builder.truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "{\n  \"name\": \"x\",\n  \"items\": [\n    {\"id\": 1},\n    {\n      \"id\": 2\n    }\n  ]\n}";
+
+    #[test]
+    fn folds_multi_line_containers_only() {
+        let index = PositionIndex::build(TEXT).unwrap();
+        let ranges: Vec<(u32, u32)> = folding_ranges(&index)
+            .iter()
+            .map(|r| (r.start_line, r.end_line))
+            .collect();
+        assert_eq!(ranges, vec![(1, 9), (3, 8), (5, 7)]);
+    }
+
+    #[test]
+    fn outline_respects_max_depth() {
+        let index = PositionIndex::build(TEXT).unwrap();
+        let outline = outline(&index, 2);
+        assert!(!outline.truncated);
+        let names: Vec<&str> = outline.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["name", "items"]);
+        assert_eq!(outline.symbols[0].detail, "\"x\"");
+        let items = &outline.symbols[1];
+        assert_eq!((items.detail.as_str(), items.start.line), ("2 items", 3));
+        let nested: Vec<&str> = items.children.iter().map(|s| s.pointer.as_str()).collect();
+        assert_eq!(nested, vec!["/items/0", "/items/1"]);
+        assert_eq!(items.children[1].detail, "1 key");
+        assert!(items.children[1].children.is_empty());
+    }
+}
@@ -0,0 +1,524 @@
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PasteSource {
+    Json,
+    JavaScript,
+    Python,
+    Php,
+    Ruby,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizedPaste {
+    pub source: PasteSource,
+    pub json: String,
+    pub changed: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Open(char),
+    Close(char),
+    Comma,
+    Colon,
+    Scope,
+    Arrow,
+    Str(String),
+    Num(String),
+    Ident(String),
+    Symbol(String),
+}
+
+#[derive(Default)]
+struct Seen {
+    arrow_in_bracket: bool,
+    arrow: bool,
+    php_array: bool,
+    symbol: bool,
+    nil: bool,
+    python: bool,
+    lossy: bool,
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$' || c == '\\'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || c == '\\'
+}
+
+fn read_string(chars: &[char], mut i: usize, raw: bool) -> DocResult<(String, usize)> {
+    let quote = chars[i];
+    let mut out = String::new();
+    i += 1;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == quote {
+            return Ok((out, i + 1));
+        }
+        if c != '\\' || raw {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+        let Some(&next) = chars.get(i + 1) else {
+            break;
+        };
+        i += 2;
+        match next {
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            'r' => out.push('\r'),
+            'b' => out.push('\u{8}'),
+            'f' => out.push('\u{c}'),
+            'v' => out.push('\u{b}'),
+            '0' => out.push('\0'),
+            'x' | 'u' => {
+                let (digits, braced) = match (next, chars.get(i)) {
+                    ('u', Some('{')) => chars[i..]
+                        .iter()
+                        .position(|&c| c == '}')
+                        .map_or((String::new(), 0), |end| {
+                            (chars[i + 1..i + end].iter().collect::<String>(), end + 1)
+                        }),
+                    _ => {
+                        let len = if next == 'x' { 2 } else { 4 };
+                        let end = (i + len).min(chars.len());
+                        (chars[i..end].iter().collect::<String>(), end - i)
+                    }
+                };
+                match u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                {
+                    Some(decoded) => {
+                        out.push(decoded);
+                        i += braced;
+                    }
+                    None => {
+                        out.push('\\');
+                        out.push(next);
+                    }
+                }
+            }
+            '\n' => {}
+            other if other == quote || matches!(other, '\\' | '/' | '"' | '\'') => out.push(other),
+            other => {
+                out.push('\\');
+                out.push(other);
+            }
+        }
+    }
+    Err(DocError::Parse("unterminated string".into()))
+}
+
+fn lex(text: &str) -> DocResult<Vec<Tok>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens: Vec<Tok> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if next == Some('/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if next == Some('*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            '{' | '[' | '(' => {
+                tokens.push(Tok::Open(c));
+                i += 1;
+            }
+            '}' | ']' | ')' => {
+                tokens.push(Tok::Close(c));
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Tok::Comma);
+                i += 1;
+            }
+            '=' if next == Some('>') => {
+                tokens.push(Tok::Arrow);
+                i += 2;
+            }
+            ':' if next == Some(':') => {
+                tokens.push(Tok::Scope);
+                i += 2;
+            }
+            ':' if next.is_some_and(is_ident_start)
+                && matches!(
+                    tokens.last(),
+                    None | Some(Tok::Open(_) | Tok::Comma | Tok::Arrow | Tok::Colon)
+                ) =>
+            {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && (is_ident_char(chars[i]) || matches!(chars[i], '?' | '!'))
+                {
+                    i += 1;
+                }
+                tokens.push(Tok::Symbol(chars[start..i].iter().collect()));
+            }
+            ':' => {
+                tokens.push(Tok::Colon);
+                i += 1;
+            }
+            '"' | '\'' | '`' => {
+                let (s, end) = read_string(&chars, i, false)?;
+                tokens.push(Tok::Str(s));
+                i = end;
+            }
+            _ if c.is_ascii_digit()
+                || (matches!(c, '-' | '+' | '.') && next.is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                let start = i;
+                i += 1;
+                while i < chars.len() {
+                    let d = chars[i];
+                    let exponent_sign = matches!(d, '+' | '-')
+                        && matches!(chars[i - 1], 'e' | 'E')
+                        && !chars[start..i].iter().any(|c| matches!(c, 'x' | 'X'));
+                    if d.is_ascii_alphanumeric() || d == '.' || d == '_' || exponent_sign {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Tok::Num(chars[start..i].iter().collect()));
+            }
+            '-' if chars[i + 1..].starts_with(&['I', 'n', 'f']) => {
+                i += 1;
+            }
+            _ if is_ident_start(c) => {
+                let start = i;
+                while i < chars.len() && is_ident_char(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let prefix = matches!(
+                    word.to_ascii_lowercase().as_str(),
+                    "u" | "b" | "r" | "rb" | "br"
+                );
+                if prefix && matches!(chars.get(i), Some('"' | '\'')) {
+                    let (s, end) = read_string(&chars, i, word.to_ascii_lowercase().contains('r'))?;
+                    tokens.push(Tok::Str(s));
+                    i = end;
+                } else {
+                    tokens.push(Tok::Ident(word));
+                }
+            }
+            ';' => i += 1,
+            other => {
+                return Err(DocError::Parse(format!("unexpected character {other:?}")));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn number(raw: &str) -> Option<Value> {
+    let cleaned: String = raw.chars().filter(|c| *c != '_').collect();
+    let cleaned = cleaned.trim_end_matches(['n', 'L', 'l']);
+    let (negative, digits) = match cleaned.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, cleaned.trim_start_matches('+')),
+    };
+    let sign = if negative { -1 } else { 1 };
+    for (prefix, radix) in [
+        ("0x", 16),
+        ("0X", 16),
+        ("0o", 8),
+        ("0O", 8),
+        ("0b", 2),
+        ("0B", 2),
+    ] {
+        if let Some(rest) = digits.strip_prefix(prefix) {
+            return i64::from_str_radix(rest, radix)
+                .ok()
+                .map(|n| Value::Number((sign * n).into()));
+        }
+    }
+    let mut text = String::from(if negative { "-" } else { "" });
+    if digits.starts_with('.') {
+        text.push('0');
+    }
+    text.push_str(digits.trim_end_matches('.'));
+    if let Ok(n) = serde_json::from_str::<Number>(&text) {
+        return Some(Value::Number(n));
+    }
+    text.parse::<f64>()
+        .ok()
+        .and_then(Number::from_f64)
+        .map(Value::Number)
+}
+
+struct Parser {
+    tokens: Vec<Tok>,
+    pos: usize,
+    seen: Seen,
+}
+
+impl Parser {
+    fn peek(&self, ahead: usize) -> Option<&Tok> {
+        self.tokens.get(self.pos + ahead)
+    }
+
+    fn bump(&mut self) -> Option<Tok> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    fn error(&self, message: &str) -> DocError {
+        DocError::Parse(format!("{message} (token {})", self.pos + 1))
+    }
+
+    fn key(&self) -> Option<String> {
+        let key = match self.peek(0)? {
+            Tok::Str(s) | Tok::Ident(s) | Tok::Num(s) | Tok::Symbol(s) => s.clone(),
+            _ => return None,
+        };
+        matches!(self.peek(1), Some(Tok::Colon | Tok::Arrow)).then_some(key)
+    }
+
+    fn entries(&mut self, close: char) -> DocResult<Vec<(Option<String>, Value)>> {
+        let mut out = Vec::new();
+        loop {
+            if self.peek(0) == Some(&Tok::Close(close)) {
+                self.pos += 1;
+                return Ok(out);
+            }
+            let key = self.key();
+            if key.is_some() {
+                if self.peek(1) == Some(&Tok::Arrow) {
+                    self.seen.arrow = true;
+                    self.seen.arrow_in_bracket |= close == ']';
+                }
+                self.pos += 2;
+            }
+            let value = self.value()?;
+            out.push((key, value));
+            match self.bump() {
+                Some(Tok::Comma) => {}
+                Some(Tok::Close(c)) if c == close => return Ok(out),
+                _ => return Err(self.error(&format!("expected ',' or '{close}'"))),
+            }
+        }
+    }
+
+    fn combine(entries: Vec<(Option<String>, Value)>, object: bool) -> Value {
+        if entries.iter().all(|(k, _)| k.is_none()) {
+            return Value::Array(entries.into_iter().map(|(_, v)| v).collect());
+        }
+        let sequential = entries
+            .iter()
+            .enumerate()
+            .all(|(i, (k, _))| k.as_deref() == Some(i.to_string().as_str()));
+        if sequential && !object {
+            return Value::Array(entries.into_iter().map(|(_, v)| v).collect());
+        }
+        let mut map = Map::new();
+        for (i, (k, v)) in entries.into_iter().enumerate() {
+            map.insert(k.unwrap_or_else(|| i.to_string()), v);
+        }
+        Value::Object(map)
+    }
+
+    fn word(&mut self, word: String) -> DocResult<Value> {
+        match word.as_str() {
+            "true" | "TRUE" => Ok(Value::Bool(true)),
+            "false" | "FALSE" => Ok(Value::Bool(false)),
+            "null" | "NULL" => Ok(Value::Null),
+            "True" | "False" => {
+                self.seen.python = true;
+                Ok(Value::Bool(word == "True"))
+            }
+            "None" => {
+                self.seen.python = true;
+                Ok(Value::Null)
+            }
+            "nil" => {
+                self.seen.nil = true;
+                Ok(Value::Null)
+            }
+            "undefined" | "NaN" | "Infinity" | "inf" | "nan" => {
+                self.seen.lossy = true;
+                Ok(Value::Null)
+            }
+            "new" => self.value(),
+            _ => {
+                while self.peek(0) == Some(&Tok::Scope) {
+                    self.pos += 2;
+                }
+                if self.peek(0) != Some(&Tok::Open('(')) {
+                    return Err(self.error(&format!("unexpected identifier {word}")));
+                }
+                self.pos += 1;
+                let mut entries = self.entries(')')?;
+                if word.eq_ignore_ascii_case("array") {
+                    self.seen.php_array = true;
+                    return Ok(Self::combine(entries, false));
+                }
+                if let [(None, _)] = entries.as_slice() {
+                    return Ok(entries.swap_remove(0).1);
+                }
+                Ok(Self::combine(entries, true))
+            }
+        }
+    }
+
+    fn value(&mut self) -> DocResult<Value> {
+        match self.bump() {
+            Some(Tok::Open('{')) => {
+                let entries = self.entries('}')?;
+                Ok(Self::combine(entries, true))
+            }
+            Some(Tok::Open('[')) => {
+                let entries = self.entries(']')?;
+                Ok(Self::combine(entries, false))
+            }
+            Some(Tok::Open('(')) => {
+                if let (Some(Tok::Ident(_)), Some(Tok::Close(')'))) = (self.peek(0), self.peek(1)) {
+                    self.pos += 2;
+                    return self.value();
+                }
+                self.seen.python = true;
+                let entries = self.entries(')')?;
+                Ok(Self::combine(entries, false))
+            }
+            Some(Tok::Str(s)) => Ok(Value::String(s)),
+            Some(Tok::Symbol(s)) => {
+                self.seen.symbol = true;
+                Ok(Value::String(s))
+            }
+            Some(Tok::Num(raw)) => {
+                number(&raw).ok_or_else(|| self.error(&format!("invalid number {raw}")))
+            }
+            Some(Tok::Ident(word)) => self.word(word),
+            Some(_) => Err(self.error("unexpected token")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+}
+
+fn source_of(seen: &Seen) -> PasteSource {
+    if seen.php_array || seen.arrow_in_bracket {
+        PasteSource::Php
+    } else if seen.symbol || seen.nil || seen.arrow {
+        PasteSource::Ruby
+    } else if seen.python {
+        PasteSource::Python
+    } else {
+        PasteSource::JavaScript
+    }
+}
+
+pub fn normalize(text: &str) -> DocResult<NormalizedPaste> {
+    if let Ok(value) = serde_json::from_str::<Value>(text) {
+        return Ok(NormalizedPaste {
+            source: PasteSource::Json,
+            json: serde_json::to_string_pretty(&value)
+                .map_err(|e| DocError::Export(e.to_string()))?,
+            changed: false,
+            warnings: Vec::new(),
+        });
+    }
+    let mut parser = Parser {
+        tokens: lex(text)?,
+        pos: 0,
+        seen: Seen::default(),
+    };
+    let value = parser.value()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(parser.error("unexpected content after the value"));
+    }
+    let mut warnings = Vec::new();
+    if parser.seen.lossy {
+        warnings.push("undefined, NaN and Infinity were replaced with null".to_string());
+    }
+    Ok(NormalizedPaste {
+        source: source_of(&parser.seen),
+        json: serde_json::to_string_pretty(&value).map_err(|e| DocError::Export(e.to_string()))?,
+        changed: true,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parsed(text: &str) -> (PasteSource, Value) {
+        let out = normalize(text).unwrap();
+        (out.source, serde_json::from_str(&out.json).unwrap())
+    }
+
+    #[test]
+    fn converts_javascript_and_python_literals() {
+        let (source, value) =
+            parsed("{name: 'Ada', tags: [`x`, 0x1F,], n: .5, /* c */ u: undefined}");
+        assert_eq!(source, PasteSource::JavaScript);
+        assert_eq!(
+            value,
+            json!({"name": "Ada", "tags": ["x", 31], "n": 0.5, "u": null})
+        );
+
+        let (source, value) =
+            parsed("{'a': True, 'b': None, 'c': (1, 2), 'd': u'caf\\xe9', 'e': 1_000}");
+        assert_eq!(source, PasteSource::Python);
+        assert_eq!(
+            value,
+            json!({"a": true, "b": null, "c": [1, 2], "d": "café", "e": 1000})
+        );
+    }
+
+    #[test]
+    fn converts_php_var_export_and_ruby_hashes() {
+        let php = "array (\n  'id' => 7,\n  'list' => \n  array (\n    0 => 'a',\n    1 => 'b',\n  ),\n  'obj' => \n  (object) array(\n     'x' => NULL,\n  ),\n)";
+        let (source, value) = parsed(php);
+        assert_eq!(source, PasteSource::Php);
+        assert_eq!(
+            value,
+            json!({"id": 7, "list": ["a", "b"], "obj": {"x": null}})
+        );
+
+        let (source, value) =
+            parsed("{:name => \"Ada\", \"age\" => 36, active: true, role: :admin, x: nil}");
+        assert_eq!(source, PasteSource::Ruby);
+        assert_eq!(
+            value,
+            json!({"name": "Ada", "age": 36, "active": true, "role": "admin", "x": null})
+        );
+    }
+
+    #[test]
+    fn passes_through_json_and_rejects_garbage() {
+        let out = normalize("{\"a\":1}").unwrap();
+        assert_eq!(out.source, PasteSource::Json);
+        assert!(!out.changed);
+        assert!(normalize("{a: foo}").is_err());
+        assert!(normalize("[1, 2").is_err());
+    }
+}
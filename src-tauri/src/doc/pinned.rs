@@ -0,0 +1,90 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::timefmt::now_millis;
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedFile {
+    pub path: String,
+    pub name: String,
+    #[serde(default)]
+    pub pinned_at: String,
+}
+
+pub fn pinned_path(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let base = super::portable::data_dir(app)?;
+    Ok(base.join("pinned.json"))
+}
+
+pub fn load(path: &FsPath) -> std::io::Result<Vec<PinnedFile>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn store(path: &FsPath, pinned: &[PinnedFile]) -> std::io::Result<()> {
+    let json =
+        serde_json::to_string_pretty(pinned).map_err(|e| std::io::Error::other(e.to_string()))?;
+    std::fs::write(path, json)
+}
+
+pub fn pin(path: &FsPath, file: &str) -> DocResult<Vec<PinnedFile>> {
+    let name = FsPath::new(file)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| DocError::Edit(format!("not a file path: {file}")))?;
+    let mut all = load(path)?;
+    if !all.iter().any(|p| p.path == file) {
+        all.push(PinnedFile {
+            path: file.to_string(),
+            name,
+            pinned_at: now_millis().to_string(),
+        });
+        store(path, &all)?;
+    }
+    Ok(all)
+}
+
+pub fn unpin(path: &FsPath, file: &str) -> std::io::Result<Vec<PinnedFile>> {
+    let mut all = load(path)?;
+    let before = all.len();
+    all.retain(|p| p.path != file);
+    if all.len() != before {
+        store(path, &all)?;
+    }
+    Ok(all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn pins_once_in_order_and_unpins() {
+        let dir = TempDir::new("pinned-order");
+        let db = dir.join("pinned.json");
+        pin(&db, "/data/a.json").unwrap();
+        pin(&db, "/data/b.json").unwrap();
+        let all = pin(&db, "/data/a.json").unwrap();
+        let names: Vec<&str> = all.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["a.json", "b.json"]);
+        let all = unpin(&db, "/data/a.json").unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(load(&db).unwrap()[0].path, "/data/b.json");
+        assert_eq!(unpin(&db, "/data/missing.json").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejects_paths_without_file_name() {
+        let dir = TempDir::new("pinned-bad");
+        assert!(pin(&dir.join("pinned.json"), "/").is_err());
+    }
+}
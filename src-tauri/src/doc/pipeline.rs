@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+use std::path::Path as FsPath;
+use std::time::{Duration, Instant};
+
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::grid_filter::{self, GridFilter};
+use super::jobs::CancelFlag;
+use super::provenance::ProvenanceWriter;
+use super::records::{self, RecordFormat};
+use super::script::{from_dynamic, restore_original, to_dynamic};
+use super::types::{DocError, DocResult};
+
+pub const PROGRESS_EVENT: &str = "pipeline-progress";
+
+const MAX_OPERATIONS: u64 = 10_000_000;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PipelineStep {
+    #[serde(rename_all = "camelCase")]
+    Filter {
+        #[serde(default)]
+        groups: Vec<Vec<GridFilter>>,
+        #[serde(default)]
+        expression: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Select { fields: Vec<String> },
+    #[serde(rename_all = "camelCase")]
+    Rename { fields: BTreeMap<String, String> },
+    #[serde(rename_all = "camelCase")]
+    Script { source: String },
+    #[serde(rename_all = "camelCase")]
+    Limit { count: u64 },
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineProgress {
+    pub records_in: u64,
+    pub records_out: u64,
+    pub bytes_read: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineResult {
+    pub output_path: String,
+    pub records_in: u64,
+    pub records_out: u64,
+    pub stopped_early: bool,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance_path: Option<String>,
+}
+
+enum Stage {
+    Filter {
+        groups: Vec<Vec<GridFilter>>,
+        expression: Option<AST>,
+    },
+    Select(Vec<String>),
+    Rename(BTreeMap<String, String>),
+    Script(AST),
+    Limit {
+        count: u64,
+        seen: u64,
+    },
+}
+
+fn lookup<'a>(record: &'a Value, field: &str) -> Option<&'a Value> {
+    if field.starts_with('/') {
+        record.pointer(field)
+    } else {
+        record.get(field)
+    }
+}
+
+fn field_name(field: &str) -> String {
+    match field.strip_prefix('/') {
+        Some(rest) => rest
+            .rsplit('/')
+            .next()
+            .unwrap_or(rest)
+            .replace("~1", "/")
+            .replace("~0", "~"),
+        None => field.to_string(),
+    }
+}
+
+fn rename(record: Value, fields: &BTreeMap<String, String>) -> Value {
+    match record {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| match fields.get(&k) {
+                    Some(to) => (to.clone(), v),
+                    None => (k, v),
+                })
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+struct Runner {
+    engine: Engine,
+    stages: Vec<Stage>,
+}
+
+impl Runner {
+    fn new(steps: &[PipelineStep]) -> DocResult<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.register_fn("to_json", |v: Dynamic| from_dynamic(v).to_string());
+        let compile = |engine: &Engine, source: &str| {
+            engine
+                .compile(source)
+                .map_err(|e| DocError::Edit(format!("script error: {e}")))
+        };
+        let mut stages = Vec::with_capacity(steps.len());
+        for step in steps {
+            stages.push(match step {
+                PipelineStep::Filter { groups, expression } => Stage::Filter {
+                    groups: groups.clone(),
+                    expression: expression
+                        .as_deref()
+                        .filter(|s| !s.trim().is_empty())
+                        .map(|s| compile(&engine, s))
+                        .transpose()?,
+                },
+                PipelineStep::Select { fields } => Stage::Select(fields.clone()),
+                PipelineStep::Rename { fields } => Stage::Rename(fields.clone()),
+                PipelineStep::Script { source } => Stage::Script(compile(&engine, source)?),
+                PipelineStep::Limit { count } => Stage::Limit {
+                    count: *count,
+                    seen: 0,
+                },
+            });
+        }
+        Ok(Self { engine, stages })
+    }
+
+    fn eval(&self, ast: &AST, record: &Value) -> DocResult<(Dynamic, Dynamic)> {
+        let mut scope = Scope::new();
+        scope.push("doc", to_dynamic(record));
+        let result = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, ast)
+            .map_err(|e| DocError::Edit(format!("script error: {e}")))?;
+        let doc = scope.get_value::<Dynamic>("doc").unwrap_or(Dynamic::UNIT);
+        Ok((result, doc))
+    }
+
+    fn exhausted(&self) -> bool {
+        self.stages
+            .iter()
+            .any(|s| matches!(s, Stage::Limit { count, seen } if seen >= count))
+    }
+
+    fn apply(&mut self, mut record: Value) -> DocResult<Option<Value>> {
+        for i in 0..self.stages.len() {
+            record = match &self.stages[i] {
+                Stage::Filter { groups, expression } => {
+                    if !grid_filter::row_passes(groups, |key| lookup(&record, key)) {
+                        return Ok(None);
+                    }
+                    if let Some(ast) = expression {
+                        let (keep, _) = self.eval(ast, &record)?;
+                        if !keep.as_bool().map_err(|t| {
+                            DocError::Edit(format!("filter expression returned {t}, not a bool"))
+                        })? {
+                            return Ok(None);
+                        }
+                    }
+                    record
+                }
+                Stage::Select(fields) => Value::Object(
+                    fields
+                        .iter()
+                        .filter_map(|f| Some((field_name(f), lookup(&record, f)?.clone())))
+                        .collect::<Map<String, Value>>(),
+                ),
+                Stage::Rename(fields) => rename(record, fields),
+                Stage::Script(ast) => {
+                    let (result, doc) = self.eval(ast, &record)?;
+                    let next = if result.is_unit() { doc } else { result };
+                    if next.is_unit() {
+                        return Ok(None);
+                    }
+                    restore_original(from_dynamic(next), &record)
+                }
+                Stage::Limit { .. } => {
+                    let Stage::Limit { count, seen } = &mut self.stages[i] else {
+                        unreachable!()
+                    };
+                    if *seen >= *count {
+                        return Ok(None);
+                    }
+                    *seen += 1;
+                    record
+                }
+            };
+        }
+        Ok(Some(record))
+    }
+}
+
+pub fn run_pipeline(
+    input: &FsPath,
+    steps: &[PipelineStep],
+    output: &FsPath,
+    provenance: bool,
+    cancel: &CancelFlag,
+    mut on_progress: impl FnMut(&PipelineProgress),
+) -> DocResult<PipelineResult> {
+    let started = Instant::now();
+    let mut runner = Runner::new(steps)?;
+    let mut reader = records::open(input)?;
+    let format = RecordFormat::from_extension(output).unwrap_or(RecordFormat::Ndjson);
+    let mut writer = records::create(output, format)?;
+    let mut origins = provenance
+        .then(|| ProvenanceWriter::create(output))
+        .transpose()?;
+    let source = input.to_string_lossy();
+    let mut progress = PipelineProgress::default();
+    let mut last_report = Instant::now();
+    let mut stopped_early = false;
+    loop {
+        if runner.exhausted() {
+            stopped_early = true;
+            break;
+        }
+        let Some(record) = reader.next_value()? else {
+            break;
+        };
+        if cancel.is_cancelled() {
+            return Err(DocError::Cancelled);
+        }
+        progress.records_in += 1;
+        if let Some(out) = runner.apply(record)? {
+            if let Some(origins) = origins.as_mut() {
+                let pointer = format!("/{}", progress.records_out);
+                origins.record(&pointer, &source, Some(reader.record_line()))?;
+            }
+            writer.write(&out)?;
+            progress.records_out += 1;
+        }
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            progress.bytes_read = reader.bytes_read();
+            on_progress(&progress);
+            last_report = Instant::now();
+        }
+    }
+    writer.finish()?;
+    progress.bytes_read = reader.bytes_read();
+    on_progress(&progress);
+    Ok(PipelineResult {
+        output_path: output.to_string_lossy().into_owned(),
+        records_in: progress.records_in,
+        records_out: progress.records_out,
+        stopped_early,
+        duration_ms: started.elapsed().as_millis() as u64,
+        provenance_path: origins.map(ProvenanceWriter::finish).transpose()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+
+    fn run(steps: Value, records: &[Value]) -> (PipelineResult, Vec<Value>) {
+        let dir = TempDir::new("pipeline");
+        let name = format!("{}", uuid::Uuid::new_v4());
+        let input = dir.join(format!("{name}-in.ndjson"));
+        let output = dir.join(format!("{name}-out.ndjson"));
+        let text: String = records.iter().map(|r| format!("{r}\n")).collect();
+        std::fs::write(&input, text).unwrap();
+        let steps: Vec<PipelineStep> = serde_json::from_value(steps).unwrap();
+        let result =
+            run_pipeline(&input, &steps, &output, false, &CancelFlag::never(), |_| {}).unwrap();
+        let mut reader = records::open(&output).unwrap();
+        let mut out = Vec::new();
+        while let Some(v) = reader.next_value().unwrap() {
+            out.push(v);
+        }
+        (result, out)
+    }
+
+    fn people() -> Vec<Value> {
+        vec![
+            json!({"name": "ada", "age": 36, "meta": {"team": "core"}}),
+            json!({"name": "bob", "age": 17, "meta": {"team": "ui"}}),
+            json!({"name": "cy", "age": 52, "meta": {"team": "core"}}),
+        ]
+    }
+
+    #[test]
+    fn filters_selects_and_renames() {
+        let (result, out) = run(
+            json!([
+                {"kind": "filter", "groups": [[{"key": "age", "op": "gte", "value": 18}]]},
+                {"kind": "select", "fields": ["name", "/meta/team"]},
+                {"kind": "rename", "fields": {"team": "squad"}}
+            ]),
+            &people(),
+        );
+        assert_eq!((result.records_in, result.records_out), (3, 2));
+        assert_eq!(
+            out,
+            vec![
+                json!({"name": "ada", "squad": "core"}),
+                json!({"name": "cy", "squad": "core"})
+            ]
+        );
+    }
+
+    #[test]
+    fn scripts_transform_and_limits_stop_reading() {
+        let (result, out) = run(
+            json!([
+                {"kind": "filter", "expression": "doc.meta.team == \"core\""},
+                {"kind": "script", "source": "doc.age += 1;"},
+                {"kind": "limit", "count": 1}
+            ]),
+            &people(),
+        );
+        assert_eq!(
+            out,
+            vec![json!({"name": "ada", "age": 37, "meta": {"team": "core"}})]
+        );
+        assert!(result.stopped_early);
+        assert_eq!(result.records_in, 1);
+    }
+
+    #[test]
+    fn bad_scripts_fail_before_reading() {
+        let steps: Vec<PipelineStep> =
+            serde_json::from_value(json!([{"kind": "script", "source": "let = ;"}])).unwrap();
+        assert!(matches!(Runner::new(&steps), Err(DocError::Edit(_))));
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+use super::{ids, timefmt};
+
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\{\{\s*(\$?[A-Za-z_][A-Za-z0-9_]*)\s*(?:\|([^}]*))?\}\}").expect("valid regex")
+});
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable {
+    pub name: String,
+    pub default: Option<String>,
+}
+
+pub fn variables_in(body: &str) -> Vec<Variable> {
+    let mut out: Vec<Variable> = Vec::new();
+    for caps in PLACEHOLDER.captures_iter(body) {
+        let name = caps[1].to_string();
+        if name.starts_with('$') {
+            continue;
+        }
+        let default = caps.get(2).map(|m| m.as_str().to_string());
+        match out.iter_mut().find(|v| v.name == name) {
+            Some(existing) => {
+                if existing.default.is_none() {
+                    existing.default = default;
+                }
+            }
+            None => out.push(Variable { name, default }),
+        }
+    }
+    out
+}
+
+pub fn expand(body: &str, vars: &HashMap<String, String>) -> String {
+    let now = timefmt::now_millis();
+    PLACEHOLDER
+        .replace_all(body, |caps: &regex::Captures<'_>| {
+            let name = &caps[1];
+            let value = match vars.get(name) {
+                Some(v) => v.clone(),
+                None => generated(name, now)
+                    .or_else(|| caps.get(2).map(|m| m.as_str().to_string()))
+                    .unwrap_or_default(),
+            };
+            escape_for_json_string(&value)
+        })
+        .into_owned()
+}
+
+fn generated(name: &str, now: u64) -> Option<String> {
+    Some(match name {
+        "$uuid" => uuid::Uuid::new_v4().to_string(),
+        "$uuid_v7" => uuid::Uuid::now_v7().to_string(),
+        "$ulid" => ids::ulid().ok()?,
+        "$nanoid" => ids::nanoid().ok()?,
+        "$date" => timefmt::date_utc(now),
+        "$datetime" | "$now" => timefmt::rfc3339_utc(now),
+        "$timestamp" => (now / 1000).to_string(),
+        "$timestamp_ms" => now.to_string(),
+        _ => return None,
+    })
+}
+
+pub fn escape_for_json_string(value: &str) -> String {
+    let quoted = serde_json::to_string(value).expect("string serialization is infallible");
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn variables_are_listed_once_with_defaults() {
+        let v = variables_in(r#"{"a": "{{name|x}}", "b": "{{ name }}", "c": "{{other}}"}"#);
+        assert_eq!(
+            v,
+            vec![
+                Variable {
+                    name: "name".into(),
+                    default: Some("x".into())
+                },
+                Variable {
+                    name: "other".into(),
+                    default: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn generators_are_not_listed_as_variables() {
+        assert!(variables_in(r#"{"id": "{{$uuid}}", "at": "{{$datetime}}"}"#).is_empty());
+    }
+
+    #[test]
+    fn expand_uses_vars_then_defaults_then_empty() {
+        let out = expand(
+            r#"["{{a}}", "{{b|fallback}}", "{{c}}"]"#,
+            &vars(&[("a", "given")]),
+        );
+        assert_eq!(out, r#"["given", "fallback", ""]"#);
+    }
+
+    #[test]
+    fn expand_escapes_quotes_and_newlines() {
+        let out = expand(r#""{{v}}""#, &vars(&[("v", "say \"hi\"\n")]));
+        assert_eq!(out, r#""say \"hi\"\n""#);
+    }
+
+    #[test]
+    fn generators_produce_fresh_values() {
+        let out = expand(
+            r#"["{{$uuid}}", "{{$uuid}}", "{{$date}}", "{{$ulid}}", "{{$nanoid}}"]"#,
+            &HashMap::new(),
+        );
+        let v: Vec<String> = serde_json::from_str(&out).unwrap();
+        assert_eq!(v[0].len(), 36);
+        assert_ne!(v[0], v[1]);
+        assert_eq!(v[2].len(), 10);
+        assert_eq!(v[3].len(), 26);
+        assert_eq!(v[4].len(), 21);
+    }
+
+    #[test]
+    fn explicit_vars_override_generators() {
+        let out = expand("{{$date}}", &vars(&[("$date", "2000-01-01")]));
+        assert_eq!(out, "2000-01-01");
+    }
+}
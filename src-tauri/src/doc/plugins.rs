@@ -0,0 +1,381 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use super::types::{DocError, DocResult};
+
+const MANIFEST_FILE: &str = "plugin.json";
+const DEFAULT_MODULE: &str = "plugin.wasm";
+pub const MAX_INPUT_BYTES: usize = 64 * 1024 * 1024;
+const MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+const DEFAULT_FUEL: u64 = 2_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryKind {
+    Transform,
+    Validator,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginEntry {
+    pub name: String,
+    pub kind: EntryKind,
+    #[serde(default)]
+    pub export: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    name: String,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    module: Option<String>,
+    #[serde(default)]
+    fuel: Option<u64>,
+    #[serde(default)]
+    entries: Vec<PluginEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub id: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub entries: Vec<PluginEntry>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginIssue {
+    #[serde(default)]
+    pub path: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginOutput {
+    pub kind: EntryKind,
+    pub content: Option<String>,
+    pub issues: Vec<PluginIssue>,
+}
+
+struct Sandbox {
+    limits: StoreLimits,
+}
+
+pub fn plugins_dir(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let base = super::portable::data_dir(app)?;
+    let dir = base.join("plugins");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn read_manifest(dir: &FsPath) -> DocResult<Manifest> {
+    let text = std::fs::read_to_string(dir.join(MANIFEST_FILE))?;
+    serde_json::from_str(&text).map_err(|e| DocError::Parse(format!("{MANIFEST_FILE}: {e}")))
+}
+
+pub fn list(dir: &FsPath) -> Vec<PluginInfo> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut out: Vec<PluginInfo> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| {
+            let id = e.file_name().to_string_lossy().into_owned();
+            match read_manifest(&e.path()) {
+                Ok(m) => PluginInfo {
+                    id,
+                    name: m.name,
+                    version: m.version,
+                    description: m.description,
+                    entries: m.entries,
+                    error: None,
+                },
+                Err(err) => PluginInfo {
+                    name: id.clone(),
+                    id,
+                    version: None,
+                    description: None,
+                    entries: Vec::new(),
+                    error: Some(err.to_string()),
+                },
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| a.id.cmp(&b.id));
+    out
+}
+
+pub fn run(
+    dir: &FsPath,
+    id: &str,
+    content: &str,
+    args: &serde_json::Value,
+) -> DocResult<PluginOutput> {
+    let (plugin_id, entry_name) = id
+        .split_once('/')
+        .ok_or_else(|| DocError::Edit(format!("plugin id must be <plugin>/<entry>: {id}")))?;
+    if plugin_id.is_empty() || plugin_id.contains(['.', '\\']) {
+        return Err(DocError::Edit(format!("invalid plugin id: {plugin_id}")));
+    }
+    let plugin_dir = dir.join(plugin_id);
+    let manifest = read_manifest(&plugin_dir)?;
+    let entry = manifest
+        .entries
+        .iter()
+        .find(|e| e.name == entry_name)
+        .ok_or_else(|| DocError::Edit(format!("unknown plugin entry: {id}")))?;
+    let module_name = manifest.module.as_deref().unwrap_or(DEFAULT_MODULE);
+    if module_name.contains(['/', '\\']) || module_name.starts_with('.') {
+        return Err(DocError::Edit(format!(
+            "invalid module name: {module_name}"
+        )));
+    }
+    let wasm = std::fs::read(plugin_dir.join(module_name))?;
+    let fuel = manifest.fuel.unwrap_or(DEFAULT_FUEL).min(DEFAULT_FUEL);
+    let export = entry.export.as_deref().unwrap_or(&entry.name);
+    let output = execute(&wasm, export, content, args, fuel)?;
+    match entry.kind {
+        EntryKind::Transform => {
+            serde_json::from_str::<serde_json::Value>(&output)
+                .map_err(|e| DocError::Parse(format!("plugin {id} returned invalid JSON: {e}")))?;
+            Ok(PluginOutput {
+                kind: EntryKind::Transform,
+                content: Some(output),
+                issues: Vec::new(),
+            })
+        }
+        EntryKind::Validator => {
+            let issues = serde_json::from_str::<Vec<PluginIssue>>(&output).map_err(|e| {
+                DocError::Parse(format!("plugin {id} returned invalid issues: {e}"))
+            })?;
+            Ok(PluginOutput {
+                kind: EntryKind::Validator,
+                content: None,
+                issues,
+            })
+        }
+    }
+}
+
+fn plugin_err(e: impl std::fmt::Display) -> DocError {
+    DocError::Edit(format!("plugin failed: {e}"))
+}
+
+fn execute(
+    wasm: &[u8],
+    export: &str,
+    content: &str,
+    args: &serde_json::Value,
+    fuel: u64,
+) -> DocResult<String> {
+    if content.len() > MAX_INPUT_BYTES {
+        return Err(DocError::TooLarge {
+            actual: content.len() as u64,
+            limit: MAX_INPUT_BYTES as u64,
+        });
+    }
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, wasm).map_err(plugin_err)?;
+    let mut store = Store::new(
+        &engine,
+        Sandbox {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MAX_MEMORY_BYTES)
+                .instances(1)
+                .build(),
+        },
+    );
+    store.limiter(|s| &mut s.limits);
+    store.set_fuel(fuel).map_err(plugin_err)?;
+
+    let linker = Linker::<Sandbox>::new(&engine);
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(plugin_err)?;
+    let memory = instance
+        .get_memory(&store, "memory")
+        .ok_or_else(|| plugin_err("module does not export memory"))?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&store, "alloc")
+        .map_err(plugin_err)?;
+    let entry = instance
+        .get_typed_func::<(i32, i32, i32, i32), i64>(&store, export)
+        .map_err(plugin_err)?;
+
+    let args_text = args.to_string();
+    let mut write = |bytes: &[u8]| -> DocResult<i32> {
+        let ptr = alloc
+            .call(&mut store, bytes.len() as i32)
+            .map_err(plugin_err)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, bytes)
+            .map_err(plugin_err)?;
+        Ok(ptr)
+    };
+    let content_ptr = write(content.as_bytes())?;
+    let args_ptr = write(args_text.as_bytes())?;
+
+    let packed = entry
+        .call(
+            &mut store,
+            (
+                content_ptr,
+                content.len() as i32,
+                args_ptr,
+                args_text.len() as i32,
+            ),
+        )
+        .map_err(plugin_err)? as u64;
+    let (ptr, len) = ((packed >> 32) as usize, (packed & 0xFFFF_FFFF) as usize);
+    if len > MAX_MEMORY_BYTES {
+        return Err(DocError::TooLarge {
+            actual: len as u64,
+            limit: MAX_MEMORY_BYTES as u64,
+        });
+    }
+    let mut buf = vec![0u8; len];
+    memory.read(&store, ptr, &mut buf).map_err(plugin_err)?;
+    String::from_utf8(buf).map_err(|_| plugin_err("output is not valid UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    const TEST_MODULE: &str = r#"(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 1024))
+  (func (export "alloc") (param $n i32) (result i32)
+    (local $r i32)
+    (local.set $r (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $n)))
+    (local.get $r))
+  (func (export "identity") (param $p i32) (param $l i32) (param $ap i32) (param $al i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $p)) (i64.const 32))
+      (i64.extend_i32_u (local.get $l))))
+  (func (export "echo_args") (param $p i32) (param $l i32) (param $ap i32) (param $al i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ap)) (i64.const 32))
+      (i64.extend_i32_u (local.get $al))))
+  (func (export "spin") (param i32 i32 i32 i32) (result i64)
+    (loop $l (br $l))
+    (i64.const 0))
+  (func (export "huge") (param i32 i32 i32 i32) (result i64)
+    (i64.const 0xFFFFFFFF)))"#;
+
+    fn plugin_root(name: &str, manifest: &str) -> TempDir {
+        let root = TempDir::new(&format!("plugins-{name}"));
+        let dir = root.join("sample");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+        std::fs::write(dir.join(DEFAULT_MODULE), TEST_MODULE).unwrap();
+        root
+    }
+
+    const MANIFEST: &str = r#"{
+  "name": "Sample",
+  "version": "1.0.0",
+  "entries": [
+    { "name": "identity", "kind": "transform" },
+    { "name": "check", "kind": "validator", "export": "echo_args" },
+    { "name": "spin", "kind": "transform" },
+    { "name": "huge", "kind": "transform" }
+  ],
+  "fuel": 100000
+}"#;
+
+    #[test]
+    fn lists_plugins_and_reports_broken_manifests() {
+        let root = plugin_root("list", MANIFEST);
+        std::fs::create_dir_all(root.join("broken")).unwrap();
+        std::fs::write(root.join("broken").join(MANIFEST_FILE), "{").unwrap();
+
+        let plugins = list(&root);
+        assert_eq!(plugins.len(), 2);
+        assert_eq!(plugins[0].id, "broken");
+        assert!(plugins[0].error.is_some());
+        assert_eq!(plugins[1].name, "Sample");
+        assert_eq!(plugins[1].entries.len(), 4);
+    }
+
+    #[test]
+    fn transform_round_trips_content() {
+        let root = plugin_root("transform", MANIFEST);
+        let out = run(
+            &root,
+            "sample/identity",
+            r#"{"a": 1}"#,
+            &serde_json::Value::Null,
+        )
+        .unwrap();
+        assert_eq!(out.kind, EntryKind::Transform);
+        assert_eq!(out.content.as_deref(), Some(r#"{"a": 1}"#));
+        let err = run(
+            &root,
+            "sample/identity",
+            "not json",
+            &serde_json::Value::Null,
+        )
+        .unwrap_err();
+        assert!(matches!(err, DocError::Parse(_)));
+    }
+
+    #[test]
+    fn validator_returns_issues() {
+        let root = plugin_root("validator", MANIFEST);
+        let args = serde_json::json!([{ "path": "/a", "message": "bad" }]);
+        let out = run(&root, "sample/check", "{}", &args).unwrap();
+        assert_eq!(
+            out.issues,
+            vec![PluginIssue {
+                path: Some("/a".into()),
+                message: "bad".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn runaway_plugins_exhaust_fuel() {
+        let root = plugin_root("fuel", MANIFEST);
+        let err = run(&root, "sample/spin", "{}", &serde_json::Value::Null).unwrap_err();
+        assert!(matches!(err, DocError::Edit(_)));
+    }
+
+    #[test]
+    fn rejects_output_lengths_past_the_memory_cap() {
+        let root = plugin_root("huge", MANIFEST);
+        let err = run(&root, "sample/huge", "{}", &serde_json::Value::Null).unwrap_err();
+        assert!(matches!(err, DocError::TooLarge { .. }));
+    }
+
+    #[test]
+    fn rejects_unknown_or_escaping_ids() {
+        let root = plugin_root("ids", MANIFEST);
+        let null = serde_json::Value::Null;
+        assert!(run(&root, "sample", "{}", &null).is_err());
+        assert!(run(&root, "sample/missing", "{}", &null).is_err());
+        assert!(run(&root, "../sample/identity", "{}", &null).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+use parking_lot::{Condvar, Mutex};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Background,
+    Normal,
+    Interactive,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityCounts {
+    pub interactive: u64,
+    pub normal: u64,
+    pub background: u64,
+}
+
+impl PriorityCounts {
+    fn slot(&mut self, priority: Priority) -> &mut u64 {
+        match priority {
+            Priority::Interactive => &mut self.interactive,
+            Priority::Normal => &mut self.normal,
+            Priority::Background => &mut self.background,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStats {
+    pub threads: usize,
+    pub target_threads: usize,
+    pub active: usize,
+    pub queued: PriorityCounts,
+    pub completed: PriorityCounts,
+    pub panicked: u64,
+}
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+struct Queued {
+    priority: Priority,
+    seq: u64,
+    job: Job,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Default)]
+struct State {
+    queue: BinaryHeap<Queued>,
+    seq: u64,
+    threads: usize,
+    target: usize,
+    active: usize,
+    background_active: usize,
+    queued: PriorityCounts,
+    completed: PriorityCounts,
+    panicked: u64,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    ready: Condvar,
+}
+
+pub struct WorkerPool {
+    shared: Arc<Shared>,
+}
+
+pub fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(2, |n| n.get())
+}
+
+fn worker(shared: Arc<Shared>) {
+    loop {
+        let queued = {
+            let mut state = shared.state.lock();
+            let queued = loop {
+                if state.threads > state.target {
+                    state.threads -= 1;
+                    return;
+                }
+                let background_cap = state.target.saturating_sub(1).max(1);
+                let runnable = state.queue.peek().is_some_and(|top| {
+                    top.priority != Priority::Background || state.background_active < background_cap
+                });
+                if runnable {
+                    break state.queue.pop().expect("peeked job");
+                }
+                shared.ready.wait(&mut state);
+            };
+            *state.queued.slot(queued.priority) -= 1;
+            state.active += 1;
+            if queued.priority == Priority::Background {
+                state.background_active += 1;
+            }
+            queued
+        };
+        let priority = queued.priority;
+        let finished = panic::catch_unwind(AssertUnwindSafe(queued.job)).is_ok();
+        let mut state = shared.state.lock();
+        state.active -= 1;
+        if priority == Priority::Background {
+            state.background_active -= 1;
+        }
+        *state.completed.slot(priority) += 1;
+        if !finished {
+            state.panicked += 1;
+        }
+        drop(state);
+        shared.ready.notify_all();
+    }
+}
+
+impl WorkerPool {
+    pub fn new(threads: usize) -> Self {
+        let pool = Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State::default()),
+                ready: Condvar::new(),
+            }),
+        };
+        pool.resize(threads);
+        pool
+    }
+
+    pub fn resize(&self, threads: usize) {
+        let target = if threads == 0 {
+            default_threads()
+        } else {
+            threads
+        };
+        let mut state = self.shared.state.lock();
+        state.target = target;
+        while state.threads < target {
+            let shared = self.shared.clone();
+            let spawned = std::thread::Builder::new()
+                .name(format!("pandia-worker-{}", state.threads + 1))
+                .spawn(move || worker(shared));
+            if let Err(e) = spawned {
+                tracing::warn!("could not start worker thread: {e}");
+                break;
+            }
+            state.threads += 1;
+        }
+        drop(state);
+        self.shared.ready.notify_all();
+    }
+
+    pub fn submit(&self, priority: Priority, job: impl FnOnce() + Send + 'static) {
+        let mut state = self.shared.state.lock();
+        state.seq += 1;
+        let seq = state.seq;
+        *state.queued.slot(priority) += 1;
+        state.queue.push(Queued {
+            priority,
+            seq,
+            job: Box::new(job),
+        });
+        drop(state);
+        self.shared.ready.notify_all();
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let state = self.shared.state.lock();
+        PoolStats {
+            threads: state.threads,
+            target_threads: state.target,
+            active: state.active,
+            queued: state.queued,
+            completed: state.completed,
+            panicked: state.panicked,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn wait_for(pool: &WorkerPool, check: impl Fn(&PoolStats) -> bool) -> PoolStats {
+        let started = Instant::now();
+        loop {
+            let stats = pool.stats();
+            if check(&stats) || started.elapsed() > WAIT {
+                return stats;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn runs_higher_priorities_first() {
+        let pool = WorkerPool::new(1);
+        let (release, gate) = mpsc::channel::<()>();
+        pool.submit(Priority::Normal, move || {
+            let _ = gate.recv();
+        });
+        wait_for(&pool, |s| s.active == 1);
+        let order = Arc::new(Mutex::new(Vec::new()));
+        for priority in [
+            Priority::Background,
+            Priority::Normal,
+            Priority::Interactive,
+        ] {
+            let order = order.clone();
+            pool.submit(priority, move || order.lock().push(priority));
+        }
+        release.send(()).unwrap();
+        let stats = wait_for(&pool, |s| s.completed.background == 1);
+        assert_eq!(stats.completed.normal, 2);
+        assert_eq!(
+            *order.lock(),
+            vec![
+                Priority::Interactive,
+                Priority::Normal,
+                Priority::Background
+            ]
+        );
+    }
+
+    #[test]
+    fn background_work_leaves_a_worker_for_interactive_tasks() {
+        let pool = WorkerPool::new(2);
+        let (release, gate) = mpsc::channel::<()>();
+        let gate = Arc::new(Mutex::new(gate));
+        for _ in 0..2 {
+            let gate = gate.clone();
+            pool.submit(Priority::Background, move || {
+                let _ = gate.lock().recv();
+            });
+        }
+        let (done, finished) = mpsc::channel();
+        pool.submit(Priority::Interactive, move || done.send(()).unwrap());
+        assert!(finished.recv_timeout(WAIT).is_ok());
+        assert_eq!(pool.stats().queued.background, 1);
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        assert_eq!(
+            wait_for(&pool, |s| s.completed.background == 2)
+                .completed
+                .background,
+            2
+        );
+    }
+
+    #[test]
+    fn resizes_and_survives_panicking_jobs() {
+        let pool = WorkerPool::new(3);
+        assert_eq!(wait_for(&pool, |s| s.threads == 3).threads, 3);
+        pool.resize(1);
+        assert_eq!(wait_for(&pool, |s| s.threads == 1).threads, 1);
+        pool.submit(Priority::Normal, || panic!("boom"));
+        let (done, finished) = mpsc::channel();
+        pool.submit(Priority::Normal, move || done.send(()).unwrap());
+        assert!(finished.recv_timeout(WAIT).is_ok());
+        assert_eq!(wait_for(&pool, |s| s.panicked == 1).panicked, 1);
+    }
+}
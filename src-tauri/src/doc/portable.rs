@@ -0,0 +1,60 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+const PORTABLE_FLAG: &str = "portable";
+const PORTABLE_DATA_DIR: &str = "data";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortableInfo {
+    pub portable: bool,
+    pub data_dir: String,
+}
+
+pub fn portable_root_for(exe: &FsPath) -> Option<PathBuf> {
+    let dir = exe.parent()?;
+    dir.join(PORTABLE_FLAG)
+        .is_file()
+        .then(|| dir.join(PORTABLE_DATA_DIR))
+}
+
+pub fn portable_root() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    portable_root_for(&exe)
+}
+
+pub fn data_dir(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let base = match portable_root() {
+        Some(dir) => dir,
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| std::io::Error::other(e.to_string()))?,
+    };
+    std::fs::create_dir_all(&base)?;
+    Ok(base)
+}
+
+pub fn info(app: &AppHandle) -> std::io::Result<PortableInfo> {
+    Ok(PortableInfo {
+        portable: portable_root().is_some(),
+        data_dir: data_dir(app)?.to_string_lossy().into_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn flag_file_next_to_exe_enables_portable_mode() {
+        let dir = TempDir::new("portable");
+        let exe = dir.join("Pandia.exe");
+        assert_eq!(portable_root_for(&exe), None);
+        std::fs::write(dir.join(PORTABLE_FLAG), "").unwrap();
+        assert_eq!(portable_root_for(&exe), Some(dir.join(PORTABLE_DATA_DIR)));
+    }
+}
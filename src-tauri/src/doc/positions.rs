@@ -0,0 +1,446 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+const MAX_DEPTH: usize = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextPosition {
+    pub line: u32,
+    pub column: u32,
+    pub offset: u32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cursor {
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeSpan {
+    pub path: Path,
+    pub pointer: String,
+    pub start: TextPosition,
+    pub end: TextPosition,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_start: Option<TextPosition>,
+    pub on_key: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateKey {
+    pub key: String,
+    pub pointer: String,
+    pub first: TextPosition,
+    pub duplicate: TextPosition,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub path: Path,
+    pub key_start: Option<usize>,
+    pub start: usize,
+    pub end: usize,
+}
+
+pub struct LineIndex<'a> {
+    text: &'a str,
+    starts: Cow<'a, [usize]>,
+}
+
+pub struct PositionIndex<'a> {
+    lines: LineIndex<'a>,
+    nodes: Cow<'a, [Node]>,
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    nodes: Vec<Node>,
+}
+
+impl Scanner<'_> {
+    fn err(&self, msg: &str) -> DocError {
+        DocError::Parse(format!("{msg} at byte {}", self.pos))
+    }
+
+    fn skip_ws(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> DocResult<()> {
+        self.skip_ws();
+        if self.bytes.get(self.pos) != Some(&byte) {
+            return Err(self.err(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn string(&mut self) -> DocResult<String> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut escaped = false;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            self.pos += 1;
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                return serde_json::from_slice(&self.bytes[start..self.pos])
+                    .map_err(|e| DocError::Parse(format!("{e} at byte {start}")));
+            }
+        }
+        Err(self.err("unterminated string"))
+    }
+
+    fn value(&mut self, path: &mut Path, key_start: Option<usize>) -> DocResult<()> {
+        if path.0.len() > MAX_DEPTH {
+            return Err(self.err("nesting too deep"));
+        }
+        self.skip_ws();
+        let start = self.pos;
+        let slot = self.nodes.len();
+        self.nodes.push(Node {
+            path: path.clone(),
+            key_start,
+            start,
+            end: start,
+        });
+        match self.bytes.get(self.pos) {
+            Some(b'{') => {
+                self.pos += 1;
+                self.skip_ws();
+                if self.bytes.get(self.pos) == Some(&b'}') {
+                    self.pos += 1;
+                } else {
+                    loop {
+                        self.skip_ws();
+                        let key_at = self.pos;
+                        if self.bytes.get(self.pos) != Some(&b'"') {
+                            return Err(self.err("expected an object key"));
+                        }
+                        let key = self.string()?;
+                        self.expect(b':')?;
+                        path.push(PathSegment::Key(key));
+                        self.value(path, Some(key_at))?;
+                        path.0.pop();
+                        self.skip_ws();
+                        match self.bytes.get(self.pos) {
+                            Some(b',') => self.pos += 1,
+                            Some(b'}') => {
+                                self.pos += 1;
+                                break;
+                            }
+                            _ => return Err(self.err("expected ',' or '}'")),
+                        }
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                self.skip_ws();
+                if self.bytes.get(self.pos) == Some(&b']') {
+                    self.pos += 1;
+                } else {
+                    let mut index = 0u32;
+                    loop {
+                        path.push(PathSegment::Index(index));
+                        self.value(path, None)?;
+                        path.0.pop();
+                        index += 1;
+                        self.skip_ws();
+                        match self.bytes.get(self.pos) {
+                            Some(b',') => self.pos += 1,
+                            Some(b']') => {
+                                self.pos += 1;
+                                break;
+                            }
+                            _ => return Err(self.err("expected ',' or ']'")),
+                        }
+                    }
+                }
+            }
+            Some(b'"') => {
+                self.string()?;
+            }
+            Some(_) => {
+                while self
+                    .bytes
+                    .get(self.pos)
+                    .is_some_and(|b| !b",]} \t\r\n".contains(b))
+                {
+                    self.pos += 1;
+                }
+                serde_json::from_slice::<serde_json::Value>(&self.bytes[start..self.pos])
+                    .map_err(|_| DocError::Parse(format!("invalid value at byte {start}")))?;
+            }
+            None => return Err(self.err("unexpected end of input")),
+        }
+        self.nodes[slot].end = self.pos;
+        Ok(())
+    }
+}
+
+pub fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+pub fn scan_value(
+    text: &str,
+    start: usize,
+    mut path: Path,
+    key_start: Option<usize>,
+) -> DocResult<(Vec<Node>, usize)> {
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        pos: start,
+        nodes: Vec::new(),
+    };
+    scanner.value(&mut path, key_start)?;
+    Ok((scanner.nodes, scanner.pos))
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            starts: Cow::Owned(line_starts(text)),
+        }
+    }
+
+    pub fn from_starts(text: &'a str, starts: &'a [usize]) -> Self {
+        Self {
+            text,
+            starts: Cow::Borrowed(starts),
+        }
+    }
+
+    pub fn position(&self, offset: usize) -> TextPosition {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.starts.partition_point(|&s| s <= offset) - 1;
+        let start = self.starts[line];
+        let column = self.text[start..offset].chars().count();
+        TextPosition {
+            line: line as u32 + 1,
+            column: column as u32 + 1,
+            offset: offset as u32,
+        }
+    }
+
+    pub fn offset(&self, line: u32, column: u32) -> DocResult<usize> {
+        let start = *self
+            .starts
+            .get((line.max(1) - 1) as usize)
+            .ok_or_else(|| DocError::Edit(format!("line {line} is past the end")))?;
+        let rest = &self.text[start..];
+        let line_text = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        let offset = line_text
+            .char_indices()
+            .nth((column.max(1) - 1) as usize)
+            .map_or(line_text.len(), |(i, _)| i);
+        Ok(start + offset)
+    }
+}
+
+impl<'a> PositionIndex<'a> {
+    pub fn build(text: &'a str) -> DocResult<Self> {
+        let (nodes, end) = scan_value(text, 0, Path::root(), None)?;
+        let rest = text[end..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+        if !rest.is_empty() {
+            return Err(DocError::Parse(format!(
+                "trailing characters at byte {}",
+                text.len() - rest.len()
+            )));
+        }
+        Ok(Self {
+            lines: LineIndex::new(text),
+            nodes: Cow::Owned(nodes),
+        })
+    }
+
+    pub fn from_parts(lines: LineIndex<'a>, nodes: &'a [Node]) -> Self {
+        Self {
+            lines,
+            nodes: Cow::Borrowed(nodes),
+        }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.lines.text
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn position(&self, offset: usize) -> TextPosition {
+        self.lines.position(offset)
+    }
+
+    pub fn offset(&self, line: u32, column: u32) -> DocResult<usize> {
+        self.lines.offset(line, column)
+    }
+
+    pub fn children(&self) -> Vec<Vec<usize>> {
+        let bytes = self.lines.text.as_bytes();
+        let mut children = vec![Vec::new(); self.nodes.len()];
+        let mut stack: Vec<usize> = Vec::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            while stack
+                .last()
+                .is_some_and(|&p| self.nodes[p].end <= node.start)
+            {
+                stack.pop();
+            }
+            if let Some(&parent) = stack.last() {
+                children[parent].push(i);
+            }
+            if matches!(bytes[node.start], b'{' | b'[') {
+                stack.push(i);
+            }
+        }
+        children
+    }
+
+    pub fn duplicate_keys(&self) -> Vec<DuplicateKey> {
+        let mut out = Vec::new();
+        for members in self.children() {
+            let mut seen: HashMap<&str, usize> = HashMap::new();
+            for child in members {
+                let node = &self.nodes[child];
+                let (Some(PathSegment::Key(key)), Some(at)) = (node.path.0.last(), node.key_start)
+                else {
+                    continue;
+                };
+                match seen.get(key.as_str()) {
+                    Some(&first) => out.push(DuplicateKey {
+                        key: key.clone(),
+                        pointer: node.path.to_pointer(),
+                        first: self.position(first),
+                        duplicate: self.position(at),
+                    }),
+                    None => {
+                        seen.insert(key.as_str(), at);
+                    }
+                }
+            }
+        }
+        out.sort_by_key(|d| d.duplicate.offset);
+        out
+    }
+
+    fn span(&self, node: &Node, on_key: bool) -> NodeSpan {
+        NodeSpan {
+            path: node.path.clone(),
+            pointer: node.path.to_pointer(),
+            start: self.position(node.start),
+            end: self.position(node.end),
+            key_start: node.key_start.map(|k| self.position(k)),
+            on_key,
+        }
+    }
+
+    pub fn path_at(&self, line: u32, column: u32) -> DocResult<NodeSpan> {
+        let offset = self.offset(line, column)?;
+        let hit = self
+            .nodes
+            .iter()
+            .rev()
+            .find(|n| n.key_start.unwrap_or(n.start) <= offset && offset < n.end)
+            .unwrap_or(&self.nodes[0]);
+        Ok(self.span(hit, offset < hit.start))
+    }
+
+    pub fn span_of(&self, path: &Path) -> DocResult<NodeSpan> {
+        self.nodes
+            .iter()
+            .find(|n| &n.path == path)
+            .map(|n| self.span(n, false))
+            .ok_or_else(|| DocError::Edit(format!("{path} is not in the text")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "{\n  \"name\": \"héllo\",\n  \"tags\": [1, {\"x\": true}]\n}\n";
+
+    fn key(k: &str) -> PathSegment {
+        PathSegment::Key(k.into())
+    }
+
+    #[test]
+    fn maps_positions_to_deepest_node() {
+        let index = PositionIndex::build(TEXT).unwrap();
+        let hit = index.path_at(2, 14).unwrap();
+        assert_eq!(hit.pointer, "/name");
+        assert!(!hit.on_key);
+        let on_key = index.path_at(2, 4).unwrap();
+        assert_eq!((on_key.pointer.as_str(), on_key.on_key), ("/name", true));
+        assert_eq!(index.path_at(3, 20).unwrap().pointer, "/tags/1/x");
+        assert_eq!(index.path_at(3, 14).unwrap().pointer, "/tags");
+        assert_eq!(index.path_at(4, 1).unwrap().pointer, "");
+    }
+
+    #[test]
+    fn maps_paths_back_to_spans() {
+        let index = PositionIndex::build(TEXT).unwrap();
+        let span = index
+            .span_of(&Path(vec![key("tags"), PathSegment::Index(1)]))
+            .unwrap();
+        assert_eq!((span.start.line, span.start.column), (3, 15));
+        assert_eq!((span.end.line, span.end.column), (3, 26));
+        let name = index.span_of(&Path(vec![key("name")])).unwrap();
+        assert_eq!(name.key_start.map(|p| p.column), Some(3));
+        assert_eq!(name.end.column, 18);
+        assert!(index.span_of(&Path(vec![key("nope")])).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert!(PositionIndex::build("{\"a\": }").is_err());
+        assert!(PositionIndex::build("[1, 2] x").is_err());
+        assert!(PositionIndex::build("[tru]").is_err());
+    }
+
+    #[test]
+    fn finds_duplicate_keys_per_object() {
+        let text = "{\"a\": {\"x\": 1},\n \"b\": [{\"x\": 1, \"x\": 2}],\n \"a\": {\"x\": 3}}";
+        let dups = PositionIndex::build(text).unwrap().duplicate_keys();
+        let found: Vec<(&str, &str, u32, u32)> = dups
+            .iter()
+            .map(|d| {
+                (
+                    d.key.as_str(),
+                    d.pointer.as_str(),
+                    d.first.line,
+                    d.duplicate.line,
+                )
+            })
+            .collect();
+        assert_eq!(found, vec![("x", "/b/0/x", 2, 2), ("a", "/a", 1, 3)]);
+        assert_eq!((dups[0].first.column, dups[0].duplicate.column), (9, 17));
+    }
+}
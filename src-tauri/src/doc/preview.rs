@@ -0,0 +1,190 @@
+use std::io::Read;
+use std::path::Path as FsPath;
+
+use serde_json::Value;
+
+use super::highlight::{tokenize_line, TokenKind};
+use super::types::DocResult;
+
+const READ_LIMIT: u64 = 512 * 1024;
+const MAX_LINES: usize = 40;
+const MAX_COLUMNS: usize = 100;
+const LINE_HEIGHT: usize = 18;
+const CHAR_WIDTH: f32 = 7.8;
+const PADDING: usize = 16;
+
+fn color(kind: TokenKind) -> &'static str {
+    match kind {
+        TokenKind::Key => "#9cdcfe",
+        TokenKind::String => "#ce9178",
+        TokenKind::Number => "#b5cea8",
+        TokenKind::Bool | TokenKind::Null => "#569cd6",
+        TokenKind::Punctuation => "#d4d4d4",
+        TokenKind::Comment => "#6a9955",
+        TokenKind::Error => "#f44747",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn summarize(value: &Value, size: u64) -> String {
+    let shape = match value {
+        Value::Object(map) => format!("object · {} keys", map.len()),
+        Value::Array(items) => format!("array · {} items", items.len()),
+        Value::String(_) => "string".into(),
+        Value::Number(_) => "number".into(),
+        Value::Bool(_) => "boolean".into(),
+        Value::Null => "null".into(),
+    };
+    format!("{shape} · {}", human_size(size))
+}
+
+fn human_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1 << 20 => format!("{:.1} MB", b as f64 / (1 << 20) as f64),
+        b if b >= 1 << 10 => format!("{:.1} KB", b as f64 / (1 << 10) as f64),
+        b => format!("{b} B"),
+    }
+}
+
+fn snippet(text: &str, size: u64) -> (String, String) {
+    let complete = size <= READ_LIMIT;
+    match serde_json::from_str::<Value>(text) {
+        Ok(value) if complete => {
+            let pretty = serde_json::to_string_pretty(&value).unwrap_or_else(|_| text.into());
+            (summarize(&value, size), pretty)
+        }
+        _ => {
+            let label = if complete {
+                format!("invalid JSON · {}", human_size(size))
+            } else {
+                format!(
+                    "large file · {} (first {})",
+                    human_size(size),
+                    human_size(READ_LIMIT)
+                )
+            };
+            (label, text.to_string())
+        }
+    }
+}
+
+fn line_svg(line: &str, y: usize) -> String {
+    let shown: String = line.chars().take(MAX_COLUMNS).collect();
+    let mut out = format!(r#"<text x="{PADDING}" y="{y}" xml:space="preserve">"#);
+    let mut column = 0u32;
+    let chars: Vec<char> = shown.chars().collect();
+    for token in tokenize_line(&shown) {
+        if token.start > column {
+            let gap: String = chars[column as usize..token.start as usize]
+                .iter()
+                .collect();
+            out.push_str(&escape(&gap));
+        }
+        let text: String = chars[token.start as usize..token.end as usize]
+            .iter()
+            .collect();
+        out.push_str(&format!(
+            r#"<tspan fill="{}">{}</tspan>"#,
+            color(token.kind),
+            escape(&text)
+        ));
+        column = token.end;
+    }
+    out.push_str("</text>");
+    out
+}
+
+pub fn render_text(text: &str, size: u64, name: &str) -> String {
+    let (summary, body) = snippet(text, size);
+    let lines: Vec<&str> = body.lines().take(MAX_LINES).collect();
+    let widest = lines
+        .iter()
+        .map(|l| l.chars().count().min(MAX_COLUMNS))
+        .max()
+        .unwrap_or(0)
+        .max(summary.chars().count() + name.chars().count() + 3);
+    let width = (widest as f32 * CHAR_WIDTH) as usize + PADDING * 2;
+    let height = (lines.len() + 2) * LINE_HEIGHT + PADDING * 2;
+    let mut svg = format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            r##"<rect width="100%" height="100%" fill="#1e1e1e"/>"##,
+            r#"<g font-family="Menlo, Consolas, monospace" font-size="13">"#,
+            r##"<text x="{p}" y="{y}" fill="#808080">{title} · {summary}</text>"##,
+        ),
+        w = width,
+        h = height,
+        p = PADDING,
+        y = PADDING + LINE_HEIGHT - 4,
+        title = escape(name),
+        summary = escape(&summary),
+    );
+    for (i, line) in lines.iter().enumerate() {
+        svg.push_str(&line_svg(line, PADDING + (i + 3) * LINE_HEIGHT - 4));
+    }
+    svg.push_str("</g></svg>");
+    svg
+}
+
+pub fn render_file(path: &FsPath) -> DocResult<String> {
+    let size = std::fs::metadata(path)?.len();
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)?
+        .take(READ_LIMIT)
+        .read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(render_text(&text, size, &name))
+}
+
+pub fn run_cli(args: &[String]) -> Result<(), String> {
+    let mut input = None;
+    let mut output = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--preview" => input = iter.next(),
+            "--output" | "-o" => output = iter.next(),
+            _ => {}
+        }
+    }
+    let input = input.ok_or("usage: pandia --preview <path> [--output <file.svg>]")?;
+    let svg = render_file(FsPath::new(input)).map_err(|e| e.to_string())?;
+    match output.filter(|o| o.as_str() != "-") {
+        Some(out) => std::fs::write(out, svg).map_err(|e| e.to_string()),
+        None => {
+            print!("{svg}");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_highlighted_summary_svg() {
+        let svg = render_text(r#"{"a":[1,true],"b":"<x>"}"#, 24, "a&b.json");
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("a&amp;b.json · object · 2 keys · 24 B"));
+        assert!(svg.contains(r##"<tspan fill="#9cdcfe">"a"</tspan>"##));
+        assert!(svg.contains("&quot;&lt;x&gt;&quot;"));
+    }
+
+    #[test]
+    fn falls_back_to_raw_text_for_invalid_input() {
+        let svg = render_text("{ broken", 8, "x.json");
+        assert!(svg.contains("invalid JSON · 8 B"));
+        assert!(svg.contains(r##"<tspan fill="#f44747">broken</tspan>"##));
+    }
+}
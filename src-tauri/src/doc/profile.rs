@@ -0,0 +1,239 @@
+use std::path::Path as FsPath;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::settings::{self, Settings, SettingsStore};
+use super::snippets::{self, Snippet};
+use super::templates;
+use super::timefmt;
+use super::types::{DocError, DocResult};
+
+const PROFILE_KIND: &str = "pandia-profile";
+const PROFILE_VERSION: u32 = 1;
+const KEYMAP_FILE: &str = "keymap.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileTemplate {
+    pub file_name: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    #[serde(rename = "$kind")]
+    pub kind: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub settings: Value,
+    #[serde(default)]
+    pub preferences: Option<Value>,
+    #[serde(default)]
+    pub keymap: Option<Value>,
+    #[serde(default)]
+    pub templates: Vec<ProfileTemplate>,
+    #[serde(default)]
+    pub snippets: Vec<Snippet>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSummary {
+    pub path: String,
+    pub templates: u32,
+    pub snippets: u32,
+    pub keymap: bool,
+    pub preferences: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedProfile {
+    pub summary: ProfileSummary,
+    pub settings: Settings,
+    pub preferences: Option<Value>,
+}
+
+fn summary(path: &FsPath, profile: &Profile) -> ProfileSummary {
+    ProfileSummary {
+        path: path.to_string_lossy().into_owned(),
+        templates: profile.templates.len() as u32,
+        snippets: profile.snippets.len() as u32,
+        keymap: profile.keymap.is_some(),
+        preferences: profile.preferences.is_some(),
+    }
+}
+
+fn read_keymap(data_dir: &FsPath) -> DocResult<Option<Value>> {
+    match std::fs::read_to_string(data_dir.join(KEYMAP_FILE)) {
+        Ok(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| DocError::Parse(format!("{KEYMAP_FILE}: {e}"))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn collect(
+    data_dir: &FsPath,
+    settings: &SettingsStore,
+    templates_dir: &FsPath,
+    snippets_path: &FsPath,
+    preferences: Option<Value>,
+) -> DocResult<Profile> {
+    let settings =
+        serde_json::to_value(settings.get()?).map_err(|e| DocError::Export(e.to_string()))?;
+    Ok(Profile {
+        kind: PROFILE_KIND.into(),
+        version: PROFILE_VERSION,
+        exported_at: timefmt::rfc3339_utc(timefmt::now_millis()),
+        settings,
+        preferences,
+        keymap: read_keymap(data_dir)?,
+        templates: templates::user_templates(templates_dir)
+            .into_iter()
+            .map(|t| ProfileTemplate {
+                file_name: t.info.file_name,
+                body: t.body,
+            })
+            .collect(),
+        snippets: snippets::load(snippets_path)?,
+    })
+}
+
+pub fn write(path: &FsPath, profile: &Profile) -> DocResult<ProfileSummary> {
+    let json =
+        serde_json::to_string_pretty(profile).map_err(|e| DocError::Export(e.to_string()))?;
+    std::fs::write(path, json)?;
+    Ok(summary(path, profile))
+}
+
+pub fn read(path: &FsPath) -> DocResult<Profile> {
+    let text = std::fs::read_to_string(path)?;
+    let profile: Profile =
+        serde_json::from_str(&text).map_err(|e| DocError::Parse(format!("profile: {e}")))?;
+    if profile.kind != PROFILE_KIND {
+        return Err(DocError::Parse("not a Pandia profile archive".into()));
+    }
+    if profile.version != PROFILE_VERSION {
+        return Err(DocError::Parse(format!(
+            "unsupported profile version {}",
+            profile.version
+        )));
+    }
+    Ok(profile)
+}
+
+fn safe_template_name(name: &str) -> Option<&str> {
+    let file = FsPath::new(name).file_name()?.to_str()?;
+    (file == name && !file.starts_with('.')).then_some(file)
+}
+
+pub fn apply(
+    path: &FsPath,
+    profile: Profile,
+    data_dir: &FsPath,
+    settings: &SettingsStore,
+    templates_dir: &FsPath,
+    snippets_path: &FsPath,
+) -> DocResult<ImportedProfile> {
+    let summary = summary(path, &profile);
+    let migrated = settings::migrate(profile.settings)?;
+    settings::validate(&migrated)?;
+    for t in &profile.templates {
+        if safe_template_name(&t.file_name).is_none() {
+            return Err(DocError::Parse(format!(
+                "template has an unsafe file name: {}",
+                t.file_name
+            )));
+        }
+    }
+    let applied = settings.update(&migrated)?;
+    if let Some(keymap) = &profile.keymap {
+        let json =
+            serde_json::to_string_pretty(keymap).map_err(|e| DocError::Export(e.to_string()))?;
+        std::fs::write(data_dir.join(KEYMAP_FILE), json)?;
+    }
+    for t in &profile.templates {
+        std::fs::write(templates_dir.join(&t.file_name), &t.body)?;
+    }
+    snippets::import(snippets_path, profile.snippets)?;
+    Ok(ImportedProfile {
+        summary,
+        settings: applied,
+        preferences: profile.preferences,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+
+    #[test]
+    fn round_trips_settings_templates_snippets_and_keymap() {
+        let root = TempDir::new("profile");
+        let (src, dst) = (root.join("src"), root.join("dst"));
+        for dir in [&src, &dst] {
+            std::fs::create_dir_all(dir.join("templates")).unwrap();
+        }
+        std::fs::write(src.join("templates/api.json"), r#"{"a": "{{x}}"}"#).unwrap();
+        std::fs::write(src.join(KEYMAP_FILE), r#"{"save": "Mod+S"}"#).unwrap();
+        std::fs::write(
+            src.join("snippets.json"),
+            r#"[{"id": "s1", "name": "pt", "body": "{\"x\": 1}"}]"#,
+        )
+        .unwrap();
+        let src_settings = SettingsStore::new(src.clone());
+        src_settings.update(&json!({ "indent": 4 })).unwrap();
+
+        let profile = collect(
+            &src,
+            &src_settings,
+            &src.join("templates"),
+            &src.join("snippets.json"),
+            Some(json!({ "$kind": "pandia-settings" })),
+        )
+        .unwrap();
+        let archive = root.join("profile.json");
+        let written = write(&archive, &profile).unwrap();
+        assert_eq!((written.templates, written.snippets), (1, 1));
+
+        let dst_settings = SettingsStore::new(dst.clone());
+        let imported = apply(
+            &archive,
+            read(&archive).unwrap(),
+            &dst,
+            &dst_settings,
+            &dst.join("templates"),
+            &dst.join("snippets.json"),
+        )
+        .unwrap();
+        assert_eq!(imported.settings.indent, 4);
+        assert!(imported.preferences.is_some());
+        assert!(dst.join("templates/api.json").exists());
+        assert!(dst.join(KEYMAP_FILE).exists());
+        assert_eq!(
+            snippets::load(&dst.join("snippets.json")).unwrap()[0].id,
+            "s1"
+        );
+    }
+
+    #[test]
+    fn rejects_foreign_archives_and_unsafe_template_names() {
+        assert_eq!(safe_template_name("a.json"), Some("a.json"));
+        assert_eq!(safe_template_name("../a.json"), None);
+        assert_eq!(safe_template_name(".hidden"), None);
+        let dir = TempDir::new("profile-bad");
+        let path = dir.join("profile.json");
+        std::fs::write(
+            &path,
+            r#"{"$kind": "other", "version": 1, "exportedAt": "", "settings": {}}"#,
+        )
+        .unwrap();
+        assert!(read(&path).is_err());
+    }
+}
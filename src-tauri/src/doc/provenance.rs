@@ -0,0 +1,92 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::Serialize;
+
+use super::types::DocResult;
+
+const SIDECAR_SUFFIX: &str = ".provenance.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Origin {
+    pub pointer: String,
+    pub file: String,
+    pub layer: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    file: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u64>,
+}
+
+pub fn sidecar_path(output: &FsPath) -> PathBuf {
+    let mut name = output.file_name().unwrap_or_default().to_os_string();
+    name.push(SIDECAR_SUFFIX);
+    output.with_file_name(name)
+}
+
+pub struct ProvenanceWriter {
+    path: PathBuf,
+    out: BufWriter<File>,
+    count: u64,
+}
+
+impl ProvenanceWriter {
+    pub fn create(output: &FsPath) -> DocResult<Self> {
+        let path = sidecar_path(output);
+        let out = BufWriter::new(File::create(&path)?);
+        Ok(Self {
+            path,
+            out,
+            count: 0,
+        })
+    }
+
+    pub fn record(&mut self, pointer: &str, file: &str, line: Option<u64>) -> DocResult<()> {
+        let sep: &[u8] = if self.count == 0 { b"{\n  " } else { b",\n  " };
+        self.out.write_all(sep)?;
+        serde_json::to_writer(&mut self.out, pointer).map_err(std::io::Error::from)?;
+        self.out.write_all(b": ")?;
+        serde_json::to_writer(&mut self.out, &Entry { file, line })
+            .map_err(std::io::Error::from)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> DocResult<String> {
+        let tail: &[u8] = if self.count == 0 { b"{}\n" } else { b"\n}\n" };
+        self.out.write_all(tail)?;
+        self.out.flush()?;
+        Ok(self.path.to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn writes_pointer_keyed_sidecar() {
+        let dir = TempDir::new("provenance");
+        let output = dir.join("out.json");
+        let mut writer = ProvenanceWriter::create(&output).unwrap();
+        writer.record("/0", "a.ndjson", Some(3)).unwrap();
+        writer.record("/1", "b.json", None).unwrap();
+        let path = writer.finish().unwrap();
+        assert!(path.ends_with("out.json.provenance.json"));
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            written,
+            json!({"/0": {"file": "a.ndjson", "line": 3}, "/1": {"file": "b.json"}})
+        );
+    }
+}
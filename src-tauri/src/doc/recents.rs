@@ -0,0 +1,225 @@
+use std::path::Path as FsPath;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use super::timefmt;
+
+pub const STORE_FILE: &str = "pandia-recents.json";
+pub const STORE_KEY: &str = "recents";
+pub const CHANGED_EVENT: &str = "recents-changed";
+pub const MENU_ENTRIES: usize = 12;
+const MAX_ENTRIES: usize = 50;
+
+static UPDATE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentEntry {
+    pub path: String,
+    pub name: String,
+    pub opened_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+pub fn sanitize(raw: Option<Value>) -> Vec<RecentEntry> {
+    let Some(Value::Array(items)) = raw else {
+        return Vec::new();
+    };
+    items
+        .into_iter()
+        .filter_map(|item| serde_json::from_value(item).ok())
+        .collect()
+}
+
+pub fn add(
+    list: Vec<RecentEntry>,
+    path: &str,
+    name: Option<String>,
+    size: Option<u64>,
+    opened_at: String,
+) -> Vec<RecentEntry> {
+    let pinned = list.iter().any(|e| e.path == path && e.pinned);
+    let name = name.unwrap_or_else(|| file_name(path));
+    let mut next = vec![RecentEntry {
+        path: path.to_string(),
+        name,
+        opened_at,
+        size,
+        pinned,
+    }];
+    next.extend(list.into_iter().filter(|e| e.path != path));
+    let cap = MAX_ENTRIES.max(next.iter().filter(|e| e.pinned).count());
+    next.truncate(cap);
+    next
+}
+
+pub fn rename(list: Vec<RecentEntry>, from: &str, to: &str) -> Vec<RecentEntry> {
+    list.into_iter()
+        .filter(|e| e.path != to)
+        .map(|mut e| {
+            if e.path == from {
+                if e.name == file_name(from) {
+                    e.name = file_name(to);
+                }
+                e.path = to.to_string();
+            }
+            e
+        })
+        .collect()
+}
+
+pub fn set_pinned(list: Vec<RecentEntry>, path: &str, pinned: bool) -> Vec<RecentEntry> {
+    list.into_iter()
+        .map(|mut e| {
+            if e.path == path {
+                e.pinned = pinned;
+            }
+            e
+        })
+        .collect()
+}
+
+fn file_name(path: &str) -> String {
+    FsPath::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+pub fn deleted(path: &str) -> bool {
+    let path = FsPath::new(path);
+    !path.exists() && path.parent().is_some_and(FsPath::is_dir)
+}
+
+pub fn prune_missing(list: Vec<RecentEntry>, exists: impl Fn(&str) -> bool) -> Vec<RecentEntry> {
+    list.into_iter()
+        .filter(|e| e.pinned || exists(&e.path))
+        .collect()
+}
+
+fn store_err(e: tauri_plugin_store::Error) -> std::io::Error {
+    std::io::Error::other(e.to_string())
+}
+
+pub fn load(app: &AppHandle) -> std::io::Result<Vec<RecentEntry>> {
+    let store = app.store(STORE_FILE).map_err(store_err)?;
+    Ok(sanitize(store.get(STORE_KEY)))
+}
+
+pub fn save(app: &AppHandle, list: &[RecentEntry]) -> std::io::Result<()> {
+    let store = app.store(STORE_FILE).map_err(store_err)?;
+    let value = serde_json::to_value(list).map_err(|e| std::io::Error::other(e.to_string()))?;
+    store.set(STORE_KEY, value);
+    store.save().map_err(store_err)?;
+    let _ = app.emit(CHANGED_EVENT, list);
+    Ok(())
+}
+
+pub fn update(
+    app: &AppHandle,
+    f: impl FnOnce(Vec<RecentEntry>) -> Vec<RecentEntry>,
+) -> std::io::Result<Vec<RecentEntry>> {
+    let _guard = UPDATE_LOCK.lock();
+    let before = load(app)?;
+    let after = f(before.clone());
+    if after != before {
+        save(app, &after)?;
+    }
+    Ok(after)
+}
+
+pub fn now() -> String {
+    timefmt::rfc3339_utc(timefmt::now_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(path: &str, pinned: bool) -> RecentEntry {
+        RecentEntry {
+            path: path.into(),
+            name: path.into(),
+            opened_at: "2026-01-01T00:00:00Z".into(),
+            size: None,
+            pinned,
+        }
+    }
+
+    #[test]
+    fn add_moves_to_front_and_keeps_pin() {
+        let list = vec![entry("/a.json", false), entry("/b.json", true)];
+        let next = add(list, "/b.json", None, Some(10), "now".into());
+        let paths: Vec<&str> = next.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["/b.json", "/a.json"]);
+        assert!(next[0].pinned);
+        assert_eq!(next[0].name, "b.json");
+        assert_eq!(next[0].size, Some(10));
+    }
+
+    #[test]
+    fn add_caps_unpinned_entries() {
+        let list: Vec<RecentEntry> = (0..MAX_ENTRIES)
+            .map(|i| entry(&format!("/{i}.json"), false))
+            .collect();
+        let next = add(list, "/new.json", None, None, "now".into());
+        assert_eq!(next.len(), MAX_ENTRIES);
+        assert_eq!(next[0].path, "/new.json");
+    }
+
+    #[test]
+    fn rename_follows_the_file_and_keeps_pin() {
+        let mut moved = entry("/dir/a.json", true);
+        moved.name = "a.json".into();
+        let list = vec![moved, entry("/b.json", false)];
+        let next = rename(list, "/dir/a.json", "/dir/c.json");
+        assert_eq!(next[0].path, "/dir/c.json");
+        assert_eq!(next[0].name, "c.json");
+        assert!(next[0].pinned);
+        assert_eq!(next[1].path, "/b.json");
+    }
+
+    #[test]
+    fn set_pinned_only_touches_the_path() {
+        let list = vec![entry("/a.json", false), entry("/b.json", false)];
+        let next = set_pinned(list, "/b.json", true);
+        assert!(!next[0].pinned && next[1].pinned);
+        assert!(!set_pinned(next, "/b.json", false)[1].pinned);
+        assert!(!deleted("/no-such-mount-pandia/x.json"));
+    }
+
+    #[test]
+    fn prune_keeps_pinned_and_existing_files() {
+        let list = vec![
+            entry("/gone.json", false),
+            entry("/here.json", false),
+            entry("/offline.json", true),
+        ];
+        let kept = prune_missing(list, |p| p == "/here.json");
+        let paths: Vec<&str> = kept.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["/here.json", "/offline.json"]);
+    }
+
+    #[test]
+    fn sanitize_reads_frontend_entries_and_skips_junk() {
+        let raw = json!([
+            {"path": "/a.json", "name": "a.json", "openedAt": "2026-01-01T00:00:00.000Z", "size": 12, "pinned": true},
+            {"path": 3},
+            {"path": "/b.json", "name": "b.json", "openedAt": "x"}
+        ]);
+        let list = sanitize(Some(raw));
+        assert_eq!(list.len(), 2);
+        assert!(list[0].pinned);
+        assert!(!list[1].pinned);
+        assert!(sanitize(Some(json!({}))).is_empty());
+    }
+}
@@ -0,0 +1,433 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path as FsPath;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::jobs::CancelFlag;
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordFormat {
+    Array,
+    Ndjson,
+}
+
+impl RecordFormat {
+    pub fn from_extension(path: &FsPath) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            "json" => Some(Self::Array),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Array => "json",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+pub struct RecordReader<R: BufRead> {
+    reader: R,
+    format: RecordFormat,
+    done: bool,
+    offset: u64,
+    line: u64,
+    record_line: u64,
+    scratch: Vec<u8>,
+}
+
+fn parse_err(line: u64, e: impl std::fmt::Display) -> DocError {
+    DocError::Parse(format!("line {line}: {e}"))
+}
+
+impl<R: BufRead> RecordReader<R> {
+    pub fn new(reader: R, format: Option<RecordFormat>) -> DocResult<Self> {
+        let mut this = Self {
+            reader,
+            format: format.unwrap_or(RecordFormat::Ndjson),
+            done: false,
+            offset: 0,
+            line: 1,
+            record_line: 0,
+            scratch: Vec::new(),
+        };
+        this.skip_whitespace()?;
+        let first = this.peek()?;
+        if format.is_none() && first == Some(b'[') {
+            this.format = RecordFormat::Array;
+        }
+        if this.format == RecordFormat::Array {
+            match first {
+                Some(b'[') => this.bump(),
+                None => this.done = true,
+                Some(_) => return Err(parse_err(this.line, "expected a JSON array")),
+            }
+        }
+        Ok(this)
+    }
+
+    pub fn format(&self) -> RecordFormat {
+        self.format
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn record_line(&self) -> u64 {
+        self.record_line
+    }
+
+    fn peek(&mut self) -> DocResult<Option<u8>> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    fn bump(&mut self) {
+        self.reader.consume(1);
+        self.offset += 1;
+    }
+
+    fn skip_whitespace(&mut self) -> DocResult<()> {
+        while let Some(b) = self.peek()? {
+            if !b.is_ascii_whitespace() {
+                break;
+            }
+            if b == b'\n' {
+                self.line += 1;
+            }
+            self.bump();
+        }
+        Ok(())
+    }
+
+    fn next_array_element(&mut self) -> DocResult<Option<Value>> {
+        self.skip_whitespace()?;
+        if self.peek()? == Some(b',') {
+            self.bump();
+            self.skip_whitespace()?;
+        }
+        match self.peek()? {
+            None => return Err(parse_err(self.line, "unterminated array")),
+            Some(b']') => {
+                self.bump();
+                self.done = true;
+                return Ok(None);
+            }
+            Some(_) => {}
+        }
+        let start_line = self.line;
+        self.record_line = start_line;
+        self.scratch.clear();
+        let (mut depth, mut in_string, mut escaped) = (0u32, false, false);
+        while let Some(b) = self.peek()? {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if b == b'\\' {
+                    escaped = true;
+                } else if b == b'"' {
+                    in_string = false;
+                }
+            } else {
+                match b {
+                    b'"' => in_string = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' if depth == 0 => break,
+                    b'}' | b']' => depth -= 1,
+                    b',' if depth == 0 => break,
+                    _ => {}
+                }
+            }
+            if b == b'\n' {
+                self.line += 1;
+            }
+            self.scratch.push(b);
+            self.bump();
+        }
+        serde_json::from_slice(&self.scratch)
+            .map(Some)
+            .map_err(|e| parse_err(start_line, e))
+    }
+
+    fn next_line(&mut self) -> DocResult<Option<Value>> {
+        loop {
+            self.scratch.clear();
+            let n = self.reader.read_until(b'\n', &mut self.scratch)?;
+            if n == 0 {
+                self.done = true;
+                return Ok(None);
+            }
+            self.offset += n as u64;
+            let line = self.line;
+            self.record_line = line;
+            self.line += 1;
+            let text = self.scratch.trim_ascii();
+            if text.is_empty() {
+                continue;
+            }
+            return serde_json::from_slice(text)
+                .map(Some)
+                .map_err(|e| parse_err(line, e));
+        }
+    }
+
+    pub fn next_value(&mut self) -> DocResult<Option<Value>> {
+        if self.done {
+            return Ok(None);
+        }
+        match self.format {
+            RecordFormat::Array => self.next_array_element(),
+            RecordFormat::Ndjson => self.next_line(),
+        }
+    }
+}
+
+pub fn open(path: &FsPath) -> DocResult<RecordReader<BufReader<File>>> {
+    let file = File::open(path)?;
+    RecordReader::new(BufReader::new(file), RecordFormat::from_extension(path))
+}
+
+pub struct RecordWriter<W: Write> {
+    writer: W,
+    format: RecordFormat,
+    count: u64,
+    bytes: u64,
+}
+
+impl<W: Write> RecordWriter<W> {
+    pub fn new(writer: W, format: RecordFormat) -> Self {
+        Self {
+            writer,
+            format,
+            count: 0,
+            bytes: 0,
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    fn put(&mut self, chunk: &[u8]) -> DocResult<()> {
+        self.writer.write_all(chunk)?;
+        self.bytes += chunk.len() as u64;
+        Ok(())
+    }
+
+    pub fn write(&mut self, value: &Value) -> DocResult<()> {
+        let text = serde_json::to_vec(value).map_err(|e| DocError::Export(e.to_string()))?;
+        self.write_raw(&text)
+    }
+
+    pub fn write_raw(&mut self, record: &[u8]) -> DocResult<()> {
+        match (self.format, self.count) {
+            (RecordFormat::Array, 0) => self.put(b"[\n  ")?,
+            (RecordFormat::Array, _) => self.put(b",\n  ")?,
+            (RecordFormat::Ndjson, _) => {}
+        }
+        self.put(record)?;
+        if self.format == RecordFormat::Ndjson {
+            self.put(b"\n")?;
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> DocResult<W> {
+        if self.format == RecordFormat::Array {
+            let tail: &[u8] = if self.count == 0 { b"[]\n" } else { b"\n]\n" };
+            self.put(tail)?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+pub fn create(path: &FsPath, format: RecordFormat) -> DocResult<RecordWriter<BufWriter<File>>> {
+    Ok(RecordWriter::new(
+        BufWriter::new(File::create(path)?),
+        format,
+    ))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum RecordSource {
+    Content { content: String },
+    Path { path: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertResult {
+    pub records: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+pub fn convert<R: BufRead, W: Write>(
+    reader: R,
+    from: RecordFormat,
+    writer: W,
+    to: RecordFormat,
+    cancel: &CancelFlag,
+) -> DocResult<(W, u64)> {
+    let mut reader = RecordReader::new(reader, Some(from))?;
+    let mut writer = RecordWriter::new(writer, to);
+    while let Some(value) = reader.next_value()? {
+        if writer.count() & 0x3FF == 0 && cancel.is_cancelled() {
+            return Err(DocError::Cancelled);
+        }
+        writer.write(&value)?;
+    }
+    let records = writer.count();
+    Ok((writer.finish()?, records))
+}
+
+pub fn convert_source(
+    source: &RecordSource,
+    from: RecordFormat,
+    to: RecordFormat,
+    output: Option<&FsPath>,
+    cancel: &CancelFlag,
+) -> DocResult<ConvertResult> {
+    let reader: Box<dyn BufRead> = match source {
+        RecordSource::Content { content } => Box::new(content.as_bytes()),
+        RecordSource::Path { path } => Box::new(BufReader::new(File::open(path)?)),
+    };
+    match output {
+        Some(out) => {
+            let (_, records) =
+                convert(reader, from, BufWriter::new(File::create(out)?), to, cancel)?;
+            Ok(ConvertResult {
+                records,
+                output_path: Some(out.to_string_lossy().into_owned()),
+                content: None,
+            })
+        }
+        None => {
+            let (bytes, records) = convert(reader, from, Vec::new(), to, cancel)?;
+            let content = String::from_utf8(bytes).map_err(|e| DocError::Export(e.to_string()))?;
+            Ok(ConvertResult {
+                records,
+                output_path: None,
+                content: Some(content),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn read_all(text: &str, format: Option<RecordFormat>) -> DocResult<Vec<Value>> {
+        let mut reader = RecordReader::new(text.as_bytes(), format)?;
+        let mut out = Vec::new();
+        while let Some(v) = reader.next_value()? {
+            out.push(v);
+        }
+        Ok(out)
+    }
+
+    #[test]
+    fn reads_array_elements_one_at_a_time() {
+        let text = "\n [ {\"a\": \"],}\"}, [1, [2]] ,\n 3, \"x\\\"\", null ]";
+        let values = read_all(text, None).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                json!({"a": "],}"}),
+                json!([1, [2]]),
+                json!(3),
+                json!("x\""),
+                Value::Null
+            ]
+        );
+        assert!(read_all("[]", None).unwrap().is_empty());
+        assert!(read_all("[1, 2", None).is_err());
+    }
+
+    #[test]
+    fn reads_ndjson_lines_and_reports_line_numbers() {
+        let values = read_all("{\"a\":1}\n\n[1,2]\r\n", Some(RecordFormat::Ndjson)).unwrap();
+        assert_eq!(values, vec![json!({"a": 1}), json!([1, 2])]);
+        let err = read_all("{\"a\":1}\n{oops}\n", None).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn writers_roundtrip_both_formats() {
+        for format in [RecordFormat::Array, RecordFormat::Ndjson] {
+            let mut writer = RecordWriter::new(Vec::new(), format);
+            writer.write(&json!({"a": 1})).unwrap();
+            writer.write(&json!([2])).unwrap();
+            assert_eq!(writer.count(), 2);
+            let bytes = writer.finish().unwrap();
+            let text = String::from_utf8(bytes).unwrap();
+            assert_eq!(
+                read_all(&text, Some(format)).unwrap(),
+                vec![json!({"a": 1}), json!([2])]
+            );
+        }
+        let empty = RecordWriter::new(Vec::new(), RecordFormat::Array)
+            .finish()
+            .unwrap();
+        assert_eq!(empty, b"[]\n");
+    }
+
+    #[test]
+    fn converts_between_array_and_ndjson() {
+        let source = RecordSource::Content {
+            content: r#"[{"a": 1}, {"b": [2, 3]}]"#.into(),
+        };
+        let out = convert_source(
+            &source,
+            RecordFormat::Array,
+            RecordFormat::Ndjson,
+            None,
+            &CancelFlag::never(),
+        )
+        .unwrap();
+        assert_eq!(out.records, 2);
+        let ndjson = out.content.unwrap();
+        assert_eq!(ndjson, "{\"a\":1}\n{\"b\":[2,3]}\n");
+
+        let back = convert_source(
+            &RecordSource::Content { content: ndjson },
+            RecordFormat::Ndjson,
+            RecordFormat::Array,
+            None,
+            &CancelFlag::never(),
+        )
+        .unwrap();
+        let value: Value = serde_json::from_str(&back.content.unwrap()).unwrap();
+        assert_eq!(value, json!([{"a": 1}, {"b": [2, 3]}]));
+
+        let not_array = RecordSource::Content {
+            content: "{\"a\":1}\n".into(),
+        };
+        assert!(convert_source(
+            &not_array,
+            RecordFormat::Array,
+            RecordFormat::Ndjson,
+            None,
+            &CancelFlag::never()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+pub const MAX_LINKS: usize = 50_000;
+pub const DEFAULT_ID_KEYS: &[&str] = &["id", "_id", "uuid", "key", "slug"];
+const REFERENCE_SUFFIXES: &[&str] = &["_id", "_ids", "Id", "Ids", "_ref", "Ref", "_key", "Key"];
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceOptions {
+    #[serde(default)]
+    pub id_keys: Option<Vec<String>>,
+    #[serde(default)]
+    pub reference_keys: Vec<String>,
+    #[serde(default)]
+    pub include_unnamed: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdField {
+    pub collection: String,
+    pub key: String,
+    pub count: u32,
+    pub unique: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceLink {
+    pub from: String,
+    pub to: String,
+    pub field: String,
+    pub value: String,
+    pub collection: String,
+    pub named: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceReport {
+    pub id_fields: Vec<IdField>,
+    pub links: Vec<ReferenceLink>,
+    pub truncated: bool,
+}
+
+struct Definition {
+    pointer: String,
+    collection: String,
+}
+
+struct Reference {
+    pointer: String,
+    owner: String,
+    field: String,
+    value: String,
+}
+
+#[derive(Default)]
+struct Scan {
+    definitions: HashMap<String, Vec<Definition>>,
+    fields: Vec<(String, String, u32)>,
+    references: Vec<Reference>,
+}
+
+pub fn analyze(content: &str, opts: &ReferenceOptions) -> DocResult<ReferenceReport> {
+    let value: Value = serde_json::from_str(content).map_err(|e| DocError::Parse(e.to_string()))?;
+    let id_keys: Vec<String> = match &opts.id_keys {
+        Some(keys) if !keys.is_empty() => keys.clone(),
+        _ => DEFAULT_ID_KEYS.iter().map(|k| k.to_string()).collect(),
+    };
+    let mut scan = Scan::default();
+    let mut path = Path::root();
+    walk(&value, &mut path, &id_keys, opts, &mut scan);
+
+    let mut report = ReferenceReport::default();
+    let mut counts: HashMap<(String, String), (u32, u32)> = HashMap::new();
+    for (collection, key, distinct) in &scan.fields {
+        let entry = counts.entry((collection.clone(), key.clone())).or_default();
+        entry.0 += 1;
+        entry.1 = entry.1.max(*distinct);
+    }
+    report.id_fields = counts
+        .into_iter()
+        .map(|((collection, key), (count, max_dupes))| IdField {
+            collection,
+            key,
+            count,
+            unique: max_dupes <= 1,
+        })
+        .collect();
+    report
+        .id_fields
+        .sort_by(|a, b| a.collection.cmp(&b.collection).then(a.key.cmp(&b.key)));
+
+    let include_unnamed = opts.include_unnamed.unwrap_or(true);
+    for reference in &scan.references {
+        let Some(targets) = scan.definitions.get(&reference.value) else {
+            continue;
+        };
+        let stem = field_stem(&reference.field);
+        let named: Vec<&Definition> = targets
+            .iter()
+            .filter(|d| names_match(&stem, &d.collection))
+            .collect();
+        let (chosen, is_named) = if named.is_empty() {
+            if !include_unnamed {
+                continue;
+            }
+            (targets.iter().collect(), false)
+        } else {
+            (named, true)
+        };
+        for target in chosen {
+            if target.pointer == reference.owner {
+                continue;
+            }
+            if report.links.len() == MAX_LINKS {
+                report.truncated = true;
+                return Ok(report);
+            }
+            report.links.push(ReferenceLink {
+                from: reference.pointer.clone(),
+                to: target.pointer.clone(),
+                field: reference.field.clone(),
+                value: reference.value.clone(),
+                collection: target.collection.clone(),
+                named: is_named,
+            });
+        }
+    }
+    Ok(report)
+}
+
+fn walk(
+    value: &Value,
+    path: &mut Path,
+    id_keys: &[String],
+    opts: &ReferenceOptions,
+    scan: &mut Scan,
+) {
+    match value {
+        Value::Object(map) => {
+            record_object(map, path, id_keys, opts, scan);
+            for (k, v) in map {
+                path.push(PathSegment::Key(k.clone()));
+                walk(v, path, id_keys, opts, scan);
+                path.0.pop();
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                path.push(PathSegment::Index(i as u32));
+                walk(item, path, id_keys, opts, scan);
+                path.0.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn record_object(
+    map: &Map<String, Value>,
+    path: &Path,
+    id_keys: &[String],
+    opts: &ReferenceOptions,
+    scan: &mut Scan,
+) {
+    let owner = path.to_pointer();
+    let collection = collection_name(path);
+    for (k, v) in map {
+        if id_keys.iter().any(|id| id.eq_ignore_ascii_case(k)) {
+            if let Some(text) = scalar_text(v) {
+                let defs = scan.definitions.entry(text).or_default();
+                defs.push(Definition {
+                    pointer: owner.clone(),
+                    collection: collection.clone(),
+                });
+                let same = defs.iter().filter(|d| d.collection == collection).count();
+                scan.fields
+                    .push((collection.clone(), k.clone(), same as u32));
+            }
+        } else if is_reference_key(k, opts) {
+            let mut field_path = path.clone();
+            field_path.push(PathSegment::Key(k.clone()));
+            match v {
+                Value::Array(items) => {
+                    for (i, item) in items.iter().enumerate() {
+                        if let Some(text) = scalar_text(item) {
+                            let mut item_path = field_path.clone();
+                            item_path.push(PathSegment::Index(i as u32));
+                            scan.references.push(Reference {
+                                pointer: item_path.to_pointer(),
+                                owner: owner.clone(),
+                                field: k.clone(),
+                                value: text,
+                            });
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(text) = scalar_text(v) {
+                        scan.references.push(Reference {
+                            pointer: field_path.to_pointer(),
+                            owner: owner.clone(),
+                            field: k.clone(),
+                            value: text,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn scalar_text(v: &Value) -> Option<String> {
+    match v {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+pub fn is_reference_key(key: &str, opts: &ReferenceOptions) -> bool {
+    opts.reference_keys.iter().any(|k| k == key)
+        || REFERENCE_SUFFIXES
+            .iter()
+            .any(|suffix| key.len() > suffix.len() && key.ends_with(suffix))
+}
+
+fn collection_name(path: &Path) -> String {
+    path.0
+        .iter()
+        .rev()
+        .find_map(|seg| match seg {
+            PathSegment::Key(k) => Some(k.clone()),
+            PathSegment::Index(_) => None,
+        })
+        .unwrap_or_default()
+}
+
+fn field_stem(field: &str) -> String {
+    let stem = REFERENCE_SUFFIXES
+        .iter()
+        .find_map(|suffix| field.strip_suffix(suffix))
+        .unwrap_or(field);
+    stem.trim_end_matches(['_', '-']).to_ascii_lowercase()
+}
+
+fn names_match(stem: &str, collection: &str) -> bool {
+    if stem.len() < 2 {
+        return false;
+    }
+    let collection = collection.to_ascii_lowercase();
+    let singular = collection
+        .strip_suffix("ies")
+        .map(|s| format!("{s}y"))
+        .or_else(|| collection.strip_suffix('s').map(str::to_string))
+        .unwrap_or_else(|| collection.clone());
+    stem == collection || stem == singular || (stem.len() > 2 && singular.ends_with(stem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_foreign_keys_to_named_collections() {
+        let text = r#"{
+            "users": [{"id": 1, "name": "Ada"}, {"id": 2, "name": "Alan"}],
+            "categories": [{"id": 1, "title": "Math"}],
+            "posts": [
+                {"id": 10, "user_id": 2, "categoryId": 1, "tagIds": [1, 2]},
+                {"id": 11, "user_id": 3}
+            ]
+        }"#;
+        let report = analyze(text, &ReferenceOptions::default()).unwrap();
+        let links: Vec<(&str, &str, bool)> = report
+            .links
+            .iter()
+            .map(|l| (l.from.as_str(), l.to.as_str(), l.named))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("/posts/0/user_id", "/users/1", true),
+                ("/posts/0/categoryId", "/categories/0", true),
+                ("/posts/0/tagIds/0", "/users/0", false),
+                ("/posts/0/tagIds/0", "/categories/0", false),
+                ("/posts/0/tagIds/1", "/users/1", false),
+            ]
+        );
+        let posts = report
+            .id_fields
+            .iter()
+            .find(|f| f.collection == "posts")
+            .unwrap();
+        assert_eq!((posts.count, posts.unique), (2, true));
+    }
+
+    #[test]
+    fn honours_custom_keys_and_unnamed_filter() {
+        let text = r#"{"teams":[{"code":"A"}],"people":[{"team":"A","owner_ref":"A"}]}"#;
+        let opts = ReferenceOptions {
+            id_keys: Some(vec!["code".into()]),
+            reference_keys: vec!["team".into()],
+            include_unnamed: Some(false),
+        };
+        let report = analyze(text, &opts).unwrap();
+        assert_eq!(report.links.len(), 1);
+        assert_eq!(report.links[0].from, "/people/0/team");
+        assert_eq!(report.links[0].to, "/teams/0");
+    }
+}
@@ -0,0 +1,298 @@
+use std::time::Duration;
+
+use dashmap::DashMap;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tauri_plugin_http::reqwest::{Client, Response, StatusCode};
+
+use super::types::{DocError, DocHandle, DocResult};
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+pub fn fetch_text(url: &str) -> Result<String, String> {
+    let url = url.to_string();
+    tauri::async_runtime::handle().block_on(async move {
+        let client = Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let resp = client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("{url}: {e}"))?;
+        resp.text().await.map_err(|e| format!("{url}: {e}"))
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SaveMethod {
+    #[default]
+    Put,
+    Patch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteOrigin {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub base: String,
+}
+
+impl RemoteOrigin {
+    pub fn revision(&self) -> Option<&str> {
+        self.etag.as_deref().or(self.last_modified.as_deref())
+    }
+
+    pub fn conditions(&self) -> Vec<(&'static str, String)> {
+        match (&self.etag, &self.last_modified) {
+            (Some(etag), _) => vec![("If-Match", etag.clone())],
+            (None, Some(modified)) => vec![("If-Unmodified-Since", modified.clone())],
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RemoteOrigins {
+    known: DashMap<DocHandle, RemoteOrigin>,
+    conflicting: DashMap<DocHandle, RemoteOrigin>,
+}
+
+impl RemoteOrigins {
+    pub fn record(&self, handle: DocHandle, origin: RemoteOrigin) {
+        self.known.insert(handle, origin);
+        self.conflicting.remove(&handle);
+    }
+
+    pub fn record_conflict(&self, handle: DocHandle, remote: RemoteOrigin) {
+        self.conflicting.insert(handle, remote);
+    }
+
+    pub fn resolved(&self, handle: DocHandle, revision: &str) -> Option<RemoteOrigin> {
+        self.conflicting
+            .get(&handle)
+            .filter(|remote| remote.revision() == Some(revision))
+            .map(|remote| remote.clone())
+    }
+
+    pub fn forget(&self, handle: DocHandle) {
+        self.known.remove(&handle);
+        self.conflicting.remove(&handle);
+    }
+
+    pub fn get(&self, handle: DocHandle) -> Option<RemoteOrigin> {
+        self.known.get(&handle).map(|o| o.clone())
+    }
+}
+
+pub enum PutOutcome {
+    Saved {
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+    PreconditionFailed,
+}
+
+fn client() -> DocResult<Client> {
+    Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| DocError::Io(std::io::Error::other(e.to_string())))
+}
+
+fn header(resp: &Response, name: &str) -> Option<String> {
+    resp.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+fn http_err(url: &str, e: impl std::fmt::Display) -> DocError {
+    DocError::Io(std::io::Error::other(format!("{url}: {e}")))
+}
+
+pub async fn fetch_versioned(url: &str) -> DocResult<RemoteOrigin> {
+    let resp = client()?
+        .get(url)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| http_err(url, e))?;
+    let etag = header(&resp, "ETag");
+    let last_modified = header(&resp, "Last-Modified");
+    let base = resp.text().await.map_err(|e| http_err(url, e))?;
+    Ok(RemoteOrigin {
+        url: url.to_string(),
+        etag,
+        last_modified,
+        base,
+    })
+}
+
+fn expressible(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Object(map) => map.values().all(expressible),
+        _ => true,
+    }
+}
+
+fn member_patch(base: Option<&Value>, target: &Value) -> Option<Value> {
+    match (base, target) {
+        (Some(base @ Value::Object(_)), Value::Object(_)) => merge_patch(base, target),
+        _ => expressible(target).then(|| target.clone()),
+    }
+}
+
+pub fn merge_patch(base: &Value, target: &Value) -> Option<Value> {
+    let Value::Object(fields) = target else {
+        return Some(target.clone());
+    };
+    let Value::Object(before) = base else {
+        return expressible(target).then(|| target.clone());
+    };
+    let mut patch = Map::new();
+    for key in before.keys().filter(|k| !fields.contains_key(*k)) {
+        patch.insert(key.clone(), Value::Null);
+    }
+    for (key, value) in fields {
+        let old = before.get(key);
+        if old != Some(value) {
+            patch.insert(key.clone(), member_patch(old, value)?);
+        }
+    }
+    Some(Value::Object(patch))
+}
+
+fn patch_body(origin: &RemoteOrigin, body: &str) -> DocResult<String> {
+    let parse = |text: &str| {
+        serde_json::from_str::<Value>(text).map_err(|e| DocError::Parse(e.to_string()))
+    };
+    let patch = merge_patch(&parse(&origin.base)?, &parse(body)?).ok_or_else(|| {
+        DocError::Edit("a JSON merge patch cannot set values to null; save with PUT".into())
+    })?;
+    serde_json::to_string(&patch).map_err(|e| DocError::Parse(e.to_string()))
+}
+
+/// Encodes the document for `method`; parses JSON for PATCH, so callers run
+/// it off the async runtime.
+pub fn request_body(origin: &RemoteOrigin, body: &str, method: SaveMethod) -> DocResult<String> {
+    match method {
+        SaveMethod::Put => Ok(body.to_string()),
+        SaveMethod::Patch => patch_body(origin, body),
+    }
+}
+
+pub async fn put_conditional(
+    origin: &RemoteOrigin,
+    payload: String,
+    method: SaveMethod,
+    force: bool,
+) -> DocResult<PutOutcome> {
+    let client = client()?;
+    let mut request = match method {
+        SaveMethod::Put => client
+            .put(&origin.url)
+            .header("Content-Type", "application/json"),
+        SaveMethod::Patch => client
+            .patch(&origin.url)
+            .header("Content-Type", "application/merge-patch+json"),
+    }
+    .body(payload);
+    if !force {
+        let conditions = origin.conditions();
+        if conditions.is_empty() {
+            return Err(DocError::Conflict(format!(
+                "{} sent no ETag or Last-Modified, so a concurrent change cannot be detected; save with force to overwrite",
+                origin.url
+            )));
+        }
+        for (name, value) in conditions {
+            request = request.header(name, value);
+        }
+    }
+    let resp = request.send().await.map_err(|e| http_err(&origin.url, e))?;
+    if resp.status() == StatusCode::PRECONDITION_FAILED {
+        return Ok(PutOutcome::PreconditionFailed);
+    }
+    let resp = resp
+        .error_for_status()
+        .map_err(|e| http_err(&origin.url, e))?;
+    let (mut etag, mut last_modified) = (header(&resp, "ETag"), header(&resp, "Last-Modified"));
+    if etag.is_none() && last_modified.is_none() {
+        // Many servers only return validators on reads; ask for them so the
+        // next save stays conditional.
+        if let Ok(head) = client
+            .head(&origin.url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+        {
+            etag = header(&head, "ETag");
+            last_modified = header(&head, "Last-Modified");
+        }
+    }
+    Ok(PutOutcome::Saved {
+        etag,
+        last_modified,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn origin(etag: Option<&str>, modified: Option<&str>) -> RemoteOrigin {
+        RemoteOrigin {
+            url: "https://example.com/config.json".into(),
+            etag: etag.map(Into::into),
+            last_modified: modified.map(Into::into),
+            base: "{}".into(),
+        }
+    }
+
+    #[test]
+    fn prefers_etags_for_conditional_saves() {
+        assert_eq!(
+            origin(Some("\"v1\""), Some("Tue, 01 Sep 2026 10:00:00 GMT")).conditions(),
+            vec![("If-Match", "\"v1\"".to_string())]
+        );
+        assert_eq!(
+            origin(None, Some("Tue, 01 Sep 2026 10:00:00 GMT")).conditions()[0].0,
+            "If-Unmodified-Since"
+        );
+        assert!(origin(None, None).conditions().is_empty());
+    }
+
+    #[test]
+    fn tracks_origins_per_document() {
+        let origins = RemoteOrigins::default();
+        let handle = DocHandle::new();
+        origins.record(handle, origin(Some("\"a\""), None));
+        origins.record_conflict(handle, origin(Some("\"b\""), None));
+        assert_eq!(origins.get(handle).unwrap().etag.as_deref(), Some("\"a\""));
+        assert!(origins.resolved(handle, "\"a\"").is_none());
+        assert!(origins.resolved(handle, "\"b\"").is_some());
+        origins.forget(handle);
+        assert!(origins.get(handle).is_none());
+        assert!(origins.resolved(handle, "\"b\"").is_none());
+    }
+
+    #[test]
+    fn merge_patch_describes_changes_against_base() {
+        let base = json!({"name": "a", "drop": 1, "nested": {"x": 1, "y": 2}, "list": [1, 2]});
+        let next = json!({"name": "a", "nested": {"x": 1, "y": 3}, "list": [1], "new": {"k": 1}});
+        assert_eq!(
+            merge_patch(&base, &next).unwrap(),
+            json!({"drop": null, "nested": {"y": 3}, "list": [1], "new": {"k": 1}})
+        );
+        assert!(merge_patch(&base, &json!({"name": null})).is_none());
+        assert_eq!(merge_patch(&base, &json!([1])).unwrap(), json!([1]));
+    }
+}
@@ -0,0 +1,240 @@
+use std::io::Read;
+use std::path::Path as FsPath;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::editorconfig::{EditorConfig, IndentStyle};
+use super::ops::sort_keys_in_place;
+use super::settings::{EffectiveSettings, LineEnding};
+use super::types::{DocError, DocResult};
+
+const SNIFF_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveHooks {
+    #[serde(default)]
+    pub format: Option<u8>,
+    #[serde(default)]
+    pub use_tabs: bool,
+    #[serde(default)]
+    pub sort_keys: bool,
+    #[serde(default)]
+    pub trim_trailing_whitespace: bool,
+    #[serde(default)]
+    pub final_newline: bool,
+    #[serde(default)]
+    pub line_ending: Option<LineEnding>,
+    #[serde(default)]
+    pub bom: Option<bool>,
+}
+
+impl SaveHooks {
+    pub fn from_effective(settings: &EffectiveSettings) -> Self {
+        Self {
+            format: settings.format_on_save.then_some(settings.indent),
+            sort_keys: settings.sort_keys_on_save,
+            trim_trailing_whitespace: settings.trim_trailing_whitespace,
+            final_newline: settings.insert_final_newline,
+            line_ending: Some(settings.line_ending),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_editorconfig(mut self, config: &EditorConfig) -> Self {
+        if let (Some(size), Some(_)) = (config.indent_size, self.format) {
+            self.format = Some(size);
+        }
+        if let Some(style) = config.indent_style {
+            self.use_tabs = style == IndentStyle::Tab;
+        }
+        if let Some(trim) = config.trim_trailing_whitespace {
+            self.trim_trailing_whitespace = trim;
+        }
+        if let Some(newline) = config.insert_final_newline {
+            self.final_newline = newline;
+        }
+        if let Some(ending) = config.end_of_line {
+            self.line_ending = Some(ending);
+        }
+        match config.charset.as_deref() {
+            Some("utf-8-bom") => self.bom = Some(true),
+            Some("utf-8") => self.bom = Some(false),
+            _ => {}
+        }
+        self
+    }
+
+    pub fn apply(&self, text: String, target: &FsPath) -> DocResult<String> {
+        let mut text = if self.format.is_some() || self.sort_keys {
+            restructure(&text, self.format, self.use_tabs, self.sort_keys)?
+        } else {
+            text
+        };
+        if self.trim_trailing_whitespace {
+            text = trim_lines(&text);
+        }
+        if self.final_newline && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        let ending = match self.line_ending {
+            Some(LineEnding::Preserve) => existing_line_ending(target),
+            other => other,
+        };
+        let text = match ending {
+            Some(LineEnding::Crlf) => to_crlf(&text),
+            Some(LineEnding::Lf) => text.replace("\r\n", "\n"),
+            _ => text,
+        };
+        Ok(match self.bom {
+            Some(true) if !text.starts_with('\u{feff}') => format!("\u{feff}{text}"),
+            Some(false) => text.trim_start_matches('\u{feff}').to_string(),
+            _ => text,
+        })
+    }
+}
+
+fn restructure(
+    text: &str,
+    indent: Option<u8>,
+    use_tabs: bool,
+    sort_keys: bool,
+) -> DocResult<String> {
+    let mut value: Value = serde_json::from_str(text.trim_start_matches('\u{feff}'))
+        .map_err(|e| DocError::Parse(e.to_string()))?;
+    if sort_keys {
+        sort_keys_in_place(&mut value, false);
+    }
+    let indent = match indent {
+        Some(0) => {
+            return serde_json::to_string(&value).map_err(|e| DocError::Export(e.to_string()))
+        }
+        Some(_) if use_tabs => "\t".to_string(),
+        Some(n) => " ".repeat(n.min(8) as usize),
+        None => detect_indent(text).unwrap_or_else(|| "  ".into()),
+    };
+    let mut out = Vec::new();
+    let mut ser = serde_json::Serializer::with_formatter(
+        &mut out,
+        serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes()),
+    );
+    value
+        .serialize(&mut ser)
+        .map_err(|e| DocError::Export(e.to_string()))?;
+    let mut formatted = String::from_utf8(out).map_err(|e| DocError::Export(e.to_string()))?;
+    if text.ends_with('\n') {
+        formatted.push('\n');
+    }
+    Ok(formatted)
+}
+
+fn detect_indent(text: &str) -> Option<String> {
+    text.lines()
+        .skip(1)
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|lead| !lead.is_empty())
+        .map(str::to_string)
+}
+
+fn trim_lines(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let (body, end) = match line.strip_suffix("\r\n") {
+            Some(body) => (body, "\r\n"),
+            None => match line.strip_suffix('\n') {
+                Some(body) => (body, "\n"),
+                None => (line, ""),
+            },
+        };
+        out.push_str(body.trim_end_matches([' ', '\t']));
+        out.push_str(end);
+    }
+    out
+}
+
+fn to_crlf(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', "\r\n")
+}
+
+fn existing_line_ending(path: &FsPath) -> Option<LineEnding> {
+    let mut head = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(SNIFF_BYTES)
+        .read_to_end(&mut head)
+        .ok()?;
+    line_ending_of(&head)
+}
+
+pub fn line_ending_of(bytes: &[u8]) -> Option<LineEnding> {
+    let newline = bytes.iter().position(|&b| b == b'\n')?;
+    Some(if newline > 0 && bytes[newline - 1] == b'\r' {
+        LineEnding::Crlf
+    } else {
+        LineEnding::Lf
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn formats_sorts_and_normalizes() {
+        let hooks = SaveHooks {
+            format: Some(4),
+            sort_keys: true,
+            final_newline: true,
+            line_ending: Some(LineEnding::Crlf),
+            ..SaveHooks::default()
+        };
+        let out = hooks
+            .apply(
+                r#"{"b": 1, "a": [true]}"#.into(),
+                FsPath::new("/nonexistent"),
+            )
+            .unwrap();
+        assert_eq!(
+            out,
+            "{\r\n    \"a\": [\r\n        true\r\n    ],\r\n    \"b\": 1\r\n}\r\n"
+        );
+        assert!(hooks.apply("{".into(), FsPath::new("/x")).is_err());
+    }
+
+    #[test]
+    fn editorconfig_overrides_settings() {
+        let config = EditorConfig {
+            indent_style: Some(IndentStyle::Tab),
+            end_of_line: Some(LineEnding::Lf),
+            insert_final_newline: Some(true),
+            charset: Some("utf-8-bom".into()),
+            ..EditorConfig::default()
+        };
+        let hooks = SaveHooks {
+            format: Some(2),
+            line_ending: Some(LineEnding::Crlf),
+            ..SaveHooks::default()
+        }
+        .with_editorconfig(&config);
+        let out = hooks.apply("[1]".into(), FsPath::new("/x")).unwrap();
+        assert_eq!(out, "\u{feff}[\n\t1\n]\n");
+    }
+
+    #[test]
+    fn trims_whitespace_and_preserves_existing_line_endings() {
+        let dir = TempDir::new("save-hooks");
+        let target = dir.join("crlf.json");
+        std::fs::write(&target, "{\r\n  \"a\": 1\r\n}").unwrap();
+        let hooks = SaveHooks {
+            trim_trailing_whitespace: true,
+            line_ending: Some(LineEnding::Preserve),
+            ..SaveHooks::default()
+        };
+        let out = hooks.apply("{  \n  \"a\": 2\t\n}".into(), &target).unwrap();
+        assert_eq!(out, "{\r\n  \"a\": 2\r\n}");
+        let fresh = hooks.apply("[1] \n".into(), &dir.join("new.json")).unwrap();
+        assert_eq!(fresh, "[1]\n");
+    }
+}
@@ -0,0 +1,212 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::completions::expand;
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+const MAX_DEPTH: usize = 32;
+const MAX_ABSENT_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DriftKind {
+    Differs,
+    Omitted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultDrift {
+    pub pointer: String,
+    pub kind: DriftKind,
+    pub default: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultsReport {
+    pub entries: Vec<DefaultDrift>,
+    pub differs: u32,
+    pub omitted: u32,
+    pub matching: u32,
+}
+
+struct Walker<'a> {
+    root: &'a Value,
+    report: DefaultsReport,
+}
+
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x == y || x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn default_of<'a>(schemas: &[&'a Map<String, Value>]) -> Option<&'a Value> {
+    schemas.iter().find_map(|s| s.get("default"))
+}
+
+impl<'a> Walker<'a> {
+    fn expand(&self, schema: &'a Value) -> Vec<&'a Map<String, Value>> {
+        let mut out = Vec::new();
+        expand(self.root, schema, &mut out, 0);
+        out
+    }
+
+    fn walk(
+        &mut self,
+        schemas: &[&'a Map<String, Value>],
+        value: Option<&Value>,
+        path: &mut Path,
+        depth: usize,
+        absent: usize,
+    ) {
+        if schemas.is_empty() || depth > MAX_DEPTH {
+            return;
+        }
+        if let Some(default) = default_of(schemas) {
+            let pointer = path.to_pointer();
+            match value {
+                None => {
+                    self.report.omitted += 1;
+                    self.report.entries.push(DefaultDrift {
+                        pointer,
+                        kind: DriftKind::Omitted,
+                        default: default.clone(),
+                        actual: None,
+                    });
+                    return;
+                }
+                Some(actual) if !same(actual, default) => {
+                    self.report.differs += 1;
+                    self.report.entries.push(DefaultDrift {
+                        pointer,
+                        kind: DriftKind::Differs,
+                        default: default.clone(),
+                        actual: Some(actual.clone()),
+                    });
+                }
+                Some(_) => self.report.matching += 1,
+            }
+        }
+        match value {
+            Some(Value::Array(items)) => {
+                for (i, item) in items.iter().enumerate() {
+                    let child: Vec<_> = schemas
+                        .iter()
+                        .filter_map(|s| {
+                            s.get("prefixItems")
+                                .and_then(|p| p.get(i))
+                                .or_else(|| s.get("items").filter(|v| v.is_object()))
+                        })
+                        .flat_map(|c| self.expand(c))
+                        .collect();
+                    path.push(PathSegment::Index(i as u32));
+                    self.walk(&child, Some(item), path, depth + 1, 0);
+                    path.0.pop();
+                }
+            }
+            Some(Value::Object(_)) => self.walk_properties(schemas, value, path, depth, 0),
+            None if absent < MAX_ABSENT_DEPTH => {
+                self.walk_properties(schemas, value, path, depth, absent + 1)
+            }
+            _ => {}
+        }
+    }
+
+    fn walk_properties(
+        &mut self,
+        schemas: &[&'a Map<String, Value>],
+        value: Option<&Value>,
+        path: &mut Path,
+        depth: usize,
+        absent: usize,
+    ) {
+        let object = value.and_then(Value::as_object);
+        let mut names: Vec<&'a String> = Vec::new();
+        for props in schemas
+            .iter()
+            .filter_map(|s| s.get("properties")?.as_object())
+        {
+            for name in props.keys() {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        for name in names {
+            let child: Vec<_> = schemas
+                .iter()
+                .filter_map(|s| s.get("properties").and_then(|p| p.get(name.as_str())))
+                .flat_map(|c| self.expand(c))
+                .collect();
+            path.push(PathSegment::Key(name.clone()));
+            let field = object.and_then(|o| o.get(name.as_str()));
+            self.walk(&child, field, path, depth + 1, absent);
+            path.0.pop();
+        }
+    }
+}
+
+pub fn diff_against_defaults(content: &str, schema: &Value) -> DocResult<DefaultsReport> {
+    let value: Value = serde_json::from_str(content).map_err(|e| DocError::Parse(e.to_string()))?;
+    let mut walker = Walker {
+        root: schema,
+        report: DefaultsReport::default(),
+    };
+    let schemas = walker.expand(schema);
+    walker.walk(&schemas, Some(&value), &mut Path::root(), 0, 0);
+    Ok(walker.report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_differing_and_omitted_defaults() {
+        let schema = json!({
+            "$defs": {"port": {"type": "integer", "default": 8080}},
+            "properties": {
+                "host": {"type": "string", "default": "localhost"},
+                "port": {"$ref": "#/$defs/port"},
+                "debug": {"type": "boolean", "default": false},
+                "tls": {
+                    "properties": {
+                        "enabled": {"default": false},
+                        "ciphers": {"default": ["TLS_AES_128_GCM_SHA256"]}
+                    }
+                },
+                "workers": {
+                    "type": "array",
+                    "items": {"properties": {"weight": {"default": 1}}}
+                }
+            }
+        });
+        let content = r#"{"host": "db.internal", "port": 8080.0, "workers": [{"weight": 2}, {}]}"#;
+        let report = diff_against_defaults(content, &schema).unwrap();
+        let summary: Vec<(&str, DriftKind)> = report
+            .entries
+            .iter()
+            .map(|e| (e.pointer.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/host", DriftKind::Differs),
+                ("/debug", DriftKind::Omitted),
+                ("/tls/enabled", DriftKind::Omitted),
+                ("/tls/ciphers", DriftKind::Omitted),
+                ("/workers/0/weight", DriftKind::Differs),
+                ("/workers/1/weight", DriftKind::Omitted),
+            ]
+        );
+        assert_eq!(report.matching, 1);
+        assert_eq!(report.entries[0].actual, Some(json!("db.internal")));
+        assert_eq!((report.differs, report.omitted), (2, 4));
+    }
+}
@@ -0,0 +1,439 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+const DEFAULT_MAX_DEPTH: u32 = 8;
+const DEFAULT_ARRAY_ITEMS: u32 = 1;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExampleOptions {
+    #[serde(default)]
+    pub base_path: Option<String>,
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+    #[serde(default)]
+    pub array_items: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaExample {
+    pub minimal: Value,
+    pub full: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Minimal,
+    Full,
+}
+
+struct Generator<'a> {
+    root: &'a Value,
+    mode: Mode,
+    max_depth: u32,
+    array_items: u32,
+}
+
+pub fn generate_examples(schema: &Value, opts: &ExampleOptions) -> SchemaExample {
+    let make = |mode| Generator {
+        root: schema,
+        mode,
+        max_depth: opts.max_depth.unwrap_or(DEFAULT_MAX_DEPTH),
+        array_items: opts.array_items.unwrap_or(DEFAULT_ARRAY_ITEMS),
+    };
+    SchemaExample {
+        minimal: make(Mode::Minimal).generate(schema, 0),
+        full: make(Mode::Full).generate(schema, 0),
+    }
+}
+
+impl Generator<'_> {
+    fn generate(&self, schema: &Value, depth: u32) -> Value {
+        let Value::Object(s) = schema else {
+            return Value::Null;
+        };
+        if depth > self.max_depth {
+            return empty_for(s);
+        }
+        if let Some(Value::Array(examples)) = s.get("examples") {
+            if let Some(first) = examples.first() {
+                return first.clone();
+            }
+        }
+        for key in ["example", "const", "default"] {
+            if let Some(v) = s.get(key) {
+                return v.clone();
+            }
+        }
+        if let Some(Value::Array(options)) = s.get("enum") {
+            if let Some(first) = options.iter().find(|v| !v.is_null()).or(options.first()) {
+                return first.clone();
+            }
+        }
+        if let Some(target) = s
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix('#'))
+            .and_then(|ptr| self.root.pointer(ptr))
+        {
+            return self.generate(target, depth + 1);
+        }
+        if let Some(Value::Array(parts)) = s.get("allOf") {
+            let mut merged = Value::Null;
+            for part in parts {
+                merged = merge(merged, self.generate(part, depth + 1));
+            }
+            let own = self.generate_typed(s, depth);
+            return merge(merged, own);
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(Value::Array(variants)) = s.get(key) {
+                let pick = variants
+                    .iter()
+                    .find(|v| v.get("type").and_then(Value::as_str) != Some("null"))
+                    .or(variants.first());
+                if let Some(variant) = pick {
+                    let own = self.generate_typed(s, depth);
+                    return merge(self.generate(variant, depth + 1), own);
+                }
+            }
+        }
+        self.generate_typed(s, depth)
+    }
+
+    fn generate_typed(&self, s: &Map<String, Value>, depth: u32) -> Value {
+        match schema_type(s) {
+            Some("object") => self.generate_object(s, depth),
+            Some("array") => self.generate_array(s, depth),
+            Some("string") => Value::String(example_string(s)),
+            Some("integer") => example_number(s, true),
+            Some("number") => example_number(s, false),
+            Some("boolean") => Value::Bool(false),
+            _ => Value::Null,
+        }
+    }
+
+    fn generate_object(&self, s: &Map<String, Value>, depth: u32) -> Value {
+        let required: Vec<&str> = s
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let props = s.get("properties").and_then(Value::as_object);
+        let extra = s.get("additionalProperties").filter(|v| v.is_object());
+        let mut out = Map::new();
+        if let Some(props) = props {
+            for (name, prop) in props {
+                if self.mode == Mode::Full || required.contains(&name.as_str()) {
+                    out.insert(name.clone(), self.generate(prop, depth + 1));
+                }
+            }
+        }
+        for name in required {
+            if !out.contains_key(name) {
+                let v = extra.map_or(Value::Null, |e| self.generate(e, depth + 1));
+                out.insert(name.to_string(), v);
+            }
+        }
+        Value::Object(out)
+    }
+
+    fn generate_array(&self, s: &Map<String, Value>, depth: u32) -> Value {
+        let min = s.get("minItems").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let max = s
+            .get("maxItems")
+            .and_then(Value::as_u64)
+            .map(|m| m as usize);
+        let tuple = s
+            .get("prefixItems")
+            .or_else(|| s.get("items").filter(|i| i.is_array()))
+            .and_then(Value::as_array);
+        let item_schema = match s.get("items") {
+            Some(i) if i.is_object() => Some(i),
+            _ => s.get("additionalItems").filter(|i| i.is_object()),
+        };
+        let wanted = match self.mode {
+            Mode::Minimal => min,
+            Mode::Full => min
+                .max(self.array_items as usize)
+                .max(tuple.map_or(0, Vec::len)),
+        };
+        let wanted = max.map_or(wanted, |m| wanted.min(m));
+        let mut out = Vec::with_capacity(wanted);
+        for i in 0..wanted {
+            let schema = tuple.and_then(|t| t.get(i)).or(item_schema);
+            match schema {
+                Some(schema) => out.push(self.generate(schema, depth + 1)),
+                None if tuple.is_some() => break,
+                None => out.push(Value::Null),
+            }
+        }
+        Value::Array(out)
+    }
+}
+
+fn schema_type(s: &Map<String, Value>) -> Option<&str> {
+    match s.get("type") {
+        Some(Value::String(t)) => Some(t.as_str()),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null")
+            .or_else(|| types.first().and_then(Value::as_str)),
+        _ if s.contains_key("properties") || s.contains_key("required") => Some("object"),
+        _ if s.contains_key("items") || s.contains_key("prefixItems") => Some("array"),
+        _ => None,
+    }
+}
+
+fn empty_for(s: &Map<String, Value>) -> Value {
+    match schema_type(s) {
+        Some("object") => Value::Object(Map::new()),
+        Some("array") => Value::Array(Vec::new()),
+        _ => Value::Null,
+    }
+}
+
+fn merge(base: Value, over: Value) -> Value {
+    match (base, over) {
+        (Value::Object(mut a), Value::Object(b)) => {
+            for (k, v) in b {
+                match a.get_mut(&k) {
+                    Some(existing) => *existing = merge(std::mem::take(existing), v),
+                    None => {
+                        a.insert(k, v);
+                    }
+                }
+            }
+            Value::Object(a)
+        }
+        (base, Value::Null) => base,
+        (_, over) => over,
+    }
+}
+
+fn example_string(s: &Map<String, Value>) -> String {
+    let base = match s.get("format").and_then(Value::as_str) {
+        Some("date-time") => "2024-01-01T00:00:00Z",
+        Some("date") => "2024-01-01",
+        Some("time") => "12:00:00Z",
+        Some("duration") => "P1D",
+        Some("email") | Some("idn-email") => "user@example.com",
+        Some("hostname") | Some("idn-hostname") => "example.com",
+        Some("ipv4") => "192.0.2.1",
+        Some("ipv6") => "2001:db8::1",
+        Some("uri") | Some("url") | Some("iri") => "https://example.com",
+        Some("uri-reference") | Some("iri-reference") => "/example",
+        Some("uuid") => "00000000-0000-4000-8000-000000000000",
+        Some("json-pointer") => "/example",
+        Some("regex") => ".*",
+        Some("byte") => "ZXhhbXBsZQ==",
+        Some("password") => "********",
+        _ => "string",
+    };
+    let min = s.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let max = s
+        .get("maxLength")
+        .and_then(Value::as_u64)
+        .map(|m| m as usize);
+    let mut out = base.to_string();
+    let len = out.chars().count();
+    if len < min {
+        out.push_str(&"x".repeat(min - len));
+    }
+    if let Some(max) = max {
+        if out.chars().count() > max {
+            out = out.chars().take(max).collect();
+        }
+    }
+    out
+}
+
+fn example_number(s: &Map<String, Value>, integer: bool) -> Value {
+    let num = |k: &str| s.get(k).and_then(Value::as_f64);
+    let step = if integer { 1.0 } else { 0.5 };
+    let exclusive_flag = |k: &str| s.get(k).and_then(Value::as_bool).unwrap_or(false);
+
+    let lo = match (num("minimum"), num("exclusiveMinimum")) {
+        (Some(m), _) if exclusive_flag("exclusiveMinimum") => Some(m + step),
+        (Some(m), Some(e)) => Some(m.max(e + step)),
+        (Some(m), None) => Some(m),
+        (None, Some(e)) => Some(e + step),
+        (None, None) => None,
+    };
+    let hi = match (num("maximum"), num("exclusiveMaximum")) {
+        (Some(m), _) if exclusive_flag("exclusiveMaximum") => Some(m - step),
+        (Some(m), Some(e)) => Some(m.min(e - step)),
+        (Some(m), None) => Some(m),
+        (None, Some(e)) => Some(e - step),
+        (None, None) => None,
+    };
+
+    let mut v = 0.0_f64;
+    if let Some(lo) = lo {
+        v = v.max(lo);
+    }
+    if let Some(hi) = hi {
+        v = v.min(hi);
+    }
+    if integer {
+        v = v.ceil();
+    }
+    if let Some(m) = num("multipleOf").filter(|m| *m > 0.0) {
+        v = (v / m).ceil() * m;
+        if let Some(hi) = hi.filter(|hi| v > *hi) {
+            v = (hi / m).floor() * m;
+        }
+    }
+    if v.fract() == 0.0 && v.abs() < i64::MAX as f64 {
+        Value::from(v as i64)
+    } else {
+        Number::from_f64(v).map_or(Value::Null, Value::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn examples(schema: Value) -> SchemaExample {
+        generate_examples(&schema, &ExampleOptions::default())
+    }
+
+    #[test]
+    fn minimal_has_only_required_full_has_all() {
+        let r = examples(json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "integer"},
+                "name": {"type": "string"}
+            }
+        }));
+        assert_eq!(r.minimal, json!({"id": 0}));
+        assert_eq!(r.full, json!({"id": 0, "name": "string"}));
+    }
+
+    #[test]
+    fn defaults_enums_and_consts_win() {
+        let r = examples(json!({
+            "type": "object",
+            "properties": {
+                "level": {"type": "string", "enum": ["info", "warn"]},
+                "retries": {"type": "integer", "default": 3},
+                "kind": {"const": "event"}
+            }
+        }));
+        assert_eq!(
+            r.full,
+            json!({"level": "info", "retries": 3, "kind": "event"})
+        );
+    }
+
+    #[test]
+    fn string_formats_and_lengths() {
+        let r = examples(json!({
+            "type": "object",
+            "properties": {
+                "at": {"type": "string", "format": "date-time"},
+                "mail": {"type": "string", "format": "email"},
+                "code": {"type": "string", "minLength": 8},
+                "tiny": {"type": "string", "maxLength": 3}
+            }
+        }));
+        assert_eq!(r.full["at"], json!("2024-01-01T00:00:00Z"));
+        assert_eq!(r.full["mail"], json!("user@example.com"));
+        assert_eq!(r.full["code"], json!("stringxx"));
+        assert_eq!(r.full["tiny"], json!("str"));
+    }
+
+    #[test]
+    fn numeric_bounds_are_respected() {
+        let r = examples(json!({
+            "type": "object",
+            "properties": {
+                "port": {"type": "integer", "minimum": 1024},
+                "neg": {"type": "integer", "maximum": -5},
+                "ex": {"type": "integer", "exclusiveMinimum": 0},
+                "step": {"type": "integer", "minimum": 7, "multipleOf": 5}
+            }
+        }));
+        assert_eq!(r.full["port"], json!(1024));
+        assert_eq!(r.full["neg"], json!(-5));
+        assert_eq!(r.full["ex"], json!(1));
+        assert_eq!(r.full["step"], json!(10));
+    }
+
+    #[test]
+    fn multiple_of_stays_inside_the_bounds() {
+        let r = examples(json!({
+            "type": "object",
+            "properties": {
+                "range": {"type": "integer", "minimum": -10, "maximum": -1, "multipleOf": 4},
+                "below_zero": {"type": "integer", "maximum": -5, "multipleOf": 3},
+                "fraction": {"type": "number", "maximum": -0.2, "multipleOf": 0.25}
+            }
+        }));
+        assert_eq!(r.full["range"], json!(-4));
+        assert_eq!(r.full["below_zero"], json!(-6));
+        assert_eq!(r.full["fraction"], json!(-0.25));
+    }
+
+    #[test]
+    fn arrays_respect_min_items_and_tuples() {
+        let r = examples(json!({
+            "type": "object",
+            "required": ["tags", "pair"],
+            "properties": {
+                "tags": {"type": "array", "items": {"type": "string"}, "minItems": 2},
+                "pair": {"type": "array", "prefixItems": [{"type": "integer"}, {"type": "boolean"}]},
+                "opt": {"type": "array", "items": {"type": "integer"}}
+            }
+        }));
+        assert_eq!(r.minimal, json!({"tags": ["string", "string"], "pair": []}));
+        assert_eq!(r.full["pair"], json!([0, false]));
+        assert_eq!(r.full["opt"], json!([0]));
+    }
+
+    #[test]
+    fn local_refs_and_all_of_merge() {
+        let r = examples(json!({
+            "$defs": {
+                "base": {"type": "object", "required": ["id"], "properties": {"id": {"type": "string", "format": "uuid"}}}
+            },
+            "allOf": [
+                {"$ref": "#/$defs/base"},
+                {"type": "object", "required": ["n"], "properties": {"n": {"type": "number"}}}
+            ]
+        }));
+        assert_eq!(
+            r.minimal,
+            json!({"id": "00000000-0000-4000-8000-000000000000", "n": 0})
+        );
+    }
+
+    #[test]
+    fn one_of_prefers_non_null_variant() {
+        let r = examples(json!({"oneOf": [{"type": "null"}, {"type": "string"}]}));
+        assert_eq!(r.full, json!("string"));
+        let r = examples(json!({"type": ["null", "boolean"]}));
+        assert_eq!(r.full, json!(false));
+    }
+
+    #[test]
+    fn recursive_schema_stops_at_max_depth() {
+        let schema = json!({
+            "$defs": {"node": {"type": "object", "required": ["child"], "properties": {"child": {"$ref": "#/$defs/node"}}}},
+            "$ref": "#/$defs/node"
+        });
+        let opts = ExampleOptions {
+            max_depth: Some(3),
+            ..Default::default()
+        };
+        let r = generate_examples(&schema, &opts);
+        assert!(r.minimal.get("child").is_some());
+        assert!(serde_json::to_string(&r.minimal).unwrap().len() < 200);
+    }
+}
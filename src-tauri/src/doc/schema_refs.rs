@@ -0,0 +1,764 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Component, Path as FsPath, PathBuf};
+use std::rc::Rc;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::types::{DocError, DocResult};
+
+const MAX_REF_DEPTH: usize = 64;
+const DATA_KEYWORDS: &[&str] = &["const", "default", "enum", "example", "examples"];
+const NAME_MAP_KEYWORDS: &[&str] = &[
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+    "patternProperties",
+    "properties",
+];
+
+pub type Fetcher<'a> = &'a dyn Fn(&str) -> Result<String, String>;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefResolution {
+    pub schema: Value,
+    pub documents: Vec<String>,
+    pub cycles: Vec<String>,
+}
+
+pub fn resolve_schema_refs(
+    schema: Value,
+    base_path: Option<&str>,
+    fetch: Fetcher<'_>,
+) -> DocResult<RefResolution> {
+    let mut resolver = Resolver::new(schema, base_path, fetch);
+    let root = resolver.load(&Source::Root)?;
+    let mut stack = Vec::new();
+    let schema = resolver.deref(&root, &Source::Root, &mut stack, false)?;
+    Ok(RefResolution {
+        schema,
+        documents: resolver.loaded,
+        cycles: resolver.cycles,
+    })
+}
+
+pub fn bundle_schema(
+    schema: Value,
+    base_path: Option<&str>,
+    fetch: Fetcher<'_>,
+) -> DocResult<RefResolution> {
+    Resolver::new(schema, base_path, fetch).bundle()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Source {
+    Root,
+    File(PathBuf),
+    Url(String),
+}
+
+impl Source {
+    fn label(&self) -> String {
+        match self {
+            Source::Root => "#".into(),
+            Source::File(p) => p.to_string_lossy().into_owned(),
+            Source::Url(u) => u.clone(),
+        }
+    }
+}
+
+enum Base {
+    None,
+    Dir(PathBuf),
+    Url(String),
+}
+
+struct BundleCtx {
+    defs_key: &'static str,
+    taken: HashSet<String>,
+    names: HashMap<Source, String>,
+    queue: VecDeque<Source>,
+}
+
+impl BundleCtx {
+    fn name_for(&mut self, src: &Source) -> String {
+        if let Some(name) = self.names.get(src) {
+            return name.clone();
+        }
+        let base = def_name_base(src);
+        let mut name = base.clone();
+        let mut n = 2;
+        while self.taken.contains(&name) {
+            name = format!("{base}_{n}");
+            n += 1;
+        }
+        self.taken.insert(name.clone());
+        self.names.insert(src.clone(), name.clone());
+        self.queue.push_back(src.clone());
+        name
+    }
+}
+
+struct Resolver<'a> {
+    root_base: Base,
+    root_file: Option<PathBuf>,
+    fetch: Fetcher<'a>,
+    docs: HashMap<Source, Rc<Value>>,
+    loaded: Vec<String>,
+    cycles: Vec<String>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(schema: Value, base_path: Option<&str>, fetch: Fetcher<'a>) -> Self {
+        let base_path = base_path.map(str::trim).filter(|b| !b.is_empty());
+        let root_id = schema
+            .get("$id")
+            .and_then(Value::as_str)
+            .filter(|id| is_url(id))
+            .map(str::to_string);
+        let (root_base, root_file) = match (base_path, root_id) {
+            (Some(b), _) if is_url(b) => (Base::Url(b.to_string()), None),
+            (Some(b), _) => {
+                let p = PathBuf::from(strip_file_scheme(b));
+                if p.is_dir() {
+                    (Base::Dir(normalize(&p)), None)
+                } else {
+                    let file = normalize(&p);
+                    let dir = file.parent().map(FsPath::to_path_buf).unwrap_or_default();
+                    (Base::Dir(dir), Some(file))
+                }
+            }
+            (None, Some(id)) => (Base::Url(id), None),
+            (None, None) => (Base::None, None),
+        };
+        let mut docs = HashMap::new();
+        docs.insert(Source::Root, Rc::new(schema));
+        Self {
+            root_base,
+            root_file,
+            fetch,
+            docs,
+            loaded: Vec::new(),
+            cycles: Vec::new(),
+        }
+    }
+
+    fn locate(&self, from: &Source, reference: &str) -> DocResult<(Source, String)> {
+        let (doc_part, fragment) = match reference.split_once('#') {
+            Some((d, f)) => (d, percent_decode(f)),
+            None => (reference, String::new()),
+        };
+        if doc_part.is_empty() {
+            return Ok((from.clone(), fragment));
+        }
+        let target = if is_url(doc_part) {
+            Source::Url(doc_part.to_string())
+        } else {
+            let rel = strip_file_scheme(doc_part);
+            match (from, &self.root_base) {
+                (Source::Url(u), _) | (Source::Root, Base::Url(u)) => Source::Url(join_url(u, rel)),
+                (Source::File(p), _) => {
+                    let dir = p.parent().unwrap_or_else(|| FsPath::new(""));
+                    Source::File(normalize(&dir.join(rel)))
+                }
+                (Source::Root, Base::Dir(d)) => Source::File(normalize(&d.join(rel))),
+                (Source::Root, Base::None) => {
+                    let p = PathBuf::from(rel);
+                    if !p.is_absolute() {
+                        return Err(DocError::Schema(format!(
+                            "cannot resolve relative $ref {reference:?} without a base path"
+                        )));
+                    }
+                    Source::File(normalize(&p))
+                }
+            }
+        };
+        let target = match target {
+            Source::File(p) if self.root_file.as_deref() == Some(p.as_path()) => Source::Root,
+            other => other,
+        };
+        Ok((target, fragment))
+    }
+
+    fn load(&mut self, src: &Source) -> DocResult<Rc<Value>> {
+        if let Some(doc) = self.docs.get(src) {
+            return Ok(Rc::clone(doc));
+        }
+        let text = match src {
+            Source::Root => return Err(DocError::Schema("root schema missing".into())),
+            Source::File(p) => std::fs::read_to_string(p)
+                .map_err(|e| DocError::Schema(format!("{}: {e}", p.display())))?,
+            Source::Url(u) => (self.fetch)(u).map_err(DocError::Schema)?,
+        };
+        let value = parse_schema_text(&text)
+            .map_err(|e| DocError::Schema(format!("{}: {e}", src.label())))?;
+        let doc = Rc::new(value);
+        self.docs.insert(src.clone(), Rc::clone(&doc));
+        self.loaded.push(src.label());
+        Ok(doc)
+    }
+
+    fn deref(
+        &mut self,
+        value: &Value,
+        src: &Source,
+        stack: &mut Vec<(Source, String)>,
+        names: bool,
+    ) -> DocResult<Value> {
+        match value {
+            Value::Object(map) => {
+                if !names {
+                    if let Some(Value::String(reference)) = map.get("$ref") {
+                        return self.deref_ref(map, reference, src, stack);
+                    }
+                }
+                Ok(Value::Object(self.deref_members(map, src, stack, names)?))
+            }
+            Value::Array(items) => items
+                .iter()
+                .map(|v| self.deref(v, src, stack, false))
+                .collect::<DocResult<Vec<_>>>()
+                .map(Value::Array),
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn deref_members(
+        &mut self,
+        map: &Map<String, Value>,
+        src: &Source,
+        stack: &mut Vec<(Source, String)>,
+        names: bool,
+    ) -> DocResult<Map<String, Value>> {
+        let mut out = Map::with_capacity(map.len());
+        for (k, v) in map {
+            if !names && k == "$ref" && v.is_string() {
+                continue;
+            }
+            let nv = if !names && DATA_KEYWORDS.contains(&k.as_str()) {
+                v.clone()
+            } else {
+                let child_names = !names && NAME_MAP_KEYWORDS.contains(&k.as_str());
+                self.deref(v, src, stack, child_names)?
+            };
+            out.insert(k.clone(), nv);
+        }
+        Ok(out)
+    }
+
+    fn deref_ref(
+        &mut self,
+        map: &Map<String, Value>,
+        reference: &str,
+        src: &Source,
+        stack: &mut Vec<(Source, String)>,
+    ) -> DocResult<Value> {
+        let key = self.locate(src, reference)?;
+        if stack.contains(&key) {
+            if !self.cycles.iter().any(|c| c == reference) {
+                self.cycles.push(reference.to_string());
+            }
+            return Ok(Value::Object(map.clone()));
+        }
+        if stack.len() >= MAX_REF_DEPTH {
+            return Err(DocError::Schema(format!(
+                "$ref chain deeper than {MAX_REF_DEPTH} at {reference:?}"
+            )));
+        }
+        let doc = self.load(&key.0)?;
+        let target = resolve_fragment(&doc, &key.1)
+            .ok_or_else(|| DocError::Schema(format!("unresolvable $ref {reference:?}")))?;
+        let target_src = key.0.clone();
+        stack.push(key);
+        let resolved = self.deref(target, &target_src, stack, false);
+        stack.pop();
+        let resolved = resolved?;
+
+        let siblings = self.deref_members(map, src, stack, false)?;
+        if siblings.is_empty() {
+            return Ok(resolved);
+        }
+        match resolved {
+            Value::Object(mut obj) if !siblings.keys().any(|k| obj.contains_key(k)) => {
+                obj.extend(siblings);
+                Ok(Value::Object(obj))
+            }
+            other => {
+                let mut obj = siblings;
+                match obj.get_mut("allOf") {
+                    Some(Value::Array(all)) => all.push(other),
+                    _ => {
+                        obj.insert("allOf".into(), Value::Array(vec![other]));
+                    }
+                }
+                Ok(Value::Object(obj))
+            }
+        }
+    }
+
+    fn bundle(mut self) -> DocResult<RefResolution> {
+        let root = self.load(&Source::Root)?;
+        let defs_key = if root.get("$defs").is_none() && root.get("definitions").is_some() {
+            "definitions"
+        } else {
+            "$defs"
+        };
+        let taken = root
+            .get(defs_key)
+            .and_then(Value::as_object)
+            .map(|m| m.keys().cloned().collect())
+            .unwrap_or_default();
+        let mut ctx = BundleCtx {
+            defs_key,
+            taken,
+            names: HashMap::new(),
+            queue: VecDeque::new(),
+        };
+
+        let mut out = self.rewrite(&root, &Source::Root, &mut ctx, false)?;
+        let mut defs = Map::new();
+        while let Some(src) = ctx.queue.pop_front() {
+            let doc = self.load(&src)?;
+            let mut body = self.rewrite(&doc, &src, &mut ctx, false)?;
+            if let Value::Object(m) = &mut body {
+                m.remove("$id");
+                m.remove("$schema");
+                if m.get("id").is_some_and(Value::is_string) {
+                    m.remove("id");
+                }
+            }
+            defs.insert(ctx.names[&src].clone(), body);
+        }
+        if !defs.is_empty() {
+            match &mut out {
+                Value::Object(m) => {
+                    let slot = m
+                        .entry(defs_key)
+                        .or_insert_with(|| Value::Object(Map::new()));
+                    if let Value::Object(existing) = slot {
+                        existing.extend(defs);
+                    }
+                }
+                _ => {
+                    return Err(DocError::Schema(
+                        "cannot bundle external $refs into a boolean root schema".into(),
+                    ))
+                }
+            }
+        }
+        Ok(RefResolution {
+            schema: out,
+            documents: self.loaded,
+            cycles: Vec::new(),
+        })
+    }
+
+    fn rewrite(
+        &mut self,
+        value: &Value,
+        src: &Source,
+        ctx: &mut BundleCtx,
+        names: bool,
+    ) -> DocResult<Value> {
+        match value {
+            Value::Object(map) => {
+                let mut out = Map::with_capacity(map.len());
+                for (k, v) in map {
+                    let nv = if !names && k == "$ref" {
+                        match v.as_str() {
+                            Some(r) => Value::String(self.bundled_ref(r, src, ctx)?),
+                            None => v.clone(),
+                        }
+                    } else if !names && DATA_KEYWORDS.contains(&k.as_str()) {
+                        v.clone()
+                    } else {
+                        let child_names = !names && NAME_MAP_KEYWORDS.contains(&k.as_str());
+                        self.rewrite(v, src, ctx, child_names)?
+                    };
+                    out.insert(k.clone(), nv);
+                }
+                Ok(Value::Object(out))
+            }
+            Value::Array(items) => items
+                .iter()
+                .map(|v| self.rewrite(v, src, ctx, false))
+                .collect::<DocResult<Vec<_>>>()
+                .map(Value::Array),
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn bundled_ref(
+        &mut self,
+        reference: &str,
+        src: &Source,
+        ctx: &mut BundleCtx,
+    ) -> DocResult<String> {
+        let (target, fragment) = self.locate(src, reference)?;
+        let doc = self.load(&target)?;
+        if resolve_fragment(&doc, &fragment).is_none() {
+            return Err(DocError::Schema(format!("unresolvable $ref {reference:?}")));
+        }
+        let pointer = match target {
+            Source::Root if !fragment.is_empty() && !fragment.starts_with('/') => {
+                return Ok(format!("#{}", encode_fragment(&fragment)));
+            }
+            Source::Root => fragment,
+            other => {
+                let pointer = if fragment.is_empty() || fragment.starts_with('/') {
+                    fragment
+                } else {
+                    anchor_pointer(&doc, &fragment, &mut String::new()).unwrap_or_default()
+                };
+                format!("/{}/{}{pointer}", ctx.defs_key, ctx.name_for(&other))
+            }
+        };
+        Ok(format!("#{}", encode_fragment(&pointer)))
+    }
+}
+
+pub fn parse_schema_text(text: &str) -> Result<Value, String> {
+    serde_json::from_str::<Value>(text).or_else(|json_err| {
+        serde_yaml_ng::from_str::<Value>(text).map_err(|_| json_err.to_string())
+    })
+}
+
+fn resolve_fragment<'v>(doc: &'v Value, fragment: &str) -> Option<&'v Value> {
+    if fragment.is_empty() {
+        Some(doc)
+    } else if fragment.starts_with('/') {
+        doc.pointer(fragment)
+    } else {
+        doc.pointer(&anchor_pointer(doc, fragment, &mut String::new())?)
+    }
+}
+
+fn anchor_pointer(value: &Value, name: &str, at: &mut String) -> Option<String> {
+    let len = at.len();
+    let found = match value {
+        Value::Object(map) => {
+            let anchor = map.get("$anchor").and_then(Value::as_str) == Some(name);
+            let legacy = map
+                .get("$id")
+                .and_then(Value::as_str)
+                .and_then(|id| id.strip_prefix('#'))
+                == Some(name);
+            if anchor || legacy {
+                return Some(at.clone());
+            }
+            map.iter()
+                .filter(|(k, _)| !DATA_KEYWORDS.contains(&k.as_str()))
+                .find_map(|(k, v)| {
+                    at.truncate(len);
+                    at.push('/');
+                    at.push_str(&k.replace('~', "~0").replace('/', "~1"));
+                    anchor_pointer(v, name, at)
+                })
+        }
+        Value::Array(items) => items.iter().enumerate().find_map(|(i, v)| {
+            at.truncate(len);
+            at.push_str(&format!("/{i}"));
+            anchor_pointer(v, name, at)
+        }),
+        _ => None,
+    };
+    at.truncate(len);
+    found
+}
+
+pub fn is_url(s: &str) -> bool {
+    s.starts_with("http://") || s.starts_with("https://")
+}
+
+fn strip_file_scheme(s: &str) -> &str {
+    s.strip_prefix("file://").unwrap_or(s)
+}
+
+pub fn normalize(p: &FsPath) -> PathBuf {
+    std::fs::canonicalize(p).unwrap_or_else(|_| {
+        let mut out = PathBuf::new();
+        for comp in p.components() {
+            match comp {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    out.pop();
+                }
+                other => out.push(other.as_os_str()),
+            }
+        }
+        out
+    })
+}
+
+fn join_url(base: &str, rel: &str) -> String {
+    let scheme_end = base.find("://").map(|i| i + 3).unwrap_or(0);
+    let authority_end = base[scheme_end..]
+        .find('/')
+        .map(|i| scheme_end + i)
+        .unwrap_or(base.len());
+    let path = match rel.strip_prefix('/') {
+        Some(abs) => format!("/{abs}"),
+        None => {
+            let base_path = base[authority_end..].split(['?', '#']).next().unwrap_or("");
+            let dir = match base_path.rfind('/') {
+                Some(i) => &base_path[..=i],
+                None => "/",
+            };
+            format!("{dir}{rel}")
+        }
+    };
+    let mut segments: Vec<&str> = Vec::new();
+    for seg in path.split('/') {
+        match seg {
+            "." => {}
+            ".." => {
+                if segments.len() > 1 {
+                    segments.pop();
+                }
+            }
+            s => segments.push(s),
+        }
+    }
+    format!("{}{}", &base[..authority_end], segments.join("/"))
+}
+
+fn def_name_base(src: &Source) -> String {
+    let raw = match src {
+        Source::Root => String::new(),
+        Source::File(p) => p
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        Source::Url(u) => {
+            let last = u
+                .split(['?', '#'])
+                .next()
+                .unwrap_or("")
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or("");
+            FsPath::new(last)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        }
+    };
+    let name: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "schema".into()
+    } else {
+        name
+    }
+}
+
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn encode_fragment(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@/?".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+    use std::cell::Cell;
+
+    fn offline(url: &str) -> Result<String, String> {
+        Err(format!("offline: {url}"))
+    }
+
+    #[test]
+    fn local_ref_is_inlined() {
+        let schema = json!({
+            "$defs": {"name": {"type": "string"}},
+            "properties": {"a": {"$ref": "#/$defs/name"}}
+        });
+        let r = resolve_schema_refs(schema, None, &offline).unwrap();
+        assert_eq!(r.schema["properties"]["a"], json!({"type": "string"}));
+        assert!(r.documents.is_empty());
+    }
+
+    #[test]
+    fn cross_file_ref_loads_relative_to_base() {
+        let dir = TempDir::new("refs-cross");
+        std::fs::write(
+            dir.join("common.json"),
+            r#"{"$defs": {"id": {"type": "integer"}}}"#,
+        )
+        .unwrap();
+        let schema = json!({"properties": {"id": {"$ref": "common.json#/$defs/id"}}});
+        let r = resolve_schema_refs(schema, dir.to_str(), &offline).unwrap();
+        assert_eq!(r.schema["properties"]["id"], json!({"type": "integer"}));
+        assert_eq!(r.documents.len(), 1);
+    }
+
+    #[test]
+    fn recursive_ref_is_reported_as_cycle() {
+        let schema = json!({
+            "$defs": {"node": {"properties": {"next": {"$ref": "#/$defs/node"}}}},
+            "$ref": "#/$defs/node"
+        });
+        let r = resolve_schema_refs(schema, None, &offline).unwrap();
+        assert_eq!(r.cycles, vec!["#/$defs/node".to_string()]);
+        assert_eq!(
+            r.schema["properties"]["next"],
+            json!({"$ref": "#/$defs/node"})
+        );
+    }
+
+    #[test]
+    fn sibling_keywords_merge_into_target() {
+        let schema = json!({
+            "$defs": {"s": {"type": "string"}},
+            "properties": {"a": {"$ref": "#/$defs/s", "description": "hi"}}
+        });
+        let r = resolve_schema_refs(schema, None, &offline).unwrap();
+        assert_eq!(
+            r.schema["properties"]["a"],
+            json!({"type": "string", "description": "hi"})
+        );
+    }
+
+    #[test]
+    fn ref_like_data_is_left_alone() {
+        let schema = json!({"default": {"$ref": "#/nope"}, "type": "object"});
+        let r = resolve_schema_refs(schema.clone(), None, &offline).unwrap();
+        assert_eq!(r.schema, schema);
+    }
+
+    #[test]
+    fn remote_documents_are_fetched_once_per_call() {
+        let calls = Cell::new(0);
+        let fetch = |_: &str| -> Result<String, String> {
+            calls.set(calls.get() + 1);
+            Ok(r#"{"$defs": {"flag": {"type": "boolean"}}}"#.to_string())
+        };
+        let url = "https://schemas.example.test/pandia-refs-cache.json";
+        let flag = json!({"$ref": format!("{url}#/$defs/flag")});
+        for _ in 0..2 {
+            let schema = json!({"items": [flag, flag]});
+            let r = resolve_schema_refs(schema, None, &fetch).unwrap();
+            assert_eq!(r.schema["items"][1], json!({"type": "boolean"}));
+        }
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn unresolvable_and_unanchored_refs_error() {
+        let err = resolve_schema_refs(json!({"$ref": "#/missing"}), None, &offline).unwrap_err();
+        assert!(matches!(err, DocError::Schema(_)));
+        let err = resolve_schema_refs(json!({"$ref": "other.json"}), None, &offline).unwrap_err();
+        assert!(err.to_string().contains("base path"));
+    }
+
+    #[test]
+    fn bundle_embeds_external_documents_under_defs() {
+        let dir = TempDir::new("refs-bundle");
+        std::fs::write(
+            dir.join("common.json"),
+            r##"{
+                "$id": "https://example.test/common.json",
+                "$defs": {
+                    "id": {"$ref": "#/$defs/positive"},
+                    "positive": {"type": "integer", "minimum": 1}
+                }
+            }"##,
+        )
+        .unwrap();
+        let schema = json!({
+            "type": "object",
+            "properties": {"id": {"$ref": "common.json#/$defs/id"}}
+        });
+        let r = bundle_schema(schema, dir.to_str(), &offline).unwrap();
+        let out = &r.schema;
+        assert_eq!(
+            out["properties"]["id"]["$ref"],
+            json!("#/$defs/common/$defs/id")
+        );
+        assert_eq!(
+            out["$defs"]["common"]["$defs"]["id"]["$ref"],
+            json!("#/$defs/common/$defs/positive")
+        );
+        assert!(out["$defs"]["common"].get("$id").is_none());
+
+        let validator = jsonschema::validator_for(out).unwrap();
+        assert!(validator.is_valid(&json!({"id": 3})));
+        assert!(!validator.is_valid(&json!({"id": 0})));
+    }
+
+    #[test]
+    fn bundle_embeds_anchor_targets_as_pointers() {
+        let fetch = |_: &str| -> Result<String, String> {
+            Ok(r#"{"$defs": {"flag": {"$anchor": "flag", "type": "boolean"}}}"#.to_string())
+        };
+        let schema = json!({"items": {"$ref": "https://example.test/types.json#flag"}});
+        let r = bundle_schema(schema, None, &fetch).unwrap();
+        assert_eq!(r.schema["items"]["$ref"], json!("#/$defs/types/$defs/flag"));
+        let validator = jsonschema::validator_for(&r.schema).unwrap();
+        assert!(validator.is_valid(&json!([true])));
+        assert!(!validator.is_valid(&json!([1])));
+    }
+
+    #[test]
+    fn bundle_keeps_local_refs_and_existing_definitions() {
+        let schema = json!({
+            "definitions": {"a": {"type": "string"}},
+            "items": {"$ref": "#/definitions/a"}
+        });
+        let r = bundle_schema(schema.clone(), None, &offline).unwrap();
+        assert_eq!(r.schema, schema);
+    }
+
+    #[test]
+    fn join_url_resolves_relative_segments() {
+        assert_eq!(
+            join_url("https://x.test/a/b/root.json", "../c/d.json"),
+            "https://x.test/a/c/d.json"
+        );
+        assert_eq!(
+            join_url("https://x.test/a/root.json", "/abs.json"),
+            "https://x.test/abs.json"
+        );
+        assert_eq!(
+            join_url("https://x.test", "d.json"),
+            "https://x.test/d.json"
+        );
+    }
+}
@@ -35,8 +35,14 @@ pub fn validate(
 ) -> Result<SchemaValidationResult, SchemaCompileError> {
     let schema_json: Value = serde_json::from_str(schema_text.trim())
         .map_err(|e| SchemaCompileError::Parse(e.to_string()))?;
+    validate_value(instance, &schema_json)
+}
 
-    let validator = jsonschema::validator_for(&schema_json)
+pub fn validate_value(
+    instance: &Value,
+    schema_json: &Value,
+) -> Result<SchemaValidationResult, SchemaCompileError> {
+    let validator = jsonschema::validator_for(schema_json)
         .map_err(|e| SchemaCompileError::Compile(e.to_string()))?;
 
     let mut errors: Vec<SchemaError> = Vec::new();
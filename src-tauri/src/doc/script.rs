@@ -0,0 +1,433 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+const MAX_TIMEOUT_MS: u64 = 60_000;
+const MAX_OPERATIONS: u64 = 200_000_000;
+const MAX_OUTPUT_LINES: usize = 1_000;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptBindings {
+    #[serde(default)]
+    pub selection: Option<Path>,
+    #[serde(default)]
+    pub vars: serde_json::Map<String, Value>,
+    #[serde(default)]
+    pub apply: bool,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptOutcome {
+    pub value: Option<Value>,
+    pub document: Option<Value>,
+    pub changed: bool,
+    pub output: Vec<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptResult {
+    pub value: Option<Value>,
+    pub document: Option<Value>,
+    pub changed: bool,
+    pub output: Vec<String>,
+    pub applied: bool,
+    pub version: Option<u64>,
+    pub duration_ms: u64,
+}
+
+type FnResult = Result<Dynamic, Box<EvalAltResult>>;
+
+pub fn to_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::Null => Dynamic::UNIT,
+        Value::Bool(b) => Dynamic::from(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Dynamic::from(i),
+            None => Dynamic::from(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => Dynamic::from(s.clone()),
+        Value::Array(items) => Dynamic::from_array(items.iter().map(to_dynamic).collect()),
+        Value::Object(map) => Dynamic::from_map(
+            map.iter()
+                .map(|(k, v)| (k.as_str().into(), to_dynamic(v)))
+                .collect(),
+        ),
+    }
+}
+
+pub fn from_dynamic(value: Dynamic) -> Value {
+    if value.is_unit() {
+        return Value::Null;
+    }
+    if let Ok(b) = value.as_bool() {
+        return Value::Bool(b);
+    }
+    if let Ok(i) = value.as_int() {
+        return Value::from(i);
+    }
+    if let Ok(f) = value.as_float() {
+        return serde_json::Number::from_f64(f)
+            .map(Value::Number)
+            .unwrap_or(Value::Null);
+    }
+    if let Ok(c) = value.as_char() {
+        return Value::String(c.to_string());
+    }
+    if value.is_string() {
+        return Value::String(value.into_string().unwrap_or_default());
+    }
+    if value.is_array() {
+        let items: Array = value.into_array().unwrap_or_default();
+        return Value::Array(items.into_iter().map(from_dynamic).collect());
+    }
+    if value.is_map() {
+        let map: Map = value.try_cast().unwrap_or_default();
+        return Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k.to_string(), from_dynamic(v)))
+                .collect(),
+        );
+    }
+    Value::String(value.to_string())
+}
+
+fn same_dynamic(a: &Dynamic, b: &Dynamic) -> bool {
+    if a.type_id() != b.type_id() {
+        return false;
+    }
+    if let (Some(x), Some(y)) = (a.read_lock::<Array>(), b.read_lock::<Array>()) {
+        return x.len() == y.len() && x.iter().zip(y.iter()).all(|(p, q)| same_dynamic(p, q));
+    }
+    if let (Some(x), Some(y)) = (a.read_lock::<Map>(), b.read_lock::<Map>()) {
+        return x.len() == y.len()
+            && x.iter()
+                .all(|(k, v)| y.get(k).is_some_and(|w| same_dynamic(v, w)));
+    }
+    if let (Ok(x), Ok(y)) = (a.as_float(), b.as_float()) {
+        return x.to_bits() == y.to_bits();
+    }
+    a.to_string() == b.to_string()
+}
+
+pub fn restore_original(value: Value, original: &Value) -> Value {
+    match (value, original) {
+        (Value::Number(n), Value::Number(orig)) => {
+            let round_trip = from_dynamic(to_dynamic(original));
+            if matches!(&round_trip, Value::Number(r) if *r == n) {
+                Value::Number(orig.clone())
+            } else {
+                Value::Number(n)
+            }
+        }
+        (Value::Object(mut map), Value::Object(orig)) => {
+            let mut out = serde_json::Map::with_capacity(map.len());
+            for (k, v) in orig {
+                if let Some(new) = map.remove(k) {
+                    out.insert(k.clone(), restore_original(new, v));
+                }
+            }
+            out.extend(map);
+            Value::Object(out)
+        }
+        (Value::Array(items), Value::Array(orig)) => Value::Array(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| match orig.get(i) {
+                    Some(o) => restore_original(v, o),
+                    None => v,
+                })
+                .collect(),
+        ),
+        (value, _) => value,
+    }
+}
+
+fn pointer_segments(pointer: &str) -> Result<Vec<String>, Box<EvalAltResult>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!("path must be a JSON pointer starting with '/': {pointer}").into());
+    };
+    Ok(rest
+        .split('/')
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn child_mut<'a>(value: &'a mut Value, seg: &str, create: bool) -> Option<&'a mut Value> {
+    match value {
+        Value::Object(map) => {
+            if create && !map.contains_key(seg) {
+                map.insert(seg.to_string(), Value::Null);
+            }
+            map.get_mut(seg)
+        }
+        Value::Array(items) => {
+            if create && seg == "-" {
+                items.push(Value::Null);
+                return items.last_mut();
+            }
+            items.get_mut(seg.parse::<usize>().ok()?)
+        }
+        _ => None,
+    }
+}
+
+fn get_fn(target: Dynamic, pointer: &str) -> FnResult {
+    let value = from_dynamic(target);
+    let mut cur = &value;
+    for seg in pointer_segments(pointer)? {
+        let next = match cur {
+            Value::Object(map) => map.get(&seg),
+            Value::Array(items) => seg.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        };
+        match next {
+            Some(v) => cur = v,
+            None => return Ok(Dynamic::UNIT),
+        }
+    }
+    Ok(to_dynamic(cur))
+}
+
+fn set_fn(target: Dynamic, pointer: &str, new: Dynamic) -> FnResult {
+    let mut value = from_dynamic(target);
+    let segments = pointer_segments(pointer)?;
+    let mut cur = &mut value;
+    for seg in &segments {
+        cur = child_mut(cur, seg, true).ok_or_else(|| format!("cannot set {pointer}"))?;
+    }
+    *cur = from_dynamic(new);
+    Ok(to_dynamic(&value))
+}
+
+fn query_fn(target: Dynamic, pattern: &str) -> FnResult {
+    let value = from_dynamic(target);
+    let segments = pointer_segments(pattern)?;
+    let mut matches = vec![&value];
+    for seg in &segments {
+        matches = matches
+            .into_iter()
+            .flat_map(|v| -> Vec<&Value> {
+                match (v, seg.as_str()) {
+                    (Value::Object(map), "*") => map.values().collect(),
+                    (Value::Array(items), "*") => items.iter().collect(),
+                    (Value::Object(map), key) => map.get(key).into_iter().collect(),
+                    (Value::Array(items), idx) => idx
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| items.get(i))
+                        .into_iter()
+                        .collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+    Ok(Dynamic::from_array(
+        matches.into_iter().map(to_dynamic).collect(),
+    ))
+}
+
+fn path_dynamic(path: &Path) -> Dynamic {
+    Dynamic::from_array(
+        path.0
+            .iter()
+            .map(|seg| match seg {
+                PathSegment::Key(k) => Dynamic::from(k.clone()),
+                PathSegment::Index(i) => Dynamic::from(*i as i64),
+            })
+            .collect(),
+    )
+}
+
+pub fn eval(
+    source: &str,
+    input: &Value,
+    selection: &Path,
+    bindings: &ScriptBindings,
+) -> DocResult<ScriptOutcome> {
+    let started = Instant::now();
+    let timeout = Duration::from_millis(
+        bindings
+            .timeout_ms
+            .unwrap_or(DEFAULT_TIMEOUT_MS)
+            .clamp(1, MAX_TIMEOUT_MS),
+    );
+    let output = Rc::new(RefCell::new(Vec::<String>::new()));
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_progress(move |_| {
+        (started.elapsed() > timeout).then(|| Dynamic::from("script timed out"))
+    });
+    let sink = output.clone();
+    engine.on_print(move |line| {
+        let mut out = sink.borrow_mut();
+        if out.len() < MAX_OUTPUT_LINES {
+            out.push(line.to_string());
+        }
+    });
+    let sink = output.clone();
+    engine.on_debug(move |line, _, _| {
+        let mut out = sink.borrow_mut();
+        if out.len() < MAX_OUTPUT_LINES {
+            out.push(line.to_string());
+        }
+    });
+    engine.register_fn("get", get_fn);
+    engine.register_fn("set", set_fn);
+    engine.register_fn("query", query_fn);
+    engine.register_fn("to_json", |v: Dynamic| from_dynamic(v).to_string());
+    engine.register_fn("parse_json", |text: &str| -> FnResult {
+        serde_json::from_str::<Value>(text)
+            .map(|v| to_dynamic(&v))
+            .map_err(|e| e.to_string().into())
+    });
+
+    let received = to_dynamic(input);
+    let mut scope = Scope::new();
+    scope.push("doc", received.clone());
+    scope.push_constant("path", path_dynamic(selection));
+    for (name, value) in &bindings.vars {
+        scope.push_constant(name.as_str(), to_dynamic(value));
+    }
+
+    let result = engine
+        .eval_with_scope::<Dynamic>(&mut scope, source)
+        .map_err(|e| DocError::Edit(format!("script error: {e}")))?;
+
+    let doc = scope.get_value::<Dynamic>("doc").unwrap_or(Dynamic::UNIT);
+    let changed = !same_dynamic(&doc, &received);
+    let document = changed.then(|| restore_original(from_dynamic(doc), input));
+    let value = (!result.is_unit()).then(|| restore_original(from_dynamic(result), input));
+    let output = output.borrow().clone();
+    Ok(ScriptOutcome {
+        value,
+        document,
+        changed,
+        output,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(source: &str, input: Value) -> ScriptOutcome {
+        eval(source, &input, &Path::root(), &ScriptBindings::default()).unwrap()
+    }
+
+    #[test]
+    fn final_expression_becomes_value_without_touching_doc() {
+        let out = run("doc.items.len()", json!({"items": [1, 2, 3]}));
+        assert_eq!(out.value, Some(json!(3)));
+        assert_eq!(out.document, None);
+        assert!(!out.changed);
+    }
+
+    #[test]
+    fn untouched_numbers_keep_their_original_text() {
+        let input: Value =
+            serde_json::from_str(r#"{"price": 1.50, "id": 123456789012345678901234, "n": 1}"#)
+                .unwrap();
+        let out = run("doc.price", input.clone());
+        assert!(!out.changed && out.document.is_none());
+
+        let out = run("doc.n = 2;", input);
+        let doc = out.document.unwrap();
+        assert_eq!(doc["price"].to_string(), "1.50");
+        assert_eq!(doc["id"].to_string(), "123456789012345678901234");
+        assert_eq!(doc["n"], json!(2));
+    }
+
+    #[test]
+    fn mutating_doc_is_reported_with_original_key_order() {
+        let out = run(
+            "doc.zeta = 0; doc.alpha += 1;",
+            json!({"zeta": 1, "alpha": 1}),
+        );
+        let v = out.document.unwrap();
+        assert_eq!(v, json!({"zeta": 0, "alpha": 2}));
+        let keys: Vec<&String> = v.as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["zeta", "alpha"]);
+        assert!(out.changed);
+    }
+
+    #[test]
+    fn unchanged_doc_yields_no_value() {
+        let out = run("let x = 1;", json!({"a": 1}));
+        assert_eq!(out.value, None);
+        assert!(!out.changed);
+    }
+
+    #[test]
+    fn helpers_get_set_query() {
+        let input = json!({"users": [{"name": "a", "age": 1}, {"name": "b", "age": 2}]});
+        assert_eq!(
+            run(r#"get(doc, "/users/1/name")"#, input.clone()).value,
+            Some(json!("b"))
+        );
+        assert_eq!(
+            run(r#"query(doc, "/users/*/age")"#, input.clone()).value,
+            Some(json!([1, 2]))
+        );
+        let out = run(r#"doc = set(doc, "/users/-", #{name: "c"});"#, input);
+        assert_eq!(out.document.unwrap()["users"][2]["name"], "c");
+    }
+
+    #[test]
+    fn print_is_captured_and_vars_are_bound() {
+        let bindings = ScriptBindings {
+            vars: json!({"factor": 10}).as_object().unwrap().clone(),
+            ..Default::default()
+        };
+        let out = eval(
+            r#"print("hi"); doc * factor"#,
+            &json!(4),
+            &Path::root(),
+            &bindings,
+        )
+        .unwrap();
+        assert_eq!(out.output, vec!["hi"]);
+        assert_eq!(out.value, Some(json!(40)));
+    }
+
+    #[test]
+    fn runaway_scripts_time_out() {
+        let bindings = ScriptBindings {
+            timeout_ms: Some(50),
+            ..Default::default()
+        };
+        let err = eval("loop {}", &json!(null), &Path::root(), &bindings).unwrap_err();
+        assert!(matches!(err, DocError::Edit(_)));
+    }
+
+    #[test]
+    fn syntax_errors_are_edit_errors() {
+        let err = eval(
+            "let = ;",
+            &json!(null),
+            &Path::root(),
+            &ScriptBindings::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, DocError::Edit(_)));
+    }
+}
@@ -153,7 +153,7 @@ pub(crate) fn string_match_snippet(s: &str, needle: &str, case_sensitive: bool)
     Some(make_snippet(s, start, end.saturating_sub(start)))
 }
 
-fn map_lower_offset(s: &str, lower_offset: usize) -> usize {
+pub(crate) fn map_lower_offset(s: &str, lower_offset: usize) -> usize {
     let mut acc = 0;
     for (orig_byte, ch) in s.char_indices() {
         if acc >= lower_offset {
@@ -0,0 +1,560 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::Instant;
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use sonic_rs::FastStr;
+
+use super::jobs::CancelFlag;
+use super::search::{map_lower_offset, prepare, MatchField, SearchOptions};
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+const NO_PARENT: u32 = u32::MAX;
+const SNIPPET_CONTEXT: usize = 32;
+
+#[derive(Debug, Clone, Copy)]
+enum Segment {
+    Key(u32),
+    Index(u32),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    parent: u32,
+    segment: Segment,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    start: usize,
+    end: usize,
+    node: u32,
+    field: MatchField,
+    escaped: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSummary {
+    pub version: u64,
+    pub bytes: u64,
+    pub tokens: u32,
+    pub trigrams: u32,
+    pub build_ms: f64,
+    pub source_offsets: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexQueryOptions {
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub max_results: u32,
+}
+
+impl IndexQueryOptions {
+    pub fn with_query(self, query: String) -> SearchOptions {
+        SearchOptions {
+            query,
+            case_sensitive: self.case_sensitive,
+            max_results: self.max_results,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedHit {
+    pub path: Path,
+    pub match_field: MatchField,
+    pub offset: u64,
+    pub length: u32,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedSearchResult {
+    pub hits: Vec<IndexedHit>,
+    pub candidates: u32,
+    pub truncated: bool,
+    pub elapsed_ms: f64,
+}
+
+pub struct SearchIndex {
+    text: FastStr,
+    nodes: Vec<Node>,
+    tokens: Vec<Token>,
+    trigrams: HashMap<[u8; 3], Vec<u32>>,
+    summary: IndexSummary,
+    line_starts: OnceCell<Vec<u32>>,
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    nodes: Vec<Node>,
+    tokens: Vec<Token>,
+}
+
+impl Scanner<'_> {
+    fn skip_ws(&mut self) {
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> DocResult<()> {
+        self.skip_ws();
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn error(&self, message: &str) -> DocError {
+        DocError::Parse(format!("{message} at byte {}", self.pos))
+    }
+
+    fn child(&mut self, parent: u32, segment: Segment) -> u32 {
+        self.nodes.push(Node { parent, segment });
+        (self.nodes.len() - 1) as u32
+    }
+
+    fn string(&mut self, node: u32, field: MatchField) -> DocResult<u32> {
+        self.pos += 1;
+        let start = self.pos;
+        let mut escaped = false;
+        loop {
+            match self.peek() {
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    escaped = true;
+                    self.pos += 2;
+                }
+                Some(_) => self.pos += 1,
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+        self.tokens.push(Token {
+            start,
+            end: self.pos,
+            node,
+            field,
+            escaped,
+        });
+        self.pos += 1;
+        Ok((self.tokens.len() - 1) as u32)
+    }
+
+    fn value(&mut self, node: u32) -> DocResult<()> {
+        self.skip_ws();
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                self.skip_ws();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                loop {
+                    self.skip_ws();
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("expected object key"));
+                    }
+                    let key_node = self.nodes.len() as u32;
+                    let key = self.string(key_node, MatchField::Key)?;
+                    self.child(node, Segment::Key(key));
+                    self.expect(b':')?;
+                    self.value(key_node)?;
+                    self.skip_ws();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(());
+                        }
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                self.skip_ws();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                let mut index = 0u32;
+                loop {
+                    let item = self.child(node, Segment::Index(index));
+                    self.value(item)?;
+                    index += 1;
+                    self.skip_ws();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(());
+                        }
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some(b'"') => self.string(node, MatchField::Value).map(|_| ()),
+            Some(_) => {
+                while let Some(b) = self.peek() {
+                    if matches!(b, b',' | b']' | b'}') || b.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                Ok(())
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+}
+
+fn trigrams_of(text: &str) -> impl Iterator<Item = [u8; 3]> + '_ {
+    text.as_bytes().windows(3).map(|w| [w[0], w[1], w[2]])
+}
+
+impl SearchIndex {
+    pub fn build(
+        text: FastStr,
+        version: u64,
+        source_offsets: bool,
+        cancel: &CancelFlag,
+    ) -> DocResult<Self> {
+        let started = Instant::now();
+        let mut scanner = Scanner {
+            bytes: text.as_bytes(),
+            pos: 0,
+            nodes: Vec::new(),
+            tokens: Vec::new(),
+        };
+        scanner.value(NO_PARENT)?;
+        let Scanner { nodes, tokens, .. } = scanner;
+
+        let mut trigrams: HashMap<[u8; 3], Vec<u32>> = HashMap::new();
+        let mut seen: Vec<[u8; 3]> = Vec::new();
+        for (id, token) in tokens.iter().enumerate() {
+            if id % 4096 == 0 && cancel.is_cancelled() {
+                return Err(DocError::Cancelled);
+            }
+            let lower = token_text(&text, token).to_lowercase();
+            seen.clear();
+            seen.extend(trigrams_of(&lower));
+            seen.sort_unstable();
+            seen.dedup();
+            for gram in &seen {
+                trigrams.entry(*gram).or_default().push(id as u32);
+            }
+        }
+
+        let summary = IndexSummary {
+            version,
+            bytes: text.len() as u64,
+            tokens: tokens.len() as u32,
+            trigrams: trigrams.len() as u32,
+            build_ms: started.elapsed().as_secs_f64() * 1000.0,
+            source_offsets,
+        };
+        Ok(Self {
+            text,
+            nodes,
+            tokens,
+            trigrams,
+            summary,
+            line_starts: OnceCell::new(),
+        })
+    }
+
+    pub fn summary(&self) -> &IndexSummary {
+        &self.summary
+    }
+
+    pub fn version(&self) -> u64 {
+        self.summary.version
+    }
+
+    fn line_starts(&self) -> &[u32] {
+        self.line_starts.get_or_init(|| {
+            std::iter::once(0)
+                .chain(line_breaks(self.text.as_bytes()))
+                .collect()
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn line_of(&self, offset: u64) -> u32 {
+        self.line_starts()
+            .partition_point(|&s| s as u64 <= offset)
+            .max(1) as u32
+    }
+
+    pub fn line_count(&self) -> u32 {
+        self.line_starts().len() as u32
+    }
+
+    pub fn lines(&self, range: Range<u32>) -> Vec<&str> {
+        let starts = self.line_starts();
+        let text: &str = &self.text;
+        let end = (range.end as usize).min(starts.len());
+        (range.start as usize..end)
+            .map(|i| {
+                let from = starts[i] as usize;
+                let to = starts.get(i + 1).map_or(text.len(), |&s| s as usize - 1);
+                text[from..to].trim_end_matches('\r')
+            })
+            .collect()
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        let text = if self.summary.source_offsets {
+            0
+        } else {
+            self.text.len()
+        };
+        let trigrams: usize = self
+            .trigrams
+            .values()
+            .map(|ids| std::mem::size_of::<([u8; 3], Vec<u32>)>() + ids.len() * 4)
+            .sum();
+        let lines = self.line_starts.get().map_or(0, |s| s.len() * 4);
+        text + lines
+            + self.nodes.len() * std::mem::size_of::<Node>()
+            + self.tokens.len() * std::mem::size_of::<Token>()
+            + trigrams
+    }
+
+    fn candidates(&self, needle: &str) -> Vec<u32> {
+        let lower = needle.to_lowercase();
+        let mut lists: Vec<&Vec<u32>> = Vec::new();
+        for gram in trigrams_of(&lower) {
+            match self.trigrams.get(&gram) {
+                Some(list) => lists.push(list),
+                None => return Vec::new(),
+            }
+        }
+        if lists.is_empty() {
+            return (0..self.tokens.len() as u32).collect();
+        }
+        lists.sort_by_key(|l| l.len());
+        let mut result = lists[0].clone();
+        for list in &lists[1..] {
+            result.retain(|id| list.binary_search(id).is_ok());
+            if result.is_empty() {
+                break;
+            }
+        }
+        result
+    }
+
+    fn path_of(&self, mut node: u32) -> Path {
+        let mut segments = Vec::new();
+        while node != NO_PARENT {
+            let n = self.nodes[node as usize];
+            segments.push(match n.segment {
+                Segment::Key(key) => {
+                    PathSegment::Key(token_text(&self.text, &self.tokens[key as usize]))
+                }
+                Segment::Index(i) => PathSegment::Index(i),
+            });
+            node = n.parent;
+        }
+        segments.reverse();
+        Path(segments)
+    }
+
+    pub fn search(&self, opts: &SearchOptions, cancel: &CancelFlag) -> IndexedSearchResult {
+        let started = Instant::now();
+        let Some((needle, cap)) = prepare(opts) else {
+            return IndexedSearchResult {
+                hits: Vec::new(),
+                candidates: 0,
+                truncated: false,
+                elapsed_ms: 0.0,
+            };
+        };
+        let candidates = self.candidates(&needle);
+        let mut hits = Vec::new();
+        let mut truncated = false;
+        for (n, id) in candidates.iter().enumerate() {
+            if n % 4096 == 0 && cancel.is_cancelled() {
+                break;
+            }
+            let token = &self.tokens[*id as usize];
+            let text = token_text(&self.text, token);
+            let Some((start, end)) = find_match(&text, &needle, opts.case_sensitive) else {
+                continue;
+            };
+            if hits.len() >= cap {
+                truncated = true;
+                break;
+            }
+            let offset = if token.escaped {
+                token.start
+            } else {
+                token.start + start
+            };
+            hits.push(IndexedHit {
+                path: self.path_of(token.node),
+                match_field: token.field,
+                offset: offset as u64,
+                length: (end - start) as u32,
+                snippet: snippet(&text, start, end),
+            });
+        }
+        IndexedSearchResult {
+            hits,
+            candidates: candidates.len() as u32,
+            truncated,
+            elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+fn token_text(text: &str, token: &Token) -> String {
+    let raw = &text[token.start..token.end];
+    if !token.escaped {
+        return raw.to_string();
+    }
+    serde_json::from_str(&text[token.start - 1..token.end + 1]).unwrap_or_else(|_| raw.to_string())
+}
+
+fn find_match(text: &str, needle: &str, case_sensitive: bool) -> Option<(usize, usize)> {
+    if case_sensitive {
+        let start = text.find(needle)?;
+        return Some((start, start + needle.len()));
+    }
+    let lower = text.to_lowercase();
+    let lo = lower.find(needle)?;
+    Some((
+        map_lower_offset(text, lo),
+        map_lower_offset(text, lo + needle.len()),
+    ))
+}
+
+fn snippet(text: &str, start: usize, end: usize) -> String {
+    let mut from = start.saturating_sub(SNIPPET_CONTEXT);
+    while !text.is_char_boundary(from) {
+        from -= 1;
+    }
+    let mut to = (end + SNIPPET_CONTEXT).min(text.len());
+    while !text.is_char_boundary(to) {
+        to += 1;
+    }
+    let prefix = if from > 0 { "…" } else { "" };
+    let suffix = if to < text.len() { "…" } else { "" };
+    format!("{prefix}{}{suffix}", &text[from..to])
+}
+
+fn line_breaks(bytes: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|(_, &b)| b == b'\n')
+        .map(|(i, _)| i as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(text: &str) -> SearchIndex {
+        SearchIndex::build(FastStr::new(text), 1, true, &CancelFlag::never()).unwrap()
+    }
+
+    fn opts(query: &str) -> SearchOptions {
+        SearchOptions {
+            query: query.into(),
+            case_sensitive: false,
+            max_results: 0,
+        }
+    }
+
+    #[test]
+    fn slices_lines_for_visible_ranges() {
+        let idx = index("{\r\n  \"a\": 1,\n  \"b\": [2]\n}");
+        assert_eq!(idx.line_count(), 4);
+        assert_eq!(idx.lines(1..3), vec!["  \"a\": 1,", "  \"b\": [2]"]);
+        assert_eq!(idx.lines(0..1), vec!["{"]);
+        assert_eq!(idx.lines(3..10), vec!["}"]);
+        assert_eq!(
+            (idx.line_of(0), idx.line_of(5), idx.line_of(100)),
+            (1, 2, 4)
+        );
+    }
+
+    #[test]
+    fn finds_values_with_paths_and_offsets() {
+        let text = r#"{"users": [{"name": "Ada Lovelace"}, {"name": "Grace Hopper"}]}"#;
+        let idx = index(text);
+        let result = idx.search(&opts("hopper"), &CancelFlag::never());
+        assert_eq!(result.hits.len(), 1);
+        let hit = &result.hits[0];
+        assert_eq!(format!("{}", hit.path), "$.users[1].name");
+        assert_eq!(hit.match_field, MatchField::Value);
+        let offset = hit.offset as usize;
+        assert_eq!(&text[offset..offset + hit.length as usize], "Hopper");
+    }
+
+    #[test]
+    fn matches_keys_and_short_queries() {
+        let idx = index(r#"{"id": 1, "nested": {"id": 2}, "tag": "x"}"#);
+        let hits = idx.search(&opts("id"), &CancelFlag::never()).hits;
+        let paths: Vec<String> = hits.iter().map(|h| format!("{}", h.path)).collect();
+        assert_eq!(paths, vec!["$.id", "$.nested.id"]);
+        assert!(hits.iter().all(|h| h.match_field == MatchField::Key));
+    }
+
+    #[test]
+    fn trigram_filter_prunes_candidates() {
+        let idx = index(r#"["alpha", "beta", "gamma", "alphabet"]"#);
+        let result = idx.search(&opts("alph"), &CancelFlag::never());
+        assert_eq!(result.candidates, 2);
+        assert_eq!(result.hits.len(), 2);
+        assert!(idx
+            .search(&opts("zzz"), &CancelFlag::never())
+            .hits
+            .is_empty());
+    }
+
+    #[test]
+    fn respects_case_sensitivity_and_cap() {
+        let idx = index(r#"["Token", "token", "TOKEN"]"#);
+        let sensitive = SearchOptions {
+            query: "token".into(),
+            case_sensitive: true,
+            max_results: 0,
+        };
+        assert_eq!(idx.search(&sensitive, &CancelFlag::never()).hits.len(), 1);
+        let capped = SearchOptions {
+            max_results: 2,
+            ..opts("token")
+        };
+        let result = idx.search(&capped, &CancelFlag::never());
+        assert_eq!(result.hits.len(), 2);
+        assert!(result.truncated);
+    }
+
+    #[test]
+    fn decodes_escaped_strings() {
+        let idx = index(r#"{"say\"s": "line\nbreak café"}"#);
+        let hits = idx.search(&opts("café"), &CancelFlag::never()).hits;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(format!("{}", hits[0].path), "$[\"say\\\"s\"]");
+    }
+}
@@ -0,0 +1,131 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::types::{DocError, DocResult};
+
+const MAX_KEYS: usize = 64;
+const MAX_SOURCE_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMetadata {
+    pub keys: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+}
+
+pub fn extract(value: &Value) -> SearchMetadata {
+    let Value::Object(map) = value else {
+        return SearchMetadata::default();
+    };
+    let text = |key: &str| map.get(key).and_then(Value::as_str).map(str::to_string);
+    SearchMetadata {
+        keys: map
+            .keys()
+            .filter(|k| !k.starts_with('$'))
+            .take(MAX_KEYS)
+            .cloned()
+            .collect(),
+        title: text("title").or_else(|| text("name")),
+        schema: text("$schema").or_else(|| text("$id")),
+    }
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+pub fn sidecar_path(path: &FsPath) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.metadata"))
+}
+
+#[cfg_attr(not(any(target_os = "macos", test)), allow(dead_code))]
+fn plist_strings(items: &[String]) -> String {
+    let mut out =
+        String::from(r#"<?xml version="1.0" encoding="UTF-8"?><plist version="1.0"><array>"#);
+    for item in items {
+        let escaped = item
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        out.push_str(&format!("<string>{escaped}</string>"));
+    }
+    out.push_str("</array></plist>");
+    out
+}
+
+#[cfg(target_os = "macos")]
+fn write_attributes(path: &FsPath, meta: &SearchMetadata) -> DocResult<()> {
+    let mut keywords = meta.keys.clone();
+    keywords.extend(meta.title.iter().chain(meta.schema.iter()).cloned());
+    let status = std::process::Command::new("xattr")
+        .arg("-w")
+        .arg("com.apple.metadata:kMDItemKeywords")
+        .arg(plist_strings(&keywords))
+        .arg(path)
+        .status()?;
+    if !status.success() {
+        return Err(
+            std::io::Error::other(format!("xattr could not tag {}", path.display())).into(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn write_attributes(path: &FsPath, meta: &SearchMetadata) -> DocResult<()> {
+    let body = serde_json::to_string_pretty(meta).map_err(|e| DocError::Export(e.to_string()))?;
+    std::fs::write(sidecar_path(path), body)?;
+    Ok(())
+}
+
+pub fn write_for_file(path: &FsPath) -> DocResult<SearchMetadata> {
+    if std::fs::metadata(path)?.len() > MAX_SOURCE_BYTES {
+        return Ok(SearchMetadata::default());
+    }
+    let value: Value = serde_json::from_slice(&std::fs::read(path)?)
+        .map_err(|e| DocError::Parse(e.to_string()))?;
+    let meta = extract(&value);
+    write_attributes(path, &meta)?;
+    Ok(meta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn extracts_top_level_keys_title_and_schema() {
+        let meta = extract(&json!({
+            "$schema": "https://json.schemastore.org/package.json",
+            "name": "pandia",
+            "version": "1.0.0",
+            "scripts": {}
+        }));
+        assert_eq!(meta.keys, vec!["name", "version", "scripts"]);
+        assert_eq!(meta.title.as_deref(), Some("pandia"));
+        assert_eq!(
+            meta.schema.as_deref(),
+            Some("https://json.schemastore.org/package.json")
+        );
+        assert_eq!(extract(&json!([1, 2])), SearchMetadata::default());
+    }
+
+    #[test]
+    fn builds_plist_keywords_and_hidden_sidecars() {
+        assert_eq!(
+            plist_strings(&["a<b".into()]),
+            r#"<?xml version="1.0" encoding="UTF-8"?><plist version="1.0"><array><string>a&lt;b</string></array></plist>"#
+        );
+        assert_eq!(
+            sidecar_path(FsPath::new("/tmp/x/data.json")),
+            PathBuf::from("/tmp/x/.data.json.metadata")
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::histogram::{parse_glob, select};
+use super::timefmt::parse_rfc3339_millis;
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AxisKind {
+    #[default]
+    Auto,
+    Number,
+    Time,
+    Category,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NullHandling {
+    #[default]
+    Keep,
+    Drop,
+    Zero,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SeriesOptions {
+    pub x_kind: AxisKind,
+    pub nulls: NullHandling,
+    pub sort_by_x: bool,
+    pub max_points: usize,
+}
+
+impl Default for SeriesOptions {
+    fn default() -> Self {
+        Self {
+            x_kind: AxisKind::Auto,
+            nulls: NullHandling::Keep,
+            sort_by_x: false,
+            max_points: 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Series {
+    pub path: String,
+    pub values: Vec<Option<f64>>,
+    pub nulls: u32,
+    pub non_numeric: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesData {
+    pub x_kind: AxisKind,
+    pub x: Vec<Value>,
+    pub series: Vec<Series>,
+    pub rows: u32,
+    pub skipped: u32,
+    pub truncated: bool,
+}
+
+enum Cell {
+    Number(f64),
+    Null,
+    Invalid,
+}
+
+fn split_record_path(segments: Vec<String>) -> DocResult<(Vec<String>, Vec<String>)> {
+    let at = segments
+        .iter()
+        .rposition(|s| s == "*")
+        .ok_or_else(|| DocError::Edit("x path needs a [*] over the records".into()))?;
+    let mut records = segments;
+    let field = records.split_off(at + 1);
+    Ok((records, field))
+}
+
+fn field<'a>(record: &'a Value, path: &[String]) -> Option<&'a Value> {
+    let mut found = Vec::new();
+    select(record, path, &mut found);
+    found.into_iter().next()
+}
+
+fn cell(value: Option<&Value>) -> Cell {
+    match value {
+        None | Some(Value::Null) => Cell::Null,
+        Some(Value::Number(n)) => n.as_f64().map_or(Cell::Invalid, Cell::Number),
+        Some(Value::String(s)) => match s.trim() {
+            "" => Cell::Null,
+            text => text
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map_or(Cell::Invalid, Cell::Number),
+        },
+        Some(_) => Cell::Invalid,
+    }
+}
+
+fn resolve_kind(requested: AxisKind, xs: &[&Value]) -> AxisKind {
+    if requested != AxisKind::Auto {
+        return requested;
+    }
+    if xs.iter().all(|x| x.is_number()) {
+        AxisKind::Number
+    } else if xs
+        .iter()
+        .all(|x| x.as_str().and_then(parse_rfc3339_millis).is_some())
+    {
+        AxisKind::Time
+    } else {
+        AxisKind::Category
+    }
+}
+
+fn x_value(x: &Value, kind: AxisKind) -> Option<Value> {
+    match kind {
+        AxisKind::Number | AxisKind::Auto => match cell(Some(x)) {
+            Cell::Number(n) => Some(Value::from(n)),
+            _ => None,
+        },
+        AxisKind::Time => match x {
+            Value::String(s) => parse_rfc3339_millis(s).map(Value::from),
+            Value::Number(n) => n.as_i64().map(Value::from),
+            _ => None,
+        },
+        AxisKind::Category => Some(match x {
+            Value::String(s) => Value::String(s.clone()),
+            other => Value::String(other.to_string()),
+        }),
+    }
+}
+
+pub fn extract(
+    content: &str,
+    x_path: &str,
+    y_paths: &[String],
+    opts: &SeriesOptions,
+) -> DocResult<SeriesData> {
+    if y_paths.is_empty() {
+        return Err(DocError::Edit("at least one y path is required".into()));
+    }
+    let root: Value = serde_json::from_str(content).map_err(|e| DocError::Parse(e.to_string()))?;
+    let (records_path, x_field) = split_record_path(parse_glob(x_path)?)?;
+    let y_fields = y_paths
+        .iter()
+        .map(|p| {
+            let segments = parse_glob(p)?;
+            Ok(match segments.strip_prefix(records_path.as_slice()) {
+                Some(rest) => rest.to_vec(),
+                None => segments,
+            })
+        })
+        .collect::<DocResult<Vec<_>>>()?;
+
+    let mut records = Vec::new();
+    select(&root, &records_path, &mut records);
+    let present: Vec<(&Value, &Value)> = records
+        .iter()
+        .filter_map(|r| field(r, &x_field).filter(|x| !x.is_null()).map(|x| (*r, x)))
+        .collect();
+    let xs: Vec<&Value> = present.iter().map(|(_, x)| *x).collect();
+    let x_kind = resolve_kind(opts.x_kind, &xs);
+
+    let mut rows: Vec<(Value, Vec<Cell>)> = Vec::new();
+    let mut skipped = (records.len() - present.len()) as u32;
+    for (record, x) in present {
+        let Some(x) = x_value(x, x_kind) else {
+            skipped += 1;
+            continue;
+        };
+        let cells: Vec<Cell> = y_fields.iter().map(|f| cell(field(record, f))).collect();
+        if opts.nulls == NullHandling::Drop && cells.iter().any(|c| !matches!(c, Cell::Number(_))) {
+            skipped += 1;
+            continue;
+        }
+        rows.push((x, cells));
+    }
+    if opts.sort_by_x {
+        rows.sort_by(|a, b| match (a.0.as_f64(), b.0.as_f64()) {
+            (Some(l), Some(r)) => l.total_cmp(&r),
+            _ => a.0.as_str().cmp(&b.0.as_str()),
+        });
+    }
+    let truncated = rows.len() > opts.max_points.max(1);
+    rows.truncate(opts.max_points.max(1));
+
+    let mut series: Vec<Series> = y_paths
+        .iter()
+        .map(|path| Series {
+            path: path.clone(),
+            values: Vec::with_capacity(rows.len()),
+            nulls: 0,
+            non_numeric: 0,
+        })
+        .collect();
+    let mut x = Vec::with_capacity(rows.len());
+    for (x_val, cells) in rows {
+        x.push(x_val);
+        for (s, c) in series.iter_mut().zip(cells) {
+            let v = match c {
+                Cell::Number(n) => Some(n),
+                Cell::Null => {
+                    s.nulls += 1;
+                    None
+                }
+                Cell::Invalid => {
+                    s.non_numeric += 1;
+                    None
+                }
+            };
+            s.values.push(match opts.nulls {
+                NullHandling::Zero => v.or(Some(0.0)),
+                _ => v,
+            });
+        }
+    }
+    Ok(SeriesData {
+        x_kind,
+        rows: x.len() as u32,
+        x,
+        series,
+        skipped,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const METRICS: &str = r#"{"samples": [
+        {"at": "2024-01-01T00:00:10Z", "cpu": 0.5, "mem": "512"},
+        {"at": "2024-01-01T00:00:00Z", "cpu": null, "mem": 256},
+        {"cpu": 0.9},
+        {"at": "2024-01-01T00:00:20Z", "cpu": "n/a", "mem": 1024}
+    ]}"#;
+
+    #[test]
+    fn aligns_series_on_parsed_timestamps() {
+        let opts = SeriesOptions {
+            sort_by_x: true,
+            ..SeriesOptions::default()
+        };
+        let y = vec!["samples[*].cpu".to_string(), "mem".to_string()];
+        let out = extract(METRICS, "samples[*].at", &y, &opts).unwrap();
+        assert_eq!(out.x_kind, AxisKind::Time);
+        assert_eq!(out.rows, 3);
+        assert_eq!(out.skipped, 1);
+        assert_eq!(out.x[0], json!(1_704_067_200_000i64));
+        assert_eq!(out.series[0].values, vec![None, Some(0.5), None]);
+        assert_eq!((out.series[0].nulls, out.series[0].non_numeric), (1, 1));
+        assert_eq!(
+            out.series[1].values,
+            vec![Some(256.0), Some(512.0), Some(1024.0)]
+        );
+    }
+
+    #[test]
+    fn null_handling_modes() {
+        let y = vec!["cpu".to_string()];
+        let drop = SeriesOptions {
+            nulls: NullHandling::Drop,
+            ..SeriesOptions::default()
+        };
+        let out = extract(METRICS, "samples[*].at", &y, &drop).unwrap();
+        assert_eq!(out.series[0].values, vec![Some(0.5)]);
+        assert_eq!(out.skipped, 3);
+
+        let zero = SeriesOptions {
+            nulls: NullHandling::Zero,
+            x_kind: AxisKind::Category,
+            ..SeriesOptions::default()
+        };
+        let out = extract(METRICS, "samples[*].at", &y, &zero).unwrap();
+        assert_eq!(out.series[0].values, vec![Some(0.5), Some(0.0), Some(0.0)]);
+        assert_eq!(out.x[0], json!("2024-01-01T00:00:10Z"));
+        assert!(extract(METRICS, "samples.0.at", &y, &zero).is_err());
+    }
+}
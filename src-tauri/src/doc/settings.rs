@@ -0,0 +1,606 @@
+use std::collections::BTreeMap;
+use std::path::{Path as FsPath, PathBuf};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::AppHandle;
+
+use super::document::MAX_DOC_BYTES;
+use super::schema_validate;
+use super::types::{DocError, DocResult};
+
+pub const SETTINGS_VERSION: u64 = 9;
+pub const CHANGED_EVENT: &str = "settings-changed";
+
+const SETTINGS_FILE: &str = "settings.json";
+const LEGACY_STORE_FILE: &str = "pandia-settings.json";
+const MIN_AUTOSAVE_MS: u64 = 250;
+const MAX_AUTOSAVE_MS: u64 = 10 * 60 * 1000;
+const MIN_LARGE_FILE_BYTES: u64 = 1024 * 1024;
+const MAX_UPDATE_INTERVAL_HOURS: u64 = 24 * 30;
+const MAX_WORKER_THREADS: u64 = 64;
+const MAX_MEMORY_LIMIT_MB: u64 = 1024 * 1024;
+const MAX_SNAPSHOT_INTERVAL_SECS: u64 = 24 * 60 * 60;
+const MAX_SNAPSHOT_LIMIT: u64 = 500;
+
+type Migration = fn(Value) -> Value;
+
+const MIGRATIONS: &[Migration] = &[
+    migrate_legacy_store,
+    add_diagnostics_opt_in,
+    add_update_preferences,
+    add_worker_threads,
+    add_memory_limit,
+    add_file_type_profiles,
+    add_save_hooks,
+    add_search_metadata,
+    add_snapshot_preferences,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DefaultView {
+    Tree,
+    Code,
+    Grid,
+    Graph,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateChannel {
+    Stable,
+    Beta,
+    Nightly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LineEnding {
+    Preserve,
+    Lf,
+    Crlf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    pub version: u64,
+    pub indent: u8,
+    pub theme: String,
+    pub large_file_threshold_bytes: u64,
+    pub autosave_interval_ms: u64,
+    pub default_view: DefaultView,
+    pub diagnostics: bool,
+    pub update_channel: UpdateChannel,
+    pub update_check_interval_hours: u64,
+    pub worker_threads: u64,
+    pub memory_limit_mb: u64,
+    pub sort_keys_on_save: bool,
+    pub format_on_open: bool,
+    pub format_on_save: bool,
+    pub trim_trailing_whitespace: bool,
+    pub insert_final_newline: bool,
+    pub line_ending: LineEnding,
+    pub file_types: BTreeMap<String, FileTypeProfile>,
+    pub write_search_metadata: bool,
+    pub snapshot_interval_secs: u64,
+    pub snapshot_limit: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTypeProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indent: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_keys_on_save: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format_on_open: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format_on_save: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim_trailing_whitespace: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insert_final_newline: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_ending: Option<LineEnding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveSettings {
+    pub indent: u8,
+    pub sort_keys_on_save: bool,
+    pub format_on_open: bool,
+    pub format_on_save: bool,
+    pub trim_trailing_whitespace: bool,
+    pub insert_final_newline: bool,
+    pub line_ending: LineEnding,
+    pub profile: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            indent: 2,
+            theme: "system".into(),
+            large_file_threshold_bytes: 10 * 1024 * 1024,
+            autosave_interval_ms: 0,
+            default_view: DefaultView::Tree,
+            diagnostics: false,
+            update_channel: UpdateChannel::Stable,
+            update_check_interval_hours: 24,
+            worker_threads: 0,
+            memory_limit_mb: 0,
+            sort_keys_on_save: false,
+            format_on_open: false,
+            format_on_save: false,
+            trim_trailing_whitespace: false,
+            insert_final_newline: false,
+            line_ending: LineEnding::Preserve,
+            file_types: default_file_types(),
+            write_search_metadata: false,
+            snapshot_interval_secs: 60,
+            snapshot_limit: 10,
+        }
+    }
+}
+
+pub fn schema() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["version", "indent", "theme", "largeFileThresholdBytes", "autosaveIntervalMs", "defaultView", "diagnostics", "updateChannel", "updateCheckIntervalHours", "workerThreads", "memoryLimitMb", "sortKeysOnSave", "formatOnOpen", "formatOnSave", "trimTrailingWhitespace", "insertFinalNewline", "lineEnding", "fileTypes", "writeSearchMetadata", "snapshotIntervalSecs", "snapshotLimit"],
+        "properties": {
+            "version": { "const": SETTINGS_VERSION },
+            "indent": { "type": "integer", "minimum": 0, "maximum": 8 },
+            "theme": { "type": "string", "minLength": 1 },
+            "largeFileThresholdBytes": {
+                "type": "integer",
+                "minimum": MIN_LARGE_FILE_BYTES,
+                "maximum": MAX_DOC_BYTES
+            },
+            "autosaveIntervalMs": {
+                "anyOf": [
+                    { "const": 0 },
+                    { "type": "integer", "minimum": MIN_AUTOSAVE_MS, "maximum": MAX_AUTOSAVE_MS }
+                ]
+            },
+            "defaultView": { "enum": ["tree", "code", "grid", "graph"] },
+            "diagnostics": { "type": "boolean" },
+            "updateChannel": { "enum": ["stable", "beta", "nightly"] },
+            "updateCheckIntervalHours": { "type": "integer", "minimum": 0, "maximum": MAX_UPDATE_INTERVAL_HOURS },
+            "workerThreads": { "type": "integer", "minimum": 0, "maximum": MAX_WORKER_THREADS },
+            "memoryLimitMb": { "type": "integer", "minimum": 0, "maximum": MAX_MEMORY_LIMIT_MB },
+            "sortKeysOnSave": { "type": "boolean" },
+            "formatOnOpen": { "type": "boolean" },
+            "formatOnSave": { "type": "boolean" },
+            "trimTrailingWhitespace": { "type": "boolean" },
+            "insertFinalNewline": { "type": "boolean" },
+            "lineEnding": { "enum": ["preserve", "lf", "crlf"] },
+            "fileTypes": {
+                "type": "object",
+                "propertyNames": { "minLength": 1 },
+                "additionalProperties": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": {
+                        "indent": { "type": "integer", "minimum": 0, "maximum": 8 },
+                        "sortKeysOnSave": { "type": "boolean" },
+                        "formatOnOpen": { "type": "boolean" },
+                        "formatOnSave": { "type": "boolean" },
+                        "trimTrailingWhitespace": { "type": "boolean" },
+                        "insertFinalNewline": { "type": "boolean" },
+                        "lineEnding": { "enum": ["preserve", "lf", "crlf"] }
+                    }
+                }
+            },
+            "writeSearchMetadata": { "type": "boolean" },
+            "snapshotIntervalSecs": { "type": "integer", "minimum": 0, "maximum": MAX_SNAPSHOT_INTERVAL_SECS },
+            "snapshotLimit": { "type": "integer", "minimum": 1, "maximum": MAX_SNAPSHOT_LIMIT }
+        }
+    })
+}
+
+fn default_file_types() -> BTreeMap<String, FileTypeProfile> {
+    BTreeMap::from([
+        (
+            "package.json".to_string(),
+            FileTypeProfile {
+                indent: Some(2),
+                sort_keys_on_save: Some(false),
+                format_on_open: Some(false),
+            },
+        ),
+        (
+            "composer.json".to_string(),
+            FileTypeProfile {
+                indent: Some(4),
+                ..FileTypeProfile::default()
+            },
+        ),
+    ])
+}
+
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+fn profile_rank(pattern: &str, name: &str, path: &str) -> Option<(u8, usize)> {
+    if pattern.contains('/') {
+        let matches = glob_matches(pattern, path) || glob_matches(&format!("*/{pattern}"), path);
+        return matches.then_some((3, pattern.len()));
+    }
+    if pattern.contains('*') {
+        return glob_matches(pattern, name).then_some((2, pattern.len()));
+    }
+    if pattern.starts_with('.') {
+        return name.ends_with(pattern).then_some((1, pattern.len()));
+    }
+    (pattern == name).then_some((4, pattern.len()))
+}
+
+pub fn effective(settings: &Settings, path: &str) -> EffectiveSettings {
+    let normalized = path.replace('\\', "/");
+    let name = normalized.rsplit('/').next().unwrap_or_default();
+    let matched = settings
+        .file_types
+        .iter()
+        .filter_map(|(pattern, profile)| {
+            profile_rank(pattern, name, &normalized).map(|rank| (rank, pattern, profile))
+        })
+        .max_by_key(|(rank, _, _)| *rank);
+    let profile = matched.map(|(_, _, p)| p.clone()).unwrap_or_default();
+    EffectiveSettings {
+        indent: profile.indent.unwrap_or(settings.indent),
+        sort_keys_on_save: profile
+            .sort_keys_on_save
+            .unwrap_or(settings.sort_keys_on_save),
+        format_on_open: profile.format_on_open.unwrap_or(settings.format_on_open),
+        format_on_save: profile.format_on_save.unwrap_or(settings.format_on_save),
+        trim_trailing_whitespace: profile
+            .trim_trailing_whitespace
+            .unwrap_or(settings.trim_trailing_whitespace),
+        insert_final_newline: profile
+            .insert_final_newline
+            .unwrap_or(settings.insert_final_newline),
+        line_ending: profile.line_ending.unwrap_or(settings.line_ending),
+        profile: matched.map(|(_, pattern, _)| pattern.clone()),
+    }
+}
+
+fn migrate_legacy_store(legacy: Value) -> Value {
+    let mut out = serde_json::to_value(Settings::default()).expect("settings serialize");
+    let appearance = legacy.get("appearance");
+    let behavior = legacy.get("behavior");
+    let auto_theme = appearance
+        .and_then(|a| a.get("autoMode"))
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+    if let Some(id) = appearance
+        .and_then(|a| a.get("themeId"))
+        .and_then(Value::as_str)
+        .filter(|_| !auto_theme)
+    {
+        out["theme"] = json!(id);
+    }
+    let autosave = behavior
+        .and_then(|b| b.get("autoSaveOnIdle"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if autosave {
+        let ms = behavior
+            .and_then(|b| b.get("autoSaveIdleMs"))
+            .and_then(Value::as_u64)
+            .unwrap_or(1500)
+            .clamp(MIN_AUTOSAVE_MS, MAX_AUTOSAVE_MS);
+        out["autosaveIntervalMs"] = json!(ms);
+    }
+    out
+}
+
+fn add_diagnostics_opt_in(mut v1: Value) -> Value {
+    v1["diagnostics"] = json!(false);
+    v1
+}
+
+fn add_update_preferences(mut v2: Value) -> Value {
+    v2["updateChannel"] = json!("stable");
+    v2["updateCheckIntervalHours"] = json!(24);
+    v2
+}
+
+fn add_worker_threads(mut v3: Value) -> Value {
+    v3["workerThreads"] = json!(0);
+    v3
+}
+
+fn add_memory_limit(mut v4: Value) -> Value {
+    v4["memoryLimitMb"] = json!(0);
+    v4
+}
+
+fn add_file_type_profiles(mut v5: Value) -> Value {
+    v5["sortKeysOnSave"] = json!(false);
+    v5["formatOnOpen"] = json!(false);
+    v5["fileTypes"] = serde_json::to_value(default_file_types()).expect("profiles serialize");
+    v5
+}
+
+fn add_save_hooks(mut v6: Value) -> Value {
+    v6["formatOnSave"] = json!(false);
+    v6["trimTrailingWhitespace"] = json!(false);
+    v6["insertFinalNewline"] = json!(false);
+    v6["lineEnding"] = json!("preserve");
+    v6
+}
+
+fn add_search_metadata(mut v7: Value) -> Value {
+    v7["writeSearchMetadata"] = json!(false);
+    v7
+}
+
+fn add_snapshot_preferences(mut v8: Value) -> Value {
+    v8["snapshotIntervalSecs"] = json!(60);
+    v8["snapshotLimit"] = json!(10);
+    v8
+}
+
+pub fn migrate(mut raw: Value) -> DocResult<Value> {
+    if !raw.is_object() {
+        return Err(DocError::Schema(
+            "settings file must contain an object".into(),
+        ));
+    }
+    let mut version = raw.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > SETTINGS_VERSION {
+        return Err(DocError::Schema(format!(
+            "settings file is version {version}, newer than this build supports ({SETTINGS_VERSION})"
+        )));
+    }
+    while version < SETTINGS_VERSION {
+        raw = MIGRATIONS[version as usize](raw);
+        version += 1;
+        raw["version"] = json!(version);
+    }
+    Ok(raw)
+}
+
+pub fn validate(raw: &Value) -> DocResult<Settings> {
+    let result = schema_validate::validate_value(raw, &schema())
+        .map_err(|e| DocError::Schema(e.to_string()))?;
+    if let Some(err) = result.errors.first() {
+        let at = if err.instance_path.is_empty() {
+            "settings".to_string()
+        } else {
+            err.instance_path.clone()
+        };
+        return Err(DocError::Schema(format!("{at}: {}", err.message)));
+    }
+    serde_json::from_value(raw.clone()).map_err(|e| DocError::Schema(e.to_string()))
+}
+
+fn read_json(path: &FsPath) -> DocResult<Option<Value>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| DocError::Parse(format!("{}: {e}", path.display()))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write(path: &FsPath, settings: &Settings) -> DocResult<()> {
+    let json =
+        serde_json::to_string_pretty(settings).map_err(|e| DocError::Export(e.to_string()))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+pub fn load_from(dir: &FsPath) -> DocResult<Settings> {
+    let path = dir.join(SETTINGS_FILE);
+    let (raw, from_file) = match read_json(&path)? {
+        Some(raw) => (raw, true),
+        None => match read_json(&dir.join(LEGACY_STORE_FILE))? {
+            Some(legacy) => (legacy, false),
+            None => return Ok(Settings::default()),
+        },
+    };
+    let stored_version = raw.get("version").and_then(Value::as_u64);
+    let settings = validate(&migrate(raw)?)?;
+    if !from_file || stored_version != Some(SETTINGS_VERSION) {
+        write(&path, &settings)?;
+    }
+    Ok(settings)
+}
+
+pub fn apply_patch(current: &Settings, patch: &Value) -> DocResult<Settings> {
+    let patch = patch
+        .as_object()
+        .ok_or_else(|| DocError::Schema("settings patch must be an object".into()))?;
+    let mut merged: Map<String, Value> = match serde_json::to_value(current) {
+        Ok(Value::Object(map)) => map,
+        _ => return Err(DocError::Schema("settings are not an object".into())),
+    };
+    for (k, v) in patch {
+        if k == "version" {
+            continue;
+        }
+        merged.insert(k.clone(), v.clone());
+    }
+    validate(&Value::Object(merged))
+}
+
+pub struct SettingsStore {
+    dir: PathBuf,
+    current: Mutex<Option<Settings>>,
+}
+
+impl SettingsStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            current: Mutex::new(None),
+        }
+    }
+
+    pub fn for_app(app: &AppHandle) -> std::io::Result<Self> {
+        Ok(Self::new(super::portable::data_dir(app)?))
+    }
+
+    fn loaded<'a>(&self, slot: &'a mut Option<Settings>) -> DocResult<&'a mut Settings> {
+        if slot.is_none() {
+            *slot = Some(load_from(&self.dir)?);
+        }
+        Ok(slot.as_mut().expect("settings loaded"))
+    }
+
+    pub fn get(&self) -> DocResult<Settings> {
+        let mut slot = self.current.lock();
+        Ok(self.loaded(&mut slot)?.clone())
+    }
+
+    pub fn update(&self, patch: &Value) -> DocResult<Settings> {
+        let mut slot = self.current.lock();
+        let current = self.loaded(&mut slot)?;
+        let next = apply_patch(current, patch)?;
+        write(&self.dir.join(SETTINGS_FILE), &next)?;
+        *current = next.clone();
+        Ok(next)
+    }
+
+    pub fn reset(&self) -> DocResult<Settings> {
+        let mut slot = self.current.lock();
+        let defaults = Settings::default();
+        write(&self.dir.join(SETTINGS_FILE), &defaults)?;
+        *slot = Some(defaults.clone());
+        Ok(defaults)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn defaults_pass_schema() {
+        let raw = serde_json::to_value(Settings::default()).unwrap();
+        assert_eq!(validate(&raw).unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn migrates_legacy_store_and_writes_current_file() {
+        let dir = TempDir::new("settings-legacy");
+        std::fs::write(
+            dir.join(LEGACY_STORE_FILE),
+            r#"{"appearance":{"themeId":"nord","autoMode":false},"behavior":{"autoSaveOnIdle":true,"autoSaveIdleMs":100}}"#,
+        )
+        .unwrap();
+        let s = load_from(&dir).unwrap();
+        assert_eq!(s.theme, "nord");
+        assert_eq!(s.autosave_interval_ms, MIN_AUTOSAVE_MS);
+        assert!(dir.join(SETTINGS_FILE).exists());
+        assert_eq!(load_from(&dir).unwrap(), s);
+    }
+
+    #[test]
+    fn upgrades_v1_files_through_every_migration() {
+        let v1 = json!({
+            "version": 1, "indent": 4, "theme": "dark", "largeFileThresholdBytes": 10485760,
+            "autosaveIntervalMs": 0, "defaultView": "code"
+        });
+        let s = validate(&migrate(v1).unwrap()).unwrap();
+        assert_eq!(s.version, SETTINGS_VERSION);
+        assert_eq!(s.indent, 4);
+        assert!(!s.diagnostics);
+        assert_eq!(s.update_channel, UpdateChannel::Stable);
+        assert_eq!(s.update_check_interval_hours, 24);
+        assert_eq!(s.worker_threads, 0);
+        assert_eq!(s.memory_limit_mb, 0);
+        assert!(!s.sort_keys_on_save && !s.format_on_open);
+        assert_eq!(s.file_types, default_file_types());
+        assert!(!s.format_on_save && !s.trim_trailing_whitespace && !s.insert_final_newline);
+        assert_eq!(s.line_ending, LineEnding::Preserve);
+        assert!(!s.write_search_metadata);
+        assert_eq!((s.snapshot_interval_secs, s.snapshot_limit), (60, 10));
+    }
+
+    #[test]
+    fn rejects_newer_versions_and_invalid_patches() {
+        assert!(migrate(json!({ "version": 99 })).is_err());
+        let base = Settings::default();
+        let next = apply_patch(&base, &json!({ "indent": 4, "defaultView": "grid" })).unwrap();
+        assert_eq!(next.indent, 4);
+        assert_eq!(next.default_view, DefaultView::Grid);
+        assert!(apply_patch(&base, &json!({ "indent": 12 })).is_err());
+        assert!(apply_patch(&base, &json!({ "autosaveIntervalMs": 10 })).is_err());
+        assert!(apply_patch(&base, &json!({ "fontSize": 3 })).is_err());
+    }
+
+    #[test]
+    fn resolves_the_most_specific_file_type_profile() {
+        let settings = apply_patch(
+            &Settings::default(),
+            &json!({
+                "indent": 4,
+                "fileTypes": {
+                    ".json": { "indent": 3 },
+                    "*.lock.json": { "formatOnOpen": true },
+                    "locales/*.json": { "sortKeysOnSave": true },
+                    "package.json": { "indent": 2 }
+                }
+            }),
+        )
+        .unwrap();
+        let at = |path: &str| effective(&settings, path);
+        assert_eq!(at("/srv/data.yaml").indent, 4);
+        assert_eq!(at("/srv/data.yaml").profile, None);
+        assert_eq!(at("/srv/export.json").indent, 3);
+        assert!(at("/srv/deps.lock.json").format_on_open);
+        assert_eq!(at("/srv/deps.lock.json").indent, 4);
+        assert!(at("C:\\app\\locales\\en.json").sort_keys_on_save);
+        assert_eq!(
+            at("/app/package.json").profile.as_deref(),
+            Some("package.json")
+        );
+        assert!(glob_matches("a*b*c", "aXbYc") && !glob_matches("a*b", "ab_"));
+        assert!(apply_patch(
+            &settings,
+            &json!({ "fileTypes": { ".json": { "tabs": 1 } } })
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn store_persists_updates() {
+        let dir = TempDir::new("settings-store");
+        let store = SettingsStore::new(dir.to_path_buf());
+        store.update(&json!({ "theme": "dark" })).unwrap();
+        assert_eq!(
+            SettingsStore::new(dir.to_path_buf()).get().unwrap().theme,
+            "dark"
+        );
+        assert_eq!(store.reset().unwrap(), Settings::default());
+    }
+}
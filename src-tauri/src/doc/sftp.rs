@@ -0,0 +1,529 @@
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine as _;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use ssh2::{
+    CheckResult, ErrorCode, FileStat, HashType, HostKeyType, KnownHostFileKind, RenameFlags,
+    Session, Sftp,
+};
+
+use super::document::MAX_DOC_BYTES;
+use super::save_hooks::{line_ending_of, SaveHooks};
+use super::settings::LineEnding;
+use super::types::{DocError, DocResult};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const SESSION_TIMEOUT_MS: u32 = 30_000;
+const DEFAULT_PORT: u16 = 22;
+const SNIFF_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SftpAuth {
+    Agent,
+    #[serde(rename_all = "camelCase")]
+    Key {
+        private_key: String,
+        #[serde(default)]
+        passphrase: Option<String>,
+    },
+    Password {
+        password: String,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpProfile {
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    pub username: String,
+    pub auth: SftpAuth,
+    #[serde(default)]
+    pub accepted_fingerprint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpConnection {
+    pub id: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub home: String,
+    pub host_key_fingerprint: Option<String>,
+    pub host_key_known: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpEntry {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+    pub modified_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpWriteResult {
+    pub path: String,
+    pub bytes: u64,
+    pub backup: Option<String>,
+}
+
+struct Connection {
+    _session: Session,
+    sftp: Sftp,
+}
+
+#[derive(Default)]
+pub struct SftpConnections {
+    connections: DashMap<String, Arc<Mutex<Connection>>>,
+}
+
+/// `LIBSSH2_FX_NO_SUCH_FILE`; `ssh2` doesn't re-export the SFTP status codes.
+const FX_NO_SUCH_FILE: i32 = 2;
+
+fn is_missing(e: &ssh2::Error) -> bool {
+    e.code() == ErrorCode::SFTP(FX_NO_SUCH_FILE)
+}
+
+fn ssh_err(context: &str, e: ssh2::Error) -> DocError {
+    let kind = if is_missing(&e) {
+        std::io::ErrorKind::NotFound
+    } else {
+        std::io::ErrorKind::Other
+    };
+    DocError::Io(std::io::Error::new(
+        kind,
+        format!("{context}: {}", e.message()),
+    ))
+}
+
+/// Only the server's "no such file" status counts as absent; a permission
+/// error or a dropped session is passed back instead of read as a new file.
+fn stat_if_exists(sftp: &Sftp, path: &FsPath) -> Result<Option<FileStat>, ssh2::Error> {
+    match sftp.stat(path) {
+        Ok(stat) => Ok(Some(stat)),
+        Err(e) if is_missing(&e) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match (path.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+pub fn remote_join(dir: &str, name: &str) -> String {
+    if dir.ends_with('/') {
+        format!("{dir}{name}")
+    } else {
+        format!("{dir}/{name}")
+    }
+}
+
+fn sibling(path: &str, suffix: &str) -> String {
+    match path.rsplit_once('/') {
+        Some((dir, name)) => format!("{dir}/.{name}{suffix}"),
+        None => format!(".{path}{suffix}"),
+    }
+}
+
+fn known_hosts_entry(host: &str, port: u16) -> String {
+    if port == DEFAULT_PORT {
+        host.to_string()
+    } else {
+        format!("[{host}]:{port}")
+    }
+}
+
+fn key_type_name(key_type: HostKeyType) -> Option<&'static str> {
+    match key_type {
+        HostKeyType::Rsa => Some("ssh-rsa"),
+        HostKeyType::Dss => Some("ssh-dss"),
+        HostKeyType::Ecdsa256 => Some("ecdsa-sha2-nistp256"),
+        HostKeyType::Ecdsa384 => Some("ecdsa-sha2-nistp384"),
+        HostKeyType::Ecdsa521 => Some("ecdsa-sha2-nistp521"),
+        HostKeyType::Ed255219 => Some("ssh-ed25519"),
+        HostKeyType::Unknown => None,
+    }
+}
+
+fn known_hosts_line(host: &str, port: u16, key: &[u8], key_type: HostKeyType) -> Option<String> {
+    Some(format!(
+        "{} {} {}\n",
+        known_hosts_entry(host, port),
+        key_type_name(key_type)?,
+        base64::engine::general_purpose::STANDARD.encode(key)
+    ))
+}
+
+/// Appends a single entry so lines libssh2 cannot parse (markers, hashed
+/// hosts, comments) survive untouched.
+fn append_known_host(file: &FsPath, line: &str) -> DocResult<()> {
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut out = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(file)?;
+    let len = out.metadata()?.len();
+    let mut needs_newline = false;
+    if len > 0 {
+        let mut last = [0u8; 1];
+        out.seek(SeekFrom::Start(len - 1))?;
+        out.read_exact(&mut last)?;
+        needs_newline = last[0] != b'\n';
+    }
+    if needs_newline {
+        out.write_all(b"\n")?;
+    }
+    out.write_all(line.as_bytes())?;
+    out.sync_all()?;
+    Ok(())
+}
+
+fn verify_host(
+    session: &Session,
+    host: &str,
+    port: u16,
+    accepted: Option<&str>,
+) -> DocResult<(Option<String>, bool)> {
+    let fingerprint = session.host_key_hash(HashType::Sha256).map(|hash| {
+        format!(
+            "SHA256:{}",
+            base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash)
+        )
+    });
+    let Some((key, key_type)) = session.host_key() else {
+        return Err(DocError::Conflict(format!(
+            "{host} did not present a host key"
+        )));
+    };
+    let mut known = session
+        .known_hosts()
+        .map_err(|e| ssh_err("known hosts", e))?;
+    let file = expand_home("~/.ssh/known_hosts");
+    let readable = known.read_file(&file, KnownHostFileKind::OpenSSH).is_ok();
+    let status = if readable {
+        known.check_port(host, port, key)
+    } else {
+        CheckResult::NotFound
+    };
+    match status {
+        CheckResult::Match => Ok((fingerprint, true)),
+        CheckResult::Mismatch => Err(DocError::Conflict(format!(
+            "host key for {host} does not match ~/.ssh/known_hosts ({})",
+            fingerprint.unwrap_or_default()
+        ))),
+        CheckResult::NotFound | CheckResult::Failure => {
+            let Some(fingerprint) = fingerprint.filter(|f| accepted == Some(f.as_str())) else {
+                return Err(DocError::Conflict(format!(
+                    "unknown host key for {host} ({}); confirm the fingerprint to connect",
+                    fingerprint.unwrap_or_default()
+                )));
+            };
+            let line = known_hosts_line(host, port, key, key_type)
+                .ok_or_else(|| DocError::Edit(format!("unsupported host key type for {host}")))?;
+            append_known_host(&file, &line)?;
+            Ok((Some(fingerprint), false))
+        }
+    }
+}
+
+fn copy_remote(sftp: &Sftp, from: &str, to: &str) -> DocResult<()> {
+    let mut source = sftp.open(FsPath::new(from)).map_err(|e| ssh_err(from, e))?;
+    let mut dest = sftp.create(FsPath::new(to)).map_err(|e| ssh_err(to, e))?;
+    std::io::copy(&mut source, &mut dest)?;
+    dest.fsync().map_err(|e| ssh_err(to, e))
+}
+
+fn replace(sftp: &Sftp, tmp: &FsPath, target: &FsPath) -> Result<(), ssh2::Error> {
+    let flags = RenameFlags::OVERWRITE | RenameFlags::ATOMIC | RenameFlags::NATIVE;
+    match sftp.rename(tmp, target, Some(flags)) {
+        Err(e) => match stat_if_exists(sftp, target)? {
+            Some(_) => {
+                sftp.unlink(target)?;
+                sftp.rename(tmp, target, None)
+            }
+            None => Err(e),
+        },
+        ok => ok,
+    }
+}
+
+impl SftpConnections {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn connect(&self, profile: &SftpProfile) -> DocResult<SftpConnection> {
+        let port = profile.port.unwrap_or(DEFAULT_PORT);
+        let addr = (profile.host.as_str(), port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| DocError::Edit(format!("cannot resolve {}", profile.host)))?;
+        let tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+        let mut session = Session::new().map_err(|e| ssh_err("ssh", e))?;
+        session.set_timeout(SESSION_TIMEOUT_MS);
+        session.set_tcp_stream(tcp);
+        session.handshake().map_err(|e| ssh_err("handshake", e))?;
+        let (host_key_fingerprint, host_key_known) = verify_host(
+            &session,
+            &profile.host,
+            port,
+            profile.accepted_fingerprint.as_deref(),
+        )?;
+
+        let user = profile.username.as_str();
+        let authed = match &profile.auth {
+            SftpAuth::Agent => session.userauth_agent(user),
+            SftpAuth::Key {
+                private_key,
+                passphrase,
+            } => session.userauth_pubkey_file(
+                user,
+                None,
+                &expand_home(private_key),
+                passphrase.as_deref(),
+            ),
+            SftpAuth::Password { password } => session.userauth_password(user, password),
+        };
+        authed.map_err(|e| ssh_err("authentication failed", e))?;
+        if !session.authenticated() {
+            return Err(DocError::Edit(format!(
+                "authentication failed for {user}@{}",
+                profile.host
+            )));
+        }
+
+        let sftp = session.sftp().map_err(|e| ssh_err("sftp", e))?;
+        let home = sftp
+            .realpath(FsPath::new("."))
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| "/".into());
+        let id = uuid::Uuid::new_v4().to_string();
+        self.connections.insert(
+            id.clone(),
+            Arc::new(Mutex::new(Connection {
+                _session: session,
+                sftp,
+            })),
+        );
+        Ok(SftpConnection {
+            id,
+            host: profile.host.clone(),
+            port,
+            username: profile.username.clone(),
+            home,
+            host_key_fingerprint,
+            host_key_known,
+        })
+    }
+
+    pub fn disconnect(&self, id: &str) -> bool {
+        self.connections.remove(id).is_some()
+    }
+
+    fn get(&self, id: &str) -> DocResult<Arc<Mutex<Connection>>> {
+        self.connections
+            .get(id)
+            .map(|c| c.clone())
+            .ok_or_else(|| DocError::Edit(format!("no SFTP connection {id}")))
+    }
+
+    pub fn list(&self, id: &str, dir: &str) -> DocResult<Vec<SftpEntry>> {
+        let conn = self.get(id)?;
+        let conn = conn.lock();
+        let mut entries: Vec<SftpEntry> = conn
+            .sftp
+            .readdir(FsPath::new(dir))
+            .map_err(|e| ssh_err(dir, e))?
+            .into_iter()
+            .filter_map(|(path, stat)| {
+                let name = path.file_name()?.to_string_lossy().into_owned();
+                Some(SftpEntry {
+                    path: remote_join(dir, &name),
+                    name,
+                    is_dir: stat.is_dir(),
+                    size: stat.size,
+                    modified_ms: stat.mtime.map(|s| s * 1000),
+                })
+            })
+            .collect();
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        Ok(entries)
+    }
+
+    pub fn read(&self, id: &str, path: &str) -> DocResult<String> {
+        let conn = self.get(id)?;
+        let conn = conn.lock();
+        let size = conn
+            .sftp
+            .stat(FsPath::new(path))
+            .map_err(|e| ssh_err(path, e))?
+            .size
+            .unwrap_or(0);
+        if size > MAX_DOC_BYTES {
+            return Err(DocError::TooLarge {
+                actual: size,
+                limit: MAX_DOC_BYTES,
+            });
+        }
+        let mut text = String::with_capacity(size as usize);
+        conn.sftp
+            .open(FsPath::new(path))
+            .map_err(|e| ssh_err(path, e))?
+            .read_to_string(&mut text)
+            .map_err(|e| DocError::Parse(format!("{path}: {e}")))?;
+        Ok(text)
+    }
+
+    pub fn write(
+        &self,
+        id: &str,
+        path: &str,
+        content: String,
+        hooks: Option<SaveHooks>,
+    ) -> DocResult<SftpWriteResult> {
+        let conn = self.get(id)?;
+        let conn = conn.lock();
+        let sftp = &conn.sftp;
+        let target = FsPath::new(path);
+        let existing = stat_if_exists(sftp, target).map_err(|e| ssh_err(path, e))?;
+        let exists = existing.is_some();
+
+        let text = match hooks {
+            Some(mut hooks) => {
+                if hooks.line_ending == Some(LineEnding::Preserve) {
+                    let mut head = Vec::new();
+                    if exists {
+                        if let Ok(file) = sftp.open(target) {
+                            let _ = file.take(SNIFF_BYTES).read_to_end(&mut head);
+                        }
+                    }
+                    hooks.line_ending = line_ending_of(&head);
+                }
+                hooks.apply(content, target)?
+            }
+            None => content,
+        };
+
+        let tmp = sibling(path, ".pandia-upload");
+        let upload = sftp
+            .create(FsPath::new(&tmp))
+            .map_err(|e| ssh_err(&tmp, e))
+            .and_then(|mut file| {
+                file.write_all(text.as_bytes())?;
+                file.fsync().map_err(|e| ssh_err(&tmp, e))
+            });
+        if let Err(e) = upload {
+            let _ = sftp.unlink(FsPath::new(&tmp));
+            return Err(e);
+        }
+        if let Some(stat) = &existing {
+            let attrs = FileStat {
+                size: None,
+                uid: stat.uid,
+                gid: stat.gid,
+                perm: stat.perm,
+                atime: None,
+                mtime: None,
+            };
+            let _ = sftp.setstat(FsPath::new(&tmp), attrs);
+        }
+
+        let backup = if exists {
+            let backup = format!("{path}.bak");
+            if let Err(e) = copy_remote(sftp, path, &backup) {
+                let _ = sftp.unlink(FsPath::new(&tmp));
+                return Err(e);
+            }
+            Some(backup)
+        } else {
+            None
+        };
+        if let Err(e) = replace(sftp, FsPath::new(&tmp), target) {
+            let missing = matches!(stat_if_exists(sftp, target), Ok(None));
+            if let Some(backup) = backup.as_deref().filter(|_| missing) {
+                let _ = copy_remote(sftp, backup, path);
+            }
+            let _ = sftp.unlink(FsPath::new(&tmp));
+            return Err(ssh_err(path, e));
+        }
+        Ok(SftpWriteResult {
+            path: path.to_string(),
+            bytes: text.len() as u64,
+            backup,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_remote_paths() {
+        assert_eq!(remote_join("/etc", "app.json"), "/etc/app.json");
+        assert_eq!(remote_join("/", "app.json"), "/app.json");
+        assert_eq!(sibling("/etc/app.json", ".tmp"), "/etc/.app.json.tmp");
+        assert_eq!(sibling("app.json", ".tmp"), ".app.json.tmp");
+        assert_eq!(known_hosts_entry("example.com", 22), "example.com");
+        assert_eq!(known_hosts_entry("example.com", 2222), "[example.com]:2222");
+    }
+
+    #[test]
+    fn formats_a_single_known_hosts_line() {
+        let line = known_hosts_line("example.com", 2222, b"key", HostKeyType::Ed255219).unwrap();
+        assert_eq!(line, "[example.com]:2222 ssh-ed25519 a2V5\n");
+        assert!(known_hosts_line("example.com", 22, b"key", HostKeyType::Unknown).is_none());
+    }
+
+    #[test]
+    fn parses_profiles_and_expands_key_paths() {
+        let profile: SftpProfile = serde_json::from_value(serde_json::json!({
+            "host": "example.com",
+            "username": "deploy",
+            "auth": { "kind": "key", "privateKey": "~/.ssh/id_ed25519" }
+        }))
+        .unwrap();
+        assert_eq!(profile.port, None);
+        let SftpAuth::Key { private_key, .. } = &profile.auth else {
+            panic!("expected key auth");
+        };
+        let expanded = expand_home(private_key);
+        assert!(expanded.ends_with(".ssh/id_ed25519"));
+        assert_eq!(expand_home("/abs/key"), PathBuf::from("/abs/key"));
+    }
+
+    #[test]
+    fn only_no_such_file_maps_to_not_found() {
+        let kind = |code| match ssh_err("x", ssh2::Error::new(code, "boom")) {
+            DocError::Io(e) => e.kind(),
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(
+            kind(ErrorCode::SFTP(FX_NO_SUCH_FILE)),
+            std::io::ErrorKind::NotFound
+        );
+        assert_eq!(kind(ErrorCode::SFTP(3)), std::io::ErrorKind::Other);
+        assert_eq!(kind(ErrorCode::Session(-7)), std::io::ErrorKind::Other);
+    }
+}
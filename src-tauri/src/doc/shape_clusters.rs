@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::eager::kind_and_child_count_eager;
+use super::histogram::{parse_glob, select};
+use super::types::{DocError, DocResult, NodeKind};
+
+const SAMPLE_INDICES: usize = 10;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShapeOptions {
+    pub include_types: bool,
+    pub max_clusters: usize,
+}
+
+impl Default for ShapeOptions {
+    fn default() -> Self {
+        Self {
+            include_types: true,
+            max_clusters: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShapeField {
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<NodeKind>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShapeCluster {
+    pub signature: String,
+    pub kind: NodeKind,
+    pub fields: Vec<ShapeField>,
+    pub count: u64,
+    pub share: f64,
+    pub sample_indices: Vec<u64>,
+    pub example: Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShapeClusters {
+    pub total: u64,
+    pub distinct: u64,
+    pub clusters: Vec<ShapeCluster>,
+    pub other: u64,
+}
+
+fn kind_of(value: &Value) -> NodeKind {
+    kind_and_child_count_eager(value).0
+}
+
+fn kind_name(kind: NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Object => "object",
+        NodeKind::Array => "array",
+        NodeKind::String => "string",
+        NodeKind::Number => "number",
+        NodeKind::Bool => "bool",
+        NodeKind::Null => "null",
+    }
+}
+
+fn shape_of(value: &Value, include_types: bool) -> (String, Vec<ShapeField>) {
+    let Value::Object(map) = value else {
+        return (kind_name(kind_of(value)).to_string(), Vec::new());
+    };
+    let mut fields: Vec<ShapeField> = map
+        .iter()
+        .map(|(key, v)| ShapeField {
+            key: key.clone(),
+            kind: include_types.then(|| kind_of(v)),
+        })
+        .collect();
+    fields.sort_by(|a, b| a.key.cmp(&b.key));
+    let parts: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            let key = serde_json::to_string(&f.key).unwrap_or_default();
+            match f.kind {
+                Some(kind) => format!("{key}:{}", kind_name(kind)),
+                None => key,
+            }
+        })
+        .collect();
+    (format!("{{{}}}", parts.join(",")), fields)
+}
+
+pub fn cluster(content: &str, path: &str, opts: &ShapeOptions) -> DocResult<ShapeClusters> {
+    let root: Value = serde_json::from_str(content).map_err(|e| DocError::Parse(e.to_string()))?;
+    let mut matched = Vec::new();
+    select(&root, &parse_glob(path)?, &mut matched);
+    if matched.is_empty() {
+        return Err(DocError::Edit(format!("nothing found at {path}")));
+    }
+    let elements = matched.into_iter().flat_map(|v| match v {
+        Value::Array(items) => items.iter().collect::<Vec<_>>(),
+        other => vec![other],
+    });
+
+    let mut by_signature: HashMap<String, usize> = HashMap::new();
+    let mut clusters: Vec<ShapeCluster> = Vec::new();
+    let mut total: u64 = 0;
+    for (index, element) in elements.enumerate() {
+        total += 1;
+        let (signature, fields) = shape_of(element, opts.include_types);
+        let at = *by_signature.entry(signature.clone()).or_insert_with(|| {
+            clusters.push(ShapeCluster {
+                signature,
+                kind: kind_of(element),
+                fields,
+                count: 0,
+                share: 0.0,
+                sample_indices: Vec::new(),
+                example: element.clone(),
+            });
+            clusters.len() - 1
+        });
+        let cluster = &mut clusters[at];
+        cluster.count += 1;
+        if cluster.sample_indices.len() < SAMPLE_INDICES {
+            cluster.sample_indices.push(index as u64);
+        }
+    }
+    clusters.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(a.sample_indices.cmp(&b.sample_indices))
+    });
+    let distinct = clusters.len() as u64;
+    clusters.truncate(opts.max_clusters.max(1));
+    for c in &mut clusters {
+        c.share = c.count as f64 / total as f64;
+    }
+    let shown: u64 = clusters.iter().map(|c| c.count).sum();
+    Ok(ShapeClusters {
+        total,
+        distinct,
+        clusters,
+        other: total - shown,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const EVENTS: &str = r#"{"events": [
+        {"type": "click", "x": 1, "y": 2},
+        {"y": 5, "x": 3, "type": "click"},
+        {"type": "view", "page": "/home"},
+        {"type": "click", "x": "7", "y": 2},
+        null,
+        {"type": "view", "page": "/about"}
+    ]}"#;
+
+    #[test]
+    fn groups_elements_by_keys_and_types() {
+        let out = cluster(EVENTS, "events", &ShapeOptions::default()).unwrap();
+        assert_eq!((out.total, out.distinct, out.other), (6, 4, 0));
+        let top = &out.clusters[0];
+        assert_eq!(top.signature, r#"{"type":string,"x":number,"y":number}"#);
+        assert_eq!((top.count, top.sample_indices.clone()), (2, vec![0, 1]));
+        assert_eq!(top.example, json!({"type": "click", "x": 1, "y": 2}));
+        assert_eq!(out.clusters[1].fields[0].key, "page");
+        assert_eq!(out.clusters[3].kind, NodeKind::Null);
+
+        let opts = ShapeOptions {
+            include_types: false,
+            max_clusters: 1,
+        };
+        let out = cluster(EVENTS, "/events", &opts).unwrap();
+        assert_eq!((out.distinct, out.clusters[0].count, out.other), (3, 3, 3));
+        assert!(out.clusters[0].fields[0].kind.is_none());
+        assert!(cluster(EVENTS, "missing", &opts).is_err());
+    }
+}
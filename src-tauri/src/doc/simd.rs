@@ -0,0 +1,187 @@
+use std::io::{self, Write};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+static LEVEL: Lazy<SimdLevel> = Lazy::new(detect);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(not(target_arch = "x86_64"), allow(dead_code))]
+pub enum SimdLevel {
+    Avx2,
+    Sse2,
+    Scalar,
+}
+
+#[cfg(target_arch = "x86_64")]
+fn detect() -> SimdLevel {
+    if is_x86_feature_detected!("avx2") {
+        SimdLevel::Avx2
+    } else {
+        SimdLevel::Sse2
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn detect() -> SimdLevel {
+    SimdLevel::Scalar
+}
+
+pub fn level() -> SimdLevel {
+    *LEVEL
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn find_avx2(bytes: &[u8]) -> Option<usize> {
+        let quote = _mm256_set1_epi8(b'"' as i8);
+        let slash = _mm256_set1_epi8(b'\\' as i8);
+        let mut i = 0;
+        while i + 32 <= bytes.len() {
+            // SAFETY: `i + 32 <= bytes.len()`, so the unaligned 32-byte load
+            // stays inside the slice.
+            let chunk = _mm256_loadu_si256(bytes.as_ptr().add(i) as *const __m256i);
+            let hits = _mm256_or_si256(
+                _mm256_cmpeq_epi8(chunk, quote),
+                _mm256_cmpeq_epi8(chunk, slash),
+            );
+            let mask = _mm256_movemask_epi8(hits) as u32;
+            if mask != 0 {
+                return Some(i + mask.trailing_zeros() as usize);
+            }
+            i += 32;
+        }
+        super::find_scalar(&bytes[i..]).map(|p| i + p)
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn find_sse2(bytes: &[u8]) -> Option<usize> {
+        let quote = _mm_set1_epi8(b'"' as i8);
+        let slash = _mm_set1_epi8(b'\\' as i8);
+        let mut i = 0;
+        while i + 16 <= bytes.len() {
+            // SAFETY: `i + 16 <= bytes.len()`, so the unaligned 16-byte load
+            // stays inside the slice.
+            let chunk = _mm_loadu_si128(bytes.as_ptr().add(i) as *const __m128i);
+            let hits = _mm_or_si128(_mm_cmpeq_epi8(chunk, quote), _mm_cmpeq_epi8(chunk, slash));
+            let mask = _mm_movemask_epi8(hits) as u32;
+            if mask != 0 {
+                return Some(i + mask.trailing_zeros() as usize);
+            }
+            i += 16;
+        }
+        super::find_scalar(&bytes[i..]).map(|p| i + p)
+    }
+}
+
+fn find_scalar(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().position(|&b| b == b'"' || b == b'\\')
+}
+
+fn find_with(level: SimdLevel, bytes: &[u8]) -> Option<usize> {
+    match level {
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx2 => {
+            debug_assert!(is_x86_feature_detected!("avx2"));
+            // SAFETY: `Avx2` only comes from `detect` (or the test matrix)
+            // after `is_x86_feature_detected!("avx2")` returned true.
+            unsafe { x86::find_avx2(bytes) }
+        }
+        #[cfg(target_arch = "x86_64")]
+        // SAFETY: SSE2 is part of the x86_64 baseline, so it is always present.
+        SimdLevel::Sse2 => unsafe { x86::find_sse2(bytes) },
+        _ => find_scalar(bytes),
+    }
+}
+
+pub fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let level = level();
+    let mut i = start + 1;
+    loop {
+        i += find_with(level, bytes.get(i..)?)?;
+        if bytes[i] == b'"' {
+            return Some(i + 1);
+        }
+        i += 2;
+    }
+}
+
+pub fn minify_to<W: Write>(source: &str, mut writer: W) -> io::Result<()> {
+    let bytes = source.as_bytes();
+    let mut run = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                writer.write_all(&bytes[run..i])?;
+                while i < bytes.len() && matches!(bytes[i], b' ' | b'\t' | b'\n' | b'\r') {
+                    i += 1;
+                }
+                run = i;
+            }
+            b'"' => {
+                i = string_end(bytes, i).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "unterminated string")
+                })?;
+            }
+            _ => i += 1,
+        }
+    }
+    writer.write_all(&bytes[run..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels() -> Vec<SimdLevel> {
+        let mut out = vec![SimdLevel::Scalar];
+        #[cfg(target_arch = "x86_64")]
+        {
+            out.push(SimdLevel::Sse2);
+            if is_x86_feature_detected!("avx2") {
+                out.push(SimdLevel::Avx2);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn every_level_agrees_with_scalar() {
+        let mut text = "x".repeat(100).into_bytes();
+        for level in levels() {
+            assert_eq!(find_with(level, &text), None);
+        }
+        for at in [0, 15, 16, 31, 32, 33, 70, 99] {
+            text[at] = if at % 2 == 0 { b'"' } else { b'\\' };
+            for level in levels() {
+                assert_eq!(find_with(level, &text[..]), find_scalar(&text), "{level:?}");
+                assert_eq!(find_with(level, &text[1..]), find_scalar(&text[1..]));
+            }
+            text[at] = b'x';
+        }
+    }
+
+    #[test]
+    fn finds_string_ends_across_escapes() {
+        let text = format!(r#""{}\"\\{}" tail"#, "a".repeat(40), "b".repeat(20));
+        let end = string_end(text.as_bytes(), 0).unwrap();
+        assert_eq!(&text[end..], " tail");
+        assert_eq!(string_end(br#""abc\""#, 0), None);
+    }
+
+    #[test]
+    fn minifies_without_touching_strings_or_numbers() {
+        let src = "{\n  \"a b\" : [1.50, 18446744073709551615],\r\n\t\"c\\\" d\": \"\\u0041\"\n}\n";
+        let mut out = Vec::new();
+        minify_to(src, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"{"a b":[1.50,18446744073709551615],"c\" d":"\u0041"}"#
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path as FsPath;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::dirdiff::{self, DirDiffOptions};
+use super::jobs::CancelFlag;
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Similarity {
+    pub score: f64,
+    pub identical: bool,
+    pub tree_similarity: f64,
+    pub edit_distance: u64,
+    pub nodes_a: u64,
+    pub nodes_b: u64,
+    pub shared_key_ratio: f64,
+    pub shared_keys: u32,
+    pub total_keys: u32,
+    pub value_overlap: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RankOptions {
+    pub extensions: Vec<String>,
+    pub recursive: bool,
+    pub include_hidden: bool,
+    pub max_files: usize,
+    pub limit: Option<usize>,
+}
+
+impl Default for RankOptions {
+    fn default() -> Self {
+        let dir = DirDiffOptions::default();
+        Self {
+            extensions: dir.extensions,
+            recursive: dir.recursive,
+            include_hidden: dir.include_hidden,
+            max_files: dir.max_files,
+            limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedFile {
+    pub relative_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<Similarity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarityRanking {
+    pub files: Vec<RankedFile>,
+    pub scanned: u32,
+    pub errors: u32,
+    pub truncated: bool,
+}
+
+#[derive(Default)]
+struct TreeMatch {
+    paired: u64,
+    equal: u64,
+}
+
+fn same_scalar(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x == y || x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn size(value: &Value) -> u64 {
+    1 + match value {
+        Value::Object(map) => map.values().map(size).sum(),
+        Value::Array(items) => items.iter().map(size).sum(),
+        _ => 0,
+    }
+}
+
+fn pair(a: &Value, b: &Value, m: &mut TreeMatch) {
+    m.paired += 1;
+    match (a, b) {
+        (Value::Object(left), Value::Object(right)) => {
+            m.equal += 1;
+            for (key, l) in left {
+                if let Some(r) = right.get(key) {
+                    pair(l, r, m);
+                }
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            m.equal += 1;
+            for (l, r) in left.iter().zip(right) {
+                pair(l, r, m);
+            }
+        }
+        (l, r) if same_scalar(l, r) => m.equal += 1,
+        _ => {}
+    }
+}
+
+fn key_paths(value: &Value, prefix: &mut String, out: &mut HashSet<String>) {
+    let len = prefix.len();
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                prefix.push('/');
+                prefix.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                out.insert(prefix.clone());
+                key_paths(child, prefix, out);
+                prefix.truncate(len);
+            }
+        }
+        Value::Array(items) => {
+            prefix.push_str("/*");
+            for item in items {
+                key_paths(item, prefix, out);
+            }
+            prefix.truncate(len);
+        }
+        _ => {}
+    }
+}
+
+fn leaf_values(value: &Value, out: &mut HashMap<String, u64>) {
+    match value {
+        Value::Object(map) => map.values().for_each(|v| leaf_values(v, out)),
+        Value::Array(items) => items.iter().for_each(|v| leaf_values(v, out)),
+        Value::Number(n) => {
+            let key = n.as_f64().map_or_else(|| n.to_string(), |f| f.to_string());
+            *out.entry(key).or_default() += 1;
+        }
+        other => *out.entry(other.to_string()).or_default() += 1,
+    }
+}
+
+fn ratio(shared: u64, total: u64) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        shared as f64 / total as f64
+    }
+}
+
+pub fn compare(a: &Value, b: &Value) -> Similarity {
+    let (nodes_a, nodes_b) = (size(a), size(b));
+    let mut m = TreeMatch::default();
+    pair(a, b, &mut m);
+    let edit_distance = (nodes_a - m.paired) + (nodes_b - m.paired) + (m.paired - m.equal);
+    let tree_similarity = ratio(2 * m.equal, nodes_a + nodes_b);
+
+    let (mut keys_a, mut keys_b) = (HashSet::new(), HashSet::new());
+    key_paths(a, &mut String::new(), &mut keys_a);
+    key_paths(b, &mut String::new(), &mut keys_b);
+    let shared_keys = keys_a.intersection(&keys_b).count() as u32;
+    let total_keys = keys_a.union(&keys_b).count() as u32;
+    let shared_key_ratio = ratio(shared_keys as u64, total_keys as u64);
+
+    let (mut values_a, mut values_b) = (HashMap::new(), HashMap::new());
+    leaf_values(a, &mut values_a);
+    leaf_values(b, &mut values_b);
+    let mut min_sum: u64 = 0;
+    let mut max_sum: u64 = values_b
+        .iter()
+        .filter(|(k, _)| !values_a.contains_key(*k))
+        .map(|(_, n)| n)
+        .sum();
+    for (value, &n) in &values_a {
+        let other = values_b.get(value).copied().unwrap_or(0);
+        min_sum += n.min(other);
+        max_sum += n.max(other);
+    }
+    let value_overlap = ratio(min_sum, max_sum);
+
+    Similarity {
+        score: (tree_similarity + shared_key_ratio + value_overlap) / 3.0,
+        identical: edit_distance == 0,
+        tree_similarity,
+        edit_distance,
+        nodes_a,
+        nodes_b,
+        shared_key_ratio,
+        shared_keys,
+        total_keys,
+        value_overlap,
+    }
+}
+
+fn parse(text: &str) -> DocResult<Value> {
+    serde_json::from_str(text).map_err(|e| DocError::Parse(e.to_string()))
+}
+
+pub fn similarity(a: &str, b: &str) -> DocResult<Similarity> {
+    Ok(compare(&parse(a)?, &parse(b)?))
+}
+
+pub fn rank_folder(
+    reference: &FsPath,
+    dir: &FsPath,
+    opts: &RankOptions,
+    cancel: &CancelFlag,
+) -> DocResult<SimilarityRanking> {
+    let reference_value = parse(&std::fs::read_to_string(reference)?)?;
+    let list_opts = DirDiffOptions {
+        extensions: opts.extensions.clone(),
+        recursive: opts.recursive,
+        include_hidden: opts.include_hidden,
+        max_files: opts.max_files,
+        ..DirDiffOptions::default()
+    };
+    let files = dirdiff::list_files(dir, &list_opts)?;
+    let truncated = files.len() >= opts.max_files;
+    let reference = reference.canonicalize().ok();
+
+    let mut ranked = Vec::new();
+    for rel in files {
+        if cancel.is_cancelled() {
+            return Err(DocError::Cancelled);
+        }
+        let path = dir.join(&rel);
+        if reference.is_some() && path.canonicalize().ok() == reference {
+            continue;
+        }
+        let result = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| parse(&text).map_err(|e| e.to_string()));
+        ranked.push(match result {
+            Ok(value) => RankedFile {
+                relative_path: rel,
+                similarity: Some(compare(&reference_value, &value)),
+                error: None,
+            },
+            Err(error) => RankedFile {
+                relative_path: rel,
+                similarity: None,
+                error: Some(error),
+            },
+        });
+    }
+    let scanned = ranked.len() as u32;
+    let errors = ranked.iter().filter(|f| f.error.is_some()).count() as u32;
+    ranked.sort_by(|a, b| {
+        let score = |f: &RankedFile| f.similarity.as_ref().map_or(-1.0, |s| s.score);
+        score(b)
+            .total_cmp(&score(a))
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    if let Some(limit) = opts.limit {
+        ranked.truncate(limit);
+    }
+    Ok(SimilarityRanking {
+        files: ranked,
+        scanned,
+        errors,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn scores_structure_keys_and_values() {
+        let a = r#"{"id": 1, "name": "x", "tags": ["a", "b"]}"#;
+        let b = r#"{"id": 1.0, "name": "y", "tags": ["a"], "extra": true}"#;
+        let out = similarity(a, b).unwrap();
+        assert_eq!((out.nodes_a, out.nodes_b, out.edit_distance), (6, 6, 3));
+        assert!((out.tree_similarity - 8.0 / 12.0).abs() < 1e-9);
+        assert_eq!((out.shared_keys, out.total_keys), (3, 4));
+        assert!((out.value_overlap - 1.0 / 3.0).abs() < 1e-9);
+        assert!(!out.identical);
+
+        let same = similarity(a, r#"{"tags": ["a", "b"], "name": "x", "id": 1}"#).unwrap();
+        assert!(same.identical && same.score == 1.0);
+        assert!(similarity(a, "{").is_err());
+    }
+
+    #[test]
+    fn ranks_folder_against_reference() {
+        let dir = TempDir::new("similarity-rank");
+        let reference = dir.join("reference.json");
+        std::fs::write(&reference, r#"{"user": {"id": 7, "roles": ["admin"]}}"#).unwrap();
+        std::fs::write(
+            dir.join("close.json"),
+            r#"{"user": {"id": 8, "roles": ["admin"]}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("far.json"), r#"[1, 2, 3]"#).unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+        std::fs::write(dir.join("notes.txt"), "{}").unwrap();
+
+        let out = rank_folder(
+            &reference,
+            &dir,
+            &RankOptions::default(),
+            &CancelFlag::never(),
+        )
+        .unwrap();
+        let order: Vec<&str> = out.files.iter().map(|f| f.relative_path.as_str()).collect();
+        assert_eq!(order, vec!["close.json", "far.json", "broken.json"]);
+        assert_eq!((out.scanned, out.errors), (3, 1));
+        assert!(out.files[2].error.is_some());
+    }
+}
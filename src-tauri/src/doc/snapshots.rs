@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use super::diff::{compute_diff, DiffEntry};
+use super::document::Document;
+use super::jobs::CancelFlag;
+use super::settings::SettingsStore;
+use super::store::DocStore;
+use super::timefmt::now_millis;
+use super::types::{DocError, DocHandle, DocResult, Path};
+
+const SCHEDULER_TICK: Duration = Duration::from_secs(5);
+const MAX_SNAPSHOT_BYTES: u64 = 16 * 1024 * 1024;
+const MAX_TOTAL_SNAPSHOT_BYTES: u64 = 128 * 1024 * 1024;
+
+struct Snapshot {
+    id: u64,
+    version: u64,
+    taken_at: u64,
+    text: Arc<str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub id: u64,
+    pub version: u64,
+    pub taken_at: u64,
+    pub age_seconds: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiff {
+    pub snapshot: SnapshotInfo,
+    pub entries: Vec<DiffEntry>,
+}
+
+impl Snapshot {
+    fn info(&self, now: u64) -> SnapshotInfo {
+        SnapshotInfo {
+            id: self.id,
+            version: self.version,
+            taken_at: self.taken_at,
+            age_seconds: now.saturating_sub(self.taken_at) / 1000,
+            bytes: self.text.len() as u64,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Snapshots {
+    docs: DashMap<DocHandle, VecDeque<Snapshot>>,
+    next_id: AtomicU64,
+}
+
+impl Snapshots {
+    pub fn due(&self, handle: DocHandle, doc: &Document, interval_ms: u64, now: u64) -> bool {
+        if doc.source_size > MAX_SNAPSHOT_BYTES {
+            return false;
+        }
+        match self
+            .docs
+            .get(&handle)
+            .and_then(|ring| ring.back().map(|last| (last.version, last.taken_at)))
+        {
+            Some((version, taken_at)) => {
+                version != doc.version && now.saturating_sub(taken_at) >= interval_ms
+            }
+            None => true,
+        }
+    }
+
+    pub fn capture(&self, handle: DocHandle, doc: &Document, limit: usize) -> DocResult<u64> {
+        let snapshot = Snapshot {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            version: doc.version,
+            taken_at: now_millis(),
+            text: doc.serialize()?.into(),
+        };
+        let id = snapshot.id;
+        {
+            let mut ring = self.docs.entry(handle).or_default();
+            ring.push_back(snapshot);
+            while ring.len() > limit.max(1) {
+                ring.pop_front();
+            }
+        }
+        self.enforce_total(MAX_TOTAL_SNAPSHOT_BYTES);
+        Ok(id)
+    }
+
+    fn enforce_total(&self, cap: u64) {
+        while self.total_bytes() > cap {
+            let oldest = self
+                .docs
+                .iter()
+                .filter_map(|ring| ring.front().map(|s| (s.taken_at, s.id, *ring.key())))
+                .min();
+            let Some((_, _, handle)) = oldest else {
+                return;
+            };
+            if let Some(mut ring) = self.docs.get_mut(&handle) {
+                ring.pop_front();
+            }
+            self.docs.remove_if(&handle, |_, ring| ring.is_empty());
+        }
+    }
+
+    pub fn bytes(&self, handle: DocHandle) -> u64 {
+        self.docs
+            .get(&handle)
+            .map_or(0, |ring| ring.iter().map(|s| s.text.len() as u64).sum())
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.docs
+            .iter()
+            .flat_map(|ring| ring.iter().map(|s| s.text.len() as u64).collect::<Vec<_>>())
+            .sum()
+    }
+
+    pub fn discard(&self, handle: DocHandle) -> u64 {
+        self.docs.remove(&handle).map_or(0, |(_, ring)| {
+            ring.iter().map(|s| s.text.len() as u64).sum()
+        })
+    }
+
+    pub fn list(&self, handle: DocHandle) -> Vec<SnapshotInfo> {
+        let now = now_millis();
+        self.docs
+            .get(&handle)
+            .map(|ring| ring.iter().rev().map(|s| s.info(now)).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn diff(&self, handle: DocHandle, id: u64, current: &Document) -> DocResult<SnapshotDiff> {
+        let (info, text) = self
+            .docs
+            .get(&handle)
+            .and_then(|ring| {
+                ring.iter()
+                    .find(|s| s.id == id)
+                    .map(|s| (s.info(now_millis()), s.text.clone()))
+            })
+            .ok_or_else(|| DocError::Edit(format!("snapshot {id} not found")))?;
+        let before = Document::from_text(&text, None)?.get_value(&Path::root())?;
+        let after = current.get_value(&Path::root())?;
+        Ok(SnapshotDiff {
+            snapshot: info,
+            entries: compute_diff(&before, &after, &CancelFlag::never())?,
+        })
+    }
+
+    pub fn retain(&self, keep: impl Fn(DocHandle) -> bool) {
+        self.docs.retain(|handle, _| keep(*handle));
+    }
+
+    pub fn tick(&self, store: &DocStore, interval_ms: u64, limit: usize) {
+        let now = now_millis();
+        for (handle, doc) in store.resident() {
+            let doc = doc.read();
+            if self.due(handle, &doc, interval_ms, now) {
+                if let Err(e) = self.capture(handle, &doc, limit) {
+                    tracing::warn!(error = %e, "document snapshot failed");
+                }
+            }
+        }
+        self.retain(|handle| store.contains(handle));
+    }
+}
+
+pub fn spawn_scheduler(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let (Some(snapshots), Some(store), Some(settings)) = (
+            app.try_state::<Arc<Snapshots>>(),
+            app.try_state::<Arc<DocStore>>(),
+            app.try_state::<Arc<SettingsStore>>(),
+        ) else {
+            return;
+        };
+        if let Ok(prefs) = settings.get() {
+            if prefs.snapshot_interval_secs > 0 {
+                snapshots.tick(
+                    &store,
+                    prefs.snapshot_interval_secs * 1000,
+                    prefs.snapshot_limit as usize,
+                );
+            }
+        }
+        std::thread::sleep(SCHEDULER_TICK);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_changed_versions_and_diffs_against_current() {
+        let store = DocStore::new();
+        let handle = store.insert(Document::from_text(r#"{"a": 1}"#, None).unwrap());
+        let snapshots = Snapshots::default();
+
+        snapshots.tick(&store, 0, 2);
+        snapshots.tick(&store, 0, 2);
+        assert_eq!(snapshots.list(handle).len(), 1);
+        let first = snapshots.list(handle)[0].id;
+
+        let replacement = Document::from_text(r#"{"a": 2, "b": true}"#, None).unwrap();
+        {
+            let arc = store.get(handle).unwrap();
+            let mut doc = arc.write();
+            let version = doc.version + 1;
+            *doc = replacement;
+            doc.version = version;
+        }
+        assert!(!snapshots.due(handle, &store.get(handle).unwrap().read(), 60_000, 0));
+        snapshots.tick(&store, 0, 2);
+        let listed = snapshots.list(handle);
+        assert_eq!(listed.len(), 2);
+        assert!(listed[0].id > listed[1].id);
+
+        let current = store.get(handle).unwrap();
+        let diff = snapshots.diff(handle, first, &current.read()).unwrap();
+        assert_eq!(diff.entries.len(), 2);
+        assert!(snapshots.diff(handle, 999, &current.read()).is_err());
+
+        store.remove(handle);
+        snapshots.tick(&store, 0, 2);
+        assert!(snapshots.list(handle).is_empty());
+    }
+
+    #[test]
+    fn total_cap_evicts_the_oldest_snapshots_first() {
+        let store = DocStore::new();
+        let snapshots = Snapshots::default();
+        let a = store.insert(Document::from_text("[1, 2, 3]", None).unwrap());
+        let b = store.insert(Document::from_text("[4]", None).unwrap());
+        snapshots
+            .capture(a, &store.get(a).unwrap().read(), 5)
+            .unwrap();
+        snapshots
+            .capture(b, &store.get(b).unwrap().read(), 5)
+            .unwrap();
+        let total = snapshots.total_bytes();
+        assert_eq!(total, snapshots.bytes(a) + snapshots.bytes(b));
+        snapshots.enforce_total(total - 1);
+        assert!(snapshots.list(a).is_empty());
+        assert_eq!(snapshots.list(b).len(), 1);
+        let remaining = snapshots.bytes(b);
+        assert_eq!(snapshots.discard(b), remaining);
+        assert_eq!(snapshots.total_bytes(), 0);
+    }
+}
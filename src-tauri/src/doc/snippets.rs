@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::document::write_atomic;
+use super::placeholders::{expand, variables_in, Variable};
+use super::timefmt::now_millis;
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub body: String,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetInput {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetInfo {
+    #[serde(flatten)]
+    pub snippet: Snippet,
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedSnippet {
+    pub text: String,
+    pub fragment: bool,
+}
+
+pub fn snippets_path(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let base = super::portable::data_dir(app)?;
+    Ok(base.join("snippets.json"))
+}
+
+pub fn load(path: &FsPath) -> std::io::Result<Vec<Snippet>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn store(path: &FsPath, snippets: &[Snippet]) -> std::io::Result<()> {
+    let json =
+        serde_json::to_string_pretty(snippets).map_err(|e| std::io::Error::other(e.to_string()))?;
+    write_atomic(path, json.as_bytes())
+}
+
+pub fn list(path: &FsPath) -> std::io::Result<Vec<SnippetInfo>> {
+    Ok(load(path)?
+        .into_iter()
+        .map(|snippet| SnippetInfo {
+            variables: variables_in(&snippet.body),
+            snippet,
+        })
+        .collect())
+}
+
+pub fn save(path: &FsPath, input: SnippetInput) -> DocResult<Snippet> {
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err(DocError::Edit("snippet name is required".into()));
+    }
+    if input.body.trim().is_empty() {
+        return Err(DocError::Edit("snippet body is empty".into()));
+    }
+    let mut all = load(path)?;
+    let now = now_millis().to_string();
+    let existing = input
+        .id
+        .as_deref()
+        .and_then(|id| all.iter_mut().find(|s| s.id == id));
+    let saved = match existing {
+        Some(s) => {
+            s.name = name;
+            s.description = input.description;
+            s.body = input.body;
+            s.updated_at = now;
+            s.clone()
+        }
+        None => {
+            let s = Snippet {
+                id: input.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                name,
+                description: input.description,
+                body: input.body,
+                created_at: now.clone(),
+                updated_at: now,
+            };
+            all.push(s.clone());
+            s
+        }
+    };
+    store(path, &all)?;
+    Ok(saved)
+}
+
+pub fn delete(path: &FsPath, id: &str) -> std::io::Result<bool> {
+    let mut all = load(path)?;
+    let before = all.len();
+    all.retain(|s| s.id != id);
+    if all.len() == before {
+        return Ok(false);
+    }
+    store(path, &all)?;
+    Ok(true)
+}
+
+pub fn import(path: &FsPath, incoming: Vec<Snippet>) -> std::io::Result<u32> {
+    let mut all = load(path)?;
+    let count = incoming.len() as u32;
+    for snippet in incoming {
+        match all.iter_mut().find(|s| s.id == snippet.id) {
+            Some(existing) => *existing = snippet,
+            None => all.push(snippet),
+        }
+    }
+    store(path, &all)?;
+    Ok(count)
+}
+
+pub fn render(
+    path: &FsPath,
+    id: &str,
+    vars: &HashMap<String, String>,
+) -> DocResult<RenderedSnippet> {
+    let snippet = load(path)?
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| DocError::Edit(format!("unknown snippet: {id}")))?;
+    render_body(&snippet.body, vars)
+}
+
+pub fn render_body(body: &str, vars: &HashMap<String, String>) -> DocResult<RenderedSnippet> {
+    let text = expand(body, vars);
+    if serde_json::from_str::<serde_json::Value>(&text).is_ok() {
+        return Ok(RenderedSnippet {
+            text,
+            fragment: false,
+        });
+    }
+    let trimmed = text.trim().trim_end_matches(',');
+    let as_members = serde_json::from_str::<serde_json::Value>(&format!("{{{trimmed}}}"));
+    let as_items = serde_json::from_str::<serde_json::Value>(&format!("[{trimmed}]"));
+    if as_members.is_ok() || as_items.is_ok() {
+        return Ok(RenderedSnippet {
+            text,
+            fragment: true,
+        });
+    }
+    Err(DocError::Parse(
+        "snippet is neither a JSON value nor a list of members/items".into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    fn input(id: Option<&str>, name: &str, body: &str) -> SnippetInput {
+        SnippetInput {
+            id: id.map(Into::into),
+            name: name.into(),
+            description: None,
+            body: body.into(),
+        }
+    }
+
+    #[test]
+    fn save_list_update_delete_cycle() {
+        let dir = TempDir::new("snippets-cycle");
+        let path = dir.join("snippets.json");
+        assert!(list(&path).unwrap().is_empty());
+
+        let s = save(&path, input(None, "user", r#"{"name": "{{name}}"}"#)).unwrap();
+        let listed = list(&path).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].variables[0].name, "name");
+
+        let updated = save(&path, input(Some(&s.id), "person", r#"{"n": 1}"#)).unwrap();
+        assert_eq!(updated.id, s.id);
+        assert_eq!(updated.created_at, s.created_at);
+        let listed = list(&path).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].snippet.name, "person");
+
+        assert!(delete(&path, &s.id).unwrap());
+        assert!(!delete(&path, &s.id).unwrap());
+    }
+
+    #[test]
+    fn save_rejects_blank_name_or_body() {
+        let dir = TempDir::new("snippets-blank");
+        let path = dir.join("snippets.json");
+        assert!(matches!(
+            save(&path, input(None, "  ", "{}")),
+            Err(DocError::Edit(_))
+        ));
+        assert!(matches!(
+            save(&path, input(None, "x", "   ")),
+            Err(DocError::Edit(_))
+        ));
+    }
+
+    #[test]
+    fn render_expands_vars_and_generators() {
+        let dir = TempDir::new("snippets-render");
+        let path = dir.join("snippets.json");
+        let s = save(
+            &path,
+            input(
+                None,
+                "event",
+                r#"{"id": "{{$uuid}}", "kind": "{{kind|click}}"}"#,
+            ),
+        )
+        .unwrap();
+        let r = render(&path, &s.id, &HashMap::new()).unwrap();
+        assert!(!r.fragment);
+        let v: serde_json::Value = serde_json::from_str(&r.text).unwrap();
+        assert_eq!(v["kind"], "click");
+        assert_eq!(v["id"].as_str().unwrap().len(), 36);
+        assert!(render(&path, "missing", &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn member_and_item_fragments_are_accepted() {
+        let members = render_body(r#""a": 1, "b": true,"#, &HashMap::new()).unwrap();
+        assert!(members.fragment);
+        let items = render_body("1, 2, 3", &HashMap::new()).unwrap();
+        assert!(items.fragment);
+        assert!(render_body("{oops", &HashMap::new()).is_err());
+    }
+}
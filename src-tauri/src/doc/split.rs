@@ -0,0 +1,274 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::jobs::CancelFlag;
+use super::provenance::ProvenanceWriter;
+use super::records::{self, RecordFormat, RecordWriter};
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SplitStrategy {
+    #[serde(rename_all = "camelCase")]
+    Records { count: u64 },
+    #[serde(rename_all = "camelCase")]
+    Bytes { max_bytes: u64 },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SplitOptions {
+    pub output_dir: Option<String>,
+    pub output_format: Option<RecordFormat>,
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkInfo {
+    pub path: String,
+    pub records: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitResult {
+    pub output_dir: String,
+    pub total_records: u64,
+    pub chunks: Vec<ChunkInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConcatResult {
+    pub output_path: String,
+    pub files: u32,
+    pub records: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance_path: Option<String>,
+}
+
+fn framing(format: RecordFormat) -> u64 {
+    match format {
+        RecordFormat::Array => 4,
+        RecordFormat::Ndjson => 1,
+    }
+}
+
+struct Chunker {
+    dir: PathBuf,
+    prefix: String,
+    format: RecordFormat,
+    current: Option<(PathBuf, RecordWriter<BufWriter<File>>)>,
+    chunks: Vec<ChunkInfo>,
+}
+
+impl Chunker {
+    fn close(&mut self) -> DocResult<()> {
+        if let Some((path, writer)) = self.current.take() {
+            let (records, bytes) = (writer.count(), writer.bytes());
+            writer.finish()?;
+            self.chunks.push(ChunkInfo {
+                path: path.to_string_lossy().into_owned(),
+                records,
+                bytes,
+            });
+        }
+        Ok(())
+    }
+
+    fn writer(&mut self) -> DocResult<&mut RecordWriter<BufWriter<File>>> {
+        if self.current.is_none() {
+            let name = format!(
+                "{}-{:04}.{}",
+                self.prefix,
+                self.chunks.len() + 1,
+                self.format.extension()
+            );
+            let path = self.dir.join(name);
+            let writer = records::create(&path, self.format)?;
+            self.current = Some((path, writer));
+        }
+        Ok(&mut self.current.as_mut().expect("chunk just opened").1)
+    }
+}
+
+pub fn split_file(
+    path: &FsPath,
+    strategy: SplitStrategy,
+    opts: &SplitOptions,
+    cancel: &CancelFlag,
+) -> DocResult<SplitResult> {
+    match strategy {
+        SplitStrategy::Records { count: 0 } | SplitStrategy::Bytes { max_bytes: 0 } => {
+            return Err(DocError::Edit(
+                "chunk size must be greater than zero".into(),
+            ));
+        }
+        _ => {}
+    }
+    let mut reader = records::open(path)?;
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "chunk".into());
+    let dir = match &opts.output_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => path
+            .parent()
+            .unwrap_or(FsPath::new(""))
+            .join(format!("{stem}-chunks")),
+    };
+    std::fs::create_dir_all(&dir)?;
+    let format = opts.output_format.unwrap_or(reader.format());
+    let mut chunker = Chunker {
+        dir: dir.clone(),
+        prefix: opts.prefix.clone().unwrap_or(stem),
+        format,
+        current: None,
+        chunks: Vec::new(),
+    };
+    let mut total_records = 0u64;
+    while let Some(value) = reader.next_value()? {
+        if cancel.is_cancelled() {
+            return Err(DocError::Cancelled);
+        }
+        let record = serde_json::to_vec(&value).map_err(|e| DocError::Export(e.to_string()))?;
+        let full = match (&chunker.current, strategy) {
+            (Some((_, w)), SplitStrategy::Records { count }) => w.count() >= count,
+            (Some((_, w)), SplitStrategy::Bytes { max_bytes }) => {
+                w.count() > 0 && w.bytes() + record.len() as u64 + framing(format) > max_bytes
+            }
+            (None, _) => false,
+        };
+        if full {
+            chunker.close()?;
+        }
+        chunker.writer()?.write_raw(&record)?;
+        total_records += 1;
+    }
+    chunker.close()?;
+    Ok(SplitResult {
+        output_dir: dir.to_string_lossy().into_owned(),
+        total_records,
+        chunks: chunker.chunks,
+    })
+}
+
+pub fn concat_files(
+    paths: &[String],
+    output: &FsPath,
+    provenance: bool,
+    cancel: &CancelFlag,
+) -> DocResult<ConcatResult> {
+    if paths.is_empty() {
+        return Err(DocError::Edit("nothing to concatenate".into()));
+    }
+    let format = RecordFormat::from_extension(output).unwrap_or(RecordFormat::Array);
+    let mut writer = records::create(output, format)?;
+    let mut origins = provenance
+        .then(|| ProvenanceWriter::create(output))
+        .transpose()?;
+    for path in paths {
+        let mut reader = records::open(FsPath::new(path))?;
+        while let Some(value) = reader.next_value()? {
+            if cancel.is_cancelled() {
+                return Err(DocError::Cancelled);
+            }
+            if let Some(origins) = origins.as_mut() {
+                let pointer = format!("/{}", writer.count());
+                origins.record(&pointer, path, Some(reader.record_line()))?;
+            }
+            writer.write(&value)?;
+        }
+    }
+    let records = writer.count();
+    writer.finish()?;
+    Ok(ConcatResult {
+        output_path: output.to_string_lossy().into_owned(),
+        files: paths.len() as u32,
+        records,
+        provenance_path: origins.map(ProvenanceWriter::finish).transpose()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::{json, Value};
+
+    fn read_all(path: &str) -> Vec<Value> {
+        let mut reader = records::open(FsPath::new(path)).unwrap();
+        let mut out = Vec::new();
+        while let Some(v) = reader.next_value().unwrap() {
+            out.push(v);
+        }
+        out
+    }
+
+    #[test]
+    fn splits_by_record_count_and_concatenates_back() {
+        let dir = TempDir::new("split-records");
+        let source = dir.join("data.json");
+        let items: Vec<Value> = (0..7).map(|i| json!({"id": i})).collect();
+        std::fs::write(&source, serde_json::to_string_pretty(&items).unwrap()).unwrap();
+        let result = split_file(
+            &source,
+            SplitStrategy::Records { count: 3 },
+            &SplitOptions::default(),
+            &CancelFlag::never(),
+        )
+        .unwrap();
+        assert_eq!(result.total_records, 7);
+        let counts: Vec<u64> = result.chunks.iter().map(|c| c.records).collect();
+        assert_eq!(counts, vec![3, 3, 1]);
+        assert!(result.chunks[0].path.ends_with("data-0001.json"));
+
+        let paths: Vec<String> = result.chunks.iter().map(|c| c.path.clone()).collect();
+        let joined = dir.join("joined.ndjson");
+        let concat = concat_files(&paths, &joined, true, &CancelFlag::never()).unwrap();
+        assert_eq!((concat.files, concat.records), (3, 7));
+        assert_eq!(read_all(&concat.output_path), items);
+        let sidecar = std::fs::read_to_string(concat.provenance_path.unwrap()).unwrap();
+        let origins: Value = serde_json::from_str(&sidecar).unwrap();
+        assert_eq!(origins["/3"]["file"], Value::from(paths[1].as_str()));
+    }
+
+    #[test]
+    fn splits_ndjson_by_size() {
+        let dir = TempDir::new("split-bytes");
+        let source = dir.join("log.ndjson");
+        let lines: String = (0..10).map(|i| format!("{{\"n\":{i}}}\n")).collect();
+        std::fs::write(&source, lines).unwrap();
+        let opts = SplitOptions {
+            output_dir: Some(dir.join("out").to_string_lossy().into_owned()),
+            prefix: Some("part".into()),
+            ..Default::default()
+        };
+        let result = split_file(
+            &source,
+            SplitStrategy::Bytes { max_bytes: 20 },
+            &opts,
+            &CancelFlag::never(),
+        )
+        .unwrap();
+        assert_eq!(result.chunks.len(), 5);
+        assert!(result
+            .chunks
+            .iter()
+            .all(|c| c.bytes <= 20 && c.records == 2));
+        assert!(result.chunks[4].path.ends_with("part-0005.ndjson"));
+        assert!(split_file(
+            &source,
+            SplitStrategy::Records { count: 0 },
+            &opts,
+            &CancelFlag::never()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::timefmt::{now_millis, rfc3339_utc};
+use super::types::{DocError, DocResult};
+
+const INDEX_FILE: &str = "index.json";
+const PREVIEW_CHARS: usize = 120;
+
+static INDEX_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StashEntry {
+    pub id: String,
+    pub label: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub bytes: u64,
+    #[serde(default)]
+    pub preview: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StashedContent {
+    #[serde(flatten)]
+    pub entry: StashEntry,
+    pub content: String,
+}
+
+pub fn stash_dir(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let dir = super::portable::data_dir(app)?.join("stash");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn load(dir: &FsPath) -> std::io::Result<Vec<StashEntry>> {
+    match std::fs::read_to_string(dir.join(INDEX_FILE)) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn store(dir: &FsPath, entries: &[StashEntry]) -> std::io::Result<()> {
+    let json =
+        serde_json::to_string_pretty(entries).map_err(|e| std::io::Error::other(e.to_string()))?;
+    let tmp = dir.join(format!("{INDEX_FILE}.tmp"));
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(tmp, dir.join(INDEX_FILE))
+}
+
+fn blob_path(dir: &FsPath, id: &str) -> DocResult<PathBuf> {
+    if id.len() != 64 || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(DocError::Edit(format!("invalid stash id: {id}")));
+    }
+    Ok(dir.join(format!("{}.blob", id.to_ascii_lowercase())))
+}
+
+fn preview(content: &str) -> String {
+    let flat = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    match flat.char_indices().nth(PREVIEW_CHARS) {
+        Some((at, _)) => format!("{}…", &flat[..at]),
+        None => flat,
+    }
+}
+
+pub fn stash(
+    dir: &FsPath,
+    content: &str,
+    label: Option<String>,
+    tags: Vec<String>,
+) -> DocResult<StashEntry> {
+    if content.trim().is_empty() {
+        return Err(DocError::Edit("nothing to stash".into()));
+    }
+    let id = blake3::hash(content.as_bytes()).to_hex().to_string();
+    let blob = blob_path(dir, &id)?;
+    let _guard = INDEX_LOCK.lock();
+    if !blob.exists() {
+        let tmp = blob.with_extension("tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &blob)?;
+    }
+    let label = label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty());
+    let mut tags: Vec<String> = tags
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    let mut all = load(dir)?;
+    let now = rfc3339_utc(now_millis());
+    let entry = match all.iter_mut().find(|e| e.id == id) {
+        Some(existing) => {
+            if let Some(label) = label {
+                existing.label = label;
+            }
+            existing.tags.extend(tags);
+            existing.tags.sort();
+            existing.tags.dedup();
+            existing.updated_at = now;
+            existing.clone()
+        }
+        None => {
+            let entry = StashEntry {
+                label: label.unwrap_or_else(|| format!("Stash {}", &id[..8])),
+                id,
+                tags,
+                bytes: content.len() as u64,
+                preview: preview(content),
+                created_at: now.clone(),
+                updated_at: now,
+            };
+            all.push(entry.clone());
+            entry
+        }
+    };
+    store(dir, &all)?;
+    Ok(entry)
+}
+
+pub fn list(dir: &FsPath) -> std::io::Result<Vec<StashEntry>> {
+    let mut all = load(dir)?;
+    all.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(all)
+}
+
+pub fn restore(dir: &FsPath, id: &str) -> DocResult<StashedContent> {
+    let blob = blob_path(dir, id)?;
+    let entry = load(dir)?
+        .into_iter()
+        .find(|e| e.id.eq_ignore_ascii_case(id))
+        .ok_or_else(|| DocError::Edit(format!("stash {id} not found")))?;
+    let content = std::fs::read_to_string(&blob)?;
+    if blake3::hash(content.as_bytes()).to_hex().as_str() != entry.id {
+        return Err(DocError::Parse(format!(
+            "stash {id} is corrupted: content does not match its hash"
+        )));
+    }
+    Ok(StashedContent { entry, content })
+}
+
+pub fn delete(dir: &FsPath, id: &str) -> DocResult<bool> {
+    let blob = blob_path(dir, id)?;
+    let _guard = INDEX_LOCK.lock();
+    let mut all = load(dir)?;
+    let before = all.len();
+    all.retain(|e| !e.id.eq_ignore_ascii_case(id));
+    if all.len() == before {
+        return Ok(false);
+    }
+    store(dir, &all)?;
+    match std::fs::remove_file(blob) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    #[test]
+    fn deduplicates_by_content_and_merges_labels() {
+        let dir = TempDir::new("stash-dedupe");
+        let first = stash(&dir, r#"{"a": 1}"#, None, vec!["api".into()]).unwrap();
+        assert!(first.label.starts_with("Stash "));
+        let again = stash(
+            &dir,
+            r#"{"a": 1}"#,
+            Some("payload".into()),
+            vec!["bug".into(), " api ".into(), "bug".into()],
+        )
+        .unwrap();
+        assert_eq!(again.id, first.id);
+        assert_eq!(again.label, "payload");
+        assert_eq!(again.tags, vec!["api", "bug"]);
+        assert!(again.created_at.ends_with('Z'));
+        stash(&dir, "[2]", Some("other".into()), Vec::new()).unwrap();
+        assert_eq!(list(&dir).unwrap().len(), 2);
+
+        let restored = restore(&dir, &first.id).unwrap();
+        assert_eq!(restored.content, r#"{"a": 1}"#);
+        assert!(delete(&dir, &first.id).unwrap());
+        assert!(restore(&dir, &first.id).is_err());
+        assert!(restore(&dir, "../index").is_err());
+    }
+
+    #[test]
+    fn detects_tampered_blobs() {
+        let dir = TempDir::new("stash-tamper");
+        let entry = stash(&dir, "secret", None, Vec::new()).unwrap();
+        std::fs::write(blob_path(&dir, &entry.id).unwrap(), "changed").unwrap();
+        assert!(matches!(restore(&dir, &entry.id), Err(DocError::Parse(_))));
+        assert!(stash(&dir, "  ", None, Vec::new()).is_err());
+    }
+}
@@ -1,14 +1,33 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
+use super::blobs::{Blob, BlobCache};
 use super::document::Document;
-use super::types::DocHandle;
+use super::memory::{
+    excess_bytes, largest_indexes, least_recent, DocumentMemory, MemoryUsage, PressureRelief,
+    Spilled,
+};
+use super::search_index::SearchIndex;
+use super::snapshots::Snapshots;
+use super::telemetry;
+use super::text_buffer::TextBuffer;
+use super::types::{DocHandle, DocResult};
 
 #[derive(Default)]
 pub struct DocStore {
     docs: DashMap<DocHandle, Arc<RwLock<Document>>>,
+    indexes: DashMap<DocHandle, Arc<SearchIndex>>,
+    buffers: DashMap<DocHandle, Arc<Mutex<TextBuffer>>>,
+    blobs: BlobCache,
+    spilled: DashMap<DocHandle, Spilled>,
+    snapshots: Arc<Snapshots>,
+    touched: DashMap<DocHandle, u64>,
+    clock: AtomicU64,
+    memory_limit: AtomicU64,
 }
 
 impl DocStore {
@@ -19,6 +38,8 @@ impl DocStore {
     pub fn insert(&self, doc: Document) -> DocHandle {
         let handle = DocHandle::new();
         self.docs.insert(handle, Arc::new(RwLock::new(doc)));
+        self.touch(handle);
+        self.relieve_pressure(handle);
         handle
     }
 
@@ -33,11 +54,324 @@ impl DocStore {
     }
 
     pub fn get(&self, handle: DocHandle) -> Option<Arc<RwLock<Document>>> {
-        self.docs.get(&handle).map(|entry| entry.clone())
+        let doc = match self.docs.get(&handle) {
+            Some(entry) => entry.clone(),
+            None => self.restore(handle)?,
+        };
+        self.touch(handle);
+        Some(doc)
     }
 
     pub fn remove(&self, handle: DocHandle) -> bool {
-        self.docs.remove(&handle).is_some()
+        self.indexes.remove(&handle);
+        self.buffers.remove(&handle);
+        self.blobs.evict(handle);
+        self.snapshots.discard(handle);
+        self.touched.remove(&handle);
+        let spilled = self.spilled.remove(&handle);
+        if let Some((_, spilled)) = &spilled {
+            spilled.discard();
+        }
+        self.docs.remove(&handle).is_some() || spilled.is_some()
+    }
+
+    fn touch(&self, handle: DocHandle) {
+        let tick = self.clock.fetch_add(1, Ordering::Relaxed);
+        self.touched.insert(handle, tick);
+    }
+
+    fn restore(&self, handle: DocHandle) -> Option<Arc<RwLock<Document>>> {
+        match self.docs.entry(handle) {
+            Entry::Occupied(entry) => Some(entry.get().clone()),
+            Entry::Vacant(entry) => {
+                let (_, spilled) = self.spilled.remove(&handle)?;
+                match spilled.load() {
+                    Ok(doc) => {
+                        spilled.discard();
+                        let doc = Arc::new(RwLock::new(doc));
+                        entry.insert(doc.clone());
+                        Some(doc)
+                    }
+                    Err(e) => {
+                        tracing::warn!("could not restore spilled document {}: {e}", handle.0);
+                        self.spilled.insert(handle, spilled);
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn contains(&self, handle: DocHandle) -> bool {
+        self.docs.contains_key(&handle) || self.spilled.contains_key(&handle)
+    }
+
+    pub fn resident(&self) -> Vec<(DocHandle, Arc<RwLock<Document>>)> {
+        self.docs
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect()
+    }
+
+    pub fn retarget_file(&self, from: &str, to: &str) -> Vec<DocHandle> {
+        let mut moved = Vec::new();
+        for entry in self.docs.iter() {
+            let mut doc = entry.value().write();
+            if doc.file_path.as_deref() == Some(from) {
+                doc.set_file_path(to.to_string());
+                moved.push(*entry.key());
+            }
+        }
+        for mut entry in self.spilled.iter_mut() {
+            if entry.value_mut().retarget(from, to) {
+                moved.push(*entry.key());
+            }
+        }
+        moved
+    }
+
+    pub fn snapshots(&self) -> Arc<Snapshots> {
+        self.snapshots.clone()
+    }
+
+    pub fn set_memory_limit(&self, bytes: u64) {
+        self.memory_limit.store(bytes, Ordering::Relaxed);
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut documents: Vec<DocumentMemory> = self
+            .docs
+            .iter()
+            .map(|entry| {
+                let handle = *entry.key();
+                let (source_path, value, history, cache) = match entry.value().try_read() {
+                    Some(doc) => (
+                        doc.source_path.clone(),
+                        doc.value_heap_bytes() as u64,
+                        doc.history_heap_bytes() as u64,
+                        doc.cache_heap_bytes() as u64,
+                    ),
+                    None => (None, 0, 0, 0),
+                };
+                DocumentMemory {
+                    handle,
+                    source_path,
+                    value_bytes: value,
+                    history_bytes: history,
+                    cache_bytes: cache,
+                    index_bytes: self
+                        .indexes
+                        .get(&handle)
+                        .map_or(0, |index| index.heap_bytes() as u64),
+                    text_buffer_bytes: self
+                        .buffers
+                        .get(&handle)
+                        .and_then(|buffer| buffer.try_lock().map(|b| b.heap_bytes() as u64))
+                        .unwrap_or(0),
+                    blob_bytes: self.blobs.bytes(handle),
+                    snapshot_bytes: self.snapshots.bytes(handle),
+                    spilled_bytes: 0,
+                }
+            })
+            .collect();
+        documents.extend(self.spilled.iter().map(|entry| DocumentMemory {
+            handle: *entry.key(),
+            source_path: entry.value().source_path().map(str::to_string),
+            value_bytes: 0,
+            history_bytes: 0,
+            cache_bytes: 0,
+            index_bytes: 0,
+            text_buffer_bytes: 0,
+            blob_bytes: self.blobs.bytes(*entry.key()),
+            snapshot_bytes: self.snapshots.bytes(*entry.key()),
+            spilled_bytes: entry.value().bytes(),
+        }));
+        documents.sort_by(|a, b| b.resident_bytes().cmp(&a.resident_bytes()));
+        MemoryUsage {
+            rss_bytes: telemetry::rss_bytes(),
+            tracked_bytes: documents.iter().map(DocumentMemory::resident_bytes).sum(),
+            spilled_bytes: documents.iter().map(|d| d.spilled_bytes).sum(),
+            limit_bytes: self.memory_limit.load(Ordering::Relaxed),
+            documents,
+        }
+    }
+
+    fn relieve_pressure(&self, keep: DocHandle) -> PressureRelief {
+        let mut relief = PressureRelief::default();
+        let limit = self.memory_limit.load(Ordering::Relaxed);
+        if limit == 0 {
+            return relief;
+        }
+        let usage = self.memory_usage();
+        let mut excess = excess_bytes(usage.tracked_bytes, limit);
+        if excess == 0 {
+            return relief;
+        }
+        let mut free = |relief: &mut PressureRelief, bytes: u64| {
+            relief.freed_bytes += bytes;
+            excess = excess.saturating_sub(bytes);
+            excess == 0
+        };
+
+        for (handle, bytes) in largest_indexes(&usage.documents, keep) {
+            if self.indexes.remove(&handle).is_some() {
+                relief.dropped_indexes += 1;
+                if free(&mut relief, bytes) {
+                    return self.report(relief);
+                }
+            }
+        }
+
+        let with_blobs = usage
+            .documents
+            .iter()
+            .filter(|d| d.blob_bytes > 0 && d.handle != keep)
+            .map(|d| d.handle);
+        for handle in with_blobs {
+            let freed = self.blobs.evict(handle);
+            if freed > 0 {
+                relief.dropped_blobs += 1;
+                if free(&mut relief, freed) {
+                    return self.report(relief);
+                }
+            }
+        }
+
+        let last_touched = |handle: DocHandle| self.touched.get(&handle).map_or(0, |tick| *tick);
+        let idle = least_recent(
+            self.docs.iter().map(|entry| *entry.key()).collect(),
+            keep,
+            last_touched,
+        );
+        for &handle in &idle {
+            let Some(doc) = self.docs.get(&handle).map(|entry| entry.clone()) else {
+                continue;
+            };
+            let Some(doc) = doc.try_read() else {
+                continue;
+            };
+            let freed = doc.clear_caches() as u64;
+            if freed > 0 {
+                relief.cleared_caches += 1;
+                if free(&mut relief, freed) {
+                    return self.report(relief);
+                }
+            }
+        }
+
+        let with_snapshots = usage
+            .documents
+            .iter()
+            .filter(|d| d.snapshot_bytes > 0)
+            .map(|d| d.handle)
+            .collect();
+        for handle in least_recent(with_snapshots, keep, last_touched) {
+            let freed = self.snapshots.discard(handle);
+            if freed > 0 {
+                relief.dropped_snapshots += 1;
+                if free(&mut relief, freed) {
+                    return self.report(relief);
+                }
+            }
+        }
+
+        for handle in idle {
+            match self.spill(handle) {
+                Ok(Some(freed)) => {
+                    relief.spilled_documents += 1;
+                    if free(&mut relief, freed) {
+                        break;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("could not spill document {}: {e}", handle.0),
+            }
+        }
+        self.report(relief)
+    }
+
+    fn spill(&self, handle: DocHandle) -> DocResult<Option<u64>> {
+        let Some(arc) = self.docs.get(&handle).map(|entry| entry.clone()) else {
+            return Ok(None);
+        };
+        let (spilled, version, freed) = {
+            let Some(doc) = arc.try_read() else {
+                return Ok(None);
+            };
+            if !doc.can_spill() {
+                return Ok(None);
+            }
+            let freed = doc.value_heap_bytes() + doc.cache_heap_bytes();
+            (Spilled::write(handle, &doc)?, doc.version, freed as u64)
+        };
+        self.spilled.insert(handle, spilled);
+        let removed = self.docs.remove_if(&handle, |_, doc| {
+            Arc::strong_count(doc) == 2 && doc.try_read().is_some_and(|d| d.version == version)
+        });
+        if removed.is_none() {
+            if let Some((_, spilled)) = self.spilled.remove(&handle) {
+                spilled.discard();
+            }
+            return Ok(None);
+        }
+        let index = self.indexes.remove(&handle);
+        Ok(Some(
+            freed + index.map_or(0, |(_, index)| index.heap_bytes() as u64),
+        ))
+    }
+
+    fn report(&self, relief: PressureRelief) -> PressureRelief {
+        if !relief.is_empty() {
+            tracing::info!(
+                dropped_indexes = relief.dropped_indexes,
+                dropped_blobs = relief.dropped_blobs,
+                cleared_caches = relief.cleared_caches,
+                dropped_snapshots = relief.dropped_snapshots,
+                spilled_documents = relief.spilled_documents,
+                freed_bytes = relief.freed_bytes,
+                "relieved memory pressure"
+            );
+        }
+        relief
+    }
+
+    pub fn search_index(&self, handle: DocHandle) -> Option<Arc<SearchIndex>> {
+        self.indexes.get(&handle).map(|entry| entry.clone())
+    }
+
+    pub fn set_search_index(&self, handle: DocHandle, index: Arc<SearchIndex>) {
+        self.indexes.insert(handle, index);
+        self.relieve_pressure(handle);
+    }
+
+    pub fn blobs(&self) -> &BlobCache {
+        &self.blobs
+    }
+
+    /// Caches a data-protocol payload; it counts toward the memory limit like
+    /// any other per-document allocation.
+    pub fn remember_blob(&self, blob: Blob) -> Blob {
+        let handle = blob.handle;
+        let blob = self.blobs.remember(blob);
+        self.relieve_pressure(handle);
+        blob
+    }
+
+    pub fn text_buffer(&self, handle: DocHandle) -> Option<Arc<Mutex<TextBuffer>>> {
+        self.buffers.get(&handle).map(|entry| entry.clone())
+    }
+
+    pub fn set_text_buffer(&self, handle: DocHandle, buffer: TextBuffer) {
+        self.buffers.insert(handle, Arc::new(Mutex::new(buffer)));
+        self.relieve_pressure(handle);
+    }
+}
+
+impl Drop for DocStore {
+    fn drop(&mut self) {
+        for entry in self.spilled.iter() {
+            entry.value().discard();
+        }
     }
 }
 
@@ -95,6 +429,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn spills_idle_documents_over_the_limit_and_restores_them() {
+        let store = DocStore::new();
+        store.set_memory_limit(1);
+        let first = store.insert(Document::from_text(r#"{"a": [1, 2, 3]}"#, None).unwrap());
+        let second = store.insert(Document::from_text(r#"{"b": 2}"#, None).unwrap());
+        let usage = store.memory_usage();
+        let spilled: Vec<DocHandle> = usage
+            .documents
+            .iter()
+            .filter(|d| d.spilled_bytes > 0)
+            .map(|d| d.handle)
+            .collect();
+        assert_eq!(spilled, vec![first]);
+        assert_eq!(usage.spilled_bytes, r#"{"a":[1,2,3]}"#.len() as u64);
+
+        let doc = store.get(first).unwrap();
+        assert_eq!(
+            doc.read().get_value(&Path::root()).unwrap(),
+            serde_json::json!({"a": [1, 2, 3]})
+        );
+        assert_eq!(doc.read().source_size, 16);
+        assert_eq!(store.memory_usage().spilled_bytes, 0);
+        assert!(store.get(second).is_some());
+    }
+
     #[test]
     fn arc_keeps_doc_alive_after_remove() {
         let (store, handle) = store_with_doc(r#"{"a": 1}"#);
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::jobs::CancelFlag;
+use super::records::{RecordFormat, RecordReader};
+use super::schema_validate::SchemaError;
+use super::types::{DocError, DocResult};
+
+const DEFAULT_MAX_FAILURES: usize = 100;
+const DEFAULT_MAX_ERRORS_PER_RECORD: usize = 10;
+const MAX_ERROR_PATHS: usize = 50;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StreamValidateOptions {
+    pub max_failures: usize,
+    pub max_errors_per_record: usize,
+    pub schema_path: Option<String>,
+}
+
+impl Default for StreamValidateOptions {
+    fn default() -> Self {
+        Self {
+            max_failures: DEFAULT_MAX_FAILURES,
+            max_errors_per_record: DEFAULT_MAX_ERRORS_PER_RECORD,
+            schema_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordFailure {
+    pub line: u64,
+    pub index: u64,
+    pub errors: Vec<SchemaError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorPathCount {
+    pub path: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamValidationReport {
+    pub records: u64,
+    pub passed: u64,
+    pub failed: u64,
+    pub parse_errors: u64,
+    pub failures: Vec<RecordFailure>,
+    pub truncated: bool,
+    pub error_paths: Vec<ErrorPathCount>,
+    pub duration_ms: u64,
+}
+
+fn path_pattern(instance_path: &str) -> String {
+    if instance_path.is_empty() {
+        return "/".into();
+    }
+    instance_path
+        .split('/')
+        .map(|seg| {
+            if !seg.is_empty() && seg.bytes().all(|b| b.is_ascii_digit()) {
+                "*"
+            } else {
+                seg
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub fn validate_records<R: BufRead>(
+    mut reader: RecordReader<R>,
+    schema: &Value,
+    opts: &StreamValidateOptions,
+    cancel: &CancelFlag,
+) -> DocResult<StreamValidationReport> {
+    let started = Instant::now();
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|e| DocError::Schema(format!("compile error: {e}")))?;
+    let mut report = StreamValidationReport {
+        records: 0,
+        passed: 0,
+        failed: 0,
+        parse_errors: 0,
+        failures: Vec::new(),
+        truncated: false,
+        error_paths: Vec::new(),
+        duration_ms: 0,
+    };
+    let mut paths: HashMap<String, u64> = HashMap::new();
+    loop {
+        if cancel.is_cancelled() {
+            return Err(DocError::Cancelled);
+        }
+        let record = match reader.next_value() {
+            Ok(Some(record)) => record,
+            Ok(None) => break,
+            Err(DocError::Parse(e)) if reader.format() == RecordFormat::Ndjson => {
+                report.records += 1;
+                report.failed += 1;
+                report.parse_errors += 1;
+                if report.failures.len() < opts.max_failures {
+                    report.failures.push(RecordFailure {
+                        line: reader.record_line(),
+                        index: report.records - 1,
+                        errors: Vec::new(),
+                        parse_error: Some(e),
+                    });
+                } else {
+                    report.truncated = true;
+                }
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        report.records += 1;
+        let mut errors = Vec::new();
+        let mut any = false;
+        for err in validator.iter_errors(&record) {
+            any = true;
+            let instance_path = err.instance_path.to_string();
+            *paths.entry(path_pattern(&instance_path)).or_default() += 1;
+            if errors.len() < opts.max_errors_per_record {
+                errors.push(SchemaError {
+                    instance_path,
+                    schema_path: err.schema_path.to_string(),
+                    message: err.to_string(),
+                });
+            }
+        }
+        if !any {
+            report.passed += 1;
+            continue;
+        }
+        report.failed += 1;
+        if report.failures.len() < opts.max_failures {
+            report.failures.push(RecordFailure {
+                line: reader.record_line(),
+                index: report.records - 1,
+                errors,
+                parse_error: None,
+            });
+        } else {
+            report.truncated = true;
+        }
+    }
+    let mut error_paths: Vec<ErrorPathCount> = paths
+        .into_iter()
+        .map(|(path, count)| ErrorPathCount { path, count })
+        .collect();
+    error_paths.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.path.cmp(&b.path)));
+    error_paths.truncate(MAX_ERROR_PATHS);
+    report.error_paths = error_paths;
+    report.duration_ms = started.elapsed().as_millis() as u64;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "integer"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }
+        })
+    }
+
+    fn run(text: &str, opts: &StreamValidateOptions) -> StreamValidationReport {
+        let reader = RecordReader::new(text.as_bytes(), None).unwrap();
+        validate_records(reader, &schema(), opts, &CancelFlag::never()).unwrap()
+    }
+
+    #[test]
+    fn reports_failures_with_lines_and_paths() {
+        let text = "{\"id\": 1}\n{\"id\": \"x\"}\n\n{oops}\n{\"id\": 2, \"tags\": [1, \"a\", 3]}\n";
+        let report = run(text, &StreamValidateOptions::default());
+        assert_eq!(
+            (
+                report.records,
+                report.passed,
+                report.failed,
+                report.parse_errors
+            ),
+            (4, 1, 3, 1)
+        );
+        let lines: Vec<u64> = report.failures.iter().map(|f| f.line).collect();
+        assert_eq!(lines, vec![2, 4, 5]);
+        assert!(report.failures[1].parse_error.is_some());
+        assert_eq!(report.failures[2].errors.len(), 2);
+        assert_eq!(
+            report.error_paths[0],
+            ErrorPathCount {
+                path: "/tags/*".into(),
+                count: 2
+            }
+        );
+    }
+
+    #[test]
+    fn caps_failures_and_errors_per_record() {
+        let text: String = (0..5).map(|_| "{\"tags\": [1, 2, 3]}\n").collect();
+        let opts = StreamValidateOptions {
+            max_failures: 2,
+            max_errors_per_record: 1,
+            ..Default::default()
+        };
+        let report = run(&text, &opts);
+        assert_eq!(report.failed, 5);
+        assert_eq!(report.failures.len(), 2);
+        assert!(report.truncated);
+        assert!(report.failures.iter().all(|f| f.errors.len() == 1));
+    }
+
+    #[test]
+    fn validates_array_files_by_element() {
+        let reader = RecordReader::new("[{\"id\": 1},\n {}]".as_bytes(), None).unwrap();
+        let report = validate_records(
+            reader,
+            &schema(),
+            &StreamValidateOptions::default(),
+            &CancelFlag::never(),
+        )
+        .unwrap();
+        assert_eq!((report.passed, report.failed), (1, 1));
+        assert_eq!(report.failures[0].line, 2);
+    }
+}
@@ -0,0 +1,568 @@
+use serde::Serialize;
+
+use super::positions::PositionIndex;
+
+const MAX_DIAGNOSTICS: usize = 200;
+const MAX_DEPTH: usize = 512;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub line: u32,
+    pub column: u32,
+    pub offset: u32,
+    pub end_offset: u32,
+    pub message: String,
+    pub expected: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntaxReport {
+    pub valid: bool,
+    pub diagnostics: Vec<Diagnostic>,
+    pub truncated: bool,
+}
+
+struct Checker<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    line_starts: Vec<usize>,
+    diagnostics: Vec<Diagnostic>,
+    truncated: bool,
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'$' | b'+' | b'-' | b'.')
+}
+
+fn expected(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+impl Checker<'_> {
+    fn report(
+        &mut self,
+        start: usize,
+        end: usize,
+        message: impl Into<String>,
+        expected: Vec<String>,
+        suggestion: Option<&str>,
+    ) {
+        if self.diagnostics.len() >= MAX_DIAGNOSTICS {
+            self.truncated = true;
+            return;
+        }
+        let line = self.line_starts.partition_point(|&s| s <= start) - 1;
+        let column = self.text[self.line_starts[line]..start].chars().count();
+        self.diagnostics.push(Diagnostic {
+            line: line as u32 + 1,
+            column: column as u32 + 1,
+            offset: start as u32,
+            end_offset: end.max(start) as u32,
+            message: message.into(),
+            expected,
+            suggestion: suggestion.map(str::to_string),
+        });
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&s| s <= offset)
+    }
+
+    fn char_end(&self, at: usize) -> usize {
+        self.text[at..]
+            .chars()
+            .next()
+            .map_or(at, |c| at + c.len_utf8())
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+                self.pos += 1;
+            }
+            let rest = &self.bytes[self.pos..];
+            let end = if rest.starts_with(b"//") {
+                rest.iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(self.bytes.len(), |i| self.pos + i)
+            } else if rest.starts_with(b"/*") {
+                rest.windows(2)
+                    .skip(2)
+                    .position(|w| w == b"*/")
+                    .map_or(self.bytes.len(), |i| self.pos + i + 4)
+            } else {
+                return;
+            };
+            let start = self.pos;
+            self.pos = end;
+            self.report(
+                start,
+                end,
+                "comments are not allowed in JSON",
+                Vec::new(),
+                Some("remove the comment"),
+            );
+        }
+    }
+
+    fn string(&mut self, quote: u8) {
+        let start = self.pos;
+        self.pos += 1;
+        if quote == b'\'' {
+            self.report(
+                start,
+                start + 1,
+                "strings must use double quotes",
+                expected(&["\""]),
+                Some("replace the single quotes with double quotes"),
+            );
+        }
+        while let Some(b) = self.peek() {
+            match b {
+                b'\n' => break,
+                b'\\' => {
+                    let esc = self.pos;
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't') => {
+                            self.pos += 1
+                        }
+                        Some(b'u') => {
+                            let hex = self.bytes.get(self.pos + 1..self.pos + 5);
+                            if hex.is_some_and(|h| h.iter().all(u8::is_ascii_hexdigit)) {
+                                self.pos += 5;
+                            } else {
+                                self.pos += 1;
+                                self.report(
+                                    esc,
+                                    self.pos,
+                                    "\\u must be followed by four hex digits",
+                                    Vec::new(),
+                                    None,
+                                );
+                            }
+                        }
+                        Some(b'\'') if quote == b'\'' => self.pos += 1,
+                        Some(_) => {
+                            let end = self.char_end(self.pos);
+                            self.pos = end;
+                            self.report(
+                                esc,
+                                end,
+                                "invalid escape sequence",
+                                Vec::new(),
+                                Some("escape the backslash as \\\\"),
+                            );
+                        }
+                        None => break,
+                    }
+                }
+                b if b == quote => {
+                    self.pos += 1;
+                    return;
+                }
+                b'\t' => {
+                    self.report(
+                        self.pos,
+                        self.pos + 1,
+                        "tab characters must be escaped inside strings",
+                        Vec::new(),
+                        Some("use \\t"),
+                    );
+                    self.pos += 1;
+                }
+                _ => self.pos += 1,
+            }
+        }
+        self.report(
+            start,
+            self.pos,
+            "unterminated string",
+            expected(&[if quote == b'\'' { "'" } else { "\"" }]),
+            Some("add the closing quote"),
+        );
+    }
+
+    fn word(&mut self) {
+        let start = self.pos;
+        while self.peek().is_some_and(is_word_byte) {
+            self.pos += 1;
+        }
+        let word = &self.text[start..self.pos];
+        let suggestion = match word {
+            "true" | "false" | "null" => return,
+            "True" | "TRUE" => Some("use lowercase true"),
+            "False" | "FALSE" => Some("use lowercase false"),
+            "None" | "nil" | "NULL" | "Null" | "undefined" => Some("use null"),
+            "NaN" | "Infinity" | "-Infinity" => {
+                Some("JSON has no NaN or Infinity; use null or a string")
+            }
+            _ => None,
+        };
+        if word.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+            if serde_json::from_str::<serde_json::Number>(word).is_err() {
+                let hint = if word.starts_with('0') && word.len() > 1 && !word.starts_with("0.") {
+                    Some("remove the leading zeros")
+                } else if word.ends_with('.') {
+                    Some("add digits after the decimal point")
+                } else {
+                    None
+                };
+                self.report(
+                    start,
+                    self.pos,
+                    format!("invalid number {word}"),
+                    Vec::new(),
+                    suggestion.or(hint),
+                );
+            }
+            return;
+        }
+        if word.starts_with('.')
+            && serde_json::from_str::<serde_json::Number>(&format!("0{word}")).is_ok()
+        {
+            self.report(
+                start,
+                self.pos,
+                format!("invalid number {word}"),
+                Vec::new(),
+                Some("add a leading zero"),
+            );
+            return;
+        }
+        self.report(
+            start,
+            self.pos,
+            format!("unexpected token {word}"),
+            expected(&["value"]),
+            suggestion.or(Some("wrap text values in double quotes")),
+        );
+    }
+
+    fn unexpected(&mut self, what: &str, expect: &[&str], suggestion: Option<&str>) {
+        let start = self.pos;
+        let end = self.char_end(start);
+        let found = &self.text[start..end];
+        self.report(
+            start,
+            end,
+            format!("expected {what} but found '{found}'"),
+            expected(expect),
+            suggestion,
+        );
+    }
+
+    fn value(&mut self, depth: usize) {
+        self.skip_trivia();
+        if depth > MAX_DEPTH {
+            self.report(self.pos, self.pos, "nesting too deep", Vec::new(), None);
+            self.pos = self.bytes.len();
+            return;
+        }
+        match self.peek() {
+            Some(b'{') => self.container(depth, b'}'),
+            Some(b'[') => self.container(depth, b']'),
+            Some(q @ (b'"' | b'\'')) => self.string(q),
+            Some(b) if is_word_byte(b) => self.word(),
+            Some(b',' | b']' | b'}') | None => {
+                let at = self.pos;
+                self.report(at, at, "missing value", expected(&["value"]), None);
+            }
+            Some(_) => {
+                self.unexpected("a value", &["value"], None);
+                self.pos = self.char_end(self.pos);
+            }
+        }
+    }
+
+    fn key(&mut self) {
+        match self.peek() {
+            Some(q @ (b'"' | b'\'')) => self.string(q),
+            Some(b) if is_word_byte(b) => {
+                let start = self.pos;
+                while self.peek().is_some_and(is_word_byte) {
+                    self.pos += 1;
+                }
+                self.report(
+                    start,
+                    self.pos,
+                    "object keys must be double-quoted strings",
+                    expected(&["\""]),
+                    Some("wrap the key in double quotes"),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn container(&mut self, depth: usize, close: u8) {
+        let open_at = self.pos;
+        let is_object = close == b'}';
+        self.pos += 1;
+        let mut after_comma: Option<usize> = None;
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                None => {
+                    let (what, hint) = if is_object {
+                        ("missing closing brace", "add '}'")
+                    } else {
+                        ("missing closing bracket", "add ']'")
+                    };
+                    let line = self.line_of(open_at);
+                    let at = self.pos;
+                    self.report(
+                        at,
+                        at,
+                        format!(
+                            "{what} for the {} opened on line {line}",
+                            if is_object { "object" } else { "array" }
+                        ),
+                        expected(&[if is_object { "}" } else { "]" }]),
+                        Some(hint),
+                    );
+                    return;
+                }
+                Some(b) if b == close => {
+                    if let Some(comma) = after_comma {
+                        self.report(
+                            comma,
+                            comma + 1,
+                            "trailing comma",
+                            expected(&[if is_object { "key" } else { "value" }]),
+                            Some("remove the trailing comma"),
+                        );
+                    }
+                    self.pos += 1;
+                    return;
+                }
+                Some(b @ (b'}' | b']')) => {
+                    let want = if is_object { "'}'" } else { "']'" };
+                    let at = self.pos;
+                    self.report(
+                        at,
+                        at + 1,
+                        format!("expected {want} but found '{}'", b as char),
+                        expected(&[if is_object { "}" } else { "]" }]),
+                        Some("mismatched bracket"),
+                    );
+                    self.pos += 1;
+                    return;
+                }
+                Some(b',') => {
+                    let at = self.pos;
+                    self.report(
+                        at,
+                        at + 1,
+                        "unexpected comma",
+                        expected(&[if is_object { "key" } else { "value" }]),
+                        Some("remove the extra comma"),
+                    );
+                    self.pos += 1;
+                    continue;
+                }
+                Some(_) => {}
+            }
+            let before = self.pos;
+            if is_object {
+                self.key();
+                if self.pos == before {
+                    self.unexpected("a key", &["\"", "}"], None);
+                    self.pos = self.char_end(self.pos);
+                    continue;
+                }
+                self.skip_trivia();
+                if self.peek() == Some(b':') {
+                    self.pos += 1;
+                } else {
+                    let at = self.pos;
+                    self.report(
+                        at,
+                        at,
+                        "missing colon after key",
+                        expected(&[":"]),
+                        Some("add ':' between the key and its value"),
+                    );
+                }
+            }
+            self.value(depth + 1);
+            after_comma = None;
+            let value_end = self.pos;
+            self.skip_trivia();
+            match self.peek() {
+                Some(b',') => {
+                    after_comma = Some(self.pos);
+                    self.pos += 1;
+                }
+                Some(b'}' | b']') | None => {}
+                Some(_) => {
+                    let close_str = if is_object { "}" } else { "]" };
+                    self.report(
+                        value_end,
+                        value_end,
+                        "missing comma",
+                        expected(&[",", close_str]),
+                        Some("add ',' between the entries"),
+                    );
+                }
+            }
+            if self.pos == before {
+                self.pos = self.char_end(self.pos).max(self.pos + 1);
+            }
+        }
+    }
+}
+
+pub fn check(text: &str) -> SyntaxReport {
+    let mut checker = Checker {
+        text,
+        bytes: text.as_bytes(),
+        pos: 0,
+        line_starts: std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect(),
+        diagnostics: Vec::new(),
+        truncated: false,
+    };
+    checker.skip_trivia();
+    if checker.peek().is_none() {
+        checker.report(0, 0, "the document is empty", expected(&["value"]), None);
+    } else {
+        checker.value(0);
+        checker.skip_trivia();
+        if checker.pos < text.len() {
+            let start = checker.pos;
+            checker.report(
+                start,
+                text.len(),
+                "unexpected content after the root value",
+                Vec::new(),
+                Some("wrap multiple values in an array"),
+            );
+        }
+    }
+    if checker.diagnostics.is_empty() {
+        if let Ok(index) = PositionIndex::build(text) {
+            for dup in index.duplicate_keys() {
+                let start = dup.duplicate.offset as usize;
+                let end = start + serde_json::to_string(&dup.key).map_or(0, |k| k.len());
+                checker.report(
+                    start,
+                    end,
+                    format!(
+                        "duplicate key {:?} (first defined on line {}, column {})",
+                        dup.key, dup.first.line, dup.first.column
+                    ),
+                    Vec::new(),
+                    Some("rename or remove one of the keys; only the last value is kept"),
+                );
+            }
+        }
+    }
+    SyntaxReport {
+        valid: checker.diagnostics.is_empty(),
+        diagnostics: checker.diagnostics,
+        truncated: checker.truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(text: &str) -> Vec<String> {
+        check(text)
+            .diagnostics
+            .into_iter()
+            .map(|d| d.message)
+            .collect()
+    }
+
+    #[test]
+    fn valid_json_has_no_diagnostics() {
+        let report = check(r#"{"a": [1, -2.5e3, "x\né"], "b": {"c": null, "d": true}}"#);
+        assert!(report.valid, "{:?}", report.diagnostics);
+        assert!(check("  42 ").valid);
+    }
+
+    #[test]
+    fn keeps_going_after_errors() {
+        let text = "{\n  \"a\": 1,\n  \"b\": 2\n  \"c\": [1, 2,],\n  d: True,\n}";
+        let report = check(text);
+        let found: Vec<(&str, u32)> = report
+            .diagnostics
+            .iter()
+            .map(|d| (d.message.as_str(), d.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("missing comma", 3),
+                ("trailing comma", 4),
+                ("object keys must be double-quoted strings", 5),
+                ("unexpected token True", 5),
+                ("trailing comma", 5),
+            ]
+        );
+        assert_eq!(
+            report.diagnostics[3].suggestion.as_deref(),
+            Some("use lowercase true")
+        );
+    }
+
+    #[test]
+    fn reports_unclosed_containers_and_strings() {
+        assert_eq!(
+            messages("{\"a\": [1, 2"),
+            vec![
+                "missing closing bracket for the array opened on line 1",
+                "missing closing brace for the object opened on line 1"
+            ]
+        );
+        let report = check("{\"a\": \"oops\n}");
+        assert_eq!(report.diagnostics[0].message, "unterminated string");
+        assert_eq!(
+            report.diagnostics[0].suggestion.as_deref(),
+            Some("add the closing quote")
+        );
+    }
+
+    #[test]
+    fn flags_json5_isms() {
+        let msgs = messages("// note\n{'a': 01, \"b\": .5}");
+        assert_eq!(
+            msgs,
+            vec![
+                "comments are not allowed in JSON",
+                "strings must use double quotes",
+                "invalid number 01",
+                "invalid number .5",
+            ]
+        );
+        assert_eq!(
+            messages("[1] [2]"),
+            vec!["unexpected content after the root value"]
+        );
+        assert_eq!(messages("[1 }"), vec!["expected ']' but found '}'"]);
+    }
+
+    #[test]
+    fn duplicate_keys_are_reported_for_otherwise_valid_json() {
+        let report = check("{\"a\": 1,\n \"a\": 2}");
+        assert!(!report.valid);
+        assert_eq!(
+            report.diagnostics[0].message,
+            "duplicate key \"a\" (first defined on line 1, column 2)"
+        );
+        assert_eq!(report.diagnostics[0].line, 2);
+    }
+}
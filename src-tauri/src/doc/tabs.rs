@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::timefmt::now_millis;
+use super::types::{DocError, DocHandle, DocResult};
+
+pub const CHANGED_EVENT: &str = "tabs-changed";
+pub const SELECT_EVENT: &str = "select-tab";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tab {
+    pub id: String,
+    pub window: String,
+    pub handle: Option<DocHandle>,
+    pub title: String,
+    pub path: Option<String>,
+    pub dirty: bool,
+    pub opened_at: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabInput {
+    #[serde(default)]
+    pub handle: Option<DocHandle>,
+    pub title: String,
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabList {
+    pub window: String,
+    pub tabs: Vec<Tab>,
+    pub active: Option<String>,
+}
+
+#[derive(Default)]
+struct Registry {
+    tabs: Vec<Tab>,
+    active: HashMap<String, String>,
+}
+
+impl Registry {
+    fn list(&self, window: &str) -> TabList {
+        TabList {
+            window: window.to_string(),
+            tabs: self
+                .tabs
+                .iter()
+                .filter(|t| t.window == window)
+                .cloned()
+                .collect(),
+            active: self.active.get(window).cloned(),
+        }
+    }
+
+    fn find(&mut self, id: &str) -> DocResult<&mut Tab> {
+        self.tabs
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or_else(|| DocError::Edit(format!("unknown tab: {id}")))
+    }
+}
+
+#[derive(Default)]
+pub struct TabRegistry {
+    inner: Mutex<Registry>,
+}
+
+impl TabRegistry {
+    pub fn open(&self, window: &str, input: TabInput) -> Tab {
+        let mut reg = self.inner.lock();
+        let existing = input.path.as_ref().and_then(|path| {
+            reg.tabs
+                .iter()
+                .find(|t| t.window == window && t.path.as_ref() == Some(path))
+                .cloned()
+        });
+        let tab = match existing {
+            Some(tab) => tab,
+            None => {
+                let tab = Tab {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    window: window.to_string(),
+                    handle: input.handle,
+                    title: input.title,
+                    path: input.path,
+                    dirty: false,
+                    opened_at: now_millis(),
+                };
+                reg.tabs.push(tab.clone());
+                tab
+            }
+        };
+        reg.active.insert(window.to_string(), tab.id.clone());
+        tab
+    }
+
+    pub fn close(&self, id: &str) -> DocResult<(Tab, TabList)> {
+        let mut reg = self.inner.lock();
+        let index = reg
+            .tabs
+            .iter()
+            .position(|t| t.id == id)
+            .ok_or_else(|| DocError::Edit(format!("unknown tab: {id}")))?;
+        let tab = reg.tabs.remove(index);
+        if reg.active.get(&tab.window) == Some(&tab.id) {
+            let siblings: Vec<&Tab> = reg.tabs.iter().filter(|t| t.window == tab.window).collect();
+            let before = reg.tabs[..index]
+                .iter()
+                .filter(|t| t.window == tab.window)
+                .count();
+            let next = siblings
+                .get(before)
+                .or_else(|| siblings.last())
+                .map(|t| t.id.clone());
+            match next {
+                Some(next) => reg.active.insert(tab.window.clone(), next),
+                None => reg.active.remove(&tab.window),
+            };
+        }
+        let list = reg.list(&tab.window);
+        Ok((tab, list))
+    }
+
+    pub fn list(&self, window: &str) -> TabList {
+        self.inner.lock().list(window)
+    }
+
+    pub fn windows(&self) -> Vec<TabList> {
+        let reg = self.inner.lock();
+        let mut labels: Vec<&str> = Vec::new();
+        for tab in &reg.tabs {
+            if !labels.contains(&tab.window.as_str()) {
+                labels.push(&tab.window);
+            }
+        }
+        labels.into_iter().map(|w| reg.list(w)).collect()
+    }
+
+    pub fn cycle(&self, window: &str, step: isize) -> Option<Tab> {
+        let mut reg = self.inner.lock();
+        let list = reg.list(window);
+        if list.tabs.is_empty() {
+            return None;
+        }
+        let current = list
+            .active
+            .and_then(|id| list.tabs.iter().position(|t| t.id == id))
+            .unwrap_or(0);
+        let next = (current as isize + step).rem_euclid(list.tabs.len() as isize) as usize;
+        let tab = list.tabs[next].clone();
+        reg.active.insert(window.to_string(), tab.id.clone());
+        Some(tab)
+    }
+
+    pub fn reorder(&self, window: &str, order: &[String]) -> DocResult<TabList> {
+        let mut reg = self.inner.lock();
+        let current: Vec<&str> = reg
+            .tabs
+            .iter()
+            .filter(|t| t.window == window)
+            .map(|t| t.id.as_str())
+            .collect();
+        let mut sorted_order: Vec<&str> = order.iter().map(String::as_str).collect();
+        let mut sorted_current = current.clone();
+        sorted_order.sort_unstable();
+        sorted_current.sort_unstable();
+        if sorted_order != sorted_current {
+            return Err(DocError::Edit(
+                "tab order must list every tab in the window exactly once".into(),
+            ));
+        }
+        let (mut moved, rest): (Vec<Tab>, Vec<Tab>) = std::mem::take(&mut reg.tabs)
+            .into_iter()
+            .partition(|t| t.window == window);
+        moved.sort_by_key(|t| order.iter().position(|id| *id == t.id));
+        reg.tabs = rest;
+        reg.tabs.extend(moved);
+        Ok(reg.list(window))
+    }
+
+    pub fn set_dirty(&self, id: &str, dirty: bool) -> DocResult<Tab> {
+        let mut reg = self.inner.lock();
+        let tab = reg.find(id)?;
+        tab.dirty = dirty;
+        Ok(tab.clone())
+    }
+
+    pub fn update(&self, id: &str, input: TabInput) -> DocResult<Tab> {
+        let mut reg = self.inner.lock();
+        let tab = reg.find(id)?;
+        tab.handle = input.handle.or(tab.handle);
+        tab.title = input.title;
+        tab.path = input.path;
+        Ok(tab.clone())
+    }
+
+    pub fn activate(&self, id: &str) -> DocResult<Tab> {
+        let mut reg = self.inner.lock();
+        let tab = reg.find(id)?.clone();
+        reg.active.insert(tab.window.clone(), tab.id.clone());
+        Ok(tab)
+    }
+
+    pub fn refresh_dirty(&self, dirty: impl Fn(DocHandle) -> Option<bool>) {
+        let mut reg = self.inner.lock();
+        for tab in reg.tabs.iter_mut() {
+            if let Some(flag) = tab.handle.and_then(&dirty) {
+                tab.dirty = flag;
+            }
+        }
+    }
+
+    pub fn close_window(&self, window: &str) -> Vec<Tab> {
+        let mut reg = self.inner.lock();
+        reg.active.remove(window);
+        let (closed, kept) = std::mem::take(&mut reg.tabs)
+            .into_iter()
+            .partition(|t| t.window == window);
+        reg.tabs = kept;
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(title: &str, path: Option<&str>) -> TabInput {
+        TabInput {
+            handle: None,
+            title: title.into(),
+            path: path.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn opens_reuses_and_closes_tabs() {
+        let tabs = TabRegistry::default();
+        let a = tabs.open("main", input("a.json", Some("/a.json")));
+        let b = tabs.open("main", input("b.json", Some("/b.json")));
+        let c = tabs.open("main", input("Untitled", None));
+        assert_eq!(tabs.open("main", input("a", Some("/a.json"))).id, a.id);
+        assert_eq!(tabs.list("main").active.as_deref(), Some(a.id.as_str()));
+
+        let (_, list) = tabs.close(&a.id).unwrap();
+        assert_eq!(list.active.as_deref(), Some(b.id.as_str()));
+        tabs.activate(&c.id).unwrap();
+        let (_, list) = tabs.close(&c.id).unwrap();
+        assert_eq!(list.active.as_deref(), Some(b.id.as_str()));
+        let (_, list) = tabs.close(&b.id).unwrap();
+        assert!(list.tabs.is_empty() && list.active.is_none());
+        assert!(tabs.close(&b.id).is_err());
+    }
+
+    #[test]
+    fn reorders_within_a_window_only() {
+        let tabs = TabRegistry::default();
+        let a = tabs.open("main", input("a", None));
+        let other = tabs.open("second", input("x", None));
+        let b = tabs.open("main", input("b", None));
+        let list = tabs.reorder("main", &[b.id.clone(), a.id.clone()]).unwrap();
+        let ids: Vec<&str> = list.tabs.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec![b.id.as_str(), a.id.as_str()]);
+        assert!(tabs.reorder("main", &[a.id.clone()]).is_err());
+        assert_eq!(tabs.cycle("main", 1).unwrap().id, a.id);
+        assert_eq!(tabs.cycle("main", 1).unwrap().id, b.id);
+        assert_eq!(tabs.cycle("main", -1).unwrap().id, a.id);
+        assert!(tabs.cycle("none", 1).is_none());
+        let windows: Vec<String> = tabs.windows().into_iter().map(|l| l.window).collect();
+        assert_eq!(windows, vec!["second".to_string(), "main".to_string()]);
+        assert_eq!(tabs.list("second").tabs[0].id, other.id);
+    }
+
+    #[test]
+    fn tracks_dirty_flags_from_documents() {
+        let tabs = TabRegistry::default();
+        let handle = DocHandle::new();
+        let tab = tabs.open(
+            "main",
+            TabInput {
+                handle: Some(handle),
+                ..input("a", None)
+            },
+        );
+        assert!(tabs.set_dirty(&tab.id, true).unwrap().dirty);
+        tabs.refresh_dirty(|h| (h == handle).then_some(false));
+        assert!(!tabs.list("main").tabs[0].dirty);
+        assert_eq!(tabs.close_window("main").len(), 1);
+    }
+}
@@ -0,0 +1,155 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::detect::infer_cell;
+use super::types::{DocError, DocResult};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabularPaste {
+    pub columns: Vec<String>,
+    pub rows: Vec<Value>,
+    pub json: String,
+}
+
+fn column_names(header: Option<Vec<String>>, width: usize) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(width);
+    for i in 0..width {
+        let base = header
+            .as_ref()
+            .and_then(|h| h.get(i))
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| format!("column{}", i + 1));
+        let mut name = base.clone();
+        let mut n = 2;
+        while names.contains(&name) {
+            name = format!("{base}_{n}");
+            n += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+fn keeps_text(cell: &str) -> bool {
+    let t = cell.trim();
+    let digits = t.trim_start_matches('-');
+    (digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0."))
+        || t.starts_with('+')
+        || (t.len() > 15 && t.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn column_values(cells: &[&str]) -> Vec<Value> {
+    let typed: Vec<Value> = cells.iter().map(|c| infer_cell(c)).collect();
+    let uniform = typed.iter().zip(cells).all(|(v, raw)| match v {
+        Value::Null => true,
+        Value::Number(_) => !keeps_text(raw),
+        Value::Bool(_) => true,
+        _ => false,
+    });
+    let kinds_agree = typed
+        .iter()
+        .filter(|v| !v.is_null())
+        .map(|v| v.is_number())
+        .collect::<Vec<_>>()
+        .windows(2)
+        .all(|w| w[0] == w[1]);
+    if uniform && kinds_agree {
+        return typed;
+    }
+    cells
+        .iter()
+        .map(|c| {
+            if c.trim().is_empty() {
+                Value::Null
+            } else {
+                Value::String(c.to_string())
+            }
+        })
+        .collect()
+}
+
+pub fn parse(text: &str, header: bool) -> DocResult<TabularPaste> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut records: Vec<Vec<String>> = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| DocError::Parse(format!("clipboard table: {e}")))?;
+        let cells: Vec<String> = record.iter().map(str::to_string).collect();
+        if cells.iter().any(|c| !c.trim().is_empty()) {
+            records.push(cells);
+        }
+    }
+    if !text.contains('\t') && records.len() < 2 {
+        return Err(DocError::Parse(
+            "clipboard does not contain tab-separated cells".into(),
+        ));
+    }
+    let head = if header && !records.is_empty() {
+        Some(records.remove(0))
+    } else {
+        None
+    };
+    let width = records
+        .iter()
+        .map(Vec::len)
+        .chain(head.as_ref().map(Vec::len))
+        .max()
+        .unwrap_or(0);
+    let columns = column_names(head, width);
+    let by_column: Vec<Vec<Value>> = (0..width)
+        .map(|c| {
+            let cells: Vec<&str> = records
+                .iter()
+                .map(|r| r.get(c).map_or("", String::as_str))
+                .collect();
+            column_values(&cells)
+        })
+        .collect();
+    let rows: Vec<Value> = (0..records.len())
+        .map(|r| {
+            let mut obj = Map::new();
+            for (c, name) in columns.iter().enumerate() {
+                obj.insert(name.clone(), by_column[c][r].clone());
+            }
+            Value::Object(obj)
+        })
+        .collect();
+    let json = serde_json::to_string_pretty(&rows).map_err(|e| DocError::Export(e.to_string()))?;
+    Ok(TabularPaste {
+        columns,
+        rows,
+        json,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn turns_spreadsheet_rows_into_typed_objects() {
+        let text = "Name\tAge\tZip\tActive\tNote\r\nAda\t36\t02139\tTRUE\t\"multi\nline\"\r\nAlan\t41.5\t10001\tFALSE\t\r\n\r\n";
+        let table = parse(text, true).unwrap();
+        assert_eq!(table.columns, vec!["Name", "Age", "Zip", "Active", "Note"]);
+        assert_eq!(
+            Value::Array(table.rows),
+            json!([
+                {"Name": "Ada", "Age": 36, "Zip": "02139", "Active": true, "Note": "multi\nline"},
+                {"Name": "Alan", "Age": 41.5, "Zip": "10001", "Active": false, "Note": null}
+            ])
+        );
+    }
+
+    #[test]
+    fn names_missing_and_duplicate_headers() {
+        let table = parse("id\t\tid\n1\t2\t3\t4\n", true).unwrap();
+        assert_eq!(table.columns, vec!["id", "column2", "id_2", "column4"]);
+        assert!(parse("just some text", true).is_err());
+    }
+}
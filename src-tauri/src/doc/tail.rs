@@ -0,0 +1,378 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::jobs::CancelFlag;
+use super::types::DocResult;
+
+pub const RECORD_EVENT: &str = "tail-record";
+
+const DEFAULT_POLL_MS: u64 = 250;
+const MAX_IDLE_WAIT_MS: u64 = 2_000;
+const DEFAULT_MAX_PER_SECOND: u32 = 200;
+const DEFAULT_MAX_PENDING: usize = 10_000;
+const READ_CHUNK: usize = 64 * 1024;
+const MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TailOptions {
+    pub from_start: bool,
+    pub poll_ms: u64,
+    pub max_per_second: u32,
+    pub max_pending: usize,
+}
+
+impl Default for TailOptions {
+    fn default() -> Self {
+        Self {
+            from_start: false,
+            poll_ms: DEFAULT_POLL_MS,
+            max_per_second: DEFAULT_MAX_PER_SECOND,
+            max_pending: DEFAULT_MAX_PENDING,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TailRecord {
+    pub line: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TailBatch {
+    pub tail_id: String,
+    pub records: Vec<TailRecord>,
+    pub dropped: u64,
+    pub truncated: bool,
+}
+
+pub struct Tailer {
+    path: PathBuf,
+    offset: u64,
+    line: u64,
+    partial: Vec<u8>,
+    skipping: bool,
+    max_line: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Polled {
+    pub records: Vec<TailRecord>,
+    pub dropped: u64,
+    pub truncated: bool,
+}
+
+fn record(line: u64, raw: &[u8]) -> Option<TailRecord> {
+    let text = raw.trim_ascii();
+    if text.is_empty() {
+        return None;
+    }
+    Some(match serde_json::from_slice::<Value>(text) {
+        Ok(value) => TailRecord {
+            line,
+            value: Some(value),
+            error: None,
+        },
+        Err(e) => TailRecord {
+            line,
+            value: None,
+            error: Some(e.to_string()),
+        },
+    })
+}
+
+impl Tailer {
+    pub fn new(path: &FsPath, from_start: bool) -> DocResult<Self> {
+        let offset = if from_start {
+            0
+        } else {
+            std::fs::metadata(path)?.len()
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            offset,
+            line: 0,
+            partial: Vec::new(),
+            skipping: false,
+            max_line: MAX_LINE_BYTES,
+        })
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    fn drain_lines(&mut self, limit: usize, polled: &mut Polled) {
+        let mut consumed = 0;
+        while polled.records.len() < limit {
+            let Some(end) = self.partial[consumed..].iter().position(|&b| b == b'\n') else {
+                break;
+            };
+            self.line += 1;
+            if std::mem::take(&mut self.skipping) {
+                polled.records.push(TailRecord {
+                    line: self.line,
+                    value: None,
+                    error: Some(format!("line longer than {} bytes skipped", self.max_line)),
+                });
+            } else if let Some(r) = record(self.line, &self.partial[consumed..consumed + end]) {
+                polled.records.push(r);
+            }
+            consumed += end + 1;
+        }
+        self.partial.drain(..consumed);
+        if polled.records.len() < limit && (self.skipping || self.partial.len() > self.max_line) {
+            if !self.skipping {
+                polled.dropped += 1;
+            }
+            self.skipping = true;
+            self.partial.clear();
+        }
+    }
+
+    pub fn poll(&mut self, limit: usize) -> DocResult<Polled> {
+        let mut polled = Polled::default();
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(polled),
+            Err(e) => return Err(e.into()),
+        };
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            self.offset = 0;
+            self.line = 0;
+            self.partial.clear();
+            self.skipping = false;
+            polled.truncated = true;
+        }
+        self.drain_lines(limit, &mut polled);
+        if polled.records.len() >= limit || len == self.offset {
+            return Ok(polled);
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = vec![0u8; READ_CHUNK];
+        while polled.records.len() < limit && self.offset < len {
+            let want = READ_CHUNK.min((len - self.offset) as usize);
+            let n = file.read(&mut buf[..want])?;
+            if n == 0 {
+                break;
+            }
+            self.offset += n as u64;
+            self.partial.extend_from_slice(&buf[..n]);
+            self.drain_lines(limit, &mut polled);
+        }
+        Ok(polled)
+    }
+}
+
+pub struct RateLimiter {
+    per_second: u32,
+    window: Instant,
+    sent: u32,
+}
+
+impl RateLimiter {
+    pub fn new(per_second: u32) -> Self {
+        Self {
+            per_second: per_second.max(1),
+            window: Instant::now(),
+            sent: 0,
+        }
+    }
+
+    pub fn allow(&mut self, wanted: usize, now: Instant) -> usize {
+        if now.duration_since(self.window) >= Duration::from_secs(1) {
+            self.window = now;
+            self.sent = 0;
+        }
+        let allowed = wanted.min((self.per_second - self.sent) as usize);
+        self.sent += allowed as u32;
+        allowed
+    }
+}
+
+/// Wakes the follow loop whenever the log changes. The parent directory is
+/// watched rather than the file so truncation, rotation and a log that is
+/// created late all still come through.
+fn watch(path: &FsPath) -> notify::Result<(RecommendedWatcher, Receiver<()>)> {
+    let (tx, rx) = mpsc::channel();
+    let name = path.file_name().map(ToOwned::to_owned);
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok_and(|e| e.paths.iter().any(|p| p.file_name() == name.as_deref())) {
+            let _ = tx.send(());
+        }
+    })?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => FsPath::new("."),
+    };
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok((watcher, rx))
+}
+
+/// Reads from `tailer` until cancelled. Reads follow the watcher's change
+/// events, at most once per `poll_ms`, so an untouched log only wakes the loop
+/// every couple of seconds to check for cancellation. Without a watcher this
+/// falls back to reading on the poll interval.
+pub fn follow(
+    tail_id: &str,
+    mut tailer: Tailer,
+    opts: &TailOptions,
+    cancel: &CancelFlag,
+    mut emit: impl FnMut(TailBatch),
+) -> DocResult<()> {
+    let mut limiter = RateLimiter::new(opts.max_per_second);
+    let mut pending: VecDeque<TailRecord> = VecDeque::new();
+    let mut dropped = 0u64;
+    let base = Duration::from_millis(opts.poll_ms.max(10));
+    let idle = base.max(Duration::from_millis(MAX_IDLE_WAIT_MS));
+    let watcher = watch(&tailer.path)
+        .map_err(|e| {
+            tracing::warn!(error = %e, tail_id, "file watcher unavailable; polling instead");
+        })
+        .ok();
+    while !cancel.is_cancelled() {
+        let started = Instant::now();
+        let room = opts.max_pending.max(1).saturating_sub(pending.len());
+        let polled = tailer.poll(room)?;
+        pending.extend(polled.records);
+        dropped += polled.dropped;
+        let allowed = limiter.allow(pending.len(), Instant::now());
+        if allowed > 0 || polled.truncated || dropped > 0 {
+            emit(TailBatch {
+                tail_id: tail_id.to_string(),
+                records: pending.drain(..allowed).collect(),
+                dropped,
+                truncated: polled.truncated,
+            });
+            dropped = 0;
+        }
+        // Held-back records are retried on the poll interval; otherwise wait
+        // for the next change.
+        if let (Some((_, changes)), true) = (&watcher, pending.is_empty()) {
+            let _ = changes.recv_timeout(idle);
+            while changes.try_recv().is_ok() {}
+        }
+        if let Some(rest) = base.checked_sub(started.elapsed()) {
+            std::thread::sleep(rest);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+    use std::io::Write;
+
+    fn append(path: &FsPath, text: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn reads_only_new_complete_lines() {
+        let dir = TempDir::new("tail");
+        let path = dir.join("log.ndjson");
+        std::fs::write(&path, "{\"old\":true}\n").unwrap();
+        let mut tailer = Tailer::new(&path, false).unwrap();
+        assert!(tailer.poll(usize::MAX).unwrap().records.is_empty());
+
+        append(&path, "{\"a\":1}\n{\"b\":");
+        let polled = tailer.poll(usize::MAX).unwrap();
+        assert_eq!(polled.records.len(), 1);
+        assert_eq!(polled.records[0].value, Some(json!({"a": 1})));
+
+        append(&path, "2}\nnot json\n");
+        let values: Vec<TailRecord> = tailer.poll(usize::MAX).unwrap().records;
+        assert_eq!(values[0].value, Some(json!({"b": 2})));
+        assert!(values[1].error.is_some());
+        assert_eq!(values[1].line, values[0].line + 1);
+
+        std::fs::write(&path, "{\"c\":3}\n").unwrap();
+        let polled = tailer.poll(usize::MAX).unwrap();
+        assert!(polled.truncated);
+        assert_eq!(polled.records[0].value, Some(json!({"c": 3})));
+        assert_eq!(polled.records[0].line, 1);
+    }
+
+    #[test]
+    fn stops_at_the_limit_and_skips_overlong_lines() {
+        let dir = TempDir::new("tail-limit");
+        let path = dir.join("log.ndjson");
+        std::fs::write(&path, "1\n2\n3\n4\n5\n").unwrap();
+        let mut tailer = Tailer::new(&path, true).unwrap();
+        tailer.max_line = 8;
+        let first = tailer.poll(2).unwrap().records;
+        assert_eq!(first.iter().map(|r| r.line).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(tailer.poll(10).unwrap().records.len(), 3);
+
+        append(&path, "[\"this line is too long\"");
+        let polled = tailer.poll(10).unwrap();
+        assert_eq!((polled.records.len(), polled.dropped), (0, 1));
+        assert!(tailer.partial.is_empty());
+        append(&path, ", 1]\n6\n");
+        let records = tailer.poll(10).unwrap().records;
+        assert!(records[0].error.is_some());
+        assert_eq!(records[1].value, Some(json!(6)));
+    }
+
+    #[test]
+    fn follow_emits_appended_records() {
+        let dir = TempDir::new("tail-follow");
+        let path = dir.join("log.ndjson");
+        std::fs::write(&path, "").unwrap();
+        let tailer = Tailer::new(&path, false).unwrap();
+        let cancel = CancelFlag::never();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let follower = {
+            let cancel = cancel.clone();
+            std::thread::spawn(move || {
+                let opts = TailOptions {
+                    poll_ms: 10,
+                    ..TailOptions::default()
+                };
+                follow("t", tailer, &opts, &cancel, |batch| {
+                    let _ = tx.send(batch);
+                })
+            })
+        };
+
+        append(&path, "{\"a\":1}\n");
+        let batch = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(batch.tail_id, "t");
+        assert_eq!(batch.records[0].value, Some(json!({"a": 1})));
+        cancel.cancel();
+        follower.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn rate_limiter_caps_each_second() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(5);
+        assert_eq!(limiter.allow(3, start), 3);
+        assert_eq!(limiter.allow(3, start), 2);
+        assert_eq!(limiter.allow(3, start), 0);
+        assert_eq!(limiter.allow(3, start + Duration::from_secs(1)), 3);
+    }
+}
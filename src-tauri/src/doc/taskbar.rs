@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager};
+
+use super::types::DocHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indicator {
+    Idle,
+    Indeterminate,
+    Percent(u8),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Task {
+    done: u64,
+    total: Option<u64>,
+}
+
+struct State {
+    next_id: u64,
+    tasks: HashMap<u64, Task>,
+    invalid: HashSet<DocHandle>,
+    shown: (Indicator, Option<i64>),
+}
+
+pub struct Taskbar {
+    state: Mutex<State>,
+}
+
+impl Default for Taskbar {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(State {
+                next_id: 0,
+                tasks: HashMap::new(),
+                invalid: HashSet::new(),
+                shown: (Indicator::Idle, None),
+            }),
+        }
+    }
+}
+
+impl Taskbar {
+    fn begin(&self, total: Option<u64>) -> u64 {
+        let mut state = self.state.lock();
+        state.next_id += 1;
+        let id = state.next_id;
+        state.tasks.insert(id, Task { done: 0, total });
+        id
+    }
+
+    fn advance(&self, id: u64, done: u64) {
+        if let Some(task) = self.state.lock().tasks.get_mut(&id) {
+            task.done = task.total.map_or(done, |t| done.min(t));
+        }
+    }
+
+    fn finish(&self, id: u64) {
+        self.state.lock().tasks.remove(&id);
+    }
+
+    pub fn set_invalid(&self, handle: DocHandle, invalid: bool) {
+        let mut state = self.state.lock();
+        if invalid {
+            state.invalid.insert(handle);
+        } else {
+            state.invalid.remove(&handle);
+        }
+    }
+
+    pub fn indicator(&self) -> Indicator {
+        indicator_of(self.state.lock().tasks.values())
+    }
+
+    pub fn badge(&self) -> Option<i64> {
+        let count = self.state.lock().invalid.len();
+        (count > 0).then_some(count as i64)
+    }
+
+    fn changes(&self) -> Option<(Indicator, Option<i64>)> {
+        let current = (self.indicator(), self.badge());
+        let mut state = self.state.lock();
+        if state.shown == current {
+            return None;
+        }
+        state.shown = current;
+        Some(current)
+    }
+}
+
+fn indicator_of<'a>(tasks: impl Iterator<Item = &'a Task>) -> Indicator {
+    let (mut any, mut done, mut total) = (false, 0u64, 0u64);
+    for task in tasks {
+        any = true;
+        if let Some(t) = task.total.filter(|t| *t > 0) {
+            done += task.done;
+            total += t;
+        }
+    }
+    match (any, total) {
+        (false, _) => Indicator::Idle,
+        (true, 0) => Indicator::Indeterminate,
+        (true, total) => Indicator::Percent((done.saturating_mul(100) / total).min(100) as u8),
+    }
+}
+
+pub fn refresh(app: &AppHandle) {
+    let Some(taskbar) = app.try_state::<Arc<Taskbar>>() else {
+        return;
+    };
+    let Some((indicator, badge)) = taskbar.changes() else {
+        return;
+    };
+    let progress = match indicator {
+        Indicator::Idle => ProgressBarState {
+            status: Some(ProgressBarStatus::None),
+            progress: None,
+        },
+        Indicator::Indeterminate => ProgressBarState {
+            status: Some(ProgressBarStatus::Indeterminate),
+            progress: None,
+        },
+        Indicator::Percent(p) => ProgressBarState {
+            status: Some(ProgressBarStatus::Normal),
+            progress: Some(p as u64),
+        },
+    };
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_progress_bar(progress);
+        let _ = window.set_badge_count(badge);
+    }
+}
+
+pub struct TaskProgress {
+    app: AppHandle,
+    id: Option<u64>,
+}
+
+impl TaskProgress {
+    pub fn start(app: &AppHandle, total: Option<u64>) -> Self {
+        let id = app
+            .try_state::<Arc<Taskbar>>()
+            .map(|taskbar| taskbar.begin(total));
+        refresh(app);
+        Self {
+            app: app.clone(),
+            id,
+        }
+    }
+
+    pub fn advance(&self, done: u64) {
+        if let (Some(id), Some(taskbar)) = (self.id, self.app.try_state::<Arc<Taskbar>>()) {
+            taskbar.advance(id, done);
+            refresh(&self.app);
+        }
+    }
+}
+
+impl Drop for TaskProgress {
+    fn drop(&mut self) {
+        if let (Some(id), Some(taskbar)) = (self.id, self.app.try_state::<Arc<Taskbar>>()) {
+            taskbar.finish(id);
+            refresh(&self.app);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_progress_across_tasks() {
+        let bar = Taskbar::default();
+        assert_eq!(bar.indicator(), Indicator::Idle);
+        let open = bar.begin(None);
+        assert_eq!(bar.indicator(), Indicator::Indeterminate);
+        let a = bar.begin(Some(10));
+        let b = bar.begin(Some(30));
+        bar.advance(a, 10);
+        bar.advance(b, 50);
+        assert_eq!(bar.indicator(), Indicator::Percent(100));
+        bar.advance(b, 0);
+        assert_eq!(bar.indicator(), Indicator::Percent(25));
+        bar.finish(a);
+        bar.finish(b);
+        bar.finish(open);
+        assert_eq!(bar.indicator(), Indicator::Idle);
+    }
+
+    #[test]
+    fn badge_counts_invalid_documents_and_reports_changes_once() {
+        let bar = Taskbar::default();
+        let (x, y) = (DocHandle::new(), DocHandle::new());
+        assert_eq!(bar.changes(), None);
+        bar.set_invalid(x, true);
+        bar.set_invalid(y, true);
+        bar.set_invalid(x, true);
+        assert_eq!(bar.changes(), Some((Indicator::Idle, Some(2))));
+        assert_eq!(bar.changes(), None);
+        bar.set_invalid(x, false);
+        bar.set_invalid(y, false);
+        assert_eq!(bar.badge(), None);
+    }
+}
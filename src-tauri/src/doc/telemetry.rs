@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use super::simd::{self, SimdLevel};
+use super::timefmt;
+
+const CAPACITY: usize = 2000;
+const SLOWEST: usize = 20;
+
+static SHARED: Lazy<Arc<Telemetry>> = Lazy::new(|| Arc::new(Telemetry::new(CAPACITY)));
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sample {
+    pub command: &'static str,
+    pub started_at: u64,
+    pub duration_ms: f64,
+    pub rss_before: Option<u64>,
+    pub rss_after: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStats {
+    pub count: u32,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub max_rss_growth: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceReport {
+    pub sample_count: u32,
+    pub capacity: u32,
+    pub rss_bytes: Option<u64>,
+    pub commands: BTreeMap<String, CommandStats>,
+    pub slowest: Vec<Sample>,
+    pub simd: SimdLevel,
+}
+
+pub struct Telemetry {
+    capacity: usize,
+    samples: Mutex<VecDeque<Sample>>,
+}
+
+impl Telemetry {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            samples: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn record(&self, sample: Sample) {
+        let mut samples = self.samples.lock();
+        if samples.len() == self.capacity {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    pub fn clear(&self) {
+        self.samples.lock().clear();
+    }
+
+    pub fn report(&self) -> PerformanceReport {
+        let samples: Vec<Sample> = self.samples.lock().iter().cloned().collect();
+        let mut by_command: BTreeMap<&'static str, Vec<&Sample>> = BTreeMap::new();
+        for s in &samples {
+            by_command.entry(s.command).or_default().push(s);
+        }
+        let commands = by_command
+            .into_iter()
+            .map(|(name, list)| (name.to_string(), stats(&list)))
+            .collect();
+        let mut slowest = samples.clone();
+        slowest.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms));
+        slowest.truncate(SLOWEST);
+        PerformanceReport {
+            sample_count: samples.len() as u32,
+            capacity: self.capacity as u32,
+            rss_bytes: rss_bytes(),
+            commands,
+            slowest,
+            simd: simd::level(),
+        }
+    }
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+fn stats(samples: &[&Sample]) -> CommandStats {
+    let mut durations: Vec<f64> = samples.iter().map(|s| s.duration_ms).collect();
+    durations.sort_by(f64::total_cmp);
+    let total: f64 = durations.iter().sum();
+    let max_rss_growth = samples
+        .iter()
+        .filter_map(|s| Some(s.rss_after? as i64 - s.rss_before? as i64))
+        .max();
+    CommandStats {
+        count: durations.len() as u32,
+        total_ms: total,
+        mean_ms: total / durations.len().max(1) as f64,
+        p50_ms: percentile(&durations, 50.0),
+        p95_ms: percentile(&durations, 95.0),
+        max_ms: durations.last().copied().unwrap_or(0.0),
+        max_rss_growth,
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn rss_bytes() -> Option<u64> {
+    None
+}
+
+pub fn shared() -> Arc<Telemetry> {
+    SHARED.clone()
+}
+
+pub struct Timing {
+    command: &'static str,
+    started_at: u64,
+    start: Instant,
+    rss_before: Option<u64>,
+}
+
+impl Drop for Timing {
+    fn drop(&mut self) {
+        let duration_ms = self.start.elapsed().as_secs_f64() * 1000.0;
+        tracing::info!(command = self.command, duration_ms, "command");
+        SHARED.record(Sample {
+            command: self.command,
+            started_at: self.started_at,
+            duration_ms,
+            rss_before: self.rss_before,
+            rss_after: rss_bytes(),
+        });
+    }
+}
+
+pub fn time(command: &'static str) -> Timing {
+    Timing {
+        command,
+        started_at: timefmt::now_millis(),
+        start: Instant::now(),
+        rss_before: rss_bytes(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(command: &'static str, duration_ms: f64) -> Sample {
+        Sample {
+            command,
+            started_at: 0,
+            duration_ms,
+            rss_before: Some(100),
+            rss_after: Some(150),
+        }
+    }
+
+    #[test]
+    fn ring_buffer_drops_oldest() {
+        let t = Telemetry::new(3);
+        for i in 0..5 {
+            t.record(sample("doc_open", i as f64));
+        }
+        let report = t.report();
+        assert_eq!(report.sample_count, 3);
+        assert_eq!(report.commands["doc_open"].max_ms, 4.0);
+        assert_eq!(report.slowest[2].duration_ms, 2.0);
+    }
+
+    #[test]
+    fn aggregates_per_command() {
+        let t = Telemetry::new(100);
+        for ms in [1.0, 2.0, 3.0, 4.0, 100.0] {
+            t.record(sample("doc_search", ms));
+        }
+        t.record(sample("doc_close", 0.5));
+        let report = t.report();
+        let search = &report.commands["doc_search"];
+        assert_eq!(search.count, 5);
+        assert_eq!(search.p50_ms, 3.0);
+        assert_eq!(search.p95_ms, 100.0);
+        assert_eq!(search.mean_ms, 22.0);
+        assert_eq!(search.max_rss_growth, Some(50));
+        assert_eq!(report.slowest[0].command, "doc_search");
+        assert_eq!(report.commands["doc_close"].count, 1);
+    }
+
+    #[test]
+    fn timing_guard_records_into_shared_buffer() {
+        drop(time("telemetry_test_command"));
+        let report = shared().report();
+        assert!(report.commands.contains_key("telemetry_test_command"));
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::placeholders::{expand, variables_in, Variable};
+use super::types::{DocError, DocResult};
+
+const BUILTIN_PREFIX: &str = "builtin:";
+const USER_PREFIX: &str = "user:";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInfo {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub builtin: bool,
+    pub file_name: String,
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub info: TemplateInfo,
+    pub body: String,
+}
+
+struct Builtin {
+    slug: &'static str,
+    name: &'static str,
+    description: &'static str,
+    file_name: &'static str,
+    body: &'static str,
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        slug: "package-json",
+        name: "package.json",
+        description: "npm package manifest",
+        file_name: "package.json",
+        body: r#"{
+  "name": "{{name|my-package}}",
+  "version": "{{version|0.1.0}}",
+  "description": "{{description|}}",
+  "main": "index.js",
+  "scripts": {
+    "test": "echo \"Error: no test specified\" && exit 1"
+  },
+  "keywords": [],
+  "author": "{{author|}}",
+  "license": "{{license|MIT}}"
+}
+"#,
+    },
+    Builtin {
+        slug: "geojson-feature-collection",
+        name: "GeoJSON FeatureCollection",
+        description: "Empty FeatureCollection with one sample point",
+        file_name: "features.geojson",
+        body: r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "geometry": {
+        "type": "Point",
+        "coordinates": [{{longitude|0}}, {{latitude|0}}]
+      },
+      "properties": {
+        "name": "{{name|Sample point}}"
+      }
+    }
+  ]
+}
+"#,
+    },
+    Builtin {
+        slug: "openapi",
+        name: "OpenAPI 3.1 skeleton",
+        description: "Minimal OpenAPI document with one path",
+        file_name: "openapi.json",
+        body: r#"{
+  "openapi": "3.1.0",
+  "info": {
+    "title": "{{title|My API}}",
+    "version": "{{version|1.0.0}}"
+  },
+  "servers": [
+    { "url": "{{server|https://api.example.com}}" }
+  ],
+  "paths": {
+    "/health": {
+      "get": {
+        "summary": "Health check",
+        "responses": {
+          "200": { "description": "OK" }
+        }
+      }
+    }
+  },
+  "components": {
+    "schemas": {}
+  }
+}
+"#,
+    },
+    Builtin {
+        slug: "json-schema",
+        name: "JSON Schema skeleton",
+        description: "Draft 2020-12 object schema",
+        file_name: "schema.json",
+        body: r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "$id": "{{id|https://example.com/schema.json}}",
+  "title": "{{title|Example}}",
+  "type": "object",
+  "properties": {
+    "id": { "type": "string" }
+  },
+  "required": ["id"],
+  "additionalProperties": false
+}
+"#,
+    },
+];
+
+pub fn user_dir(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let base = super::portable::data_dir(app)?;
+    let dir = base.join("templates");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+pub fn builtin_templates() -> Vec<Template> {
+    BUILTINS
+        .iter()
+        .map(|b| Template {
+            info: TemplateInfo {
+                id: format!("{BUILTIN_PREFIX}{}", b.slug),
+                name: b.name.into(),
+                description: Some(b.description.into()),
+                builtin: true,
+                file_name: b.file_name.into(),
+                variables: variables_in(b.body),
+            },
+            body: b.body.into(),
+        })
+        .collect()
+}
+
+pub fn user_templates(dir: &FsPath) -> Vec<Template> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut out: Vec<Template> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+            if !matches!(ext.as_str(), "json" | "jsonc" | "json5" | "geojson") {
+                return None;
+            }
+            let stem = path.file_stem()?.to_string_lossy().into_owned();
+            let body = std::fs::read_to_string(&path).ok()?;
+            Some(Template {
+                info: TemplateInfo {
+                    id: format!("{USER_PREFIX}{stem}"),
+                    name: stem,
+                    description: None,
+                    builtin: false,
+                    file_name: path.file_name()?.to_string_lossy().into_owned(),
+                    variables: variables_in(&body),
+                },
+                body,
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| a.info.name.to_lowercase().cmp(&b.info.name.to_lowercase()));
+    out
+}
+
+pub fn all_templates(dir: Option<&FsPath>) -> Vec<Template> {
+    let mut out = builtin_templates();
+    if let Some(dir) = dir {
+        out.extend(user_templates(dir));
+    }
+    out
+}
+
+pub fn list(dir: &FsPath) -> Vec<TemplateInfo> {
+    all_templates(Some(dir))
+        .into_iter()
+        .map(|t| t.info)
+        .collect()
+}
+
+pub fn find(dir: Option<&FsPath>, id: &str) -> DocResult<Template> {
+    all_templates(dir)
+        .into_iter()
+        .find(|t| t.info.id == id)
+        .ok_or_else(|| DocError::Edit(format!("unknown template: {id}")))
+}
+
+pub fn render(body: &str, vars: &HashMap<String, String>) -> DocResult<String> {
+    let text = expand(body, vars);
+    serde_json::from_str::<serde_json::Value>(&text)
+        .map_err(|e| DocError::Parse(format!("template produced invalid JSON: {e}")))?;
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn every_builtin_renders_with_defaults() {
+        for t in builtin_templates() {
+            let text = render(&t.body, &HashMap::new())
+                .unwrap_or_else(|e| panic!("{} failed: {e}", t.info.id));
+            assert!(!text.contains("{{"), "{} left placeholders", t.info.id);
+        }
+    }
+
+    #[test]
+    fn render_substitutes_and_escapes_values() {
+        let t = find(None, "builtin:package-json").unwrap();
+        let text = render(&t.body, &vars(&[("name", "a\"b"), ("version", "2.0.0")])).unwrap();
+        let v: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(v["name"], "a\"b");
+        assert_eq!(v["version"], "2.0.0");
+        assert_eq!(v["license"], "MIT");
+    }
+
+    #[test]
+    fn numeric_placeholders_render_unquoted() {
+        let t = find(None, "builtin:geojson-feature-collection").unwrap();
+        let text = render(
+            &t.body,
+            &vars(&[("longitude", "13.4"), ("latitude", "52.5")]),
+        )
+        .unwrap();
+        let v: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            v["features"][0]["geometry"]["coordinates"],
+            serde_json::json!([13.4, 52.5])
+        );
+    }
+
+    #[test]
+    fn invalid_output_is_a_parse_error() {
+        let err = render(r#"{"n": {{n}}}"#, &vars(&[("n", "not a number")])).unwrap_err();
+        assert!(matches!(err, DocError::Parse(_)));
+    }
+
+    #[test]
+    fn user_templates_are_read_from_dir() {
+        let dir = TempDir::new("templates");
+        std::fs::write(dir.join("Invoice.json"), r#"{"customer": "{{customer}}"}"#).unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let user = user_templates(&dir);
+        assert_eq!(user.len(), 1);
+        assert_eq!(user[0].info.id, "user:Invoice");
+        assert!(!user[0].info.builtin);
+        assert_eq!(user[0].info.variables[0].name, "customer");
+
+        let t = find(Some(&*dir), "user:Invoice").unwrap();
+        let text = render(&t.body, &vars(&[("customer", "ACME")])).unwrap();
+        assert_eq!(text, r#"{"customer": "ACME"}"#);
+
+        assert!(find(Some(&*dir), "user:missing").is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+
+use super::outline::{self, Outline};
+use super::positions::{self, Cursor, LineIndex, Node, PositionIndex, TextPosition};
+use super::syntax::{self, SyntaxReport};
+use super::types::{DocError, DocResult, Path};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextRange {
+    pub start: Cursor,
+    pub end: Cursor,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEditResult {
+    pub version: u64,
+    pub line_count: u32,
+    pub byte_len: u64,
+    pub structure_valid: bool,
+    pub reparsed_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub syntax: Option<SyntaxReport>,
+}
+
+pub struct TextBuffer {
+    rope: Rope,
+    nodes: Option<Vec<Node>>,
+    version: u64,
+    doc_version: u64,
+}
+
+fn full_scan(text: &str) -> Option<Vec<Node>> {
+    let (nodes, end) = positions::scan_value(text, 0, Path::root(), None).ok()?;
+    text[end..]
+        .trim_start_matches(|c: char| c.is_ascii_whitespace())
+        .is_empty()
+        .then_some(nodes)
+}
+
+impl TextBuffer {
+    /// Seeds a buffer mirroring the document at `doc_version`.
+    pub fn new(text: String, doc_version: u64) -> Self {
+        Self {
+            rope: Rope::from_str(&text),
+            nodes: full_scan(&text),
+            version: 0,
+            doc_version,
+        }
+    }
+
+    /// Replaces the text after the document changed underneath the buffer;
+    /// the version still moves forward so clients refetch.
+    pub fn reseed(&mut self, text: String, doc_version: u64) -> TextEditResult {
+        self.rope = Rope::from_str(&text);
+        self.nodes = full_scan(&text);
+        self.version += 1;
+        self.doc_version = doc_version;
+        self.result(text.len())
+    }
+
+    pub fn doc_version(&self) -> u64 {
+        self.doc_version
+    }
+
+    pub fn set_doc_version(&mut self, doc_version: u64) {
+        self.doc_version = doc_version;
+    }
+
+    pub fn text(&self) -> String {
+        self.rope.to_string()
+    }
+
+    pub fn outline(&self, max_depth: usize) -> DocResult<Outline> {
+        let nodes = self
+            .nodes
+            .as_deref()
+            .ok_or_else(|| DocError::Parse("the buffer is not valid JSON".into()))?;
+        let text = self.rope.to_string();
+        let index = PositionIndex::from_parts(LineIndex::new(&text), nodes);
+        Ok(outline::outline(&index, max_depth))
+    }
+
+    fn offset(&self, cursor: Cursor) -> DocResult<usize> {
+        let line = (cursor.line.max(1) - 1) as usize;
+        if line >= self.rope.len_lines() {
+            return Err(DocError::Edit(format!(
+                "line {} is past the end",
+                cursor.line
+            )));
+        }
+        let text = self.rope.line(line);
+        let len = text.len_chars() - usize::from(text.chars().last() == Some('\n'));
+        let column = ((cursor.column.max(1) - 1) as usize).min(len);
+        Ok(self.rope.line_to_byte(line) + text.char_to_byte(column))
+    }
+
+    pub fn read(&self, range: TextRange) -> DocResult<String> {
+        let start = self.offset(range.start)?;
+        let end = self.offset(range.end)?;
+        if end < start {
+            return Err(DocError::Edit("read range ends before it starts".into()));
+        }
+        Ok(self.rope.byte_slice(start..end).to_string())
+    }
+
+    pub fn read_lines(&self, first: u32, count: u32) -> Vec<String> {
+        let first = first.max(1) as usize - 1;
+        let last = first
+            .saturating_add(count as usize)
+            .min(self.rope.len_lines());
+        (first..last)
+            .map(|i| {
+                let line = self.rope.line(i).to_string();
+                let line = line.strip_suffix('\n').unwrap_or(&line);
+                line.strip_suffix('\r').unwrap_or(line).to_string()
+            })
+            .collect()
+    }
+
+    pub fn position(&self, offset: usize) -> TextPosition {
+        let char_idx = self.rope.byte_to_char(offset.min(self.rope.len_bytes()));
+        let line = self.rope.char_to_line(char_idx);
+        TextPosition {
+            line: line as u32 + 1,
+            column: (char_idx - self.rope.line_to_char(line)) as u32 + 1,
+            offset: self.rope.char_to_byte(char_idx) as u32,
+        }
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        let nodes = self.nodes.as_ref().map_or(0, |nodes| {
+            nodes.len() * std::mem::size_of::<Node>()
+                + nodes.iter().map(|n| n.path.0.len() * 32).sum::<usize>()
+        });
+        self.rope.capacity() + nodes
+    }
+
+    pub fn status(&self) -> TextEditResult {
+        self.result(self.rope.len_bytes())
+    }
+
+    pub fn apply(&mut self, range: TextRange, new_text: &str) -> DocResult<TextEditResult> {
+        let start = self.offset(range.start)?;
+        let end = self.offset(range.end)?;
+        if end < start {
+            return Err(DocError::Edit("edit range ends before it starts".into()));
+        }
+        let (from, to) = (self.rope.byte_to_char(start), self.rope.byte_to_char(end));
+        self.rope.remove(from..to);
+        self.rope.insert(from, new_text);
+        let delta = new_text.len() as isize - (end - start) as isize;
+        let reparsed = self.reparse(start, end, delta);
+        self.version += 1;
+        Ok(self.result(reparsed))
+    }
+
+    fn reparse(&mut self, start: usize, end: usize, delta: isize) -> usize {
+        let shift = |offset: usize| (offset as isize + delta) as usize;
+        if let Some(nodes) = &mut self.nodes {
+            let rope = &self.rope;
+            let container = nodes.iter().rposition(|n| {
+                n.start < start && end < n.end && matches!(rope.byte(n.start), b'{' | b'[')
+            });
+            if let Some(i) = container {
+                let old_end = nodes[i].end;
+                let j = nodes[i + 1..]
+                    .iter()
+                    .position(|n| n.start >= old_end)
+                    .map_or(nodes.len(), |p| i + 1 + p);
+                let node = &nodes[i];
+                let base = node.start;
+                let text = rope.byte_slice(base..shift(old_end)).to_string();
+                let scanned = positions::scan_value(&text, 0, node.path.clone(), None);
+                if let Ok((mut fresh, len)) = scanned {
+                    if base + len == shift(old_end) {
+                        for n in &mut fresh {
+                            n.start += base;
+                            n.end += base;
+                            n.key_start = n.key_start.map(|k| k + base);
+                        }
+                        fresh[0].key_start = node.key_start;
+                        for n in &mut nodes[..i] {
+                            if n.end >= old_end {
+                                n.end = shift(n.end);
+                            }
+                        }
+                        for n in &mut nodes[j..] {
+                            n.start = shift(n.start);
+                            n.end = shift(n.end);
+                            n.key_start = n.key_start.map(shift);
+                        }
+                        nodes.splice(i..j, fresh);
+                        return len;
+                    }
+                }
+            }
+        }
+        let text = self.rope.to_string();
+        self.nodes = full_scan(&text);
+        text.len()
+    }
+
+    fn result(&self, reparsed: usize) -> TextEditResult {
+        TextEditResult {
+            version: self.version,
+            line_count: self.rope.len_lines() as u32,
+            byte_len: self.rope.len_bytes() as u64,
+            structure_valid: self.nodes.is_some(),
+            reparsed_bytes: reparsed as u64,
+            syntax: self
+                .nodes
+                .is_none()
+                .then(|| syntax::check(&self.rope.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(line: u32, start: u32, end: u32) -> TextRange {
+        TextRange {
+            start: Cursor {
+                line,
+                column: start,
+            },
+            end: Cursor { line, column: end },
+        }
+    }
+
+    fn assert_fresh(buffer: &TextBuffer) {
+        let text = buffer.rope.to_string();
+        assert_eq!(buffer.rope.len_lines(), positions::line_starts(&text).len());
+        assert_eq!(buffer.nodes, full_scan(&text));
+    }
+
+    #[test]
+    fn reparses_only_the_enclosing_container() {
+        let mut buffer = TextBuffer::new("{\"a\": [1, 2],\n \"b\": {\"c\": \"x\"}}".into(), 0);
+        let result = buffer.apply(range(1, 12, 12), ", 3").unwrap();
+        assert_eq!((result.version, result.reparsed_bytes), (1, 9));
+        assert!(result.structure_valid);
+        assert_fresh(&buffer);
+
+        let result = buffer
+            .apply(range(2, 13, 16), "\"y\",\n  \"d\": null")
+            .unwrap();
+        assert_eq!(result.line_count, 3);
+        assert!(result.reparsed_bytes < result.byte_len);
+        assert_fresh(&buffer);
+        assert_eq!(
+            buffer.rope.to_string(),
+            "{\"a\": [1, 2, 3],\n \"b\": {\"c\": \"y\",\n  \"d\": null}}"
+        );
+    }
+
+    #[test]
+    fn reseeding_keeps_the_version_moving() {
+        let mut buffer = TextBuffer::new("[1]".into(), 3);
+        buffer.apply(range(1, 3, 3), ", 2").unwrap();
+        let result = buffer.reseed("{\"a\": 1}".into(), 5);
+        assert_eq!(result.version, 2);
+        assert_eq!(buffer.doc_version(), 5);
+        assert_eq!(buffer.text(), "{\"a\": 1}");
+        assert_fresh(&buffer);
+    }
+
+    #[test]
+    fn reads_ranges_and_lines() {
+        let buffer = TextBuffer::new("{\r\n  \"a\": 1,\r\n  \"b\": 2\r\n}".into(), 0);
+        assert_eq!(buffer.read_lines(2, 2), vec!["  \"a\": 1,", "  \"b\": 2"]);
+        assert_eq!(buffer.read_lines(4, 10), vec!["}"]);
+        assert_eq!(buffer.read(range(3, 3, 6)).unwrap(), "\"b\"");
+        assert_eq!(buffer.position(10).line, 2);
+    }
+
+    #[test]
+    fn positions_snap_to_char_boundaries() {
+        let buffer = TextBuffer::new("[\"é\",\n \"ü\"]".into(), 0);
+        let inside = buffer.position(3);
+        assert_eq!((inside.line, inside.column, inside.offset), (1, 3, 2));
+        let second = buffer.position(10);
+        assert_eq!((second.line, second.column, second.offset), (2, 3, 9));
+        assert_eq!(buffer.position(1000).offset, 13);
+    }
+
+    #[test]
+    fn falls_back_to_syntax_report_until_fixed() {
+        let mut buffer = TextBuffer::new("[1, {\"a\": 2}]".into(), 0);
+        let broken = buffer.apply(range(1, 12, 13), "").unwrap();
+        assert!(!broken.structure_valid);
+        assert!(!broken.syntax.unwrap().valid);
+        assert!(buffer.outline(2).is_err());
+
+        let fixed = buffer.apply(range(1, 12, 12), "}").unwrap();
+        assert!(fixed.structure_valid && fixed.syntax.is_none());
+        assert_fresh(&buffer);
+        assert_eq!(buffer.outline(2).unwrap().symbols.len(), 2);
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+pub fn date_utc(millis: u64) -> String {
+    let (y, m, d) = civil_from_days((millis / 86_400_000) as i64);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+pub fn rfc3339_utc(millis: u64) -> String {
+    let secs_of_day = (millis / 1000) % 86_400;
+    format!(
+        "{}T{:02}:{:02}:{:02}.{:03}Z",
+        date_utc(millis),
+        secs_of_day / 3600,
+        (secs_of_day / 60) % 60,
+        secs_of_day % 60,
+        millis % 1000
+    )
+}
+
+pub fn parse_rfc3339_millis(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let b = s.as_bytes();
+    if b.len() < 10 || b[4] != b'-' || b[7] != b'-' {
+        return None;
+    }
+    let num = |range: std::ops::Range<usize>| -> Option<i64> {
+        let part = s.get(range)?;
+        if !part.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        part.parse().ok()
+    };
+    let (y, m, d) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+    let mut millis = days_from_civil(y, m as u32, d as u32) * 86_400_000;
+    let rest = &s[10..];
+    if rest.is_empty() {
+        return Some(millis);
+    }
+    let rest_bytes = rest.as_bytes();
+    if !matches!(rest_bytes[0], b'T' | b't' | b' ') || rest.len() < 6 || rest_bytes[3] != b':' {
+        return None;
+    }
+    let (h, min) = (num(11..13)?, num(14..16)?);
+    let mut secs = h * 3600 + min * 60;
+    let mut tail = &s[16..];
+    if let Some(after) = tail.strip_prefix(':') {
+        secs += num(17..19)?;
+        tail = &after[2..];
+        if let Some(frac) = tail.strip_prefix('.') {
+            let digits = frac.bytes().take_while(u8::is_ascii_digit).count();
+            let ms: String = frac[..digits]
+                .chars()
+                .chain("000".chars())
+                .take(3)
+                .collect();
+            millis += ms.parse::<i64>().ok()?;
+            tail = &frac[digits..];
+        }
+    }
+    millis += secs * 1000;
+    let offset = match tail {
+        "" | "Z" | "z" => 0,
+        _ => {
+            let sign = match tail.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let hhmm = tail[1..].replace(':', "");
+            if hhmm.len() != 4 || !hhmm.bytes().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let h: i64 = hhmm[..2].parse().ok()?;
+            let m: i64 = hhmm[2..].parse().ok()?;
+            sign * (h * 3600 + m * 60) * 1000
+        }
+    };
+    Some(millis - offset)
+}
+
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = (y - era * 400) as u64;
+    let mp = if m > 2 { m - 3 } else { m + 9 } as u64;
+    let doy = (153 * mp + 2) / 5 + d as u64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe as i64 - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = (z - era * 146_097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe as i64 + era * 400;
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch_and_known_dates() {
+        assert_eq!(date_utc(0), "1970-01-01");
+        assert_eq!(date_utc(1_704_067_200_000), "2024-01-01");
+        assert_eq!(date_utc(1_709_164_800_000), "2024-02-29");
+    }
+
+    #[test]
+    fn rfc3339_includes_time_and_millis() {
+        assert_eq!(
+            rfc3339_utc(1_704_067_200_000 + 3_723_045),
+            "2024-01-01T01:02:03.045Z"
+        );
+    }
+
+    #[test]
+    fn parses_rfc3339_with_offsets() {
+        let base = 1_704_067_200_000 + 3_723_045;
+        assert_eq!(parse_rfc3339_millis("2024-01-01T01:02:03.045Z"), Some(base));
+        assert_eq!(
+            parse_rfc3339_millis("2024-01-01T03:02:03.045+02:00"),
+            Some(base)
+        );
+        assert_eq!(parse_rfc3339_millis("2024-02-29"), Some(1_709_164_800_000));
+        assert_eq!(parse_rfc3339_millis("1969-12-31T23:59:59Z"), Some(-1000));
+        assert_eq!(parse_rfc3339_millis("2024-13-01"), None);
+        assert_eq!(parse_rfc3339_millis("yesterday"), None);
+    }
+
+    #[test]
+    fn rejects_multibyte_time_without_panicking() {
+        assert_eq!(parse_rfc3339_millis("2024-01-01 1é:00"), None);
+        assert_eq!(parse_rfc3339_millis("2024-01-01Té0:00"), None);
+        assert_eq!(parse_rfc3339_millis("2024-01-01T10:00:0é"), None);
+        assert_eq!(parse_rfc3339_millis("2024-01-01T10:00+0é:00"), None);
+    }
+}
@@ -46,6 +46,18 @@ impl Path {
     pub fn is_root(&self) -> bool {
         self.0.is_empty()
     }
+
+    pub fn to_pointer(&self) -> String {
+        let mut out = String::new();
+        for seg in &self.0 {
+            out.push('/');
+            match seg {
+                PathSegment::Key(k) => out.push_str(&k.replace('~', "~0").replace('/', "~1")),
+                PathSegment::Index(i) => out.push_str(&i.to_string()),
+            }
+        }
+        out
+    }
 }
 
 impl fmt::Display for Path {
@@ -159,6 +171,12 @@ pub enum DocError {
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("conflict: {0}")]
+    Conflict(String),
+
+    #[error("not downloaded: {0}")]
+    NotDownloaded(String),
+
     #[error("cancelled")]
     Cancelled,
 }
@@ -183,6 +201,8 @@ pub enum ErrorKind {
     Schema,
     Export,
     Io,
+    Conflict,
+    NotDownloaded,
     Cancelled,
 }
 
@@ -197,8 +217,13 @@ impl From<DocError> for WireError {
             DocError::Schema(_) => ErrorKind::Schema,
             DocError::Export(_) => ErrorKind::Export,
             DocError::Io(_) => ErrorKind::Io,
+            DocError::Conflict(_) => ErrorKind::Conflict,
+            DocError::NotDownloaded(_) => ErrorKind::NotDownloaded,
             DocError::Cancelled => ErrorKind::Cancelled,
         };
+        if kind != ErrorKind::Cancelled {
+            tracing::warn!(kind = ?kind, "command failed");
+        }
         WireError {
             kind,
             message: e.to_string(),
@@ -253,6 +278,17 @@ mod tests {
         assert_eq!(roundtrip(&path), path);
     }
 
+    #[test]
+    fn path_renders_escaped_json_pointer() {
+        let path = Path(vec![
+            PathSegment::Key("a/b".into()),
+            PathSegment::Index(2),
+            PathSegment::Key("~x".into()),
+        ]);
+        assert_eq!(path.to_pointer(), "/a~1b/2/~0x");
+        assert_eq!(Path::root().to_pointer(), "");
+    }
+
     #[test]
     fn path_display_dot_form_for_bare_identifiers() {
         let p = Path(vec![
@@ -0,0 +1,326 @@
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use super::settings::{SettingsStore, UpdateChannel};
+use super::taskbar::TaskProgress;
+use super::timefmt;
+use super::types::{DocError, DocResult};
+
+pub const STATUS_EVENT: &str = "update-status";
+
+const RELEASES: &str = "https://github.com/hendurhance/pandia/releases";
+const STATE_FILE: &str = "updater.json";
+const SCHEDULER_TICK: Duration = Duration::from_secs(15 * 60);
+const HOUR_MS: u64 = 60 * 60 * 1000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum UpdateState {
+    Idle,
+    Checking,
+    UpToDate,
+    #[serde(rename_all = "camelCase")]
+    Available {
+        version: String,
+        notes: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Downloading {
+        version: String,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ReadyToInstall {
+        version: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateStatus {
+    pub state: UpdateState,
+    pub channel: UpdateChannel,
+    pub current_version: String,
+    pub last_check_at: Option<u64>,
+    pub next_check_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Persisted {
+    last_check_at: Option<u64>,
+}
+
+struct Pending {
+    update: Update,
+    bytes: Option<Vec<u8>>,
+}
+
+struct Inner {
+    state: UpdateState,
+    last_check_at: Option<u64>,
+    pending: Option<Pending>,
+}
+
+pub struct Updater {
+    state_path: PathBuf,
+    inner: Mutex<Inner>,
+}
+
+pub fn endpoint(channel: UpdateChannel) -> String {
+    match channel {
+        UpdateChannel::Stable => format!("{RELEASES}/latest/download/latest.json"),
+        UpdateChannel::Beta => format!("{RELEASES}/download/beta/latest.json"),
+        UpdateChannel::Nightly => format!("{RELEASES}/download/nightly/latest.json"),
+    }
+}
+
+pub fn next_check_at(last_check_at: Option<u64>, interval_hours: u64) -> Option<u64> {
+    if interval_hours == 0 {
+        return None;
+    }
+    Some(last_check_at.map_or(0, |t| t + interval_hours * HOUR_MS))
+}
+
+fn read_persisted(path: &FsPath) -> Persisted {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+impl Updater {
+    pub fn new(data_dir: &FsPath) -> Self {
+        let state_path = data_dir.join(STATE_FILE);
+        let persisted = read_persisted(&state_path);
+        Self {
+            state_path,
+            inner: Mutex::new(Inner {
+                state: UpdateState::Idle,
+                last_check_at: persisted.last_check_at,
+                pending: None,
+            }),
+        }
+    }
+
+    pub fn status(&self, app: &AppHandle, settings: &SettingsStore) -> DocResult<UpdateStatus> {
+        let prefs = settings.get()?;
+        let inner = self.inner.lock();
+        Ok(UpdateStatus {
+            state: inner.state.clone(),
+            channel: prefs.update_channel,
+            current_version: app.package_info().version.to_string(),
+            last_check_at: inner.last_check_at,
+            next_check_at: next_check_at(inner.last_check_at, prefs.update_check_interval_hours),
+        })
+    }
+
+    fn set_state(&self, app: &AppHandle, state: UpdateState) {
+        self.inner.lock().state = state.clone();
+        let _ = app.emit(STATUS_EVENT, &state);
+    }
+
+    fn fail(&self, app: &AppHandle, message: String) -> DocError {
+        self.set_state(
+            app,
+            UpdateState::Error {
+                message: message.clone(),
+            },
+        );
+        DocError::Io(std::io::Error::other(message))
+    }
+
+    pub fn forget_pending(&self, app: &AppHandle) {
+        self.inner.lock().pending = None;
+        self.set_state(app, UpdateState::Idle);
+    }
+
+    pub async fn check(&self, app: &AppHandle, channel: UpdateChannel) -> DocResult<UpdateState> {
+        if matches!(
+            self.inner.lock().state,
+            UpdateState::Checking | UpdateState::Downloading { .. }
+        ) {
+            return Err(DocError::Edit("an update check is already running".into()));
+        }
+        self.set_state(app, UpdateState::Checking);
+        let url = endpoint(channel)
+            .parse()
+            .map_err(|e| self.fail(app, format!("bad update endpoint: {e}")))?;
+        let updater = app
+            .updater_builder()
+            .endpoints(vec![url])
+            .and_then(|b| b.build())
+            .map_err(|e| self.fail(app, e.to_string()))?;
+        let found = updater
+            .check()
+            .await
+            .map_err(|e| self.fail(app, e.to_string()))?;
+        let now = timefmt::now_millis();
+        let state = match found {
+            Some(update) => {
+                let state = UpdateState::Available {
+                    version: update.version.clone(),
+                    notes: update.body.clone(),
+                };
+                self.inner.lock().pending = Some(Pending {
+                    update,
+                    bytes: None,
+                });
+                state
+            }
+            None => {
+                self.inner.lock().pending = None;
+                UpdateState::UpToDate
+            }
+        };
+        self.inner.lock().last_check_at = Some(now);
+        let persisted = Persisted {
+            last_check_at: Some(now),
+        };
+        if let Ok(json) = serde_json::to_string(&persisted) {
+            let _ = std::fs::write(&self.state_path, json);
+        }
+        self.set_state(app, state.clone());
+        Ok(state)
+    }
+
+    pub async fn download(self: &Arc<Self>, app: &AppHandle) -> DocResult<UpdateState> {
+        let update = {
+            let inner = self.inner.lock();
+            match &inner.pending {
+                Some(Pending {
+                    bytes: Some(_),
+                    update,
+                }) => {
+                    return Ok(UpdateState::ReadyToInstall {
+                        version: update.version.clone(),
+                    })
+                }
+                Some(Pending { update, .. }) => update.clone(),
+                None => return Err(DocError::Edit("no update is available".into())),
+            }
+        };
+        let version = update.version.clone();
+        self.set_state(
+            app,
+            UpdateState::Downloading {
+                version: version.clone(),
+                downloaded: 0,
+                total: None,
+            },
+        );
+        let progress = Arc::clone(self);
+        let progress_app = app.clone();
+        let progress_version = version.clone();
+        let mut downloaded = 0u64;
+        let mut task: Option<TaskProgress> = None;
+        let bytes = update
+            .download(
+                move |chunk, total| {
+                    downloaded += chunk as u64;
+                    task.get_or_insert_with(|| TaskProgress::start(&progress_app, total))
+                        .advance(downloaded);
+                    progress.set_state(
+                        &progress_app,
+                        UpdateState::Downloading {
+                            version: progress_version.clone(),
+                            downloaded,
+                            total,
+                        },
+                    );
+                },
+                || {},
+            )
+            .await
+            .map_err(|e| self.fail(app, e.to_string()))?;
+        self.inner.lock().pending = Some(Pending {
+            update,
+            bytes: Some(bytes),
+        });
+        let state = UpdateState::ReadyToInstall { version };
+        self.set_state(app, state.clone());
+        Ok(state)
+    }
+
+    pub fn install_pending(&self) -> DocResult<bool> {
+        let Some(Pending {
+            update,
+            bytes: Some(bytes),
+        }) = self.inner.lock().pending.take()
+        else {
+            return Ok(false);
+        };
+        update
+            .install(bytes)
+            .map_err(|e| DocError::Io(std::io::Error::other(e.to_string())))?;
+        Ok(true)
+    }
+}
+
+pub fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (Some(updater), Some(settings)) = (
+                app.try_state::<Arc<Updater>>(),
+                app.try_state::<Arc<SettingsStore>>(),
+            ) else {
+                return;
+            };
+            let due = settings.get().ok().and_then(|prefs| {
+                let last = updater.inner.lock().last_check_at;
+                next_check_at(last, prefs.update_check_interval_hours)
+                    .filter(|at| *at <= timefmt::now_millis())
+                    .map(|_| prefs.update_channel)
+            });
+            if let Some(channel) = due {
+                if let Err(e) = updater.check(&app, channel).await {
+                    tracing::warn!(error = %e, "scheduled update check failed");
+                }
+            }
+            tokio::time::sleep(SCHEDULER_TICK).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels_map_to_release_endpoints() {
+        assert!(endpoint(UpdateChannel::Stable).ends_with("/releases/latest/download/latest.json"));
+        assert!(endpoint(UpdateChannel::Beta).ends_with("/download/beta/latest.json"));
+        assert!(endpoint(UpdateChannel::Nightly).ends_with("/download/nightly/latest.json"));
+    }
+
+    #[test]
+    fn schedules_next_check_from_interval() {
+        assert_eq!(next_check_at(None, 24), Some(0));
+        assert_eq!(next_check_at(Some(1_000), 2), Some(1_000 + 2 * HOUR_MS));
+        assert_eq!(next_check_at(Some(1_000), 0), None);
+    }
+
+    #[test]
+    fn status_serializes_with_state_tag() {
+        let json = serde_json::to_value(UpdateState::Downloading {
+            version: "1.2.0".into(),
+            downloaded: 10,
+            total: Some(100),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "state": "downloading", "version": "1.2.0", "downloaded": 10, "total": 100 })
+        );
+    }
+}
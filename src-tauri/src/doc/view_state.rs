@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::path::{Path as FsPath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::bookmarks::file_key;
+use super::disk_hash;
+use super::settings::DefaultView;
+use super::timefmt::now_millis;
+use super::types::DocResult;
+
+const MAX_ENTRIES: usize = 500;
+const MAX_EXPANDED: usize = 10_000;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewState {
+    #[serde(default)]
+    pub expanded: Vec<String>,
+    #[serde(default)]
+    pub selected: Option<String>,
+    #[serde(default)]
+    pub scroll_top: f64,
+    #[serde(default)]
+    pub scroll_left: f64,
+    #[serde(default)]
+    pub view_mode: Option<DefaultView>,
+    #[serde(default)]
+    pub column_widths: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ViewStateMatch {
+    Exact,
+    PathOnly,
+    ContentOnly,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedViewState {
+    pub state: ViewState,
+    pub matched: ViewStateMatch,
+    pub saved_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    path: String,
+    hash: String,
+    saved_at: u64,
+    state: ViewState,
+}
+
+pub fn view_state_path(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let base = super::portable::data_dir(app)?;
+    Ok(base.join("view-state.json"))
+}
+
+fn load_all(path: &FsPath) -> std::io::Result<Vec<Entry>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn store_all(path: &FsPath, all: &[Entry]) -> std::io::Result<()> {
+    let json = serde_json::to_string(all).map_err(|e| std::io::Error::other(e.to_string()))?;
+    std::fs::write(path, json)
+}
+
+pub fn save(db: &FsPath, file: &str, mut state: ViewState) -> DocResult<()> {
+    let hash = disk_hash::hash_file(FsPath::new(file))?;
+    let key = file_key(file);
+    state.expanded.truncate(MAX_EXPANDED);
+    let mut all = load_all(db)?;
+    all.retain(|e| e.path != key && e.hash != hash);
+    all.push(Entry {
+        path: key,
+        hash,
+        saved_at: now_millis(),
+        state,
+    });
+    if all.len() > MAX_ENTRIES {
+        all.sort_by_key(|e| std::cmp::Reverse(e.saved_at));
+        all.truncate(MAX_ENTRIES);
+    }
+    store_all(db, &all)?;
+    Ok(())
+}
+
+pub fn load(db: &FsPath, file: &str) -> DocResult<Option<LoadedViewState>> {
+    let hash = disk_hash::hash_file(FsPath::new(file))?;
+    let key = file_key(file);
+    let all = load_all(db)?;
+    let found = all
+        .iter()
+        .filter_map(|e| {
+            let matched = match (e.path == key, e.hash == hash) {
+                (true, true) => ViewStateMatch::Exact,
+                (true, false) => ViewStateMatch::PathOnly,
+                (false, true) => ViewStateMatch::ContentOnly,
+                (false, false) => return None,
+            };
+            Some((matched, e))
+        })
+        .min_by_key(|(matched, _)| *matched as u8);
+    Ok(found.map(|(matched, e)| LoadedViewState {
+        state: e.state.clone(),
+        matched,
+        saved_at: e.saved_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    fn state(scroll_top: f64) -> ViewState {
+        ViewState {
+            expanded: vec!["".into(), "/users".into()],
+            scroll_top,
+            view_mode: Some(DefaultView::Grid),
+            column_widths: BTreeMap::from([("name".to_string(), 180)]),
+            ..ViewState::default()
+        }
+    }
+
+    #[test]
+    fn restores_exact_matches_and_reports_changed_content() {
+        let dir = TempDir::new("view-state-exact");
+        let db = dir.join("view-state.json");
+        let file = dir.join("a.json").to_string_lossy().into_owned();
+        std::fs::write(&file, r#"{"users": []}"#).unwrap();
+        assert!(load(&db, &file).unwrap().is_none());
+
+        save(&db, &file, state(120.0)).unwrap();
+        let loaded = load(&db, &file).unwrap().unwrap();
+        assert_eq!(loaded.matched, ViewStateMatch::Exact);
+        assert_eq!(loaded.state, state(120.0));
+
+        std::fs::write(&file, r#"{"users": [1]}"#).unwrap();
+        let loaded = load(&db, &file).unwrap().unwrap();
+        assert_eq!(loaded.matched, ViewStateMatch::PathOnly);
+    }
+
+    #[test]
+    fn follows_content_to_a_new_path() {
+        let dir = TempDir::new("view-state-moved");
+        let db = dir.join("view-state.json");
+        let old = dir.join("old.json").to_string_lossy().into_owned();
+        let new = dir.join("new.json").to_string_lossy().into_owned();
+        std::fs::write(&old, "[1, 2, 3]").unwrap();
+        save(&db, &old, state(40.0)).unwrap();
+        std::fs::rename(&old, &new).unwrap();
+
+        let loaded = load(&db, &new).unwrap().unwrap();
+        assert_eq!(loaded.matched, ViewStateMatch::ContentOnly);
+        assert_eq!(loaded.state.scroll_top, 40.0);
+
+        save(&db, &new, state(10.0)).unwrap();
+        assert_eq!(load_all(&db).unwrap().len(), 1);
+    }
+}
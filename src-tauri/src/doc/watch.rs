@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::path::Path as FsPath;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::dirdiff::{self, DirDiffOptions};
+use super::jobs::CancelFlag;
+use super::lint::{self, LintConfig, LintFinding, Severity};
+use super::schema_validate::SchemaError;
+use super::timefmt::now_millis;
+use super::types::{DocError, DocResult};
+
+pub const RESULT_EVENT: &str = "watch-result";
+
+const DEFAULT_POLL_MS: u64 = 1_000;
+const MAX_ERRORS_PER_FILE: usize = 50;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ValidationProfile {
+    pub schema: Option<String>,
+    pub schema_path: Option<String>,
+    pub lint: bool,
+    pub lint_config: Option<LintConfig>,
+    pub extensions: Vec<String>,
+    pub recursive: bool,
+    pub poll_ms: u64,
+}
+
+impl Default for ValidationProfile {
+    fn default() -> Self {
+        Self {
+            schema: None,
+            schema_path: None,
+            lint: true,
+            lint_config: None,
+            extensions: DirDiffOptions::default().extensions,
+            recursive: true,
+            poll_ms: DEFAULT_POLL_MS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileCheck {
+    pub path: String,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_error: Option<String>,
+    pub schema_errors: Vec<SchemaError>,
+    pub lint_findings: Vec<LintFinding>,
+    pub checked_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchBatch {
+    pub watch_id: String,
+    pub results: Vec<FileCheck>,
+    pub removed: Vec<String>,
+    pub passed: u32,
+    pub failed: u32,
+}
+
+pub struct Checker {
+    validator: Option<jsonschema::Validator>,
+    lint: Option<LintConfig>,
+}
+
+impl Checker {
+    pub fn new(
+        root: &FsPath,
+        schema: Option<&Value>,
+        profile: &ValidationProfile,
+    ) -> DocResult<Self> {
+        let validator = schema
+            .map(|s| {
+                jsonschema::validator_for(s)
+                    .map_err(|e| DocError::Schema(format!("compile error: {e}")))
+            })
+            .transpose()?;
+        let lint = match (&profile.lint_config, profile.lint) {
+            (_, false) => None,
+            (Some(config), true) => Some(config.clone()),
+            (None, true) => Some(match lint::find_config(root) {
+                Some(found) => lint::load_config(&found)?,
+                None => LintConfig::default(),
+            }),
+        };
+        Ok(Self { validator, lint })
+    }
+
+    pub fn check(&self, rel: &str, text: &str) -> FileCheck {
+        let mut check = FileCheck {
+            path: rel.to_string(),
+            passed: true,
+            parse_error: None,
+            schema_errors: Vec::new(),
+            lint_findings: Vec::new(),
+            checked_at: now_millis(),
+        };
+        let value: Value = match serde_json::from_str(text) {
+            Ok(v) => v,
+            Err(e) => {
+                check.passed = false;
+                check.parse_error = Some(e.to_string());
+                return check;
+            }
+        };
+        if let Some(validator) = &self.validator {
+            check.schema_errors = validator
+                .iter_errors(&value)
+                .take(MAX_ERRORS_PER_FILE)
+                .map(|err| SchemaError {
+                    instance_path: err.instance_path.to_string(),
+                    schema_path: err.schema_path.to_string(),
+                    message: err.to_string(),
+                })
+                .collect();
+        }
+        if let Some(config) = &self.lint {
+            if let Ok((findings, _)) = lint::lint(text, config) {
+                check.lint_findings = findings.into_iter().take(MAX_ERRORS_PER_FILE).collect();
+            }
+        }
+        check.passed = check.schema_errors.is_empty()
+            && !check
+                .lint_findings
+                .iter()
+                .any(|f| f.severity == Severity::Error);
+        check
+    }
+}
+
+type Stamp = (Option<SystemTime>, u64);
+
+fn stamp(path: &FsPath) -> Option<Stamp> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok(), meta.len()))
+}
+
+fn scan(
+    root: &FsPath,
+    opts: &DirDiffOptions,
+    checker: &Checker,
+    seen: &mut HashMap<String, Stamp>,
+) -> DocResult<(Vec<FileCheck>, Vec<String>)> {
+    let files = dirdiff::list_files(root, opts)?;
+    let removed: Vec<String> = seen
+        .keys()
+        .filter(|rel| !files.contains(*rel))
+        .cloned()
+        .collect();
+    for rel in &removed {
+        seen.remove(rel);
+    }
+    let mut results = Vec::new();
+    for rel in files {
+        let path = root.join(&rel);
+        let Some(current) = stamp(&path) else {
+            continue;
+        };
+        if seen.get(&rel) == Some(&current) {
+            continue;
+        }
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        results.push(checker.check(&rel, &text));
+        seen.insert(rel, current);
+    }
+    Ok((results, removed))
+}
+
+pub fn watch(
+    watch_id: &str,
+    root: &FsPath,
+    checker: &Checker,
+    profile: &ValidationProfile,
+    cancel: &CancelFlag,
+    mut emit: impl FnMut(WatchBatch),
+) -> DocResult<()> {
+    let opts = DirDiffOptions {
+        extensions: profile.extensions.clone(),
+        recursive: profile.recursive,
+        ..DirDiffOptions::default()
+    };
+    let interval = Duration::from_millis(profile.poll_ms.max(100));
+    let mut seen = HashMap::new();
+    while !cancel.is_cancelled() {
+        let (results, removed) = scan(root, &opts, checker, &mut seen)?;
+        if !results.is_empty() || !removed.is_empty() {
+            let passed = results.iter().filter(|r| r.passed).count() as u32;
+            emit(WatchBatch {
+                watch_id: watch_id.to_string(),
+                failed: results.len() as u32 - passed,
+                passed,
+                results,
+                removed,
+            });
+        }
+        std::thread::sleep(interval);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+    use serde_json::json;
+
+    #[test]
+    fn rescans_only_changed_files() {
+        let dir = TempDir::new("watch");
+        std::fs::write(dir.join("ok.json"), r#"{"port": 80}"#).unwrap();
+        std::fs::write(dir.join("bad.json"), r#"{"port": "80"}"#).unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+
+        let schema = json!({"properties": {"port": {"type": "integer"}}});
+        let profile = ValidationProfile {
+            lint: false,
+            ..ValidationProfile::default()
+        };
+        let checker = Checker::new(&dir, Some(&schema), &profile).unwrap();
+        let opts = DirDiffOptions::default();
+        let mut seen = HashMap::new();
+
+        let (results, removed) = scan(&dir, &opts, &checker, &mut seen).unwrap();
+        let outcome: Vec<(&str, bool)> = results
+            .iter()
+            .map(|r| (r.path.as_str(), r.passed))
+            .collect();
+        assert_eq!(
+            outcome,
+            vec![
+                ("bad.json", false),
+                ("broken.json", false),
+                ("ok.json", true)
+            ]
+        );
+        assert!(results[1].parse_error.is_some());
+        assert!(removed.is_empty());
+
+        std::fs::write(dir.join("bad.json"), r#"{"port": 8080, "tls": true}"#).unwrap();
+        std::fs::remove_file(dir.join("broken.json")).unwrap();
+        let (results, removed) = scan(&dir, &opts, &checker, &mut seen).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].passed);
+        assert_eq!(removed, vec!["broken.json"]);
+    }
+}
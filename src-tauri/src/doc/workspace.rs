@@ -0,0 +1,402 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path as FsPath, PathBuf};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::dirdiff::{self, DirDiffOptions};
+use super::references::{self, ReferenceOptions};
+use super::schema_refs::{is_url, normalize, parse_schema_text, percent_decode};
+use super::types::{DocError, DocResult, Path, PathSegment};
+
+const EXTENSIONS: &[&str] = &["json", "geojson", "yaml", "yml"];
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub file: String,
+    pub pointer: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReferenceKind {
+    Definition,
+    Ref,
+    Id,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceReference {
+    pub location: Location,
+    pub kind: ReferenceKind,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSummary {
+    pub root: String,
+    pub files: u32,
+    pub definitions: u32,
+    pub references: u32,
+    pub errors: Vec<String>,
+}
+
+struct RefSite {
+    location: Location,
+    raw: String,
+    target: Option<(String, String)>,
+}
+
+struct IdSite {
+    location: Location,
+    value: String,
+}
+
+#[derive(Default)]
+struct Index {
+    files: HashSet<String>,
+    ids: HashMap<String, Vec<Location>>,
+    anchors: HashMap<(String, String), String>,
+    refs: Vec<RefSite>,
+    id_refs: Vec<IdSite>,
+}
+
+#[derive(Default)]
+pub struct WorkspaceIndex {
+    inner: Mutex<Option<Index>>,
+}
+
+impl WorkspaceIndex {
+    pub fn index(&self, root: &FsPath) -> DocResult<WorkspaceSummary> {
+        let opts = DirDiffOptions {
+            extensions: EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+            ..DirDiffOptions::default()
+        };
+        let root = normalize(root);
+        let mut index = Index::default();
+        let mut summary = WorkspaceSummary {
+            root: root.to_string_lossy().into_owned(),
+            ..WorkspaceSummary::default()
+        };
+        for rel in dirdiff::list_files(&root, &opts)? {
+            let path = root.join(&rel);
+            match read_document(&path) {
+                Ok(value) => {
+                    let file = path.to_string_lossy().into_owned();
+                    let mut pointer = Path::root();
+                    index.walk(&file, &path, &value, &mut pointer);
+                    index.files.insert(file);
+                }
+                Err(e) => summary.errors.push(format!("{rel}: {e}")),
+            }
+        }
+        summary.files = index.files.len() as u32;
+        summary.definitions =
+            index.ids.values().map(|v| v.len() as u32).sum::<u32>() + index.anchors.len() as u32;
+        summary.references = (index.refs.len() + index.id_refs.len()) as u32;
+        *self.inner.lock() = Some(index);
+        Ok(summary)
+    }
+
+    pub fn find_references(
+        &self,
+        query: &str,
+        file: Option<&str>,
+    ) -> DocResult<Vec<WorkspaceReference>> {
+        let guard = self.inner.lock();
+        let index = guard.as_ref().ok_or_else(not_indexed)?;
+        let query = query.trim();
+        if query.starts_with('/') || query.contains('#') {
+            let target = if query.starts_with('/') {
+                let file = file.ok_or_else(needs_file)?;
+                Location {
+                    file: normalize(FsPath::new(file)).to_string_lossy().into_owned(),
+                    pointer: query.to_string(),
+                }
+            } else {
+                let (target_file, fragment) =
+                    split_ref(query, file.map(FsPath::new)).ok_or_else(needs_file)?;
+                index.resolve(&target_file, &fragment)
+            };
+            return Ok(index
+                .refs
+                .iter()
+                .filter(|site| {
+                    site.target
+                        .as_ref()
+                        .is_some_and(|(f, frag)| index.resolve(f, frag) == target)
+                })
+                .map(|site| WorkspaceReference {
+                    location: site.location.clone(),
+                    kind: ReferenceKind::Ref,
+                    value: site.raw.clone(),
+                })
+                .collect());
+        }
+        let defined = index.ids.get(query).map(Vec::as_slice).unwrap_or_default();
+        let definitions = defined.iter().map(|loc| WorkspaceReference {
+            location: loc.clone(),
+            kind: ReferenceKind::Definition,
+            value: query.to_string(),
+        });
+        let uses = index
+            .id_refs
+            .iter()
+            .filter(|site| site.value == query)
+            .map(|site| WorkspaceReference {
+                location: site.location.clone(),
+                kind: ReferenceKind::Id,
+                value: site.value.clone(),
+            });
+        Ok(definitions.chain(uses).collect())
+    }
+
+    pub fn go_to_definition(&self, file: &str, pointer: &str) -> DocResult<Vec<Location>> {
+        let guard = self.inner.lock();
+        let index = guard.as_ref().ok_or_else(not_indexed)?;
+        let file = normalize(FsPath::new(file)).to_string_lossy().into_owned();
+        let nested = format!("{pointer}/$ref");
+        let at = |p: &str| Location {
+            file: file.clone(),
+            pointer: p.to_string(),
+        };
+        let (here, nested) = (at(pointer), at(&nested));
+        if let Some(site) = index
+            .refs
+            .iter()
+            .find(|s| s.location == here || s.location == nested)
+        {
+            return Ok(site
+                .target
+                .as_ref()
+                .filter(|(f, _)| index.files.contains(f))
+                .map(|(f, frag)| index.resolve(f, frag))
+                .into_iter()
+                .collect());
+        }
+        Ok(index
+            .id_refs
+            .iter()
+            .find(|s| s.location == here)
+            .and_then(|s| index.ids.get(&s.value))
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+impl Index {
+    fn walk(&mut self, file: &str, fs_path: &FsPath, value: &Value, path: &mut Path) {
+        match value {
+            Value::Object(map) => {
+                self.record(file, fs_path, map, path);
+                for (k, v) in map {
+                    path.push(PathSegment::Key(k.clone()));
+                    self.walk(file, fs_path, v, path);
+                    path.0.pop();
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    path.push(PathSegment::Index(i as u32));
+                    self.walk(file, fs_path, item, path);
+                    path.0.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record(
+        &mut self,
+        file: &str,
+        fs_path: &FsPath,
+        map: &serde_json::Map<String, Value>,
+        path: &Path,
+    ) {
+        let pointer = path.to_pointer();
+        let at = |p: String| Location {
+            file: file.to_string(),
+            pointer: p,
+        };
+        let opts = ReferenceOptions::default();
+        for (k, v) in map {
+            let key_pointer = || {
+                let mut p = path.clone();
+                p.push(PathSegment::Key(k.clone()));
+                p.to_pointer()
+            };
+            match (k.as_str(), v) {
+                ("$ref", Value::String(raw)) => self.refs.push(RefSite {
+                    location: at(key_pointer()),
+                    raw: raw.clone(),
+                    target: split_ref(raw, Some(fs_path)),
+                }),
+                ("$anchor", Value::String(name)) => {
+                    self.anchors
+                        .insert((file.to_string(), name.clone()), pointer.clone());
+                }
+                _ if references::DEFAULT_ID_KEYS.contains(&k.as_str()) => {
+                    if let Some(text) = references::scalar_text(v) {
+                        self.ids.entry(text).or_default().push(at(pointer.clone()));
+                    }
+                }
+                _ if references::is_reference_key(k, &opts) => {
+                    let values: Vec<(String, &Value)> = match v {
+                        Value::Array(items) => items
+                            .iter()
+                            .enumerate()
+                            .map(|(i, item)| (format!("{}/{i}", key_pointer()), item))
+                            .collect(),
+                        other => vec![(key_pointer(), other)],
+                    };
+                    for (p, item) in values {
+                        if let Some(value) = references::scalar_text(item) {
+                            self.id_refs.push(IdSite {
+                                location: at(p),
+                                value,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn resolve(&self, file: &str, fragment: &str) -> Location {
+        let pointer = if fragment.is_empty() || fragment.starts_with('/') {
+            fragment.to_string()
+        } else {
+            self.anchors
+                .get(&(file.to_string(), fragment.to_string()))
+                .cloned()
+                .unwrap_or_default()
+        };
+        Location {
+            file: file.to_string(),
+            pointer,
+        }
+    }
+}
+
+fn read_document(path: &FsPath) -> Result<Value, String> {
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_FILE_BYTES {
+        return Err(format!("skipped, larger than {MAX_FILE_BYTES} bytes"));
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_schema_text(&text)
+}
+
+fn split_ref(raw: &str, base: Option<&FsPath>) -> Option<(String, String)> {
+    if is_url(raw) {
+        return None;
+    }
+    let (path_part, fragment) = raw.split_once('#').unwrap_or((raw, ""));
+    let fragment = percent_decode(fragment);
+    let base = base?;
+    let target: PathBuf = if path_part.is_empty() {
+        base.to_path_buf()
+    } else {
+        let rel = percent_decode(path_part.strip_prefix("file://").unwrap_or(path_part));
+        base.parent().unwrap_or(FsPath::new("")).join(rel)
+    };
+    Some((normalize(&target).to_string_lossy().into_owned(), fragment))
+}
+
+fn not_indexed() -> DocError {
+    DocError::Edit("no workspace has been indexed".into())
+}
+
+fn needs_file() -> DocError {
+    DocError::Edit("a file is required to resolve a relative reference".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::test_support::TempDir;
+
+    fn workspace(name: &str) -> (TempDir, PathBuf, WorkspaceIndex) {
+        let tmp = TempDir::new(&format!("workspace-{name}"));
+        let dir = tmp.to_path_buf();
+        std::fs::create_dir_all(dir.join("schemas")).unwrap();
+        std::fs::write(
+            dir.join("schemas/common.json"),
+            r#"{"$defs":{"user":{"$anchor":"User","type":"object"}}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("api.json"),
+            r##"{
+                "properties": {
+                    "owner": {"$ref": "schemas/common.json#/$defs/user"},
+                    "editor": {"$ref": "./schemas/common.json#User"},
+                    "copy": {"$ref": "#/properties/owner"}
+                },
+                "items": [{"id": 7}],
+                "links": [{"item_id": 7}]
+            }"##,
+        )
+        .unwrap();
+        let index = WorkspaceIndex::default();
+        let summary = index.index(&dir).unwrap();
+        assert_eq!(summary.files, 2);
+        (tmp, normalize(&dir), index)
+    }
+
+    #[test]
+    fn jumps_across_files_and_back() {
+        let (_tmp, dir, index) = workspace("jumps");
+        let api = dir.join("api.json").to_string_lossy().into_owned();
+        let common = dir
+            .join("schemas/common.json")
+            .to_string_lossy()
+            .into_owned();
+        let target = Location {
+            file: common.clone(),
+            pointer: "/$defs/user".into(),
+        };
+        assert_eq!(
+            index
+                .go_to_definition(&api, "/properties/owner/$ref")
+                .unwrap(),
+            vec![target.clone()]
+        );
+        assert_eq!(
+            index.go_to_definition(&api, "/properties/editor").unwrap(),
+            vec![target]
+        );
+        let refs = index.find_references("/$defs/user", Some(&common)).unwrap();
+        let pointers: Vec<&str> = refs.iter().map(|r| r.location.pointer.as_str()).collect();
+        assert_eq!(
+            pointers,
+            vec!["/properties/owner/$ref", "/properties/editor/$ref"]
+        );
+    }
+
+    #[test]
+    fn resolves_id_values() {
+        let (_tmp, dir, index) = workspace("ids");
+        let api = dir.join("api.json").to_string_lossy().into_owned();
+        assert_eq!(
+            index.go_to_definition(&api, "/links/0/item_id").unwrap(),
+            vec![Location {
+                file: api.clone(),
+                pointer: "/items/0".into(),
+            }]
+        );
+        let kinds: Vec<ReferenceKind> = index
+            .find_references("7", None)
+            .unwrap()
+            .iter()
+            .map(|r| r.kind)
+            .collect();
+        assert_eq!(kinds, vec![ReferenceKind::Definition, ReferenceKind::Id]);
+    }
+}
@@ -1,11 +1,17 @@
 mod commands;
+mod data_protocol;
 pub(crate) mod doc;
+mod lsp;
 
+use crate::doc::telemetry;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Mutex;
 use tauri::{
-    menu::{Menu, MenuEvent, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder},
+    menu::{
+        CheckMenuItemBuilder, Menu, MenuEvent, MenuItemBuilder, MenuItemKind, PredefinedMenuItem,
+        Submenu, SubmenuBuilder,
+    },
     AppHandle, Emitter, Manager, RunEvent,
 };
 
@@ -17,8 +23,38 @@ pub(crate) struct RecentFile {
 
 pub(crate) struct AppState {
     pub pending_files: Mutex<Vec<String>>,
+    pub menu_recents: Mutex<Vec<RecentFile>>,
+    pub telemetry: std::sync::Arc<doc::telemetry::Telemetry>,
+    pub disk_hashes: doc::disk_hash::DiskHashes,
+    pub remote_origins: doc::remote::RemoteOrigins,
+    pub node_menu_target: Mutex<Option<NodeMenuTarget>>,
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct NodeMenuTarget {
+    pub window: String,
+    pub pointer: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeContextAction {
+    action: String,
+    pointer: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct NodeMenuCapabilities {
+    editable: bool,
+    container: bool,
+    extractable: bool,
+}
+
+const NODE_MENU_PREFIX: &str = "node_menu::";
+const RECENT_MENU_ID: &str = "open_recent";
+const WINDOW_MENU_PREFIX: &str = "window::";
+
 const SUPPORTED_EXTENSIONS: &[&str] = &["json", "jsonc", "json5", "geojson", "jsonl", "ndjson"];
 
 fn is_supported_file(path: &str) -> bool {
@@ -54,11 +90,41 @@ fn emit_file_open(app: &AppHandle, paths: Vec<String>) {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if std::env::args().skip(1).any(|arg| arg == "--lsp") {
+        let stdin = std::io::stdin();
+        if let Err(e) = lsp::serve(stdin.lock(), std::io::stdout().lock()) {
+            eprintln!("pandia --lsp: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--check") {
+        match doc::git_hooks::run_cli(&args) {
+            Ok(true) => return,
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("pandia --check: {e}");
+                std::process::exit(2);
+            }
+        }
+    }
+    if args.iter().any(|arg| arg == "--preview") {
+        if let Err(e) = doc::preview::run_cli(&args) {
+            eprintln!("pandia --preview: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let cli_files: Vec<String> = std::env::args()
         .skip(1)
         .filter(|arg| !arg.starts_with('-') && is_supported_file(arg))
         .collect();
 
+    let store = std::sync::Arc::new(doc::store::DocStore::new());
+    let snapshots = store.snapshots();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
@@ -67,11 +133,33 @@ pub fn run() {
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(AppState {
             pending_files: Mutex::new(cli_files),
+            menu_recents: Mutex::new(Vec::new()),
+            telemetry: doc::telemetry::shared(),
+            disk_hashes: doc::disk_hash::DiskHashes::default(),
+            remote_origins: doc::remote::RemoteOrigins::default(),
+            node_menu_target: Mutex::new(None),
         })
-        .manage(std::sync::Arc::new(doc::store::DocStore::new()))
+        .manage(store)
         .manage(std::sync::Arc::new(doc::jobs::JobRegistry::default()))
+        .manage(std::sync::Arc::new(doc::pool::WorkerPool::new(0)))
+        .manage(std::sync::Arc::new(doc::sftp::SftpConnections::new()))
+        .manage(std::sync::Arc::new(doc::tabs::TabRegistry::default()))
+        .manage(std::sync::Arc::new(doc::taskbar::Taskbar::default()))
+        .manage(std::sync::Arc::new(doc::notify::Notifier::default()))
+        .manage(std::sync::Arc::new(
+            doc::workspace::WorkspaceIndex::default(),
+        ))
+        .manage(snapshots)
+        .manage(std::sync::Arc::new(parking_lot::Mutex::new(
+            lsp::Server::default(),
+        )))
+        .register_asynchronous_uri_scheme_protocol(
+            data_protocol::SCHEME,
+            |ctx, request, responder| data_protocol::serve(ctx.app_handle(), request, responder),
+        )
         .invoke_handler(tauri::generate_handler![
             commands::doc_open,
             commands::doc_close,
@@ -101,17 +189,218 @@ pub fn run() {
             commands::doc_history,
             commands::doc_save,
             commands::doc_set_file_path,
+            commands::doc_merge_disk,
             commands::doc_backup,
             commands::doc_backup_clear,
             commands::doc_backup_scan,
             commands::doc_export,
             commands::doc_export_preview,
             commands::doc_export_to_file,
-            refresh_recent_files,
+            commands::resolve_schema_refs,
+            commands::bundle_schema,
+            commands::schema_example,
+            commands::list_templates,
+            commands::create_from_template,
+            commands::list_snippets,
+            commands::save_snippet,
+            commands::delete_snippet,
+            commands::render_snippet,
+            commands::generate_ids,
+            commands::list_plugins,
+            commands::run_plugin,
+            commands::run_external_tool,
+            commands::list_external_tools,
+            commands::revoke_external_tool,
+            commands::run_script,
+            commands::git_status,
+            commands::git_show_head,
+            commands::git_diff_head,
+            commands::parse_conflict_file,
+            commands::write_resolution,
+            commands::lockfile_analyze,
+            commands::lockfile_diff,
+            commands::parse_log_file,
+            commands::har_list_entries,
+            commands::har_summary,
+            commands::har_extract_body,
+            commands::notebook_cells,
+            commands::notebook_set_cell_source,
+            commands::notebook_save,
+            commands::k8s_open_stream,
+            commands::k8s_validate,
+            commands::k8s_cleanup,
+            commands::helm_validate_values,
+            commands::resolve_placeholders,
+            commands::i18n_compare,
+            commands::i18n_merge,
+            commands::get_settings,
+            commands::update_settings,
+            commands::reset_settings,
+            commands::get_portable_info,
+            commands::export_settings,
+            commands::import_settings,
+            commands::get_log_path,
+            commands::collect_diagnostics,
+            commands::set_update_channel,
+            commands::get_update_status,
+            commands::check_for_updates_now,
+            commands::download_update,
+            commands::build_search_index,
+            commands::search_document,
+            commands::resolve_path,
+            commands::get_path_context,
+            commands::add_bookmark,
+            commands::remove_bookmark,
+            commands::list_bookmarks,
+            commands::annotate,
+            commands::export_annotations,
+            commands::import_annotations,
+            commands::pin_file,
+            commands::unpin_file,
+            commands::list_pinned,
+            commands::add_recent_file,
+            commands::remove_recent_file,
+            commands::clear_recent_files,
+            commands::list_recent_files,
+            commands::pin_recent_file,
+            commands::diff_directories,
+            commands::diff_adhoc,
+            commands::list_diff_profiles,
+            commands::save_diff_profile,
+            commands::delete_diff_profile,
+            commands::export_diff,
+            commands::import_diff_patch,
+            commands::get_content_hash,
+            commands::extract_subtree,
+            commands::inline_refs,
+            commands::split_file,
+            commands::concat_files,
+            commands::array_to_ndjson,
+            commands::ndjson_to_array,
+            commands::run_pipeline,
+            commands::dedupe_ndjson,
+            commands::tail_file,
+            commands::validate_ndjson_against_schema,
+            commands::profile_ndjson,
+            commands::position_to_path,
+            commands::path_to_position,
+            commands::validate_json,
+            commands::lint_json,
+            commands::find_duplicate_keys,
+            commands::get_completions,
+            commands::get_hover_info,
+            commands::get_folding_ranges,
+            commands::get_outline,
+            commands::lsp_message,
+            commands::text_buffer_open,
+            commands::apply_text_edit,
+            commands::text_buffer_outline,
+            commands::text_buffer_read,
+            commands::text_buffer_lines,
+            commands::text_buffer_position,
+            commands::get_worker_pool_stats,
+            commands::get_memory_usage,
+            commands::preview_binary,
+            commands::save_view_state,
+            commands::load_view_state,
+            commands::list_macros,
+            commands::save_macro,
+            commands::delete_macro,
+            commands::run_macro,
+            commands::run_macro_batch,
+            commands::get_effective_settings,
+            commands::get_editorconfig,
+            commands::stat_file,
+            commands::touch_file,
+            commands::set_file_permissions,
+            commands::delete_file_to_trash,
+            commands::rename_file,
+            commands::duplicate_file,
+            commands::materialize_file,
+            commands::sftp_connect,
+            commands::sftp_disconnect,
+            commands::sftp_list,
+            commands::sftp_read,
+            commands::sftp_write,
+            commands::fetch_json_url,
+            commands::save_to_url,
+            commands::open_tab,
+            commands::close_tab,
+            commands::list_tabs,
+            commands::reorder_tabs,
+            commands::set_tab_dirty,
+            commands::update_tab,
+            commands::activate_tab,
+            commands::select_tab,
+            commands::tokenize_range,
+            commands::get_document_heatmap,
+            commands::format_selection,
+            commands::minify_selection,
+            commands::validate_fragment,
+            commands::normalize_pasted,
+            commands::parse_tabular_clipboard,
+            commands::detect_colors,
+            commands::convert_color,
+            commands::analyze_references,
+            commands::index_workspace,
+            commands::find_references,
+            commands::go_to_definition,
+            commands::check_links,
+            commands::merge_layers,
+            commands::watch_directory,
+            commands::export_diagnostics,
+            commands::install_git_hook,
+            commands::uninstall_git_hook,
+            commands::list_snapshots,
+            commands::diff_with_snapshot,
+            commands::align_documents,
+            commands::anonymize_preserving_shape,
+            commands::stash_content,
+            commands::list_stash,
+            commands::restore_stash,
+            commands::delete_stash,
+            commands::diff_against_defaults,
+            commands::value_histogram,
+            commands::extract_series,
+            commands::layout_graph,
+            commands::expand_graph_node,
+            commands::similarity,
+            commands::rank_similar_files,
+            commands::cluster_by_shape,
+            commands::normalize_types,
             drain_pending_files,
+            get_performance_report,
+            clear_performance_report,
+            show_node_context_menu,
         ])
         .setup(|app| {
-            let menu = build_menu(app.handle(), &[])?;
+            let settings = doc::settings::SettingsStore::for_app(app.handle())?;
+            let data_dir = doc::portable::data_dir(app.handle())?;
+            if let Ok(current) = settings.get() {
+                commands::apply_runtime_settings(
+                    &current,
+                    &doc::diagnostics::log_dir(&data_dir),
+                    &app.state::<std::sync::Arc<doc::pool::WorkerPool>>(),
+                    &app.state::<std::sync::Arc<doc::store::DocStore>>(),
+                )?;
+            }
+            app.manage(std::sync::Arc::new(settings));
+            app.manage(std::sync::Arc::new(doc::updates::Updater::new(&data_dir)));
+            doc::updates::spawn_scheduler(app.handle().clone());
+            doc::snapshots::spawn_scheduler(app.handle().clone());
+            let recents: Vec<RecentFile> = doc::recents::load(app.handle())
+                .unwrap_or_default()
+                .into_iter()
+                .take(doc::recents::MENU_ENTRIES)
+                .map(|e| RecentFile {
+                    path: e.path,
+                    name: e.name,
+                })
+                .collect();
+            if let Ok(mut menu_recents) = app.state::<AppState>().menu_recents.lock() {
+                *menu_recents = recents.clone();
+            }
+            let menu = build_menu(app.handle(), &recents)?;
             app.set_menu(menu)?;
 
             #[cfg(debug_assertions)]
@@ -125,6 +414,26 @@ pub fn run() {
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|app, event| {
+            if let RunEvent::Exit = &event {
+                if let Some(updater) = app.try_state::<std::sync::Arc<doc::updates::Updater>>() {
+                    if let Err(e) = updater.install_pending() {
+                        tracing::warn!(error = %e, "installing downloaded update failed");
+                    }
+                }
+            }
+            if let RunEvent::WindowEvent { label, event, .. } = &event {
+                match event {
+                    tauri::WindowEvent::Destroyed => {
+                        if let Some(tabs) =
+                            app.try_state::<std::sync::Arc<doc::tabs::TabRegistry>>()
+                        {
+                            tabs.close_window(label);
+                        }
+                    }
+                    tauri::WindowEvent::Focused(true) => doc::notify::on_focus(app),
+                    _ => {}
+                }
+            }
             #[cfg(target_os = "macos")]
             if let RunEvent::Opened { urls } = &event {
                 let paths: Vec<String> = urls
@@ -146,6 +455,35 @@ pub fn run() {
         });
 }
 
+fn fill_recent_menu(
+    app: &tauri::AppHandle,
+    menu: &Submenu<tauri::Wry>,
+    recents: &[RecentFile],
+) -> Result<(), tauri::Error> {
+    if recents.is_empty() {
+        let no_recent = MenuItemBuilder::with_id("no_recent", "No Recent Files")
+            .enabled(false)
+            .build(app)?;
+        return menu.append(&no_recent);
+    }
+    for r in recents {
+        menu.append(&MenuItemBuilder::with_id(format!("recent::{}", r.path), &r.name).build(app)?)?;
+    }
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItemBuilder::with_id("clear_recent_files", "Clear Recent Files").build(app)?)
+}
+
+fn find_submenu(items: Vec<MenuItemKind<tauri::Wry>>, id: &str) -> Option<Submenu<tauri::Wry>> {
+    items.into_iter().find_map(|item| {
+        let submenu = item.as_submenu()?.clone();
+        if submenu.id().0 == id {
+            Some(submenu)
+        } else {
+            find_submenu(submenu.items().ok()?, id)
+        }
+    })
+}
+
 fn build_menu(
     app: &tauri::AppHandle,
     recents: &[RecentFile],
@@ -169,30 +507,51 @@ fn build_menu(
     let new_file = MenuItemBuilder::with_id("new_file", "New File")
         .accelerator("CmdOrCtrl+N")
         .build(app)?;
+    let new_from_schema =
+        MenuItemBuilder::with_id("new_from_schema", "New from Schema...").build(app)?;
+    let templates_menu = {
+        let user_dir = doc::templates::user_dir(app).ok();
+        let mut builder = SubmenuBuilder::new(app, "New from Template");
+        let mut items = Vec::new();
+        for t in doc::templates::all_templates(user_dir.as_deref()) {
+            items.push(
+                MenuItemBuilder::with_id(format!("template::{}", t.info.id), &t.info.name)
+                    .build(app)?,
+            );
+        }
+        for it in &items {
+            builder = builder.item(it);
+        }
+        builder.build()?
+    };
     let open_file = MenuItemBuilder::with_id("open_file", "Open File...")
         .accelerator("CmdOrCtrl+O")
         .build(app)?;
 
-    let recent_files_menu = {
-        let mut builder = SubmenuBuilder::new(app, "Open Recent");
-        if recents.is_empty() {
-            let no_recent = MenuItemBuilder::with_id("no_recent", "No Recent Files")
+    let recent_files_menu = SubmenuBuilder::with_id(app, RECENT_MENU_ID, "Open Recent").build()?;
+    fill_recent_menu(app, &recent_files_menu, recents)?;
+
+    let favorites_menu = {
+        let pinned = doc::pinned::pinned_path(app)
+            .and_then(|p| doc::pinned::load(&p))
+            .unwrap_or_default();
+        let mut builder = SubmenuBuilder::new(app, "Favorites");
+        if pinned.is_empty() {
+            let no_pinned = MenuItemBuilder::with_id("no_pinned", "No Pinned Files")
                 .enabled(false)
                 .build(app)?;
-            builder.item(&no_recent).build()?
+            builder.item(&no_pinned).build()?
         } else {
-            let mut items = Vec::with_capacity(recents.len());
-            for r in recents {
+            let mut items = Vec::with_capacity(pinned.len());
+            for p in &pinned {
                 items.push(
-                    MenuItemBuilder::with_id(format!("recent::{}", r.path), &r.name).build(app)?,
+                    MenuItemBuilder::with_id(format!("pinned::{}", p.path), &p.name).build(app)?,
                 );
             }
             for it in &items {
                 builder = builder.item(it);
             }
-            let clear_recent =
-                MenuItemBuilder::with_id("clear_recent_files", "Clear Recent Files").build(app)?;
-            builder.separator().item(&clear_recent).build()?
+            builder.build()?
         }
     };
 
@@ -221,8 +580,11 @@ fn build_menu(
     let file_menu = SubmenuBuilder::new(app, "File")
         .item(&new_tab)
         .item(&new_file)
+        .item(&new_from_schema)
+        .item(&templates_menu)
         .item(&open_file)
         .item(&recent_files_menu)
+        .item(&favorites_menu)
         .separator()
         .item(&save_file)
         .item(&save_as)
@@ -251,6 +613,29 @@ fn build_menu(
     let find_replace = MenuItemBuilder::with_id("find_replace", "Find and Replace...")
         .accelerator("CmdOrCtrl+H")
         .build(app)?;
+    let snippets_menu = {
+        let snippets = doc::snippets::snippets_path(app)
+            .and_then(|p| doc::snippets::load(&p))
+            .unwrap_or_default();
+        let mut builder = SubmenuBuilder::new(app, "Insert Snippet");
+        if snippets.is_empty() {
+            let no_snippets = MenuItemBuilder::with_id("no_snippets", "No Snippets")
+                .enabled(false)
+                .build(app)?;
+            builder.item(&no_snippets).build()?
+        } else {
+            let mut items = Vec::with_capacity(snippets.len());
+            for s in &snippets {
+                items.push(
+                    MenuItemBuilder::with_id(format!("snippet::{}", s.id), &s.name).build(app)?,
+                );
+            }
+            for it in &items {
+                builder = builder.item(it);
+            }
+            builder.build()?
+        }
+    };
     let edit_menu = SubmenuBuilder::new(app, "Edit")
         .item(&undo)
         .item(&redo)
@@ -260,6 +645,8 @@ fn build_menu(
         .item(&PredefinedMenuItem::paste(app, Some("Paste"))?)
         .item(&PredefinedMenuItem::select_all(app, Some("Select All"))?)
         .separator()
+        .item(&snippets_menu)
+        .separator()
         .item(&find)
         .item(&find_next)
         .item(&find_prev)
@@ -319,6 +706,66 @@ fn build_menu(
         .item(&compare_files)
         .build()?;
 
+    let select_next_tab = MenuItemBuilder::with_id("select_next_tab", "Show Next Tab")
+        .accelerator("CmdOrCtrl+Alt+Right")
+        .build(app)?;
+    let select_prev_tab = MenuItemBuilder::with_id("select_prev_tab", "Show Previous Tab")
+        .accelerator("CmdOrCtrl+Alt+Left")
+        .build(app)?;
+    let window_menu = {
+        let open = app
+            .try_state::<std::sync::Arc<doc::tabs::TabRegistry>>()
+            .map(|tabs| tabs.windows())
+            .unwrap_or_default();
+        let mut builder = SubmenuBuilder::new(app, "Window");
+        #[cfg(target_os = "macos")]
+        {
+            builder = builder
+                .item(&PredefinedMenuItem::minimize(app, Some("Minimize"))?)
+                .item(&PredefinedMenuItem::maximize(app, Some("Zoom"))?)
+                .separator();
+        }
+        builder = builder.item(&select_next_tab).item(&select_prev_tab);
+        let mut headers = Vec::new();
+        let mut items = Vec::new();
+        for list in &open {
+            if open.len() > 1 {
+                let title = app
+                    .get_webview_window(&list.window)
+                    .and_then(|w| w.title().ok())
+                    .unwrap_or_else(|| list.window.clone());
+                headers.push(
+                    MenuItemBuilder::with_id(format!("{WINDOW_MENU_PREFIX}{}", list.window), title)
+                        .build(app)?,
+                );
+            }
+            let mut group = Vec::with_capacity(list.tabs.len());
+            for tab in &list.tabs {
+                let title = if tab.dirty {
+                    format!("{} •", tab.title)
+                } else {
+                    tab.title.clone()
+                };
+                group.push(
+                    CheckMenuItemBuilder::with_id(format!("tab::{}", tab.id), title)
+                        .checked(list.active.as_deref() == Some(tab.id.as_str()))
+                        .build(app)?,
+                );
+            }
+            items.push(group);
+        }
+        for (i, group) in items.iter().enumerate() {
+            builder = builder.separator();
+            if let Some(header) = headers.get(i) {
+                builder = builder.item(header);
+            }
+            for it in group {
+                builder = builder.item(it);
+            }
+        }
+        builder.build()?
+    };
+
     let keyboard_shortcuts = MenuItemBuilder::with_id("keyboard_shortcuts", "Keyboard Shortcuts")
         .accelerator("CmdOrCtrl+/")
         .build(app)?;
@@ -353,6 +800,7 @@ fn build_menu(
             &edit_menu,
             &view_menu,
             &tools_menu,
+            &window_menu,
             &help_menu,
         ],
     )?;
@@ -360,23 +808,135 @@ fn build_menu(
     #[cfg(not(target_os = "macos"))]
     let menu = Menu::with_items(
         app,
-        &[&file_menu, &edit_menu, &view_menu, &tools_menu, &help_menu],
+        &[
+            &file_menu,
+            &edit_menu,
+            &view_menu,
+            &tools_menu,
+            &window_menu,
+            &help_menu,
+        ],
     )?;
 
     Ok(menu)
 }
 
+fn build_node_context_menu(
+    app: &AppHandle,
+    capabilities: &NodeMenuCapabilities,
+) -> Result<Menu<tauri::Wry>, tauri::Error> {
+    let item = |id: &str, label: &str, enabled: bool| {
+        MenuItemBuilder::with_id(format!("{NODE_MENU_PREFIX}{id}"), label)
+            .enabled(enabled)
+            .build(app)
+    };
+    let copy_as = SubmenuBuilder::new(app, "Copy as")
+        .item(&item("copy_minified", "Minified JSON", true)?)
+        .item(&item("copy_pointer", "JSON Pointer", true)?)
+        .item(&item("copy_jq", "jq Path", true)?)
+        .item(&item("copy_escaped", "Escaped String", true)?)
+        .build()?;
+    Menu::with_items(
+        app,
+        &[
+            &item("copy_value", "Copy Value", true)?,
+            &item("copy_path", "Copy Path", true)?,
+            &copy_as,
+            &PredefinedMenuItem::separator(app)?,
+            &item("delete", "Delete", capabilities.editable)?,
+            &item("duplicate", "Duplicate", capabilities.editable)?,
+            &PredefinedMenuItem::separator(app)?,
+            &item("expand_all", "Expand All", capabilities.container)?,
+            &item(
+                "extract_to_file",
+                "Extract to File…",
+                capabilities.extractable,
+            )?,
+        ],
+    )
+}
+
+#[tauri::command]
+fn show_node_context_menu(
+    app: AppHandle,
+    window: tauri::Window,
+    state: tauri::State<'_, AppState>,
+    pointer: String,
+    capabilities: Option<NodeMenuCapabilities>,
+) -> Result<(), String> {
+    let _timing = telemetry::time("show_node_context_menu");
+    let menu = build_node_context_menu(&app, &capabilities.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+    if let Ok(mut target) = state.node_menu_target.lock() {
+        *target = Some(NodeMenuTarget {
+            window: window.label().to_string(),
+            pointer,
+        });
+    }
+    window.popup_menu(&menu).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn drain_pending_files(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    let _timing = telemetry::time("drain_pending_files");
     let mut pending = state.pending_files.lock().map_err(|e| e.to_string())?;
     Ok(std::mem::take(&mut *pending))
 }
 
 #[tauri::command]
-fn refresh_recent_files(app: AppHandle, items: Vec<RecentFile>) -> Result<(), String> {
+fn get_performance_report(state: tauri::State<'_, AppState>) -> telemetry::PerformanceReport {
+    state.telemetry.report()
+}
+
+#[tauri::command]
+fn clear_performance_report(state: tauri::State<'_, AppState>) {
+    state.telemetry.clear();
+}
+
+/// Swaps the entries of the Open Recent submenu without rebuilding the rest
+/// of the menu bar.
+pub(crate) fn set_menu_recents(
+    app: &AppHandle,
+    entries: &[doc::recents::RecentEntry],
+) -> Result<(), String> {
+    let recents: Vec<RecentFile> = entries
+        .iter()
+        .take(doc::recents::MENU_ENTRIES)
+        .map(|e| RecentFile {
+            path: e.path.clone(),
+            name: e.name.clone(),
+        })
+        .collect();
+    if let Some(state) = app.try_state::<AppState>() {
+        if let Ok(mut menu_recents) = state.menu_recents.lock() {
+            *menu_recents = recents.clone();
+        }
+    }
+    let app2 = app.clone();
+    app.run_on_main_thread(move || {
+        let submenu = app2
+            .menu()
+            .and_then(|menu| find_submenu(menu.items().ok()?, RECENT_MENU_ID));
+        let Some(submenu) = submenu else {
+            let _ = rebuild_menu(&app2);
+            return;
+        };
+        while let Ok(Some(_)) = submenu.remove_at(0) {}
+        let _ = fill_recent_menu(&app2, &submenu, &recents);
+    })
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) fn rebuild_menu(app: &AppHandle) -> Result<(), String> {
+    let mut recents = Vec::new();
+    if let Some(state) = app.try_state::<AppState>() {
+        if let Ok(r) = state.menu_recents.lock() {
+            recents = r.clone();
+        }
+    }
     let app2 = app.clone();
     app.run_on_main_thread(move || {
-        if let Ok(menu) = build_menu(&app2, &items) {
+        if let Ok(menu) = build_menu(&app2, &recents) {
             let _ = app2.set_menu(menu);
         }
     })
@@ -385,6 +945,48 @@ fn refresh_recent_files(app: AppHandle, items: Vec<RecentFile>) -> Result<(), St
 
 fn handle_menu_event(app: &tauri::AppHandle, event: MenuEvent) {
     let menu_id = event.id().as_ref();
+    if let Some(action) = menu_id.strip_prefix(NODE_MENU_PREFIX) {
+        let target = app
+            .try_state::<AppState>()
+            .and_then(|state| state.node_menu_target.lock().ok()?.take());
+        if let Some(target) = target {
+            let _ = app.emit_to(
+                target.window.as_str(),
+                "node-context-action",
+                NodeContextAction {
+                    action: action.to_string(),
+                    pointer: target.pointer,
+                },
+            );
+        }
+        return;
+    }
+    if let Some(label) = menu_id.strip_prefix(WINDOW_MENU_PREFIX) {
+        if let Some(window) = app.get_webview_window(label) {
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+        }
+        return;
+    }
+    if let Some(tabs) = app.try_state::<std::sync::Arc<doc::tabs::TabRegistry>>() {
+        let selected = if let Some(id) = menu_id.strip_prefix("tab::") {
+            tabs.activate(id).ok()
+        } else if menu_id == "select_next_tab" || menu_id == "select_prev_tab" {
+            let focused = app
+                .webview_windows()
+                .into_iter()
+                .find(|(_, w)| w.is_focused().unwrap_or(false))
+                .map(|(label, _)| label)
+                .unwrap_or_else(|| "main".to_string());
+            tabs.cycle(&focused, if menu_id == "select_next_tab" { 1 } else { -1 })
+        } else {
+            None
+        };
+        if let Some(tab) = selected {
+            commands::route_select_tab(app, tab);
+            return;
+        }
+    }
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.emit("menu-event", menu_id);
     }
@@ -0,0 +1,590 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read, Write};
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::doc::completions::{self, CompletionKind};
+use crate::doc::goto;
+use crate::doc::hover;
+use crate::doc::lint::{self, LintConfig, Severity};
+use crate::doc::outline::{self, OutlineSymbol};
+use crate::doc::positions::{Cursor, LineIndex, PositionIndex, TextPosition};
+use crate::doc::syntax;
+use crate::doc::types::{DocError, DocResult, NodeKind};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const REQUEST_FAILED: i64 = -32803;
+const OUTLINE_DEPTH: usize = 8;
+
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let length = length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Value) -> io::Result<()> {
+    let body = serde_json::to_vec(message)?;
+    write!(output, "Content-Length: {}\r\n\r\n", body.len())?;
+    output.write_all(&body)?;
+    output.flush()
+}
+
+pub fn serve<R: BufRead, W: Write>(mut input: R, mut output: W) -> io::Result<()> {
+    let mut server = Server::default();
+    while let Some(message) = read_message(&mut input)? {
+        if message.get("method").and_then(Value::as_str) == Some("exit") {
+            break;
+        }
+        for reply in server.handle(&message) {
+            write_message(&mut output, &reply)?;
+        }
+    }
+    Ok(())
+}
+
+/// Converts between positions.rs columns (chars, 1-based) and LSP
+/// characters, which count UTF-16 code units unless the client agreed to
+/// utf-32.
+struct Columns<'a> {
+    lines: Vec<&'a str>,
+    utf16: bool,
+}
+
+impl<'a> Columns<'a> {
+    fn new(text: &'a str, utf16: bool) -> Self {
+        Self {
+            lines: text.split('\n').collect(),
+            utf16,
+        }
+    }
+
+    fn line(&self, line: u32) -> &str {
+        self.lines
+            .get(line.saturating_sub(1) as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn character(&self, line: u32, column: u32) -> u32 {
+        let chars = column.saturating_sub(1);
+        if !self.utf16 {
+            return chars;
+        }
+        self.line(line)
+            .chars()
+            .take(chars as usize)
+            .map(|c| c.len_utf16() as u32)
+            .sum()
+    }
+
+    fn column(&self, line: u32, character: u32) -> u32 {
+        if !self.utf16 {
+            return character + 1;
+        }
+        let mut units = 0;
+        let mut chars = 0;
+        for c in self.line(line).chars() {
+            if units >= character {
+                break;
+            }
+            units += c.len_utf16() as u32;
+            chars += 1;
+        }
+        chars + 1
+    }
+
+    fn position(&self, p: TextPosition) -> Value {
+        json!({
+            "line": p.line.saturating_sub(1),
+            "character": self.character(p.line, p.column),
+        })
+    }
+
+    fn range(&self, start: TextPosition, end: TextPosition) -> Value {
+        json!({"start": self.position(start), "end": self.position(end)})
+    }
+
+    fn cursor(&self, params: &Value) -> Cursor {
+        let at = |key: &str| {
+            params
+                .pointer(&format!("/position/{key}"))
+                .and_then(Value::as_u64)
+                .unwrap_or(0) as u32
+        };
+        let line = at("line") + 1;
+        Cursor {
+            line,
+            column: self.column(line, at("character")),
+        }
+    }
+}
+
+fn kind_name(kind: NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Object => "object",
+        NodeKind::Array => "array",
+        NodeKind::String => "string",
+        NodeKind::Number => "number",
+        NodeKind::Bool => "boolean",
+        NodeKind::Null => "null",
+    }
+}
+
+fn symbol_kind(kind: NodeKind) -> u8 {
+    match kind {
+        NodeKind::Object => 19,
+        NodeKind::Array => 18,
+        NodeKind::String => 15,
+        NodeKind::Number => 16,
+        NodeKind::Bool => 17,
+        NodeKind::Null => 21,
+    }
+}
+
+fn symbol_json(columns: &Columns, symbol: &OutlineSymbol) -> Value {
+    let range = columns.range(symbol.start, symbol.end);
+    json!({
+        "name": if symbol.name.is_empty() { "\"\"" } else { symbol.name.as_str() },
+        "detail": symbol.detail,
+        "kind": symbol_kind(symbol.kind),
+        "range": range,
+        "selectionRange": range,
+        "children": symbol
+            .children
+            .iter()
+            .map(|child| symbol_json(columns, child))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn config_for(uri: &str) -> LintConfig {
+    uri.strip_prefix("file://")
+        .and_then(|path| std::path::Path::new(path).parent())
+        .and_then(lint::find_config)
+        .and_then(|found| lint::load_config(&found).ok())
+        .unwrap_or_default()
+}
+
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, String>,
+    utf32: bool,
+}
+
+impl Server {
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let params = message.get("params").unwrap_or(&Value::Null);
+        let Some(id) = message.get("id") else {
+            return self.notify(method, params);
+        };
+        if method.is_empty() {
+            return Vec::new();
+        }
+        let result = match method {
+            "initialize" => Ok(self.initialize(params)),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/completion" => self.completion(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/formatting" => self.formatting(params),
+            "textDocument/documentSymbol" => self.symbols(params),
+            "textDocument/foldingRange" => self.folding(params),
+            _ => {
+                return vec![json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {"code": METHOD_NOT_FOUND, "message": format!("unsupported method {method}")},
+                })]
+            }
+        };
+        vec![match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err(e) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": REQUEST_FAILED, "message": e.to_string()},
+            }),
+        }]
+    }
+
+    fn notify(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params
+            .pointer("/textDocument/uri")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let text = match method {
+            "textDocument/didOpen" => params.pointer("/textDocument/text"),
+            "textDocument/didChange" => params
+                .get("contentChanges")
+                .and_then(Value::as_array)
+                .and_then(|changes| changes.last())
+                .and_then(|change| change.get("text")),
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return vec![publish(&uri, Vec::new())];
+            }
+            _ => return Vec::new(),
+        };
+        let Some(text) = text.and_then(Value::as_str) else {
+            return Vec::new();
+        };
+        self.documents.insert(uri.clone(), text.to_string());
+        vec![publish(&uri, diagnostics(&uri, text, !self.utf32))]
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        self.utf32 = params
+            .pointer("/capabilities/general/positionEncodings")
+            .and_then(Value::as_array)
+            .is_some_and(|encodings| encodings.iter().any(|e| e == "utf-32"));
+        json!({
+            "capabilities": {
+                "positionEncoding": if self.utf32 { "utf-32" } else { "utf-16" },
+                "textDocumentSync": 1,
+                "completionProvider": {"triggerCharacters": ["\"", ":", ",", "{", "["]},
+                "hoverProvider": true,
+                "documentFormattingProvider": true,
+                "documentSymbolProvider": true,
+                "foldingRangeProvider": true,
+            },
+            "serverInfo": {"name": "pandia", "version": env!("CARGO_PKG_VERSION")},
+        })
+    }
+
+    fn columns<'a>(&self, text: &'a str) -> Columns<'a> {
+        Columns::new(text, !self.utf32)
+    }
+
+    fn text(&self, params: &Value) -> DocResult<&str> {
+        let uri = params
+            .pointer("/textDocument/uri")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        self.documents.get(uri).map(String::as_str).ok_or_else(|| {
+            DocError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{uri} is not open"),
+            ))
+        })
+    }
+
+    fn completion(&self, params: &Value) -> DocResult<Value> {
+        let text = self.text(params)?;
+        let columns = self.columns(text);
+        let result = completions::complete(text, columns.cursor(params), None)?;
+        let range = columns.range(result.replace_start, result.replace_end);
+        let items: Vec<Value> = result
+            .items
+            .into_iter()
+            .map(|item| {
+                let mut out = Map::new();
+                out.insert("label".into(), item.label.into());
+                let kind = match item.kind {
+                    CompletionKind::Property => 10,
+                    CompletionKind::Value => 12,
+                    CompletionKind::Closer => 24,
+                };
+                out.insert("kind".into(), kind.into());
+                out.insert(
+                    "textEdit".into(),
+                    json!({"range": range, "newText": item.insert_text}),
+                );
+                if let Some(detail) = item.detail {
+                    out.insert("detail".into(), detail.into());
+                }
+                if let Some(doc) = item.documentation {
+                    out.insert("documentation".into(), doc.into());
+                }
+                Value::Object(out)
+            })
+            .collect();
+        Ok(json!({"isIncomplete": false, "items": items}))
+    }
+
+    fn hover(&self, params: &Value) -> DocResult<Value> {
+        let text = self.text(params)?;
+        let Ok(index) = PositionIndex::build(text) else {
+            return Ok(Value::Null);
+        };
+        let columns = self.columns(text);
+        let at = columns.cursor(params);
+        let span = index.path_at(at.line, at.column)?;
+        let root: Value = serde_json::from_str(text).map_err(|e| DocError::Parse(e.to_string()))?;
+        let value = goto::value_at(&root, &span.path)
+            .cloned()
+            .unwrap_or_default();
+        let info = hover::hover(&span.path, &value, None);
+        let mut summary = format!(
+            "`{}` — **{}**",
+            if info.pointer.is_empty() {
+                "/"
+            } else {
+                &info.pointer
+            },
+            kind_name(info.kind)
+        );
+        if let Some(count) = info.child_count {
+            summary.push_str(&format!(" · {count} children"));
+        }
+        summary.push_str(&format!(" · {} bytes", info.size));
+        let mut lines = vec![summary];
+        if let Some(stats) = &info.array_stats {
+            lines.push(format!("≈{} distinct values", stats.distinct_estimate));
+            if let Some(n) = &stats.numeric {
+                lines.push(format!(
+                    "min {} · max {} · mean {:.3}",
+                    n.min, n.max, n.mean
+                ));
+            }
+        }
+        Ok(json!({
+            "contents": {"kind": "markdown", "value": lines.join("\n\n")},
+            "range": columns.range(span.start, span.end),
+        }))
+    }
+
+    fn formatting(&self, params: &Value) -> DocResult<Value> {
+        let text = self.text(params)?;
+        let value: Value =
+            serde_json::from_str(text).map_err(|e| DocError::Parse(e.to_string()))?;
+        let tab_size = params
+            .pointer("/options/tabSize")
+            .and_then(Value::as_u64)
+            .unwrap_or(2) as usize;
+        let indent = match params
+            .pointer("/options/insertSpaces")
+            .and_then(Value::as_bool)
+        {
+            Some(false) => "\t".to_string(),
+            _ => " ".repeat(tab_size),
+        };
+        let mut out = Vec::new();
+        let mut ser = serde_json::Serializer::with_formatter(
+            &mut out,
+            serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes()),
+        );
+        value
+            .serialize(&mut ser)
+            .map_err(|e| DocError::Export(e.to_string()))?;
+        let mut formatted = String::from_utf8_lossy(&out).into_owned();
+        if text.ends_with('\n') {
+            formatted.push('\n');
+        }
+        let lines = LineIndex::new(text);
+        Ok(json!([{
+            "range": self
+                .columns(text)
+                .range(lines.position(0), lines.position(text.len())),
+            "newText": formatted,
+        }]))
+    }
+
+    fn symbols(&self, params: &Value) -> DocResult<Value> {
+        let text = self.text(params)?;
+        let Ok(index) = PositionIndex::build(text) else {
+            return Ok(json!([]));
+        };
+        let columns = self.columns(text);
+        let outline = outline::outline(&index, OUTLINE_DEPTH);
+        Ok(Value::Array(
+            outline
+                .symbols
+                .iter()
+                .map(|symbol| symbol_json(&columns, symbol))
+                .collect(),
+        ))
+    }
+
+    fn folding(&self, params: &Value) -> DocResult<Value> {
+        let Ok(index) = PositionIndex::build(self.text(params)?) else {
+            return Ok(json!([]));
+        };
+        Ok(Value::Array(
+            outline::folding_ranges(&index)
+                .iter()
+                .map(|r| json!({"startLine": r.start_line - 1, "endLine": r.end_line - 1}))
+                .collect(),
+        ))
+    }
+}
+
+fn diagnostics(uri: &str, text: &str, utf16: bool) -> Vec<Value> {
+    let lines = LineIndex::new(text);
+    let columns = Columns::new(text, utf16);
+    let mut out: Vec<Value> = syntax::check(text)
+        .diagnostics
+        .iter()
+        .map(|d| {
+            json!({
+                "range": columns.range(
+                    lines.position(d.offset as usize),
+                    lines.position(d.end_offset as usize),
+                ),
+                "severity": 1,
+                "source": "pandia",
+                "message": d.message,
+            })
+        })
+        .collect();
+    if let Ok((findings, _)) = lint::lint(text, &config_for(uri)) {
+        for finding in findings.iter().filter(|f| f.rule != "duplicate-keys") {
+            let severity = match finding.severity {
+                Severity::Error => 1,
+                Severity::Warning => 2,
+                Severity::Info | Severity::Off => 3,
+            };
+            let at = json!({
+                "line": finding.line - 1,
+                "character": columns.character(finding.line, finding.column),
+            });
+            out.push(json!({
+                "range": {"start": at, "end": at},
+                "severity": severity,
+                "source": "pandia",
+                "code": finding.rule,
+                "message": finding.message,
+            }));
+        }
+    }
+    out
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {"uri": uri, "diagnostics": diagnostics},
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(message: Value) -> Vec<u8> {
+        let body = message.to_string();
+        format!("Content-Length: {}\r\n\r\n{body}", body.len()).into_bytes()
+    }
+
+    fn open(server: &mut Server, text: &str) -> Vec<Value> {
+        server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {"textDocument": {"uri": "mem://a.json", "languageId": "json", "version": 1, "text": text}},
+        }))
+    }
+
+    #[test]
+    fn serves_framed_messages_until_exit() {
+        let mut input = Vec::new();
+        input.extend(frame(
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+        ));
+        input.extend(frame(json!({"jsonrpc": "2.0", "method": "exit"})));
+        input.extend(frame(
+            json!({"jsonrpc": "2.0", "id": 2, "method": "shutdown"}),
+        ));
+        let mut output = Vec::new();
+        serve(&input[..], &mut output).unwrap();
+        let mut reader = &output[..];
+        let reply = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["result"]["capabilities"]["hoverProvider"], true);
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn publishes_diagnostics_and_answers_requests() {
+        let mut server = Server::default();
+        let published = open(&mut server, "{\"a\": 1,}");
+        assert_eq!(published[0]["method"], "textDocument/publishDiagnostics");
+        assert!(!published[0]["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+
+        open(&mut server, "{\n  \"a\": [1, 2]\n}");
+        let reply = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 7, "method": "textDocument/documentSymbol",
+            "params": {"textDocument": {"uri": "mem://a.json"}},
+        }));
+        assert_eq!(reply[0]["result"][0]["name"], "a");
+        assert_eq!(reply[0]["result"][0]["kind"], 18);
+
+        let reply = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 8, "method": "textDocument/hover",
+            "params": {"textDocument": {"uri": "mem://a.json"}, "position": {"line": 1, "character": 8}},
+        }));
+        let hover = reply[0]["result"]["contents"]["value"].as_str().unwrap();
+        assert!(hover.starts_with("`/a/0` — **number**"), "{hover}");
+
+        let reply = server.handle(&json!({"jsonrpc": "2.0", "id": 9, "method": "nope"}));
+        assert_eq!(reply[0]["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn converts_columns_to_the_negotiated_encoding() {
+        let hover_b = |server: &mut Server, character: u32| {
+            server.handle(&json!({
+                "jsonrpc": "2.0", "id": 1, "method": "textDocument/hover",
+                "params": {"textDocument": {"uri": "mem://a.json"}, "position": {"line": 0, "character": character}},
+            }))[0]["result"]
+                .clone()
+        };
+        let text = "{\"😀\": 1, \"b\": 2}";
+
+        let mut server = Server::default();
+        let init = server
+            .handle(&json!({"jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {}}));
+        assert_eq!(
+            init[0]["result"]["capabilities"]["positionEncoding"],
+            "utf-16"
+        );
+        open(&mut server, text);
+        let result = hover_b(&mut server, 15);
+        assert!(result["contents"]["value"]
+            .as_str()
+            .unwrap()
+            .starts_with("`/b`"));
+        assert_eq!(result["range"]["start"]["character"], 15);
+
+        let mut server = Server::default();
+        server.handle(&json!({
+            "jsonrpc": "2.0", "id": 0, "method": "initialize",
+            "params": {"capabilities": {"general": {"positionEncodings": ["utf-32", "utf-16"]}}},
+        }));
+        open(&mut server, text);
+        let result = hover_b(&mut server, 14);
+        assert!(result["contents"]["value"]
+            .as_str()
+            .unwrap()
+            .starts_with("`/b`"));
+        assert_eq!(result["range"]["start"]["character"], 14);
+    }
+}
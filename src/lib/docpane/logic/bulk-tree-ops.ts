@@ -10,14 +10,14 @@ export interface BulkExpandDeps {
 	setBusy: (b: boolean) => void;
 }
 
-export async function expandAll(deps: BulkExpandDeps): Promise<void> {
+export async function expandAll(deps: BulkExpandDeps, under: Path = []): Promise<void> {
 	deps.setBusy(true);
 	try {
 		let pass = 0;
 		while (pass < SAFETY_PASSES) {
 			const targets: Path[] = [];
 			for (const r of deps.tree.rows) {
-				if (isContent(r) && isExpandable(r) && !r.expanded) {
+				if (isContent(r) && isExpandable(r) && !r.expanded && isWithin(r.path, under)) {
 					targets.push(r.path);
 				}
 			}
@@ -38,6 +38,10 @@ export async function expandAll(deps: BulkExpandDeps): Promise<void> {
 	}
 }
 
+function isWithin(path: Path, prefix: Path): boolean {
+	return prefix.length <= path.length && prefix.every((seg, i) => path[i] === seg);
+}
+
 export interface BulkCollapseDeps {
 	tree: Pick<TreeRowsController, 'setRows' | 'toggleAt'>;
 	summary: { rootKind: NodeKind; rootChildCount: number | null } | null;
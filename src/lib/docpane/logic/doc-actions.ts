@@ -30,6 +30,7 @@ export interface DocPaneActions {
 	menuPaste: (r: ContentRow) => void;
 	menuExtract: (r: ContentRow) => void;
 	showTypegen: (lang: TypegenLang) => void;
+	insertSnippet: (id: string) => void;
 	onPickCompareFile: () => void;
 	exitCompare: () => void;
 }
@@ -28,6 +28,12 @@ const ROUTES: Record<string, (a: DocPaneActions) => void> = {
 	find_prev: (a) => cycleFind(a, -1),
 };
 
+const SNIPPET_PREFIX = 'snippet::';
+
 export function handleDocMenuEvent(id: string, actions: DocPaneActions): void {
+	if (id.startsWith(SNIPPET_PREFIX)) {
+		actions.insertSnippet(id.slice(SNIPPET_PREFIX.length));
+		return;
+	}
 	ROUTES[id]?.(actions);
 }
@@ -40,6 +40,7 @@ export function isRepairableError(error: string, kind?: IpcErrorKind | null): bo
 
 async function readSourceText(source: OpenSource): Promise<string | null> {
 	if (source.kind === 'text') return source.text;
+	if (source.kind === 'template') return null;
 	try {
 		return await readTextFile(source.path);
 	} catch {
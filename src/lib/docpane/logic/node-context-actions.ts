@@ -0,0 +1,37 @@
+import type { Path } from '$lib/ipc/types';
+import type { ContentRow } from '$lib/views/tree/logic/model';
+import type { createNodeActions } from '$lib/views/tree/logic/node-actions';
+import { parseJsonPointer } from '$lib/util/path';
+
+export interface NodeContextAction {
+	action: string;
+	pointer: string;
+}
+
+export interface NodeContextDeps {
+	nodeActions: ReturnType<typeof createNodeActions>;
+	rowAt: (path: Path) => ContentRow | null;
+	expandUnder: (path: Path) => Promise<void>;
+}
+
+const ROUTES: Record<string, (d: NodeContextDeps, row: ContentRow) => Promise<void>> = {
+	copy_value: (d, row) => d.nodeActions.copy(row),
+	copy_path: (d, row) => d.nodeActions.copyPath(row),
+	copy_minified: (d, row) => d.nodeActions.copyAs(row, 'minified'),
+	copy_pointer: (d, row) => d.nodeActions.copyAs(row, 'pointer'),
+	copy_jq: (d, row) => d.nodeActions.copyAs(row, 'jq'),
+	copy_escaped: (d, row) => d.nodeActions.copyAs(row, 'escaped'),
+	delete: (d, row) => d.nodeActions.remove(row),
+	duplicate: (d, row) => d.nodeActions.duplicate(row),
+	expand_all: (d, row) => d.expandUnder(row.path),
+	extract_to_file: (d, row) => d.nodeActions.extract(row),
+};
+
+export async function handleNodeContextAction(
+	event: NodeContextAction,
+	deps: NodeContextDeps,
+): Promise<void> {
+	const route = ROUTES[event.action];
+	const row = deps.rowAt(parseJsonPointer(event.pointer));
+	if (route && row) await route(deps, row);
+}
@@ -1,6 +1,7 @@
-import { save as saveDialog } from '@tauri-apps/plugin-dialog';
+import { ask, save as saveDialog } from '@tauri-apps/plugin-dialog';
 import {
 	docOpen,
+	createFromTemplate,
 	docClose,
 	docSummary,
 	docApplyOp,
@@ -8,6 +9,7 @@ import {
 	docUndo,
 	docRedo,
 	docSave,
+	docMergeDisk,
 	docBackupClear,
 	IpcError,
 	type IpcErrorKind,
@@ -84,7 +86,10 @@ export class DocSessionController {
 			if (!proceed) return;
 		}
 		const name = source.kind === 'file' ? source.path : (source.name ?? '(inline)');
-		await this.load(() => docOpen(source), name);
+		await this.load(
+			() => (source.kind === 'template' ? createFromTemplate(source.id) : docOpen(source)),
+			name,
+		);
 		if (this.deps.getError() !== null) {
 			await runAutoRepair(source, name, {
 				enabled: () => behaviorPrefs.autoRepairOnPaste,
@@ -219,6 +224,70 @@ export class DocSessionController {
 			await this.refreshSummary();
 			if (!opts.silent) this.deps.flash(`saved ${basename(res.path)}`);
 			return true;
+		} catch (e) {
+			if (e instanceof IpcError && e.kind === 'conflict' && !opts.silent) {
+				return this.saveOverConflict();
+			}
+			this.deps.setError(String(e));
+			return false;
+		}
+	};
+
+	private saveOverConflict = async (): Promise<boolean> => {
+		if (!this.handle) return false;
+		const name = basename(this.sourceName ?? 'This file');
+		const merge = await ask(
+			`${name} was changed by another program since it was opened. Merge those changes into your version before saving?`,
+			{
+				title: 'File changed on disk',
+				kind: 'warning',
+				okLabel: 'Merge',
+				cancelLabel: 'More options',
+			},
+		);
+		if (merge) return this.mergeFromDisk();
+		const overwrite = await ask(
+			`Overwrite ${name} with your version and discard the changes made on disk?`,
+			{
+				title: 'File changed on disk',
+				kind: 'warning',
+				okLabel: 'Overwrite',
+				cancelLabel: 'Cancel',
+			},
+		);
+		if (!overwrite) return false;
+		try {
+			const res = await docSave(this.handle, undefined, true);
+			this.clearBackup();
+			await this.refreshSummary();
+			this.deps.flash(`saved ${basename(res.path)}`);
+			return true;
+		} catch (e) {
+			this.deps.setError(String(e));
+			return false;
+		}
+	};
+
+	private mergeFromDisk = async (): Promise<boolean> => {
+		if (!this.handle) return false;
+		try {
+			const merged = await docMergeDisk(this.handle);
+			if (!merged.clean) {
+				const n = merged.conflicts.length;
+				this.deps.setError(
+					`${n} conflicting change${n === 1 ? '' : 's'} between your version and the file on disk; compare them before saving`,
+				);
+				return false;
+			}
+			if (merged.applied) {
+				await this.refreshSummary();
+				await this.deps.tree.refetchAfterOp(merged.applied.affectedPaths);
+			}
+			const res = await docSave(this.handle);
+			this.clearBackup();
+			await this.refreshSummary();
+			this.deps.flash(`merged and saved ${basename(res.path)}`);
+			return true;
 		} catch (e) {
 			this.deps.setError(String(e));
 			return false;
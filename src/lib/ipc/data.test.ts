@@ -0,0 +1,26 @@
+import { describe, it, expect, vi, afterEach } from 'vitest';
+import { dataUrl, fetchData } from './data';
+import { IpcError } from './error';
+
+afterEach(() => {
+	vi.unstubAllGlobals();
+});
+
+describe('dataUrl', () => {
+	it('encodes the query for the pandia-data scheme', () => {
+		const url = dataUrl('export/abc', { format: 'json-min' });
+		expect(url.endsWith('/export/abc?format=json-min')).toBe(true);
+	});
+});
+
+describe('fetchData', () => {
+	it('returns the body and maps wire errors to IpcError', async () => {
+		const missing = JSON.stringify({ kind: 'notFound', message: 'gone' });
+		const fetchMock = vi.fn();
+		fetchMock.mockResolvedValueOnce(new Response('[1]'));
+		fetchMock.mockResolvedValueOnce(new Response(missing, { status: 404 }));
+		vi.stubGlobal('fetch', fetchMock);
+		expect(await fetchData('doc/abc')).toBe('[1]');
+		await expect(fetchData('doc/abc')).rejects.toBeInstanceOf(IpcError);
+	});
+});
@@ -0,0 +1,26 @@
+import { toIpcError } from './error';
+
+const SCHEME = 'pandia-data';
+
+// Windows and Android webviews only reach custom schemes through http://<scheme>.localhost.
+export function dataUrl(path: string, query: Record<string, string> = {}): string {
+	const windowsLike = /Windows|Android/.test(navigator.userAgent);
+	const base = windowsLike ? `http://${SCHEME}.localhost` : `${SCHEME}://localhost`;
+	const search = new URLSearchParams(query).toString();
+	return `${base}/${path}${search ? `?${search}` : ''}`;
+}
+
+export async function fetchData(path: string, query: Record<string, string> = {}): Promise<string> {
+	const response = await fetch(dataUrl(path, query));
+	const body = await response.text();
+	if (!response.ok) {
+		let error: unknown = body;
+		try {
+			error = JSON.parse(body);
+		} catch {
+			// not a structured error; surface the raw body
+		}
+		throw toIpcError(error);
+	}
+	return body;
+}
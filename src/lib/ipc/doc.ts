@@ -1,5 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
 import { parseLossless } from '$lib/util/lossless';
+import { fetchData } from './data';
 import { toIpcError } from './error';
 import type {
 	ApplyResult,
@@ -15,12 +16,15 @@ import type {
 	OpenResult,
 	OpenSource,
 	Path,
+	RenderedSnippet,
 	RepairResult,
 	SaveResult,
+	SchemaExample,
 	SchemaValidationResult,
 	SearchHit,
 	SearchOptions,
 	Summary,
+	TemplateInfo,
 	TypegenLang,
 } from './types';
 
@@ -33,10 +37,18 @@ function call<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
 	});
 }
 
-export function docOpen(source: OpenSource): Promise<OpenResult> {
+export function docOpen(source: Exclude<OpenSource, { kind: 'template' }>): Promise<OpenResult> {
 	return call<OpenResult>('doc_open', { source });
 }
 
+export function listTemplates(): Promise<TemplateInfo[]> {
+	return call<TemplateInfo[]>('list_templates', {});
+}
+
+export function createFromTemplate(id: string): Promise<OpenResult> {
+	return call<OpenResult>('create_from_template', { id });
+}
+
 export function docClose(handle: DocHandle): Promise<boolean> {
 	return call<boolean>('doc_close', { handle });
 }
@@ -58,6 +70,12 @@ export function docValueJson(handle: DocHandle, path: Path): Promise<string> {
 	return call<string>('doc_value_json', { handle, path });
 }
 
+export function docValueMinified(handle: DocHandle, path: Path): Promise<string> {
+	return call<{ text: string }>('minify_selection', {
+		selection: { kind: 'node', handle, path },
+	}).then((r) => r.text);
+}
+
 export function docGetRows(
 	handle: DocHandle,
 	path: Path,
@@ -245,6 +263,14 @@ export function docValidateSchema(
 	return call<SchemaValidationResult>('doc_validate_schema', { handle, schema });
 }
 
+export function renderSnippet(id: string): Promise<RenderedSnippet> {
+	return call<RenderedSnippet>('render_snippet', { id });
+}
+
+export function schemaExample(schema: string, basePath: string | null): Promise<SchemaExample> {
+	return call<SchemaExample>('schema_example', { schema, options: { basePath } });
+}
+
 export function docGenerateTypes(
 	handle: DocHandle,
 	lang: TypegenLang,
@@ -261,14 +287,24 @@ export function docHistory(handle: DocHandle): Promise<HistoryView> {
 	return call<HistoryView>('doc_history', { handle });
 }
 
-export function docSave(handle: DocHandle, path?: string): Promise<SaveResult> {
-	return call<SaveResult>('doc_save', { handle, path: path ?? null });
+export function docSave(handle: DocHandle, path?: string, force = false): Promise<SaveResult> {
+	return call<SaveResult>('doc_save', { handle, path: path ?? null, force });
 }
 
 export function docSetFilePath(handle: DocHandle, path: string): Promise<Summary> {
 	return call<Summary>('doc_set_file_path', { handle, path });
 }
 
+export interface DiskMergeResult {
+	clean: boolean;
+	conflicts: Path[];
+	applied: ApplyResult | null;
+}
+
+export function docMergeDisk(handle: DocHandle): Promise<DiskMergeResult> {
+	return call<DiskMergeResult>('doc_merge_disk', { handle });
+}
+
 export function docBackup(handle: DocHandle, displayName: string | null): Promise<boolean> {
 	return call<boolean>('doc_backup', { handle, displayName });
 }
@@ -282,7 +318,7 @@ export function docBackupScan(): Promise<BackupRecord[]> {
 }
 
 export function docExport(handle: DocHandle, format: ExportFormat): Promise<string> {
-	return call<string>('doc_export', { handle, format });
+	return fetchData(`export/${handle}`, { format });
 }
 
 export interface ExportPreview {
@@ -7,6 +7,8 @@ export type IpcErrorKind =
 	| 'schema'
 	| 'export'
 	| 'io'
+	| 'conflict'
+	| 'notDownloaded'
 	| 'cancelled'
 	| 'unknown';
 
@@ -33,7 +33,26 @@ export interface SaveResult {
 
 export type OpenSource =
 	| { kind: 'file'; path: string }
-	| { kind: 'text'; text: string; name: string | null };
+	| { kind: 'text'; text: string; name: string | null }
+	| { kind: 'template'; id: string; name: string };
+
+export interface TemplateInfo {
+	id: string;
+	name: string;
+	description: string | null;
+	builtin: boolean;
+	fileName: string;
+}
+
+export interface RenderedSnippet {
+	text: string;
+	fragment: boolean;
+}
+
+export interface SchemaExample {
+	minimal: unknown;
+	full: unknown;
+}
 
 export interface OpenResult {
 	handle: DocHandle;
@@ -11,6 +11,7 @@ export interface ShellCommandDeps {
 	nextTab: () => void;
 	prevTab: () => void;
 	openFile: () => Promise<void> | void;
+	newFromSchema: () => Promise<void> | void;
 	openInTab: (source: OpenSource) => boolean;
 	toggleSidebar: () => void;
 	revealSchemaPanel: () => void;
@@ -141,6 +142,7 @@ export function buildMenuRouteMap(deps: ShellCommandDeps): Record<string, () =>
 		toggle_sidebar: deps.toggleSidebar,
 		compare_files: deps.toggleComparePicker,
 		open_file: () => void deps.openFile(),
+		new_from_schema: () => void deps.newFromSchema(),
 		validate_json: deps.revealSchemaPanel,
 		clear_recent_files: deps.clearRecents,
 		keyboard_shortcuts: deps.openHelp,
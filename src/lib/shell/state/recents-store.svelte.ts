@@ -1,10 +1,11 @@
-import { loadPersisted, savePersisted, RECENTS_FILE } from '$lib/util/persist';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { loadPersisted, RECENTS_FILE } from '$lib/util/persist';
 import { PersistedStore } from '$lib/util/persisted-store.svelte';
 import { basename } from '$lib/util/path';
 
 const STORE_FILE = RECENTS_FILE;
 const STORE_KEY = 'recents';
-const MAX_ENTRIES = 50;
 
 export interface RecentFile {
 	path: string;
@@ -36,42 +37,35 @@ function sanitize(raw: unknown): RecentFile[] {
 
 class RecentsStore extends PersistedStore {
 	list: RecentFile[] = $state([]);
+	private listening = false;
 
 	protected async load(): Promise<void> {
 		const raw = await loadPersisted<RecentFile[]>(STORE_FILE, STORE_KEY);
 		this.list = sanitize(raw);
+		if (!this.listening) {
+			this.listening = true;
+			void listen<RecentFile[]>('recents-changed', (e) => {
+				this.list = sanitize(e.payload);
+			});
+		}
+		void invoke('list_recent_files');
 	}
 
 	add(path: string, name?: string, size?: number): void {
-		const now = new Date().toISOString();
-		const display = name ?? basename(path);
-		const existing = this.list.find((e) => e.path === path);
-		const pinned = existing?.pinned ?? false; // preserve pin across re-opens
-		const filtered = this.list.filter((e) => e.path !== path);
-		const next = [{ path, name: display, openedAt: now, size, pinned }, ...filtered];
-		const pinnedCount = next.filter((e) => e.pinned).length;
-		const cap = Math.max(MAX_ENTRIES, pinnedCount);
-		this.list = next.slice(0, cap);
-		void this.persist();
+		void invoke('add_recent_file', { path, name: name ?? basename(path), size });
 	}
 
 	remove(path: string): void {
-		this.list = this.list.filter((e) => e.path !== path);
-		void this.persist();
+		void invoke('remove_recent_file', { path });
 	}
 
 	clear(): void {
-		this.list = this.list.filter((e) => e.pinned);
-		void this.persist();
+		void invoke('clear_recent_files');
 	}
 
 	togglePin(path: string): void {
-		this.list = this.list.map((e) => (e.path === path ? { ...e, pinned: !e.pinned } : e));
-		void this.persist();
-	}
-
-	private persist(): Promise<void> {
-		return savePersisted(STORE_FILE, STORE_KEY, this.list);
+		const pinned = !this.list.find((e) => e.path === path)?.pinned;
+		void invoke('pin_recent_file', { path, pinned });
 	}
 }
 
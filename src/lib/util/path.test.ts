@@ -1,5 +1,11 @@
 import { describe, it, expect } from 'vitest';
-import { pathToString, parsePath, parseJsonPointer } from './path';
+import {
+	pathToString,
+	pathToJq,
+	parsePath,
+	parseJsonPointer,
+	pathToJsonPointer,
+} from './path';
 
 describe('pathToString', () => {
 	it('renders the root path as $', () => {
@@ -15,6 +21,17 @@ describe('pathToString', () => {
 	});
 });
 
+describe('pathToJq', () => {
+	it('renders the root path as .', () => {
+		expect(pathToJq([])).toBe('.');
+	});
+
+	it('uses dot form for identifiers and brackets for indices and other keys', () => {
+		expect(pathToJq(['events', 42, 'ts'])).toBe('.events[42].ts');
+		expect(pathToJq([0, 'weird-key'])).toBe('.[0]["weird-key"]');
+	});
+});
+
 describe('parsePath', () => {
 	it('parses the root', () => {
 		expect(parsePath('$')).toEqual({ ok: true, path: [] });
@@ -46,4 +63,10 @@ describe('parseJsonPointer', () => {
 		expect(parseJsonPointer('/a~1b/~0c')).toEqual(['a/b', '~c']);
 		expect(parseJsonPointer('/events/4')).toEqual(['events', 4]);
 	});
+
+	it('round-trips with pathToJsonPointer', () => {
+		const path = ['a/b', '~c', 4];
+		expect(pathToJsonPointer(path)).toBe('/a~1b/~0c/4');
+		expect(parseJsonPointer(pathToJsonPointer(path))).toEqual(path);
+	});
 });
@@ -15,6 +15,22 @@ export function pathToString(path: Path): string {
 	return s;
 }
 
+export function pathToJq(path: Path): string {
+	if (path.length === 0) return '.';
+	let s = '';
+	for (const seg of path as PathSegment[]) {
+		if (typeof seg === 'number') {
+			s += `[${seg}]`;
+		} else if (JQ_IDENT_RE.test(seg)) {
+			s += `.${seg}`;
+		} else {
+			s += `[${JSON.stringify(seg)}]`;
+		}
+	}
+	return s.startsWith('[') ? `.${s}` : s;
+}
+
+const JQ_IDENT_RE = /^[a-zA-Z_][a-zA-Z0-9_]*$/;
 const BARE_IDENT_RE = /^[a-zA-Z_$][a-zA-Z0-9_$]*$/;
 function isBareIdentifier(key: string): boolean {
 	return BARE_IDENT_RE.test(key);
@@ -80,6 +96,12 @@ export function parsePath(input: string): { ok: true; path: Path } | { ok: false
 	return { ok: true, path: segs };
 }
 
+export function pathToJsonPointer(path: Path): string {
+	return (path as PathSegment[])
+		.map((seg) => '/' + String(seg).replace(/~/g, '~0').replace(/\//g, '~1'))
+		.join('');
+}
+
 export function parseJsonPointer(ptr: string): Path {
 	if (ptr === '' || ptr === '/') return ptr === '/' ? [''] : [];
 	return ptr
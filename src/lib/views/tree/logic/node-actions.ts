@@ -1,14 +1,16 @@
-import { docGetSlice, docGetValue, docValueJson } from '$lib/ipc/doc';
+import { docGetSlice, docGetValue, docValueJson, docValueMinified } from '$lib/ipc/doc';
 import { pathKey, type ContentRow, type Row } from './model';
 import { findIndexPaged } from './paging';
 import { reorderDestination } from '$lib/util/reorder';
-import { pathToString } from '$lib/util/path';
+import { pathToJq, pathToJsonPointer, pathToString } from '$lib/util/path';
 import type { PromptController } from '$lib/ui/prompt.svelte';
 import type { ApplyResult, DocHandle, Op, OpenSource, Path } from '$lib/ipc/types';
 
 const CHUNK = 200;
 const OBJECT_REORDER_MAX = 2000;
 
+export type CopyFormat = 'minified' | 'pointer' | 'jq' | 'escaped';
+
 export interface CutMark {
 	path: Path;
 
@@ -90,6 +92,30 @@ export function createNodeActions(deps: NodeActionDeps) {
 		}
 	}
 
+	async function insertTextAfter(row: ContentRow, text: string, keyPrompt: string) {
+		const parentPath = row.path.slice(0, -1);
+		if (typeof row.key === 'number') {
+			const inserted = await deps.apply({
+				kind: 'insertItemText',
+				path: parentPath,
+				index: row.key + 1,
+				text,
+			});
+			return inserted !== null;
+		}
+		const newKey = await deps.prompt.show(keyPrompt, `${row.key}_copy`);
+		if (newKey === null || newKey === '') return false;
+		const rowPosition = await findKeyPosition(parentPath, row.key);
+		const inserted = await deps.apply({
+			kind: 'insertKeyText',
+			path: parentPath,
+			key: newKey,
+			text,
+			position: rowPosition === null ? null : rowPosition + 1,
+		});
+		return inserted !== null;
+	}
+
 	function extractName(row: ContentRow): string {
 		if (row.path.length === 0) return 'document.json';
 		const last = row.key;
@@ -273,6 +299,33 @@ export function createNodeActions(deps: NodeActionDeps) {
 			}
 		},
 
+		async copyAs(row: ContentRow, format: CopyFormat) {
+			const handle = deps.handle();
+			if (!handle) return;
+			try {
+				let text: string;
+				switch (format) {
+					case 'minified':
+						text = await docValueMinified(handle, row.path);
+						break;
+					case 'escaped':
+						text = JSON.stringify(await docValueMinified(handle, row.path));
+						break;
+					case 'pointer':
+						text = pathToJsonPointer(row.path);
+						break;
+					case 'jq':
+						text = pathToJq(row.path);
+						break;
+				}
+				await navigator.clipboard.writeText(text);
+				deps.setCutMark(null);
+				deps.flash('copied to clipboard');
+			} catch (e) {
+				deps.setError(String(e));
+			}
+		},
+
 		async cut(row: ContentRow) {
 			if (row.depth === 0) return; // can't cut the root
 			const handle = deps.handle();
@@ -309,27 +362,7 @@ export function createNodeActions(deps: NodeActionDeps) {
 			const isCutPaste =
 				cut !== null && cut.text === text && pathKey(cut.path.slice(0, -1)) !== pathKey(parentPath);
 
-			if (typeof row.key === 'number') {
-				const inserted = await deps.apply({
-					kind: 'insertItemText',
-					path: parentPath,
-					index: row.key + 1,
-					text,
-				});
-				if (!inserted) return;
-			} else {
-				const newKey = await deps.prompt.show('paste as new key:', `${row.key}_copy`);
-				if (newKey === null || newKey === '') return;
-				const rowPosition = await findKeyPosition(parentPath, row.key);
-				const inserted = await deps.apply({
-					kind: 'insertKeyText',
-					path: parentPath,
-					key: newKey,
-					text,
-					position: rowPosition === null ? null : rowPosition + 1,
-				});
-				if (!inserted) return;
-			}
+			if (!(await insertTextAfter(row, text, 'paste as new key:'))) return;
 
 			if (isCutPaste && cut) {
 				const sourceParent = cut.path.slice(0, -1);
@@ -351,6 +384,11 @@ export function createNodeActions(deps: NodeActionDeps) {
 			deps.setCutMark(null);
 		}),
 
+		insertSnippet: guard(async (row: ContentRow, text: string) => {
+			if (row.depth === 0 || !deps.handle()) return;
+			await insertTextAfter(row, text, 'snippet as new key:');
+		}),
+
 		async extract(row: ContentRow) {
 			const handle = deps.handle();
 			if (!handle) return;